@@ -0,0 +1,56 @@
+#!/usr/bin/env node
+
+// Checks that the wasm build generates the same boards as native, by comparing
+// its board fingerprints to those that truncate_core's tests check against.
+// Run `.backstage/build-web-client.sh` first to build the wasm.
+
+const fs = require("fs");
+const path = require("path");
+const vm = require("vm");
+
+const STATIC_DIR = path.join(__dirname, "../web_client/src/static");
+const FINGERPRINTS_FILE = path.join(
+  __dirname,
+  "../truncate_core/src/board_fingerprints.txt",
+);
+
+const readLines = (text) =>
+  text.split("\n").filter((line) => line.trim() && !line.startsWith("#"));
+
+const main = async () => {
+  // The bindings are built with --no-modules, so are loaded the same way as in the web worker
+  const context = vm.createContext({
+    self: globalThis,
+    TextDecoder,
+    TextEncoder,
+    WebAssembly,
+    console,
+  });
+  vm.runInContext(
+    fs.readFileSync(path.join(STATIC_DIR, "truncate_client.js"), "utf8"),
+    context,
+  );
+  const wasm_bindgen = vm.runInContext("wasm_bindgen", context);
+  await wasm_bindgen(
+    fs.readFileSync(path.join(STATIC_DIR, "truncate_client_bg.wasm")),
+  );
+
+  const expected = readLines(fs.readFileSync(FINGERPRINTS_FILE, "utf8"));
+  const actual = readLines(wasm_bindgen.board_fingerprints());
+
+  const mismatched = expected.filter((line, i) => actual[i] !== line);
+  if (mismatched.length || actual.length !== expected.length) {
+    console.error("Boards generated on wasm differ from native for:");
+    for (const line of mismatched) {
+      console.error(`  ${line.split(" ").slice(0, 3).join(" ")}`);
+    }
+    process.exit(1);
+  }
+
+  console.log(`All ${actual.length} boards generated the same on wasm`);
+};
+
+main().catch((error) => {
+  console.error(error);
+  process.exit(1);
+});
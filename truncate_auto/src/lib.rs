@@ -1,5 +1,4 @@
 use service::{move_request, player_move};
-use std::collections::HashMap;
 use truncate_core::{
     board::Coordinate,
     game::Game,
@@ -14,7 +13,7 @@ pub mod service {
 pub static TRUNCATE_DICT: &str = include_str!("../../dict_builder/final_wordlist.txt");
 
 pub fn init_dict() -> anyhow::Result<WordDict> {
-    let mut valid_words = HashMap::new();
+    let mut valid_words = WordDict::new();
     let lines = TRUNCATE_DICT.lines();
 
     for line in lines {
@@ -69,23 +68,25 @@ pub fn move_to_player_move(
             position,
         } => (
             player,
-            player_move::Move::PlaceMove(service::PlaceMove {
+            Some(player_move::Move::PlaceMove(service::PlaceMove {
                 tile: tile.to_string(),
                 position: to_wire_coord(&position),
-            }),
+            })),
         ),
         Move::Swap { player, positions } => (
             player,
-            player_move::Move::SwapMove(service::SwapMove {
+            Some(player_move::Move::SwapMove(service::SwapMove {
                 from: to_wire_coord(&positions[0]),
                 to: to_wire_coord(&positions[1]),
-            }),
+            })),
         ),
+        // Exchanged and redrawn tiles stay out of sight in the hands
+        Move::Exchange { player, .. } | Move::Mulligan { player } => (player, None),
     };
     service::PlayerMove {
         player_id: *player_id as u32,
         board: Some(board),
-        r#move: Some(mv),
+        r#move: mv,
         game_over,
     }
 }
@@ -146,5 +147,6 @@ pub fn invert_move(game: &Game, game_move: &Move) -> Move {
                 game.board.reciprocal_coordinate(positions[1]),
             ],
         },
+        Move::Exchange { .. } | Move::Mulligan { .. } => game_move.clone(),
     }
 }
@@ -5,7 +5,6 @@ use truncate_auto::{invert_move, move_request_to_move, move_to_player_move};
 use service::play_game_request;
 use service::truncate_server::{Truncate, TruncateServer};
 use service::{ErrorReply, MoveReply, PlayGameReply, PlayGameRequest};
-use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::{error::Error, io::ErrorKind, net::ToSocketAddrs, pin::Pin};
 use tokio::sync::{mpsc, oneshot};
@@ -14,11 +13,11 @@ use tonic::{transport::Server, Response, Status};
 use truncate_core::board::{Board, Square};
 use truncate_core::player::Hand;
 use truncate_core::rules::{BoardOrientation, GameRules};
-use truncate_core::{game::Game, judge::WordData, moves::Move};
+use truncate_core::{game::Game, judge::WordDict, moves::Move};
 
 pub struct AutoServer {
     pending_game: Arc<Mutex<Option<oneshot::Sender<GamePlayer>>>>,
-    valid_words: Arc<HashMap<String, WordData>>,
+    valid_words: Arc<WordDict>,
 }
 
 type ResponseStream = Pin<Box<dyn Stream<Item = Result<PlayGameReply, Status>> + Send>>;
@@ -157,7 +156,7 @@ impl GamePlayer {
 struct GameHandler {
     players: Vec<GamePlayer>,
     game: Game,
-    valid_words: Arc<HashMap<String, WordData>>,
+    valid_words: Arc<WordDict>,
 }
 
 impl GameHandler {
@@ -273,7 +272,7 @@ impl GameHandler {
                             Some((game_move, gr.is_some())),
                         ));
                     }
-                    Err(msg) => return Ok((error_reply(req.request_id, msg), None)),
+                    Err(msg) => return Ok((error_reply(req.request_id, msg.to_string()), None)),
                 }
             }
             Err(resp) => {
@@ -2,9 +2,9 @@ use eframe::egui;
 use epaint::vec2;
 use instant::Duration;
 use truncate_core::{
-    messages::{RoomCode, TruncateToken},
+    messages::{DailyStateMessage, RoomCode, TruncateToken},
     npc::scoring::NPCPersonality,
-    rules::GameRules,
+    rules::{GameRules, Language, RulesPreset},
 };
 
 use crate::{
@@ -13,15 +13,26 @@ use crate::{
     lil_bits::{ChangelogSplashUI, SplashUI},
     regions::{
         active_game::{ActiveGame, HeaderType},
+        forced_wins::ForcedWinsState,
+        gallery::GalleryState,
         generator::GeneratorState,
         lobby::Lobby,
         native_menu::render_native_menu_if_required,
+        npc_ladder::NpcLadderState,
+        prediction_trainer::PredictionTrainerState,
+        puzzle_rush::PuzzleRushState,
+        replay_comparison::ReplayComparisonState,
         replayer::ReplayerState,
         single_player::SinglePlayerState,
         tutorial::TutorialState,
+        word_ladder::WordLadderState,
+        word_of_day::WordOfTheDayState,
     },
     utils::{
+        crash,
+        embed::EmbedGameEnd,
         includes::{changelogs, ChangePriority, Tutorial},
+        oauth,
         urls::back_to_menu,
     },
 };
@@ -38,14 +49,78 @@ pub enum GameStatus {
     PendingDaily,
     PendingJoin(RoomCode),
     PendingCreate,
+    /// Waiting for a random opponent who wants the same rules, alongside this many players
+    PendingMatch((RulesPreset, usize)),
+    /// Waiting on a provider, then the server, to sign the player in, and whether that's done
+    PendingSignIn(bool),
     PendingStart(Lobby),
     Active(ActiveGame),
-    Concluded(ActiveGame, u64),
+    Concluded(ActiveGame, Option<u64>),
+    /// Watching someone else's game, a little behind the players
+    Spectating(ActiveGame),
     PendingReplay,
     Replay(ReplayerState),
+    /// Waiting on both of the attempts being compared to arrive from the server
+    PendingComparison(Vec<DailyStateMessage>),
+    Comparison(ReplayComparisonState),
+    /// Guessing each of the opponent's moves in a replayed game
+    Prediction(PredictionTrainerState),
+    WordLadder(WordLadderState),
+    NpcLadder(NpcLadderState),
+    /// Forced win puzzles, found ahead of time by the dueller
+    ForcedWins(ForcedWinsState),
+    PuzzleRush(PuzzleRushState),
+    WordOfTheDay(WordOfTheDayState),
+    Gallery(GalleryState),
     HardError(Vec<String>),
 }
 
+impl GameStatus {
+    /// Names the part of the client that is open, for crash reports
+    pub fn region(&self) -> &'static str {
+        match self {
+            GameStatus::None(_, _) => "menu",
+            GameStatus::Generator(_) => "generator",
+            GameStatus::Tutorial(_) => "tutorial",
+            GameStatus::PendingSinglePlayer(_) => "pending_single_player",
+            GameStatus::SinglePlayer(_) => "single_player",
+            GameStatus::PendingDaily => "pending_daily",
+            GameStatus::PendingJoin(_) => "pending_join",
+            GameStatus::PendingCreate => "pending_create",
+            GameStatus::PendingMatch(_) => "pending_match",
+            GameStatus::PendingSignIn(_) => "pending_sign_in",
+            GameStatus::PendingStart(_) => "lobby",
+            GameStatus::Active(_) => "active_game",
+            GameStatus::Concluded(_, _) => "concluded_game",
+            GameStatus::Spectating(_) => "spectating",
+            GameStatus::PendingReplay => "pending_replay",
+            GameStatus::Replay(_) => "replay",
+            GameStatus::PendingComparison(_) => "pending_comparison",
+            GameStatus::Comparison(_) => "comparison",
+            GameStatus::Prediction(_) => "prediction",
+            GameStatus::WordLadder(_) => "word_ladder",
+            GameStatus::NpcLadder(_) => "npc_ladder",
+            GameStatus::ForcedWins(_) => "forced_wins",
+            GameStatus::PuzzleRush(_) => "puzzle_rush",
+            GameStatus::WordOfTheDay(_) => "word_of_the_day",
+            GameStatus::Gallery(_) => "gallery",
+            GameStatus::HardError(_) => "hard_error",
+        }
+    }
+
+    /// The game being shown, for statuses that are built around one
+    pub fn active_game_mut(&mut self) -> Option<&mut ActiveGame> {
+        match self {
+            GameStatus::Tutorial(tutorial) => tutorial.active_game_mut(),
+            GameStatus::SinglePlayer(sp) => Some(&mut sp.active_game),
+            GameStatus::Active(game)
+            | GameStatus::Concluded(game, _)
+            | GameStatus::Spectating(game) => Some(game),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct AppInnerStorage {
     pub changelog_ui: Option<ChangelogSplashUI>,
@@ -53,6 +128,23 @@ pub struct AppInnerStorage {
 
 pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Duration) {
     handle_server_msg(outer, ui);
+    crash::set_region(outer.game_status.region());
+    let mut game_end = None;
+    if let Some(game) = outer.game_status.active_game_mut() {
+        game.depot.flags = outer.flags;
+        if outer.embed.options.read_only {
+            game.depot.interactions.view_only = true;
+        }
+
+        let gameplay = &game.depot.gameplay;
+        game_end = gameplay.winner.map(|winner| EmbedGameEnd {
+            room_code: gameplay.room_code.clone(),
+            winner,
+            player: gameplay.player_number as usize,
+            won: winner == gameplay.player_number as usize,
+        });
+    }
+    outer.embed.track_game_end(game_end);
 
     if outer.log_frames {
         let ctx = ui.ctx().clone();
@@ -101,6 +193,39 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
         }
     }
 
+    if let Some(report) = &outer.pending_crash_report {
+        let resp = SplashUI::new(vec!["TRUNCATE CRASHED".to_string()])
+            .byline(vec![
+                "Something went wrong last time you played.".to_string(),
+                "Send us an anonymous report to help fix it?".to_string(),
+            ])
+            .with_button(
+                "send",
+                "SEND REPORT".to_string(),
+                outer.theme.button_primary,
+                14.0,
+            )
+            .with_button(
+                "discard",
+                "DON'T SEND".to_string(),
+                outer.theme.button_secondary,
+                14.0,
+            )
+            .render(ui, &outer.theme, current_time, &outer.map_texture);
+
+        match resp.clicked {
+            Some("send") => {
+                outer
+                    .tx_player
+                    .try_send(PlayerMessage::ReportCrash(report.clone()))
+                    .unwrap();
+                outer.pending_crash_report = None;
+            }
+            Some("discard") => outer.pending_crash_report = None,
+            _ => return,
+        }
+    }
+
     let mut new_game_status = None;
     let loading_changelog = outer
         .launched_code
@@ -246,6 +371,11 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
         }
     }
 
+    if let Some(callback) = oauth::take_callback() {
+        outer.signing_in = true;
+        outer.tx_player.try_send(callback.into_message()).unwrap();
+    }
+
     if let Some(launched_code) = outer.launched_code.take() {
         new_game_status = handle_launch_code(&launched_code, outer, ui);
     }
@@ -273,7 +403,11 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
                 match msg {
                     PlayerMessage::StartGame => {
                         let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
-                        let single_player_game = SinglePlayerState::new(
+                        // Free play isn't replayed like the daily puzzle,
+                        // so the bot is free to open from its book
+                        let mut npc = NPCPersonality::jet();
+                        npc.params.opening_book = true;
+                        let mut single_player_game = SinglePlayerState::new(
                             "classic".to_string(),
                             ui.ctx(),
                             outer.map_texture.clone(),
@@ -281,11 +415,13 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
                             editor_state.board.clone(),
                             editor_state.board_seed.clone(),
                             rules_generation,
+                            Language::English,
                             true,
                             HeaderType::Timers,
-                            NPCPersonality::jet(),
+                            npc,
                             outer.event_dispatcher.clone(),
                         );
+                        single_player_game.active_game.depot.ui_state.word_finder = true;
                         new_game_status = Some(GameStatus::SinglePlayer(single_player_game));
                     }
                     _ => {
@@ -310,6 +446,9 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             ) {
                 send(msg);
             }
+            if let Some(replay) = sp.moment_replay.take() {
+                new_game_status = Some(GameStatus::Replay(replay));
+            }
         }
         GameStatus::PendingDaily => {
             let splash = SplashUI::new(if let Some(error) = &outer.error {
@@ -331,6 +470,37 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
                 back_to_menu();
             }
         }
+        GameStatus::PendingSignIn(signed_in) => {
+            let splash = if *signed_in {
+                SplashUI::new(vec!["SIGNED IN".to_string()]).with_button(
+                    "continue",
+                    "CONTINUE".to_string(),
+                    outer.theme.button_primary,
+                    14.0,
+                )
+            } else {
+                SplashUI::new(if let Some(error) = &outer.error {
+                    vec![error.clone()]
+                } else {
+                    vec!["SIGNING IN".to_string()]
+                })
+                .animated(outer.error.is_none())
+                .with_button(
+                    "cancel",
+                    "CANCEL".to_string(),
+                    outer.theme.button_primary,
+                    14.0,
+                )
+            };
+
+            let resp = splash.render(ui, &outer.theme, current_time, &outer.map_texture);
+
+            if resp.clicked.is_some() {
+                back_to_menu();
+                // Native clients have no menu page to go back to, so return to the in-game menu
+                new_game_status = Some(GameStatus::None(String::new(), None));
+            }
+        }
         GameStatus::PendingJoin(room_code) => {
             let splash = SplashUI::new(if let Some(error) = &outer.error {
                 vec![error.clone()]
@@ -354,6 +524,12 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
         GameStatus::PendingCreate => {
             let splash = SplashUI::new(if let Some(error) = &outer.error {
                 vec![error.clone()]
+            } else if let Some((position, estimated_wait_secs)) = outer.queued {
+                let mut lines = vec!["SERVER IS BUSY".to_string(), format!("#{position} IN LINE")];
+                if let Some(secs) = estimated_wait_secs {
+                    lines.push(format!("ABOUT {} MIN", (secs / 60).max(1)));
+                }
+                lines
             } else {
                 vec!["CREATING ROOM".to_string()]
             })
@@ -371,6 +547,34 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
                 back_to_menu();
             }
         }
+        GameStatus::PendingMatch((ruleset, waiting)) => {
+            let splash = SplashUI::new(if let Some(error) = &outer.error {
+                vec![error.clone()]
+            } else {
+                vec![
+                    "FINDING AN OPPONENT".to_string(),
+                    format!("{} RULES", ruleset.to_string().to_uppercase()),
+                    match waiting {
+                        0 | 1 => "NOBODY ELSE WAITING YET".to_string(),
+                        n => format!("{} PLAYERS WAITING", *n - 1),
+                    },
+                ]
+            })
+            .animated(outer.error.is_none())
+            .with_button(
+                "cancel",
+                "CANCEL".to_string(),
+                outer.theme.button_primary,
+                14.0,
+            );
+
+            let resp = splash.render(ui, &outer.theme, current_time, &outer.map_texture);
+
+            if resp.clicked == Some("cancel") {
+                send(PlayerMessage::CancelMatch);
+                back_to_menu();
+            }
+        }
         GameStatus::PendingStart(editor_state) => {
             if let Some(msg) = editor_state.render(ui, &outer.theme) {
                 send(msg);
@@ -385,8 +589,19 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             if let Some(PlayerMessage::Rematch) = game.render(ui, current_time, None) {
                 send(PlayerMessage::Rematch);
             }
+            if let (Some(rating), Some(token)) = (&mut outer.gallery_rating, &outer.logged_in_as) {
+                if let Some(msg) = rating.render(ui, &outer.theme, &outer.map_texture, token) {
+                    send(msg);
+                }
+            }
         }
-        GameStatus::PendingReplay => {
+        GameStatus::Spectating(game) => {
+            // Spectators can't act on the game, so all they can send is chat
+            if let Some(msg @ PlayerMessage::Chat { .. }) = game.render(ui, current_time, None) {
+                send(msg);
+            }
+        }
+        GameStatus::PendingReplay | GameStatus::PendingComparison(_) => {
             let splash = SplashUI::new(if let Some(error) = &outer.error {
                 vec![error.clone()]
             } else {
@@ -407,7 +622,65 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             }
         }
         GameStatus::Replay(replay) => {
-            replay.render(ui, &outer.theme, current_time, &outer.backchannel);
+            if replay.render(ui, &outer.theme, current_time, &outer.backchannel) {
+                let trainer = replay.prediction_trainer(ui.ctx(), outer.logged_in_as.clone());
+                new_game_status = Some(GameStatus::Prediction(trainer));
+            }
+        }
+        GameStatus::Comparison(comparison) => {
+            comparison.render(ui, &outer.theme, current_time);
+        }
+        GameStatus::Prediction(trainer) => {
+            if let Some(msg) = trainer.render(ui, &outer.theme, current_time) {
+                send(msg);
+            }
+        }
+        GameStatus::WordLadder(ladder) => {
+            if let Some(msg) = ladder.render(ui, &outer.theme, &outer.map_texture) {
+                send(msg);
+            }
+        }
+        GameStatus::NpcLadder(ladder) => {
+            for msg in ladder.render(
+                ui,
+                &outer.theme,
+                &outer.map_texture,
+                current_time,
+                &outer.backchannel,
+            ) {
+                send(msg);
+            }
+        }
+        GameStatus::ForcedWins(puzzles) => {
+            for msg in puzzles.render(
+                ui,
+                &outer.theme,
+                &outer.map_texture,
+                current_time,
+                &outer.backchannel,
+            ) {
+                send(msg);
+            }
+        }
+        GameStatus::PuzzleRush(rush) => {
+            for msg in rush.render(ui, &outer.theme, &outer.map_texture, current_time) {
+                send(msg);
+            }
+        }
+        GameStatus::WordOfTheDay(daily) => {
+            daily.render(ui, &outer.theme, &outer.map_texture, current_time);
+        }
+        GameStatus::Gallery(gallery) => {
+            if let Some(board_id) = gallery.render(ui, &outer.theme, &outer.map_texture) {
+                // The board is swapped in once the server has opened the room
+                outer.pending_gallery_board = Some(board_id);
+                send(PlayerMessage::NewGame {
+                    player_name: outer.name.clone(),
+                    effective_day: outer.launched_at_day,
+                    rules: None,
+                });
+                new_game_status = Some(GameStatus::PendingCreate);
+            }
         }
         GameStatus::HardError(msg) => {
             let splash = SplashUI::new(msg.clone()).with_button(
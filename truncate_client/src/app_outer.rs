@@ -1,4 +1,4 @@
-use std::sync::OnceLock;
+use std::sync::{OnceLock, RwLock};
 
 use futures::channel::mpsc::{Receiver, Sender};
 use instant::Duration;
@@ -8,20 +8,33 @@ type S = Sender<PlayerMessage>;
 
 use super::utils::Theme;
 use crate::app_inner::AppInnerStorage;
+use crate::regions::gallery::GalleryRating;
+use crate::utils::connection::ConnectionQuality;
+use crate::utils::crash;
 use crate::utils::daily::get_puzzle_day;
+use crate::utils::embed::Embed;
 use crate::utils::includes::changelogs;
 use crate::utils::macros::current_time;
-use crate::{app_inner, utils::glyph_utils::Glypher};
+use crate::utils::perf;
+use crate::utils::tex::atlas::load_classic_atlas;
+use crate::utils::theming::{SystemPreference, ThemeChoice};
+use crate::utils::timing::{set_low_power, until_next_repaint};
+use crate::{
+    app_inner,
+    utils::glyph_utils::{Glypher, FALLBACK_FONTS},
+};
 use eframe::egui::{self, Frame, Margin, TextureOptions};
 #[cfg(target_arch = "wasm32")]
 use eframe::wasm_bindgen::JsValue;
 use epaint::{Color32, Stroke, TextureHandle};
 use truncate_core::{
     board::Board,
-    messages::{GameMessage, PlayerMessage},
-    npc::scoring::NPCParams,
+    flags::Flags,
+    messages::{CrashReport, GameMessage, PlayerMessage},
+    npc::{scoring::NPCParams, SearchShare},
     player::Player,
-    rules::GameRules,
+    rules::{GameRules, Language},
+    schedule::ScheduledGame,
 };
 
 pub const ART_CHANGE_DAY: u32 = 293;
@@ -36,18 +49,23 @@ pub struct Backchannel {
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "type", content = "content")]
 pub enum BackchannelMsg {
-    /// Finds the best move for the next player in a given game state
+    /// Finds the best move for the next player in a given game state,
+    /// looking only at the given share of moves so that a search can be spread across hosts
     EvalGame {
         board: Board,
         rules: GameRules,
         players: Vec<Player>,
         next_player: usize,
         npc_params: NPCParams,
+        budget_ms: Option<u64>,
+        share: SearchShare,
     },
     /// Tells the outer host to add a given word to the NPC's known dictionaries
     Remember { word: String },
     /// Tells the outer host to forget all words learned via BackchannelMsg::Remember
     Forget,
+    /// Gives the outer host the words of a language other than English, for the NPC to play with
+    UseLanguage { language: Language, words: String },
     /// Tells the outer host to copy the given text, and optionally
     /// open a system share dialog.
     /// More reliable than copying within egui, as the browser JS
@@ -55,6 +73,8 @@ pub enum BackchannelMsg {
     Copy { text: String, share: ShareType },
     /// Checks if any answer has been posted for a given message
     QueryFor { id: String },
+    /// Checks how far the search for a given `EvalGame` message has got
+    ProgressOf { id: String },
 }
 
 #[derive(Serialize, Deserialize)]
@@ -100,15 +120,22 @@ impl Backchannel {
 }
 
 pub static TEXTURE_MEASUREMENT: OnceLock<TextureMeasurement> = OnceLock::new();
-pub static TEXTURE_IMAGE: OnceLock<egui::ColorImage> = OnceLock::new();
+/// The atlas currently being drawn from, which changes with the seasons
+pub static TEXTURE_IMAGE: RwLock<Option<egui::ColorImage>> = RwLock::new(None);
 pub static GLYPHER: OnceLock<Glypher> = OnceLock::new();
 
 pub struct OuterApplication {
     pub name: String,
     pub theme: Theme,
+    /// The light palette for this build's art and season, before the player's theme choice is applied
+    pub light_theme: Theme,
+    /// The theme choice and system preference that `theme` was last resolved from
+    pub theme_resolved_from: Option<(ThemeChoice, SystemPreference)>,
     pub launched_at_day: u32,
     pub started_login_at: Option<Duration>,
     pub logged_in_as: Option<String>,
+    /// Set while the server is finishing a sign in with a provider
+    pub signing_in: bool,
     pub unread_changelogs: Vec<String>,
     pub inner_storage: AppInnerStorage,
     pub game_status: app_inner::GameStatus,
@@ -117,7 +144,29 @@ pub struct OuterApplication {
     pub map_texture: TextureHandle,
     pub launched_code: Option<String>,
     pub error: Option<String>,
+    /// Our place in line while the server is too busy to open a new room,
+    /// and roughly how many seconds we have left to wait
+    pub queued: Option<(usize, Option<u64>)>,
+    pub connection: ConnectionQuality,
+    /// Experiments the server has switched on for this session
+    pub flags: Flags,
+    /// A gallery board picked to start a room with, waiting on the room to open
+    pub pending_gallery_board: Option<String>,
+    /// Set while the latest game was played on a gallery board
+    pub gallery_rating: Option<GalleryRating>,
+    /// Left behind by a crash in the last session, until the player chooses whether to send it
+    pub pending_crash_report: Option<CrashReport>,
+    /// The player's upcoming games, shown on the menu
+    pub scheduled_games: Vec<ScheduledGame>,
+    /// How far ahead the menu schedules a new game for
+    pub schedule_in_hours: u32,
+    /// A scheduled game's invite, waiting on the player to be logged in to accept it
+    pub pending_invite: Option<String>,
+    /// A game's notation, pasted into the menu to be replayed
+    pub notation_draft: String,
     pub backchannel: Backchannel,
+    /// Options and callbacks from a page hosting the game, when it has been embedded
+    pub embed: Embed,
     pub log_frames: bool,
     pub frames: debug::FrameHistory,
     pub event_dispatcher: EventDispatcher,
@@ -129,6 +178,7 @@ impl OuterApplication {
         rx_game: R,
         mut tx_player: S,
         room_code: Option<String>,
+        embed: Embed,
         #[cfg(target_arch = "wasm32")] backchannel: js_sys::Function,
     ) -> Self {
         let mut fonts = egui::FontDefinitions::default();
@@ -157,6 +207,19 @@ impl OuterApplication {
                 .insert(egui::FontFamily::Proportional, vec!["pixel".to_owned()]);
         }
 
+        // Our fonts only cover basic Latin, so fall back to egui's own fonts
+        // for names, chat, and dictionaries in other scripts
+        for family in [
+            egui::FontFamily::Name("Truncate-Heavy".into()),
+            egui::FontFamily::Proportional,
+        ] {
+            let fallbacks = FALLBACK_FONTS
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .filter(|name| fonts.font_data.contains_key(name));
+            fonts.families.entry(family).or_default().extend(fallbacks);
+        }
+
         cc.egui_ctx.set_fonts(fonts);
 
         let glypher = Glypher::new();
@@ -241,11 +304,14 @@ impl OuterApplication {
             }
         }
 
-        let theme = if launched_at_day >= ART_CHANGE_DAY {
+        let light_theme = if launched_at_day >= ART_CHANGE_DAY {
             Theme::day()
         } else {
             Theme::old_day()
         };
+        let mut theme = light_theme.clone();
+        embed.options.theme.apply(&mut theme);
+        ThemeChoice::load().store(&cc.egui_ctx);
 
         {
             use egui::FontFamily;
@@ -287,9 +353,12 @@ impl OuterApplication {
         Self {
             name: player_name,
             theme,
+            light_theme,
+            theme_resolved_from: None,
             launched_at_day,
             started_login_at: Some(current_time!()),
             logged_in_as: None,
+            signing_in: false,
             unread_changelogs: vec![],
             game_status,
             inner_storage: AppInnerStorage::default(),
@@ -298,7 +367,18 @@ impl OuterApplication {
             map_texture,
             launched_code: room_code,
             error: None,
+            queued: None,
+            connection: ConnectionQuality::default(),
+            flags: Flags::default(),
+            pending_gallery_board: None,
+            gallery_rating: None,
+            pending_crash_report: crash::take_pending_report(),
+            scheduled_games: vec![],
+            schedule_in_hours: 24,
+            pending_invite: None,
+            notation_draft: String::new(),
             backchannel,
+            embed,
             log_frames: false,
             frames: debug::FrameHistory::default(),
             event_dispatcher: EventDispatcher {
@@ -360,7 +440,11 @@ pub struct TextureMeasurement {
     pub y_padding_pct: f32,
 }
 
-fn load_textures(ctx: &egui::Context, glypher: &Glypher, launched_at_day: u32) -> TextureHandle {
+pub(crate) fn load_textures(
+    ctx: &egui::Context,
+    glypher: &Glypher,
+    launched_at_day: u32,
+) -> TextureHandle {
     let image = if launched_at_day >= ART_CHANGE_DAY {
         image::load_from_memory(include_bytes!("../img/truncate_packed.png")).unwrap()
     } else {
@@ -398,34 +482,72 @@ fn load_textures(ctx: &egui::Context, glypher: &Glypher, launched_at_day: u32) -
     let image_buffer = image.to_rgba8();
     let pixels = image_buffer.as_flat_samples();
     let image = egui::ColorImage::from_rgba_unmultiplied(size, pixels.as_slice());
-    _ = TEXTURE_IMAGE.set(image.clone());
+    load_classic_atlas(image.clone());
 
     ctx.load_texture("tiles", image, TextureOptions::NEAREST)
 }
 
 impl eframe::App for OuterApplication {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Anything that can run at a lower frame rate opts back in while rendering
+        set_low_power(false);
+
+        let resolve_from = (ThemeChoice::current(ctx), SystemPreference::current(frame));
+        if self.theme_resolved_from != Some(resolve_from) {
+            self.theme_resolved_from = Some(resolve_from);
+            self.restyle(
+                ctx,
+                resolve_from.0.resolve(&self.light_theme, resolve_from.1),
+            );
+        }
+
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F3)) {
+            perf::toggle_hud();
+        }
+
         egui::CentralPanel::default()
             .frame(Frame::default().fill(self.theme.water))
             .show(ctx, |ui| app_inner::render(self, ui, current_time!()));
 
+        perf::render_hud(ctx, &self.backchannel);
+
         if self.log_frames {
             self.frames
                 .on_new_frame(ctx.input(|i| i.time), frame.info().cpu_usage);
         }
+        perf::end_frame(
+            ctx.input(|i| i.time),
+            frame.info().cpu_usage,
+            self.connection.latency_ms(),
+        );
+    }
+}
+
+impl OuterApplication {
+    /// Switches everything on screen over to a new theme, keeping any colours set by an embedding page
+    fn restyle(&mut self, ctx: &egui::Context, mut theme: Theme) {
+        self.embed.options.theme.apply(&mut theme);
+
+        ctx.style_mut(|style| {
+            style.visuals.window_fill = theme.water;
+            style.visuals.panel_fill = theme.water;
+        });
+
+        if let Some(game) = self.game_status.active_game_mut() {
+            game.restyle(&theme);
+        }
+        self.theme = theme;
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 fn setup_repaint_truncate_animations(egui_ctx: egui::Context) -> std::thread::JoinHandle<()> {
     std::thread::spawn(move || loop {
-        let current_time = current_time!();
-        let subsec = current_time.subsec_millis();
         // In-game animations should try align with the quarter-second tick,
         // so we try to repaint around that tick to keep them looking consistent.
         // (Adding an extra millisecond so we don't have to worry about `> 250` vs `>= 250`)
-        let next_tick = 251 - (subsec % 250);
-        std::thread::sleep(instant::Duration::from_millis(next_tick as u64));
+        let next_tick = until_next_repaint(current_time!()) + 1;
+        std::thread::sleep(instant::Duration::from_millis(next_tick));
         egui_ctx.request_repaint();
     })
 }
@@ -433,12 +555,10 @@ fn setup_repaint_truncate_animations(egui_ctx: egui::Context) -> std::thread::Jo
 #[cfg(target_arch = "wasm32")]
 async fn setup_repaint_truncate_animations_web(egui_ctx: egui::Context) {
     loop {
-        let current_time = current_time!();
-        let subsec = current_time.subsec_millis();
         // In-game animations should try align with the quarter-second tick,
         // so we try to repaint around that tick to keep them looking consistent.
-        let next_tick = 250 - (subsec % 250);
-        gloo_timers::future::TimeoutFuture::new(next_tick).await;
+        let next_tick = until_next_repaint(current_time!());
+        gloo_timers::future::TimeoutFuture::new(next_tick as u32).await;
         egui_ctx.request_repaint();
     }
 }
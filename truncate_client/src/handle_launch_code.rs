@@ -3,22 +3,30 @@ use eframe::egui;
 use truncate_core::{
     board::Board,
     generation::{generate_board, BoardSeed},
-    messages::LobbyPlayerMessage,
+    messages::{LobbyPlayerMessage, RoomRole},
     npc::scoring::NPCPersonality,
-    rules::GameRules,
+    rotation::PuzzleCategory,
+    rules::{GameRules, Language, RulesPreset},
 };
 
 use crate::{
     app_inner::GameStatus,
     regions::{
-        active_game::HeaderType, lobby::Lobby, single_player::SinglePlayerState,
-        tutorial::TutorialState,
+        active_game::HeaderType, forced_wins::ForcedWinsState, gallery::GalleryState, lobby::Lobby,
+        npc_ladder::NpcLadderState, puzzle_rush::PuzzleRushState, single_player::SinglePlayerState,
+        tutorial::TutorialState, word_ladder::WordLadderState, word_of_day::WordOfTheDayState,
+    },
+    utils::{
+        self,
+        daily::{get_puzzle_day, has_language_notes, player_language},
+        game_evals::has_language_dict,
+        macros::current_time,
+        oauth, perf,
     },
-    utils::{self, daily::get_puzzle_day, macros::current_time},
 };
 
 use super::OuterApplication;
-use truncate_core::messages::PlayerMessage;
+use truncate_core::messages::{OAuthProvider, PlayerMessage};
 
 pub fn handle_launch_code(
     launch_code: &String,
@@ -47,9 +55,20 @@ pub fn handle_launch_code(
                 outer.event_dispatcher.clone(),
             )));
         }
+        "TUTORIAL_CONTINUE" => {
+            return Some(GameStatus::Tutorial(TutorialState::resume(
+                "rules".to_string(),
+                utils::includes::rules(outer.launched_at_day),
+                ui.ctx(),
+                outer.map_texture.clone(),
+                &outer.theme,
+                outer.event_dispatcher.clone(),
+            )));
+        }
         "SINGLE_PLAYER" => {
             outer.event_dispatcher.event("single_player_lobby");
-            let mut board = Board::new(9, 9);
+            let [width, height] = outer.embed.options.board_size().unwrap_or([9, 9]);
+            let mut board = Board::new(width, height);
             board.grow();
             return Some(GameStatus::PendingSinglePlayer(Lobby::new(
                 ui.ctx(),
@@ -59,11 +78,13 @@ pub fn handle_launch_code(
                         name: "You".into(),
                         index: 0,
                         color: (128, 128, 255),
+                        role: RoomRole::Host,
                     },
                     LobbyPlayerMessage {
                         name: "Computer".into(),
                         index: 1,
                         color: (255, 80, 80),
+                        role: RoomRole::Player,
                     },
                 ],
                 0,
@@ -75,12 +96,97 @@ pub fn handle_launch_code(
             let day = outer.launched_at_day;
             // let exact_current_day = get_puzzle_day(current_time!());
             // TODO: Handle day not matching exact_current_day
+            // The client only bundles English puzzles and words, so other languages' are downloaded first,
+            // and the puzzle is loaded once they arrive
+            let language = player_language();
             if let Some(token) = &outer.logged_in_as {
-                send_to_server(PlayerMessage::LoadDailyPuzzle(token.clone(), day));
+                if has_language_dict(language) && has_language_notes(language) {
+                    send_to_server(PlayerMessage::LoadDailyPuzzle(token.clone(), day, language));
+                } else {
+                    send_to_server(PlayerMessage::DownloadLanguage(language));
+                }
             }
 
             return Some(GameStatus::PendingDaily);
         }
+        "WEEKLY_PUZZLE" | "THEMED_PUZZLE" => {
+            let (category, period) = PuzzleCategory::running(outer.launched_at_day)
+                .into_iter()
+                .find(|(category, _)| match category {
+                    PuzzleCategory::WeeklyHard => launch_code == "WEEKLY_PUZZLE",
+                    PuzzleCategory::Themed(_) => launch_code == "THEMED_PUZZLE",
+                    PuzzleCategory::Daily => false,
+                })?;
+            outer.event_dispatcher.event(category.key());
+            if let Some(token) = &outer.logged_in_as {
+                send_to_server(PlayerMessage::LoadCategoryPuzzle {
+                    player_token: token.clone(),
+                    category,
+                    period,
+                });
+            }
+
+            return Some(GameStatus::PendingDaily);
+        }
+        "WORD_LADDER" => {
+            outer.event_dispatcher.event("word_ladder");
+            if let Some(token) = &outer.logged_in_as {
+                send_to_server(PlayerMessage::LoadWordLadder(token.clone()));
+            }
+
+            return Some(GameStatus::WordLadder(WordLadderState::new(
+                outer.launched_at_day,
+                outer.logged_in_as.clone(),
+            )));
+        }
+        "NPC_LADDER" => {
+            outer.event_dispatcher.event("npc_ladder");
+            if let Some(token) = &outer.logged_in_as {
+                send_to_server(PlayerMessage::LoadNpcProgress(token.clone()));
+            }
+
+            return Some(GameStatus::NpcLadder(NpcLadderState::new(
+                outer.logged_in_as.clone(),
+                GameRules::latest(Some(outer.launched_at_day)).0,
+                outer.event_dispatcher.clone(),
+            )));
+        }
+        "PUZZLES" => {
+            outer.event_dispatcher.event("forced_wins");
+
+            return Some(GameStatus::ForcedWins(ForcedWinsState::new(
+                outer.logged_in_as.clone(),
+                outer.event_dispatcher.clone(),
+            )));
+        }
+        "GALLERY" => {
+            outer.event_dispatcher.event("gallery");
+            send_to_server(PlayerMessage::LoadGallery);
+
+            return Some(GameStatus::Gallery(GalleryState::new()));
+        }
+        "PUZZLE_RUSH" => {
+            outer.event_dispatcher.event("puzzle_rush");
+            send_to_server(PlayerMessage::LoadPuzzleRush {
+                player_token: outer.logged_in_as.clone(),
+                day: outer.launched_at_day,
+                language: Language::English,
+            });
+
+            return Some(GameStatus::PuzzleRush(PuzzleRushState::new(
+                outer.launched_at_day,
+                outer.logged_in_as.clone(),
+                outer.event_dispatcher.clone(),
+            )));
+        }
+        "WORD_OF_THE_DAY" => {
+            outer.event_dispatcher.event("word_of_the_day");
+            send_to_server(PlayerMessage::LoadWordOfTheDay(outer.launched_at_day));
+
+            return Some(GameStatus::WordOfTheDay(WordOfTheDayState::new(
+                outer.event_dispatcher.clone(),
+            )));
+        }
         "RANDOM_PUZZLE" => {
             let seed = (current_time!().as_micros() % 243985691) as u32;
             let board_seed = BoardSeed::new(seed);
@@ -100,6 +206,7 @@ pub fn handle_launch_code(
                 board,
                 Some(board_seed),
                 rules_generation,
+                Language::English,
                 true,
                 header,
                 NPCPersonality::jet(),
@@ -126,6 +233,7 @@ pub fn handle_launch_code(
                 board,
                 Some(board_seed),
                 rules_generation,
+                Language::English,
                 true,
                 header,
                 NPCPersonality::mellite(),
@@ -133,6 +241,35 @@ pub fn handle_launch_code(
             );
             return Some(GameStatus::SinglePlayer(puzzle_game));
         }
+        "DEBUG_PERF" => {
+            // Phones have no key to toggle the overlay with
+            if !perf::hud_showing() {
+                perf::toggle_hud();
+            }
+        }
+        "DEBUG_PERF_LARGE" => {
+            // Frame times with the overlay up on a 60x60 board, to check that scrolling stays flat
+            if !perf::hud_showing() {
+                perf::toggle_hud();
+            }
+            let seed_for_hand_tiles = BoardSeed::new_with_generation(0, 1);
+            let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
+            let large_game = SinglePlayerState::new(
+                "perf_large".to_string(),
+                ui.ctx(),
+                outer.map_texture.clone(),
+                outer.theme.clone(),
+                Board::new(60, 60),
+                Some(seed_for_hand_tiles),
+                rules_generation,
+                Language::English,
+                true,
+                HeaderType::Timers,
+                NPCPersonality::jet(),
+                outer.event_dispatcher.clone(),
+            );
+            return Some(GameStatus::SinglePlayer(large_game));
+        }
         "DEBUG_BEHEMOTH" => {
             let behemoth_board = Board::from_string(include_str!("../tutorials/test_board.txt"));
             let seed_for_hand_tiles = BoardSeed::new_with_generation(0, 1);
@@ -145,6 +282,7 @@ pub fn handle_launch_code(
                 behemoth_board,
                 Some(seed_for_hand_tiles),
                 rules_generation,
+                Language::English,
                 true,
                 HeaderType::Timers,
                 NPCPersonality::jet(),
@@ -212,6 +350,7 @@ pub fn handle_launch_code(
                 board,
                 Some(board_seed),
                 rules_generation,
+                Language::English,
                 player == 0,
                 header,
                 npc,
@@ -238,11 +377,100 @@ pub fn handle_launch_code(
         }
     }
 
+    if launch_code.starts_with("COMPARE:") {
+        let ids: Vec<_> = launch_code.split(':').skip(1).collect();
+        if let [first, second] = ids.as_slice() {
+            send_to_server(PlayerMessage::LoadReplay(first.to_string()));
+            send_to_server(PlayerMessage::LoadReplay(second.to_string()));
+            return Some(GameStatus::PendingComparison(vec![]));
+        } else {
+            return Some(GameStatus::HardError(vec![
+                "Sorry, that comparison URL".to_string(),
+                "doesn't look right!".to_string(),
+            ]));
+        }
+    }
+
+    if launch_code.starts_with("SPECTATE:") {
+        if let Some(room_code) = launch_code.split(':').skip(1).next() {
+            send_to_server(PlayerMessage::SpectateGame(room_code.to_string()));
+            return Some(GameStatus::PendingJoin(room_code.to_string()));
+        } else {
+            return Some(GameStatus::HardError(vec![
+                "Sorry, that spectator URL".to_string(),
+                "doesn't look right!".to_string(),
+            ]));
+        }
+    }
+
+    // The web menu reopens the game with this code when a provider sends the player back
+    if launch_code == "SIGNED_IN" {
+        return Some(GameStatus::PendingSignIn(false));
+    }
+
+    // Sends the player off to sign in with a provider, such as SIGN_IN:GITHUB
+    if let Some(requested) = launch_code.strip_prefix("SIGN_IN:") {
+        let message = OAuthProvider::ALL
+            .into_iter()
+            .find(|provider| provider.to_string().eq_ignore_ascii_case(requested))
+            .and_then(oauth::start_sign_in);
+        let Some(message) = message else {
+            return Some(GameStatus::HardError(vec![
+                "Sorry, signing in there".to_string(),
+                "isn't available!".to_string(),
+            ]));
+        };
+
+        send_to_server(message);
+        return Some(GameStatus::PendingSignIn(false));
+    }
+
+    // Accepts an invite to a scheduled game, then shows it in the menu's upcoming games
+    if let Some(invite_code) = launch_code.strip_prefix("SCHEDULED:") {
+        outer.event_dispatcher.event("accept_scheduled_game");
+        match &outer.logged_in_as {
+            Some(token) => send_to_server(PlayerMessage::AcceptScheduledGame {
+                player_token: token.clone(),
+                player_name: outer.name.clone(),
+                invite_code: invite_code.to_string(),
+            }),
+            None => outer.pending_invite = Some(invite_code.to_string()),
+        }
+        return Some(GameStatus::None(String::new(), None));
+    }
+
+    // Matches use the classic rules unless another preset is named, such as FIND_MATCH:BLITZ
+    if launch_code == "FIND_MATCH" || launch_code.starts_with("FIND_MATCH:") {
+        let requested = launch_code.split(':').skip(1).next();
+        let ruleset = match requested {
+            None => Some(RulesPreset::Classic),
+            Some(name) => RulesPreset::ALL
+                .into_iter()
+                .find(|preset| preset.to_string().eq_ignore_ascii_case(name)),
+        };
+        let Some(ruleset) = ruleset else {
+            return Some(GameStatus::HardError(vec![
+                "Sorry, those rules".to_string(),
+                "don't look right!".to_string(),
+            ]));
+        };
+
+        outer.event_dispatcher.event("find_match");
+        send_to_server(PlayerMessage::FindMatch {
+            player_name: outer.name.clone(),
+            effective_day: outer.launched_at_day,
+            ruleset,
+            latency_ms: outer.connection.latency_ms(),
+        });
+        return Some(GameStatus::PendingMatch((ruleset, 1)));
+    }
+
     // No room code means we start a new game.
     if launch_code.is_empty() {
         send_to_server(PlayerMessage::NewGame {
             player_name: outer.name.clone(),
             effective_day: outer.launched_at_day,
+            rules: None,
         });
         return Some(GameStatus::PendingCreate);
     }
@@ -2,24 +2,35 @@ use eframe::egui;
 use truncate_core::{
     game::{self, GAME_COLOR_BLUE, GAME_COLOR_RED},
     generation,
-    rules::GameRules,
+    judge::language_words,
+    rotation::PuzzleCategory,
+    rules::{GameRules, HandSwaps, Language, Spelling},
 };
 
 use crate::{
     app_inner::GameStatus,
+    app_outer::{BackchannelMsg, ShareType},
     regions::{
         active_game::{ActiveGame, GameLocation, HeaderType},
+        gallery::GalleryRating,
         lobby::Lobby,
+        replay_comparison::ReplayComparisonState,
         replayer::ReplayerState,
     },
     utils::{
-        daily::{get_playable_daily_puzzle, get_raw_daily_puzzle},
-        game_evals::get_main_dict,
+        daily::{
+            get_playable_daily_puzzle, get_playable_rotation_puzzle, get_raw_daily_puzzle,
+            use_language_notes,
+        },
+        game_evals::{get_dict, use_language_dict},
+        macros::current_time,
+        oauth, packs,
+        tex::atlas::apply_season,
     },
 };
 
 use super::OuterApplication;
-use truncate_core::messages::{GameMessage, GameStateMessage};
+use truncate_core::messages::{DailyStateMessage, GameMessage, GameStateMessage, PlayerMessage};
 
 /// Main delegator for all messages from the server to the client,
 /// both in-game and other.
@@ -33,11 +44,41 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
         match msg {
             GameMessage::Ping | GameMessage::Ack(_) | GameMessage::PleaseLogin => { /* handled at comms layer */
             }
+            GameMessage::LatencyPong(sent_at) => {
+                let round_trip_ms = (current_time!().as_millis() as u64).saturating_sub(sent_at);
+                outer.connection.record(round_trip_ms);
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    game.depot.gameplay.latency_ms = outer.connection.latency_ms();
+                }
+            }
+            GameMessage::Queued {
+                position,
+                estimated_wait_secs,
+            } => {
+                outer.queued = Some((position, estimated_wait_secs));
+            }
+            GameMessage::FindingMatch { ruleset, waiting } => {
+                if let GameStatus::PendingMatch(status) = &mut outer.game_status {
+                    *status = (ruleset, waiting);
+                }
+            }
             GameMessage::JoinedLobby(player_index, id, players, board, token) => {
+                outer.queued = None;
+
                 // If we're already in a lobby, treat this as a lobby update
-                // (the websocket probably dropped and reconnected)
+                // (the websocket probably dropped and reconnected, or a player ahead of us was removed)
                 if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
                     if lobby.room_code.to_uppercase() == id.to_uppercase() {
+                        #[cfg(target_arch = "wasm32")]
+                        {
+                            let local_storage =
+                                web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                            local_storage
+                                .set_item("truncate_active_token", &token)
+                                .unwrap();
+                        }
+
+                        lobby.player_index = player_index;
                         lobby.players = players;
                         lobby.update_board(board, ui);
                         continue;
@@ -59,14 +100,40 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                         .set_hash(id.to_uppercase().as_str());
                 }
 
-                outer.game_status = GameStatus::PendingStart(Lobby::new(
+                let mut lobby = Lobby::new(
                     ui.ctx(),
                     id.to_uppercase(),
                     players,
                     player_index,
                     board,
                     outer.map_texture.clone(),
-                ))
+                );
+                lobby.spectator_delay_secs = Some(0);
+                lobby.hand_swap_turns = Some(0);
+                lobby.lenient_spelling = Some(false);
+                lobby.blank_tiles = Some(0);
+                if let Some(board_id) = outer.pending_gallery_board.take() {
+                    outer
+                        .tx_player
+                        .try_send(PlayerMessage::UseGalleryBoard(board_id))
+                        .unwrap();
+                }
+                outer
+                    .tx_player
+                    .try_send(PlayerMessage::RequestPacks)
+                    .unwrap();
+                if let Some(player_token) = &outer.logged_in_as {
+                    lobby.player_token = Some(player_token.clone());
+                    outer
+                        .tx_player
+                        .try_send(PlayerMessage::LoadSavedBoards(player_token.clone()))
+                        .unwrap();
+                    outer
+                        .tx_player
+                        .try_send(PlayerMessage::RequestFairPlay(player_token.clone()))
+                        .unwrap();
+                }
+                outer.game_status = GameStatus::PendingStart(lobby);
             }
             GameMessage::LobbyUpdate(_player_index, _id, players, board) => {
                 match &mut outer.game_status {
@@ -86,9 +153,11 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                 board,
                 hand,
                 changes: _,
+                event_seq: _,
                 game_ends_at,
                 paused,
                 remaining_turns,
+                hand_swap_in,
             }) => {
                 // If we're already in a game, treat this as a game update
                 // (the websocket probably dropped and reconnected)
@@ -101,17 +170,27 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                             next_player_number,
                             board,
                             hand,
-                            changes: vec![], // TODO: Try get latest changes on reconnect without dupes
+                            // Changes we missed are requested separately, so they aren't duplicated
+                            changes: vec![],
+                            event_seq: 0,
                             game_ends_at,
                             paused,
                             remaining_turns,
+                            hand_swap_in,
                         };
+                        let since = game.last_event_seq();
                         game.apply_new_state(update);
+                        outer
+                            .tx_player
+                            .try_send(PlayerMessage::RequestEventsSince(since))
+                            .unwrap();
                         continue;
                     }
                 }
 
-                outer.game_status = GameStatus::Active(ActiveGame::new(
+                // Set again if this game is on a gallery board
+                outer.gallery_rating = None;
+                let mut game = ActiveGame::new(
                     ui.ctx(),
                     room_code.to_uppercase(),
                     None,
@@ -126,11 +205,26 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     GameLocation::Online,
                     game_ends_at,
                     remaining_turns,
-                ));
+                );
+                game.depot.gameplay.hand_swap_in = hand_swap_in;
+                outer.game_status = GameStatus::Active(game);
             }
             GameMessage::GameUpdate(state_message) => match &mut outer.game_status {
                 GameStatus::Active(game) => {
+                    let since = game.last_event_seq();
+                    let seq = state_message.event_seq;
                     game.apply_new_state(state_message);
+
+                    if seq > since + 1 {
+                        outer
+                            .tx_player
+                            .try_send(PlayerMessage::RequestEventsSince(since))
+                            .unwrap();
+                    }
+                    outer
+                        .tx_player
+                        .try_send(PlayerMessage::AckEvents(seq))
+                        .unwrap();
                 }
                 _ => {
                     outer.game_status = GameStatus::HardError(vec![
@@ -152,7 +246,7 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     ])
                 }
             },
-            GameMessage::GameEnd(state_message, winner) => {
+            GameMessage::GameEnd(state_message, winner, end_state) => {
                 #[cfg(target_arch = "wasm32")]
                 {
                     let local_storage =
@@ -164,11 +258,60 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     GameStatus::Active(game) => {
                         game.apply_new_state(state_message);
                         game.depot.gameplay.winner = Some(winner as usize);
-                        outer.game_status = GameStatus::Concluded(game.clone(), winner);
+                        game.depot.gameplay.end_state = Some(end_state);
+                        outer.game_status = GameStatus::Concluded(game.clone(), Some(winner));
                     }
                     _ => {}
                 }
             }
+            GameMessage::GameDrawn(state_message, _, end_state) => {
+                #[cfg(target_arch = "wasm32")]
+                {
+                    let local_storage =
+                        web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                    local_storage.remove_item("truncate_active_token").unwrap();
+                }
+
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    game.apply_new_state(state_message);
+                    game.depot.gameplay.end_state = Some(end_state);
+                    outer.game_status = GameStatus::Concluded(game.clone(), None);
+                }
+            }
+            GameMessage::PlayerPresence { player, away } => {
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    let name = game
+                        .players
+                        .get(player as usize)
+                        .map(|p| p.name.clone())
+                        .unwrap_or_else(|| "Your opponent".into());
+                    game.depot.gameplay.error_msg = Some(if away {
+                        format!("{name} appears to be away")
+                    } else {
+                        format!("{name} is back")
+                    });
+                }
+            }
+            GameMessage::Pointed { player, coordinate } => {
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    let shown_at = game.depot.timing.current_time;
+                    game.depot
+                        .interactions
+                        .pointed_squares
+                        .push((coordinate, player, shown_at));
+                }
+            }
+            GameMessage::Chat {
+                channel,
+                sender,
+                text,
+            } => {
+                if let GameStatus::Active(game) | GameStatus::Spectating(game) =
+                    &mut outer.game_status
+                {
+                    game.receive_chat(channel, sender, text);
+                }
+            }
             GameMessage::GameError(_id, _num, err) => match &mut outer.game_status {
                 GameStatus::Active(game) => {
                     // assert_eq!(game.room_code, id);
@@ -196,6 +339,22 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     GameStatus::Tutorial(tut) => {
                         tut.load_definitions(definitions);
                     }
+                    GameStatus::NpcLadder(ladder) => {
+                        if let Some(game) = ladder.active_game() {
+                            game.hydrate_meanings(definitions.clone());
+                            if let Some(dict_ui) = &mut game.active_game.dictionary_ui {
+                                dict_ui.load_definitions(definitions);
+                            }
+                        }
+                    }
+                    GameStatus::ForcedWins(puzzles) => {
+                        if let Some(game) = puzzles.active_game() {
+                            game.hydrate_meanings(definitions.clone());
+                            if let Some(dict_ui) = &mut game.active_game.dictionary_ui {
+                                dict_ui.load_definitions(definitions);
+                            }
+                        }
+                    }
                     _ => { /* Soft unreachable */ }
                 }
             }
@@ -212,23 +371,109 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                         .unwrap();
                 }
 
+                if let Some(invite_code) = outer.pending_invite.take() {
+                    outer
+                        .tx_player
+                        .try_send(PlayerMessage::AcceptScheduledGame {
+                            player_token: player_token.clone(),
+                            player_name: outer.name.clone(),
+                            invite_code,
+                        })
+                        .unwrap();
+                } else {
+                    outer
+                        .tx_player
+                        .try_send(PlayerMessage::LoadScheduledGames(player_token.clone()))
+                        .unwrap();
+                }
+
                 outer.logged_in_as = Some(player_token);
                 outer.unread_changelogs = unread_changelogs;
+
+                if std::mem::take(&mut outer.signing_in) {
+                    if let GameStatus::PendingSignIn(signed_in) = &mut outer.game_status {
+                        *signed_in = true;
+                    }
+                }
+            }
+            GameMessage::ActiveSeason(season) => {
+                #[allow(unused_mut)]
+                let mut classic_look = false;
+                #[cfg(target_arch = "wasm32")]
+                {
+                    let local_storage =
+                        web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                    classic_look = local_storage
+                        .get_item("truncate_classic_look")
+                        .unwrap()
+                        .unwrap_or_default()
+                        .parse()
+                        .unwrap_or_default();
+                }
+
+                // The old art predates seasons, so it keeps its own look
+                if !outer.theme.use_old_art {
+                    let shown = apply_season(&mut outer.map_texture, season, classic_look);
+                    outer.light_theme = outer.light_theme.clone().with_season(shown);
+                    outer.theme = outer.theme.clone().with_season(shown);
+                    if let GameStatus::Active(game) = &mut outer.game_status {
+                        game.show_season(shown);
+                    }
+                }
+            }
+            GameMessage::SignInProviders(providers) => {
+                oauth::set_providers(providers);
+            }
+            GameMessage::SignInRedirect(url) => {
+                // Web players leave the page to sign in, while native players
+                // sign in with their browser and come back to the game
+                ui.ctx().open_url(egui::OpenUrl {
+                    url,
+                    new_tab: cfg!(not(target_arch = "wasm32")),
+                });
+            }
+            GameMessage::SessionFlags(flags) => {
+                outer.flags = flags;
+            }
+            GameMessage::MissedEvents(events) => {
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    game.apply_missed_events(events);
+                    outer
+                        .tx_player
+                        .try_send(PlayerMessage::AckEvents(game.last_event_seq()))
+                        .unwrap();
+                }
             }
             GameMessage::ResumeDailyPuzzle(latest_puzzle_state, best_puzzle) => {
-                let mut puzzle_game = get_playable_daily_puzzle(
-                    ui.ctx(),
-                    latest_puzzle_state.puzzle_day,
-                    &outer.map_texture,
-                    &outer.theme,
-                    &outer.backchannel,
-                    outer.event_dispatcher.clone(),
-                );
+                let mut puzzle_game = match latest_puzzle_state.category {
+                    PuzzleCategory::Daily => get_playable_daily_puzzle(
+                        ui.ctx(),
+                        latest_puzzle_state.puzzle_day,
+                        latest_puzzle_state.language,
+                        &outer.map_texture,
+                        &outer.theme,
+                        &outer.backchannel,
+                        outer.event_dispatcher.clone(),
+                    ),
+                    category => {
+                        let Some(puzzle_game) = get_playable_rotation_puzzle(
+                            ui.ctx(),
+                            category,
+                            latest_puzzle_state.puzzle_day,
+                            &outer.map_texture,
+                            &outer.theme,
+                            outer.event_dispatcher.clone(),
+                        ) else {
+                            continue;
+                        };
+                        puzzle_game
+                    }
+                };
 
                 if let Some(best_puzzle) = best_puzzle {
                     let mut best_game = puzzle_game.game.clone();
                     best_game.rules.battle_delay = 0;
-                    let dict_lock = get_main_dict();
+                    let dict_lock = get_dict(best_game.rules.language);
                     let dict = dict_lock.as_ref().unwrap();
 
                     for next_move in best_puzzle.current_moves.into_iter() {
@@ -252,6 +497,11 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     _ => {}
                 }
                 puzzle_game.move_sequence = latest_puzzle_state.current_moves.clone();
+                // Attempts from before think times were kept have none for their moves
+                puzzle_game.think_secs = latest_puzzle_state.think_secs.clone();
+                puzzle_game
+                    .think_secs
+                    .resize(latest_puzzle_state.current_moves.len(), 0);
 
                 let delay = puzzle_game.game.rules.battle_delay;
                 puzzle_game.game.rules.battle_delay = 0;
@@ -275,50 +525,348 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                 }
                 _ => {}
             },
-            GameMessage::LoadDailyReplay(puzzle_state) => {
-                let (seed, info) = get_raw_daily_puzzle(puzzle_state.puzzle_day);
-                let human_starts = info.as_ref().map(|(h, _)| *h).unwrap_or(true);
-                let rules_generation = info
-                    .as_ref()
-                    .map(|(_, i)| i.rules_generation)
-                    .unwrap_or_else(|| GameRules::latest(Some(outer.launched_at_day)).0);
-
-                let mut game = game::Game::new(
-                    9,
-                    9,
-                    Some(seed.seed as u64),
-                    GameRules::generation(rules_generation),
-                );
-                if human_starts {
-                    game.add_player("You".into());
-                    game.add_player("Computer".into());
+            GameMessage::WordLadderStats(stats) => {
+                if let GameStatus::WordLadder(ladder) = &mut outer.game_status {
+                    ladder.stats = Some(stats);
+                }
+            }
+            GameMessage::NpcProgress(progress) => {
+                if let GameStatus::NpcLadder(ladder) = &mut outer.game_status {
+                    ladder.merge_progress(progress);
+                }
+            }
+            GameMessage::PuzzleRushLeaderboard(leaderboard) => {
+                if let GameStatus::PuzzleRush(rush) = &mut outer.game_status {
+                    rush.leaderboard = Some(leaderboard);
+                }
+            }
+            GameMessage::PuzzleRushStarted(seed) => {
+                if let GameStatus::PuzzleRush(rush) = &mut outer.game_status {
+                    rush.started_seed = Some(seed);
+                }
+            }
+            GameMessage::WordOfTheDay(daily) => {
+                if let GameStatus::WordOfTheDay(state) = &mut outer.game_status {
+                    state.daily = Some(daily);
+                }
+            }
+            GameMessage::SpectatorDelay(delay_secs) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.spectator_delay_secs = Some(delay_secs);
+                }
+            }
+            GameMessage::HandSwaps(turns) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.hand_swap_turns = Some(turns);
+                    if let Some(rules) = &mut lobby.rules {
+                        rules.hand_swaps = match turns {
+                            0 => HandSwaps::None,
+                            turns => HandSwaps::Every { turns },
+                        };
+                    }
+                }
+            }
+            GameMessage::LenientSpelling(lenient) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.lenient_spelling = Some(lenient);
+                    if let Some(rules) = &mut lobby.rules {
+                        rules.battle_rules.spelling = if lenient {
+                            Spelling::Lenient
+                        } else {
+                            Spelling::Strict
+                        };
+                    }
+                }
+            }
+            GameMessage::BlankTiles(count) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.blank_tiles = Some(count);
+                    if let Some(rules) = &mut lobby.rules {
+                        rules.blank_tiles = count;
+                    }
+                }
+            }
+            GameMessage::RoomRules(rules) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    if let Some(pack_ref) = &rules.dictionary_pack {
+                        if !packs::has_pack(pack_ref) {
+                            outer
+                                .tx_player
+                                .try_send(PlayerMessage::DownloadPack(pack_ref.id.clone()))
+                                .unwrap();
+                        }
+                    }
+                    lobby.update_rules(rules);
+                }
+            }
+            GameMessage::RulesWarnings(warnings) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.rules_warnings = warnings;
+                }
+            }
+            GameMessage::AvailablePacks(available) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.available_packs = available;
+                }
+            }
+            GameMessage::PackContents(pack, words) => {
+                if let Err(err) = packs::store_pack(pack, words) {
+                    outer.error = Some(err);
+                }
+            }
+            GameMessage::LanguageContents {
+                language,
+                words,
+                seed_notes,
+            } => {
+                // Without the language's words and puzzles, the player is given the English puzzle
+                let mut puzzle_language = Language::English;
+                if let (Some(words), Some(seed_notes)) = (words, seed_notes) {
+                    if use_language_notes(language, &seed_notes).is_ok() {
+                        use_language_dict(language, language_words(&words, language));
+                        if outer.backchannel.is_open() {
+                            outer
+                                .backchannel
+                                .send_msg(BackchannelMsg::UseLanguage { language, words });
+                        }
+                        puzzle_language = language;
+                    }
+                }
+
+                if let (GameStatus::PendingDaily, Some(token)) =
+                    (&outer.game_status, &outer.logged_in_as)
+                {
+                    outer
+                        .tx_player
+                        .try_send(PlayerMessage::LoadDailyPuzzle(
+                            token.clone(),
+                            outer.launched_at_day,
+                            puzzle_language,
+                        ))
+                        .unwrap();
+                }
+            }
+            GameMessage::RoomLanguage {
+                language,
+                available,
+            } => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.language = Some(language);
+                    lobby.available_languages = available;
+                }
+            }
+            GameMessage::Gallery(boards) => {
+                if let GameStatus::Gallery(gallery) = &mut outer.game_status {
+                    gallery.boards = Some(boards);
+                }
+            }
+            GameMessage::PlayingGalleryBoard { board_id, name } => {
+                outer.gallery_rating = Some(GalleryRating::new(board_id, name));
+            }
+            GameMessage::SavedBoards(saved_boards) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.saved_boards = saved_boards;
+                }
+            }
+            GameMessage::FairPlay(record) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    lobby.fair_play = Some(record);
+                }
+            }
+            GameMessage::ArbiterNotice {
+                state,
+                action,
+                reason,
+            } => {
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    game.apply_arbiter_notice(state, action, reason);
+                }
+            }
+            GameMessage::MulliganOffer { divergence_bits } => {
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    game.depot.gameplay.mulligan_offer = Some(divergence_bits);
+                }
+            }
+            GameMessage::ScheduledGames(scheduled_games) => {
+                outer.scheduled_games = scheduled_games;
+            }
+            GameMessage::Kicked(room_code) => {
+                #[cfg(target_arch = "wasm32")]
+                {
+                    let local_storage =
+                        web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                    local_storage.remove_item("truncate_active_token").unwrap();
+                }
 
-                    game.players[0].color = GAME_COLOR_BLUE;
-                    game.players[1].color = GAME_COLOR_RED;
+                let room_code = room_code.to_uppercase();
+                outer.error = Some(format!("REMOVED FROM {room_code}"));
+                outer.game_status = GameStatus::PendingJoin(room_code);
+            }
+            GameMessage::SpectatorUpdate { state, delay_secs } => {
+                let title = if delay_secs > 0 {
+                    format!("Spectating, {delay_secs}s behind")
                 } else {
-                    game.add_player("Computer".into());
-                    game.add_player("You".into());
+                    "Spectating".to_string()
+                };
 
-                    game.players[0].color = GAME_COLOR_RED;
-                    game.players[1].color = GAME_COLOR_BLUE;
+                if let GameStatus::Spectating(game) = &mut outer.game_status {
+                    if game.depot.gameplay.room_code.to_uppercase()
+                        == state.room_code.to_uppercase()
+                    {
+                        game.apply_new_state(state);
+                        continue;
+                    }
                 }
 
-                let mut board = generation::generate_board(seed.clone())
-                    .expect("Common seeds should always generate a board")
-                    .board;
-                board.cache_special_squares();
-                game.board = board.clone();
+                let mut game = ActiveGame::new(
+                    ui.ctx(),
+                    state.room_code.to_uppercase(),
+                    None,
+                    None,
+                    state.players,
+                    state.player_number,
+                    state.next_player_number,
+                    state.board,
+                    state.hand,
+                    outer.map_texture.clone(),
+                    outer.theme.clone(),
+                    GameLocation::Online,
+                    state.game_ends_at,
+                    state.remaining_turns,
+                );
+                game.depot.gameplay.hand_swap_in = state.hand_swap_in;
+                game.depot.ui_state.game_header = HeaderType::Summary {
+                    title,
+                    attempt: None,
+                };
+                game.depot.ui_state.hand_hidden = true;
+                game.depot.interactions.view_only = true;
+                outer.game_status = GameStatus::Spectating(game);
+            }
+            GameMessage::GameArchive(archive) => {
+                if outer.backchannel.is_open() {
+                    outer.backchannel.send_msg(BackchannelMsg::Copy {
+                        text: archive,
+                        share: ShareType::None,
+                    });
+                } else {
+                    ui.ctx().output_mut(|o| o.copied_text = archive);
+                }
+            }
+            GameMessage::PuzzleDifficulty(_) => {
+                // Difficulty reports are for operators, and aren't shown in the game
+            }
+            GameMessage::LoadDailyReplay(puzzle_state) => {
+                if let GameStatus::PendingComparison(attempts) = &mut outer.game_status {
+                    attempts.push(puzzle_state);
+                    if attempts.len() < 2 {
+                        continue;
+                    }
+
+                    let second = attempts.pop().unwrap();
+                    let first = attempts.pop().unwrap();
+                    if first.puzzle_day != second.puzzle_day {
+                        outer.game_status = GameStatus::HardError(vec![
+                            "Those attempts are from".to_string(),
+                            "different puzzles!".to_string(),
+                        ]);
+                        continue;
+                    }
+
+                    let side = |attempt: DailyStateMessage| {
+                        let (game, as_player) =
+                            daily_replay_game(attempt.puzzle_day, outer.launched_at_day);
+                        (
+                            format!("ATTEMPT #{}", attempt.attempt + 1),
+                            game,
+                            attempt.current_moves,
+                            as_player,
+                        )
+                    };
+                    let comparison = ReplayComparisonState::new(
+                        ui.ctx(),
+                        outer.map_texture.clone(),
+                        side(first),
+                        side(second),
+                    );
+                    outer.game_status = GameStatus::Comparison(comparison);
+                    continue;
+                }
 
+                let (game, as_player) =
+                    daily_replay_game(puzzle_state.puzzle_day, outer.launched_at_day);
                 let replayer = ReplayerState::new(
                     ui.ctx(),
                     outer.map_texture.clone(),
                     outer.theme.clone(),
                     game,
                     puzzle_state.current_moves,
-                    if human_starts { 0 } else { 1 },
+                    puzzle_state.think_secs,
+                    as_player,
+                )
+                .with_seed(get_raw_daily_puzzle(puzzle_state.puzzle_day, Language::English).0);
+                outer.game_status = GameStatus::Replay(replayer);
+            }
+            GameMessage::LoadGameReplay(replay) => {
+                let mut game = game::Game::new(9, 9, Some(replay.bag_seed), replay.rules);
+                for name in replay.player_names {
+                    game.add_player(name);
+                }
+                let mut board = replay.board;
+                board.cache_special_squares();
+                game.board = board;
+
+                let replayer = ReplayerState::new(
+                    ui.ctx(),
+                    outer.map_texture.clone(),
+                    outer.theme.clone(),
+                    game,
+                    replay.moves,
+                    vec![],
+                    0,
                 );
                 outer.game_status = GameStatus::Replay(replayer);
             }
         }
     }
 }
+
+/// Sets up a daily puzzle as it was before any moves were played,
+/// alongside which player the human was.
+/// Replays are of the bundled English puzzles, as other languages' are only downloaded to play them.
+fn daily_replay_game(puzzle_day: u32, launched_at_day: u32) -> (game::Game, usize) {
+    let (seed, info) = get_raw_daily_puzzle(puzzle_day, Language::English);
+    let human_starts = info.as_ref().map(|(h, _)| *h).unwrap_or(true);
+    let rules_generation = info
+        .as_ref()
+        .map(|(_, i)| i.rules_generation)
+        .unwrap_or_else(|| GameRules::latest(Some(launched_at_day)).0);
+
+    let mut game = game::Game::new(
+        9,
+        9,
+        Some(seed.seed as u64),
+        GameRules::generation(rules_generation),
+    );
+    if human_starts {
+        game.add_player("You".into());
+        game.add_player("Computer".into());
+
+        game.players[0].color = GAME_COLOR_BLUE;
+        game.players[1].color = GAME_COLOR_RED;
+    } else {
+        game.add_player("Computer".into());
+        game.add_player("You".into());
+
+        game.players[0].color = GAME_COLOR_RED;
+        game.players[1].color = GAME_COLOR_BLUE;
+    }
+
+    let mut board = generation::generate_board(seed.clone())
+        .expect("Common seeds should always generate a board")
+        .board;
+    board.cache_special_squares();
+    game.board = board;
+
+    (game, if human_starts { 0 } else { 1 })
+}
@@ -36,6 +36,8 @@ impl WebHandle {
 
         // Make sure panics are logged using `console.error`.
         console_error_panic_hook::set_once();
+        // Keep an anonymous report of any panic, to offer to send on the next launch
+        utils::crash::install_panic_hook();
 
         // Redirect tracing to console.log and friends:
         tracing_wasm::set_as_global_default();
@@ -64,6 +66,68 @@ impl WebHandle {
         server_url: &str,
         room_code: &str,
         backchannel: js_sys::Function,
+    ) -> Result<(), wasm_bindgen::JsValue> {
+        self.launch(
+            canvas_id,
+            server_url,
+            room_code,
+            backchannel,
+            utils::embed::Embed::default(),
+        )
+        .await
+    }
+
+    /// Starts the app inside another site, in place of `start`.
+    /// `options` is a JSON object of `theme` colours, `board_size` and `read_only`,
+    /// and `on_game_end` is called with a JSON summary each time a game finishes.
+    #[wasm_bindgen]
+    pub async fn start_embedded(
+        &self,
+        canvas_id: &str,
+        server_url: &str,
+        room_code: &str,
+        backchannel: js_sys::Function,
+        options: &str,
+        on_game_end: Option<js_sys::Function>,
+    ) -> Result<(), wasm_bindgen::JsValue> {
+        use utils::embed::{Embed, EmbedOptions};
+
+        let embed = Embed::new(EmbedOptions::from_json(options), on_game_end);
+        self.launch(canvas_id, server_url, room_code, backchannel, embed)
+            .await
+    }
+
+    /// Shut down eframe and clean up resources.
+    #[wasm_bindgen]
+    pub fn destroy(&self) {
+        self.runner.destroy();
+    }
+
+    #[wasm_bindgen]
+    pub fn has_panicked(&self) -> bool {
+        self.runner.has_panicked()
+    }
+
+    #[wasm_bindgen]
+    pub fn panic_message(&self) -> Option<String> {
+        self.runner.panic_summary().map(|s| s.message())
+    }
+
+    #[wasm_bindgen]
+    pub fn panic_callstack(&self) -> Option<String> {
+        self.runner.panic_summary().map(|s| s.callstack())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WebHandle {
+    async fn launch(
+        &self,
+        canvas_id: &str,
+        server_url: &str,
+        room_code: &str,
+        backchannel: js_sys::Function,
+        embed: utils::embed::Embed,
     ) -> Result<(), wasm_bindgen::JsValue> {
         let web_options = eframe::WebOptions::default();
 
@@ -97,33 +161,13 @@ impl WebHandle {
                         rx_game,
                         tx_player,
                         Some(room_code),
+                        embed,
                         backchannel,
                     ))
                 }),
             )
             .await
     }
-
-    /// Shut down eframe and clean up resources.
-    #[wasm_bindgen]
-    pub fn destroy(&self) {
-        self.runner.destroy();
-    }
-
-    #[wasm_bindgen]
-    pub fn has_panicked(&self) -> bool {
-        self.runner.has_panicked()
-    }
-
-    #[wasm_bindgen]
-    pub fn panic_message(&self) -> Option<String> {
-        self.runner.panic_summary().map(|s| s.message())
-    }
-
-    #[wasm_bindgen]
-    pub fn panic_callstack(&self) -> Option<String> {
-        self.runner.panic_summary().map(|s| s.callstack())
-    }
 }
 
 // Functions used in the web worker
@@ -145,6 +189,8 @@ pub fn backchannel(msg: String) -> String {
             players,
             next_player,
             npc_params,
+            budget_ms,
+            share,
         } => {
             let mut game = truncate_core::game::Game::new(9, 9, None, rules);
             game.board = board;
@@ -158,9 +204,18 @@ pub fn backchannel(msg: String) -> String {
                     .expect("Please don't play Truncate before 1970")
                     .as_secs(),
             );
-            let best = utils::game_evals::client_best_move(&game, &npc_params);
+            let progress = std::sync::Arc::new(truncate_core::npc::SearchProgress::reporting_to(
+                post_search_progress,
+            ));
+            let result = utils::game_evals::client_share_move(
+                &game,
+                &npc_params,
+                budget_ms,
+                share,
+                Some(progress),
+            );
 
-            return serde_json::to_string(&best).expect("Resultant move should be serializable");
+            return serde_json::to_string(&result).expect("Resultant move should be serializable");
         }
         BackchannelMsg::Remember { word } => {
             utils::game_evals::remember(&word);
@@ -170,11 +225,43 @@ pub fn backchannel(msg: String) -> String {
             utils::game_evals::forget();
             return String::new();
         }
+        BackchannelMsg::UseLanguage { language, words } => {
+            let words = truncate_core::judge::language_words(&words, language);
+            utils::game_evals::use_language_dict(language, words);
+            return String::new();
+        }
         BackchannelMsg::QueryFor { .. } => {
             unreachable!("Backchannel should not be passing through QueryFor")
         }
+        BackchannelMsg::ProgressOf { .. } => {
+            unreachable!("Backchannel should not be passing through ProgressOf")
+        }
         BackchannelMsg::Copy { .. } => {
             unreachable!("Backchannel should not be passing through Copy")
         }
     }
 }
+
+/// Passes the search's progress from the web worker to the page, through the
+/// `truncate_search_progress` function that the worker provides
+#[cfg(target_arch = "wasm32")]
+fn post_search_progress(report: truncate_core::npc::SearchReport) {
+    let Ok(post) = js_sys::Reflect::get(
+        &js_sys::global(),
+        &JsValue::from_str("truncate_search_progress"),
+    ) else {
+        return;
+    };
+    if let Some(post) = post.dyn_ref::<js_sys::Function>() {
+        let report = serde_json::to_string(&report).expect("Progress should be serializable");
+        _ = post.call1(&JsValue::NULL, &JsValue::from(report));
+    }
+}
+
+// Used by `.backstage/check-board-determinism.js` to check that boards
+// generate on wasm exactly as they do on native.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn board_fingerprints() -> String {
+    truncate_core::generation::board_fingerprints()
+}
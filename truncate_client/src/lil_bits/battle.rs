@@ -5,7 +5,11 @@ use eframe::{
     emath::Align,
 };
 use epaint::{vec2, Color32, Galley, Rect, Vec2};
-use truncate_core::reporting::{BattleReport, BattleWord};
+use interpolation::Ease;
+use truncate_core::{
+    bag::spell_tiles,
+    reporting::{BattleReport, BattleWord},
+};
 
 use crate::utils::{
     depot::{AestheticDepot, TruncateDepot},
@@ -79,8 +83,14 @@ impl<'a> BattleUI<'a> {
                             },
                         )
                     } else {
+                        // Lenient spelling rules read some words as others, so show both
+                        let label = if w.misspelled() {
+                            format!("{} ({})", w.original_word, w.resolved_word)
+                        } else {
+                            spell_tiles(&w.resolved_word)
+                        };
                         ui.painter().layout_no_wrap(
-                            w.resolved_word.clone(),
+                            label,
                             FontId::new(
                                 aesthetics.theme.letter_size * 0.75,
                                 egui::FontFamily::Name("Truncate-Heavy".into()),
@@ -163,11 +173,30 @@ impl<'a> BattleUI<'a> {
     }
 
     pub fn render(self, ui: &mut egui::Ui, depot: &mut TruncateDepot) {
-        let TruncateDepot { aesthetics, .. } = depot;
+        let TruncateDepot {
+            aesthetics, flags, ..
+        } = depot;
 
         let battle_id = Id::new("battle").with(self.battle.battle_number.unwrap_or_default());
         let prev_battle_storage: Option<BattleUIState> = ui.memory(|m| m.data.get_temp(battle_id));
 
+        // Experimental: slide the report up into place once it can be drawn
+        if flags.new_battle_animation {
+            let slide = ui
+                .ctx()
+                .animate_value_with_time(
+                    battle_id.with("slide"),
+                    if prev_battle_storage.is_some() {
+                        1.0
+                    } else {
+                        0.0
+                    },
+                    0.3,
+                )
+                .calc(interpolation::EaseFunction::QuadraticOut);
+            ui.add_space((1.0 - slide) * 16.0);
+        }
+
         // Paint the background dialog based on the size of the battle last frame
         if let Some(BattleUIState {
             size_last_frame,
@@ -297,6 +326,29 @@ impl<'a> BattleUI<'a> {
                 );
             }
 
+            if let Some(amendment) = &self.battle.amendment {
+                let msg = if amendment.overturned {
+                    "overturned on appeal"
+                } else {
+                    "upheld on appeal"
+                };
+
+                ui.add_space(5.0);
+                let galley = ui.painter().layout_no_wrap(
+                    msg.to_string(),
+                    FontId::new(
+                        aesthetics.theme.letter_size * 0.3,
+                        egui::FontFamily::Name("Truncate-Heavy".into()),
+                    ),
+                    if render_transparent {
+                        Color32::TRANSPARENT
+                    } else {
+                        aesthetics.theme.text
+                    },
+                );
+                battle_rect = battle_rect.union(self.paint_galleys(vec![galley], ui, false).rect);
+            }
+
             if !active {
                 return battle_rect;
             }
@@ -318,13 +370,26 @@ impl<'a> BattleUI<'a> {
                     }
                     ui.add_space(12.0);
                     TextHelper::heavy(
-                        &word.resolved_word,
+                        &spell_tiles(&word.resolved_word),
                         aesthetics.theme.letter_size * 0.5,
                         None,
                         ui,
                     )
                     .paint(aesthetics.theme.text, ui, false);
 
+                    if word.misspelled() {
+                        TextHelper::light(
+                            &format!(
+                                "Spelled {}, close enough for lenient spelling",
+                                word.original_word
+                            ),
+                            24.0,
+                            Some(ui.available_width()),
+                            ui,
+                        )
+                        .paint(aesthetics.theme.text, ui, false);
+                    }
+
                     match (word.valid, &word.meanings) {
                         (Some(true), Some(meanings)) if !meanings.is_empty() => TextHelper::light(
                             &if meanings[0].pos.is_empty() {
@@ -1,9 +1,9 @@
 use std::path::PathBuf;
 
-use epaint::{emath::Align2, pos2, vec2, Rect, Vec2};
+use epaint::{emath::Align2, pos2, vec2, Rect, Stroke, Vec2};
 use instant::Duration;
 use truncate_core::{
-    board::{Board, Coordinate, Direction, Square},
+    board::{Board, Coordinate, Square},
     messages::PlayerMessage,
     player::Hand,
     reporting::BoardChange,
@@ -13,10 +13,15 @@ use eframe::egui::{self, Id, Order, Sense};
 use hashbrown::HashMap;
 
 use crate::utils::{
-    depot::TruncateDepot,
+    board_view::BoardView,
+    control_devices::place_from_hand,
+    depot::{GameplayDepot, InteractionDepot, TimingDepot, TruncateDepot},
     mapper::{MappedBoard, MappedTile, MappedTileVariant, MappedTiles},
 };
 
+/// How long a marker stays on a square after someone points at it
+const POINTER_DURATION: Duration = Duration::from_secs(2);
+
 pub struct BoardUI<'a> {
     board: &'a Board,
     interactive: bool,
@@ -53,8 +58,12 @@ impl<'a> BoardUI<'a> {
         let mut tile_is_hovered = None;
         let mut drag_underway = false;
 
-        // TODO: Do something better for this
-        let invert = depot.gameplay.player_number == 0;
+        let view = BoardView::for_player(
+            self.board,
+            depot.gameplay.player_number as usize,
+            depot.ui_state.view_rotation,
+        );
+        mapped_board.set_view_rotation(depot.ui_state.view_rotation);
 
         let game_area = ui.available_rect_before_wrap();
         ui.set_clip_rect(game_area);
@@ -147,6 +156,7 @@ impl<'a> BoardUI<'a> {
                                             aesthetics,
                                             interactions,
                                             gameplay,
+                                            timing,
                                             ..
                                         } = depot;
 
@@ -168,7 +178,14 @@ impl<'a> BoardUI<'a> {
                                                 });
                                             }
 
-                                            if square_response.clicked() {
+                                            if square_response.clicked() && interactions.pointing {
+                                                msg = Some(point_at(
+                                                    interactions,
+                                                    gameplay,
+                                                    timing,
+                                                    coord,
+                                                ));
+                                            } else if square_response.clicked() {
                                                 if interactions.selected_tile_on_board.is_some() {
                                                     interactions.selected_tile_on_board = None;
                                                 }
@@ -176,13 +193,26 @@ impl<'a> BoardUI<'a> {
                                                 if let Some((tile, _)) =
                                                     interactions.selected_tile_in_hand
                                                 {
-                                                    msg = Some(PlayerMessage::Place(
-                                                        coord,
-                                                        *hand.get(tile).unwrap(),
-                                                    ));
+                                                    if depot.ui_state.confirms_placement()
+                                                        && interactions.pending_square()
+                                                            != Some(coord)
+                                                    {
+                                                        // Mis-taps are costly on small screens, so the first tap
+                                                        // only previews the tile until the square is tapped again
+                                                        interactions.pending_placement =
+                                                            Some((tile, coord));
+                                                    } else {
+                                                        msg = place_from_hand(
+                                                            interactions,
+                                                            coord,
+                                                            *hand.get(tile).unwrap(),
+                                                        );
 
-                                                    interactions.selected_tile_in_hand = None;
-                                                    interactions.selected_square_on_board = None;
+                                                        interactions.selected_tile_in_hand = None;
+                                                        interactions.selected_square_on_board =
+                                                            None;
+                                                        interactions.pending_placement = None;
+                                                    }
                                                 } else if !depot.ui_state.is_touch {
                                                     if interactions
                                                         .selected_square_on_board
@@ -199,10 +229,11 @@ impl<'a> BoardUI<'a> {
 
                                             if let Some(tile) = interactions.released_tile {
                                                 if tile.1 == coord {
-                                                    msg = Some(PlayerMessage::Place(
+                                                    msg = place_from_hand(
+                                                        interactions,
                                                         coord,
                                                         *hand.get(tile.0).unwrap(),
-                                                    ));
+                                                    );
                                                     interactions.selected_tile_in_hand = None;
                                                     interactions.selected_tile_on_board = None;
                                                     interactions.released_tile = None;
@@ -240,7 +271,14 @@ impl<'a> BoardUI<'a> {
                                                     o.cursor_icon = egui::CursorIcon::PointingHand
                                                 });
                                             }
-                                            if tile_response.clicked() {
+                                            if tile_response.clicked() && interactions.pointing {
+                                                msg = Some(point_at(
+                                                    interactions,
+                                                    gameplay,
+                                                    timing,
+                                                    coord,
+                                                ));
+                                            } else if tile_response.clicked() {
                                                 if matches!(
                                                     interactions.selected_tile_on_board,
                                                     Some((c, _)) if c == coord
@@ -309,13 +347,7 @@ impl<'a> BoardUI<'a> {
                                                             aesthetics.theme.ring_selected_hovered,
                                                         ),
                                                         highlight: None,
-                                                        orientation: if *square_player
-                                                            == gameplay.player_number as usize
-                                                        {
-                                                            Direction::North
-                                                        } else {
-                                                            Direction::South
-                                                        },
+                                                        orientation: view.facing(*square_player),
                                                     }],
                                                     aesthetics,
                                                     Some(interactions),
@@ -429,14 +461,14 @@ impl<'a> BoardUI<'a> {
                             };
 
                         for (rownum, row) in rows {
-                            if invert {
+                            if view.is_rotated() {
                                 render_row(rownum, Box::new(row.iter().enumerate().rev()));
                             } else {
                                 render_row(rownum, Box::new(row.iter().enumerate()));
                             }
                         }
                     };
-                    if invert {
+                    if view.is_rotated() {
                         render(Box::new(self.board.squares.iter().enumerate().rev()));
                     } else {
                         render(Box::new(self.board.squares.iter().enumerate()));
@@ -473,6 +505,9 @@ impl<'a> BoardUI<'a> {
             depot.interactions.dragging_tile_on_board = None;
         }
 
+        self.render_pointers(ui, board_texture_dest, mapped_board.buffer(), view, depot);
+        self.render_aging(ui, board_texture_dest, mapped_board.buffer(), view, depot);
+
         if !self.interactive {
             return None;
         }
@@ -619,4 +654,129 @@ impl<'a> BoardUI<'a> {
 
         msg
     }
+
+    /// Paints a fading ring over any squares that players have recently pointed at
+    fn render_pointers(
+        &self,
+        ui: &mut egui::Ui,
+        board_texture_dest: Rect,
+        buffer: usize,
+        view: BoardView,
+        depot: &mut TruncateDepot,
+    ) {
+        let now = depot.timing.current_time;
+        depot
+            .interactions
+            .pointed_squares
+            .retain(|(_, _, shown_at)| now.saturating_sub(*shown_at) < POINTER_DURATION);
+
+        if depot.interactions.pointed_squares.is_empty() {
+            return;
+        }
+
+        let grid_size = depot.aesthetics.theme.grid_size;
+        let origin = board_texture_dest.min + Vec2::splat(grid_size * buffer as f32);
+
+        let pointer_area = egui::Area::new(egui::Id::new("board_pointer_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(Align2::LEFT_TOP, vec2(0.0, 0.0))
+            .interactable(false);
+        pointer_area.show(ui.ctx(), |ui| {
+            for (coord, player, shown_at) in &depot.interactions.pointed_squares {
+                let screen = view.to_screen(*coord);
+                let center =
+                    origin + vec2(screen.x as f32 + 0.5, screen.y as f32 + 0.5) * grid_size;
+
+                let progress =
+                    now.saturating_sub(*shown_at).as_secs_f32() / POINTER_DURATION.as_secs_f32();
+                let color = depot
+                    .aesthetics
+                    .player_colors
+                    .get(*player as usize)
+                    .copied()
+                    .unwrap_or(depot.aesthetics.theme.text)
+                    .gamma_multiply(1.0 - progress);
+
+                ui.painter().circle_stroke(
+                    center,
+                    grid_size * (0.3 + 0.4 * progress),
+                    Stroke::new(grid_size * 0.1, color),
+                );
+            }
+        });
+
+        // Keep painting until the markers have faded out
+        ui.ctx().request_repaint();
+    }
+
+    /// Marks tiles in words that have weakened from sitting unchanged, with a dot per letter of strength lost
+    fn render_aging(
+        &self,
+        ui: &mut egui::Ui,
+        board_texture_dest: Rect,
+        buffer: usize,
+        view: BoardView,
+        depot: &TruncateDepot,
+    ) {
+        let Some(aging) = &self.board.aging else {
+            return;
+        };
+
+        let grid_size = depot.aesthetics.theme.grid_size;
+        let origin = board_texture_dest.min + Vec2::splat(grid_size * buffer as f32);
+
+        let aging_area = egui::Area::new(egui::Id::new("board_aging_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(Align2::LEFT_TOP, vec2(0.0, 0.0))
+            .interactable(false);
+        aging_area.show(ui.ctx(), |ui| {
+            for (y, row) in self.board.squares.iter().enumerate() {
+                for (x, square) in row.iter().enumerate() {
+                    if !matches!(square, Square::Occupied { foggy: false, .. }) {
+                        continue;
+                    }
+                    let coord = Coordinate::new(x, y);
+                    let weakness = self
+                        .board
+                        .get_words(coord)
+                        .iter()
+                        .map(|word| aging.weakness(word))
+                        .max()
+                        .unwrap_or_default();
+                    if weakness == 0 {
+                        continue;
+                    }
+
+                    let screen = view.to_screen(coord);
+                    let corner =
+                        origin + vec2(screen.x as f32 + 0.2, screen.y as f32 + 0.2) * grid_size;
+                    for dot in 0..weakness.min(3) {
+                        ui.painter().circle_filled(
+                            corner + vec2(dot as f32 * grid_size * 0.15, 0.0),
+                            grid_size * 0.05,
+                            depot.aesthetics.theme.word_invalid,
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Points at a square on the board, showing it locally as well as for the other players
+fn point_at(
+    interactions: &mut InteractionDepot,
+    gameplay: &GameplayDepot,
+    timing: &TimingDepot,
+    coord: Coordinate,
+) -> PlayerMessage {
+    interactions.pointing = false;
+    interactions.selected_square_on_board = None;
+    interactions.selected_tile_on_board = None;
+    interactions
+        .pointed_squares
+        .push((coord, gameplay.player_number, timing.current_time));
+    PlayerMessage::Point(coord)
 }
@@ -307,11 +307,8 @@ impl<'a> EditorUI<'a> {
             });
 
             if let Some((coord, new_state)) = modify_pos {
-                // Not bounds-checking values as they came from the above loop over this very state.
-                self.board.squares[coord.y][coord.x] = new_state;
-
                 // TODO: Put board mirroring behind a flag
-                {
+                let mirrored = {
                     let recip = self.board.reciprocal_coordinate(coord);
 
                     // TODO: Player mirroring won't work for >2 players
@@ -349,10 +346,15 @@ impl<'a> EditorUI<'a> {
                         }
                     };
 
-                    self.board.squares[recip.y][recip.x] = mirrored_state;
-                }
+                    (recip, mirrored_state)
+                };
 
-                edited = true;
+                // Positions came from the above loop over this very board, so are always in bounds
+                let changes = self
+                    .board
+                    .apply_batch(vec![(coord, new_state), mirrored])
+                    .expect("Edited squares should be on the board");
+                edited = !changes.is_empty();
             }
         });
 
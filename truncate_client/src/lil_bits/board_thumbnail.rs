@@ -0,0 +1,59 @@
+use eframe::egui::{self, Sense};
+use epaint::{vec2, Color32, Rect, Vec2};
+use truncate_core::board::{Board, Square};
+
+use crate::utils::{Darken, Theme};
+
+/// A flat, untextured drawing of a board, small enough to show many of them side by side
+pub struct BoardThumbnailUI<'a> {
+    board: &'a Board,
+    player_colors: &'a [Color32],
+}
+
+impl<'a> BoardThumbnailUI<'a> {
+    pub fn new(board: &'a Board, player_colors: &'a [Color32]) -> Self {
+        Self {
+            board,
+            player_colors,
+        }
+    }
+
+    /// Fits the board within a square of the given size, keeping its squares square
+    pub fn render(self, size: f32, ui: &mut egui::Ui, theme: &Theme) -> egui::Response {
+        let (rect, response) = ui.allocate_exact_size(Vec2::splat(size), Sense::click());
+        if !ui.is_rect_visible(rect) {
+            return response;
+        }
+
+        let width = self.board.width().max(1) as f32;
+        let height = self.board.height().max(1) as f32;
+        let cell = size / width.max(height);
+        let origin = rect.center() - vec2(cell * width, cell * height) / 2.0;
+
+        let painter = ui.painter();
+        painter.rect_filled(rect, theme.rounding, theme.water);
+
+        let player_color = |player: usize| {
+            self.player_colors
+                .get(player)
+                .copied()
+                .unwrap_or(theme.text)
+        };
+
+        for (y, row) in self.board.squares.iter().enumerate() {
+            for (x, square) in row.iter().enumerate() {
+                let color = match square {
+                    Square::Water { .. } | Square::Fog {} => continue,
+                    Square::Land { .. } | Square::Obelisk { .. } => theme.grass,
+                    Square::Town { player, .. } => player_color(*player),
+                    Square::Artifact { player, .. } => player_color(*player).darken(),
+                    Square::Occupied { player, .. } => player_color(*player),
+                };
+                let min = origin + vec2(x as f32 * cell, y as f32 * cell);
+                painter.rect_filled(Rect::from_min_size(min, Vec2::splat(cell)), 0.0, color);
+            }
+        }
+
+        response
+    }
+}
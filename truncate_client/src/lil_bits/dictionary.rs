@@ -6,9 +6,12 @@ use epaint::{
 
 use std::{collections::HashMap, f32};
 use truncate_core::{
+    board::Board,
+    finder::{fits_on_board, suggest_words},
     judge::Outcome,
     messages::PlayerMessage,
     reporting::{BattleReport, BattleWord, WordMeaning},
+    rules::Truncation,
 };
 
 use crate::utils::{
@@ -21,12 +24,20 @@ use crate::utils::{
 
 use super::BattleUI;
 
+/// How many words the finder lists at once
+const SUGGESTION_LIMIT: usize = 12;
+
 #[derive(Clone)]
 pub struct DictionaryUI {
     current_word: String,
     is_valid: bool,
     focus_in_n_frames: usize,
     definitions: HashMap<String, Option<Vec<WordMeaning>>>,
+    /// Whether the input holds letters to find words from, rather than a word to look up
+    finding_words: bool,
+    /// Whether found words need to have room on the board
+    only_placeable: bool,
+    suggestions: Vec<String>,
 }
 
 impl DictionaryUI {
@@ -36,6 +47,9 @@ impl DictionaryUI {
             is_valid: false,
             focus_in_n_frames: if initial_focus { 2 } else { 0 },
             definitions: HashMap::new(),
+            finding_words: false,
+            only_placeable: false,
+            suggestions: vec![],
         }
     }
 
@@ -45,10 +59,29 @@ impl DictionaryUI {
         }
     }
 
+    fn find_words(&mut self, board: &Board, player: usize) {
+        let dict_lock = get_main_dict();
+        let dict = dict_lock.as_ref().unwrap();
+
+        let mut fits_by_length = HashMap::new();
+        self.suggestions = suggest_words(&self.current_word, dict, usize::MAX)
+            .into_iter()
+            .filter(|word| {
+                !self.only_placeable
+                    || *fits_by_length.entry(word.len()).or_insert_with(|| {
+                        fits_on_board(word.len(), board, player, &Truncation::Root)
+                    })
+            })
+            .take(SUGGESTION_LIMIT)
+            .collect();
+        self.is_valid = !self.suggestions.is_empty();
+    }
+
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
         depot: &mut TruncateDepot,
+        board: &Board,
     ) -> Option<PlayerMessage> {
         let mut msg = None;
 
@@ -152,7 +185,57 @@ impl DictionaryUI {
             depot.ui_state.dictionary_focused = false;
         }
 
-        if input.response.changed() {
+        let player = depot.gameplay.player_number as usize;
+        if depot.ui_state.word_finder {
+            ui.add_space(10.0);
+
+            let mode = if self.finding_words {
+                "CHECK A WORD"
+            } else {
+                "FIND WORDS FROM LETTERS"
+            };
+            if TextHelper::heavy(mode, 12.0, None, ui)
+                .centered_button(
+                    depot.aesthetics.theme.button_secondary,
+                    depot.aesthetics.theme.text,
+                    &depot.aesthetics.map_texture,
+                    ui,
+                )
+                .clicked()
+            {
+                self.finding_words = !self.finding_words;
+                self.current_word.clear();
+                self.suggestions.clear();
+                self.is_valid = false;
+            }
+
+            if self.finding_words {
+                ui.add_space(10.0);
+
+                let placement = if self.only_placeable {
+                    "SHOWING WORDS THAT FIT THE BOARD"
+                } else {
+                    "SHOWING ALL WORDS"
+                };
+                if TextHelper::heavy(placement, 12.0, None, ui)
+                    .centered_button(
+                        depot.aesthetics.theme.button_secondary,
+                        depot.aesthetics.theme.text,
+                        &depot.aesthetics.map_texture,
+                        ui,
+                    )
+                    .clicked()
+                {
+                    self.only_placeable = !self.only_placeable;
+                    self.find_words(board, player);
+                }
+            }
+        }
+
+        if input.response.changed() && self.finding_words {
+            self.current_word = self.current_word.to_ascii_lowercase();
+            self.find_words(board, player);
+        } else if input.response.changed() {
             self.current_word = self.current_word.to_ascii_lowercase();
 
             let dict_lock = get_main_dict();
@@ -167,7 +250,22 @@ impl DictionaryUI {
             }
         }
 
-        if !self.current_word.is_empty() {
+        if self.finding_words && !self.current_word.is_empty() {
+            depot.ui_state.dictionary_showing_definition = true;
+
+            ui.add_space(20.0);
+            if self.suggestions.is_empty() {
+                TextHelper::light("No words found", 16.0, None, ui).paint(Color32::WHITE, ui, true);
+            }
+            for word in &self.suggestions {
+                TextHelper::heavy(&word.to_uppercase(), 16.0, None, ui).paint(
+                    depot.aesthetics.theme.word_valid.lighten(),
+                    ui,
+                    true,
+                );
+                ui.add_space(6.0);
+            }
+        } else if !self.current_word.is_empty() {
             depot.ui_state.dictionary_showing_definition = true;
 
             let meanings = if self.is_valid {
@@ -198,6 +296,7 @@ impl DictionaryUI {
                     valid: Some(self.is_valid),
                 }],
                 outcome: Outcome::DefenderWins,
+                amendment: None,
             };
 
             let desired_battle_width = ui.available_width().min(550.0);
@@ -217,7 +316,12 @@ impl DictionaryUI {
             depot.ui_state.dictionary_showing_definition = false;
 
             if !depot.ui_state.dictionary_focused && self.focus_in_n_frames == 0 {
-                let text = TextHelper::heavy("Search", 20.0, None, ui);
+                let placeholder = if self.finding_words {
+                    "Your letters"
+                } else {
+                    "Search"
+                };
+                let text = TextHelper::heavy(placeholder, 20.0, None, ui);
                 text.paint_within(
                     input.response.rect,
                     Align2::CENTER_CENTER,
@@ -0,0 +1,227 @@
+use eframe::egui::{self, Margin, Order};
+use epaint::{emath::Align2, hex_color, vec2, Color32, TextureHandle};
+
+use truncate_core::board::{Board, BoardValidator, BoardWarning};
+
+use crate::{
+    regions::lobby::BoardEditingMode,
+    utils::{
+        includes::{self, EditorGuide, EditorGuideStep, EditorTask, ScenarioStep},
+        text::TextHelper,
+        Diaphanize, Lighten, Theme,
+    },
+};
+
+const GUIDE_WIDTH: f32 = 260.0;
+
+/// Something the lobby needs to do on behalf of the guide
+pub enum EditorGuideAction {
+    /// Swap the board out for a generated one to build on
+    Generate,
+    Close,
+}
+
+/// Walks someone through their first board edit, one part of the editor at a time
+#[derive(Clone)]
+pub struct EditorGuideState {
+    guide: EditorGuide,
+    step: usize,
+}
+
+impl EditorGuideState {
+    pub fn new() -> Self {
+        Self {
+            guide: includes::editor_guide(),
+            step: 0,
+        }
+    }
+
+    /// Whether this player has already finished or skipped the guide
+    pub fn completed() -> bool {
+        #[allow(unused_mut)]
+        let mut completed = false;
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            completed = local_storage
+                .get_item("truncate_editor_guide_done")
+                .unwrap()
+                .is_some();
+        }
+
+        completed
+    }
+
+    fn mark_completed() {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            local_storage
+                .set_item("truncate_editor_guide_done", "true")
+                .unwrap();
+        }
+    }
+
+    /// The problems with the board that a task asks the player to fix
+    fn task_warnings(task: EditorTask, board: &Board) -> Vec<BoardWarning> {
+        BoardValidator::check(board, 2)
+            .into_iter()
+            .filter(|warning| match task {
+                EditorTask::Land => matches!(
+                    warning,
+                    BoardWarning::NotEnoughLand { .. } | BoardWarning::SplitLand { .. }
+                ),
+                EditorTask::Towns => matches!(warning, BoardWarning::MissingTown { .. }),
+                EditorTask::Docks => matches!(
+                    warning,
+                    BoardWarning::MissingDock { .. } | BoardWarning::StrandedDock { .. }
+                ),
+            })
+            .collect()
+    }
+
+    /// Moves to the next step, picking the editing tool its task is about
+    fn advance(&mut self, editing_mode: &mut BoardEditingMode) {
+        self.step += 1;
+        if let Some(EditorGuideStep::Task { task, .. }) = self.guide.steps.get(self.step) {
+            *editing_mode = match task {
+                EditorTask::Land => BoardEditingMode::Land,
+                EditorTask::Towns => BoardEditingMode::Town(0),
+                EditorTask::Docks => BoardEditingMode::Artifact(0),
+            };
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        board: &Board,
+        editing_mode: &mut BoardEditingMode,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+    ) -> Option<EditorGuideAction> {
+        let Some(step) = self.guide.steps.get(self.step).cloned() else {
+            return Some(EditorGuideAction::Close);
+        };
+        let mut action = None;
+        let mut next = false;
+
+        let area = egui::Area::new(egui::Id::new("editor_guide_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(Align2::LEFT_TOP, vec2(8.0, 8.0));
+
+        area.show(ui.ctx(), |ui| {
+            egui::Frame::none()
+                .fill(hex_color!("#111111dd"))
+                .inner_margin(Margin::same(12.0))
+                .rounding(4.0)
+                .show(ui, |ui| {
+                    ui.set_width(GUIDE_WIDTH);
+                    ui.spacing_mut().item_spacing = vec2(6.0, 6.0);
+
+                    match &step {
+                        EditorGuideStep::Task { task, description } => {
+                            TextHelper::light(description, 14.0, Some(GUIDE_WIDTH), ui).paint(
+                                Color32::WHITE,
+                                ui,
+                                false,
+                            );
+
+                            let warnings = Self::task_warnings(*task, board);
+                            if warnings.is_empty() {
+                                TextHelper::heavy("LOOKS GOOD!", 10.0, None, ui).paint(
+                                    theme.word_valid,
+                                    ui,
+                                    false,
+                                );
+                            }
+                            for warning in &warnings {
+                                TextHelper::light(
+                                    &warning.to_string(),
+                                    12.0,
+                                    Some(GUIDE_WIDTH),
+                                    ui,
+                                )
+                                .paint(
+                                    theme.word_invalid,
+                                    ui,
+                                    false,
+                                );
+                            }
+
+                            let next_color = if warnings.is_empty() {
+                                theme.button_primary
+                            } else {
+                                theme.text.lighten().lighten()
+                            };
+                            if TextHelper::heavy("NEXT", 10.0, None, ui)
+                                .button(next_color, theme.text, map_texture, ui)
+                                .clicked()
+                                && warnings.is_empty()
+                            {
+                                next = true;
+                            }
+                        }
+                        EditorGuideStep::Scenario(ScenarioStep::Dialog { message }) => {
+                            TextHelper::light(message, 14.0, Some(GUIDE_WIDTH), ui).paint(
+                                Color32::WHITE,
+                                ui,
+                                false,
+                            );
+                            if TextHelper::heavy("NEXT", 10.0, None, ui)
+                                .button(theme.button_primary, theme.text, map_texture, ui)
+                                .clicked()
+                            {
+                                next = true;
+                            }
+                        }
+                        EditorGuideStep::Scenario(ScenarioStep::EndAction { end_message }) => {
+                            TextHelper::light(end_message, 14.0, Some(GUIDE_WIDTH), ui).paint(
+                                Color32::WHITE,
+                                ui,
+                                false,
+                            );
+                            if TextHelper::heavy("DONE", 10.0, None, ui)
+                                .button(theme.button_primary, theme.text, map_texture, ui)
+                                .clicked()
+                            {
+                                Self::mark_completed();
+                                action = Some(EditorGuideAction::Close);
+                            }
+                            return;
+                        }
+                        EditorGuideStep::Scenario(_) => {
+                            // Moves only make sense in the tutorials, so skip past any here
+                            next = true;
+                            return;
+                        }
+                    }
+
+                    ui.add_space(12.0);
+
+                    if TextHelper::heavy("GENERATE A STARTING POINT", 10.0, None, ui)
+                        .button(theme.button_secondary, theme.text, map_texture, ui)
+                        .clicked()
+                    {
+                        action = Some(EditorGuideAction::Generate);
+                    }
+
+                    if TextHelper::heavy("SKIP GUIDE", 10.0, None, ui)
+                        .button(Color32::WHITE.diaphanize(), theme.text, map_texture, ui)
+                        .clicked()
+                    {
+                        Self::mark_completed();
+                        action = Some(EditorGuideAction::Close);
+                    }
+                });
+        });
+
+        if next {
+            self.advance(editing_mode);
+        }
+
+        action
+    }
+}
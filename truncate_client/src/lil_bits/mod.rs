@@ -2,22 +2,28 @@ mod battle;
 mod board;
 mod board_editor;
 mod board_editor_square;
+mod board_thumbnail;
 mod changes_splash;
 mod dictionary;
+mod editor_guide;
 mod hand;
 mod hand_square;
 pub mod result_modal;
 mod splash;
+mod time_usage;
 mod timer;
 
 pub use battle::BattleUI;
 pub use board::BoardUI;
 pub use board_editor::EditorUI;
 pub use board_editor_square::EditorSquareUI;
+pub use board_thumbnail::BoardThumbnailUI;
 pub use changes_splash::ChangelogSplashUI;
 pub use dictionary::DictionaryUI;
+pub use editor_guide::{EditorGuideAction, EditorGuideState};
 pub use hand::HandUI;
 pub use hand_square::HandSquareUI;
 pub use result_modal::ResultModalUI;
 pub use splash::SplashUI;
+pub use time_usage::TimeUsageUI;
 pub use timer::TimerUI;
@@ -19,6 +19,7 @@ pub struct DailyActions {
     replay_link: String,
     replay_copied_at: Option<Duration>,
     share_copied_at: Option<Duration>,
+    export_requested_at: Option<Duration>,
     won_today: bool,
     won_yesterday: bool,
     is_sharing: bool,
@@ -101,6 +102,7 @@ impl DailyActions {
             ),
             replay_copied_at: None,
             share_copied_at: None,
+            export_requested_at: None,
             won_today: win_history(0),
             won_yesterday: win_history(1),
             is_sharing: false,
@@ -122,6 +124,13 @@ impl DailyActions {
         {
             self.replay_copied_at = None;
         }
+
+        if self
+            .export_requested_at
+            .is_some_and(|s| depot.timing.current_time - s > Duration::from_secs(2))
+        {
+            self.export_requested_at = None;
+        }
     }
 
     pub fn render_shares(
@@ -160,8 +169,16 @@ impl DailyActions {
                 };
                 let replay_button_text = TextHelper::heavy(replay_text, 12.0, None, ui);
 
+                // The archive is copied once the server sends it back
+                let export_text = if self.export_requested_at.is_some() {
+                    "COPYING GAMES..."
+                } else {
+                    "EXPORT ALL GAMES"
+                };
+                let export_button_text = TextHelper::heavy(export_text, 12.0, None, ui);
+
                 let button_height = share_button_text.get_button_height(ui);
-                let block_height = button_height * 2.5;
+                let block_height = button_height * 4.0;
 
                 ui.add_space((ui.available_height() - block_height) / 2.0);
 
@@ -231,6 +248,20 @@ impl DailyActions {
 
                     self.replay_copied_at = Some(depot.timing.current_time);
                 }
+
+                ui.add_space(button_height * 0.5);
+
+                let export_button = export_button_text.centered_button(
+                    theme.button_primary,
+                    theme.text,
+                    map_texture,
+                    ui,
+                );
+
+                if self.export_requested_at.is_none() && export_button.clicked() {
+                    msg = Some(ResultModalAction::ExportGames);
+                    self.export_requested_at = Some(depot.timing.current_time);
+                }
             });
         });
 
@@ -0,0 +1,110 @@
+use eframe::egui;
+use epaint::{emath::Align2, hex_color, vec2, Color32, Mesh, Pos2, Rect, Shape, Stroke};
+use truncate_core::mastery::LetterMastery;
+
+use crate::utils::text::TextHelper;
+
+/// Most letters to chart at once, taken from both ends of the player's rankings
+const CHART_LETTERS: usize = 8;
+/// A radar chart needs a few spokes to look like anything
+const MIN_CHART_LETTERS: usize = 3;
+
+/// Radar chart of the player's win rate with their strongest and weakest letters
+#[derive(Clone)]
+pub struct LetterMasteryChart {
+    /// Letters around the chart, clockwise from the top, with their win rates
+    spokes: Vec<(char, f32)>,
+    summary: String,
+}
+
+impl LetterMasteryChart {
+    pub fn new(mastery: &LetterMastery) -> Option<Self> {
+        let ranked = mastery.ranked();
+        if ranked.len() < MIN_CHART_LETTERS {
+            return None;
+        }
+
+        let strongest = ranked.first()?.0;
+        let weakest = ranked.last()?.0;
+
+        let mut spokes: Vec<_> = if ranked.len() <= CHART_LETTERS {
+            ranked
+        } else {
+            let half = CHART_LETTERS / 2;
+            ranked[..half]
+                .iter()
+                .chain(ranked[ranked.len() - half..].iter())
+                .copied()
+                .collect()
+        };
+        spokes.sort_by_key(|(letter, _)| *letter);
+
+        Some(Self {
+            spokes,
+            summary: format!("Strongest with {strongest}, practice {weakest}"),
+        })
+    }
+
+    pub fn render(&self, ui: &mut egui::Ui, chart_rect: Rect) {
+        let fz = 12.0;
+
+        let summary = TextHelper::light(&self.summary, fz, None, ui);
+        let summary_height = summary.mesh_size().y;
+        summary.paint_within(chart_rect, Align2::CENTER_TOP, Color32::WHITE, ui);
+
+        let mut web_rect = chart_rect.clone();
+        *web_rect.top_mut() += summary_height + 6.0;
+        let center = web_rect.center();
+        // Leave room around the web for the letter labels
+        let radius = (web_rect.width().min(web_rect.height()) / 2.0 - fz).max(0.0);
+
+        let spoke_count = self.spokes.len();
+        let point = |spoke: usize, reach: f32| -> Pos2 {
+            let angle = std::f32::consts::TAU * spoke as f32 / spoke_count as f32
+                - std::f32::consts::FRAC_PI_2;
+            center + vec2(angle.cos(), angle.sin()) * radius * reach
+        };
+
+        let web_stroke = Stroke::new(1.0, hex_color!("#FFFFFF44"));
+        for reach in [0.5, 1.0] {
+            let ring = (0..spoke_count).map(|s| point(s, reach)).collect();
+            ui.painter().add(Shape::closed_line(ring, web_stroke));
+        }
+
+        let mut mesh = Mesh::default();
+        let fill = hex_color!("#6DAF6B88");
+        mesh.colored_vertex(center, fill);
+        for (spoke, (letter, win_rate)) in self.spokes.iter().enumerate() {
+            ui.painter()
+                .line_segment([center, point(spoke, 1.0)], web_stroke);
+
+            mesh.colored_vertex(point(spoke, *win_rate), fill);
+            let next = (spoke + 1) % spoke_count;
+            mesh.add_triangle(0, spoke as u32 + 1, next as u32 + 1);
+
+            let label_pos = point(spoke, 1.0) + (point(spoke, 1.0) - center).normalized() * fz;
+            let label = letter.to_string();
+            TextHelper::heavy(&label, fz, None, ui).paint_within(
+                Rect::from_center_size(label_pos, vec2(fz * 2.0, fz * 2.0)),
+                Align2::CENTER_CENTER,
+                Color32::WHITE,
+                ui,
+            );
+        }
+        ui.painter().add(Shape::mesh(mesh));
+
+        let outline = self
+            .spokes
+            .iter()
+            .enumerate()
+            .map(|(spoke, (_, win_rate))| point(spoke, *win_rate))
+            .collect();
+        ui.painter().add(Shape::closed_line(
+            outline,
+            Stroke::new(2.0, hex_color!("#6DAF6B")),
+        ));
+
+        // Mark the centre so an empty chart still reads as a chart
+        ui.painter().circle_filled(center, 2.0, Color32::WHITE);
+    }
+}
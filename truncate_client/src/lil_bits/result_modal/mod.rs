@@ -1,10 +1,11 @@
 use epaint::{emath::Align2, hex_color, vec2, Color32, TextureHandle};
 use instant::Duration;
 use interpolation::Ease;
-use truncate_core::{game::Game, messages::DailyStats};
+use truncate_core::{game::Game, messages::DailyStats, npc::key_moments::KeyMoment};
 
 mod daily_actions;
 mod graph;
+mod mastery;
 mod msg_mock;
 
 use eframe::egui::{self, Align, CursorIcon, Id, Layout, Order, Sense};
@@ -19,7 +20,10 @@ use crate::{
     },
 };
 
-use self::{daily_actions::DailyActions, graph::DailySplashGraph, msg_mock::ShareMessageMock};
+use self::{
+    daily_actions::DailyActions, graph::DailySplashGraph, mastery::LetterMasteryChart,
+    msg_mock::ShareMessageMock,
+};
 
 /*
 
@@ -32,6 +36,8 @@ TODOs for the daily splash screen:
 pub struct ResultModalDaily {
     pub stats: DailyStats,
     graph: DailySplashGraph,
+    /// Only shown once the player has fought enough online battles to chart
+    mastery_chart: Option<LetterMasteryChart>,
     daily_actions: DailyActions,
     streak_length: usize,
     win_rate: f32,
@@ -40,8 +46,12 @@ pub struct ResultModalDaily {
 #[derive(Clone)]
 pub struct ResultModalUnique {
     won: bool,
+    /// Whether the game ended without a winner, rather than being lost
+    drawn: bool,
     msg_mock: ShareMessageMock,
     share_copied_at: Option<Duration>,
+    /// The turning points of the game, each opening the replay at its move
+    key_moments: Vec<KeyMoment>,
 }
 
 #[derive(Clone)]
@@ -75,19 +85,7 @@ impl ResultModalUI {
         best_game: Option<&Game>,
         day: u32,
     ) -> Self {
-        let streak_length = stats
-            .days
-            .values()
-            .rev()
-            .enumerate()
-            .find_map(|(streak_length, day)| {
-                if day.attempts.iter().any(|a| a.won) {
-                    None
-                } else {
-                    Some(streak_length)
-                }
-            })
-            .unwrap_or_else(|| stats.days.len());
+        let streak_length = stats.streak_length();
 
         let win_count = stats
             .days
@@ -103,6 +101,7 @@ impl ResultModalUI {
         ResultModalUI::seed_animations(ui);
 
         let graph = DailySplashGraph::new(ui, &stats, depot.timing.current_time);
+        let mastery_chart = LetterMasteryChart::new(&stats.letter_mastery);
         let daily_actions = DailyActions::new(
             best_game.unwrap_or(game),
             player_move_count,
@@ -115,6 +114,7 @@ impl ResultModalUI {
             contents: ResultModalVariant::Daily(ResultModalDaily {
                 stats,
                 graph,
+                mastery_chart,
                 daily_actions,
                 streak_length,
                 win_rate: win_count as f32 / attempted_day_count as f32,
@@ -127,14 +127,17 @@ impl ResultModalUI {
         game: &Game,
         depot: &mut TruncateDepot,
         won: bool,
+        key_moments: Vec<KeyMoment>,
     ) -> Self {
         ResultModalUI::seed_animations(ui);
 
         Self {
             contents: ResultModalVariant::Unique(ResultModalUnique {
                 won,
+                drawn: game.winner.is_none() && game.adjudication.is_some(),
                 msg_mock: ShareMessageMock::new_unique(game, &depot),
                 share_copied_at: None,
+                key_moments,
             }),
         }
     }
@@ -197,6 +200,10 @@ pub enum ResultModalAction {
     Resign,
     SharedText,
     SharedReplay,
+    /// Asks the server for an archive of every daily puzzle the player has played
+    ExportGames,
+    /// Opens the replay at the given key moment
+    ViewMoment(KeyMoment),
 }
 
 impl ResultModalUI {
@@ -350,7 +357,11 @@ impl ResultModalUI {
                                 ui,
                             );
                         } else {
-                            let summary_string = "No worries,".to_string();
+                            let summary_string = if u.drawn {
+                                "A draw,".to_string()
+                            } else {
+                                "No worries,".to_string()
+                            };
                             let summary_text =
                                 TextHelper::heavy(&summary_string, 14.0, None, &mut ui);
 
@@ -421,6 +432,19 @@ impl ResultModalUI {
                         Sense::hover(),
                     );
                     daily.graph.render(ui, graph_rect.shrink2(vec2(10.0, 0.0)));
+
+                    if let Some(chart) = &daily.mastery_chart {
+                        let (chart_rect, _) = ui.allocate_exact_size(
+                            vec2(ui.available_width(), ui.available_height() / 3.0),
+                            Sense::hover(),
+                        );
+                        chart.render(ui, chart_rect.shrink(10.0));
+                    }
+
+                    if let Some(accuracy) = daily.stats.prediction_stats.accuracy() {
+                        let line = format!("{}% prediction accuracy", (accuracy * 100.0) as usize);
+                        TextHelper::heavy(&line, 10.0, None, ui).paint(Color32::WHITE, ui, true);
+                    }
                 }
 
                 match &mut self.contents {
@@ -510,6 +534,38 @@ impl ResultModalUI {
                                     unique.share_copied_at = Some(depot.timing.current_time);
                                 }
 
+                                for moment in unique.key_moments.iter().rev() {
+                                    ui.add_space(6.0);
+                                    let card = format!(
+                                        "MOVE {}: {}",
+                                        moment.turn + 1,
+                                        moment.explanation.to_uppercase()
+                                    );
+                                    let text = TextHelper::heavy(
+                                        &card,
+                                        10.0,
+                                        Some(ui.available_width() - 40.0),
+                                        ui,
+                                    );
+                                    let card_button = text.centered_button(
+                                        theme.button_secondary,
+                                        theme.text,
+                                        map_texture,
+                                        ui,
+                                    );
+                                    if card_button.clicked() {
+                                        msg = Some(ResultModalAction::ViewMoment(moment.clone()));
+                                    }
+                                }
+                                if !unique.key_moments.is_empty() {
+                                    ui.add_space(6.0);
+                                    TextHelper::heavy("KEY MOMENTS", 10.0, None, ui).paint(
+                                        Color32::WHITE,
+                                        ui,
+                                        true,
+                                    );
+                                }
+
                                 ui.add_space(ui.available_height() * 0.05);
                                 unique.msg_mock.render(ui, theme, map_texture);
                             },
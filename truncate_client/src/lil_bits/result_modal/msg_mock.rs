@@ -197,6 +197,7 @@ impl ShareMessageMock {
         let won = game.winner;
 
         let player_won = won == Some(player);
+        let drawn = won.is_none() && game.adjudication.is_some();
 
         let plur = |num: u32| if num == 1 { "" } else { "s" };
 
@@ -204,6 +205,9 @@ impl ShareMessageMock {
             if player_won {
                 return format!("Won puzzle");
             }
+            if drawn {
+                return format!("Drew puzzle");
+            }
             return format!("Lost puzzle");
         };
 
@@ -226,6 +230,8 @@ impl ShareMessageMock {
 
         if player_won {
             format!("Truncate Town Puzzle\nWon{counts}\n{share_link}")
+        } else if drawn {
+            format!("Truncate Town Puzzle\nDrew{counts}\n{share_link}")
         } else {
             format!("Truncate Town Puzzle\nLost{counts}\n{share_link}")
         }
@@ -0,0 +1,67 @@
+use eframe::egui::{self, Sense};
+use epaint::{pos2, vec2, Color32, Rect};
+
+use crate::utils::text::TextHelper;
+
+use super::TimerUI;
+
+/// A bar for each move of a game, as tall as the time spent on it,
+/// so players can see where their clock went.
+pub struct TimeUsageUI<'a> {
+    /// The player behind each move, and the seconds they spent on it, in the order played
+    moves: &'a [(usize, u64)],
+    player_colors: &'a [Color32],
+}
+
+impl<'a> TimeUsageUI<'a> {
+    pub fn new(moves: &'a [(usize, u64)], player_colors: &'a [Color32]) -> Self {
+        Self {
+            moves,
+            player_colors,
+        }
+    }
+
+    pub fn render(self, ui: &mut egui::Ui, text_color: Color32) {
+        let Some(longest) = self
+            .moves
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (_, secs))| *secs)
+        else {
+            return;
+        };
+        let (longest_move, (_, longest_secs)) = longest;
+
+        let (graph_rect, _) =
+            ui.allocate_exact_size(vec2(ui.available_width(), 48.0), Sense::hover());
+        let bar_width = graph_rect.width() / self.moves.len() as f32;
+        // A game of instant moves still draws a sliver for each
+        let scale = graph_rect.height() / (*longest_secs).max(1) as f32;
+
+        for (i, (player, secs)) in self.moves.iter().enumerate() {
+            let height = (*secs as f32 * scale).max(1.0);
+            let left = graph_rect.left() + bar_width * i as f32;
+            let bar = Rect::from_min_max(
+                pos2(left, graph_rect.bottom() - height),
+                pos2(left + bar_width, graph_rect.bottom()),
+            )
+            .shrink2(vec2((bar_width * 0.1).min(2.0), 0.0));
+
+            let color = self
+                .player_colors
+                .get(*player)
+                .copied()
+                .unwrap_or(Color32::GRAY);
+            ui.painter().rect_filled(bar, 1.0, color);
+        }
+
+        ui.add_space(5.0);
+        let summary = format!(
+            "Longest think: {} on move {}",
+            TimerUI::human_time(*longest_secs as i64, true),
+            longest_move + 1
+        );
+        TextHelper::light(&summary, 10.0, Some(ui.available_width()), ui)
+            .paint(text_color, ui, true);
+    }
+}
@@ -1,5 +1,5 @@
 use time::Duration;
-use truncate_core::{messages::GamePlayerMessage, reporting::TimeChange};
+use truncate_core::{game::GameOutcome, messages::GamePlayerMessage, reporting::TimeChange};
 
 use eframe::egui::{self, Layout, Response, Sense};
 use epaint::{emath::Align, hex_color, vec2, Color32, Stroke};
@@ -68,12 +68,19 @@ impl<'a> TimerUI<'a> {
         }
     }
 
-    fn human_time(seconds: i64, absolute: bool) -> String {
+    pub fn human_time(seconds: i64, absolute: bool) -> String {
         let abs_secs = seconds.abs();
+        let h_days = abs_secs / (60 * 60 * 24);
+        let h_hours = abs_secs / (60 * 60);
         let h_minutes = abs_secs / 60;
         let h_seconds = abs_secs % 60;
 
-        let mut time_string = if h_minutes > 0 {
+        // Correspondence clocks run for days, where the seconds stop mattering
+        let mut time_string = if h_days > 0 {
+            format!("{h_days}d{}h", h_hours % 24)
+        } else if h_hours > 0 {
+            format!("{h_hours}h{}m", h_minutes % 60)
+        } else if h_minutes > 0 {
             format!("{h_minutes}m{h_seconds}s")
         } else {
             format!("{h_seconds}s")
@@ -95,7 +102,8 @@ impl<'a> TimerUI<'a> {
                 let elapsed = now.checked_sub(next_turn);
                 if let Some(elapsed) = elapsed {
                     if let Some(time) = self.player.time_remaining {
-                        self.time = time - Duration::seconds(elapsed as i64);
+                        let ticked = elapsed.saturating_sub(self.player.clock_delay);
+                        self.time = time - Duration::seconds(ticked as i64);
                         format!("{}", TimerUI::human_time(self.time.whole_seconds(), false))
                     } else {
                         format!("")
@@ -125,6 +133,24 @@ impl<'a> TimerUI<'a> {
     }
 
     fn calculate_byline(&mut self) -> String {
+        match self.depot.gameplay.end_state.as_ref().map(|e| &e.outcome) {
+            Some(GameOutcome::Draw(_)) => {
+                return "Drawn".into();
+            }
+            Some(GameOutcome::Won {
+                winner,
+                tiebreaker: Some(tiebreaker),
+            }) => {
+                let result = if *winner == self.player.index {
+                    "Victorious"
+                } else {
+                    "Defeated"
+                };
+                return format!("{result} on {tiebreaker}");
+            }
+            _ => {}
+        };
+
         match self.depot.gameplay.winner {
             Some(player) if player == self.player.index => {
                 return "Victorious".into();
@@ -143,12 +169,20 @@ impl<'a> TimerUI<'a> {
             Some(next_turn) => {
                 let now = self.depot.timing.current_time.as_secs();
                 let elapsed = now.checked_sub(next_turn);
-                if elapsed.is_some() {
-                    if self.friend {
-                        return format!("Your turn!");
-                    } else {
-                        return format!("Playing");
+                if let Some(elapsed) = elapsed {
+                    let status = if self.friend { "Your turn!" } else { "Playing" };
+                    if let Some(deadline) = self.player.move_deadline {
+                        let left = deadline.saturating_sub(now) as i64;
+                        return format!("{status} - {} to move", TimerUI::human_time(left, true));
                     }
+                    if elapsed < self.player.clock_delay {
+                        let starts_in = (self.player.clock_delay - elapsed) as i64;
+                        return format!(
+                            "{status} - clock starts in {}",
+                            TimerUI::human_time(starts_in, true)
+                        );
+                    }
+                    return status.to_string();
                 } else {
                     let starts_in = (next_turn.saturating_sub(now) as i64) * -1;
                     return format!("Turn starts in {}", TimerUI::human_time(starts_in, true));
@@ -188,7 +222,8 @@ impl<'a> TimerUI<'a> {
             }
 
             // Paint time remaining sector of bar
-            let remaining_time_proportion = (self.time / allotted_time) as f32;
+            // Increments can bank more time than the player started with, so keep the bar in its frame
+            let remaining_time_proportion = ((self.time / allotted_time) as f32).min(1.0);
             if self.right_align {
                 bar.set_left(bar.right() - remaining_time_proportion * inner_timer_rect.width());
                 ui.painter().rect_filled(bar, timer_rounding, timer_color);
@@ -297,7 +332,22 @@ impl<'a> TimerUI<'a> {
             text.paint_at(pos, timer_color, ui);
         }
 
-        let byline_string = self.calculate_byline();
+        let mut byline_string = self.calculate_byline();
+        if let (true, None, Some(turns)) = (
+            self.friend,
+            self.depot.gameplay.winner,
+            self.depot.gameplay.hand_swap_in,
+        ) {
+            let countdown = match turns {
+                1 => "Hands swap after this turn".to_string(),
+                turns => format!("Hands swap in {turns} turns"),
+            };
+            byline_string = if byline_string.is_empty() {
+                countdown
+            } else {
+                format!("{byline_string} - {countdown}")
+            };
+        }
         let text = TextHelper::heavy(&byline_string, font_z_small, None, ui);
         let byline_size = text.size();
         let byline_y_offset = vec2(0.0, 10.0 + name_size.y + 5.0);
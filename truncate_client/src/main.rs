@@ -13,8 +13,11 @@ use futures::channel::{mpsc, oneshot};
 use tokio::runtime::Builder;
 
 use app_outer::OuterApplication;
+use utils::embed::Embed;
 
 fn main() {
+    utils::crash::install_panic_hook();
+
     let connect_addr = std::env::args()
         .nth(1)
         .unwrap_or_else(|| "wss://citadel.truncate.town".into());
@@ -45,7 +48,13 @@ fn main() {
         options,
         Box::new(move |cc| {
             tx_context.send(cc.egui_ctx.clone()).unwrap();
-            Box::new(OuterApplication::new(cc, rx_game, tx_player, None))
+            Box::new(OuterApplication::new(
+                cc,
+                rx_game,
+                tx_player,
+                None,
+                Embed::default(),
+            ))
         }),
     )
     .unwrap();
@@ -6,6 +6,8 @@ use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 
 use truncate_core::messages::{GameMessage, PlayerMessage};
 
+use crate::utils::{crash, macros::current_time};
+
 /*
  TODO: Implement the pending_messages retry flow from web_comms
 */
@@ -34,9 +36,15 @@ pub async fn connect(
             let parsed_msg: GameMessage =
                 serde_json::from_str(msg.unwrap().to_text().expect("Was not valid UTF-8"))
                     .expect("Was not valid JSON");
+            crash::record_received(&parsed_msg);
 
             if matches!(parsed_msg, GameMessage::Ping) {
                 _ = tx_player.clone().send(PlayerMessage::Ping).await;
+                let sent_at = current_time!().as_millis() as u64;
+                _ = tx_player
+                    .clone()
+                    .send(PlayerMessage::LatencyPing(sent_at))
+                    .await;
             }
 
             tx_game
@@ -52,7 +60,10 @@ pub async fn connect(
 
     let player_messages = {
         rx_player
-            .map(|msg| Ok(Message::Text(serde_json::to_string(&msg).unwrap())))
+            .map(|msg| {
+                crash::record_sent(&msg);
+                Ok(Message::Text(serde_json::to_string(&msg).unwrap()))
+            })
             .forward(outgoing)
     };
 
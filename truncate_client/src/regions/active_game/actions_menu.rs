@@ -1,6 +1,6 @@
 use epaint::{emath::Align2, vec2};
 
-use truncate_core::messages::PlayerMessage;
+use truncate_core::{messages::PlayerMessage, reporting::Change};
 
 use eframe::{
     egui::{self, Layout, Order, Sense},
@@ -9,10 +9,21 @@ use eframe::{
 
 use crate::{
     lil_bits::DictionaryUI,
-    utils::{text::TextHelper, urls::back_to_menu},
+    utils::{
+        board_view::ViewRotation,
+        control_devices::InputProfile,
+        tex::atlas::{apply_season, scheduled_season},
+        text::TextHelper,
+        theming::ThemeChoice,
+        urls::back_to_menu,
+    },
 };
 
-use super::{ActiveGame, GameLocation};
+use super::{ActiveGame, GameLocation, HandLayout};
+
+/// Limits on the bot's thinking time that the menu cycles through, for slower devices
+const NPC_BUDGETS_MS: [Option<u64>; 4] = [None, Some(2000), Some(1000), Some(500)];
+const NPC_THREADS: [Option<usize>; 4] = [None, Some(1), Some(2), Some(4)];
 
 impl ActiveGame {
     pub fn render_actions_menu(
@@ -108,6 +119,376 @@ impl ActiveGame {
                         }
                     }
 
+                    ui.add_space(menu_spacing);
+
+                    let text = if self.depot.ui_state.low_power_mode {
+                        TextHelper::heavy("FULL FRAME RATE", 14.0, None, ui)
+                    } else {
+                        TextHelper::heavy("LOW POWER MODE", 14.0, None, ui)
+                    };
+
+                    if text
+                        .button(
+                            self.depot.aesthetics.theme.button_secondary,
+                            self.depot.aesthetics.theme.text,
+                            &self.depot.aesthetics.map_texture,
+                            ui,
+                        )
+                        .clicked()
+                    {
+                        self.depot.ui_state.low_power_mode = !self.depot.ui_state.low_power_mode;
+
+                        #[cfg(target_arch = "wasm32")]
+                        {
+                            let local_storage =
+                                web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                            local_storage
+                                .set_item(
+                                    "truncate_low_power",
+                                    &self.depot.ui_state.low_power_mode.to_string(),
+                                )
+                                .unwrap();
+                        }
+                    }
+
+                    if !self.depot.ui_state.is_mobile && !self.depot.ui_state.hand_hidden {
+                        ui.add_space(menu_spacing);
+
+                        let text = match self.depot.ui_state.hand_layout {
+                            HandLayout::Auto => TextHelper::heavy("HAND: AUTO", 14.0, None, ui),
+                            HandLayout::Stacked => {
+                                TextHelper::heavy("HAND: BELOW BOARD", 14.0, None, ui)
+                            }
+                            HandLayout::Docked => {
+                                TextHelper::heavy("HAND: BESIDE BOARD", 14.0, None, ui)
+                            }
+                        };
+
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.ui_state.hand_layout =
+                                self.depot.ui_state.hand_layout.next();
+
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let local_storage =
+                                    web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                                local_storage
+                                    .set_item(
+                                        "truncate_hand_layout",
+                                        self.depot.ui_state.hand_layout.as_setting(),
+                                    )
+                                    .unwrap();
+                            }
+                        }
+                    }
+
+                    ui.add_space(menu_spacing);
+
+                    let text = match self.depot.ui_state.view_rotation {
+                        ViewRotation::Facing => {
+                            TextHelper::heavy("LETTERS: FACING", 14.0, None, ui)
+                        }
+                        ViewRotation::Upright => {
+                            TextHelper::heavy("LETTERS: UPRIGHT", 14.0, None, ui)
+                        }
+                    };
+
+                    if text
+                        .button(
+                            self.depot.aesthetics.theme.button_secondary,
+                            self.depot.aesthetics.theme.text,
+                            &self.depot.aesthetics.map_texture,
+                            ui,
+                        )
+                        .clicked()
+                    {
+                        self.depot.ui_state.view_rotation =
+                            self.depot.ui_state.view_rotation.next();
+
+                        #[cfg(target_arch = "wasm32")]
+                        {
+                            let local_storage =
+                                web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                            local_storage
+                                .set_item(
+                                    "truncate_view_rotation",
+                                    self.depot.ui_state.view_rotation.as_setting(),
+                                )
+                                .unwrap();
+                        }
+                    }
+
+                    ui.add_space(menu_spacing);
+
+                    let theme_choice = ThemeChoice::current(ui.ctx());
+                    let text = TextHelper::heavy(theme_choice.label(), 14.0, None, ui);
+                    if text
+                        .button(
+                            self.depot.aesthetics.theme.button_secondary,
+                            self.depot.aesthetics.theme.text,
+                            &self.depot.aesthetics.map_texture,
+                            ui,
+                        )
+                        .clicked()
+                    {
+                        // The outer app picks this up next frame and restyles everything
+                        theme_choice.next().store(ui.ctx());
+                    }
+
+                    ui.add_space(menu_spacing);
+
+                    let label = match self.depot.ui_state.input_preference {
+                        Some(profile) => format!("INPUT: {}", profile.label()),
+                        None => format!(
+                            "INPUT: AUTO ({})",
+                            self.depot.ui_state.input_profile.label()
+                        ),
+                    };
+                    let text = TextHelper::heavy(&label, 14.0, None, ui);
+
+                    if text
+                        .button(
+                            self.depot.aesthetics.theme.button_secondary,
+                            self.depot.aesthetics.theme.text,
+                            &self.depot.aesthetics.map_texture,
+                            ui,
+                        )
+                        .clicked()
+                    {
+                        self.depot.ui_state.input_preference =
+                            InputProfile::next_preference(self.depot.ui_state.input_preference);
+
+                        #[cfg(target_arch = "wasm32")]
+                        {
+                            let local_storage =
+                                web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                            match self.depot.ui_state.input_preference {
+                                Some(profile) => local_storage
+                                    .set_item("truncate_input_profile", profile.as_setting())
+                                    .unwrap(),
+                                None => {
+                                    local_storage.remove_item("truncate_input_profile").unwrap()
+                                }
+                            }
+                        }
+                    }
+
+                    if self.depot.ui_state.is_touch {
+                        ui.add_space(menu_spacing);
+
+                        let label = match self.depot.ui_state.confirm_placement {
+                            Some(true) => "CONFIRM TAPS: ON",
+                            Some(false) => "CONFIRM TAPS: OFF",
+                            None => "CONFIRM TAPS: AUTO",
+                        };
+                        let text = TextHelper::heavy(label, 14.0, None, ui);
+
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.ui_state.confirm_placement =
+                                match self.depot.ui_state.confirm_placement {
+                                    None => Some(true),
+                                    Some(true) => Some(false),
+                                    Some(false) => None,
+                                };
+                            self.depot.interactions.pending_placement = None;
+
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let local_storage =
+                                    web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                                match self.depot.ui_state.confirm_placement {
+                                    Some(confirm) => local_storage
+                                        .set_item(
+                                            "truncate_confirm_placement",
+                                            &confirm.to_string(),
+                                        )
+                                        .unwrap(),
+                                    None => local_storage
+                                        .remove_item("truncate_confirm_placement")
+                                        .unwrap(),
+                                }
+                            }
+                        }
+                    }
+
+                    if !matches!(self.location, GameLocation::Tutorial) {
+                        ui.add_space(menu_spacing);
+
+                        let text = if self.depot.ui_state.truncation_preview {
+                            TextHelper::heavy("HIDE WEAK POINTS", 14.0, None, ui)
+                        } else {
+                            TextHelper::heavy("SHOW WEAK POINTS", 14.0, None, ui)
+                        };
+
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.ui_state.truncation_preview =
+                                !self.depot.ui_state.truncation_preview;
+
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let local_storage =
+                                    web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                                local_storage
+                                    .set_item(
+                                        "truncate_truncation_preview",
+                                        &self.depot.ui_state.truncation_preview.to_string(),
+                                    )
+                                    .unwrap();
+                            }
+                        }
+                    }
+
+                    if self.depot.gameplay.npc.is_some() {
+                        ui.add_space(menu_spacing);
+
+                        let label = match self.depot.ui_state.npc_budget_ms {
+                            Some(ms) => format!("BOT THINKS FOR {:.1}S", ms as f32 / 1000.0),
+                            None => "BOT THINKS FREELY".to_string(),
+                        };
+                        let text = TextHelper::heavy(&label, 14.0, None, ui);
+
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let current = NPC_BUDGETS_MS
+                                .iter()
+                                .position(|b| *b == self.depot.ui_state.npc_budget_ms)
+                                .unwrap_or_default();
+                            self.depot.ui_state.npc_budget_ms =
+                                NPC_BUDGETS_MS[(current + 1) % NPC_BUDGETS_MS.len()];
+
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let local_storage =
+                                    web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                                match self.depot.ui_state.npc_budget_ms {
+                                    Some(ms) => local_storage
+                                        .set_item("truncate_npc_budget", &ms.to_string())
+                                        .unwrap(),
+                                    None => {
+                                        local_storage.remove_item("truncate_npc_budget").unwrap()
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // Only searches with a budget are shared between threads
+                    if self.depot.gameplay.npc.is_some()
+                        && self.depot.ui_state.npc_budget_ms.is_some()
+                    {
+                        ui.add_space(menu_spacing);
+
+                        let label = match self.depot.ui_state.npc_threads {
+                            Some(1) => "BOT USES 1 CORE".to_string(),
+                            Some(threads) => format!("BOT USES {threads} CORES"),
+                            None => "BOT USES SPARE CORES".to_string(),
+                        };
+                        let text = TextHelper::heavy(&label, 14.0, None, ui);
+
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let current = NPC_THREADS
+                                .iter()
+                                .position(|t| *t == self.depot.ui_state.npc_threads)
+                                .unwrap_or_default();
+                            self.depot.ui_state.npc_threads =
+                                NPC_THREADS[(current + 1) % NPC_THREADS.len()];
+
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let local_storage =
+                                    web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                                match self.depot.ui_state.npc_threads {
+                                    Some(threads) => local_storage
+                                        .set_item("truncate_npc_threads", &threads.to_string())
+                                        .unwrap(),
+                                    None => {
+                                        local_storage.remove_item("truncate_npc_threads").unwrap()
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if let Some(season) = scheduled_season() {
+                        ui.add_space(menu_spacing);
+
+                        let label = if self.depot.ui_state.classic_look {
+                            format!("{} LOOK", season.to_string().to_uppercase())
+                        } else {
+                            "CLASSIC LOOK".to_string()
+                        };
+                        let text = TextHelper::heavy(&label, 14.0, None, ui);
+
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.ui_state.classic_look = !self.depot.ui_state.classic_look;
+                            let shown = apply_season(
+                                &mut self.depot.aesthetics.map_texture,
+                                Some(season),
+                                self.depot.ui_state.classic_look,
+                            );
+                            self.show_season(shown);
+
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let local_storage =
+                                    web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                                local_storage
+                                    .set_item(
+                                        "truncate_classic_look",
+                                        &self.depot.ui_state.classic_look.to_string(),
+                                    )
+                                    .unwrap();
+                            }
+                        }
+                    }
+
                     if matches!(self.location, GameLocation::Online) {
                         ui.add_space(menu_spacing);
 
@@ -134,6 +515,42 @@ impl ActiveGame {
                         }
                     }
 
+                    if matches!(self.location, GameLocation::Online) {
+                        ui.add_space(menu_spacing);
+
+                        let text = TextHelper::heavy("POINT AT A SQUARE", 14.0, None, ui);
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_primary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.interactions.pointing = true;
+                            self.depot.ui_state.actions_menu_open = false;
+                        }
+                    }
+
+                    if matches!(self.location, GameLocation::Online) && self.can_appeal() {
+                        ui.add_space(menu_spacing);
+
+                        let text = TextHelper::heavy("APPEAL LAST BATTLE", 14.0, None, ui);
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_primary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            msg = Some(PlayerMessage::Appeal);
+                            self.depot.ui_state.actions_menu_open = false;
+                        }
+                    }
+
                     // TODO: Resigning is largely implented for multiplayer games as well, but we need to:
                     // - Resolve why the update isn't being sent from the server
                     // - Show the confirmation modal inside active_game (we only show it in single player)
@@ -178,3 +595,35 @@ impl ActiveGame {
         msg
     }
 }
+
+impl ActiveGame {
+    /// Whether the last turn's battle ruled out one of our words, and we have an appeal to spend on it
+    fn can_appeal(&self) -> bool {
+        let player_number = self.depot.gameplay.player_number;
+        let has_appeals = self
+            .players
+            .get(player_number as usize)
+            .is_some_and(|p| p.appeals_remaining > 0);
+        if !has_appeals || self.depot.gameplay.winner.is_some() {
+            return false;
+        }
+
+        // If it's our turn, the battle was an attack on us
+        let we_attacked = self.depot.gameplay.next_player_number != Some(player_number);
+        self.depot
+            .gameplay
+            .changes
+            .iter()
+            .any(|change| match change {
+                Change::Battle(battle) if battle.amendment.is_none() => {
+                    let ours = if we_attacked {
+                        &battle.attackers
+                    } else {
+                        &battle.defenders
+                    };
+                    ours.iter().any(|word| word.valid == Some(false))
+                }
+                _ => false,
+            })
+    }
+}
@@ -0,0 +1,83 @@
+use epaint::{emath::Align2, vec2, Color32};
+
+use truncate_core::{
+    bag::{blank_as, BLANK},
+    messages::PlayerMessage,
+};
+
+use eframe::egui::{self, Layout, Order};
+
+use crate::utils::{text::TextHelper, Diaphanize};
+
+use super::ActiveGame;
+
+/// How many letters sit on each row of the picker
+const LETTERS_PER_ROW: usize = 7;
+
+impl ActiveGame {
+    /// Asks which letter a blank being placed should be played as
+    pub fn render_blank_picker(&mut self, ui: &mut egui::Ui) -> Option<PlayerMessage> {
+        let coord = self.depot.interactions.picking_blank?;
+        let mut msg = None;
+
+        let picker_area = ui.available_rect_before_wrap();
+        let area = egui::Area::new(egui::Id::new("blank_picker_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0));
+
+        area.show(ui.ctx(), |ui| {
+            ui.painter().clone().rect_filled(
+                picker_area,
+                0.0,
+                self.depot.aesthetics.theme.water.gamma_multiply(0.6),
+            );
+
+            ui.with_layout(Layout::top_down(egui::Align::Center), |ui| {
+                let theme = &self.depot.aesthetics.theme;
+                let map_texture = &self.depot.aesthetics.map_texture;
+                let letter_color = Color32::WHITE.diaphanize();
+
+                TextHelper::heavy("PLAY BLANK AS", 14.0, None, ui).paint(theme.text, ui, true);
+                ui.add_space(10.0);
+
+                let letters: Vec<_> = ('A'..='Z').collect();
+                for row in letters.chunks(LETTERS_PER_ROW) {
+                    ui.horizontal(|ui| {
+                        for letter in row {
+                            if TextHelper::heavy(&letter.to_string(), 14.0, None, ui)
+                                .button(letter_color, theme.text, map_texture, ui)
+                                .clicked()
+                            {
+                                msg = blank_as(*letter)
+                                    .map(|blank| PlayerMessage::Place(coord, blank));
+                            }
+                        }
+                    });
+                }
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if TextHelper::heavy("LEAVE WILD", 12.0, None, ui)
+                        .button(theme.button_primary, theme.text, map_texture, ui)
+                        .clicked()
+                    {
+                        msg = Some(PlayerMessage::Place(coord, BLANK));
+                    }
+                    if TextHelper::heavy("CANCEL", 12.0, None, ui)
+                        .button(theme.button_secondary, theme.text, map_texture, ui)
+                        .clicked()
+                    {
+                        self.depot.interactions.picking_blank = None;
+                    }
+                });
+            });
+        });
+
+        if msg.is_some() {
+            self.depot.interactions.picking_blank = None;
+        }
+
+        msg
+    }
+}
@@ -0,0 +1,100 @@
+use epaint::Color32;
+use truncate_core::messages::{ChatChannel, PlayerMessage};
+
+use eframe::egui;
+
+use crate::utils::text::TextHelper;
+
+use super::{ActiveGame, ChatLine, SidebarTab};
+
+impl ActiveGame {
+    pub fn receive_chat(&mut self, channel: ChatChannel, sender: String, text: String) {
+        let log = match channel {
+            ChatChannel::Players => &mut self.chat.players,
+            ChatChannel::Observers => &mut self.chat.observers,
+        };
+        log.push(ChatLine { sender, text });
+
+        if !(self.depot.ui_state.sidebar_toggled || self.depot.ui_state.hand_docked)
+            || self.depot.ui_state.sidebar_tab != SidebarTab::Chat(channel)
+        {
+            self.depot.ui_state.unread_sidebar = true;
+        }
+    }
+
+    pub fn render_chat(
+        &mut self,
+        ui: &mut egui::Ui,
+        channel: ChatChannel,
+    ) -> Option<PlayerMessage> {
+        let mut msg = None;
+        let theme = &self.depot.aesthetics.theme;
+        let line_width = Some(ui.available_width() - 10.0);
+
+        // Spectators can only talk on the observers' channel, which they can't mute
+        let spectating = self.depot.interactions.view_only;
+
+        if channel == ChatChannel::Observers && !spectating {
+            let mute_text = if self.chat.observers_muted {
+                "SHOW OBSERVER CHAT"
+            } else {
+                "HIDE OBSERVER CHAT"
+            };
+            if TextHelper::heavy(mute_text, 12.0, None, ui)
+                .button(
+                    theme.button_secondary,
+                    theme.text,
+                    &self.depot.aesthetics.map_texture,
+                    ui,
+                )
+                .clicked()
+            {
+                self.chat.observers_muted = !self.chat.observers_muted;
+                msg = Some(PlayerMessage::MuteChat {
+                    channel,
+                    muted: self.chat.observers_muted,
+                });
+            }
+            ui.add_space(10.0);
+
+            let note = if self.chat.observers_muted {
+                "Observer chat is hidden, so nobody can coach you during the game"
+            } else {
+                "Observers chat amongst themselves here, and can't post to the players"
+            };
+            TextHelper::light(note, 12.0, line_width, ui).paint(theme.text, ui, false);
+            ui.add_space(10.0);
+        }
+
+        for line in self.chat.channel(channel) {
+            TextHelper::heavy(&line.sender, 12.0, line_width, ui).paint(theme.text, ui, false);
+            TextHelper::light(&line.text, 12.0, line_width, ui).paint(
+                theme.text.gamma_multiply(0.8),
+                ui,
+                false,
+            );
+            ui.add_space(6.0);
+        }
+
+        if (channel == ChatChannel::Observers) == spectating {
+            ui.add_space(4.0);
+            let input = egui::TextEdit::singleline(&mut self.chat.draft)
+                .desired_width(ui.available_width())
+                .hint_text("Say something")
+                .text_color(Color32::WHITE)
+                .show(ui);
+
+            self.depot.ui_state.chat_focused = input.response.has_focus();
+
+            if input.response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let text = std::mem::take(&mut self.chat.draft);
+                if !text.trim().is_empty() {
+                    msg = Some(PlayerMessage::Chat { channel, text });
+                }
+                input.response.request_focus();
+            }
+        }
+
+        msg
+    }
+}
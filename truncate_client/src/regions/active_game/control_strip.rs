@@ -1,6 +1,6 @@
 use epaint::{emath::Align2, hex_color, vec2, Rect, Vec2};
 
-use truncate_core::messages::PlayerMessage;
+use truncate_core::{game::GameOutcome, messages::PlayerMessage};
 
 use eframe::{
     egui::{self, CursorIcon, Layout, Order, Sense},
@@ -8,7 +8,7 @@ use eframe::{
 };
 
 use crate::{
-    lil_bits::{DictionaryUI, HandUI},
+    lil_bits::{DictionaryUI, HandUI, TimeUsageUI},
     utils::{
         tex::{render_tex_quad, tiles},
         text::TextHelper,
@@ -42,12 +42,18 @@ impl ActiveGame {
         }
 
         let avail_width = ui.available_width();
+        // A docked hand sits at the foot of the column on the right
+        let control_align = if self.depot.ui_state.hand_docked {
+            Align2::RIGHT_BOTTOM
+        } else {
+            Align2::LEFT_BOTTOM
+        };
 
         let error_area = egui::Area::new(egui::Id::new("error_layer"))
             .movable(false)
             .order(Order::Tooltip)
             .anchor(
-                Align2::LEFT_BOTTOM,
+                control_align,
                 -vec2(
                     0.0,
                     self.depot
@@ -95,7 +101,7 @@ impl ActiveGame {
         let area = egui::Area::new(egui::Id::new("controls_layer"))
             .movable(false)
             .order(Order::Foreground)
-            .anchor(Align2::LEFT_BOTTOM, control_anchor);
+            .anchor(control_align, control_anchor);
 
         let resp = area.show(ui.ctx(), |ui| {
             // TODO: We can likely use Memory::area_rect now instead of tracking sizes ourselves
@@ -115,7 +121,205 @@ impl ActiveGame {
 
                     ui.add_space(10.0);
 
-                    if self.depot.gameplay.winner.is_some() {
+                    if let Some((ruling, reason)) = &self.depot.gameplay.arbiter_notice {
+                        TextHelper::heavy(
+                            &format!("ARBITER: {ruling}"),
+                            16.0,
+                            Some(avail_width - 20.0),
+                            ui,
+                        )
+                        .paint(self.depot.aesthetics.theme.text, ui, true);
+                        ui.add_space(5.0);
+                        TextHelper::light(reason, 12.0, Some(avail_width - 20.0), ui).paint(
+                            self.depot.aesthetics.theme.text,
+                            ui,
+                            true,
+                        );
+                        ui.add_space(15.0);
+                    }
+
+                    if let Some(divergence_bits) = self.depot.gameplay.mulligan_offer {
+                        TextHelper::heavy("POOR STARTING HAND", 16.0, Some(avail_width - 20.0), ui)
+                            .paint(self.depot.aesthetics.theme.text, ui, true);
+                        ui.add_space(5.0);
+                        let line = format!(
+                            "Your hand strays {divergence_bits:.1} bits from the bag's odds, and can be redrawn once before your first turn"
+                        );
+                        TextHelper::light(&line, 12.0, Some(avail_width - 20.0), ui).paint(
+                            self.depot.aesthetics.theme.text,
+                            ui,
+                            true,
+                        );
+                        ui.add_space(5.0);
+
+                        let text = TextHelper::heavy("REDRAW", 12.0, None, ui);
+                        if text
+                            .centered_button(
+                                self.depot.aesthetics.theme.button_primary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.gameplay.mulligan_offer = None;
+                            msg = Some(PlayerMessage::Mulligan);
+                        }
+                        ui.add_space(5.0);
+                        let text = TextHelper::heavy("KEEP", 12.0, None, ui);
+                        if text
+                            .centered_button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.gameplay.mulligan_offer = None;
+                        }
+                        ui.add_space(15.0);
+                    }
+
+                    // Now that the game is over, show everything that was hidden during play
+                    if let Some(end_state) = &self.depot.gameplay.end_state {
+                        let name_of = |player: usize| {
+                            self.players
+                                .get(player)
+                                .map(|p| p.name.as_str())
+                                .unwrap_or("Unknown")
+                        };
+                        let result = match &end_state.outcome {
+                            GameOutcome::Draw(adjudication) => {
+                                Some(("DRAWN".to_string(), adjudication.to_string()))
+                            }
+                            GameOutcome::Won {
+                                winner,
+                                tiebreaker: Some(tiebreaker),
+                            } => Some((
+                                format!("{} WINS ON {}", name_of(*winner), tiebreaker)
+                                    .to_uppercase(),
+                                format!("The game was drawn, so it went to {tiebreaker}"),
+                            )),
+                            GameOutcome::Won {
+                                tiebreaker: None, ..
+                            } => None,
+                        };
+                        if let Some((heading, reason)) = result {
+                            TextHelper::heavy(&heading, 16.0, Some(avail_width - 20.0), ui).paint(
+                                self.depot.aesthetics.theme.text,
+                                ui,
+                                true,
+                            );
+                            ui.add_space(5.0);
+                            TextHelper::light(&reason, 12.0, Some(avail_width - 20.0), ui).paint(
+                                self.depot.aesthetics.theme.text,
+                                ui,
+                                true,
+                            );
+                            ui.add_space(15.0);
+                        }
+
+                        let mut reveal_lines: Vec<_> = end_state
+                            .hands
+                            .iter()
+                            .enumerate()
+                            .map(|(player, hand)| format!("{} held {hand}", name_of(player)))
+                            .collect();
+                        reveal_lines.push(if end_state.bag.is_empty() {
+                            "The bag was empty".to_string()
+                        } else {
+                            format!(
+                                "Left in the bag: {}",
+                                end_state.bag.iter().collect::<String>()
+                            )
+                        });
+
+                        for line in reveal_lines {
+                            TextHelper::light(&line, 12.0, Some(avail_width - 20.0), ui).paint(
+                                self.depot.aesthetics.theme.text,
+                                ui,
+                                true,
+                            );
+                            ui.add_space(5.0);
+                        }
+
+                        let think_times: Vec<_> = end_state
+                            .think_times
+                            .iter()
+                            .map(|think| (think.player, think.secs))
+                            .collect();
+                        if !think_times.is_empty() {
+                            ui.add_space(10.0);
+                            TimeUsageUI::new(&think_times, &self.depot.aesthetics.player_colors)
+                                .render(ui, self.depot.aesthetics.theme.text);
+                        }
+
+                        ui.add_space(15.0);
+                    } else if let Some(remaining) = self
+                        .players
+                        .get(self.depot.gameplay.player_number as usize)
+                        .and_then(|p| p.tiles_remaining)
+                    {
+                        // Without redraws the bag won't save us, so make the countdown visible
+                        let line = match remaining {
+                            0 => "No tiles left to place".to_string(),
+                            1 => "1 tile left to place".to_string(),
+                            n => format!("{n} tiles left to place"),
+                        };
+                        TextHelper::light(&line, 12.0, Some(avail_width - 20.0), ui).paint(
+                            self.depot.aesthetics.theme.text,
+                            ui,
+                            true,
+                        );
+                        ui.add_space(10.0);
+                    }
+
+                    let my_turn = self
+                        .depot
+                        .gameplay
+                        .next_player_number
+                        .is_some_and(|n| n == self.depot.gameplay.player_number);
+                    if let Some(points) = self
+                        .players
+                        .get(self.depot.gameplay.player_number as usize)
+                        .and_then(|p| p.action_points_remaining)
+                        .filter(|_| my_turn && self.depot.gameplay.winner.is_none())
+                    {
+                        let line = match points {
+                            1 => "1 action point left this turn".to_string(),
+                            n => format!("{n} action points left this turn"),
+                        };
+                        TextHelper::light(&line, 12.0, Some(avail_width - 20.0), ui).paint(
+                            self.depot.aesthetics.theme.text,
+                            ui,
+                            true,
+                        );
+
+                        if let Some((_, tile)) = self.depot.interactions.selected_tile_in_hand {
+                            ui.add_space(5.0);
+                            let label = format!("EXCHANGE {}", tile.to_ascii_uppercase());
+                            let text = TextHelper::heavy(&label, 12.0, None, ui);
+                            if text
+                                .centered_button(
+                                    self.depot.aesthetics.theme.button_secondary,
+                                    self.depot.aesthetics.theme.text,
+                                    &self.depot.aesthetics.map_texture,
+                                    ui,
+                                )
+                                .clicked()
+                            {
+                                self.depot.interactions.selected_tile_in_hand = None;
+                                msg = Some(PlayerMessage::Exchange(tile));
+                            }
+                        }
+                        ui.add_space(10.0);
+                    }
+
+                    // Drawn games have no winner, but are just as over
+                    if self.depot.gameplay.winner.is_some()
+                        || self.depot.gameplay.end_state.is_some()
+                    {
                         if matches!(self.location, GameLocation::Online) {
                             let text = TextHelper::heavy("REMATCH", 12.0, None, ui);
                             if text
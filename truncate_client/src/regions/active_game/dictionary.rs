@@ -57,7 +57,7 @@ impl ActiveGame {
                     ui.expand_to_include_rect(inner_dict_area);
 
                     ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
-                        msg = dict_ui.render(ui, &mut self.depot);
+                        msg = dict_ui.render(ui, &mut self.depot, &self.board);
                     });
                 });
             });
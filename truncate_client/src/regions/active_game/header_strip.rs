@@ -1,6 +1,7 @@
 use epaint::{emath::Align2, vec2, Rect, Vec2};
+use instant::Duration;
 
-use truncate_core::messages::PlayerMessage;
+use truncate_core::{board::Square, game::Game, messages::PlayerMessage, rules::Visibility};
 
 use eframe::{
     egui::{self, CursorIcon, Layout, Order, Sense},
@@ -17,6 +18,9 @@ use crate::{
 
 use super::{ActiveGame, HeaderType};
 
+/// How long the header's copy button says the result was copied
+const SUMMARY_COPIED_DURATION: Duration = Duration::from_secs(2);
+
 impl ActiveGame {
     pub fn render_header_strip(
         &mut self,
@@ -63,7 +67,8 @@ impl ActiveGame {
                     let item_spacing = 10.0;
                     let button_size = 48.0;
 
-                    if !self.depot.ui_state.is_mobile {
+                    // A docked hand keeps the battles on screen, so there's nothing to toggle
+                    if !self.depot.ui_state.is_mobile && !self.depot.ui_state.hand_docked {
                         ui.add_space(item_spacing);
                         let (mut sidebar_button_rect, sidebar_button_resp) =
                             ui.allocate_exact_size(Vec2::splat(button_size), Sense::click());
@@ -162,16 +167,63 @@ impl ActiveGame {
                         }
                         HeaderType::Summary { title, attempt: _ } => {
                             let summary_height = 50.0;
-                            let summary_width = ui.available_width();
                             let (rect, _) = ui.allocate_exact_size(
-                                vec2(summary_width, summary_height),
+                                vec2(ui.available_width(), summary_height),
                                 Sense::hover(),
                             );
+
+                            let copy_button =
+                                self.depot.ui_state.share_summary.as_ref().map(|_| {
+                                    let copied =
+                                        self.depot.ui_state.summary_copied_at.is_some_and(|at| {
+                                            self.depot.timing.current_time.saturating_sub(at)
+                                                < SUMMARY_COPIED_DURATION
+                                        });
+                                    TextHelper::heavy(
+                                        if copied { "COPIED" } else { "COPY" },
+                                        10.0,
+                                        None,
+                                        ui,
+                                    )
+                                });
+                            // Both sides are kept clear so that the title stays centered
+                            let button_space = copy_button
+                                .as_ref()
+                                .map(|text| text.mesh_size().x + 30.0)
+                                .unwrap_or_default();
+
+                            if let Some(copy_button) = copy_button {
+                                let mut button_ui =
+                                    ui.child_ui(rect, Layout::right_to_left(Align::Center));
+                                button_ui.add_space(5.0);
+                                if copy_button
+                                    .button(
+                                        self.depot.aesthetics.theme.button_primary,
+                                        self.depot.aesthetics.theme.text,
+                                        &self.depot.aesthetics.map_texture,
+                                        &mut button_ui,
+                                    )
+                                    .clicked()
+                                {
+                                    if let Some(share_summary) = &self.depot.ui_state.share_summary
+                                    {
+                                        button_ui
+                                            .ctx()
+                                            .output_mut(|o| o.copied_text = share_summary.clone());
+                                    }
+                                    self.depot.ui_state.summary_copied_at =
+                                        Some(self.depot.timing.current_time);
+                                }
+                            }
+
+                            let rect =
+                                rect.shrink2(vec2(button_space.min(rect.width() / 4.0), 0.0));
+                            let summary_width = rect.width();
                             let mut ui = ui.child_ui(rect, Layout::top_down(Align::LEFT));
 
                             let active_player = self.depot.gameplay.player_number;
                             let summary = if let Some(game) = game_ref {
-                                format!(
+                                let moves = format!(
                                     "{} move{}",
                                     game.player_turn_count[active_player as usize],
                                     if game.player_turn_count[active_player as usize] == 1 {
@@ -179,7 +231,15 @@ impl ActiveGame {
                                     } else {
                                         "s"
                                     },
-                                )
+                                );
+                                if matches!(game.rules.visibility, Visibility::Mystery)
+                                    && game.winner.is_none()
+                                {
+                                    let revealed = revealed_land(game, active_player as usize);
+                                    format!("{moves}, {revealed}% revealed")
+                                } else {
+                                    moves
+                                }
                             } else {
                                 "".to_string()
                             };
@@ -248,3 +308,26 @@ impl ActiveGame {
         (Some(resp.response.rect), msg)
     }
 }
+
+/// How much of the land a player has uncovered in a mystery game, as a percentage
+fn revealed_land(game: &Game, player: usize) -> usize {
+    let seen = &game.players[player].seen_tiles;
+    let visible = game.board.fog_of_war(player, &game.rules.visibility, seen);
+
+    let is_land = |sq: &Square| !matches!(sq, Square::Water { .. } | Square::Fog {});
+    let land = game
+        .board
+        .squares
+        .iter()
+        .flatten()
+        .filter(|sq| is_land(sq))
+        .count();
+    let revealed = visible
+        .squares
+        .iter()
+        .flatten()
+        .filter(|sq| is_land(sq))
+        .count();
+
+    (revealed * 100).checked_div(land).unwrap_or_default()
+}
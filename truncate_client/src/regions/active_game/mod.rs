@@ -1,12 +1,19 @@
-use epaint::{Color32, TextureHandle};
+use epaint::{emath::Align2, Color32, TextureHandle};
 use instant::Duration;
 use truncate_core::{
     board::{Board, Coordinate},
+    flags::Flags,
     generation::BoardSeed,
-    messages::{GamePlayerMessage, GameStateMessage, PlayerMessage, RoomCode},
-    npc::scoring::NPCPersonality,
+    messages::{
+        ArbiterAction, ChatChannel, GamePlayerMessage, GameStateMessage, PlayerMessage, RoomCode,
+        SequencedChanges,
+    },
+    npc::{scoring::NPCPersonality, SearchReport},
     player::Hand,
-    reporting::{BoardChange, BoardChangeAction, BoardChangeDetail, Change, TimeChange},
+    reporting::{
+        BagChange, BagEvent, BoardChange, BoardChangeAction, BoardChangeDetail, Change, TimeChange,
+    },
+    season::Season,
 };
 
 use eframe::{
@@ -18,22 +25,42 @@ use hashbrown::HashMap;
 use crate::{
     lil_bits::{BoardUI, DictionaryUI},
     utils::{
-        control_devices,
+        control_devices::{self, gamepad::GamepadInput, InputProfile},
         depot::{
             AestheticDepot, AudioDepot, BoardDepot, GameplayDepot, InteractionDepot, RegionDepot,
             TimingDepot, TruncateDepot, UIStateDepot,
         },
         mapper::{MappedBoard, MappedTiles},
-        timing::get_qs_tick,
+        text::TextHelper,
+        timing::{get_qs_tick, set_low_power},
         Theme,
     },
 };
 
 mod actions_menu;
+mod blank_picker;
+mod chat;
 mod control_strip;
 mod dictionary;
 mod header_strip;
+mod placement_preview;
 mod sidebar;
+mod swap_preview;
+mod truncation_preview;
+
+/// How long an online player can sit idle on their turn before being nudged
+const DEFAULT_IDLE_THRESHOLD_SECS: u64 = 90;
+/// How long after a turn change to keep the full frame rate, so battles can finish animating
+const LOW_POWER_SETTLE_SECS: u64 = 3;
+/// How long an opponent's turn runs before we show that they're thinking,
+/// so that quick replies don't flash the indicator
+const THINKING_INDICATOR_SECS: u64 = 2;
+/// How wide the column is when the hand is docked beside the board
+const DOCKED_COLUMN_WIDTH: f32 = 360.0;
+/// How wide the layout must be before the hand docks itself automatically
+const DOCKED_LAYOUT_BREAKPOINT: f32 = 1100.0;
+/// How often a connected gamepad is checked for input
+const GAMEPAD_POLL_INTERVAL: Duration = Duration::from_millis(30);
 
 #[derive(Clone, Default, Debug)]
 pub enum HeaderType {
@@ -46,6 +73,87 @@ pub enum HeaderType {
     None,
 }
 
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub enum SidebarTab {
+    #[default]
+    Battles,
+    Chat(ChatChannel),
+}
+
+/// Where the hand and battles sit relative to the board
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub enum HandLayout {
+    /// Docked on wide desktop layouts, and stacked otherwise
+    #[default]
+    Auto,
+    /// The hand below the board, with battles in a toggled sidebar
+    Stacked,
+    /// The hand and battles in a column beside the board, so the board keeps the full height
+    Docked,
+}
+
+impl HandLayout {
+    pub fn next(self) -> Self {
+        match self {
+            HandLayout::Auto => HandLayout::Stacked,
+            HandLayout::Stacked => HandLayout::Docked,
+            HandLayout::Docked => HandLayout::Auto,
+        }
+    }
+
+    pub fn as_setting(self) -> &'static str {
+        match self {
+            HandLayout::Auto => "auto",
+            HandLayout::Stacked => "stacked",
+            HandLayout::Docked => "docked",
+        }
+    }
+
+    pub fn from_setting(layout: &str) -> Option<Self> {
+        match layout {
+            "auto" => Some(HandLayout::Auto),
+            "stacked" => Some(HandLayout::Stacked),
+            "docked" => Some(HandLayout::Docked),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ChatLine {
+    pub sender: String,
+    pub text: String,
+}
+
+#[derive(Clone)]
+pub struct ChatLog {
+    pub players: Vec<ChatLine>,
+    pub observers: Vec<ChatLine>,
+    pub draft: String,
+    /// Mirrors the server, which holds back observer chat until a player asks for it
+    pub observers_muted: bool,
+}
+
+impl Default for ChatLog {
+    fn default() -> Self {
+        Self {
+            players: vec![],
+            observers: vec![],
+            draft: String::new(),
+            observers_muted: true,
+        }
+    }
+}
+
+impl ChatLog {
+    pub fn channel(&self, channel: ChatChannel) -> &Vec<ChatLine> {
+        match channel {
+            ChatChannel::Players => &self.players,
+            ChatChannel::Observers => &self.observers,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum GameLocation {
     Tutorial,
@@ -66,8 +174,12 @@ pub struct ActiveGame {
     pub new_hand_tiles: Vec<usize>,
     pub time_changes: Vec<TimeChange>,
     pub turn_reports: Vec<Vec<Change>>,
+    /// Server sequence number of each entry in `turn_reports`, zero where it has none
+    pub turn_report_seqs: Vec<u64>,
     pub location: GameLocation,
     pub dictionary_ui: Option<DictionaryUI>,
+    pub chat: ChatLog,
+    pub gamepad: GamepadInput,
 }
 
 impl ActiveGame {
@@ -102,6 +214,8 @@ impl ActiveGame {
             },
             timing: TimingDepot {
                 game_ends_at,
+                idle_threshold: matches!(location, GameLocation::Online)
+                    .then(|| Duration::from_secs(DEFAULT_IDLE_THRESHOLD_SECS)),
                 ..TimingDepot::default()
             },
             gameplay: GameplayDepot {
@@ -114,6 +228,11 @@ impl ActiveGame {
                 last_battle_origin: None,
                 npc,
                 remaining_turns,
+                hand_swap_in: None,
+                end_state: None,
+                latency_ms: None,
+                arbiter_notice: None,
+                mulligan_offer: None,
             },
             aesthetics: AestheticDepot {
                 theme: theme.clone(),
@@ -124,6 +243,7 @@ impl ActiveGame {
                 destruction_duration: 0.6,
             },
             audio: AudioDepot::default(),
+            flags: Flags::default(),
         };
 
         #[cfg(target_arch = "wasm32")]
@@ -135,6 +255,76 @@ impl ActiveGame {
                 .unwrap_or_default()
                 .parse()
                 .unwrap_or_default();
+
+            depot.ui_state.classic_look = local_storage
+                .get_item("truncate_classic_look")
+                .unwrap()
+                .unwrap_or_default()
+                .parse()
+                .unwrap_or_default();
+
+            depot.ui_state.truncation_preview = local_storage
+                .get_item("truncate_truncation_preview")
+                .unwrap()
+                .unwrap_or_default()
+                .parse()
+                .unwrap_or_default();
+
+            depot.ui_state.confirm_placement = local_storage
+                .get_item("truncate_confirm_placement")
+                .unwrap()
+                .and_then(|confirm| confirm.parse().ok());
+
+            depot.ui_state.npc_budget_ms = local_storage
+                .get_item("truncate_npc_budget")
+                .unwrap()
+                .and_then(|ms| ms.parse().ok());
+
+            depot.ui_state.npc_threads = local_storage
+                .get_item("truncate_npc_threads")
+                .unwrap()
+                .and_then(|threads| threads.parse().ok());
+
+            if let Some(low_power_mode) = local_storage
+                .get_item("truncate_low_power")
+                .unwrap()
+                .and_then(|enabled| enabled.parse().ok())
+            {
+                depot.ui_state.low_power_mode = low_power_mode;
+            }
+
+            if let Some(hand_layout) = local_storage
+                .get_item("truncate_hand_layout")
+                .unwrap()
+                .and_then(|layout| HandLayout::from_setting(&layout))
+            {
+                depot.ui_state.hand_layout = hand_layout;
+            }
+
+            if let Some(view_rotation) = local_storage
+                .get_item("truncate_view_rotation")
+                .unwrap()
+                .and_then(|rotation| {
+                    crate::utils::board_view::ViewRotation::from_setting(&rotation)
+                })
+            {
+                depot.ui_state.view_rotation = view_rotation;
+            }
+
+            depot.ui_state.input_preference = local_storage
+                .get_item("truncate_input_profile")
+                .unwrap()
+                .and_then(|profile| InputProfile::from_setting(&profile));
+
+            if depot.timing.idle_threshold.is_some() {
+                if let Some(secs) = local_storage
+                    .get_item("truncate_idle_threshold")
+                    .unwrap()
+                    .and_then(|secs| secs.parse().ok())
+                {
+                    depot.timing.idle_threshold = Some(Duration::from_secs(secs));
+                }
+            }
         }
 
         Self {
@@ -156,8 +346,11 @@ impl ActiveGame {
             new_hand_tiles: vec![],
             time_changes: vec![],
             turn_reports: vec![],
+            turn_report_seqs: vec![],
             location,
             dictionary_ui: None,
+            chat: ChatLog::default(),
+            gamepad: GamepadInput::default(),
         }
     }
 }
@@ -175,23 +368,26 @@ impl ActiveGame {
             self.depot.aesthetics.qs_tick = cur_tick;
         }
 
-        let kb_msg = control_devices::keyboard::handle_input(
-            ui.ctx(),
-            &self.board,
-            &self.hand,
-            &mut self.depot,
-        );
+        let mut intents = control_devices::keyboard::read_intents(ui.ctx(), &self.depot);
+        let gamepad_intents = match self.depot.ui_state.input_preference {
+            None | Some(InputProfile::Gamepad) => self.gamepad.read_intents(current_time),
+            Some(_) => vec![],
+        };
+        control_devices::detect_profile(ui.ctx(), !gamepad_intents.is_empty(), &mut self.depot);
+        intents.extend(gamepad_intents);
 
-        if !self.depot.ui_state.is_touch {
-            // If we ever receive any touch event,
-            // irrevocably put Truncate into touch mode.
-            if ui.input(|i| {
-                i.events
-                    .iter()
-                    .any(|event| matches!(event, egui::Event::Touch { .. }))
-            }) {
-                self.depot.ui_state.is_touch = true;
-            }
+        // Boards that are only for looking at ignore the keyboard and gamepad too
+        let kb_msg = if self.depot.interactions.view_only {
+            None
+        } else {
+            control_devices::apply_intents(&intents, &self.board, &self.hand, &mut self.depot)
+        };
+        if !intents.is_empty() {
+            ui.ctx().request_repaint();
+        }
+        if self.gamepad.connected {
+            // Gamepads don't wake egui up, so keep checking on them
+            ui.ctx().request_repaint_after(GAMEPAD_POLL_INTERVAL);
         }
 
         let mut game_space = ui.available_rect_before_wrap();
@@ -210,15 +406,42 @@ impl ActiveGame {
             self.depot.ui_state.is_mobile = true;
         }
 
-        if !self.depot.ui_state.is_mobile && self.depot.ui_state.sidebar_toggled {
+        let hand_docked = !self.depot.ui_state.is_mobile
+            && !self.depot.ui_state.hand_hidden
+            && match self.depot.ui_state.hand_layout {
+                HandLayout::Stacked => false,
+                HandLayout::Docked => true,
+                HandLayout::Auto => {
+                    ui.available_width() >= DOCKED_LAYOUT_BREAKPOINT
+                        && ui.available_width() > ui.available_height()
+                }
+            };
+        self.depot.ui_state.hand_docked = hand_docked;
+
+        if hand_docked {
+            game_space.set_right(game_space.right() - DOCKED_COLUMN_WIDTH);
+            sidebar_space.set_left(sidebar_space.right() - DOCKED_COLUMN_WIDTH);
+        } else if !self.depot.ui_state.is_mobile && self.depot.ui_state.sidebar_toggled {
             game_space.set_right(game_space.right() - 300.0);
             sidebar_space.set_left(sidebar_space.right() - 300.0);
         }
 
-        let mut control_strip_ui = ui.child_ui(game_space, Layout::top_down(Align::LEFT));
+        let control_space = if hand_docked {
+            sidebar_space
+        } else {
+            game_space
+        };
+        let mut control_strip_ui = ui.child_ui(control_space, Layout::top_down(Align::LEFT));
         let (control_strip_rect, control_player_message) =
             self.render_control_strip(&mut control_strip_ui);
 
+        if hand_docked {
+            // The battles fill whatever the hand leaves of the column
+            if let Some(control_strip_rect) = control_strip_rect {
+                sidebar_space.set_bottom(control_strip_rect.top());
+            }
+        }
+
         let mut timer_strip_ui = ui.child_ui(game_space, Layout::top_down(Align::LEFT));
         let (timer_strip_rect, timer_player_message) =
             self.render_header_strip(&mut timer_strip_ui, game_ref);
@@ -229,7 +452,7 @@ impl ActiveGame {
         if let Some(timer_strip_rect) = timer_strip_rect {
             game_space.set_top(timer_strip_rect.bottom());
         }
-        if let Some(control_strip_rect) = control_strip_rect {
+        if let Some(control_strip_rect) = control_strip_rect.filter(|_| !hand_docked) {
             game_space.set_bottom(control_strip_rect.top());
         }
         let mut game_space_ui = ui.child_ui(game_space, Layout::top_down(Align::LEFT));
@@ -242,6 +465,10 @@ impl ActiveGame {
 
         let dict_player_message = self.render_dictionary(ui);
 
+        let blank_player_message = self.render_blank_picker(&mut game_space_ui);
+
+        self.preview_truncation();
+
         let player_message = BoardUI::new(&self.board)
             .interactive(!self.depot.interactions.view_only)
             .render(
@@ -252,13 +479,156 @@ impl ActiveGame {
                 &mut self.mapped_overlay,
                 &mut self.depot,
             )
+            .or(blank_player_message)
             .or(actions_player_message)
             .or(control_player_message)
             .or(timer_player_message)
             .or(dict_player_message)
             .or(sidebar_player_message);
 
-        kb_msg.or(player_message)
+        self.render_swap_preview(&mut game_space_ui, game_ref);
+        let placement_player_message = self.render_placement_preview(&mut game_space_ui, game_ref);
+        let player_message = player_message.or(placement_player_message);
+
+        self.render_thinking_indicator(&mut game_space_ui);
+
+        self.schedule_frames(&mut game_space_ui);
+
+        let player_message = kb_msg.or(player_message);
+        // Presence is only sent on quiet frames so it never displaces a real move
+        let presence_message = self.track_presence(ui, player_message.is_none());
+
+        player_message.or(presence_message)
+    }
+
+    /// Redraws the game after the seasonal atlas has been swapped underneath it
+    pub fn show_season(&mut self, season: Option<Season>) {
+        self.depot.aesthetics.theme = self.depot.aesthetics.theme.clone().with_season(season);
+        self.mapped_board.invalidate();
+        self.mapped_hand.invalidate();
+        self.mapped_overlay.invalidate();
+    }
+
+    /// Redraws the game in a new theme, such as after the player switches to dark mode
+    pub fn restyle(&mut self, theme: &Theme) {
+        self.depot.aesthetics.theme = self.depot.aesthetics.theme.clone().with_palette(theme);
+        self.mapped_board.invalidate();
+        self.mapped_hand.invalidate();
+        self.mapped_overlay.invalidate();
+    }
+
+    /// Drops to a low repaint frequency while we wait on an opponent and nothing is animating.
+    /// Input and server messages repaint regardless, which brings us back out of it.
+    fn schedule_frames(&mut self, ui: &mut egui::Ui) {
+        let timing = &self.depot.timing;
+        let gameplay = &self.depot.gameplay;
+
+        let their_turn = gameplay
+            .next_player_number
+            .is_some_and(|n| n != gameplay.player_number);
+        let settled = timing.current_time.saturating_sub(timing.last_turn_change)
+            > Duration::from_secs(LOW_POWER_SETTLE_SECS);
+        let interacting = ui.memory(|m| m.is_anything_being_dragged());
+
+        let engaged = self.depot.ui_state.low_power_mode
+            && their_turn
+            && settled
+            && gameplay.winner.is_none()
+            && !interacting;
+
+        self.depot.ui_state.low_power_engaged = engaged;
+        set_low_power(engaged);
+
+        if engaged {
+            TextHelper::light("LOW POWER", 10.0, None, ui).paint_within(
+                ui.max_rect().shrink(8.0),
+                Align2::RIGHT_TOP,
+                self.depot.aesthetics.theme.text.gamma_multiply(0.6),
+                ui,
+            );
+        }
+    }
+
+    /// Shows how long the opponent has spent on their turn, and how long the bot has been searching,
+    /// so that a slow turn never looks like a frozen game
+    fn render_thinking_indicator(&mut self, ui: &mut egui::Ui) {
+        let timing = &self.depot.timing;
+        let gameplay = &self.depot.gameplay;
+
+        let Some(thinker) = gameplay
+            .next_player_number
+            .filter(|n| *n != gameplay.player_number)
+            .and_then(|n| self.players.iter().find(|p| p.index as u64 == n))
+        else {
+            return;
+        };
+        if gameplay.winner.is_some() || timing.paused {
+            return;
+        }
+
+        let elapsed = timing.current_time.saturating_sub(timing.last_turn_change);
+        if elapsed < Duration::from_secs(THINKING_INDICATOR_SECS) {
+            return;
+        }
+
+        // Only our own bot can say how far its search has got
+        let thinking = format!("{} is thinking… {}s", thinker.name, elapsed.as_secs());
+        let label = match timing.npc_search {
+            Some(SearchReport { assessed, depth: 0 }) => {
+                format!("{thinking}, {assessed} boards checked")
+            }
+            Some(SearchReport { assessed, depth }) => {
+                format!("{thinking}, {depth} turns ahead over {assessed} boards")
+            }
+            None => thinking,
+        };
+
+        TextHelper::light(&label, 10.0, None, ui).paint_within(
+            ui.max_rect().shrink(8.0),
+            Align2::LEFT_TOP,
+            self.depot.aesthetics.theme.text.gamma_multiply(0.6),
+            ui,
+        );
+    }
+
+    /// Nudges the player when they've been idle on their turn for too long,
+    /// and lets their opponent know when they seem to have wandered off or come back.
+    fn track_presence(&mut self, ui: &mut egui::Ui, can_send: bool) -> Option<PlayerMessage> {
+        let current_time = self.depot.timing.current_time;
+        if ui.input(|i| !i.events.is_empty()) {
+            self.depot.timing.last_interaction = current_time;
+        }
+
+        let idle_threshold = self.depot.timing.idle_threshold?;
+        if !can_send {
+            return None;
+        }
+
+        let idle_since = self
+            .depot
+            .timing
+            .last_interaction
+            .max(self.depot.timing.last_turn_change);
+        let idle_for = current_time.saturating_sub(idle_since);
+
+        if self.depot.ui_state.appears_away {
+            if idle_for < idle_threshold {
+                self.depot.ui_state.appears_away = false;
+                return Some(PlayerMessage::Presence { away: false });
+            }
+            return None;
+        }
+
+        let our_turn =
+            self.depot.gameplay.next_player_number == Some(self.depot.gameplay.player_number);
+        let game_running = self.depot.gameplay.end_state.is_none() && !self.depot.timing.paused;
+        if our_turn && game_running && idle_for >= idle_threshold {
+            self.depot.ui_state.appears_away = true;
+            self.depot.gameplay.error_msg = Some("Still there? It's your turn!".into());
+            return Some(PlayerMessage::Presence { away: true });
+        }
+
+        None
     }
 
     pub fn apply_new_timing(&mut self, state_message: GameStateMessage) {
@@ -270,9 +640,11 @@ impl ActiveGame {
             board: _,
             hand: _,
             changes: _,
+            event_seq: _,
             game_ends_at,
             paused,
             remaining_turns: _,
+            hand_swap_in: _,
         } = state_message;
 
         self.players = players;
@@ -290,9 +662,11 @@ impl ActiveGame {
             board,
             hand: _,
             changes,
+            event_seq,
             game_ends_at,
             paused,
             remaining_turns,
+            hand_swap_in,
         } = state_message;
 
         // assert_eq!(self.room_code, room_code);
@@ -322,6 +696,7 @@ impl ActiveGame {
         self.depot.timing.game_ends_at = game_ends_at;
         self.depot.timing.paused = paused;
         self.depot.gameplay.remaining_turns = remaining_turns;
+        self.depot.gameplay.hand_swap_in = hand_swap_in;
 
         self.depot.gameplay.changes = changes.clone();
 
@@ -334,6 +709,11 @@ impl ActiveGame {
                 .insert(board_change.detail.coordinate, board_change.clone());
         }
 
+        // Once our hand has changed, whether redrawn or played from, it can't be redrawn
+        if changes.iter().any(|c| matches!(c, Change::Hand(_))) {
+            self.depot.gameplay.mulligan_offer = None;
+        }
+
         for hand_change in changes.iter().filter_map(|c| match c {
             Change::Hand(change) => Some(change),
             _ => None,
@@ -375,11 +755,98 @@ impl ActiveGame {
             self.depot.gameplay.last_battle_origin = None;
         }
 
-        self.turn_reports.push(changes);
+        let bag_announcement = changes.iter().find_map(|change| match change {
+            Change::Bag(BagChange {
+                event: BagEvent::Exhausted { refills: false },
+                ..
+            }) => Some("The bag is empty, endgame!"),
+            Change::Bag(BagChange {
+                event: BagEvent::Reshuffled,
+                ..
+            }) => Some("The bag ran out and was reshuffled"),
+            _ => None,
+        });
+
+        self.file_turn_report(event_seq, changes);
 
         // TODO: Verify that our modified hand matches the actual hand in GameStateMessage
 
         self.depot.interactions.playing_tile = None;
-        self.depot.gameplay.error_msg = None;
+        self.depot.interactions.pending_placement = None;
+        self.depot.gameplay.error_msg = bag_announcement.map(str::to_string);
+        // Any ruling from an arbiter has been played on from
+        self.depot.gameplay.arbiter_notice = None;
+    }
+
+    /// Takes on the game as a tournament arbiter has left it, and keeps their ruling on screen
+    /// until the next move is played
+    pub fn apply_arbiter_notice(
+        &mut self,
+        state_message: GameStateMessage,
+        action: ArbiterAction,
+        reason: String,
+    ) {
+        // Annulled moves can give back tiles, so take the whole hand rather than the changes
+        let hand = state_message.hand.clone();
+        self.apply_new_state(state_message);
+        self.hand = hand;
+        self.new_hand_tiles = vec![];
+        self.mapped_hand.invalidate();
+
+        let ruling = match action {
+            ArbiterAction::AdjustClock { player, secs } => {
+                let name = self
+                    .players
+                    .get(player as usize)
+                    .map(|p| p.name.as_str())
+                    .unwrap_or("Unknown");
+                if secs >= 0 {
+                    format!("{secs}s added to {name}'s clock")
+                } else {
+                    format!("{}s taken from {name}'s clock", secs.unsigned_abs())
+                }
+            }
+            action => action.to_string(),
+        };
+        self.depot.gameplay.arbiter_notice = Some((ruling.to_uppercase(), reason));
+    }
+
+    /// The latest turn we have a report for, to catch up from after a dropped connection
+    pub fn last_event_seq(&self) -> u64 {
+        self.turn_report_seqs
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Files a turn's changes amongst the reports in turn order,
+    /// returning false if that turn had already been reported
+    fn file_turn_report(&mut self, seq: u64, changes: Vec<Change>) -> bool {
+        if seq != 0 && self.turn_report_seqs.contains(&seq) {
+            return false;
+        }
+
+        let position = self
+            .turn_report_seqs
+            .iter()
+            .position(|existing| seq != 0 && *existing > seq)
+            .unwrap_or(self.turn_reports.len());
+        self.turn_reports.insert(position, changes);
+        self.turn_report_seqs.insert(position, seq);
+        true
+    }
+
+    /// Adds reports for any turns that went missing while we were disconnected
+    pub fn apply_missed_events(&mut self, events: Vec<SequencedChanges>) {
+        for SequencedChanges { seq, changes } in events {
+            let battle_occurred = changes
+                .iter()
+                .any(|change| matches!(change, Change::Battle(_)));
+
+            if self.file_turn_report(seq, changes) && battle_occurred {
+                self.depot.ui_state.unread_sidebar = true;
+            }
+        }
     }
 }
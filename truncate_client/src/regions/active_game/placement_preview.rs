@@ -0,0 +1,106 @@
+use epaint::{emath::Align2, vec2, Rounding};
+use truncate_core::{
+    game::Game, judge::Outcome, messages::PlayerMessage, moves::Move, rules::Language,
+};
+
+use eframe::egui::{self, Frame, Margin, Order};
+
+use crate::utils::{
+    control_devices::place_from_hand, game_evals::get_dict, text::TextHelper, Lighten,
+};
+
+use super::{swap_preview::render_words, ActiveGame};
+
+impl ActiveGame {
+    /// Shows the words and battles a tile tapped onto the board would make,
+    /// with buttons to play it or put it back. Words are only judged in games played on this device.
+    pub fn render_placement_preview(
+        &mut self,
+        ui: &mut egui::Ui,
+        game_ref: Option<&Game>,
+    ) -> Option<PlayerMessage> {
+        let coord = self.depot.interactions.pending_square()?;
+        let (_, tile) = self.depot.interactions.selected_tile_in_hand?;
+        let mut msg = None;
+
+        let dict_lock = get_dict(game_ref.map_or(Language::English, |game| game.rules.language));
+        let dict = dict_lock.as_ref();
+        let preview = game_ref.and_then(|game| {
+            game.preview_move(
+                Move::Place {
+                    player: self.depot.gameplay.player_number as usize,
+                    tile,
+                    position: coord,
+                },
+                dict,
+                dict,
+            )
+            .ok()
+        });
+
+        let theme = &self.depot.aesthetics.theme;
+        let map_texture = &self.depot.aesthetics.map_texture;
+        let mut confirmed = false;
+        let mut cancelled = false;
+
+        egui::Area::new(egui::Id::new("placement_preview_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_TOP, vec2(0.0, ui.max_rect().top() + 10.0))
+            .show(ui.ctx(), |ui| {
+                Frame::none()
+                    .fill(theme.water.lighten())
+                    .rounding(Rounding::same(theme.rounding))
+                    .inner_margin(Margin::same(10.0))
+                    .show(ui, |ui| {
+                        ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                            if let Some(preview) = &preview {
+                                render_words(&preview.after, ui, theme);
+
+                                ui.add_space(6.0);
+                                let battles = if preview.battles.is_empty() {
+                                    "NO BATTLES".to_string()
+                                } else {
+                                    let won = preview
+                                        .battles
+                                        .iter()
+                                        .filter(|b| matches!(b.outcome, Outcome::AttackerWins(_)))
+                                        .count();
+                                    format!("{} BATTLE(S), {won} WON", preview.battles.len())
+                                };
+                                TextHelper::heavy(&battles, 10.0, None, ui)
+                                    .paint(theme.text, ui, true);
+                                ui.add_space(8.0);
+                            }
+
+                            ui.horizontal(|ui| {
+                                if TextHelper::heavy(&format!("PLAY {tile}"), 12.0, None, ui)
+                                    .button(theme.button_primary, theme.text, map_texture, ui)
+                                    .clicked()
+                                {
+                                    confirmed = true;
+                                }
+                                if TextHelper::heavy("CANCEL", 12.0, None, ui)
+                                    .button(theme.button_secondary, theme.text, map_texture, ui)
+                                    .clicked()
+                                {
+                                    cancelled = true;
+                                }
+                            });
+                        });
+                    });
+            });
+
+        let interactions = &mut self.depot.interactions;
+        if confirmed {
+            msg = place_from_hand(interactions, coord, tile);
+            interactions.selected_tile_in_hand = None;
+            interactions.selected_square_on_board = None;
+        }
+        if confirmed || cancelled {
+            interactions.pending_placement = None;
+        }
+
+        msg
+    }
+}
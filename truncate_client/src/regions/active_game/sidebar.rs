@@ -1,6 +1,9 @@
 use epaint::{emath::Align2, vec2, FontId, Vec2};
 
-use truncate_core::{messages::PlayerMessage, reporting::Change};
+use truncate_core::{
+    messages::{ChatChannel, PlayerMessage},
+    reporting::Change,
+};
 
 use eframe::{
     egui::{self, CursorIcon, Layout, Order, ScrollArea, Sense},
@@ -8,18 +11,33 @@ use eframe::{
 };
 
 use crate::{
-    lil_bits::BattleUI,
-    utils::tex::{render_tex_quad, tiles},
+    lil_bits::{BattleUI, TimerUI},
+    utils::{
+        connection::describe_latency,
+        tex::{render_tex_quad, tiles},
+        text::TextHelper,
+    },
 };
 
-use super::ActiveGame;
+use super::{ActiveGame, GameLocation, SidebarTab};
 
 impl ActiveGame {
     pub fn render_sidebar(&mut self, ui: &mut egui::Ui) -> Option<PlayerMessage> {
-        if self.depot.ui_state.sidebar_hidden || !self.depot.ui_state.sidebar_toggled {
+        // Only stays set if the chat is still on screen to claim the keyboard
+        self.depot.ui_state.chat_focused = false;
+
+        if self.depot.ui_state.sidebar_hidden
+            || !(self.depot.ui_state.sidebar_toggled || self.depot.ui_state.hand_docked)
+        {
             return None;
         }
 
+        let mut msg = None;
+        let has_chat = matches!(self.location, GameLocation::Online);
+        if !has_chat {
+            self.depot.ui_state.sidebar_tab = SidebarTab::Battles;
+        }
+
         let area = egui::Area::new(egui::Id::new("sidebar_layer"))
             .movable(false)
             .order(Order::Foreground)
@@ -65,14 +83,57 @@ impl ActiveGame {
                     ui.add_space(10.0);
                 }
 
+                if has_chat {
+                    ui.horizontal(|ui| {
+                        for (label, tab) in [
+                            ("BATTLES", SidebarTab::Battles),
+                            ("CHAT", SidebarTab::Chat(ChatChannel::Players)),
+                            ("OBSERVERS", SidebarTab::Chat(ChatChannel::Observers)),
+                        ] {
+                            let color = if self.depot.ui_state.sidebar_tab == tab {
+                                self.depot.aesthetics.theme.button_primary
+                            } else {
+                                self.depot.aesthetics.theme.button_secondary
+                            };
+                            if TextHelper::heavy(label, 10.0, None, ui)
+                                .button(
+                                    color,
+                                    self.depot.aesthetics.theme.text,
+                                    &self.depot.aesthetics.map_texture,
+                                    ui,
+                                )
+                                .clicked()
+                            {
+                                self.depot.ui_state.sidebar_tab = tab;
+                            }
+                        }
+                    });
+                    ui.add_space(10.0);
+
+                    // Helps tell a slow opponent apart from a slow connection
+                    if let Some(latency_ms) = self.depot.gameplay.latency_ms {
+                        TextHelper::light(&describe_latency(latency_ms), 10.0, None, ui).paint(
+                            self.depot.aesthetics.theme.text,
+                            ui,
+                            false,
+                        );
+                        ui.add_space(10.0);
+                    }
+                }
+
                 ui.with_layout(Layout::bottom_up(Align::LEFT), |ui| {
                     ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
                         ScrollArea::new([false, true]).show(ui, |ui| {
                             // Small hack to fill the scroll area
                             ui.allocate_at_least(vec2(ui.available_width(), 1.0), Sense::hover());
 
+                            if let SidebarTab::Chat(channel) = self.depot.ui_state.sidebar_tab {
+                                msg = self.render_chat(ui, channel);
+                                return;
+                            }
+
                             let room = ui.painter().layout_no_wrap(
-                                "Battles".into(),
+                                "Moves".into(),
                                 FontId::new(
                                     self.depot.aesthetics.theme.letter_size / 2.0,
                                     egui::FontFamily::Name("Truncate-Heavy".into()),
@@ -85,6 +146,25 @@ impl ActiveGame {
                             ui.add_space(15.0);
 
                             for turn in self.turn_reports.iter().rev() {
+                                if let Some(think) = turn.iter().find_map(|change| match change {
+                                    Change::Think(think) => Some(think),
+                                    _ => None,
+                                }) {
+                                    let name = self
+                                        .players
+                                        .get(think.player)
+                                        .map(|p| p.name.as_str())
+                                        .unwrap_or("Unknown");
+                                    let line = format!(
+                                        "Turn {}: {name} took {}",
+                                        think.turn,
+                                        TimerUI::human_time(think.secs as i64, true)
+                                    );
+                                    TextHelper::light(&line, 10.0, Some(ui.available_width()), ui)
+                                        .paint(self.depot.aesthetics.theme.text, ui, false);
+                                    ui.add_space(8.0);
+                                }
+
                                 for battle in turn.iter().filter_map(|change| match change {
                                     Change::Battle(battle) => Some(battle),
                                     _ => None,
@@ -100,6 +180,6 @@ impl ActiveGame {
             });
         });
 
-        None
+        msg
     }
 }
@@ -0,0 +1,120 @@
+use epaint::{emath::Align2, vec2, Rounding};
+use truncate_core::{
+    board::Coordinate, game::Game, judge::Outcome, moves::Move, reporting::BattleWord,
+};
+
+use eframe::{
+    egui::{self, Frame, Layout, Margin, Order},
+    emath::Align,
+};
+
+use crate::utils::{game_evals::get_dict, text::TextHelper, Lighten, Theme};
+
+use super::ActiveGame;
+
+impl ActiveGame {
+    /// The two tiles the player is lining up to swap, before they let go or click
+    fn pending_swap(&self) -> Option<[Coordinate; 2]> {
+        let interactions = &self.depot.interactions;
+
+        let (from, to) = if let Some((from, _)) = interactions.dragging_tile_on_board {
+            let to = interactions
+                .hovered_occupied_square_on_board
+                .and_then(|region| region.coord)?;
+            (from, to)
+        } else {
+            let (from, _) = interactions.selected_tile_on_board?;
+            let (to, _) = interactions.hovered_tile_on_board?;
+            (from, to)
+        };
+
+        (from != to).then_some([from, to])
+    }
+
+    /// Shows the words on either side of a pending swap, and any battles it would start.
+    /// This needs the full game to judge against, so only appears in games played on this device.
+    pub fn render_swap_preview(&mut self, ui: &mut egui::Ui, game_ref: Option<&Game>) {
+        let Some(game) = game_ref else {
+            return;
+        };
+        let Some(positions) = self.pending_swap() else {
+            return;
+        };
+
+        let dict_lock = get_dict(game.rules.language);
+        let dict = dict_lock.as_ref();
+        let Ok(preview) = game.preview_move(
+            Move::Swap {
+                player: self.depot.gameplay.player_number as usize,
+                positions,
+            },
+            dict,
+            dict,
+        ) else {
+            // Swaps that can't be made will be explained by the error when they're attempted
+            return;
+        };
+
+        let theme = &self.depot.aesthetics.theme;
+        let column_width = 120.0;
+
+        egui::Area::new(egui::Id::new("swap_preview_layer"))
+            .movable(false)
+            .interactable(false)
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_TOP, vec2(0.0, ui.max_rect().top() + 10.0))
+            .show(ui.ctx(), |ui| {
+                Frame::none()
+                    .fill(theme.water.lighten())
+                    .rounding(Rounding::same(theme.rounding))
+                    .inner_margin(Margin::same(10.0))
+                    .show(ui, |ui| {
+                        ui.horizontal_top(|ui| {
+                            for (heading, words) in
+                                [("BEFORE", &preview.before), ("AFTER", &preview.after)]
+                            {
+                                ui.allocate_ui_with_layout(
+                                    vec2(column_width, 0.0),
+                                    Layout::top_down(Align::Center),
+                                    |ui| {
+                                        TextHelper::heavy(heading, 10.0, None, ui)
+                                            .paint(theme.text, ui, true);
+                                        ui.add_space(4.0);
+                                        render_words(words, ui, theme);
+                                    },
+                                );
+                            }
+                        });
+
+                        ui.add_space(6.0);
+                        let battles = if preview.battles.is_empty() {
+                            "NO BATTLES".to_string()
+                        } else {
+                            let won = preview
+                                .battles
+                                .iter()
+                                .filter(|b| matches!(b.outcome, Outcome::AttackerWins(_)))
+                                .count();
+                            format!("{} BATTLE(S), {won} WON", preview.battles.len())
+                        };
+                        TextHelper::heavy(&battles, 10.0, None, ui).paint(theme.text, ui, true);
+                    });
+            });
+    }
+}
+
+pub(super) fn render_words(words: &[BattleWord], ui: &mut egui::Ui, theme: &Theme) {
+    if words.is_empty() {
+        TextHelper::light("NO WORDS", 12.0, None, ui).paint(theme.text, ui, true);
+        return;
+    }
+
+    for word in words {
+        let color = match word.valid {
+            Some(true) => theme.word_valid,
+            Some(false) => theme.word_invalid,
+            None => theme.text,
+        };
+        TextHelper::heavy(&word.resolved_word, 12.0, None, ui).paint(color, ui, true);
+    }
+}
@@ -0,0 +1,36 @@
+use truncate_core::board::Square;
+
+use super::{ActiveGame, GameLocation};
+
+impl ActiveGame {
+    /// While hovering one of our own tiles, highlights every tile that would be truncated
+    /// if the opponent took that one, so weak links show up before they're exploited.
+    pub fn preview_truncation(&mut self) {
+        // The tutorial drives these highlights itself
+        if matches!(self.location, GameLocation::Tutorial) {
+            return;
+        }
+
+        let player = self.depot.gameplay.player_number as usize;
+        let hovered = self
+            .depot
+            .interactions
+            .hovered_tile_on_board
+            .filter(|_| self.depot.ui_state.truncation_preview)
+            .and_then(|(coord, square)| match square {
+                Square::Occupied { player: p, .. } if p == player => Some(coord),
+                _ => None,
+            });
+
+        self.depot.interactions.highlight_squares = hovered.and_then(|coord| {
+            let mut stranded: Vec<_> = self
+                .board
+                .cut_tiles(player)
+                .remove(&coord)?
+                .into_iter()
+                .collect();
+            stranded.sort();
+            Some(stranded)
+        });
+    }
+}
@@ -0,0 +1,161 @@
+use eframe::egui::{self, Layout, Sense};
+use epaint::{emath::Align, vec2, Color32, TextureHandle};
+use instant::Duration;
+use truncate_core::{
+    board::Board,
+    messages::{PlayerMessage, TruncateToken},
+    npc::{forced_wins::ForcedWinPuzzle, progression::puzzle_opponent},
+    rules::Language,
+};
+
+use crate::{
+    app_outer::{Backchannel, EventDispatcher},
+    utils::{text::TextHelper, urls::back_to_menu, Theme},
+};
+
+use super::{active_game::HeaderType, single_player::SinglePlayerState};
+
+const PUZZLES: &[u8] = include_bytes!("../../../truncate_dueller/puzzles.yml");
+
+/// Puzzles found by the dueller, where the player has to force a win against the NPC's best defence
+pub struct ForcedWinsState {
+    puzzles: Vec<ForcedWinPuzzle>,
+    player_token: Option<TruncateToken>,
+    event_dispatcher: EventDispatcher,
+    /// The puzzle being played, by index
+    active: Option<(usize, SinglePlayerState)>,
+}
+
+impl ForcedWinsState {
+    pub fn new(player_token: Option<TruncateToken>, event_dispatcher: EventDispatcher) -> Self {
+        let puzzles =
+            serde_yaml::from_slice(PUZZLES).expect("Puzzle file should match the puzzle format");
+
+        Self {
+            puzzles,
+            player_token,
+            event_dispatcher,
+            active: None,
+        }
+    }
+
+    fn start_puzzle(
+        &mut self,
+        index: usize,
+        ctx: &egui::Context,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+    ) {
+        let Some(puzzle) = self.puzzles.get(index).cloned() else {
+            return;
+        };
+
+        let mut game = SinglePlayerState::new(
+            "forced_win".to_string(),
+            ctx,
+            map_texture.clone(),
+            theme.clone(),
+            Board::new(9, 9),
+            None,
+            puzzle.rules_generation,
+            Language::English,
+            true,
+            HeaderType::Summary {
+                title: format!("Win in {} #{}", puzzle.turns, index + 1),
+                attempt: None,
+            },
+            puzzle_opponent(None),
+            self.event_dispatcher.clone(),
+        );
+        game.set_forced_win(puzzle, ctx);
+
+        self.active = Some((index, game));
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        current_time: Duration,
+        backchannel: &Backchannel,
+    ) -> Vec<PlayerMessage> {
+        if let Some((index, game)) = &mut self.active {
+            let index = *index;
+            let (top_banner, _) =
+                ui.allocate_at_least(vec2(ui.available_width(), 40.0), Sense::hover());
+            let mut banner_ui = ui.child_ui(top_banner, Layout::left_to_right(Align::Center));
+            let back = TextHelper::heavy("BACK TO PUZZLES", 12.0, None, ui)
+                .centered_button(
+                    theme.button_primary,
+                    theme.text,
+                    map_texture,
+                    &mut banner_ui,
+                )
+                .clicked();
+            let next = game.human_won()
+                && index + 1 < self.puzzles.len()
+                && TextHelper::heavy("NEXT PUZZLE", 12.0, None, ui)
+                    .centered_button(
+                        theme.button_primary,
+                        theme.text,
+                        map_texture,
+                        &mut banner_ui,
+                    )
+                    .clicked();
+
+            let msgs = game.render(ui, theme, current_time, backchannel, &self.player_token);
+
+            if back {
+                self.active = None;
+            } else if next {
+                self.start_puzzle(index + 1, ui.ctx(), theme, map_texture);
+            }
+            return msgs;
+        }
+
+        let mut play = None;
+
+        ui.add_space(40.0);
+        TextHelper::heavy("PUZZLES", 14.0, None, ui).paint(Color32::WHITE, ui, true);
+        ui.add_space(8.0);
+        TextHelper::light(
+            "Every hand is in view and nobody draws. Force a win, however the computer defends.",
+            14.0,
+            Some(ui.available_width() - 20.0),
+            ui,
+        )
+        .paint(Color32::WHITE, ui, true);
+        ui.add_space(20.0);
+
+        for (index, puzzle) in self.puzzles.iter().enumerate() {
+            let label = format!("#{}: WIN IN {}", index + 1, puzzle.turns);
+            if TextHelper::heavy(&label, 12.0, None, ui)
+                .centered_button(theme.button_primary, theme.text, map_texture, ui)
+                .clicked()
+            {
+                play = Some(index);
+            }
+            ui.add_space(10.0);
+        }
+
+        ui.add_space(20.0);
+        if TextHelper::heavy("BACK TO MENU", 12.0, None, ui)
+            .centered_button(theme.button_primary, theme.text, map_texture, ui)
+            .clicked()
+        {
+            back_to_menu();
+        }
+
+        if let Some(index) = play {
+            self.start_puzzle(index, ui.ctx(), theme, map_texture);
+        }
+
+        vec![]
+    }
+
+    /// Word definitions from the server belong to whichever puzzle is being played
+    pub fn active_game(&mut self) -> Option<&mut SinglePlayerState> {
+        self.active.as_mut().map(|(_, game)| game)
+    }
+}
@@ -0,0 +1,199 @@
+use eframe::egui::{self, Order, ScrollArea};
+use epaint::{emath::Align2, vec2, Color32, TextureHandle};
+use truncate_core::{
+    game::{GAME_COLOR_BLUE, GAME_COLOR_RED},
+    messages::{GalleryBoard, PlayerMessage, TruncateToken},
+};
+
+use crate::{
+    lil_bits::BoardThumbnailUI,
+    utils::{text::TextHelper, urls::back_to_menu, Diaphanize, Theme},
+};
+
+const THUMBNAIL_SIZE: f32 = 96.0;
+
+/// Browses the community boards that moderators have let into the gallery
+pub struct GalleryState {
+    pub boards: Option<Vec<GalleryBoard>>,
+    player_colors: Vec<Color32>,
+}
+
+impl GalleryState {
+    pub fn new() -> Self {
+        Self {
+            boards: None,
+            player_colors: [GAME_COLOR_BLUE, GAME_COLOR_RED]
+                .into_iter()
+                .map(|(r, g, b)| Color32::from_rgb(r, g, b))
+                .collect(),
+        }
+    }
+
+    /// Returns the ID of a board the player picked to start a room with
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+    ) -> Option<String> {
+        let mut picked = None;
+
+        ScrollArea::new([false, true]).show(ui, |ui| {
+            ui.add_space(40.0);
+            TextHelper::heavy("BOARD GALLERY", 14.0, None, ui).paint(Color32::WHITE, ui, true);
+            ui.add_space(20.0);
+
+            match &self.boards {
+                None => {
+                    TextHelper::light("Loading boards", 14.0, None, ui).paint(
+                        Color32::WHITE,
+                        ui,
+                        true,
+                    );
+                }
+                Some(boards) if boards.is_empty() => {
+                    TextHelper::light("Nobody has shared a board yet", 14.0, None, ui).paint(
+                        Color32::WHITE,
+                        ui,
+                        true,
+                    );
+                }
+                Some(boards) => {
+                    for gallery_board in boards {
+                        ui.horizontal(|ui| {
+                            ui.add_space(20.0);
+                            let thumbnail =
+                                BoardThumbnailUI::new(&gallery_board.board, &self.player_colors)
+                                    .render(THUMBNAIL_SIZE, ui, theme);
+                            ui.add_space(12.0);
+
+                            let play = ui
+                                .vertical(|ui| {
+                                    TextHelper::heavy(
+                                        &gallery_board.name.to_uppercase(),
+                                        12.0,
+                                        None,
+                                        ui,
+                                    )
+                                    .paint(
+                                        Color32::WHITE,
+                                        ui,
+                                        false,
+                                    );
+                                    TextHelper::light(
+                                        &summarize(gallery_board),
+                                        12.0,
+                                        Some(ui.available_width() - 20.0),
+                                        ui,
+                                    )
+                                    .paint(
+                                        Color32::WHITE.diaphanize(),
+                                        ui,
+                                        false,
+                                    );
+                                    ui.add_space(6.0);
+                                    TextHelper::heavy("PLAY", 10.0, None, ui)
+                                        .button(theme.button_primary, theme.text, map_texture, ui)
+                                        .clicked()
+                                })
+                                .inner;
+
+                            if play || thumbnail.clicked() {
+                                picked = Some(gallery_board.id.clone());
+                            }
+                        });
+                        ui.add_space(16.0);
+                    }
+                }
+            }
+
+            ui.add_space(20.0);
+            if TextHelper::heavy("BACK TO MENU", 12.0, None, ui)
+                .centered_button(theme.button_primary, theme.text, map_texture, ui)
+                .clicked()
+            {
+                back_to_menu();
+            }
+        });
+
+        picked
+    }
+}
+
+fn summarize(gallery_board: &GalleryBoard) -> String {
+    let author = gallery_board.author.as_deref().unwrap_or("Anonymous");
+    let plays = match gallery_board.plays {
+        1 => "1 play".to_string(),
+        plays => format!("{plays} plays"),
+    };
+    match gallery_board.rating {
+        Some(rating) => format!(
+            "By {author}, {plays}, rated {rating:.1} by {}",
+            gallery_board.ratings
+        ),
+        None => format!("By {author}, {plays}, not yet rated"),
+    }
+}
+
+/// Asks for a rating once a game on a gallery board has finished
+pub struct GalleryRating {
+    board_id: String,
+    name: String,
+    stars: Option<u8>,
+}
+
+impl GalleryRating {
+    pub fn new(board_id: String, name: String) -> Self {
+        Self {
+            board_id,
+            name,
+            stars: None,
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        player_token: &TruncateToken,
+    ) -> Option<PlayerMessage> {
+        let mut msg = None;
+
+        let area = egui::Area::new(egui::Id::new("gallery_rating_layer"))
+            .movable(false)
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_BOTTOM, vec2(0.0, -12.0));
+
+        area.show(ui.ctx(), |ui| {
+            let label = match self.stars {
+                Some(_) => format!("THANKS FOR RATING {}", self.name.to_uppercase()),
+                None => format!("RATE {}", self.name.to_uppercase()),
+            };
+            TextHelper::heavy(&label, 10.0, None, ui).paint(Color32::WHITE, ui, true);
+
+            ui.horizontal(|ui| {
+                for stars in 1..=5 {
+                    let color = if self.stars.is_some_and(|rated| stars <= rated) {
+                        theme.gold_medal
+                    } else {
+                        Color32::WHITE.diaphanize()
+                    };
+                    if TextHelper::heavy(&stars.to_string(), 12.0, None, ui)
+                        .button(color, theme.text, map_texture, ui)
+                        .clicked()
+                    {
+                        self.stars = Some(stars);
+                        msg = Some(PlayerMessage::RateBoard {
+                            player_token: player_token.clone(),
+                            board_id: self.board_id.clone(),
+                            stars,
+                        });
+                    }
+                }
+            });
+        });
+
+        msg
+    }
+}
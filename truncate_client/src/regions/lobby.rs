@@ -5,16 +5,25 @@ use epaint::{
 
 use truncate_core::{
     board::Board,
-    generation::BoardSeed,
-    messages::{LobbyPlayerMessage, PlayerMessage, RoomCode},
+    generation::{generate_board, BoardSeed},
+    messages::{
+        FairPlayRecord, LobbyPlayerMessage, PlayerMessage, RoomCode, RoomRole, SavedBoard,
+        TruncateToken,
+    },
+    packs::DictionaryPack,
+    rules::{
+        GameRules, HandSwaps, Language, Mulligan, RulesPreset, RulesWarning, Spelling, Terrain,
+        Tiebreaker, Truncation, TurnEconomy,
+    },
 };
 
 use eframe::egui::{self, Layout, Order, RichText, ScrollArea};
 
 use crate::{
-    lil_bits::EditorUI,
+    lil_bits::{EditorGuideAction, EditorGuideState, EditorUI},
     utils::{
         depot::{AestheticDepot, TimingDepot},
+        macros::current_time,
         mapper::MappedBoard,
         text::TextHelper,
         Diaphanize, Lighten, Theme,
@@ -29,6 +38,12 @@ pub enum BoardEditingMode {
     Artifact(usize),
 }
 
+/// The spectator delays a lobby can cycle through, in seconds
+const SPECTATOR_DELAYS: [u64; 4] = [0, 30, 120, 300];
+/// The hand swap intervals a lobby can cycle through, in turns
+const HAND_SWAP_INTERVALS: [u32; 4] = [0, 6, 10, 16];
+const BLANK_TILE_COUNTS: [usize; 4] = [0, 2, 4, 8];
+
 #[derive(Clone)]
 pub struct Lobby {
     pub board: Board,
@@ -38,9 +53,37 @@ pub struct Lobby {
     pub player_index: u64,
     pub mapped_board: MappedBoard,
     pub editing_mode: BoardEditingMode,
+    /// Shown alongside the editor until the player has been through it once
+    pub editor_guide: Option<EditorGuideState>,
     pub copied_code: bool,
     pub aesthetics: AestheticDepot,
     pub timing: TimingDepot,
+    /// How far behind spectators watch, for online rooms that can have them
+    pub spectator_delay_secs: Option<u64>,
+    /// The rules the room will be played with, for online rooms
+    pub rules: Option<GameRules>,
+    /// Anything odd about those rules for the players in the room, kept up to date by the server
+    pub rules_warnings: Vec<RulesWarning>,
+    /// How many turns pass between hand swaps, for online rooms. Zero when hands aren't swapped.
+    pub hand_swap_turns: Option<u32>,
+    /// Whether close misspellings are accepted, for online rooms
+    pub lenient_spelling: Option<bool>,
+    /// How many blank tiles are in the bag, for online rooms
+    pub blank_tiles: Option<usize>,
+    /// The language the room is played in, once the server offers more than one
+    pub language: Option<Language>,
+    pub available_languages: Vec<Language>,
+    /// Word lists the server offers on top of its languages' dictionaries
+    pub available_packs: Vec<DictionaryPack>,
+    /// Set when logged in, so that boards can be saved to the player's account
+    pub player_token: Option<TruncateToken>,
+    pub saved_boards: Vec<SavedBoard>,
+    /// The slot the current board will be saved into
+    pub saving_as: String,
+    /// How reliably the logged in player finishes their games
+    pub fair_play: Option<FairPlayRecord>,
+    /// The player's explanation for leaving a game, if they're appealing one
+    pub appeal_reason: String,
 }
 
 impl Lobby {
@@ -74,12 +117,38 @@ impl Lobby {
             player_index,
             board,
             editing_mode: BoardEditingMode::None,
+            editor_guide: None,
             copied_code: false,
             aesthetics,
             timing: TimingDepot::default(),
+            spectator_delay_secs: None,
+            rules: None,
+            rules_warnings: vec![],
+            hand_swap_turns: None,
+            lenient_spelling: None,
+            blank_tiles: None,
+            language: None,
+            available_languages: vec![],
+            available_packs: vec![],
+            player_token: None,
+            saved_boards: vec![],
+            saving_as: String::new(),
+            fair_play: None,
+            appeal_reason: String::new(),
         }
     }
 
+    /// Takes on the room's rules, along with the settings that are picked separately from them
+    pub fn update_rules(&mut self, rules: GameRules) {
+        self.hand_swap_turns = Some(match rules.hand_swaps {
+            HandSwaps::None => 0,
+            HandSwaps::Every { turns } => turns,
+        });
+        self.lenient_spelling = Some(matches!(rules.battle_rules.spelling, Spelling::Lenient));
+        self.blank_tiles = Some(rules.blank_tiles);
+        self.rules = Some(rules);
+    }
+
     pub fn update_board(&mut self, board: Board, ui: &mut egui::Ui) {
         self.mapped_board.remap_texture(
             &ui.ctx(),
@@ -92,8 +161,31 @@ impl Lobby {
         self.board = board;
     }
 
+    /// This player's role in the room, which decides which setup controls they're shown
+    pub fn role(&self) -> RoomRole {
+        self.players
+            .get(self.player_index as usize)
+            .map(|p| p.role)
+            .unwrap_or_default()
+    }
+
+    /// Replaces the board with a freshly generated one, for the editor guide's starting point
+    fn generate_starting_board(&mut self, ctx: &egui::Context) {
+        let seed = (current_time!().as_micros() % 243985691) as u32;
+        let board_seed = BoardSeed::new(seed);
+        let board = generate_board(board_seed.clone())
+            .expect("Common seeds can be reasonably expected to produce a board")
+            .board;
+
+        self.mapped_board
+            .remap_texture(ctx, &self.aesthetics, &self.timing, None, None, &board);
+        self.board = board;
+        self.board_seed = Some(board_seed);
+    }
+
     pub fn render_lobby(&mut self, ui: &mut egui::Ui, theme: &Theme) -> Option<PlayerMessage> {
         let mut msg = None;
+        let role = self.role();
 
         let area = egui::Area::new(egui::Id::new("lobby_sidebar_layer"))
             .movable(false)
@@ -150,23 +242,30 @@ impl Lobby {
                         }
                     }
 
-                    let start_button_color = if self.players.len() > 1 {
-                        theme.button_primary
-                    } else {
-                        theme.text.lighten().lighten()
-                    };
+                    if role.can_manage() {
+                        let start_button_color = if self.players.len() > 1 {
+                            theme.button_primary
+                        } else {
+                            theme.text.lighten().lighten()
+                        };
 
-                    let text = TextHelper::heavy("START GAME", 14.0, None, ui);
-                    if text
-                        .full_button(
-                            start_button_color,
-                            theme.text,
-                            &self.aesthetics.map_texture,
-                            ui,
-                        )
-                        .clicked()
-                    {
-                        msg = Some(PlayerMessage::StartGame);
+                        let text = TextHelper::heavy("START GAME", 14.0, None, ui);
+                        if text
+                            .full_button(
+                                start_button_color,
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            msg = Some(PlayerMessage::StartGame);
+                        }
+                    } else {
+                        ui.label(
+                            RichText::new("Waiting for the host to start the game")
+                                .color(Color32::WHITE),
+                        );
                     }
 
                     ui.add_space(12.0);
@@ -221,25 +320,602 @@ impl Lobby {
                                 egui::FontFamily::Name("Truncate-Heavy".into()),
                             ),
                         ));
+                        if let Some(role_msg) = self.render_role_controls(player, role, ui, theme) {
+                            msg = Some(role_msg);
+                        }
                     }
 
                     ui.add_space(32.0);
 
-                    let text = TextHelper::heavy("EDIT BOARD", 10.0, None, ui);
+                    if let Some(delay_secs) =
+                        self.spectator_delay_secs.filter(|_| role.can_manage())
+                    {
+                        let label = match delay_secs {
+                            0 => "SPECTATOR DELAY: OFF".to_string(),
+                            secs if secs < 60 => format!("SPECTATOR DELAY: {secs}S"),
+                            secs => format!("SPECTATOR DELAY: {}MIN", secs / 60),
+                        };
+                        let text = TextHelper::heavy(&label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let next_delay = SPECTATOR_DELAYS
+                                .iter()
+                                .copied()
+                                .find(|d| *d > delay_secs)
+                                .unwrap_or(0);
+                            msg = Some(PlayerMessage::SetSpectatorDelay(next_delay));
+                        }
+                    }
+
+                    if let Some(rules) = self.rules.as_ref().filter(|_| role.can_manage()) {
+                        let preset = RulesPreset::matching(rules);
+                        let label = match preset {
+                            Some(preset) => format!("RULES: {}", preset.to_string().to_uppercase()),
+                            None => "RULES: CUSTOM".to_string(),
+                        };
+                        let text = TextHelper::heavy(&label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let next_preset = RulesPreset::ALL
+                                .iter()
+                                .skip_while(|p| Some(**p) != preset)
+                                .nth(1)
+                                .copied()
+                                .unwrap_or(RulesPreset::Classic);
+                            msg = Some(PlayerMessage::SetRules(next_preset.rules()));
+                        }
+
+                        let label = match rules.turn_economy {
+                            TurnEconomy::SingleAction => "TURNS: ONE ACTION",
+                            TurnEconomy::ActionPoints { .. } => "TURNS: ACTION POINTS",
+                        };
+                        let text = TextHelper::heavy(label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let mut next_rules = rules.clone();
+                            next_rules.turn_economy = match rules.turn_economy {
+                                TurnEconomy::SingleAction => TurnEconomy::action_points(),
+                                TurnEconomy::ActionPoints { .. } => TurnEconomy::SingleAction,
+                            };
+                            msg = Some(PlayerMessage::SetRules(next_rules));
+                        }
+
+                        let label = match rules.terrain {
+                            Terrain::Flat => "TERRAIN: FLAT",
+                            Terrain::Elevated => "TERRAIN: HIGH GROUND",
+                        };
+                        let text = TextHelper::heavy(label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let mut next_rules = rules.clone();
+                            next_rules.terrain = match rules.terrain {
+                                Terrain::Flat => Terrain::Elevated,
+                                Terrain::Elevated => Terrain::Flat,
+                            };
+                            msg = Some(PlayerMessage::SetRules(next_rules));
+                        }
+
+                        let label = if rules.tiebreakers.is_empty() {
+                            "DRAWS: STAND"
+                        } else {
+                            "DRAWS: TIEBREAKS"
+                        };
+                        let text = TextHelper::heavy(label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let mut next_rules = rules.clone();
+                            next_rules.tiebreakers = if rules.tiebreakers.is_empty() {
+                                Tiebreaker::ALL.to_vec()
+                            } else {
+                                Vec::new()
+                            };
+                            msg = Some(PlayerMessage::SetRules(next_rules));
+                        }
+
+                        let label = match rules.mulligan {
+                            Mulligan::None => "MULLIGANS: OFF",
+                            Mulligan::NoVowels => "MULLIGANS: NO VOWELS",
+                            Mulligan::Entropy { .. } => "MULLIGANS: POOR HANDS",
+                        };
+                        let text = TextHelper::heavy(label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let mut next_rules = rules.clone();
+                            next_rules.mulligan = match rules.mulligan {
+                                Mulligan::None => Mulligan::NoVowels,
+                                Mulligan::NoVowels => Mulligan::entropy(),
+                                Mulligan::Entropy { .. } => Mulligan::None,
+                            };
+                            msg = Some(PlayerMessage::SetRules(next_rules));
+                        }
+
+                        let truncation_label = match rules.truncation {
+                            Truncation::Root => Some("TRUNCATION: INSTANT"),
+                            Truncation::Withering => Some("TRUNCATION: WITHERING"),
+                            Truncation::None | Truncation::Larger => None,
+                        };
+                        if let Some(label) = truncation_label {
+                            let text = TextHelper::heavy(label, 10.0, None, ui);
+                            if text
+                                .button(
+                                    Color32::WHITE.diaphanize(),
+                                    theme.text,
+                                    &self.aesthetics.map_texture,
+                                    ui,
+                                )
+                                .clicked()
+                            {
+                                let mut next_rules = rules.clone();
+                                next_rules.truncation = match rules.truncation {
+                                    Truncation::Withering => Truncation::Root,
+                                    _ => Truncation::Withering,
+                                };
+                                msg = Some(PlayerMessage::SetRules(next_rules));
+                            }
+                        }
+                    }
+
+                    // Everyone sees these, so they know what they're in for before the game starts
+                    for warning in &self.rules_warnings {
+                        ui.label(RichText::new(warning.to_string()).color(Color32::YELLOW));
+                    }
+
+                    if let Some(swap_turns) = self.hand_swap_turns.filter(|_| role.can_manage()) {
+                        let label = match swap_turns {
+                            0 => "HAND SWAPS: OFF".to_string(),
+                            turns => format!("HAND SWAPS: EVERY {turns} TURNS"),
+                        };
+                        let text = TextHelper::heavy(&label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let next_turns = HAND_SWAP_INTERVALS
+                                .iter()
+                                .copied()
+                                .find(|t| *t > swap_turns)
+                                .unwrap_or(0);
+                            msg = Some(PlayerMessage::SetHandSwaps(next_turns));
+                        }
+                    }
+
+                    if let Some(lenient) = self.lenient_spelling.filter(|_| role.can_manage()) {
+                        let label = if lenient {
+                            "SPELLING: LENIENT (FOR KIDS)"
+                        } else {
+                            "SPELLING: STRICT"
+                        };
+                        let text = TextHelper::heavy(label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            msg = Some(PlayerMessage::SetLenientSpelling(!lenient));
+                        }
+                    }
+
+                    if let Some(blank_tiles) = self.blank_tiles.filter(|_| role.can_manage()) {
+                        let label = match blank_tiles {
+                            0 => "BLANK TILES: NONE".to_string(),
+                            count => format!("BLANK TILES: {count}"),
+                        };
+                        let text = TextHelper::heavy(&label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let next_count = BLANK_TILE_COUNTS
+                                .iter()
+                                .copied()
+                                .find(|c| *c > blank_tiles)
+                                .unwrap_or(0);
+                            msg = Some(PlayerMessage::SetBlankTiles(next_count));
+                        }
+                    }
+
+                    if let Some(language) = self.language.filter(|_| role.can_manage()) {
+                        let label = format!("LANGUAGE: {}", language.to_string().to_uppercase());
+                        let text = TextHelper::heavy(&label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            let next_language = self
+                                .available_languages
+                                .iter()
+                                .skip_while(|l| **l != language)
+                                .nth(1)
+                                .or(self.available_languages.first())
+                                .copied()
+                                .unwrap_or(language);
+                            msg = Some(PlayerMessage::SetLanguage(next_language));
+                        }
+                    }
+
+                    if let Some(rules) = self
+                        .rules
+                        .as_ref()
+                        .filter(|_| role.can_manage() && !self.available_packs.is_empty())
+                    {
+                        let current = rules.dictionary_pack.as_ref().and_then(|pack_ref| {
+                            self.available_packs
+                                .iter()
+                                .position(|pack| pack.id == pack_ref.id)
+                        });
+                        let label = match current {
+                            Some(index) => format!(
+                                "DICTIONARY: {}",
+                                self.available_packs[index].name.to_uppercase()
+                            ),
+                            None => "DICTIONARY: STANDARD".to_string(),
+                        };
+                        let text = TextHelper::heavy(&label, 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            // Cycles through each pack before returning to the language's dictionary
+                            let next_pack = match current {
+                                Some(index) => self.available_packs.get(index + 1),
+                                None => self.available_packs.first(),
+                            };
+                            msg = Some(PlayerMessage::SetDictionaryPack(
+                                next_pack.map(|pack| pack.id.clone()),
+                            ));
+                        }
+                    }
+
+                    if role.can_manage() {
+                        let text = TextHelper::heavy("EDIT BOARD", 10.0, None, ui);
+                        if text
+                            .button(
+                                Color32::WHITE.diaphanize(),
+                                theme.text,
+                                &self.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.editing_mode = BoardEditingMode::Land;
+                            if !EditorGuideState::completed() {
+                                self.editor_guide = Some(EditorGuideState::new());
+                            }
+                        }
+                    }
+
+                    if let Some(saved_msg) = self.render_saved_boards(ui, theme) {
+                        msg = Some(saved_msg);
+                    }
+
+                    if let Some(appeal_msg) = self.render_fair_play(ui, theme) {
+                        msg = Some(appeal_msg);
+                    }
+                });
+            });
+        });
+
+        msg
+    }
+
+    /// Shows another player's role, with buttons for anything our own role lets us change about them
+    fn render_role_controls(
+        &self,
+        player: &LobbyPlayerMessage,
+        role: RoomRole,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+    ) -> Option<PlayerMessage> {
+        let mut msg = None;
+
+        ui.horizontal(|ui| {
+            match player.role {
+                RoomRole::Host => ui.label(RichText::new("HOST").color(Color32::WHITE)),
+                RoomRole::CoHost => ui.label(RichText::new("CO-HOST").color(Color32::WHITE)),
+                RoomRole::Player => ui.label(RichText::new("PLAYER").color(Color32::WHITE)),
+                RoomRole::Spectator => ui.label(RichText::new("SPECTATOR").color(Color32::WHITE)),
+            };
+
+            if role == RoomRole::Host && player.role != RoomRole::Host {
+                let (label, next_role) = if player.role == RoomRole::CoHost {
+                    ("REMOVE CO-HOST", RoomRole::Player)
+                } else {
+                    ("MAKE CO-HOST", RoomRole::CoHost)
+                };
+                let text = TextHelper::heavy(label, 10.0, None, ui);
+                if text
+                    .button(
+                        Color32::WHITE.diaphanize(),
+                        theme.text,
+                        &self.aesthetics.map_texture,
+                        ui,
+                    )
+                    .clicked()
+                {
+                    msg = Some(PlayerMessage::PromotePlayer {
+                        player: player.index,
+                        role: next_role,
+                    });
+                }
+            }
+
+            let can_kick = match player.role {
+                RoomRole::Host => false,
+                RoomRole::CoHost => role == RoomRole::Host,
+                RoomRole::Player => role.can_manage(),
+                RoomRole::Spectator => false,
+            };
+            if can_kick {
+                let text = TextHelper::heavy("KICK", 10.0, None, ui);
+                if text
+                    .button(
+                        theme.button_scary,
+                        theme.text,
+                        &self.aesthetics.map_texture,
+                        ui,
+                    )
+                    .clicked()
+                {
+                    msg = Some(PlayerMessage::KickPlayer(player.index));
+                }
+            }
+        });
+
+        msg
+    }
+
+    /// Lists the boards saved to the player's account, and lets them save the current board
+    fn render_saved_boards(&mut self, ui: &mut egui::Ui, theme: &Theme) -> Option<PlayerMessage> {
+        let Some(player_token) = self.player_token.clone() else {
+            return None;
+        };
+        let mut msg = None;
+        let can_manage = self.role().can_manage();
+
+        ui.add_space(32.0);
+        ui.label(RichText::new("Saved Boards:").color(Color32::WHITE));
+
+        for saved in &self.saved_boards {
+            ui.horizontal(|ui| {
+                let name = saved.name.to_uppercase();
+                let text = TextHelper::heavy(&name, 10.0, None, ui);
+                if text
+                    .button(
+                        Color32::WHITE.diaphanize(),
+                        theme.text,
+                        &self.aesthetics.map_texture,
+                        ui,
+                    )
+                    .clicked()
+                    && can_manage
+                {
+                    // The server echoes the board back to everyone in the lobby
+                    msg = Some(PlayerMessage::EditBoard(saved.board.clone()));
+                }
+
+                if let Some(status) = saved.gallery {
+                    ui.label(
+                        RichText::new(status.to_string().to_uppercase()).color(Color32::WHITE),
+                    );
+                } else {
+                    let text = TextHelper::heavy("SHARE", 10.0, None, ui);
                     if text
                         .button(
-                            Color32::WHITE.diaphanize(),
+                            theme.button_primary,
                             theme.text,
                             &self.aesthetics.map_texture,
                             ui,
                         )
                         .clicked()
                     {
-                        self.editing_mode = BoardEditingMode::Land;
+                        msg = Some(PlayerMessage::ShareBoard {
+                            player_token: player_token.clone(),
+                            board_id: saved.id.clone(),
+                        });
                     }
-                });
+                }
+
+                let text = TextHelper::heavy("DELETE", 10.0, None, ui);
+                if text
+                    .button(
+                        theme.button_scary,
+                        theme.text,
+                        &self.aesthetics.map_texture,
+                        ui,
+                    )
+                    .clicked()
+                {
+                    msg = Some(PlayerMessage::DeleteSavedBoard {
+                        player_token: player_token.clone(),
+                        board_id: saved.id.clone(),
+                    });
+                }
             });
-        });
+        }
+
+        let input = ui.add(
+            egui::TextEdit::singleline(&mut self.saving_as)
+                .hint_text("Board name")
+                .frame(false)
+                .margin(egui::vec2(0.0, 0.0))
+                .text_color(Color32::WHITE),
+        );
+        ui.painter().rect_stroke(
+            input.rect.expand2(vec2(4.0, 2.0)),
+            2.0,
+            Stroke::new(1.0, Color32::WHITE),
+        );
+
+        let name = self.saving_as.trim();
+        let save_button_color = if name.is_empty() {
+            theme.text.lighten().lighten()
+        } else {
+            Color32::WHITE.diaphanize()
+        };
+        let text = TextHelper::heavy("SAVE BOARD", 10.0, None, ui);
+        if text
+            .button(
+                save_button_color,
+                theme.text,
+                &self.aesthetics.map_texture,
+                ui,
+            )
+            .clicked()
+            && !name.is_empty()
+        {
+            msg = Some(PlayerMessage::SaveBoard {
+                player_token,
+                name: name.to_string(),
+                board: self.board.clone(),
+            });
+        }
+
+        msg
+    }
+
+    /// Shows the player's fair play rating, and lets them appeal the games they were counted as leaving
+    fn render_fair_play(&mut self, ui: &mut egui::Ui, theme: &Theme) -> Option<PlayerMessage> {
+        let (Some(player_token), Some(fair_play)) = (&self.player_token, &self.fair_play) else {
+            return None;
+        };
+        let mut msg = None;
+
+        ui.add_space(32.0);
+        ui.label(RichText::new(format!("Fair play: {}%", fair_play.rating)).color(Color32::WHITE));
+        if fair_play.penalty_secs > 0 {
+            let minutes = fair_play.penalty_secs.div_ceil(60);
+            ui.label(
+                RichText::new(format!(
+                    "You left a recent game early, so you can play again in {minutes} minute{}",
+                    if minutes == 1 { "" } else { "s" }
+                ))
+                .color(Color32::WHITE),
+            );
+        }
+
+        let unappealed: Vec<_> = fair_play
+            .recent_abandonments
+            .iter()
+            .filter(|abandonment| !abandonment.appealed)
+            .collect();
+        if unappealed.is_empty() {
+            return None;
+        }
+
+        let input = ui.add(
+            egui::TextEdit::singleline(&mut self.appeal_reason)
+                .hint_text("Why did you leave?")
+                .frame(false)
+                .margin(egui::vec2(0.0, 0.0))
+                .text_color(Color32::WHITE),
+        );
+        ui.painter().rect_stroke(
+            input.rect.expand2(vec2(4.0, 2.0)),
+            2.0,
+            Stroke::new(1.0, Color32::WHITE),
+        );
+
+        let reason = self.appeal_reason.trim();
+        let appeal_button_color = if reason.is_empty() {
+            theme.text.lighten().lighten()
+        } else {
+            Color32::WHITE.diaphanize()
+        };
+        for abandonment in unappealed {
+            let label = format!("APPEAL ROOM {}", abandonment.room_code.to_uppercase());
+            let text = TextHelper::heavy(
+                &label,
+                10.0,
+                None,
+                ui,
+            );
+            if text
+                .button(
+                    appeal_button_color,
+                    theme.text,
+                    &self.aesthetics.map_texture,
+                    ui,
+                )
+                .clicked()
+                && !reason.is_empty()
+            {
+                msg = Some(PlayerMessage::AppealAbandonment {
+                    player_token: player_token.clone(),
+                    abandonment_id: abandonment.id.clone(),
+                    reason: reason.to_string(),
+                });
+            }
+        }
+
+        if msg.is_some() {
+            self.appeal_reason.clear();
+        }
 
         msg
     }
@@ -274,6 +950,23 @@ impl Lobby {
                     &self.board,
                 );
             }
+
+            if let Some(guide) = &mut self.editor_guide {
+                match guide.render(
+                    &self.board,
+                    &mut self.editing_mode,
+                    ui,
+                    theme,
+                    &self.aesthetics.map_texture,
+                ) {
+                    Some(EditorGuideAction::Generate) => {
+                        self.generate_starting_board(ui.ctx());
+                        msg = Some(PlayerMessage::EditBoard(self.board.clone()));
+                    }
+                    Some(EditorGuideAction::Close) => self.editor_guide = None,
+                    None => {}
+                }
+            }
         }
 
         msg
@@ -1,7 +1,15 @@
 pub mod active_game;
+pub mod forced_wins;
+pub mod gallery;
 pub mod generator;
 pub mod lobby;
 pub mod native_menu;
+pub mod npc_ladder;
+pub mod prediction_trainer;
+pub mod puzzle_rush;
+pub mod replay_comparison;
 pub mod replayer;
 pub mod single_player;
 pub mod tutorial;
+pub mod word_ladder;
+pub mod word_of_day;
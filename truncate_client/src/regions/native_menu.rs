@@ -1,18 +1,23 @@
 use eframe::egui;
 
 use truncate_core::{
-    board::Board, generation::BoardSeed, messages::LobbyPlayerMessage,
-    npc::scoring::NPCPersonality, rules::GameRules,
+    archive::GameArchive,
+    board::Board,
+    generation::BoardSeed,
+    messages::{LobbyPlayerMessage, RoomRole},
+    npc::scoring::NPCPersonality,
+    rules::{GameRules, Language, RulesPreset},
 };
 
 use crate::{
     app_inner::GameStatus,
-    app_outer::OuterApplication,
+    app_outer::{BackchannelMsg, OuterApplication, ShareType},
     regions::{
-        active_game::HeaderType, generator::GeneratorState, lobby::Lobby,
-        single_player::SinglePlayerState, tutorial::TutorialState,
+        active_game::HeaderType, gallery::GalleryState, generator::GeneratorState, lobby::Lobby,
+        replayer::ReplayerState, single_player::SinglePlayerState, tutorial::TutorialState,
+        word_of_day::WordOfTheDayState,
     },
-    utils,
+    utils::{self, macros::current_time},
 };
 
 use truncate_core::messages::PlayerMessage;
@@ -56,11 +61,13 @@ pub fn render_native_menu_if_required(
                             name: "You".into(),
                             index: 0,
                             color: (128, 128, 255),
+                            role: RoomRole::Host,
                         },
                         LobbyPlayerMessage {
                             name: "Computer".into(),
                             index: 1,
                             color: (255, 80, 80),
+                            role: RoomRole::Player,
                         },
                     ],
                     0,
@@ -81,6 +88,7 @@ pub fn render_native_menu_if_required(
                     behemoth_board,
                     Some(seed_for_hand_tiles),
                     rules_generation,
+                    Language::English,
                     true,
                     HeaderType::Timers,
                     NPCPersonality::jet(),
@@ -93,9 +101,37 @@ pub fn render_native_menu_if_required(
                 send_to_server(PlayerMessage::NewGame {
                     player_name: outer.name.clone(),
                     effective_day: outer.launched_at_day,
+                    rules: None,
                 });
                 return Some(GameStatus::PendingCreate);
             }
+            if ui.button("Find Opponent").clicked() {
+                send_to_server(PlayerMessage::FindMatch {
+                    player_name: outer.name.clone(),
+                    effective_day: outer.launched_at_day,
+                    ruleset: RulesPreset::Classic,
+                    latency_ms: outer.connection.latency_ms(),
+                });
+                return Some(GameStatus::PendingMatch((RulesPreset::Classic, 1)));
+            }
+            if ui.button("Word of the Day").clicked() {
+                send_to_server(PlayerMessage::LoadWordOfTheDay(outer.launched_at_day));
+                return Some(GameStatus::WordOfTheDay(WordOfTheDayState::new(
+                    outer.event_dispatcher.clone(),
+                )));
+            }
+            if ui.button("Gallery").clicked() {
+                send_to_server(PlayerMessage::LoadGallery);
+                return Some(GameStatus::Gallery(GalleryState::new()));
+            }
+            for provider in utils::oauth::providers() {
+                if ui.button(format!("Sign in with {provider}")).clicked() {
+                    if let Some(msg) = utils::oauth::start_sign_in(provider) {
+                        send_to_server(msg);
+                        return Some(GameStatus::PendingSignIn(false));
+                    }
+                }
+            }
             ui.text_edit_singleline(room_code);
             if ui.button("Join Game").clicked() {
                 send_to_server(PlayerMessage::JoinGame(
@@ -112,8 +148,122 @@ pub fn render_native_menu_if_required(
                     return Some(GameStatus::PendingJoin("...".into()));
                 }
             }
-            None
+            ui.text_edit_multiline(&mut outer.notation_draft);
+            if ui.button("Replay Notation").clicked() {
+                let read = outer
+                    .notation_draft
+                    .parse::<GameArchive>()
+                    .and_then(|archive| Ok((archive.to_game()?, archive.moves)));
+                return Some(match read {
+                    Ok(((game, seed), moves)) => GameStatus::Replay(
+                        ReplayerState::new(
+                            ui.ctx(),
+                            outer.map_texture.clone(),
+                            outer.theme.clone(),
+                            game,
+                            moves,
+                            vec![],
+                            0,
+                        )
+                        .with_seed(seed),
+                    ),
+                    Err(e) => GameStatus::HardError(vec![
+                        "Sorry, that notation".to_string(),
+                        "couldn't be read!".to_string(),
+                        e.to_string(),
+                    ]),
+                });
+            }
+            render_scheduled_games(outer, ui)
         }
         _ => None,
     }
 }
+
+/// Lists the player's upcoming games, and lets them arrange another
+fn render_scheduled_games(outer: &mut OuterApplication, ui: &mut egui::Ui) -> Option<GameStatus> {
+    let player_token = outer.logged_in_as.clone()?;
+    let now = current_time!().as_secs();
+    let mut copy = None;
+    let mut send = None;
+
+    ui.separator();
+    ui.label("Upcoming games");
+    if outer.scheduled_games.is_empty() {
+        ui.label("Nothing scheduled yet");
+    }
+    for scheduled in &outer.scheduled_games {
+        let guest = scheduled.guest.as_deref().unwrap_or("an open seat");
+        ui.label(format!(
+            "{} game, {} vs {}, {}",
+            scheduled.ruleset,
+            scheduled.host,
+            guest,
+            starts_in(scheduled.starts_at, now)
+        ));
+
+        ui.horizontal(|ui| {
+            if let Some(room_token) = &scheduled.room_token {
+                if ui.button("Join").clicked() {
+                    send = Some(PlayerMessage::RejoinGame(room_token.clone()));
+                }
+                return;
+            }
+            if scheduled.guest.is_none() && ui.button("Copy Invite").clicked() {
+                copy = Some((scheduled.link.clone(), ShareType::Url));
+            }
+            if ui.button("Copy Calendar Invite").clicked() {
+                copy = Some((scheduled.calendar_invite(), ShareType::None));
+            }
+            if ui.button("Cancel").clicked() {
+                send = Some(PlayerMessage::CancelScheduledGame {
+                    player_token: player_token.clone(),
+                    invite_code: scheduled.invite_code.clone(),
+                });
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Schedule a game in");
+        ui.add(egui::DragValue::new(&mut outer.schedule_in_hours).clamp_range(1..=24 * 90));
+        ui.label("hours:");
+        for ruleset in RulesPreset::ALL {
+            if ui.button(ruleset.to_string()).clicked() {
+                send = Some(PlayerMessage::ScheduleGame {
+                    player_token: player_token.clone(),
+                    player_name: outer.name.clone(),
+                    effective_day: outer.launched_at_day,
+                    // Rounded to the minute, to make for a tidier calendar
+                    starts_at: (now / 60 + outer.schedule_in_hours as u64 * 60) * 60,
+                    ruleset,
+                });
+            }
+        }
+    });
+
+    if let Some((text, share)) = copy {
+        if outer.backchannel.is_open() {
+            outer
+                .backchannel
+                .send_msg(BackchannelMsg::Copy { text, share });
+        } else {
+            ui.ctx().output_mut(|o| o.copied_text = text);
+        }
+    }
+
+    let joining = matches!(send, Some(PlayerMessage::RejoinGame(_)));
+    if let Some(msg) = send {
+        outer.tx_player.try_send(msg).unwrap();
+    }
+    joining.then(|| GameStatus::PendingJoin("...".into()))
+}
+
+fn starts_in(starts_at: u64, now: u64) -> String {
+    match starts_at.saturating_sub(now) {
+        0 => "starting now".to_string(),
+        secs if secs < 60 * 60 => format!("in {} minute(s)", (secs / 60).max(1)),
+        secs if secs < 60 * 60 * 24 => format!("in {} hour(s)", secs / (60 * 60)),
+        secs => format!("in {} day(s)", secs / (60 * 60 * 24)),
+    }
+}
@@ -0,0 +1,315 @@
+use eframe::egui::{self, Layout, Sense};
+use epaint::{emath::Align, vec2, Color32, TextureHandle};
+use instant::Duration;
+use truncate_core::{
+    generation::{generate_board, BoardSeed},
+    messages::{PlayerMessage, TruncateToken},
+    npc::progression::{rungs, NpcProgress, TileSkin},
+    rules::Language,
+};
+
+use crate::{
+    app_outer::{Backchannel, EventDispatcher},
+    utils::{text::TextHelper, urls::back_to_menu, Theme},
+};
+
+use super::{active_game::HeaderType, single_player::SinglePlayerState};
+
+pub struct NpcLadderState {
+    progress: NpcProgress,
+    skin: TileSkin,
+    player_token: Option<TruncateToken>,
+    rules_generation: u32,
+    event_dispatcher: EventDispatcher,
+    /// The rung being played, and whether a win in the current game has been recorded
+    active: Option<(u32, SinglePlayerState, bool)>,
+}
+
+impl NpcLadderState {
+    pub fn new(
+        player_token: Option<TruncateToken>,
+        rules_generation: u32,
+        event_dispatcher: EventDispatcher,
+    ) -> Self {
+        #[allow(unused_mut)]
+        let mut progress = NpcProgress::default();
+        #[allow(unused_mut)]
+        let mut skin = TileSkin::Classic;
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            if let Some(stored) = local_storage
+                .get_item("truncate_npc_progress")
+                .unwrap()
+                .and_then(|p| serde_json::from_str(&p).ok())
+            {
+                progress = stored;
+            }
+            if let Some(stored) = local_storage
+                .get_item("truncate_tile_skin")
+                .unwrap()
+                .and_then(TileSkin::from_id)
+            {
+                skin = stored;
+            }
+        }
+
+        Self {
+            progress,
+            skin,
+            player_token,
+            rules_generation,
+            event_dispatcher,
+            active: None,
+        }
+    }
+
+    /// Combines progress from the server with anything won on this device
+    pub fn merge_progress(&mut self, progress: NpcProgress) {
+        self.progress.merge(&progress);
+        self.store_locally();
+    }
+
+    fn store_locally(&self) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            local_storage
+                .set_item(
+                    "truncate_npc_progress",
+                    &serde_json::to_string(&self.progress).unwrap(),
+                )
+                .unwrap();
+            local_storage
+                .set_item("truncate_tile_skin", &self.skin.to_string())
+                .unwrap();
+        }
+    }
+
+    /// Skins are only usable once earned, whatever was stored on this device
+    fn current_skin(&self) -> TileSkin {
+        if self.progress.skins().contains(&self.skin) {
+            self.skin
+        } else {
+            TileSkin::Classic
+        }
+    }
+
+    fn next_skin(&mut self) {
+        let skins = self.progress.skins();
+        let current = skins
+            .iter()
+            .position(|s| *s == self.current_skin())
+            .unwrap_or_default();
+        self.skin = skins[(current + 1) % skins.len()];
+        self.store_locally();
+    }
+
+    fn start_rung(
+        &mut self,
+        rung: u32,
+        ctx: &egui::Context,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        current_time: Duration,
+    ) {
+        let Some(details) = rungs().into_iter().nth(rung as usize) else {
+            return;
+        };
+
+        let board_seed = BoardSeed::new((current_time.as_micros() % 243985691) as u32);
+        let board = generate_board(board_seed.clone())
+            .expect("Common seeds can be reasonably expected to produce a board")
+            .board;
+
+        let mut game = SinglePlayerState::new(
+            format!("ladder_{}", details.personality.name),
+            ctx,
+            map_texture.clone(),
+            theme.clone(),
+            board,
+            Some(board_seed),
+            self.rules_generation,
+            Language::English,
+            true,
+            HeaderType::Summary {
+                title: details.title.to_string(),
+                attempt: None,
+            },
+            details.personality,
+            self.event_dispatcher.clone(),
+        );
+        game.set_human_color(self.current_skin().color());
+
+        self.active = Some((rung, game, false));
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        current_time: Duration,
+        backchannel: &Backchannel,
+    ) -> Vec<PlayerMessage> {
+        if let Some((rung, game, recorded)) = &mut self.active {
+            let (top_banner, _) =
+                ui.allocate_at_least(vec2(ui.available_width(), 40.0), Sense::hover());
+            let mut banner_ui = ui.child_ui(top_banner, Layout::left_to_right(Align::Center));
+            let back = TextHelper::heavy("BACK TO LADDER", 12.0, None, ui)
+                .centered_button(
+                    theme.button_primary,
+                    theme.text,
+                    map_texture,
+                    &mut banner_ui,
+                )
+                .clicked();
+
+            let mut msgs = game.render(ui, theme, current_time, backchannel, &self.player_token);
+
+            if !game.human_won() {
+                *recorded = false;
+            } else if !*recorded {
+                *recorded = true;
+
+                // Track the win locally first so the ladder still climbs when offline
+                if self.progress.record_win(*rung).is_ok() {
+                    let board_seed = game.active_game.depot.board_info.board_seed.as_ref();
+                    if let (Some(token), Some(seed)) = (&self.player_token, board_seed) {
+                        msgs.push(PlayerMessage::PersistNpcWin {
+                            player_token: token.clone(),
+                            rung: *rung,
+                            moves: game.move_sequence.clone(),
+                            rules_generation: game.rules_generation(),
+                            board_seed: (seed.generation, seed.seed),
+                            human_player: game.human_player(),
+                        });
+                    }
+                    self.store_locally();
+                }
+            }
+
+            if back {
+                self.active = None;
+            }
+            return msgs;
+        }
+
+        let mut play = None;
+
+        ui.add_space(40.0);
+        TextHelper::heavy("NPC LADDER", 14.0, None, ui).paint(Color32::WHITE, ui, true);
+        ui.add_space(8.0);
+        TextHelper::light(
+            "Beat each opponent to unlock the next",
+            14.0,
+            Some(ui.available_width() - 20.0),
+            ui,
+        )
+        .paint(Color32::WHITE, ui, true);
+        ui.add_space(20.0);
+
+        for (index, rung) in rungs().into_iter().enumerate() {
+            let index = index as u32;
+            let beaten = self.progress.beaten.contains(&index);
+            let unlocked = self.progress.is_unlocked(index);
+
+            let reward = rung
+                .reward
+                .map(|skin| format!(", earns the {skin} skin"))
+                .unwrap_or_default();
+            TextHelper::light(
+                &format!("{} ({}){reward}", rung.title, rung.personality.name),
+                14.0,
+                Some(ui.available_width() - 20.0),
+                ui,
+            )
+            .paint(
+                if beaten {
+                    theme.word_valid
+                } else {
+                    Color32::WHITE
+                },
+                ui,
+                true,
+            );
+            ui.add_space(4.0);
+
+            let label = if !unlocked {
+                "LOCKED"
+            } else if beaten {
+                "PLAY AGAIN"
+            } else {
+                "PLAY"
+            };
+            let button = TextHelper::heavy(label, 12.0, None, ui).centered_button(
+                if unlocked {
+                    theme.button_primary
+                } else {
+                    theme.water
+                },
+                theme.text,
+                map_texture,
+                ui,
+            );
+            if unlocked && button.clicked() {
+                play = Some(index);
+            }
+            ui.add_space(10.0);
+        }
+
+        ui.add_space(10.0);
+        let skin = self.current_skin();
+        if self.progress.skins().len() > 1
+            && TextHelper::heavy(
+                &format!("TILE SKIN: {}", skin.to_string().to_uppercase()),
+                12.0,
+                None,
+                ui,
+            )
+            .centered_button(
+                {
+                    let (r, g, b) = skin.color();
+                    Color32::from_rgb(r, g, b)
+                },
+                theme.text,
+                map_texture,
+                ui,
+            )
+            .clicked()
+        {
+            self.next_skin();
+        }
+
+        if self.player_token.is_none() {
+            ui.add_space(10.0);
+            TextHelper::light(
+                "Offline, so progress is only saved on this device",
+                14.0,
+                None,
+                ui,
+            )
+            .paint(Color32::WHITE, ui, true);
+        }
+
+        ui.add_space(20.0);
+        if TextHelper::heavy("BACK TO MENU", 12.0, None, ui)
+            .centered_button(theme.button_primary, theme.text, map_texture, ui)
+            .clicked()
+        {
+            back_to_menu();
+        }
+
+        if let Some(rung) = play {
+            self.start_rung(rung, ui.ctx(), theme, map_texture, current_time);
+        }
+
+        vec![]
+    }
+
+    /// Word definitions from the server belong to whichever ladder game is being played
+    pub fn active_game(&mut self) -> Option<&mut SinglePlayerState> {
+        self.active.as_mut().map(|(_, game, _)| game)
+    }
+}
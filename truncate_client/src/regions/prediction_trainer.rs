@@ -0,0 +1,390 @@
+use eframe::egui::{self, Sense};
+use epaint::{vec2, Color32, Stroke, TextureHandle};
+use instant::Duration;
+use truncate_core::{
+    board::{Coordinate, Square},
+    game::Game,
+    messages::{PlayerMessage, TruncateToken},
+    moves::Move,
+    prediction::{PredictionGrade, PredictionTrainer},
+    reporting::{BoardChange, BoardChangeAction, BoardChangeDetail, Change},
+};
+
+use crate::utils::{
+    depot::{AestheticDepot, GameplayDepot, TimingDepot},
+    game_evals::get_main_dict,
+    mapper::MappedBoard,
+    text::TextHelper,
+    urls::back_to_menu,
+    Diaphanize, Theme,
+};
+
+fn describe(played: &Move) -> String {
+    match played {
+        Move::Place { tile, .. } => format!("THEY PLAYED {tile}"),
+        Move::Swap { .. } => "THEY SWAPPED TWO TILES".to_string(),
+        _ => "THEY MOVED".to_string(),
+    }
+}
+
+/// What the opponent actually did, shown until the player moves on
+#[derive(Clone)]
+struct Reveal {
+    grade: Option<PredictionGrade>,
+    actual: Move,
+}
+
+/// Replays a game from one player's side, stopping before each of the opponent's moves
+/// for the player to guess where they played
+#[derive(Clone)]
+pub struct PredictionTrainerState {
+    trainer: PredictionTrainer,
+    map_texture: TextureHandle,
+    mapped_board: MappedBoard,
+    aesthetics: AestheticDepot,
+    timing: TimingDepot,
+    gameplay: GameplayDepot,
+    player_token: Option<TruncateToken>,
+    /// Squares picked for the guess, where two of the opponent's tiles make a swap
+    selected: Vec<Coordinate>,
+    tile: Option<char>,
+    reveal: Option<Reveal>,
+    submitted: bool,
+}
+
+impl PredictionTrainerState {
+    pub fn new(
+        ctx: &egui::Context,
+        map_texture: TextureHandle,
+        mut game: Game,
+        moves: Vec<Move>,
+        as_player: usize,
+        player_token: Option<TruncateToken>,
+    ) -> Self {
+        game.rules.battle_delay = 0;
+
+        let player_colors: Vec<_> = game
+            .players
+            .iter()
+            .map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2))
+            .collect();
+
+        let aesthetics = AestheticDepot {
+            theme: Theme::day(),
+            qs_tick: 0,
+            map_texture: map_texture.clone(),
+            player_colors,
+            destruction_tick: 0.05,
+            destruction_duration: 0.6,
+        };
+        let mapped_board = MappedBoard::new(ctx, &aesthetics, &game.board, 2, as_player, true);
+
+        let gameplay = GameplayDepot {
+            room_code: "PREDICT".into(),
+            player_number: as_player as u64,
+            next_player_number: game.next_player.map(|p| p as u64),
+            error_msg: None,
+            winner: None,
+            changes: vec![],
+            last_battle_origin: None,
+            npc: None,
+            remaining_turns: None,
+            hand_swap_in: None,
+            end_state: None,
+            latency_ms: None,
+            arbiter_notice: None,
+            mulligan_offer: None,
+        };
+
+        let mut trainer = PredictionTrainer::new(game, moves, as_player);
+        let dict_lock = get_main_dict();
+        trainer.advance(dict_lock.as_ref());
+        drop(dict_lock);
+
+        let mut state = Self {
+            trainer,
+            map_texture,
+            mapped_board,
+            aesthetics,
+            timing: TimingDepot::default(),
+            gameplay,
+            player_token,
+            selected: vec![],
+            tile: None,
+            reveal: None,
+            submitted: false,
+        };
+        state.moved();
+        state
+    }
+
+    /// Catches the board's animations up with the moves that were just played on it
+    fn moved(&mut self) {
+        let game = self.trainer.game();
+        self.gameplay.next_player_number = game.next_player.map(|p| p as u64);
+        self.gameplay.winner = game.winner;
+        self.gameplay.changes = game.recent_changes.clone();
+
+        let battle_occurred = game
+            .recent_changes
+            .iter()
+            .any(|change| matches!(change, Change::Battle(_)));
+        self.gameplay.last_battle_origin = if battle_occurred {
+            game.recent_changes.iter().find_map(|change| match change {
+                Change::Board(BoardChange {
+                    detail: BoardChangeDetail { coordinate, .. },
+                    action: BoardChangeAction::Added,
+                }) => Some(*coordinate),
+                _ => None,
+            })
+        } else {
+            None
+        };
+    }
+
+    fn opponent(&self) -> usize {
+        (self.trainer.as_player() + 1) % self.trainer.game().players.len().max(1)
+    }
+
+    /// The move the picked squares and tile make up, if they make one
+    fn current_guess(&self) -> Option<Move> {
+        let player = self.opponent();
+        match (self.selected.as_slice(), self.tile) {
+            ([position], Some(tile)) => Some(Move::Place {
+                player,
+                tile,
+                position: *position,
+            }),
+            ([a, b], _) => Some(Move::Swap {
+                player,
+                positions: [*a, *b],
+            }),
+            _ => None,
+        }
+    }
+
+    fn select(&mut self, coord: Coordinate) {
+        let opponent = self.opponent();
+        let is_opponents = |coord: &Coordinate| {
+            matches!(
+                self.trainer.game().board.get(*coord),
+                Ok(Square::Occupied { player, .. }) if player == opponent
+            )
+        };
+
+        if let Some(index) = self.selected.iter().position(|c| *c == coord) {
+            self.selected.remove(index);
+        } else if self.selected.len() == 1
+            && is_opponents(&self.selected[0])
+            && is_opponents(&coord)
+        {
+            self.selected.push(coord);
+            self.tile = None;
+        } else {
+            self.selected = vec![coord];
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        current_time: Duration,
+    ) -> Option<PlayerMessage> {
+        self.timing.current_time = current_time;
+        let mut msg = None;
+
+        ui.add_space(20.0);
+
+        ui.horizontal(|ui| {
+            let button_color = Color32::WHITE.diaphanize();
+
+            if TextHelper::heavy("MENU", 10.0, None, ui)
+                .button(theme.button_primary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                back_to_menu();
+            }
+
+            if self.reveal.is_some() {
+                if TextHelper::heavy("NEXT", 10.0, None, ui)
+                    .button(theme.button_primary, theme.text, &self.map_texture, ui)
+                    .clicked()
+                {
+                    self.reveal = None;
+                    let dict_lock = get_main_dict();
+                    self.trainer.advance(dict_lock.as_ref());
+                    drop(dict_lock);
+                    self.moved();
+                    self.timing.last_turn_change = current_time;
+                }
+            } else if self.trainer.awaiting_guess() {
+                let guess = self.current_guess();
+                let guess_color = if guess.is_some() {
+                    theme.button_primary
+                } else {
+                    button_color
+                };
+                let guess_clicked = TextHelper::heavy("GUESS", 10.0, None, ui)
+                    .button(guess_color, theme.text, &self.map_texture, ui)
+                    .clicked();
+                let skip_clicked = TextHelper::heavy("SKIP", 10.0, None, ui)
+                    .button(button_color, theme.text, &self.map_texture, ui)
+                    .clicked();
+
+                let dict_lock = get_main_dict();
+                let reveal = match guess {
+                    Some(guess) if guess_clicked => self
+                        .trainer
+                        .guess(&guess, dict_lock.as_ref())
+                        .map(|(grade, actual)| Reveal {
+                            grade: Some(grade),
+                            actual,
+                        }),
+                    _ if skip_clicked => {
+                        self.trainer.skip(dict_lock.as_ref()).map(|actual| Reveal {
+                            grade: None,
+                            actual,
+                        })
+                    }
+                    _ => None,
+                };
+                drop(dict_lock);
+
+                if reveal.is_some() {
+                    self.reveal = reveal;
+                    self.selected.clear();
+                    self.tile = None;
+                    self.moved();
+                    self.timing.last_turn_change = current_time;
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+
+        let stats = self.trainer.stats;
+        let accuracy = match stats.accuracy() {
+            Some(accuracy) => format!(
+                "{} PREDICTION(S), {:.0}% ACCURATE",
+                stats.predictions,
+                accuracy * 100.0
+            ),
+            None => "GUESS YOUR OPPONENT'S NEXT MOVE".to_string(),
+        };
+        TextHelper::heavy(&accuracy, 10.0, None, ui).paint(theme.text, ui, true);
+
+        if let Some(reveal) = &self.reveal {
+            let (verdict, color) = match reveal.grade {
+                Some(PredictionGrade::Exact) => ("EXACTLY RIGHT!", theme.word_valid),
+                Some(PredictionGrade::Close) => ("CLOSE!", theme.gold_medal),
+                Some(PredictionGrade::Missed) => ("MISSED", theme.word_invalid),
+                None => ("SKIPPED", theme.text),
+            };
+            let line = format!("{verdict} {}", describe(&reveal.actual));
+            TextHelper::heavy(&line, 10.0, None, ui).paint(color, ui, true);
+        } else if self.trainer.awaiting_guess() {
+            let opponent = self.opponent();
+            if let Some(hand) = self
+                .trainer
+                .game()
+                .players
+                .get(opponent)
+                .map(|p| p.hand.clone())
+            {
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    for tile in hand.iter() {
+                        let color = if self.tile == Some(*tile) {
+                            theme.button_primary
+                        } else {
+                            Color32::WHITE.diaphanize()
+                        };
+                        if TextHelper::heavy(&tile.to_string(), 10.0, None, ui)
+                            .button(color, theme.text, &self.map_texture, ui)
+                            .clicked()
+                        {
+                            self.tile = Some(*tile);
+                            self.selected.truncate(1);
+                        }
+                    }
+                });
+            }
+        } else if self.trainer.is_finished() {
+            if let Some(error) = self.trainer.error() {
+                TextHelper::light(&error.to_string(), 10.0, Some(ui.available_width()), ui).paint(
+                    theme.word_invalid,
+                    ui,
+                    true,
+                );
+            }
+            TextHelper::heavy("END OF GAME", 10.0, None, ui).paint(theme.text, ui, true);
+
+            if !self.submitted {
+                self.submitted = true;
+                msg = self
+                    .player_token
+                    .as_ref()
+                    .filter(|_| stats.predictions > 0)
+                    .map(|token| PlayerMessage::PersistPredictions {
+                        player_token: token.clone(),
+                        stats,
+                    });
+            }
+        }
+
+        ui.add_space(10.0);
+
+        let game = self.trainer.game();
+        self.mapped_board.remap_texture(
+            ui.ctx(),
+            &self.aesthetics,
+            &self.timing,
+            None,
+            Some(&self.gameplay),
+            &game.board,
+        );
+
+        let mut board_space = ui.available_rect_before_wrap().shrink(10.0);
+        let height_from_width = game.board.height() as f32 / game.board.width() as f32;
+        let target_height = board_space.width() * height_from_width;
+
+        if target_height <= board_space.height() {
+            let diff = (board_space.height() - target_height) / 2.0;
+            board_space = board_space.shrink2(vec2(0.0, diff));
+        } else {
+            let width_from_height = game.board.width() as f32 / game.board.height() as f32;
+            let target_width = board_space.height() * width_from_height;
+            let diff = (board_space.width() - target_width) / 2.0;
+            board_space = board_space.shrink2(vec2(diff, 0.0));
+        }
+
+        self.mapped_board.render_to_rect(board_space, None, ui);
+
+        for coord in &self.selected {
+            let square = self
+                .mapped_board
+                .square_rect(&game.board, board_space, *coord);
+            ui.painter()
+                .rect_stroke(square, 2.0, Stroke::new(2.0, theme.ring_selected));
+        }
+
+        if self.trainer.awaiting_guess() && self.reveal.is_none() {
+            let board_resp = ui.interact(
+                board_space,
+                ui.id().with("prediction_board"),
+                Sense::click(),
+            );
+            let clicked = board_resp
+                .clicked()
+                .then(|| board_resp.interact_pointer_pos())
+                .flatten()
+                .and_then(|pos| self.mapped_board.square_at(&game.board, board_space, pos));
+            if let Some(coord) = clicked {
+                self.select(coord);
+            }
+        }
+
+        msg
+    }
+}
@@ -0,0 +1,353 @@
+use eframe::egui;
+use epaint::{Color32, TextureHandle};
+use instant::Duration;
+use truncate_core::{
+    messages::{GamePlayerMessage, PlayerMessage, TruncateToken},
+    moves::Move,
+    rules::Language,
+    rush::{
+        Drill, DrillWords, PuzzleRush, RushLeaderboard, RUSH_DURATION_SECS,
+        RUSH_MISTAKE_PENALTY_SECS,
+    },
+};
+
+use crate::{
+    app_outer::EventDispatcher,
+    utils::{game_evals::get_main_dict, text::TextHelper, urls::back_to_menu, Theme},
+};
+
+use super::active_game::{ActiveGame, GameLocation, HeaderType};
+
+/// How long the time penalty is shown on the clock after a mistake
+const MISTAKE_FLASH: Duration = Duration::from_millis(1200);
+
+pub struct PuzzleRushState {
+    day: u32,
+    player_token: Option<TruncateToken>,
+    event_dispatcher: EventDispatcher,
+    /// The run in progress, or the last one finished
+    rush: Option<PuzzleRush>,
+    /// Gathered from the dictionary when a run starts, and reused for each of its drills
+    drill_words: Option<DrillWords>,
+    drill: Option<(Drill, ActiveGame)>,
+    started_at: Duration,
+    last_mistake_at: Option<Duration>,
+    finished: bool,
+    /// Ranked runs wait for the server to hand out their seed, which also starts their clock
+    awaiting_seed: bool,
+    pub started_seed: Option<u32>,
+    pub leaderboard: Option<RushLeaderboard>,
+}
+
+impl PuzzleRushState {
+    pub fn new(
+        day: u32,
+        player_token: Option<TruncateToken>,
+        event_dispatcher: EventDispatcher,
+    ) -> Self {
+        Self {
+            day,
+            player_token,
+            event_dispatcher,
+            rush: None,
+            drill_words: None,
+            drill: None,
+            started_at: Duration::ZERO,
+            last_mistake_at: None,
+            finished: false,
+            awaiting_seed: false,
+            started_seed: None,
+            leaderboard: None,
+        }
+    }
+
+    fn start(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        current_time: Duration,
+        seed: u32,
+    ) {
+        self.event_dispatcher.event("puzzle_rush_start");
+
+        self.awaiting_seed = false;
+        self.rush = Some(PuzzleRush::new(seed));
+        self.drill_words = None;
+        self.started_at = current_time;
+        self.last_mistake_at = None;
+        self.finished = false;
+        self.load_drill(ctx, theme, map_texture);
+    }
+
+    /// Sets up the board for the current drill, also used to reset it after a mistake
+    fn load_drill(&mut self, ctx: &egui::Context, theme: &Theme, map_texture: &TextureHandle) {
+        let Some(rush) = &self.rush else {
+            return;
+        };
+        let dict_lock = get_main_dict();
+        let dict = dict_lock.as_ref().unwrap();
+        let drill_words = self
+            .drill_words
+            .get_or_insert_with(|| DrillWords::new(dict));
+
+        let Some(drill) = rush.current_drill(drill_words, dict) else {
+            // We've run out of drills to give, which is as good as running out of time
+            self.drill = None;
+            return;
+        };
+        let game = drill.game();
+
+        let mut active_game = ActiveGame::new(
+            ctx,
+            "PUZZLE_RUSH".into(),
+            None,
+            None,
+            game.players
+                .iter()
+                .map(|p| GamePlayerMessage::new(p, &game))
+                .collect(),
+            0,
+            Some(0),
+            game.board.clone(),
+            game.players[0].hand.clone(),
+            map_texture.clone(),
+            theme.clone(),
+            GameLocation::Local,
+            None,
+            None,
+        );
+        active_game.depot.ui_state.game_header = HeaderType::Summary {
+            title: String::new(),
+            attempt: None,
+        };
+
+        self.drill = Some((drill, active_game));
+    }
+
+    fn finish(&mut self) -> Option<PlayerMessage> {
+        self.finished = true;
+        self.drill = None;
+        self.event_dispatcher.event("puzzle_rush_finish");
+
+        let rush = self.rush.as_ref()?;
+        let token = self.player_token.as_ref()?;
+        Some(PlayerMessage::PersistPuzzleRush {
+            player_token: token.clone(),
+            day: self.day,
+            rush: rush.clone(),
+            language: Language::English,
+        })
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        current_time: Duration,
+    ) -> Vec<PlayerMessage> {
+        let mut msgs = vec![];
+
+        if let Some(seed) = self.started_seed.take() {
+            self.start(ui.ctx(), theme, map_texture, current_time, seed);
+        }
+
+        if !self.finished {
+            if let Some(rush) = &self.rush {
+                let time_left = rush.time_left(current_time.saturating_sub(self.started_at));
+                if time_left.is_zero() || self.drill.is_none() {
+                    msgs.extend(self.finish());
+                } else {
+                    msgs.extend(self.render_drill(ui, theme, map_texture, current_time, time_left));
+                    return msgs;
+                }
+            }
+        }
+
+        let mut play = false;
+
+        ui.add_space(40.0);
+        TextHelper::heavy("PUZZLE RUSH", 14.0, None, ui).paint(Color32::WHITE, ui, true);
+        ui.add_space(8.0);
+
+        if let Some(rush) = self.rush.as_ref().filter(|_| self.finished) {
+            TextHelper::light(
+                &format!(
+                    "Solved {} drill{}",
+                    rush.solved(),
+                    if rush.solved() == 1 { "" } else { "s" }
+                ),
+                20.0,
+                None,
+                ui,
+            )
+            .paint(Color32::WHITE, ui, true);
+            TextHelper::light(
+                &format!(
+                    "Best streak {}, with {} mistake{}",
+                    rush.best_streak,
+                    rush.mistakes,
+                    if rush.mistakes == 1 { "" } else { "s" }
+                ),
+                14.0,
+                None,
+                ui,
+            )
+            .paint(Color32::WHITE, ui, true);
+        } else {
+            TextHelper::light(
+                &format!(
+                    "Take the town in one move, as many times as you can in {} minutes. \
+                    Each mistake costs {RUSH_MISTAKE_PENALTY_SECS} seconds.",
+                    RUSH_DURATION_SECS / 60
+                ),
+                14.0,
+                Some(ui.available_width() - 20.0),
+                ui,
+            )
+            .paint(Color32::WHITE, ui, true);
+        }
+        ui.add_space(20.0);
+
+        let play_text = if self.awaiting_seed {
+            "STARTING"
+        } else if self.finished {
+            "PLAY AGAIN"
+        } else {
+            "START"
+        };
+        if TextHelper::heavy(play_text, 12.0, None, ui)
+            .centered_button(theme.button_primary, theme.text, map_texture, ui)
+            .clicked()
+            && !self.awaiting_seed
+        {
+            play = true;
+        }
+        ui.add_space(20.0);
+
+        if let Some(leaderboard) = &self.leaderboard {
+            TextHelper::heavy("TODAY'S BEST", 12.0, None, ui).paint(Color32::WHITE, ui, true);
+            ui.add_space(8.0);
+            if leaderboard.top.is_empty() {
+                TextHelper::light("Nobody has played yet", 14.0, None, ui).paint(
+                    Color32::WHITE,
+                    ui,
+                    true,
+                );
+            }
+            for (rank, score) in leaderboard.top.iter().enumerate() {
+                let name = score.name.as_deref().unwrap_or("Anonymous");
+                TextHelper::light(
+                    &format!("{}. {name} - {}", rank + 1, score.solved),
+                    14.0,
+                    Some(ui.available_width() - 20.0),
+                    ui,
+                )
+                .paint(Color32::WHITE, ui, true);
+                ui.add_space(4.0);
+            }
+            if let Some(best) = leaderboard.personal_best {
+                ui.add_space(6.0);
+                TextHelper::light(&format!("Your best today: {best}"), 14.0, None, ui).paint(
+                    theme.word_valid,
+                    ui,
+                    true,
+                );
+            }
+        }
+
+        if self.player_token.is_none() {
+            ui.add_space(10.0);
+            TextHelper::light("Offline, so runs won't be ranked", 14.0, None, ui).paint(
+                Color32::WHITE,
+                ui,
+                true,
+            );
+        }
+
+        ui.add_space(20.0);
+        if TextHelper::heavy("BACK TO MENU", 12.0, None, ui)
+            .centered_button(theme.button_primary, theme.text, map_texture, ui)
+            .clicked()
+        {
+            back_to_menu();
+        }
+
+        if play {
+            if let Some(token) = &self.player_token {
+                self.awaiting_seed = true;
+                msgs.push(PlayerMessage::StartPuzzleRush(token.clone()));
+            } else {
+                // Offline runs aren't ranked, so any seed will do
+                let seed = (current_time.as_micros() % 243985691) as u32;
+                self.start(ui.ctx(), theme, map_texture, current_time, seed);
+            }
+        }
+
+        msgs
+    }
+
+    fn render_drill(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        current_time: Duration,
+        time_left: Duration,
+    ) -> Option<PlayerMessage> {
+        let (Some(rush), Some((drill, active_game))) = (&mut self.rush, &mut self.drill) else {
+            return None;
+        };
+
+        let secs = time_left.as_secs();
+        let penalty = self
+            .last_mistake_at
+            .filter(|at| current_time.saturating_sub(*at) < MISTAKE_FLASH)
+            .map(|_| format!(" -{RUSH_MISTAKE_PENALTY_SECS}s"))
+            .unwrap_or_default();
+        active_game.depot.ui_state.game_header = HeaderType::Summary {
+            title: format!(
+                "{}:{:02}{penalty}  SOLVED {}  STREAK {}",
+                secs / 60,
+                secs % 60,
+                rush.solved(),
+                rush.streak
+            ),
+            attempt: None,
+        };
+        // Keep the countdown ticking even when nothing else is happening
+        ui.ctx().request_repaint_after(Duration::from_millis(250));
+
+        let Some(PlayerMessage::Place(position, tile)) = active_game.render(ui, current_time, None)
+        else {
+            return None;
+        };
+
+        let solved = {
+            let dict_lock = get_main_dict();
+            let dict = dict_lock.as_ref().unwrap();
+            let next_move = Move::Place {
+                player: 0,
+                tile,
+                position,
+            };
+            rush.attempt(drill, next_move, dict)
+        };
+
+        if !solved {
+            self.last_mistake_at = Some(current_time);
+        }
+        // Either move on to the next drill, or put this one back how it was
+        self.load_drill(ui.ctx(), theme, map_texture);
+        if !solved {
+            if let Some((_, active_game)) = &mut self.drill {
+                active_game.depot.gameplay.error_msg = Some(format!(
+                    "That doesn't take the town, {RUSH_MISTAKE_PENALTY_SECS} seconds lost"
+                ));
+            }
+        }
+
+        None
+    }
+}
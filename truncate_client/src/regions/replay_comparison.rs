@@ -0,0 +1,319 @@
+use eframe::egui::{self, Rect};
+use epaint::{vec2, Color32, TextureHandle};
+use instant::Duration;
+use truncate_core::{
+    game::Game,
+    moves::Move,
+    replay::ReplayComparison,
+    reporting::{BoardChange, BoardChangeAction, BoardChangeDetail, Change},
+};
+
+use crate::utils::{
+    depot::{AestheticDepot, GameplayDepot, TimingDepot},
+    game_evals::get_main_dict,
+    mapper::MappedBoard,
+    text::TextHelper,
+    timing::get_qs_tick,
+    urls::back_to_menu,
+    Diaphanize, Theme,
+};
+
+/// How many quarter seconds each turn stays on screen while playing
+const TICKS_PER_TURN: u64 = 2;
+
+/// The board for one side of the comparison, which keeps its own textures
+#[derive(Clone)]
+struct ComparedBoard {
+    label: String,
+    mapped_board: MappedBoard,
+    aesthetics: AestheticDepot,
+    gameplay: GameplayDepot,
+}
+
+impl ComparedBoard {
+    fn new(
+        ctx: &egui::Context,
+        map_texture: TextureHandle,
+        label: String,
+        game: &Game,
+        as_player: usize,
+    ) -> Self {
+        let player_colors: Vec<_> = game
+            .players
+            .iter()
+            .map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2))
+            .collect();
+
+        let aesthetics = AestheticDepot {
+            theme: Theme::day(),
+            qs_tick: 0,
+            map_texture,
+            player_colors,
+            destruction_tick: 0.05,
+            destruction_duration: 0.6,
+        };
+        let mapped_board = MappedBoard::new(ctx, &aesthetics, &game.board, 2, as_player, true);
+
+        let gameplay = GameplayDepot {
+            room_code: "COMPARE".into(),
+            player_number: as_player as u64,
+            next_player_number: game.next_player.map(|p| p as u64),
+            error_msg: None,
+            winner: None,
+            changes: vec![],
+            last_battle_origin: None,
+            npc: None,
+            remaining_turns: None,
+            hand_swap_in: None,
+            end_state: None,
+            latency_ms: None,
+            arbiter_notice: None,
+            mulligan_offer: None,
+        };
+
+        Self {
+            label,
+            mapped_board,
+            aesthetics,
+            gameplay,
+        }
+    }
+
+    /// Catches the board's animations up with a move that was just played on it
+    fn moved(&mut self, game: &Game) {
+        self.gameplay.next_player_number = game.next_player.map(|p| p as u64);
+        self.gameplay.winner = game.winner;
+        self.gameplay.changes = game.recent_changes.clone();
+
+        let battle_occurred = game
+            .recent_changes
+            .iter()
+            .any(|change| matches!(change, Change::Battle(_)));
+        self.gameplay.last_battle_origin = if battle_occurred {
+            game.recent_changes.iter().find_map(|change| match change {
+                Change::Board(BoardChange {
+                    detail: BoardChangeDetail { coordinate, .. },
+                    action: BoardChangeAction::Added,
+                }) => Some(*coordinate),
+                _ => None,
+            })
+        } else {
+            None
+        };
+    }
+
+    fn render(&mut self, game: &Game, timing: &TimingDepot, rect: Rect, ui: &mut egui::Ui) {
+        ui.allocate_ui_at_rect(rect, |ui| {
+            TextHelper::heavy(&self.label, 10.0, None, ui).paint(
+                self.aesthetics.theme.text,
+                ui,
+                true,
+            );
+
+            self.mapped_board.remap_texture(
+                ui.ctx(),
+                &self.aesthetics,
+                timing,
+                None,
+                Some(&self.gameplay),
+                &game.board,
+            );
+
+            let mut board_space = ui.available_rect_before_wrap().shrink(10.0);
+            let height_from_width = game.board.height() as f32 / game.board.width() as f32;
+            let target_height = board_space.width() * height_from_width;
+
+            if target_height <= board_space.height() {
+                let diff = (board_space.height() - target_height) / 2.0;
+                board_space = board_space.shrink2(vec2(0.0, diff));
+            } else {
+                let width_from_height = game.board.width() as f32 / game.board.height() as f32;
+                let target_width = board_space.height() * width_from_height;
+                let diff = (board_space.width() - target_width) / 2.0;
+                board_space = board_space.shrink2(vec2(diff, 0.0));
+            }
+
+            self.mapped_board.render_to_rect(board_space, None, ui);
+        });
+    }
+}
+
+/// Plays two games of the same puzzle side by side, turn for turn,
+/// such as two friends' attempts at a daily puzzle
+#[derive(Clone)]
+pub struct ReplayComparisonState {
+    comparison: ReplayComparison,
+    boards: [ComparedBoard; 2],
+    map_texture: TextureHandle,
+    timing: TimingDepot,
+    playing: bool,
+    played_at_tick: Option<u64>,
+}
+
+impl ReplayComparisonState {
+    /// Takes each side as its label, the game it starts from, its moves,
+    /// and the player it should be seen from
+    pub fn new(
+        ctx: &egui::Context,
+        map_texture: TextureHandle,
+        left: (String, Game, Vec<Move>, usize),
+        right: (String, Game, Vec<Move>, usize),
+    ) -> Self {
+        let (left_label, mut left_game, left_moves, left_player) = left;
+        let (right_label, mut right_game, right_moves, right_player) = right;
+        left_game.rules.battle_delay = 0;
+        right_game.rules.battle_delay = 0;
+
+        let boards = [
+            ComparedBoard::new(
+                ctx,
+                map_texture.clone(),
+                left_label,
+                &left_game,
+                left_player,
+            ),
+            ComparedBoard::new(
+                ctx,
+                map_texture.clone(),
+                right_label,
+                &right_game,
+                right_player,
+            ),
+        ];
+
+        Self {
+            comparison: ReplayComparison::new((left_game, left_moves), (right_game, right_moves)),
+            boards,
+            map_texture,
+            timing: TimingDepot::default(),
+            playing: true,
+            played_at_tick: None,
+        }
+    }
+
+    fn step(&mut self, current_time: Duration) {
+        let dict_lock = get_main_dict();
+        let moved = self.comparison.step(dict_lock.as_ref());
+        drop(dict_lock);
+
+        for (side, board) in self.boards.iter_mut().enumerate() {
+            if moved[side] {
+                board.moved(self.comparison.game(side));
+            }
+        }
+        self.timing.last_turn_change = current_time;
+    }
+
+    /// Jumps straight to a turn, without animating the moves in between
+    fn seek(&mut self, turn: usize, current_time: Duration) {
+        let dict_lock = get_main_dict();
+        self.comparison.seek(turn, dict_lock.as_ref());
+        drop(dict_lock);
+
+        for (side, board) in self.boards.iter_mut().enumerate() {
+            board.moved(self.comparison.game(side));
+            board.gameplay.changes.clear();
+            board.gameplay.last_battle_origin = None;
+        }
+        self.timing.last_turn_change = current_time;
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, theme: &Theme, current_time: Duration) {
+        let now = get_qs_tick(current_time);
+        let start = *self.played_at_tick.get_or_insert(now);
+        self.timing.current_time = current_time;
+
+        if self.playing && now.saturating_sub(start) >= TICKS_PER_TURN {
+            if self.comparison.is_finished() {
+                self.playing = false;
+            } else {
+                self.step(current_time);
+            }
+            self.played_at_tick = Some(now);
+        }
+
+        ui.add_space(20.0);
+
+        ui.horizontal(|ui| {
+            let button_color = Color32::WHITE.diaphanize();
+
+            if TextHelper::heavy("MENU", 10.0, None, ui)
+                .button(theme.button_primary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                back_to_menu();
+            }
+
+            let play_label = if self.playing { "PAUSE" } else { "PLAY" };
+            if TextHelper::heavy(play_label, 10.0, None, ui)
+                .button(button_color, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                if self.comparison.is_finished() {
+                    self.seek(0, current_time);
+                }
+                self.playing = !self.playing;
+                self.played_at_tick = Some(now);
+            }
+
+            if TextHelper::heavy("BACK", 10.0, None, ui)
+                .button(button_color, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.playing = false;
+                let turn = self.comparison.turn().saturating_sub(1);
+                self.seek(turn, current_time);
+            }
+
+            if TextHelper::heavy("NEXT", 10.0, None, ui)
+                .button(button_color, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.playing = false;
+                self.step(current_time);
+            }
+
+            if TextHelper::heavy("RESTART", 10.0, None, ui)
+                .button(button_color, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.seek(0, current_time);
+                self.playing = true;
+                self.played_at_tick = Some(now);
+            }
+        });
+
+        ui.add_space(10.0);
+        let line = format!(
+            "TURN {} OF {}",
+            self.comparison.turn(),
+            self.comparison.total_turns()
+        );
+        TextHelper::heavy(&line, 10.0, None, ui).paint(theme.text, ui, true);
+        for side in 0..2 {
+            if let Some(error) = self.comparison.error(side) {
+                let line = format!("{}: {error}", self.boards[side].label);
+                TextHelper::light(&line, 10.0, Some(ui.available_width()), ui).paint(
+                    theme.word_invalid,
+                    ui,
+                    true,
+                );
+            }
+        }
+        ui.add_space(10.0);
+
+        // Boards sit side by side on wide screens, and one above the other on tall ones
+        let space = ui.available_rect_before_wrap();
+        let halves = if space.width() >= space.height() {
+            let middle = space.center().x;
+            [space.with_max_x(middle), space.with_min_x(middle)]
+        } else {
+            let middle = space.center().y;
+            [space.with_max_y(middle), space.with_min_y(middle)]
+        };
+
+        for (side, half) in halves.into_iter().enumerate() {
+            self.boards[side].render(self.comparison.game(side), &self.timing, half, ui);
+        }
+    }
+}
@@ -2,13 +2,23 @@ use eframe::egui;
 use epaint::{vec2, Color32, TextureHandle};
 use instant::Duration;
 use truncate_core::{
+    archive::GameArchive,
     game::Game,
+    generation::BoardSeed,
+    judge::WordDict,
+    messages::TruncateToken,
     moves::Move,
+    npc::{
+        endgame::{EndgameLimits, EndgameSolver, Proof},
+        key_moments::KeyMoment,
+    },
     reporting::{BoardChange, BoardChangeAction, BoardChangeDetail, Change},
 };
 
 use crate::{
     app_outer::Backchannel,
+    lil_bits::TimerUI,
+    regions::prediction_trainer::PredictionTrainerState,
     utils::{
         depot::{AestheticDepot, GameplayDepot, TimingDepot},
         game_evals::get_main_dict,
@@ -46,12 +56,21 @@ pub struct ReplayerState {
     mapped_board: MappedBoard,
     theme: Theme,
     move_sequence: Vec<Move>,
+    /// Seconds spent on each move in the sequence, as recorded when it was played
+    think_secs: Vec<u32>,
     next_move: usize,
     played_at_tick: Option<u64>,
     playback_speed: PlaybackSpeed,
+    /// The key moment the replay is paused on, if it was opened from one
+    moment: Option<KeyMoment>,
+    /// What the endgame solver proved about the current position, and for which player,
+    /// once the game is small enough to solve
+    verdict: Option<(usize, Proof)>,
     aesthetics: AestheticDepot,
     timing: TimingDepot,
     gameplay: GameplayDepot,
+    /// The seed the board was generated from, which the game's notation needs
+    board_seed: Option<BoardSeed>,
 }
 
 impl ReplayerState {
@@ -61,6 +80,7 @@ impl ReplayerState {
         theme: Theme,
         mut game: Game,
         move_sequence: Vec<Move>,
+        think_secs: Vec<u32>,
         as_player: usize,
     ) -> Self {
         game.rules.battle_delay = 0;
@@ -91,6 +111,11 @@ impl ReplayerState {
             last_battle_origin: None,
             npc: None,
             remaining_turns: None,
+            hand_swap_in: None,
+            end_state: None,
+            latency_ms: None,
+            arbiter_notice: None,
+            mulligan_offer: None,
         };
 
         game.start();
@@ -103,15 +128,24 @@ impl ReplayerState {
             mapped_board,
             theme,
             move_sequence,
+            think_secs,
             next_move: 0,
             played_at_tick: None,
             playback_speed: PlaybackSpeed::Regular,
+            moment: None,
+            verdict: None,
             aesthetics,
             timing: TimingDepot::default(),
             gameplay,
+            board_seed: None,
         }
     }
 
+    pub fn with_seed(mut self, seed: BoardSeed) -> Self {
+        self.board_seed = Some(seed);
+        self
+    }
+
     pub fn play_next_turn(&mut self, current_time: Duration, qs_tick: u64) {
         let Some(next_move) = self.move_sequence.get(self.next_move) else {
             return;
@@ -124,6 +158,7 @@ impl ReplayerState {
             .play_turn(next_move.clone(), Some(dict), Some(dict), None);
 
         self.next_move += 1;
+        self.solve_endgame(dict);
 
         self.timing.last_turn_change = current_time;
 
@@ -172,13 +207,61 @@ impl ReplayerState {
         }
     }
 
+    /// Skips straight past the move of a key moment, pausing there with its explanation shown
+    pub fn jump_to(&mut self, moment: KeyMoment) {
+        let dict_lock = get_main_dict();
+        let dict = dict_lock.as_ref().unwrap();
+
+        self.game = self.base_game.clone();
+        for next_move in self.move_sequence.iter().take(moment.turn + 1) {
+            _ = self
+                .game
+                .play_turn(next_move.clone(), Some(dict), Some(dict), None);
+        }
+        self.next_move = moment.turn + 1;
+        self.solve_endgame(dict);
+
+        self.gameplay.next_player_number = self.game.next_player.map(|p| p as u64);
+        self.gameplay.changes = self.game.recent_changes.clone();
+        self.gameplay.last_battle_origin = None;
+        self.moment = Some(moment);
+    }
+
+    /// Solves the position if it has become small enough, so the replay can show who was bound to win
+    fn solve_endgame(&mut self, dict: &WordDict) {
+        let Some(player) = self.game.next_player else {
+            self.verdict = None;
+            return;
+        };
+        let mut solver = EndgameSolver::new(EndgameLimits::default(), [Some(dict); 2]);
+        let proof = solver.solve(&self.game);
+        self.verdict = proof.is_proven().then_some((player, proof));
+    }
+
+    /// Trains on the game from the start, guessing the opponent's moves
+    pub fn prediction_trainer(
+        &self,
+        ctx: &egui::Context,
+        player_token: Option<TruncateToken>,
+    ) -> PredictionTrainerState {
+        PredictionTrainerState::new(
+            ctx,
+            self.map_texture.clone(),
+            self.base_game.clone(),
+            self.move_sequence.clone(),
+            self.as_player,
+            player_token,
+        )
+    }
+
+    /// Returns whether the player asked to train on the game by predicting its moves
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
         theme: &Theme,
         current_time: Duration,
         _backchannel: &Backchannel,
-    ) {
+    ) -> bool {
         let start = self
             .played_at_tick
             .get_or_insert_with(|| get_qs_tick(current_time));
@@ -187,7 +270,7 @@ impl ReplayerState {
 
         self.timing.current_time = current_time.clone();
 
-        if elapsed >= self.playback_speed.ticks() {
+        if self.moment.is_none() && elapsed >= self.playback_speed.ticks() {
             self.play_next_turn(current_time, now);
         }
 
@@ -208,14 +291,108 @@ impl ReplayerState {
             .centered_button(theme.button_primary, theme.text, &self.map_texture, ui)
             .clicked()
         {
+            let board_seed = self.board_seed.take();
             *self = Self::new(
                 ui.ctx(),
                 self.map_texture.clone(),
                 theme.clone(),
                 self.base_game.clone(),
                 self.move_sequence.clone(),
+                self.think_secs.clone(),
                 self.as_player,
             );
+            self.board_seed = board_seed;
+        }
+
+        ui.add_space(20.0);
+
+        let text = TextHelper::heavy("PREDICT MOVES", 12.0, None, ui);
+        let predict_clicked = text
+            .centered_button(theme.button_primary, theme.text, &self.map_texture, ui)
+            .clicked();
+
+        if let Some(board_seed) = &self.board_seed {
+            ui.add_space(20.0);
+
+            let text = TextHelper::heavy("COPY NOTATION", 12.0, None, ui);
+            if text
+                .centered_button(theme.button_primary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                let dict_lock = get_main_dict();
+                let archive = GameArchive::from_game(
+                    "Truncate".to_string(),
+                    &self.base_game,
+                    board_seed,
+                    &self.move_sequence,
+                    dict_lock.as_ref(),
+                );
+                ui.ctx().output_mut(|o| o.copied_text = archive.to_string());
+            }
+        }
+
+        if let Some(moment) = &self.moment {
+            ui.add_space(20.0);
+            let text = TextHelper::heavy("CONTINUE REPLAY", 12.0, None, ui);
+            let continue_button =
+                text.centered_button(theme.button_primary, theme.text, &self.map_texture, ui);
+
+            ui.add_space(20.0);
+            TextHelper::heavy(
+                &moment.explanation.to_uppercase(),
+                10.0,
+                Some(ui.available_width() - 20.0),
+                ui,
+            )
+            .paint(theme.text, ui, true);
+
+            if continue_button.clicked() {
+                self.moment = None;
+                self.played_at_tick = Some(now);
+            }
+        }
+
+        let last_move = self.next_move.checked_sub(1);
+        if let Some((played, secs)) =
+            last_move.and_then(|i| Some((self.move_sequence.get(i)?, self.think_secs.get(i)?)))
+        {
+            let player = match played {
+                Move::Place { player, .. }
+                | Move::Swap { player, .. }
+                | Move::Exchange { player, .. }
+                | Move::Mulligan { player } => *player,
+            };
+            let who = if player == self.as_player {
+                "YOU"
+            } else {
+                "COMPUTER"
+            };
+            let line = format!(
+                "MOVE {}: {who} THOUGHT FOR {}",
+                self.next_move,
+                TimerUI::human_time(*secs as i64, true)
+            );
+
+            ui.add_space(20.0);
+            TextHelper::heavy(&line, 10.0, None, ui).paint(theme.text, ui, true);
+        }
+
+        if let Some((player, proof)) = self.verdict {
+            let (winner, turns) = match proof {
+                Proof::Win { turns } => (player, turns),
+                Proof::Loss { turns } => ((player + 1) % 2, turns),
+                Proof::Unproven => unreachable!("Only proven positions are kept"),
+            };
+            let outlook = if winner == self.as_player {
+                "YOU ARE WINNING"
+            } else {
+                "YOU ARE LOSING"
+            };
+            let line = format!("ENDGAME SOLVED: {outlook}, FORCED WITHIN {turns} TURN(S)");
+
+            ui.add_space(20.0);
+            TextHelper::heavy(&line, 10.0, Some(ui.available_width() - 20.0), ui)
+                .paint(theme.text, ui, true);
         }
 
         self.mapped_board.remap_texture(
@@ -243,5 +420,7 @@ impl ReplayerState {
         }
 
         self.mapped_board.render_to_rect(board_space, None, ui);
+
+        predict_clicked
     }
 }
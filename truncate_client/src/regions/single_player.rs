@@ -1,15 +1,24 @@
 use eframe::egui::{self, Layout, Sense};
-use epaint::{emath::Align, hex_color, vec2, TextureHandle};
+use epaint::{emath::Align, hex_color, vec2, Color32, TextureHandle};
 use instant::Duration;
 use truncate_core::{
     board::Board,
     game::{Game, GAME_COLOR_BLUE, GAME_COLOR_RED},
     generation::BoardSeed,
-    messages::{DailyStats, GamePlayerMessage, GameStateMessage, PlayerMessage},
+    messages::{
+        DailyStats, GameEndStateMessage, GamePlayerMessage, GameStateMessage, PlayerMessage,
+    },
     moves::Move,
-    npc::scoring::NPCPersonality,
-    reporting::WordMeaning,
-    rules::GameRules,
+    npc::{
+        forced_wins::ForcedWinPuzzle,
+        key_moments::{key_moments, KeyMoment},
+        scoring::NPCPersonality,
+        SearchReport, SearchShare, ShareResult,
+    },
+    replay::Replay,
+    reporting::{AttemptSummary, WordMeaning},
+    rotation::PuzzleCategory,
+    rules::{GameRules, Language, Visibility},
 };
 
 use crate::{
@@ -19,20 +28,38 @@ use crate::{
         ResultModalUI,
     },
     utils::{
-        game_evals::{client_best_move, forget, get_main_dict, remember},
+        game_evals::{forget, get_dict, remember, search_threads},
+        perf,
         text::TextHelper,
         Theme,
     },
 };
 
-use super::active_game::{ActiveGame, GameLocation, HeaderType};
+use super::{
+    active_game::{ActiveGame, GameLocation, HeaderType},
+    replayer::ReplayerState,
+};
+
+#[cfg(target_arch = "wasm32")]
+use crate::utils::game_evals::client_best_move;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::utils::game_evals::BackgroundSearch;
+
+/// How many key moments are shown once a game is over
+const KEY_MOMENT_COUNT: usize = 3;
 
 #[derive(Clone)]
 pub struct SinglePlayerState {
     pub name: String,
     pub game: Game,
     rules_generation: u32,
+    /// The language of the game's letters and words, kept across any rematches
+    language: Language,
     human_starts: bool,
+    /// Whether the board is hidden from the human until their tiles reach it
+    mystery: bool,
+    /// Which run of puzzles this game belongs to, where it's a puzzle
+    category: PuzzleCategory,
     pub active_game: ActiveGame,
     next_response_at: Option<Duration>,
     winner: Option<usize>,
@@ -41,14 +68,27 @@ pub struct SinglePlayerState {
     turns: usize,
     debugging_npc: bool,
     npc: NPCPersonality,
-    waiting_on_backchannel: Option<String>,
+    /// The messages for each share of the NPC's search, while it's running on the backchannel
+    waiting_on_backchannel: Vec<String>,
+    /// The NPC's search when there's no backchannel to run it on
+    #[cfg(not(target_arch = "wasm32"))]
+    background_search: Option<BackgroundSearch>,
     pub header: HeaderType,
     pub daily_stats: Option<DailyStats>,
     pub best_game: Option<Game>,
     splash: Option<ResultModalUI>,
     hide_splash: bool,
     pub move_sequence: Vec<Move>,
+    /// Seconds spent on each move in the sequence
+    pub think_secs: Vec<u32>,
+    /// The game as it was before the first move in the sequence
+    starting_game: Option<Game>,
+    /// A replay of this game to switch to, opened from a key moment
+    pub moment_replay: Option<ReplayerState>,
     event_dispatcher: EventDispatcher,
+    human_color: (u8, u8, u8),
+    /// The forced win being solved, where the NPC defends it rather than playing freely
+    forced_win: Option<ForcedWinPuzzle>,
 }
 
 impl SinglePlayerState {
@@ -60,6 +100,7 @@ impl SinglePlayerState {
         mut board: Board,
         seed: Option<BoardSeed>,
         rules_generation: u32,
+        language: Language,
         human_starts: bool,
         header: HeaderType,
         npc: NPCPersonality,
@@ -86,6 +127,9 @@ impl SinglePlayerState {
             game.players[0].color = GAME_COLOR_RED;
             game.players[1].color = GAME_COLOR_BLUE;
         }
+        if language != Language::English {
+            game.set_language(language);
+        }
 
         board.cache_special_squares();
         game.board = board.clone();
@@ -119,7 +163,10 @@ impl SinglePlayerState {
             name,
             game,
             rules_generation,
+            language,
             human_starts,
+            mystery: false,
+            category: PuzzleCategory::Daily,
             active_game,
             next_response_at: None,
             winner: None,
@@ -128,17 +175,149 @@ impl SinglePlayerState {
             turns: 0,
             debugging_npc: false,
             npc,
-            waiting_on_backchannel: None,
+            waiting_on_backchannel: vec![],
+            #[cfg(not(target_arch = "wasm32"))]
+            background_search: None,
             header,
             daily_stats: None,
             best_game: None,
             splash: None,
             hide_splash: false,
             move_sequence: vec![],
+            think_secs: vec![],
+            starting_game: None,
+            moment_replay: None,
             event_dispatcher,
+            human_color: GAME_COLOR_BLUE,
+            forced_win: None,
+        }
+    }
+
+    /// Recolours the human player's tiles, keeping the colour across any rematches
+    pub fn set_human_color(&mut self, color: (u8, u8, u8)) {
+        let human_player = if self.human_starts { 0 } else { 1 };
+        self.human_color = color;
+
+        self.game.players[human_player].color = color;
+        if let Some(player) = self.active_game.players.get_mut(human_player) {
+            player.color = color;
+        }
+        if let Some(player_color) = self
+            .active_game
+            .depot
+            .aesthetics
+            .player_colors
+            .get_mut(human_player)
+        {
+            *player_color = Color32::from_rgb(color.0, color.1, color.2);
+        }
+    }
+
+    /// Hides the board from the human, for it to be revealed as their territory expands.
+    /// The NPC still sees the whole board, as it did when the puzzle was verified.
+    pub fn set_mystery(&mut self) {
+        let human_player = if self.human_starts { 0 } else { 1 };
+        self.mystery = true;
+        self.game.rules.visibility = Visibility::Mystery;
+
+        let (filtered_board, _) = self.game.filter_game_to_player(human_player);
+        self.active_game.board = filtered_board;
+    }
+
+    /// Marks the game as one of a category's puzzles, dealing from the category's bag if it has one
+    pub fn set_category(&mut self, category: PuzzleCategory) {
+        let human_player = if self.human_starts { 0 } else { 1 };
+        self.category = category;
+
+        if let Some(distribution) = category.tile_distribution(self.game.rules.tile_generation) {
+            self.game.use_tile_distribution(distribution);
+            self.active_game.hand = self.game.players[human_player].hand.clone();
+            self.active_game.mapped_hand.invalidate();
+        }
+    }
+
+    /// Sets up a forced win puzzle, which the human has to win within its turns.
+    /// Any move that lets the win slip away loses the puzzle.
+    pub fn set_forced_win(&mut self, puzzle: ForcedWinPuzzle, ctx: &egui::Context) {
+        let human_player = puzzle.player;
+        let mut game = puzzle.game();
+        game.board.cache_special_squares();
+        for (index, player) in game.players.iter_mut().enumerate() {
+            player.color = if index == human_player {
+                self.human_color
+            } else {
+                GAME_COLOR_RED
+            };
+        }
+
+        let (filtered_board, _) = game.filter_game_to_player(human_player);
+        let mut active_game = ActiveGame::new(
+            ctx,
+            "SINGLE_PLAYER".into(),
+            None,
+            Some(self.npc.clone()),
+            game.players
+                .iter()
+                .map(|p| GamePlayerMessage::new(p, &game))
+                .collect(),
+            human_player as u64,
+            game.next_player.map(|p| p as u64),
+            filtered_board,
+            game.players[human_player].hand.clone(),
+            self.map_texture.clone(),
+            self.theme.clone(),
+            GameLocation::Local,
+            None,
+            None,
+        );
+        active_game.depot.ui_state.game_header = self.header.clone();
+
+        self.human_starts = human_player == 0;
+        self.game = game;
+        self.active_game = active_game;
+        self.turns = 0;
+        self.next_response_at = None;
+        self.winner = None;
+        self.move_sequence = vec![];
+        self.think_secs = vec![];
+        self.starting_game = None;
+        self.forced_win = Some(puzzle);
+    }
+
+    /// Clears the NPC's progress once its move is in, recording how long it took
+    fn finish_npc_search(&mut self, current_time: Duration) {
+        let timing = &mut self.active_game.depot.timing;
+        timing.npc_search = None;
+        if let Some(since) = timing.npc_thinking_since.take() {
+            perf::record_npc_compute(current_time.saturating_sub(since).as_millis() as u64);
+        }
+    }
+
+    /// The board the NPC evaluates its moves on
+    fn npc_board(&self, npc_player: usize) -> Board {
+        if self.mystery {
+            self.game.board.clone()
+        } else {
+            self.game.filter_game_to_player(npc_player).0
+        }
+    }
+
+    pub fn human_won(&self) -> bool {
+        self.winner == Some(self.human_player())
+    }
+
+    pub fn human_player(&self) -> usize {
+        if self.human_starts {
+            0
+        } else {
+            1
         }
     }
 
+    pub fn rules_generation(&self) -> u32 {
+        self.rules_generation
+    }
+
     fn sub_event(&mut self, event: String) {
         self.event_dispatcher
             .event(format!("single_player_{}_{}", self.name, event));
@@ -178,25 +357,39 @@ impl SinglePlayerState {
         ctx: &egui::Context,
         backchannel: &Backchannel,
     ) {
+        // Forced wins start again from the same position
+        if let Some(puzzle) = self.forced_win.clone() {
+            return self.set_forced_win(puzzle, ctx);
+        }
+
         let mut game = Game::new(
             9,
             9,
             Some(seed.seed as u64),
             GameRules::generation(self.rules_generation),
         );
+        if self.mystery {
+            game.rules.visibility = Visibility::Mystery;
+        }
         self.human_starts = human_starts;
         if self.human_starts {
             game.add_player("You".into());
             game.add_player("Computer".into());
 
-            game.players[0].color = GAME_COLOR_BLUE;
+            game.players[0].color = self.human_color;
             game.players[1].color = GAME_COLOR_RED;
         } else {
             game.add_player("Computer".into());
             game.add_player("You".into());
 
             game.players[0].color = GAME_COLOR_RED;
-            game.players[1].color = GAME_COLOR_BLUE;
+            game.players[1].color = self.human_color;
+        }
+        if self.language != Language::English {
+            game.set_language(self.language);
+        }
+        if let Some(distribution) = self.category.tile_distribution(game.rules.tile_generation) {
+            game.use_tile_distribution(distribution);
         }
 
         let mut rand_board = truncate_core::generation::generate_board(seed.clone())
@@ -207,6 +400,8 @@ impl SinglePlayerState {
         game.board = rand_board;
         game.start();
 
+        let (filtered_board, _) = game.filter_game_to_player(if self.human_starts { 0 } else { 1 });
+
         let mut active_game = ActiveGame::new(
             ctx,
             "SINGLE_PLAYER".into(),
@@ -218,7 +413,7 @@ impl SinglePlayerState {
                 .collect(),
             if self.human_starts { 0 } else { 1 },
             Some(0),
-            game.board.clone(),
+            filtered_board,
             game.players[if self.human_starts { 0 } else { 1 }]
                 .hand
                 .clone(),
@@ -238,6 +433,8 @@ impl SinglePlayerState {
         self.next_response_at = None;
         self.winner = None;
         self.move_sequence = vec![];
+        self.think_secs = vec![];
+        self.starting_game = None;
         self.event_dispatcher = self.event_dispatcher.clone();
 
         if backchannel.is_open() {
@@ -247,6 +444,46 @@ impl SinglePlayerState {
         }
     }
 
+    /// The turning points of the game so far, as the NPC evaluates them
+    fn key_moments(&self) -> Vec<KeyMoment> {
+        let Some(starting_game) = &self.starting_game else {
+            return vec![];
+        };
+
+        let dict_lock = get_dict(self.language);
+        let replay = Replay::from_game(
+            starting_game.clone(),
+            self.move_sequence.clone(),
+            dict_lock.as_ref(),
+        );
+        key_moments(
+            replay,
+            dict_lock.as_ref(),
+            &self.npc.params,
+            KEY_MOMENT_COUNT,
+        )
+    }
+
+    /// The finished puzzle as a grid of battles, for the header to copy
+    fn share_summary(&self, human_player: usize) -> Option<String> {
+        let HeaderType::Summary {
+            title,
+            attempt: Some(_),
+        } = &self.header
+        else {
+            return None;
+        };
+        let starting_game = self.starting_game.as_ref()?;
+
+        let dict_lock = get_dict(self.language);
+        let replay = Replay::from_game(
+            starting_game.clone(),
+            self.move_sequence.clone(),
+            dict_lock.as_ref(),
+        );
+        Some(AttemptSummary::from_replay(replay, human_player).share_text(title))
+    }
+
     /// If the server sent through some new word definitions,
     /// dig deep and update all past battles to reference the definitions
     pub fn hydrate_meanings(&mut self, definitions: Vec<(String, Option<Vec<WordMeaning>>)>) {
@@ -282,13 +519,14 @@ impl SinglePlayerState {
         let human_player = if self.human_starts { 0 } else { 1 };
 
         self.turns += 1;
-        let dict_lock = get_main_dict();
+        let dict_lock = get_dict(self.language);
         let dict = dict_lock.as_ref().unwrap();
 
         // When actually playing the turn, make sure we pass in the real dict
         // for both the attack and defense roles.
         match self.game.play_turn(next_move, Some(dict), Some(dict), None) {
             Ok(winner) => {
+                let winner = winner.or_else(|| self.game.resolve_endgame([Some(dict); 2]));
                 self.winner = winner;
 
                 if track_events {
@@ -298,23 +536,13 @@ impl SinglePlayerState {
                         } else {
                             self.sub_event("lost".to_string())
                         }
+                    } else if self.game.is_over() {
+                        self.sub_event("drew".to_string())
                     }
                 }
 
-                let changes: Vec<_> = self
-                    .game
-                    .recent_changes
-                    .clone()
-                    .into_iter()
-                    .filter(|change| match change {
-                        truncate_core::reporting::Change::Board(_) => true,
-                        truncate_core::reporting::Change::Hand(hand_change) => {
-                            hand_change.player == human_player
-                        }
-                        truncate_core::reporting::Change::Battle(_) => true,
-                        truncate_core::reporting::Change::Time(_) => true,
-                    })
-                    .collect();
+                // Only the human's hand changes are kept, along with anything on the board they can see
+                let (board, changes) = self.game.filter_game_to_player(human_player);
 
                 let battle_words: Vec<_> = changes
                     .iter()
@@ -364,19 +592,30 @@ impl SinglePlayerState {
                         .collect(),
                     player_number: human_player as u64,
                     next_player_number: self.game.next_player.map(|p| p as u64),
-                    board: self.game.board.clone(),
+                    board,
                     hand: self.game.players[human_player].hand.clone(),
                     changes,
+                    event_seq: 0,
                     game_ends_at: None,
                     paused: false,
                     remaining_turns: None,
+                    hand_swap_in: self.game.turns_until_hand_swap(),
                 };
                 self.active_game.apply_new_state(state_message);
 
+                // Drawn games have no winner, so the end state is what marks them as over
+                if winner.is_none() {
+                    if let Some(adjudication) = &self.game.adjudication {
+                        self.active_game.depot.gameplay.error_msg = Some(adjudication.to_string());
+                        self.active_game.depot.gameplay.end_state =
+                            GameEndStateMessage::new(&self.game);
+                    }
+                }
+
                 return Ok(battle_words);
             }
             Err(msg) => {
-                self.active_game.depot.gameplay.error_msg = Some(msg);
+                self.active_game.depot.gameplay.error_msg = Some(msg.to_string());
                 return Err(());
             }
         }
@@ -502,12 +741,41 @@ impl SinglePlayerState {
                     Some(ResultModalAction::SharedReplay) => {
                         self.sub_event("shared_replay".to_string());
                     }
+                    Some(ResultModalAction::ExportGames) => {
+                        self.sub_event("exported_games".to_string());
+                        if let Some(token) = logged_in_as {
+                            msgs_to_server.push(PlayerMessage::ExportGames(token.clone()));
+                        }
+                    }
+                    Some(ResultModalAction::ViewMoment(moment)) => {
+                        self.sub_event("viewed_key_moment".to_string());
+                        if let Some(starting_game) = &self.starting_game {
+                            let mut replayer = ReplayerState::new(
+                                ui.ctx(),
+                                self.map_texture.clone(),
+                                theme.clone(),
+                                starting_game.clone(),
+                                self.move_sequence.clone(),
+                                self.think_secs.clone(),
+                                human_player,
+                            );
+                            if let Some(seed) = &self.active_game.depot.board_info.board_seed {
+                                replayer = replayer.with_seed(seed.clone());
+                            }
+                            replayer.jump_to(moment);
+                            self.moment_replay = Some(replayer);
+                        }
+                    }
                     None => {}
                 }
             }
         }
 
-        if self.winner.is_some() {
+        if self.winner.is_some() || self.game.is_over() {
+            if self.active_game.depot.ui_state.share_summary.is_none() {
+                self.active_game.depot.ui_state.share_summary = self.share_summary(human_player);
+            }
+
             let is_daily_puzzle = self
                 .active_game
                 .depot
@@ -515,12 +783,16 @@ impl SinglePlayerState {
                 .board_seed
                 .as_ref()
                 .map(|s| s.day)
-                .flatten();
+                .flatten()
+                .filter(|_| self.category == PuzzleCategory::Daily);
 
             if let Some(puzzle_day) = is_daily_puzzle {
                 if let Some(token) = logged_in_as {
                     if self.splash.is_none() {
-                        msgs_to_server.push(PlayerMessage::RequestStats(token.clone()));
+                        msgs_to_server.push(PlayerMessage::RequestStats(
+                            token.clone(),
+                            self.game.rules.language,
+                        ));
                     }
                 }
 
@@ -569,11 +841,15 @@ impl SinglePlayerState {
                     self.splash = Some(ResultModalUI::new_loading(&mut ui));
 
                     if let Some(token) = logged_in_as {
-                        msgs_to_server.push(PlayerMessage::RequestStats(token.clone()));
+                        msgs_to_server.push(PlayerMessage::RequestStats(
+                            token.clone(),
+                            self.game.rules.language,
+                        ));
                     }
                 }
             } else {
                 if self.splash.is_none() {
+                    let key_moments = self.key_moments();
                     self.splash = Some(ResultModalUI::new_unique(
                         &mut ui,
                         &self.game,
@@ -582,6 +858,7 @@ impl SinglePlayerState {
                             self.winner,
                             Some(p) if  p == human_player
                         ),
+                        key_moments,
                     ));
                 }
             }
@@ -598,50 +875,159 @@ impl SinglePlayerState {
         self.next_response_at = None;
 
         if self.game.next_player.unwrap() == npc_player {
-            if let Some(turn_starts_no_later_than) = self
+            if let Some(puzzle) = &self.forced_win {
+                // The defence is solved rather than searched, which is quick over a puzzle's few turns
+                let dict_lock = get_dict(self.language);
+                if let Some(Move::Place { tile, position, .. }) =
+                    puzzle.defence(&self.game, dict_lock.as_ref().unwrap())
+                {
+                    next_msg = Some((npc_player, PlayerMessage::Place(position, tile)));
+                }
+            } else if let Some(turn_starts_no_later_than) = self
                 .game
                 .get_player(self.game.next_player.unwrap())
                 .unwrap()
                 .turn_starts_no_later_than
             {
+                // Everyone should face the same bot on the daily puzzle, however fast their device
+                let is_daily = self
+                    .active_game
+                    .depot
+                    .board_info
+                    .board_seed
+                    .as_ref()
+                    .is_some_and(|seed| seed.day.is_some());
+                let npc_budget_ms = if is_daily {
+                    None
+                } else {
+                    self.active_game.depot.ui_state.npc_budget_ms
+                };
+
                 if backchannel.is_open() {
-                    if let Some(pending_msg) = &self.waiting_on_backchannel {
+                    if !self.waiting_on_backchannel.is_empty() {
+                        let reports: Vec<SearchReport> = self
+                            .waiting_on_backchannel
+                            .iter()
+                            .filter_map(|id| {
+                                backchannel
+                                    .send_msg(crate::app_outer::BackchannelMsg::ProgressOf {
+                                        id: id.clone(),
+                                    })
+                                    .and_then(|p| serde_json::from_str(&p).ok())
+                            })
+                            .collect();
+                        if !reports.is_empty() {
+                            // The search is only as deep as the shallowest of its shares
+                            let all_reported = reports.len() == self.waiting_on_backchannel.len();
+                            self.active_game.depot.timing.npc_search = Some(SearchReport {
+                                assessed: reports.iter().map(|r| r.assessed).sum(),
+                                depth: reports
+                                    .iter()
+                                    .map(|r| r.depth)
+                                    .min()
+                                    .filter(|_| all_reported)
+                                    .unwrap_or_default(),
+                            });
+                        }
+
                         // Do nothing if a message is pending but our turn hasn't yet started,
                         // we'll fetch the turn once we're allowed to play.
                         // It is allowed to play here, but waiting lets battle animations play out.
                         if turn_starts_no_later_than <= current_time.as_secs() {
-                            let msg_response =
-                                backchannel.send_msg(crate::app_outer::BackchannelMsg::QueryFor {
-                                    id: pending_msg.clone(),
-                                });
-                            if let Some(msg_response) = msg_response {
-                                let player_msg: PlayerMessage = serde_json::from_str(&msg_response)
-                                    .expect("Backchannel should be sending valid JSON");
-                                next_msg = Some((npc_player, player_msg));
-                                self.waiting_on_backchannel = None;
+                            let results: Vec<ShareResult> = self
+                                .waiting_on_backchannel
+                                .iter()
+                                .map_while(|id| {
+                                    backchannel.send_msg(
+                                        crate::app_outer::BackchannelMsg::QueryFor {
+                                            id: id.clone(),
+                                        },
+                                    )
+                                })
+                                .map(|response| {
+                                    serde_json::from_str(&response)
+                                        .expect("Backchannel should be sending valid JSON")
+                                })
+                                .collect();
+                            if results.len() == self.waiting_on_backchannel.len() {
+                                if let Some(player_msg) = ShareResult::combine(&results) {
+                                    next_msg = Some((npc_player, player_msg));
+                                }
+                                self.waiting_on_backchannel.clear();
+                                self.finish_npc_search(current_time);
                             }
                         }
                     } else {
-                        let (filtered_board, _) = self.game.filter_game_to_player(npc_player);
-                        let pending_msg =
-                            backchannel.send_msg(crate::app_outer::BackchannelMsg::EvalGame {
-                                board: filtered_board,
-                                rules: self.game.rules.clone(),
-                                players: self.game.players.clone(),
-                                next_player: npc_player,
-                                npc_params: self.npc.params,
-                            });
-                        self.waiting_on_backchannel = pending_msg;
+                        // Budgeted searches are spread across the host's workers,
+                        // while the rest are searched whole so that they're reproducible
+                        let of = match npc_budget_ms {
+                            Some(_) => search_threads(self.active_game.depot.ui_state.npc_threads),
+                            None => 1,
+                        };
+                        let filtered_board = self.npc_board(npc_player);
+                        self.waiting_on_backchannel = (0..of)
+                            .filter_map(|index| {
+                                backchannel.send_msg(crate::app_outer::BackchannelMsg::EvalGame {
+                                    board: filtered_board.clone(),
+                                    rules: self.game.rules.clone(),
+                                    players: self.game.players.clone(),
+                                    next_player: npc_player,
+                                    npc_params: self.npc.params,
+                                    budget_ms: npc_budget_ms,
+                                    share: SearchShare { index, of },
+                                })
+                            })
+                            .collect();
+
+                        self.active_game.depot.timing.npc_thinking_since = Some(current_time);
                     }
                 } else {
-                    // If we have no backchannel available to evaluate moves through,
-                    // just evaluate the move on this thread and live with blocking.
-                    let (filtered_board, _) = self.game.filter_game_to_player(npc_player);
-                    let mut evaluation_game = self.game.clone();
-                    evaluation_game.board = filtered_board;
+                    // Without a backchannel to evaluate moves through, search on another thread
+                    // and pick the move up once our turn has started, as with the backchannel.
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        let search = match &self.background_search {
+                            Some(search) => search.clone(),
+                            None => {
+                                let mut evaluation_game = self.game.clone();
+                                evaluation_game.board = self.npc_board(npc_player);
+                                let search = BackgroundSearch::start(
+                                    evaluation_game,
+                                    self.npc.params,
+                                    npc_budget_ms,
+                                    search_threads(self.active_game.depot.ui_state.npc_threads),
+                                );
+                                self.background_search = Some(search.clone());
+                                self.active_game.depot.timing.npc_thinking_since =
+                                    Some(current_time);
+                                search
+                            }
+                        };
+                        self.active_game.depot.timing.npc_search = Some(search.report());
+
+                        if turn_starts_no_later_than <= current_time.as_secs() {
+                            if let Some(best) = search.take_result() {
+                                next_msg = Some((npc_player, best));
+                                self.background_search = None;
+                                self.finish_npc_search(current_time);
+                            }
+                        }
+                    }
 
+                    // The web always has a backchannel, so this only blocks if that ever changes
+                    #[cfg(target_arch = "wasm32")]
                     if turn_starts_no_later_than <= current_time.as_secs() {
-                        let best = client_best_move(&evaluation_game, &self.npc.params);
+                        let mut evaluation_game = self.game.clone();
+                        evaluation_game.board = self.npc_board(npc_player);
+                        let started = instant::Instant::now();
+                        let best = client_best_move(
+                            &evaluation_game,
+                            &self.npc.params,
+                            npc_budget_ms,
+                            1,
+                            None,
+                        );
+                        perf::record_npc_compute(started.elapsed().as_millis() as u64);
                         next_msg = Some((npc_player, best));
                     }
                 }
@@ -658,12 +1044,45 @@ impl SinglePlayerState {
                 player,
                 positions: [from, to],
             }),
+            Some((player, PlayerMessage::Exchange(tile))) => Some(Move::Exchange { player, tile }),
+            Some((player, PlayerMessage::Mulligan)) => Some(Move::Mulligan { player }),
             _ => None,
         };
 
         if let Some(next_move) = next_move {
+            // Forced wins are lost by any move that doesn't keep the win within the puzzle's turns
+            let lets_the_win_slip = match &self.forced_win {
+                Some(puzzle) if self.game.next_player == Some(human_player) => {
+                    let dict_lock = get_dict(self.language);
+                    !puzzle
+                        .winning_moves(&self.game, dict_lock.as_ref().unwrap())
+                        .contains(&next_move)
+                }
+                _ => false,
+            };
+
+            if self.move_sequence.is_empty() {
+                self.starting_game = Some(self.game.clone());
+            }
             if let Ok(battle_words) = self.handle_move(next_move.clone(), backchannel, true) {
                 self.move_sequence.push(next_move.clone());
+                let think_secs = self
+                    .game
+                    .recent_changes
+                    .iter()
+                    .find_map(|change| match change {
+                        truncate_core::reporting::Change::Think(think) => Some(think.secs as u32),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                self.think_secs.push(think_secs);
+
+                if lets_the_win_slip && self.winner.is_none() {
+                    self.game.resign_player(human_player);
+                    self.winner = Some(npc_player);
+                    self.active_game.depot.gameplay.error_msg =
+                        Some("That lets the defender off the hook".to_string());
+                }
 
                 if let Some(seed) = &self.active_game.depot.board_info.board_seed {
                     if seed.day.is_some() {
@@ -673,7 +1092,12 @@ impl SinglePlayerState {
                                 day: seed.day.unwrap(),
                                 human_player: human_player as u32,
                                 moves: self.move_sequence.clone(),
+                                think_secs: self.think_secs.clone(),
                                 won: self.winner == Some(human_player),
+                                category: self.category,
+                                language: self.game.rules.language,
+                                rules_generation: Some(self.rules_generation),
+                                board_seed: Some((seed.generation, seed.seed)),
                             });
 
                             // Ensure we never pull up an old splash screen without this move
@@ -3,7 +3,7 @@ use std::collections::{HashMap, HashSet};
 use eframe::egui::{self, Align, Align2, CursorIcon, Layout, NumExt, Order, Sense};
 use epaint::{vec2, Color32, Rect, TextureHandle, Vec2};
 use instant::Duration;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use truncate_core::{
     bag::TileBag,
     board::{Board, Coordinate},
@@ -79,9 +79,60 @@ enum ChangeStage {
     None,
 }
 
+/// How far through a tutorial the player got, so they can pick it back up later
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct TutorialCheckpoint {
+    /// Ties the checkpoint to one version of the tutorial, as stages may have changed since
+    effective_day: u32,
+    stage: usize,
+    step: usize,
+}
+
+impl TutorialCheckpoint {
+    fn storage_key(name: &str) -> String {
+        format!("truncate_tutorial_checkpoint_{name}")
+    }
+
+    #[allow(unused_variables)]
+    fn load(name: &str) -> Option<Self> {
+        let key = Self::storage_key(name);
+        #[allow(unused_mut)]
+        let mut checkpoint = None;
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            checkpoint = local_storage
+                .get_item(&key)
+                .unwrap()
+                .and_then(|c| serde_json::from_str(&c).ok());
+        }
+
+        checkpoint
+    }
+
+    #[allow(unused_variables)]
+    fn store(checkpoint: Option<Self>, name: &str) {
+        let key = Self::storage_key(name);
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            match checkpoint {
+                Some(checkpoint) => local_storage
+                    .set_item(&key, &serde_json::to_string(&checkpoint).unwrap())
+                    .unwrap(),
+                None => local_storage.remove_item(&key).unwrap(),
+            }
+        }
+    }
+}
+
 pub struct TutorialState {
     name: String,
     stage_index: usize,
+    /// The last checkpoint stored for this tutorial
+    checkpoint: Option<TutorialCheckpoint>,
     change_stage_next_frame: ChangeStage,
     stage: Option<TutorialStage>,
     stage_changed_at: Duration,
@@ -114,6 +165,13 @@ impl TutorialStage {
                     self.active_game.depot.interactions.highlight_squares =
                         Some(positions.to_vec());
                 }
+                Move::Exchange { tile, .. } => {
+                    self.active_game.depot.interactions.highlight_tiles = Some(vec![tile]);
+                }
+                Move::Mulligan { .. } => {
+                    self.active_game.depot.interactions.highlight_tiles = None;
+                    self.active_game.depot.interactions.highlight_squares = None;
+                }
             }
         } else {
             self.active_game.depot.interactions.highlight_tiles = None;
@@ -133,6 +191,28 @@ impl TutorialStage {
         self.step += 1;
     }
 
+    /// Plays through the scenario up to `step`, as if the player had followed along
+    fn fast_forward(&mut self, step: usize) {
+        while self.step < step {
+            let result = match self.get_step().cloned() {
+                Some(ScenarioStep::OwnMove { you, .. }) => {
+                    self.handle_move(action_to_move(0, &you))
+                }
+                Some(ScenarioStep::ComputerMove { computer, .. }) => {
+                    self.handle_move(action_to_move(1, &computer))
+                }
+                Some(ScenarioStep::Dialog { .. }) => {
+                    self.increment_step();
+                    Ok(())
+                }
+                Some(ScenarioStep::EndAction { .. }) | None => Err(()),
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+
     fn handle_move(&mut self, next_move: Move) -> Result<(), ()> {
         if let Some(next_tile) = match self.get_step() {
             Some(ScenarioStep::OwnMove { gets, .. }) => Some(gets),
@@ -159,6 +239,8 @@ impl TutorialStage {
                         }
                         truncate_core::reporting::Change::Battle(_) => true,
                         truncate_core::reporting::Change::Time(_) => true,
+                        truncate_core::reporting::Change::Bag(_) => true,
+                        truncate_core::reporting::Change::Think(_) => true,
                     })
                     .collect();
                 let room_code = self.active_game.depot.gameplay.room_code.clone();
@@ -176,9 +258,11 @@ impl TutorialStage {
                     board: self.game.board.clone(),
                     hand: self.game.players[0].hand.clone(),
                     changes,
+                    event_seq: 0,
                     game_ends_at: None,
                     paused: false,
                     remaining_turns: None,
+                    hand_swap_in: None,
                 };
                 self.active_game.apply_new_state(state_message);
                 self.active_game.depot.gameplay.winner = possible_winner;
@@ -210,6 +294,7 @@ impl TutorialState {
         Self {
             name,
             stage_index: 0,
+            checkpoint: None,
             change_stage_next_frame: ChangeStage::None,
             stage: stage_zero,
             stage_changed_at: Duration::from_secs(0),
@@ -218,6 +303,72 @@ impl TutorialState {
         }
     }
 
+    /// Starts the tutorial from wherever the player last left it on this device
+    pub fn resume(
+        name: String,
+        tutorial: Tutorial,
+        ctx: &egui::Context,
+        map_texture: TextureHandle,
+        theme: &Theme,
+        event_dispatcher: EventDispatcher,
+    ) -> Self {
+        let checkpoint = TutorialCheckpoint::load(&name)
+            .filter(|c| c.effective_day == tutorial.effective_day)
+            .filter(|c| TutorialState::get_nth_scenario(&tutorial, c.stage).is_some());
+        let mut state = TutorialState::new(
+            name,
+            tutorial,
+            ctx,
+            map_texture.clone(),
+            theme,
+            event_dispatcher,
+        );
+
+        if let Some(checkpoint) = checkpoint {
+            state.stage_index = checkpoint.stage;
+            state.stage = TutorialState::get_stage(
+                checkpoint.stage,
+                &state.tutorial,
+                ctx,
+                map_texture,
+                theme,
+            );
+            if let Some(stage) = &mut state.stage {
+                stage.fast_forward(checkpoint.step);
+            }
+            state.checkpoint = Some(checkpoint);
+            state.sub_event("resume".to_string());
+        }
+
+        state
+    }
+
+    /// Remembers where the player is up to, forgetting it once they reach the end
+    /// (or start again from the beginning)
+    fn save_checkpoint(&mut self) {
+        let checkpoint = match &self.stage {
+            Some(stage) if self.stage_index == 0 && stage.step == 0 => None,
+            Some(stage) if !matches!(stage.get_step(), Some(ScenarioStep::EndAction { .. })) => {
+                Some(TutorialCheckpoint {
+                    effective_day: self.tutorial.effective_day,
+                    stage: self.stage_index,
+                    step: stage.step,
+                })
+            }
+            _ => None,
+        };
+
+        if checkpoint != self.checkpoint {
+            TutorialCheckpoint::store(checkpoint, &self.name);
+            self.checkpoint = checkpoint;
+        }
+    }
+
+    /// The game in the current stage, if the stage has loaded
+    pub fn active_game_mut(&mut self) -> Option<&mut ActiveGame> {
+        self.stage.as_mut().map(|stage| &mut stage.active_game)
+    }
+
     pub fn load_definitions(&mut self, definitions: Vec<(String, Option<Vec<WordMeaning>>)>) {
         if let Some(stage) = &mut self.stage {
             if let Some(dict_ui) = &mut stage.active_game.dictionary_ui {
@@ -273,6 +424,10 @@ impl TutorialState {
                         paused_turn_delta: None,
                         swap_count: 0,
                         penalties_incurred: 0,
+                        appeals_used: 0,
+                        drawn_this_turn: 0,
+                        action_points_spent: 0,
+                        mulliganed: false,
                         color: GAME_COLOR_BLUE,
                         seen_tiles: HashSet::new(),
                     },
@@ -288,6 +443,10 @@ impl TutorialState {
                         paused_turn_delta: None,
                         swap_count: 0,
                         penalties_incurred: 0,
+                        appeals_used: 0,
+                        drawn_this_turn: 0,
+                        action_points_spent: 0,
+                        mulliganed: false,
                         color: GAME_COLOR_RED,
                         seen_tiles: HashSet::new(),
                     },
@@ -300,11 +459,17 @@ impl TutorialState {
                 turn_count: 0,
                 player_turn_count: vec![0, 0],
                 recent_changes: vec![],
+                think_times: vec![],
                 started_at: None,
                 game_ends_at: None,
                 next_player: Some(0),
                 paused: false,
                 winner: None,
+                adjudication: None,
+                tiebreaker: None,
+                position_history: HashMap::new(),
+                no_progress_turns: 0,
+                appealable: None,
             };
 
             let mut active_game = ActiveGame::new(
@@ -410,6 +575,8 @@ impl TutorialState {
             self.increment_stage(ui.ctx(), map_texture.clone(), theme);
         }
 
+        self.save_checkpoint();
+
         let can_decrement_stage = self.can_decrement_stage();
         let can_increment_stage = self.can_increment_stage();
 
@@ -0,0 +1,223 @@
+use eframe::egui;
+use epaint::{emath::Align, Color32, TextureHandle};
+use truncate_core::{
+    ladder::{LadderStats, WordLadder},
+    messages::{PlayerMessage, TruncateToken},
+};
+
+use crate::utils::{game_evals::get_main_dict, text::TextHelper, urls::back_to_menu, Theme};
+
+pub struct WordLadderState {
+    day: u32,
+    ladder: Option<WordLadder>,
+    words: Vec<String>,
+    current_word: String,
+    error: Option<String>,
+    player_token: Option<TruncateToken>,
+    pub stats: Option<LadderStats>,
+}
+
+impl WordLadderState {
+    pub fn new(day: u32, player_token: Option<TruncateToken>) -> Self {
+        let dict_lock = get_main_dict();
+        let dict = dict_lock.as_ref().unwrap();
+        let ladder = WordLadder::daily(day, dict);
+
+        Self {
+            day,
+            words: ladder.iter().map(|l| l.start.clone()).collect(),
+            ladder,
+            current_word: String::new(),
+            error: None,
+            player_token,
+            stats: None,
+        }
+    }
+
+    fn solved(&self) -> bool {
+        self.ladder
+            .as_ref()
+            .is_some_and(|l| self.words.last() == Some(&l.target))
+    }
+
+    fn submit_word(&mut self) -> Option<PlayerMessage> {
+        let ladder = self.ladder.as_ref()?;
+        let word = self.current_word.trim().to_ascii_lowercase();
+        let previous = self.words.last()?;
+
+        let dict_lock = get_main_dict();
+        let dict = dict_lock.as_ref().unwrap();
+
+        if let Err(e) = WordLadder::valid_step(previous, &word, dict) {
+            self.error = Some(e.to_string());
+            return None;
+        }
+
+        self.error = None;
+        self.current_word.clear();
+        self.words.push(word);
+
+        if self.words.last() != Some(&ladder.target) {
+            return None;
+        }
+
+        // Track the solve locally so the streak updates even when offline
+        let steps = (self.words.len() - 1) as u32;
+        self.stats
+            .get_or_insert_with(LadderStats::default)
+            .solved
+            .insert(self.day, steps);
+
+        self.player_token
+            .as_ref()
+            .map(|token| PlayerMessage::PersistWordLadder {
+                player_token: token.clone(),
+                day: self.day,
+                words: self.words.clone(),
+            })
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+    ) -> Option<PlayerMessage> {
+        let mut msg = None;
+
+        let Some(ladder) = &self.ladder else {
+            TextHelper::heavy("NO WORD LADDER TODAY", 14.0, None, ui).paint(
+                Color32::WHITE,
+                ui,
+                true,
+            );
+            return None;
+        };
+        let solved = self.solved();
+        let steps = self.words.len() - 1;
+        let par = ladder.par;
+        let word_length = ladder.start.len();
+        let title = format!(
+            "Turn {} into {}",
+            ladder.start.to_uppercase(),
+            ladder.target.to_uppercase()
+        );
+
+        ui.add_space(40.0);
+        TextHelper::heavy("WORD LADDER", 14.0, None, ui).paint(Color32::WHITE, ui, true);
+        ui.add_space(8.0);
+        TextHelper::light(&title, 20.0, None, ui).paint(Color32::WHITE, ui, true);
+        TextHelper::light(
+            "Change one letter at a time, making a word each step",
+            14.0,
+            Some(ui.available_width() - 20.0),
+            ui,
+        )
+        .paint(Color32::WHITE, ui, true);
+        ui.add_space(20.0);
+
+        for word in &self.words {
+            TextHelper::heavy(&word.to_uppercase(), 18.0, None, ui).paint(
+                if solved {
+                    theme.word_valid
+                } else {
+                    Color32::WHITE
+                },
+                ui,
+                true,
+            );
+            ui.add_space(4.0);
+        }
+
+        ui.add_space(10.0);
+
+        if solved {
+            let verdict = match steps.cmp(&par) {
+                std::cmp::Ordering::Less => "under par!",
+                std::cmp::Ordering::Equal => "right on par",
+                std::cmp::Ordering::Greater => "over par",
+            };
+            TextHelper::light(
+                &format!("Solved in {steps} steps, {verdict}"),
+                20.0,
+                None,
+                ui,
+            )
+            .paint(Color32::WHITE, ui, true);
+        } else {
+            ui.vertical_centered(|ui| {
+                let input = egui::TextEdit::singleline(&mut self.current_word)
+                    .desired_width(ui.available_width().min(200.0))
+                    .char_limit(word_length)
+                    .horizontal_align(Align::Center)
+                    .font(egui::FontId::new(
+                        20.0,
+                        egui::FontFamily::Name("Truncate-Heavy".into()),
+                    ))
+                    .show(ui);
+
+                if input.response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    msg = self.submit_word();
+                    input.response.request_focus();
+                }
+            });
+            ui.add_space(10.0);
+
+            if let Some(error) = &self.error {
+                TextHelper::light(error, 14.0, Some(ui.available_width() - 20.0), ui).paint(
+                    theme.word_invalid,
+                    ui,
+                    true,
+                );
+                ui.add_space(10.0);
+            }
+
+            TextHelper::light(&format!("Par {par}, {steps} steps so far"), 14.0, None, ui).paint(
+                Color32::WHITE,
+                ui,
+                true,
+            );
+            ui.add_space(10.0);
+
+            if self.words.len() > 1
+                && TextHelper::heavy("UNDO", 12.0, None, ui)
+                    .centered_button(theme.button_primary, theme.text, map_texture, ui)
+                    .clicked()
+            {
+                self.words.pop();
+                self.error = None;
+            }
+        }
+
+        if let Some(stats) = &self.stats {
+            let streak = stats.streak(self.day);
+            ui.add_space(10.0);
+            TextHelper::light(
+                &format!("Streak: {streak} day{}", if streak == 1 { "" } else { "s" }),
+                14.0,
+                None,
+                ui,
+            )
+            .paint(Color32::WHITE, ui, true);
+        }
+
+        if self.player_token.is_none() {
+            ui.add_space(10.0);
+            TextHelper::light("Offline, so this ladder won't be saved", 14.0, None, ui).paint(
+                Color32::WHITE,
+                ui,
+                true,
+            );
+        }
+
+        ui.add_space(20.0);
+        if TextHelper::heavy("BACK TO MENU", 12.0, None, ui)
+            .centered_button(theme.button_primary, theme.text, map_texture, ui)
+            .clicked()
+        {
+            back_to_menu();
+        }
+
+        msg
+    }
+}
@@ -0,0 +1,213 @@
+use eframe::egui;
+use epaint::{Color32, TextureHandle};
+use instant::Duration;
+use truncate_core::{
+    messages::{GamePlayerMessage, PlayerMessage},
+    moves::Move,
+    rush::Drill,
+    word_of_day::WordOfTheDay,
+};
+
+use crate::{
+    app_outer::EventDispatcher,
+    utils::{game_evals::get_main_dict, text::TextHelper, urls::back_to_menu, Theme},
+};
+
+use super::active_game::{ActiveGame, GameLocation, HeaderType};
+
+/// Definitions beyond this many per part of speech are left off the card
+const MAX_DEFS_PER_POS: usize = 2;
+
+pub struct WordOfTheDayState {
+    event_dispatcher: EventDispatcher,
+    pub daily: Option<WordOfTheDay>,
+    drill: Option<(Drill, ActiveGame)>,
+    solved: bool,
+}
+
+impl WordOfTheDayState {
+    pub fn new(event_dispatcher: EventDispatcher) -> Self {
+        Self {
+            event_dispatcher,
+            daily: None,
+            drill: None,
+            solved: false,
+        }
+    }
+
+    /// Sets up the board for the word's drill, also used to reset it after a mistake
+    fn load_drill(&mut self, ctx: &egui::Context, theme: &Theme, map_texture: &TextureHandle) {
+        let Some(daily) = &self.daily else {
+            return;
+        };
+        let dict_lock = get_main_dict();
+        let dict = dict_lock.as_ref().unwrap();
+
+        let Some(drill) = daily.drill(dict) else {
+            self.drill = None;
+            return;
+        };
+        let game = drill.game();
+
+        let mut active_game = ActiveGame::new(
+            ctx,
+            "WORD_OF_THE_DAY".into(),
+            None,
+            None,
+            game.players
+                .iter()
+                .map(|p| GamePlayerMessage::new(p, &game))
+                .collect(),
+            0,
+            Some(0),
+            game.board.clone(),
+            game.players[0].hand.clone(),
+            map_texture.clone(),
+            theme.clone(),
+            GameLocation::Local,
+            None,
+            None,
+        );
+        active_game.depot.ui_state.game_header = HeaderType::Summary {
+            title: format!("Finish {} to take the town", daily.word.to_uppercase()),
+            attempt: None,
+        };
+
+        self.drill = Some((drill, active_game));
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        current_time: Duration,
+    ) {
+        if self.drill.is_some() {
+            self.render_drill(ui, theme, map_texture, current_time);
+            return;
+        }
+
+        let mut practice = false;
+
+        ui.add_space(40.0);
+        TextHelper::heavy("WORD OF THE DAY", 14.0, None, ui).paint(Color32::WHITE, ui, true);
+        ui.add_space(12.0);
+
+        let Some(daily) = &self.daily else {
+            TextHelper::light("Loading...", 14.0, None, ui).paint(Color32::WHITE, ui, true);
+            return;
+        };
+
+        TextHelper::heavy(&daily.word.to_uppercase(), 24.0, None, ui).paint(
+            theme.word_valid,
+            ui,
+            true,
+        );
+        ui.add_space(12.0);
+
+        if daily.definitions.is_empty() {
+            TextHelper::light("No definition available", 14.0, None, ui).paint(
+                Color32::WHITE,
+                ui,
+                true,
+            );
+        }
+        for meaning in &daily.definitions {
+            for def in meaning.defs.iter().take(MAX_DEFS_PER_POS) {
+                TextHelper::light(
+                    &format!("{}: {def}", meaning.pos),
+                    14.0,
+                    Some(ui.available_width() - 20.0),
+                    ui,
+                )
+                .paint(Color32::WHITE, ui, true);
+                ui.add_space(4.0);
+            }
+        }
+        ui.add_space(20.0);
+
+        if self.solved {
+            TextHelper::light("Town taken, nicely placed!", 14.0, None, ui).paint(
+                theme.word_valid,
+                ui,
+                true,
+            );
+            ui.add_space(10.0);
+        }
+
+        if TextHelper::heavy(
+            if self.solved {
+                "PRACTICE AGAIN"
+            } else {
+                "PRACTICE"
+            },
+            12.0,
+            None,
+            ui,
+        )
+        .centered_button(theme.button_primary, theme.text, map_texture, ui)
+        .clicked()
+        {
+            practice = true;
+        }
+
+        ui.add_space(20.0);
+        if TextHelper::heavy("BACK TO MENU", 12.0, None, ui)
+            .centered_button(theme.button_primary, theme.text, map_texture, ui)
+            .clicked()
+        {
+            back_to_menu();
+        }
+
+        if practice {
+            self.event_dispatcher.event("word_of_the_day_practice");
+            self.solved = false;
+            self.load_drill(ui.ctx(), theme, map_texture);
+        }
+    }
+
+    fn render_drill(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        current_time: Duration,
+    ) {
+        let Some((drill, active_game)) = &mut self.drill else {
+            return;
+        };
+
+        let Some(PlayerMessage::Place(position, tile)) = active_game.render(ui, current_time, None)
+        else {
+            return;
+        };
+
+        let solved = {
+            let dict_lock = get_main_dict();
+            let dict = dict_lock.as_ref().unwrap();
+            let next_move = Move::Place {
+                player: 0,
+                tile,
+                position,
+            };
+            drill.is_solved_by(next_move, dict)
+        };
+
+        if solved {
+            self.event_dispatcher.event("word_of_the_day_solved");
+            self.solved = true;
+            self.drill = None;
+            return;
+        }
+
+        // Put the drill back how it was for another go
+        self.load_drill(ui.ctx(), theme, map_texture);
+        if let (Some((_, active_game)), Some(daily)) = (&mut self.drill, &self.daily) {
+            active_game.depot.gameplay.error_msg = Some(format!(
+                "That doesn't finish {}, try again",
+                daily.word.to_uppercase()
+            ));
+        }
+    }
+}
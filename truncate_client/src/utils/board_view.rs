@@ -0,0 +1,98 @@
+use truncate_core::board::{Board, Coordinate, Direction, SignedCoordinate};
+
+/// How the letters on the board face the player
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub enum ViewRotation {
+    /// Opponent's letters face the opponent, as if sitting across a table
+    #[default]
+    Facing,
+    /// Every letter is upright for the player, whoever played it
+    Upright,
+}
+
+impl ViewRotation {
+    pub fn next(self) -> Self {
+        match self {
+            ViewRotation::Facing => ViewRotation::Upright,
+            ViewRotation::Upright => ViewRotation::Facing,
+        }
+    }
+
+    pub fn as_setting(self) -> &'static str {
+        match self {
+            ViewRotation::Facing => "facing",
+            ViewRotation::Upright => "upright",
+        }
+    }
+
+    pub fn from_setting(rotation: &str) -> Option<Self> {
+        match rotation {
+            "facing" => Some(ViewRotation::Facing),
+            "upright" => Some(ViewRotation::Upright),
+            _ => None,
+        }
+    }
+}
+
+/// Turns the board so that a player's root sits at the bottom of their screen,
+/// translating between the core's coordinates and where squares are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardView {
+    player: usize,
+    rotated: bool,
+    width: usize,
+    height: usize,
+    rotation: ViewRotation,
+}
+
+impl BoardView {
+    pub fn for_player(board: &Board, player: usize, rotation: ViewRotation) -> Self {
+        Self {
+            player,
+            // Players sitting at the north of the board would otherwise see their root at the top
+            rotated: board.orientations.get(player) == Some(&Direction::North),
+            width: board.width(),
+            height: board.height(),
+            rotation,
+        }
+    }
+
+    /// Whether the board is drawn upside down from how the core stores it
+    pub fn is_rotated(&self) -> bool {
+        self.rotated
+    }
+
+    /// Where a square on the board is drawn, counting from the top left of the screen
+    pub fn to_screen(&self, coord: Coordinate) -> Coordinate {
+        if self.rotated {
+            Coordinate::new(
+                self.width.saturating_sub(coord.x + 1),
+                self.height.saturating_sub(coord.y + 1),
+            )
+        } else {
+            coord
+        }
+    }
+
+    /// Which square on the board is drawn at a place on the screen,
+    /// which may be in the water around the board
+    pub fn to_board(&self, screen: SignedCoordinate) -> SignedCoordinate {
+        if self.rotated {
+            SignedCoordinate::new(
+                self.width as isize - screen.x - 1,
+                self.height as isize - screen.y - 1,
+            )
+        } else {
+            screen
+        }
+    }
+
+    /// Which way up a player's tiles are drawn, where `North` is upright on the screen
+    pub fn facing(&self, player: usize) -> Direction {
+        if player == self.player || self.rotation == ViewRotation::Upright {
+            Direction::North
+        } else {
+            Direction::South
+        }
+    }
+}
@@ -0,0 +1,51 @@
+/// Round trips slower than this are always worth logging
+const SPIKE_FLOOR_MS: u64 = 300;
+/// Round trips this many times slower than usual are logged as spikes
+const SPIKE_FACTOR: f64 = 2.5;
+
+/// Tracks how responsive our connection to the server is, from the latency pings
+/// sent alongside the server's keepalive pings
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionQuality {
+    latest_ms: Option<u64>,
+    /// Smoothed round trip time, which spikes are measured against
+    typical_ms: Option<f64>,
+    pub spikes: usize,
+}
+
+impl ConnectionQuality {
+    /// Records a round trip, logging it if it was unusually slow
+    pub fn record(&mut self, round_trip_ms: u64) {
+        if let Some(typical) = self.typical_ms {
+            if round_trip_ms > SPIKE_FLOOR_MS && round_trip_ms as f64 > typical * SPIKE_FACTOR {
+                self.spikes += 1;
+                tracing::warn!(
+                    round_trip_ms,
+                    typical_ms = typical as u64,
+                    spikes = self.spikes,
+                    "Latency spike"
+                );
+            }
+        }
+
+        self.latest_ms = Some(round_trip_ms);
+        self.typical_ms = Some(match self.typical_ms {
+            Some(typical) => typical * 0.8 + round_trip_ms as f64 * 0.2,
+            None => round_trip_ms as f64,
+        });
+    }
+
+    pub fn latency_ms(&self) -> Option<u64> {
+        self.latest_ms
+    }
+}
+
+/// A short description of a round trip time for showing to the player
+pub fn describe_latency(latency_ms: u64) -> String {
+    let quality = match latency_ms {
+        0..=150 => "good",
+        151..=400 => "fair",
+        _ => "poor",
+    };
+    format!("Connection {quality}, {latency_ms}ms")
+}
@@ -0,0 +1,190 @@
+use instant::Duration;
+
+use super::Intent;
+
+/// How far a stick has to be pushed before it moves the cursor
+const STICK_DEADZONE: f32 = 0.5;
+/// How long a direction is held before the cursor starts repeating
+const REPEAT_DELAY: Duration = Duration::from_millis(350);
+/// How often the cursor moves while a direction stays held
+const REPEAT_INTERVAL: Duration = Duration::from_millis(120);
+
+/// The state of the first connected gamepad, in the layout of a standard controller
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct GamepadSnapshot {
+    /// A on Xbox layouts, cross on PlayStation layouts
+    pub south: bool,
+    pub east: bool,
+    pub west: bool,
+    pub north: bool,
+    pub left_shoulder: bool,
+    pub right_shoulder: bool,
+    /// Left and right, then up and down, with right and down positive
+    pub dpad: [isize; 2],
+    /// The left stick, with right and down positive
+    pub stick: [f32; 2],
+}
+
+impl GamepadSnapshot {
+    /// The single square the player is pointing the cursor towards, if any
+    fn direction(&self) -> Option<[isize; 2]> {
+        if self.dpad != [0, 0] {
+            return Some(self.dpad);
+        }
+
+        let [x, y] = self.stick;
+        if x.abs().max(y.abs()) < STICK_DEADZONE {
+            return None;
+        }
+        // Diagonals on a stick are hard to hold, so only move along the axis pushed furthest
+        if x.abs() >= y.abs() {
+            Some([x.signum() as isize, 0])
+        } else {
+            Some([0, y.signum() as isize])
+        }
+    }
+}
+
+/// Reads the gamepad each frame, turning presses and held directions into intents
+#[derive(Clone, Default)]
+pub struct GamepadInput {
+    previous: GamepadSnapshot,
+    /// The direction being held, and when the cursor should next move along it
+    held: Option<([isize; 2], Duration)>,
+    pub connected: bool,
+}
+
+impl GamepadInput {
+    pub fn read_intents(&mut self, current_time: Duration) -> Vec<Intent> {
+        let Some(snapshot) = poll() else {
+            self.connected = false;
+            self.previous = GamepadSnapshot::default();
+            self.held = None;
+            return vec![];
+        };
+        self.connected = true;
+
+        let mut intents = vec![];
+        let pressed = |now: bool, before: bool| now && !before;
+        let previous = self.previous;
+
+        match (snapshot.direction(), self.held) {
+            (None, _) => self.held = None,
+            (Some(direction), Some((held, next_move))) if held == direction => {
+                if current_time >= next_move {
+                    intents.push(Intent::MoveCursor(direction));
+                    self.held = Some((direction, current_time + REPEAT_INTERVAL));
+                }
+            }
+            (Some(direction), _) => {
+                intents.push(Intent::MoveCursor(direction));
+                self.held = Some((direction, current_time + REPEAT_DELAY));
+            }
+        }
+
+        if pressed(snapshot.left_shoulder, previous.left_shoulder) {
+            intents.push(Intent::CycleHandSlot(-1));
+        }
+        if pressed(snapshot.right_shoulder, previous.right_shoulder) {
+            intents.push(Intent::CycleHandSlot(1));
+        }
+        if pressed(snapshot.south, previous.south) {
+            intents.push(Intent::PlaySelectedTile);
+        }
+        if pressed(snapshot.west, previous.west) {
+            intents.push(Intent::SelectForSwap);
+        }
+        if pressed(snapshot.north, previous.north) {
+            intents.push(Intent::ToggleDictionary);
+        }
+        if pressed(snapshot.east, previous.east) {
+            intents.push(Intent::Cancel);
+        }
+
+        self.previous = snapshot;
+        intents
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+fn poll() -> Option<GamepadSnapshot> {
+    use gilrs::{Axis, Button, Gilrs};
+    use std::cell::RefCell;
+
+    thread_local! {
+        static GILRS: RefCell<Option<Gilrs>> = RefCell::new(Gilrs::new().ok());
+    }
+
+    GILRS.with(|gilrs| {
+        let mut gilrs = gilrs.borrow_mut();
+        let gilrs = gilrs.as_mut()?;
+        // Events have to be drained for gilrs to update the state of each gamepad
+        while gilrs.next_event().is_some() {}
+
+        let (_, gamepad) = gilrs
+            .gamepads()
+            .find(|(_, gamepad)| gamepad.is_connected())?;
+        let axis = |neg: Button, pos: Button| {
+            gamepad.is_pressed(pos) as isize - gamepad.is_pressed(neg) as isize
+        };
+
+        Some(GamepadSnapshot {
+            south: gamepad.is_pressed(Button::South),
+            east: gamepad.is_pressed(Button::East),
+            west: gamepad.is_pressed(Button::West),
+            north: gamepad.is_pressed(Button::North),
+            left_shoulder: gamepad.is_pressed(Button::LeftTrigger),
+            right_shoulder: gamepad.is_pressed(Button::RightTrigger),
+            dpad: [
+                axis(Button::DPadLeft, Button::DPadRight),
+                axis(Button::DPadUp, Button::DPadDown),
+            ],
+            // gilrs has up as positive on the stick
+            stick: [
+                gamepad.value(Axis::LeftStickX),
+                -gamepad.value(Axis::LeftStickY),
+            ],
+        })
+    })
+}
+
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "gamepad")))]
+fn poll() -> Option<GamepadSnapshot> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn poll() -> Option<GamepadSnapshot> {
+    use eframe::wasm_bindgen::JsCast;
+
+    let gamepads = web_sys::window()?.navigator().get_gamepads().ok()?;
+    let gamepad = gamepads
+        .iter()
+        .filter_map(|gamepad| gamepad.dyn_into::<web_sys::Gamepad>().ok())
+        .find(|gamepad| gamepad.connected())?;
+
+    // Indices from the browser's standard gamepad mapping
+    let buttons = gamepad.buttons();
+    let button = |index: u32| {
+        buttons
+            .get(index)
+            .dyn_into::<web_sys::GamepadButton>()
+            .is_ok_and(|button| button.pressed())
+    };
+    let axes = gamepad.axes();
+    let axis = |index: u32| axes.get(index).as_f64().unwrap_or_default() as f32;
+
+    Some(GamepadSnapshot {
+        south: button(0),
+        east: button(1),
+        west: button(2),
+        north: button(3),
+        left_shoulder: button(4),
+        right_shoulder: button(5),
+        dpad: [
+            button(15) as isize - button(14) as isize,
+            button(13) as isize - button(12) as isize,
+        ],
+        stick: [axis(0), axis(1)],
+    })
+}
@@ -1 +1,290 @@
+use eframe::egui;
+use truncate_core::{
+    bag::{blank_as, BLANK},
+    board::{Board, Coordinate, Square},
+    messages::PlayerMessage,
+    player::Hand,
+};
+
+use crate::utils::{
+    board_view::BoardView,
+    depot::{InteractionDepot, TruncateDepot},
+};
+
+pub mod gamepad;
 pub mod keyboard;
+
+/// The kinds of device a player can play with, which change how the game is presented to them
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub enum InputProfile {
+    #[default]
+    Mouse,
+    Touch,
+    Gamepad,
+}
+
+impl InputProfile {
+    /// Cycles through picking a device, then back to detecting it
+    pub fn next_preference(preference: Option<Self>) -> Option<Self> {
+        match preference {
+            None => Some(InputProfile::Mouse),
+            Some(InputProfile::Mouse) => Some(InputProfile::Touch),
+            Some(InputProfile::Touch) => Some(InputProfile::Gamepad),
+            Some(InputProfile::Gamepad) => None,
+        }
+    }
+
+    pub fn as_setting(self) -> &'static str {
+        match self {
+            InputProfile::Mouse => "mouse",
+            InputProfile::Touch => "touch",
+            InputProfile::Gamepad => "gamepad",
+        }
+    }
+
+    pub fn from_setting(profile: &str) -> Option<Self> {
+        match profile {
+            "mouse" => Some(InputProfile::Mouse),
+            "touch" => Some(InputProfile::Touch),
+            "gamepad" => Some(InputProfile::Gamepad),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputProfile::Mouse => "MOUSE",
+            InputProfile::Touch => "TOUCH",
+            InputProfile::Gamepad => "GAMEPAD",
+        }
+    }
+}
+
+/// Something the player asked the game to do, whichever device they asked with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Intent {
+    /// Moves the board cursor by a number of squares, as seen from the player's side of the board
+    MoveCursor([isize; 2]),
+    /// Plays the tile in this slot of the hand to the board cursor
+    PlayHandSlot(usize),
+    /// Plays this letter from the hand to the board cursor
+    PlayLetter(char),
+    /// Moves the hand selection along by a number of slots
+    CycleHandSlot(isize),
+    /// Plays the selected tile in the hand to the board cursor
+    PlaySelectedTile,
+    /// Picks up the tile under the board cursor, or swaps it with the one already picked up
+    SelectForSwap,
+    ToggleDictionary,
+    /// Backs out of whatever is open or selected
+    Cancel,
+}
+
+/// Works out which device the player is using, from what they touched this frame
+pub fn detect_profile(ctx: &egui::Context, used_gamepad: bool, depot: &mut TruncateDepot) {
+    let (touched, pointed) = ctx.input(|i| {
+        (
+            i.events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Touch { .. })),
+            i.pointer.is_moving() || i.pointer.any_pressed(),
+        )
+    });
+
+    let ui_state = &mut depot.ui_state;
+    if touched {
+        ui_state.input_profile = InputProfile::Touch;
+    } else if used_gamepad {
+        ui_state.input_profile = InputProfile::Gamepad;
+    } else if pointed && ui_state.input_profile == InputProfile::Gamepad {
+        ui_state.input_profile = InputProfile::Mouse;
+    }
+
+    ui_state.is_touch = match ui_state.input_preference {
+        Some(preference) => preference == InputProfile::Touch,
+        // If we ever receive any touch event,
+        // irrevocably put Truncate into touch mode.
+        None => ui_state.is_touch || touched,
+    };
+}
+
+/// Places a tile from the hand, or for a blank, asks which letter to play it as first
+pub fn place_from_hand(
+    interactions: &mut InteractionDepot,
+    coord: Coordinate,
+    tile: char,
+) -> Option<PlayerMessage> {
+    if tile == BLANK {
+        interactions.picking_blank = Some(coord);
+        None
+    } else {
+        Some(PlayerMessage::Place(coord, tile))
+    }
+}
+
+/// Carries out the player's intents against the board and hand, returning the move they make, if any
+pub fn apply_intents(
+    intents: &[Intent],
+    board: &Board,
+    hand: &Hand,
+    depot: &mut TruncateDepot,
+) -> Option<PlayerMessage> {
+    let mut msg = None;
+
+    let ensure_board_selection = |depot: &mut TruncateDepot| {
+        if let Some((coord, _)) = depot.interactions.selected_square_on_board {
+            return coord;
+        }
+        if let Some((coord, sq)) = depot.interactions.previous_selected_square_on_board {
+            depot.interactions.selected_square_on_board = Some((coord.clone(), sq));
+            return coord;
+        }
+        let artifact = board.artifacts.iter().find(|d| {
+            board.get(**d).is_ok_and(
+                |s| matches!(s, Square::Artifact{player: p, ..} if p == depot.gameplay.player_number as usize),
+            )
+        });
+        let coord = artifact.cloned().unwrap_or_else(|| Coordinate::new(0, 0));
+        depot.interactions.selected_square_on_board =
+            Some((coord.clone(), board.get(coord).unwrap()));
+        coord
+    };
+
+    let move_selection = |depot: &mut TruncateDepot, mut movement: [isize; 2]| {
+        // If nothing is selected, the first interaction shouldn't move the cursor.
+        // At the start of the game, it should select the artifact,
+        // and otherwise it should select the previously selected square.
+        if depot.interactions.selected_square_on_board.is_none() {
+            ensure_board_selection(depot);
+            return;
+        }
+
+        let current_selection = ensure_board_selection(depot);
+
+        let view = BoardView::for_player(
+            board,
+            depot.gameplay.player_number as usize,
+            depot.ui_state.view_rotation,
+        );
+        if view.is_rotated() {
+            movement[0] *= -1;
+            movement[1] *= -1;
+        }
+
+        let mut new_x = (current_selection.x as isize) + movement[0];
+        let mut new_y = (current_selection.y as isize) + movement[1];
+
+        new_x = new_x.min(board.width() as isize - 1);
+        new_y = new_y.min(board.height() as isize - 1);
+
+        new_x = new_x.max(0);
+        new_y = new_y.max(0);
+
+        let new_coord = Coordinate {
+            x: new_x as usize,
+            y: new_y as usize,
+        };
+
+        if let Ok(sq) = board.get(new_coord) {
+            depot.interactions.selected_square_on_board = Some((new_coord, sq));
+            depot.interactions.previous_selected_square_on_board = Some((new_coord, sq));
+        }
+    };
+
+    for intent in intents {
+        match *intent {
+            Intent::ToggleDictionary => {
+                let ui_state = &mut depot.ui_state;
+                if !ui_state.dictionary_open {
+                    ui_state.dictionary_open = true;
+                    ui_state.dictionary_opened_by_keyboard = true;
+                } else if ui_state.dictionary_opened_by_keyboard {
+                    ui_state.dictionary_open = false;
+                    ui_state.dictionary_opened_by_keyboard = false;
+                }
+            }
+            Intent::Cancel => {
+                if depot.ui_state.dictionary_open {
+                    depot.ui_state.dictionary_open = false;
+                    depot.ui_state.dictionary_focused = false;
+                } else {
+                    depot.interactions.selected_tile_on_board = None;
+                    depot.interactions.selected_tile_in_hand = None;
+                    depot.interactions.picking_blank = None;
+                }
+            }
+            // Everything else plays on the board, which is covered while the dictionary is open
+            _ if depot.ui_state.dictionary_open => {}
+            Intent::MoveCursor(movement) => move_selection(depot, movement),
+            Intent::PlayHandSlot(slot) => {
+                let current_selection = ensure_board_selection(depot);
+
+                if let Some(char) = hand.get(slot) {
+                    msg = place_from_hand(&mut depot.interactions, current_selection, *char)
+                }
+            }
+            Intent::PlayLetter(letter) => {
+                // Typing while picking a blank's letter plays the blank as that letter
+                if let Some(coord) = depot.interactions.picking_blank.take() {
+                    msg = blank_as(letter).map(|blank| PlayerMessage::Place(coord, blank));
+                    continue;
+                }
+
+                let current_selection = ensure_board_selection(depot);
+
+                // Fall back to a blank when the letter isn't in the hand
+                let tile = match blank_as(letter) {
+                    Some(blank) if hand.find(letter).is_none() && hand.find(BLANK).is_some() => {
+                        blank
+                    }
+                    _ => letter,
+                };
+                msg = Some(PlayerMessage::Place(current_selection, tile))
+            }
+            Intent::CycleHandSlot(step) => {
+                if hand.is_empty() {
+                    continue;
+                }
+                let slot = match depot.interactions.selected_tile_in_hand {
+                    Some((slot, _)) => {
+                        (slot as isize + step).rem_euclid(hand.len() as isize) as usize
+                    }
+                    None => 0,
+                };
+                depot.interactions.selected_tile_in_hand = hand.get(slot).map(|c| (slot, *c));
+            }
+            Intent::PlaySelectedTile => {
+                let current_selection = ensure_board_selection(depot);
+
+                if let Some((_, char)) = depot.interactions.selected_tile_in_hand.take() {
+                    msg = place_from_hand(&mut depot.interactions, current_selection, char)
+                }
+            }
+            Intent::SelectForSwap => {
+                let current_selection = ensure_board_selection(depot);
+                if matches!(board.get(current_selection), Ok(Square::Occupied { .. })) {
+                    if let Some((already_selected_tile, _)) =
+                        depot.interactions.selected_tile_on_board
+                    {
+                        if already_selected_tile == current_selection {
+                            depot.interactions.selected_tile_on_board = None;
+                        } else {
+                            msg = Some(PlayerMessage::Swap(
+                                already_selected_tile,
+                                current_selection,
+                            ));
+                            depot.interactions.selected_tile_on_board = None;
+                        }
+                    } else {
+                        depot.interactions.selected_tile_on_board =
+                            Some((current_selection, board.get(current_selection).unwrap()));
+                    }
+                } else {
+                    depot.interactions.selected_tile_on_board = None;
+                }
+            }
+        }
+    }
+
+    msg
+}
@@ -0,0 +1,155 @@
+use std::{
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+
+use serde::Serialize;
+use truncate_core::{
+    board::Board,
+    messages::{CrashReport, GameMessage, PlayerMessage},
+};
+
+/// How many of the latest protocol messages are kept for a crash report
+const RECENT_MESSAGE_LIMIT: usize = 30;
+#[cfg(target_arch = "wasm32")]
+const PENDING_REPORT_KEY: &str = "truncate_crash_report";
+
+/// What we know about the client at any moment, in case it panics
+struct CrashContext {
+    region: &'static str,
+    recent_messages: VecDeque<String>,
+    board_hash: Option<u64>,
+}
+
+static CRASH_CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    region: "startup",
+    recent_messages: VecDeque::new(),
+    board_hash: None,
+});
+
+pub fn set_region(region: &'static str) {
+    if let Ok(mut context) = CRASH_CONTEXT.lock() {
+        context.region = region;
+    }
+}
+
+pub fn record_sent(msg: &PlayerMessage) {
+    if matches!(msg, PlayerMessage::Ping | PlayerMessage::LatencyPing(_)) {
+        return;
+    }
+    record(format!("sent {}", message_kind(msg)), None);
+}
+
+pub fn record_received(msg: &GameMessage) {
+    use GameMessage::*;
+
+    let (summary, board) = match msg {
+        Ping | LatencyPong(_) | Ack(_) => return,
+        JoinedLobby(_, _, _, board, _) | LobbyUpdate(_, _, _, board) => {
+            (message_kind(msg), Some(board))
+        }
+        StartedGame(state)
+        | GameTimingUpdate(state)
+        | GameUpdate(state)
+        | GameEnd(state, _, _)
+        | GameDrawn(state, _, _) => (
+            format!(
+                "{} #{} with {} change(s)",
+                message_kind(msg),
+                state.event_seq,
+                state.changes.len()
+            ),
+            Some(&state.board),
+        ),
+        _ => (message_kind(msg), None),
+    };
+    record(format!("received {summary}"), board);
+}
+
+fn record(summary: String, board: Option<&Board>) {
+    let Ok(mut context) = CRASH_CONTEXT.lock() else {
+        return;
+    };
+    if context.recent_messages.len() >= RECENT_MESSAGE_LIMIT {
+        context.recent_messages.pop_front();
+    }
+    context.recent_messages.push_back(summary);
+    if let Some(board) = board {
+        let mut hasher = DefaultHasher::new();
+        board.squares.hash(&mut hasher);
+        context.board_hash = Some(hasher.finish());
+    }
+}
+
+/// Names the kind of message without any of its contents,
+/// which can hold hands, player names, and tokens
+fn message_kind<T: Serialize>(msg: &T) -> String {
+    match serde_json::to_value(msg) {
+        Ok(serde_json::Value::String(kind)) => kind,
+        Ok(serde_json::Value::Object(fields)) => fields.keys().next().cloned().unwrap_or_default(),
+        _ => "Unknown".into(),
+    }
+}
+
+/// Captures a report whenever the client panics, which is offered to the player next launch
+pub fn install_panic_hook() {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        store_pending_report(capture(info.to_string()));
+        previous_hook(info);
+    }));
+}
+
+fn capture(panic_message: String) -> CrashReport {
+    // We could have panicked while holding the lock, so don't wait on it
+    let (region, recent_messages, board_hash) = match CRASH_CONTEXT.try_lock() {
+        Ok(context) => (
+            context.region.to_string(),
+            context.recent_messages.iter().cloned().collect(),
+            context.board_hash,
+        ),
+        Err(_) => ("unknown".to_string(), vec![], None),
+    };
+
+    CrashReport {
+        client_version: option_env!("TR_COMMIT")
+            .unwrap_or(env!("CARGO_PKG_VERSION"))
+            .to_string(),
+        region,
+        panic_message,
+        recent_messages,
+        board_hash,
+    }
+}
+
+#[allow(unused_variables)]
+fn store_pending_report(report: CrashReport) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let Some(local_storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+        else {
+            return;
+        };
+        if let Ok(report) = serde_json::to_string(&report) {
+            _ = local_storage.set_item(PENDING_REPORT_KEY, &report);
+        }
+    }
+}
+
+/// Takes the report left behind by a crash in the last session, so it is only offered once
+pub fn take_pending_report() -> Option<CrashReport> {
+    #[allow(unused_mut)]
+    let mut report = None;
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+        if let Ok(Some(stored)) = local_storage.get_item(PENDING_REPORT_KEY) {
+            _ = local_storage.remove_item(PENDING_REPORT_KEY);
+            report = serde_json::from_str(&stored).ok();
+        }
+    }
+
+    report
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Mutex};
 
 use chrono::Offset;
 use eframe::egui;
@@ -7,8 +7,9 @@ use instant::Duration;
 use serde::{Deserialize, Serialize};
 use truncate_core::{
     generation::{generate_board, get_game_verification, BoardSeed},
-    npc::scoring::NPCPersonality,
-    rules::GameRules,
+    npc::progression::puzzle_opponent,
+    rotation::{self, PuzzleCategory, PuzzleTheme},
+    rules::{GameRules, Language},
 };
 
 use crate::{
@@ -19,8 +20,9 @@ use crate::{
 use super::Theme;
 
 const SEED_NOTES: &[u8] = include_bytes!("../../../truncate_dueller/seed_notes.yml");
-// January 29, 2023
-pub const DAILY_PUZZLE_DAY_ZERO: usize = 19751;
+/// The notes for another language's daily puzzles, downloaded from the server alongside its words
+static LANGUAGE_NOTES: Mutex<Option<(Language, NotesFile)>> = Mutex::new(None);
+pub const DAILY_PUZZLE_DAY_ZERO: usize = rotation::DAILY_PUZZLE_DAY_ZERO as usize;
 
 /**
  * TODO: Store NotesFile and SeedNote type definitions in a common crate
@@ -33,6 +35,12 @@ pub struct SeedNote {
     pub board_generation: u32,
     pub rules_generation: u32,
     pub verification: String,
+    /// Mystery puzzles hide the board, revealing it as the player's tiles spread
+    #[serde(default)]
+    pub mystery: bool,
+    /// The rung of the NPC ladder that plays the puzzle, rather than the usual opponent
+    #[serde(default)]
+    pub npc_rung: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -49,12 +57,57 @@ pub fn get_puzzle_day(current_time: Duration) -> u32 {
     day
 }
 
-pub type HumanStarts = bool;
-pub fn get_raw_daily_puzzle(day: u32) -> (BoardSeed, Option<(HumanStarts, SeedNote)>) {
-    let loaded_notes: NotesFile =
-        serde_yaml::from_slice(SEED_NOTES).expect("Seed notes should match the spec");
+/// The language to play the daily puzzle in, going by the browser's language.
+/// Players whose language the server has no puzzles for are given the English puzzle.
+pub fn player_language() -> Language {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let browser_language = web_sys::window().unwrap().navigator().language();
+        if let Some(language) = browser_language
+            .as_deref()
+            .and_then(|tag| tag.split('-').next())
+            .and_then(Language::from_code)
+        {
+            return language;
+        }
+    }
 
-    let notes = loaded_notes.notes.get(&day);
+    Language::English
+}
+
+/// Keeps the downloaded notes for another language's daily puzzles, replacing any other language's notes
+pub fn use_language_notes(language: Language, seed_notes: &str) -> Result<(), String> {
+    let notes: NotesFile = serde_yaml::from_str(seed_notes)
+        .map_err(|_| format!("The {language} daily puzzles didn't download correctly"))?;
+    *LANGUAGE_NOTES.lock().unwrap() = Some((language, notes));
+    Ok(())
+}
+
+/// Whether the daily puzzles in the given language are ready to play
+pub fn has_language_notes(language: Language) -> bool {
+    language == Language::English
+        || LANGUAGE_NOTES
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|(notes_language, _)| *notes_language == language)
+}
+
+pub type HumanStarts = bool;
+pub fn get_raw_daily_puzzle(
+    day: u32,
+    language: Language,
+) -> (BoardSeed, Option<(HumanStarts, SeedNote)>) {
+    let notes = match LANGUAGE_NOTES.lock().unwrap().as_ref() {
+        Some((notes_language, notes)) if *notes_language == language => {
+            notes.notes.get(&day).cloned()
+        }
+        _ => {
+            let loaded_notes: NotesFile =
+                serde_yaml::from_slice(SEED_NOTES).expect("Seed notes should match the spec");
+            loaded_notes.notes.get(&day).cloned()
+        }
+    };
 
     if let Some(notes) = notes {
         let mut board_seed = BoardSeed::new_with_generation(notes.board_generation, day).day(day);
@@ -62,7 +115,7 @@ pub fn get_raw_daily_puzzle(day: u32) -> (BoardSeed, Option<(HumanStarts, SeedNo
         for _ in 0..notes.rerolls {
             board_seed.external_reroll();
         }
-        let info = Some((notes.best_player == 0, notes.clone()));
+        let info = Some((notes.best_player == 0, notes));
 
         (board_seed, info)
     } else {
@@ -75,12 +128,13 @@ pub fn get_raw_daily_puzzle(day: u32) -> (BoardSeed, Option<(HumanStarts, SeedNo
 pub fn get_playable_daily_puzzle(
     ctx: &egui::Context,
     day: u32,
+    language: Language,
     map_texture: &TextureHandle,
     theme: &Theme,
     _backchannel: &Backchannel,
     event_dispatcher: EventDispatcher,
 ) -> SinglePlayerState {
-    let (board_seed, info) = get_raw_daily_puzzle(day);
+    let (board_seed, info) = get_raw_daily_puzzle(day, language);
 
     let mut header_sentinel = if info.is_some() { '#' } else { '?' };
     let human_starts = info.as_ref().map(|(h, _)| *h).unwrap_or(true);
@@ -93,6 +147,8 @@ pub fn get_playable_daily_puzzle(
         .as_ref()
         .map(|(_, note)| note.rules_generation)
         .unwrap_or_else(|| GameRules::latest(Some(day)).0);
+    let mystery = info.as_ref().is_some_and(|(_, note)| note.mystery);
+    let npc_rung = info.as_ref().and_then(|(_, note)| note.npc_rung);
 
     let mut game_state = SinglePlayerState::new(
         "daily".to_string(),
@@ -102,9 +158,10 @@ pub fn get_playable_daily_puzzle(
         board,
         Some(board_seed.clone()),
         rules_generation,
+        language,
         human_starts,
         HeaderType::None, // Replaced soon with HeaderType::Summary
-        NPCPersonality::jet(),
+        puzzle_opponent(npc_rung),
         event_dispatcher,
     );
 
@@ -115,10 +172,62 @@ pub fn get_playable_daily_puzzle(
         }
     }
 
+    let title = if mystery {
+        game_state.set_mystery();
+        format!("Truncate Town Mystery {header_sentinel}{day}")
+    } else {
+        format!("Truncate Town Day {header_sentinel}{day}")
+    };
+
     game_state.header = HeaderType::Summary {
-        title: format!("Truncate Town Day {header_sentinel}{day}"),
+        title,
         attempt: Some(0),
     };
 
     game_state
 }
+
+/// One of the weekly or themed puzzles, which the server keeps attempts at apart from the daily puzzle.
+/// Their boards come straight from the category's seed, so they aren't checked by the dueller.
+pub fn get_playable_rotation_puzzle(
+    ctx: &egui::Context,
+    category: PuzzleCategory,
+    period: u32,
+    map_texture: &TextureHandle,
+    theme: &Theme,
+    event_dispatcher: EventDispatcher,
+) -> Option<SinglePlayerState> {
+    let board_seed = category.board_seed(period)?;
+    let board = generate_board(board_seed.clone())
+        .expect("Common seeds should always generate a board")
+        .board;
+
+    let mut game_state = SinglePlayerState::new(
+        category.key().to_string(),
+        ctx,
+        map_texture.clone(),
+        theme.clone(),
+        board,
+        Some(board_seed),
+        GameRules::latest(Some(category.first_day(period))).0,
+        Language::English,
+        true,
+        HeaderType::None, // Replaced soon with HeaderType::Summary
+        puzzle_opponent(category.npc_rung()),
+        event_dispatcher,
+    );
+    game_state.set_category(category);
+
+    let name = match category {
+        PuzzleCategory::Daily => "Day",
+        PuzzleCategory::WeeklyHard => "Weekly Hard",
+        PuzzleCategory::Themed(PuzzleTheme::VowelHeavy) => "Vowel Heavy",
+        PuzzleCategory::Themed(PuzzleTheme::ConsonantHeavy) => "Consonant Heavy",
+    };
+    game_state.header = HeaderType::Summary {
+        title: format!("Truncate Town {name} #{period}"),
+        attempt: Some(0),
+    };
+
+    Some(game_state)
+}
@@ -2,15 +2,16 @@ use epaint::{vec2, Color32, Rect, TextureHandle, Vec2};
 use instant::Duration;
 use truncate_core::{
     board::{Coordinate, Square},
+    flags::Flags,
     generation::BoardSeed,
-    messages::RoomCode,
-    npc::scoring::NPCPersonality,
+    messages::{GameEndStateMessage, RoomCode},
+    npc::{scoring::NPCPersonality, SearchReport},
     reporting::Change,
 };
 
-use crate::regions::active_game::HeaderType;
+use crate::regions::active_game::{HandLayout, HeaderType, SidebarTab};
 
-use super::Theme;
+use super::{board_view::ViewRotation, control_devices::InputProfile, Theme};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct HoveredRegion {
@@ -37,6 +38,23 @@ pub struct InteractionDepot {
     pub selected_tile_in_hand: Option<(usize, char)>,
     pub highlight_tiles: Option<Vec<char>>,
     pub highlight_squares: Option<Vec<Coordinate>>,
+    /// Whether the next tap on the board points at a square, rather than playing to it
+    pub pointing: bool,
+    /// Squares recently pointed at, with who pointed and when
+    pub pointed_squares: Vec<(Coordinate, u64, Duration)>,
+    /// The square a blank is being placed on, while the player picks its letter
+    pub picking_blank: Option<Coordinate>,
+    /// A tile in the hand previewed on a square, waiting on a second tap to be played
+    pub pending_placement: Option<(usize, Coordinate)>,
+}
+
+impl InteractionDepot {
+    /// The square the selected tile is previewed on, if it's still the tile that was tapped onto it
+    pub fn pending_square(&self) -> Option<Coordinate> {
+        let (pending_tile, coord) = self.pending_placement?;
+        let (selected_tile, _) = self.selected_tile_in_hand?;
+        (pending_tile == selected_tile).then_some(coord)
+    }
 }
 
 #[derive(Clone, Default)]
@@ -51,9 +69,21 @@ pub struct UIStateDepot {
     pub sidebar_toggled: bool,
     pub sidebar_hidden: bool,
     pub unread_sidebar: bool,
+    pub sidebar_tab: SidebarTab,
+    pub chat_focused: bool,
     pub hand_hidden: bool,
+    /// Where the player would like the hand, which may be left up to the layout
+    pub hand_layout: HandLayout,
+    /// Whether the hand is docked beside the board this frame
+    pub hand_docked: bool,
+    /// Whether the opponent's letters are turned upright on the board
+    pub view_rotation: ViewRotation,
     pub is_mobile: bool,
     pub is_touch: bool,
+    /// The device the player last played with
+    pub input_profile: InputProfile,
+    /// The device the player has chosen to play with, rather than leaving it to be detected
+    pub input_preference: Option<InputProfile>,
     pub game_header: HeaderType,
     pub actions_menu_open: bool,
     pub dictionary_open: bool,
@@ -61,6 +91,33 @@ pub struct UIStateDepot {
     pub dictionary_opened_by_keyboard: bool,
     pub dictionary_showing_definition: bool,
     pub hand_height_last_frame: f32,
+    pub appears_away: bool,
+    /// Whether the player allows repaints to slow down while waiting on their opponent
+    pub low_power_mode: bool,
+    pub low_power_engaged: bool,
+    /// Whether the player has opted out of seasonal themes
+    pub classic_look: bool,
+    /// How long the bot may think about each move on this device, if limited
+    pub npc_budget_ms: Option<u64>,
+    /// How many threads the bot may search with on this device, if chosen
+    pub npc_threads: Option<usize>,
+    /// Whether hovering our own tiles shows what would be truncated if they were lost
+    pub truncation_preview: bool,
+    /// Whether the dictionary can suggest words from the player's letters, which is only for practice
+    pub word_finder: bool,
+    /// Whether tapping a tile onto the board previews it before it's played,
+    /// where `None` leaves it on for small screens
+    pub confirm_placement: Option<bool>,
+    /// The finished game's result, ready to be copied from the header
+    pub share_summary: Option<String>,
+    pub summary_copied_at: Option<Duration>,
+}
+
+impl UIStateDepot {
+    /// Whether placing a tile by tapping takes a second tap to confirm
+    pub fn confirms_placement(&self) -> bool {
+        self.is_touch && self.confirm_placement.unwrap_or(self.is_mobile)
+    }
 }
 
 #[derive(Clone)]
@@ -88,6 +145,13 @@ pub struct TimingDepot {
     pub last_turn_change: Duration,
     pub game_ends_at: Option<u64>,
     pub paused: bool,
+    pub last_interaction: Duration,
+    /// How long the player can be idle on their turn before we check in on them
+    pub idle_threshold: Option<Duration>,
+    /// When the bot started evaluating its current move, if it's still thinking
+    pub npc_thinking_since: Option<Duration>,
+    /// How far the bot's search for its current move has got
+    pub npc_search: Option<SearchReport>,
 }
 
 #[derive(Clone)]
@@ -101,6 +165,15 @@ pub struct GameplayDepot {
     pub last_battle_origin: Option<Coordinate>,
     pub npc: Option<NPCPersonality>,
     pub remaining_turns: Option<u64>,
+    /// Turns left until hands are swapped, in games that swap them
+    pub hand_swap_in: Option<u64>,
+    pub end_state: Option<GameEndStateMessage>,
+    /// Our latest round trip to the server, for online games
+    pub latency_ms: Option<u64>,
+    /// The latest ruling from a tournament arbiter, and their reason for it
+    pub arbiter_notice: Option<(String, String)>,
+    /// How far our starting hand strays from the bag, in bits, while we can still redraw it
+    pub mulligan_offer: Option<f32>,
 }
 
 #[derive(Clone)]
@@ -128,4 +201,5 @@ pub struct TruncateDepot {
     pub gameplay: GameplayDepot,
     pub aesthetics: AestheticDepot,
     pub audio: AudioDepot,
+    pub flags: Flags,
 }
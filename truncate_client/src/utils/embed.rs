@@ -0,0 +1,134 @@
+use epaint::Color32;
+use serde::{Deserialize, Serialize};
+
+use super::Theme;
+
+/// Board sizes an embed can ask for, beyond which towns stop fitting or the board stops fitting the screen
+const EMBED_BOARD_SIZES: std::ops::RangeInclusive<usize> = 5..=20;
+
+/// Colours a host page can swap into the theme, each as a `#RRGGBB` hex string
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeOverrides {
+    pub water: Option<String>,
+    pub grass: Option<String>,
+    pub text: Option<String>,
+    pub button_primary: Option<String>,
+    pub button_secondary: Option<String>,
+}
+
+impl ThemeOverrides {
+    pub fn apply(&self, theme: &mut Theme) {
+        let overrides = [
+            (&self.water, &mut theme.water),
+            (&self.grass, &mut theme.grass),
+            (&self.text, &mut theme.text),
+            (&self.button_primary, &mut theme.button_primary),
+            (&self.button_secondary, &mut theme.button_secondary),
+        ];
+        for (hex, color) in overrides {
+            let Some(hex) = hex else {
+                continue;
+            };
+            match parse_hex(hex) {
+                Some(parsed) => *color = parsed,
+                None => tracing::warn!("Ignoring embed colour {hex}, expected #RRGGBB"),
+            }
+        }
+    }
+}
+
+/// How a page embedding Truncate wants the game set up, passed to `start_embedded` as JSON
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EmbedOptions {
+    pub theme: ThemeOverrides,
+    /// Width and height of the board for single player games started from the embed
+    pub board_size: Option<[usize; 2]>,
+    /// Shows games without letting the visitor play any moves
+    pub read_only: bool,
+}
+
+impl EmbedOptions {
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_json(options: &str) -> Self {
+        if options.trim().is_empty() {
+            return Self::default();
+        }
+        serde_json::from_str(options).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring embed options that don't parse: {e}");
+            Self::default()
+        })
+    }
+
+    /// The land size requested for single player boards, kept within sizes we can lay out
+    pub fn board_size(&self) -> Option<[usize; 2]> {
+        let clamp = |n: usize| n.clamp(*EMBED_BOARD_SIZES.start(), *EMBED_BOARD_SIZES.end());
+        self.board_size.map(|[w, h]| [clamp(w), clamp(h)])
+    }
+}
+
+/// Handed to the host page's game-end callback, as JSON
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbedGameEnd {
+    pub room_code: String,
+    pub winner: usize,
+    pub player: usize,
+    pub won: bool,
+}
+
+/// Everything the client knows about the page it has been embedded in, if any
+#[derive(Default)]
+pub struct Embed {
+    pub options: EmbedOptions,
+    #[cfg(target_arch = "wasm32")]
+    pub on_game_end: Option<js_sys::Function>,
+    /// Set once the game on screen has been reported as finished, until a game is underway again
+    reported: bool,
+}
+
+impl Embed {
+    #[cfg(target_arch = "wasm32")]
+    pub fn new(options: EmbedOptions, on_game_end: Option<js_sys::Function>) -> Self {
+        Self {
+            options,
+            on_game_end,
+            reported: false,
+        }
+    }
+
+    /// Called each frame with how the game on screen ended, if it has,
+    /// telling the host page about each game once as it finishes
+    pub fn track_game_end(&mut self, result: Option<EmbedGameEnd>) {
+        let Some(result) = result else {
+            self.reported = false;
+            return;
+        };
+        if self.reported {
+            return;
+        }
+        self.reported = true;
+
+        #[cfg(target_arch = "wasm32")]
+        if let Some(on_game_end) = &self.on_game_end {
+            use eframe::wasm_bindgen::JsValue;
+
+            let result = serde_json::to_string(&result).expect("Game end should be serializable");
+            if let Err(e) = on_game_end.call1(&JsValue::NULL, &JsValue::from(result)) {
+                tracing::warn!("Embedding page's game end callback failed: {e:?}");
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = result;
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color32> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
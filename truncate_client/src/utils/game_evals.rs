@@ -1,10 +1,18 @@
-use std::sync::{Mutex, MutexGuard};
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
 
 use truncate_core::{
     game::Game,
     judge::{WordData, WordDict},
     messages::PlayerMessage,
-    npc::scoring::{NPCParams, NPCVocab},
+    npc::{
+        endgame::{EndgameLimits, EndgameSolver},
+        scoring::{NPCParams, NPCVocab},
+        Arborist, SearchProgress, SearchShare, ShareResult,
+    },
+    rules::Language,
 };
 
 pub static TRUNCATE_DICT: &str = include_str!("../../../dict_builder/final_wordlist.txt");
@@ -13,6 +21,9 @@ static TOTAL_DICT: Mutex<Option<WordDict>> = Mutex::new(None);
 static SMALL_VOCAB_DICT_SAFE: Mutex<Option<WordDict>> = Mutex::new(None);
 static MEDIUM_VOCAB_DICT_SAFE: Mutex<Option<WordDict>> = Mutex::new(None);
 static LARGE_VOCAB_DICT_UNSAFE: Mutex<Option<WordDict>> = Mutex::new(None);
+/// The words of another language, downloaded from the server for its daily puzzles
+static LANGUAGE_DICT: Mutex<Option<WordDict>> = Mutex::new(None);
+static DICT_LANGUAGE: Mutex<Language> = Mutex::new(Language::English);
 
 fn ensure_dicts() {
     let mut total_dict = TOTAL_DICT.lock().unwrap();
@@ -21,10 +32,10 @@ fn ensure_dicts() {
     let mut large_vocab_dict = LARGE_VOCAB_DICT_UNSAFE.lock().unwrap();
 
     if total_dict.is_none() {
-        let mut valid_words = std::collections::HashMap::new();
-        let mut small_vocab_words = std::collections::HashMap::new();
-        let mut medium_vocab_words = std::collections::HashMap::new();
-        let mut large_vocab_words = std::collections::HashMap::new();
+        let mut valid_words = WordDict::new();
+        let mut small_vocab_words = WordDict::new();
+        let mut medium_vocab_words = WordDict::new();
+        let mut large_vocab_words = WordDict::new();
         let lines = TRUNCATE_DICT.lines();
 
         for line in lines {
@@ -101,7 +112,91 @@ pub fn get_main_dict() -> MutexGuard<'static, Option<WordDict>> {
     TOTAL_DICT.lock().unwrap()
 }
 
-pub fn client_best_move(game: &Game, npc_params: &NPCParams) -> PlayerMessage {
+/// Keeps the downloaded words of a language other than English, replacing any other language's words
+pub fn use_language_dict(language: Language, words: WordDict) {
+    *LANGUAGE_DICT.lock().unwrap() = Some(words);
+    *DICT_LANGUAGE.lock().unwrap() = language;
+}
+
+/// Whether the words of the given language are ready to play with
+pub fn has_language_dict(language: Language) -> bool {
+    language == Language::English || *DICT_LANGUAGE.lock().unwrap() == language
+}
+
+/// The dictionary for games in the given language, which is the main dictionary
+/// unless the language's words have been downloaded
+pub fn get_dict(language: Language) -> MutexGuard<'static, Option<WordDict>> {
+    if language == Language::English || *DICT_LANGUAGE.lock().unwrap() != language {
+        return get_main_dict();
+    }
+
+    LANGUAGE_DICT.lock().unwrap()
+}
+
+pub fn client_best_move(
+    game: &Game,
+    npc_params: &NPCParams,
+    budget_ms: Option<u64>,
+    threads: usize,
+    progress: Option<Arc<SearchProgress>>,
+) -> PlayerMessage {
+    let mut arb = npc_arborist(npc_params, budget_ms, progress);
+    // Only budgeted searches are threaded, as they already vary with the speed of the device,
+    // leaving searches that need to be reproducible (such as the daily puzzle) single threaded
+    if budget_ms.is_some() {
+        arb.threaded(threads);
+    }
+
+    client_search(game, npc_params, &mut arb)
+}
+
+/// Searches one share of the NPC's moves, for combining with the other shares
+/// that are searched elsewhere, such as on other web workers
+pub fn client_share_move(
+    game: &Game,
+    npc_params: &NPCParams,
+    budget_ms: Option<u64>,
+    share: SearchShare,
+    progress: Option<Arc<SearchProgress>>,
+) -> ShareResult {
+    let mut arb = npc_arborist(npc_params, budget_ms, progress);
+    // Each share counts towards the same cap, as the threads of a threaded search do
+    arb.capped(npc_params.evaluation_cap / share.of.max(1));
+    arb.sharing(share);
+
+    let chosen = client_search(game, npc_params, &mut arb);
+    arb.share_result(chosen)
+}
+
+fn npc_arborist(
+    npc_params: &NPCParams,
+    budget_ms: Option<u64>,
+    progress: Option<Arc<SearchProgress>>,
+) -> Arborist {
+    let mut arb = Arborist::pruning();
+    arb.capped(npc_params.evaluation_cap);
+    if let Some(budget_ms) = budget_ms {
+        arb.budgeted(Duration::from_millis(budget_ms));
+    }
+    if let Some(progress) = progress {
+        arb.reporting(progress);
+    }
+    arb
+}
+
+fn client_search(game: &Game, npc_params: &NPCParams, arb: &mut Arborist) -> PlayerMessage {
+    if game.rules.language != Language::English && has_language_dict(game.rules.language) {
+        // Other languages have no frequencies to go by, so the NPC knows every word in their list
+        let language_dict = get_dict(game.rules.language);
+        return search_with_dicts(
+            game,
+            npc_params,
+            arb,
+            language_dict.as_ref(),
+            language_dict.as_ref(),
+        );
+    }
+
     ensure_dicts();
 
     let npc_known_dict = match npc_params.vocab {
@@ -110,20 +205,43 @@ pub fn client_best_move(game: &Game, npc_params: &NPCParams) -> PlayerMessage {
     };
     let player_known_dict = LARGE_VOCAB_DICT_UNSAFE.lock().unwrap();
 
+    search_with_dicts(
+        game,
+        npc_params,
+        arb,
+        npc_known_dict.as_ref(),
+        player_known_dict.as_ref(),
+    )
+}
+
+fn search_with_dicts(
+    game: &Game,
+    npc_params: &NPCParams,
+    arb: &mut Arborist,
+    npc_known_dict: Option<&WordDict>,
+    player_known_dict: Option<&WordDict>,
+) -> PlayerMessage {
     let _start = instant::SystemTime::now()
         .duration_since(instant::SystemTime::UNIX_EPOCH)
         .expect("Please don't play Truncate before 1970")
         .as_millis();
 
-    let mut arb = truncate_core::npc::Arborist::pruning();
-    arb.capped(npc_params.evaluation_cap);
+    // Small endgames can be solved outright, so play a forced win whenever there is one
+    if let Some(npc_player) = game.next_player {
+        let mut dictionaries = [player_known_dict, player_known_dict];
+        dictionaries[npc_player % 2] = npc_known_dict;
+        let mut solver = EndgameSolver::new(EndgameLimits::default(), dictionaries);
+        if let Some(winning_move) = solver.winning_move(game) {
+            return winning_move;
+        }
+    }
 
     let (best_move, _score) = truncate_core::game::Game::best_move(
         game,
-        npc_known_dict.as_ref(),
-        player_known_dict.as_ref(),
+        npc_known_dict,
+        player_known_dict,
         npc_params.max_depth,
-        Some(&mut arb),
+        Some(arb),
         false,
         npc_params,
     );
@@ -136,6 +254,84 @@ pub fn client_best_move(game: &Game, npc_params: &NPCParams) -> PlayerMessage {
     best_move
 }
 
+/// A search for the NPC's move on its own thread, so that the game keeps drawing while it runs
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+pub struct BackgroundSearch {
+    progress: Arc<SearchProgress>,
+    result: Arc<Mutex<Option<PlayerMessage>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl BackgroundSearch {
+    pub fn start(
+        game: Game,
+        npc_params: NPCParams,
+        budget_ms: Option<u64>,
+        threads: usize,
+    ) -> Self {
+        let search = Self {
+            progress: Arc::new(SearchProgress::new()),
+            result: Arc::new(Mutex::new(None)),
+        };
+
+        let progress = search.progress.clone();
+        let result = search.result.clone();
+        std::thread::spawn(move || {
+            let best = client_best_move(&game, &npc_params, budget_ms, threads, Some(progress));
+            *result.lock().unwrap() = Some(best);
+        });
+
+        search
+    }
+
+    pub fn report(&self) -> truncate_core::npc::SearchReport {
+        self.progress.report()
+    }
+
+    /// The move found, once the search has finished
+    pub fn take_result(&self) -> Option<PlayerMessage> {
+        self.result.lock().unwrap().take()
+    }
+}
+
+/// How many threads the NPC can search with, as chosen in the settings or else with `TRUNCATE_NPC_THREADS`.
+/// Defaults to all but one of the device's cores, leaving one free for drawing the game.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn search_threads(setting: Option<usize>) -> usize {
+    setting
+        .or_else(|| {
+            std::env::var("TRUNCATE_NPC_THREADS")
+                .ok()
+                .and_then(|threads| threads.parse().ok())
+        })
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|cores| cores.get().saturating_sub(1))
+                .unwrap_or(1)
+        })
+        .max(1)
+}
+
+/// Web workers each load their own copy of the dictionaries,
+/// so unless the player asks for more the web client stops at this many
+#[cfg(target_arch = "wasm32")]
+const DEFAULT_WEB_WORKERS: usize = 4;
+
+/// How many web workers the NPC can search with, as chosen in the settings.
+/// Defaults to all but one of the device's cores, leaving one free for drawing the game.
+#[cfg(target_arch = "wasm32")]
+pub fn search_threads(setting: Option<usize>) -> usize {
+    setting
+        .unwrap_or_else(|| {
+            let cores = web_sys::window()
+                .map(|w| w.navigator().hardware_concurrency() as usize)
+                .unwrap_or(1);
+            cores.saturating_sub(1).min(DEFAULT_WEB_WORKERS)
+        })
+        .max(1)
+}
+
 /// Adds the given word to the static dictionaries for the NPC
 pub fn remember(word: &String) {
     ensure_dicts();
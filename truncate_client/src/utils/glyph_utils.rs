@@ -1,38 +1,153 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
 
 use crate::utils::mapper::ImageMusher;
 use ab_glyph::{Font, FontRef, ScaleFont};
 use epaint::{
     ahash::{HashMap, HashMapExt},
+    text::FontDefinitions,
     Color32, ColorImage,
 };
+use truncate_core::bag::{digraph_letters, is_blank, BLANK};
 
-struct InnerGlypher {
+/// Fonts bundled with egui that cover what ours don't, such as accented, Greek, and Cyrillic
+/// letters, and emoji. Each is scaled so its letters come out about as large as the tile font's.
+pub const FALLBACK_FONTS: [(&str, f32); 3] = [
+    ("Ubuntu-Light", 1.25),
+    ("NotoEmoji-Regular", 1.0),
+    ("emoji-icon-font", 1.0),
+];
+
+struct GlyphFont {
     font: FontRef<'static>,
+    scale: f32,
+}
+
+struct InnerGlypher {
+    /// The tile font, followed by the fonts to try when it has no glyph for a character
+    fonts: Vec<GlyphFont>,
     cache: HashMap<(char, usize), ColorImage>,
 }
 
 impl InnerGlypher {
     fn new() -> Self {
-        Self {
+        let mut fonts = vec![GlyphFont {
             font: ab_glyph::FontRef::try_from_slice(include_bytes!(
                 "../../font/PressStart2P-Regular.ttf"
             ))
             .unwrap(),
+            scale: 1.0,
+        }];
+
+        let bundled = FontDefinitions::default();
+        for (name, scale) in FALLBACK_FONTS {
+            // Only fonts that live for the whole program can be borrowed for painting
+            let Some(Cow::Borrowed(bytes)) = bundled.font_data.get(name).map(|data| &data.font)
+            else {
+                continue;
+            };
+            if let Ok(font) = FontRef::try_from_slice(*bytes) {
+                fonts.push(GlyphFont { font, scale });
+            }
+        }
+
+        Self {
+            fonts,
             cache: HashMap::with_capacity(256),
         }
     }
 
     fn cached_paint(&mut self, glyph_id: char, scale: usize) -> ColorImage {
+        let fonts = &self.fonts;
         self.cache
             .entry((glyph_id, scale))
-            .or_insert_with(|| paint(&self.font, glyph_id, scale))
+            .or_insert_with(|| paint_with_fallback(fonts, glyph_id, scale))
             .clone()
     }
 }
 
-fn paint(font: &FontRef<'static>, glyph_id: char, scale: usize) -> ColorImage {
-    let font = font.as_scaled(ab_glyph::PxScale::from(scale as f32));
+fn paint_with_fallback(fonts: &[GlyphFont], glyph_id: char, scale: usize) -> ColorImage {
+    if let Some(letters) = digraph_letters(glyph_id) {
+        return paint_digraph(fonts, letters, scale);
+    }
+    if glyph_id != BLANK && is_blank(glyph_id) {
+        return paint_blank(fonts, glyph_id, scale);
+    }
+
+    let Some(glyph_font) = fonts
+        .iter()
+        .find(|f| f.font.glyph_id(glyph_id).0 != 0)
+        .or(fonts.first())
+    else {
+        return ColorImage::default();
+    };
+
+    let font_scale = scale as f32 * glyph_font.scale;
+    let image = paint(&glyph_font.font, glyph_id, font_scale);
+
+    // Some scripts run wider or taller than the tile font,
+    // so shrink them to fit the same box and stay centered on the tile
+    let largest = image.width().max(image.height());
+    if largest > scale {
+        paint(
+            &glyph_font.font,
+            glyph_id,
+            font_scale * scale as f32 / largest as f32,
+        )
+    } else {
+        image
+    }
+}
+
+/// Paints each letter of a digraph tile smaller, side by side, so that they fit on one tile
+fn paint_digraph(fonts: &[GlyphFont], letters: &str, scale: usize) -> ColorImage {
+    const LETTER_GAP: usize = 1;
+
+    let glyphs: Vec<_> = letters
+        .chars()
+        .map(|letter| paint_with_fallback(fonts, letter, scale * 2 / 3))
+        .collect();
+    let width = glyphs.iter().map(|g| g.width() + LETTER_GAP).sum::<usize>() - LETTER_GAP;
+    let height = glyphs.iter().map(|g| g.height()).max().unwrap_or_default();
+
+    let mut image = ColorImage::new([width, height], Color32::TRANSPARENT);
+    let mut x = 0;
+    for glyph in &glyphs {
+        // Line the letters up along their bottom edge
+        image.hard_overlay(glyph, [x, height - glyph.height()]);
+        x += glyph.width() + LETTER_GAP;
+    }
+
+    image
+}
+
+/// Paints a blank played as a letter a little smaller and underlined,
+/// so it can be told apart from a drawn tile of the same letter
+fn paint_blank(fonts: &[GlyphFont], glyph_id: char, scale: usize) -> ColorImage {
+    const UNDERLINE_GAP: usize = 2;
+
+    let letter = glyph_id.to_uppercase().next().unwrap_or(glyph_id);
+    let glyph = paint_with_fallback(fonts, letter, scale * 4 / 5);
+    let underline = (scale / 12).max(1);
+
+    let mut image = ColorImage::new(
+        [glyph.width(), glyph.height() + UNDERLINE_GAP + underline],
+        Color32::TRANSPARENT,
+    );
+    image.hard_overlay(&glyph, [0, 0]);
+    for y in glyph.height() + UNDERLINE_GAP..image.height() {
+        for x in 0..image.width() {
+            image[(x, y)] = Color32::WHITE;
+        }
+    }
+
+    image
+}
+
+fn paint(font: &FontRef<'static>, glyph_id: char, scale: f32) -> ColorImage {
+    let font = font.as_scaled(ab_glyph::PxScale::from(scale));
 
     let image = font
         .outline_glyph(font.scaled_glyph(glyph_id))
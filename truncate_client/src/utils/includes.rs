@@ -46,6 +46,32 @@ pub enum ScenarioStep {
     },
 }
 
+/// The walkthrough shown the first time someone edits a board
+#[derive(Deserialize, Debug, Clone)]
+pub struct EditorGuide {
+    pub steps: Vec<EditorGuideStep>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum EditorGuideStep {
+    /// Waits for the board to pass the checks for one part of the editor
+    Task {
+        task: EditorTask,
+        description: String,
+    },
+    /// Dialogs and end messages, written the same way as in the tutorials
+    Scenario(ScenarioStep),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EditorTask {
+    Land,
+    Towns,
+    Docks,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub enum ChangePriority {
     High,
@@ -80,3 +106,8 @@ pub fn changelogs() -> HashMap<&'static str, Tutorial> {
         ),
     ])
 }
+
+pub fn editor_guide() -> EditorGuide {
+    serde_yaml::from_slice(include_bytes!("../../tutorials/editor_guide.yml"))
+        .expect("Editor guide should match EditorGuide format")
+}
@@ -1,7 +1,7 @@
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 
 use eframe::egui;
-use epaint::{hex_color, pos2, Color32, ColorImage, Mesh, Rect, Shape, TextureHandle};
+use epaint::{hex_color, pos2, vec2, Color32, ColorImage, Mesh, Pos2, Rect, Shape, TextureHandle};
 use instant::Duration;
 use truncate_core::{
     board::{Board, BoardDistances, Coordinate, Direction, SignedCoordinate, Square},
@@ -17,10 +17,12 @@ use self::image_manipulation::alpha_blend;
 pub use self::image_manipulation::ImageMusher;
 
 use super::{
+    board_view::{BoardView, ViewRotation},
     depot::{
         AestheticDepot, GameplayDepot, HoveredRegion, InteractionDepot, TimingDepot, UIStateDepot,
     },
     glyph_utils::Glypher,
+    perf,
     tex::{self, BGTexType, PieceLayer, Tex, TexLayers, TileDecoration},
     Lighten,
 };
@@ -102,6 +104,7 @@ struct MapState {
     prev_dragging: Option<(Coordinate, Square)>,
     prev_occupied_hover: Option<HoveredRegion>,
     prev_square_hover: Option<HoveredRegion>,
+    prev_pending_square: Option<Coordinate>,
     prev_changes: Vec<Change>,
     generic_tick: u32,
 }
@@ -112,12 +115,20 @@ pub struct MappedBoard {
     state_memory: Option<MapState>,
     /// Used to break cache and force a repaint
     generic_repaint_tick: u32,
+    /// Set when every square needs repainting, rather than just those that changed
+    needs_full_repaint: bool,
+    /// Squares part way through an animation, which repaint as it plays out
+    animating: HashSet<SignedCoordinate>,
+    /// Squares that were flashing when the board last painted, which need clearing once they stop
+    prev_highlights: Vec<Coordinate>,
     resolved_textures: Option<ResolvedTextureLayers>,
     /// Number of tiles to paint around the board in every direction
     map_buffer: usize,
     map_seed: usize,
-    inverted: bool,
+    /// How the board last painted is turned for `for_player`
+    view: BoardView,
     for_player: usize,
+    rotation: ViewRotation,
     daytime: bool,
     last_tick: u64,
     forecasted_wind: u8,
@@ -147,11 +158,15 @@ impl MappedBoard {
             ],
             state_memory: None,
             generic_repaint_tick: 0,
+            needs_full_repaint: false,
+            animating: HashSet::new(),
+            prev_highlights: vec![],
             resolved_textures: None,
             map_buffer,
             map_seed: (secs % 100000) as usize,
-            inverted: for_player == 0,
+            view: BoardView::for_player(board, for_player, ViewRotation::default()),
             for_player,
+            rotation: ViewRotation::default(),
             daytime,
             last_tick: 0,
             forecasted_wind: 0,
@@ -169,6 +184,20 @@ impl MappedBoard {
         self.map_buffer
     }
 
+    /// Forces the next remap to redraw every square, e.g. after the atlas changes
+    pub fn invalidate(&mut self) {
+        self.generic_repaint_tick += 1;
+        self.needs_full_repaint = true;
+    }
+
+    /// Turns opponent's letters upright or back, repainting if that changes
+    pub fn set_view_rotation(&mut self, rotation: ViewRotation) {
+        if self.rotation != rotation {
+            self.rotation = rotation;
+            self.invalidate();
+        }
+    }
+
     pub fn render_to_rect(&self, rect: Rect, ui_state: Option<&UIStateDepot>, ui: &mut egui::Ui) {
         let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
 
@@ -196,7 +225,42 @@ impl MappedBoard {
         }
     }
 
-    pub fn render_coord_to_rect(&self, mut coord: Coordinate, rect: Rect, ui: &mut egui::Ui) {
+    /// Which square of the board is drawn under a point, when the board was rendered to `rect`
+    pub fn square_at(&self, board: &Board, rect: Rect, pos: Pos2) -> Option<Coordinate> {
+        if !rect.contains(pos) {
+            return None;
+        }
+
+        let cols = board.width() + self.map_buffer * 2;
+        let rows = board.height() + self.map_buffer * 2;
+        let col = ((pos.x - rect.min.x) / rect.width() * cols as f32) as isize;
+        let row = ((pos.y - rect.min.y) / rect.height() * rows as f32) as isize;
+
+        self.view
+            .to_board(SignedCoordinate::new(
+                col - self.map_buffer as isize,
+                row - self.map_buffer as isize,
+            ))
+            .real_coord()
+            .filter(|coord| coord.x < board.width() && coord.y < board.height())
+    }
+
+    /// Where a square of the board is drawn, when the board was rendered to `rect`
+    pub fn square_rect(&self, board: &Board, rect: Rect, coord: Coordinate) -> Rect {
+        let cols = board.width() + self.map_buffer * 2;
+        let rows = board.height() + self.map_buffer * 2;
+        let square_size = vec2(rect.width() / cols as f32, rect.height() / rows as f32);
+
+        let screen = self.view.to_screen(coord);
+        let min = rect.min
+            + vec2(
+                (screen.x + self.map_buffer) as f32 * square_size.x,
+                (screen.y + self.map_buffer) as f32 * square_size.y,
+            );
+        Rect::from_min_size(min, square_size)
+    }
+
+    pub fn render_coord_to_rect(&self, coord: Coordinate, rect: Rect, ui: &mut egui::Ui) {
         let Some(memory) = &self.state_memory else {
             return;
         };
@@ -204,10 +268,7 @@ impl MappedBoard {
         let tile_width = 1.0 / memory.prev_board.width() as f32;
         let tile_height = 1.0 / memory.prev_board.height() as f32;
 
-        if self.inverted {
-            coord.x = memory.prev_board.width() - coord.x - 1;
-            coord.y = memory.prev_board.height() - coord.y - 1;
-        }
+        let coord = self.view.to_screen(coord);
 
         let uv = Rect::from_min_max(
             pos2(
@@ -230,7 +291,7 @@ impl MappedBoard {
     fn wind_vane(&mut self, tick: u64) {
         if self.last_tick != tick {
             self.last_tick = tick;
-            if self.inverted {
+            if self.view.is_rotated() {
                 self.winds.pop_back();
             } else {
                 self.winds.pop_front();
@@ -245,7 +306,7 @@ impl MappedBoard {
                 self.incoming_wind += (off_target / 3).clamp(1, 20);
             }
 
-            if self.inverted {
+            if self.view.is_rotated() {
                 self.winds.push_front(self.incoming_wind);
             } else {
                 self.winds.push_back(self.incoming_wind);
@@ -284,7 +345,7 @@ impl MappedBoard {
             .map(|pos| pos.map(|p| board.get(p).ok()).flatten())
             .collect();
 
-        if self.inverted {
+        if self.view.is_rotated() {
             neighbor_squares.rotate_left(4);
         }
 
@@ -336,13 +397,22 @@ impl MappedBoard {
             layers.mist = Some([tex::tiles::BASE_WATER; 4]);
         }
 
-        let orient = |player: usize| {
-            if player == self.for_player {
-                Direction::North
-            } else {
-                Direction::South
-            }
-        };
+        // High ground is shaded solid, rather than checkered like the rest of the land
+        let on_high_ground = coord
+            .real_coord()
+            .zip(board.elevation.as_ref())
+            .is_some_and(|(c, elevation)| elevation.is_high(c));
+        if on_high_ground && matches!(square, Square::Land { .. }) {
+            layers.checkerboard = Some(tex::tiles::quad::CHECKERBOARD);
+        }
+
+        // Tiles cut off from their artifact wilt until they're reconnected or truncated
+        let withering = coord
+            .real_coord()
+            .zip(board.withering.as_ref())
+            .is_some_and(|(c, withering)| withering.is_withering(c));
+
+        let orient = |player: usize| self.view.facing(player);
 
         let square_is_highlighted = interactions.is_some_and(|i| {
             coord
@@ -353,6 +423,8 @@ impl MappedBoard {
         let mut tile_was_added = false;
         let mut tile_was_swapped = false;
         let mut tile_was_victor = false;
+        let mut tile_was_slid = false;
+        let mut slid_from_here = None;
 
         let base_destructo_time = (timing.current_time - timing.last_turn_change).as_secs_f32();
         let mut destructo_time = base_destructo_time;
@@ -371,6 +443,16 @@ impl MappedBoard {
                 }
             }
 
+            slid_from_here = gameplay.changes.iter().find_map(|c| match c {
+                Change::Board(b) => match b.action {
+                    BoardChangeAction::Slid { from } if coord.real_coord() == Some(from) => {
+                        Some(b.detail.square)
+                    }
+                    _ => None,
+                },
+                _ => None,
+            });
+
             let changes = gameplay.changes.iter().filter_map(|c| match c {
                 Change::Board(b)
                     if coord.real_coord().is_some_and(|c| c == b.detail.coordinate) =>
@@ -408,6 +490,10 @@ impl MappedBoard {
                     BoardChangeAction::Victorious => {
                         tile_was_victor = true;
                     }
+                    BoardChangeAction::Slid { .. } => {
+                        tile_was_slid = true;
+                    }
+                    BoardChangeAction::Withered => { /* drawn from the board's withering tiles */ }
                     BoardChangeAction::Defeated => {
                         // TODO: We could use `validity` below to show whether a tile
                         // lost on length or lost on being invalid.
@@ -501,6 +587,19 @@ impl MappedBoard {
             }
         }
 
+        // Slid tiles hold their old squares until the rest of the turn has played out
+        let held_square;
+        let square = if (tile_was_slid || slid_from_here.is_some())
+            && base_destructo_time < aesthetics.destruction_duration
+        {
+            wants_repaint = true;
+            tile_was_slid = false;
+            held_square = slid_from_here.unwrap_or(Square::land());
+            &held_square
+        } else {
+            square
+        };
+
         match square {
             Square::Occupied {
                 player,
@@ -586,7 +685,7 @@ impl MappedBoard {
                 if highlight.is_none() {
                     if tile_was_added {
                         highlight = Some(aesthetics.theme.ring_added);
-                    } else if tile_was_swapped {
+                    } else if tile_was_swapped || tile_was_slid {
                         highlight = Some(aesthetics.theme.ring_modified);
                     }
                 }
@@ -610,7 +709,11 @@ impl MappedBoard {
                     color = Some(aesthetics.theme.ring_selected_hovered);
                 }
 
-                let mut variant = MappedTileVariant::Healthy;
+                let mut variant = if withering {
+                    MappedTileVariant::Dying
+                } else {
+                    MappedTileVariant::Healthy
+                };
                 if let Some(GameplayDepot {
                     winner: Some(winner),
                     ..
@@ -663,12 +766,12 @@ impl MappedBoard {
                 if let Some((interactions, coord)) = interactions.zip(coord.real_coord()) {
                     if let Some((_, tile_char)) = interactions.selected_tile_in_hand {
                         // Don't show preview tiles if anything is being dragged (i.e. a tile from the hand)
-                        if !ctx.memory(|m| m.is_anything_being_dragged())
-                            && interactions
-                                .hovered_unoccupied_square_on_board
-                                .as_ref()
-                                .is_some_and(|h| h.coord == Some(coord))
-                        {
+                        let previewed = interactions
+                            .hovered_unoccupied_square_on_board
+                            .as_ref()
+                            .is_some_and(|h| h.coord == Some(coord))
+                            || interactions.pending_square() == Some(coord);
+                        if !ctx.memory(|m| m.is_anything_being_dragged()) && previewed {
                             let self_color = gameplay
                                 .map(|gameplay| {
                                     player_colors.get(gameplay.player_number as usize).cloned()
@@ -769,22 +872,19 @@ impl MappedBoard {
             dest_row * (measures.inner_tile_height_px * 2),
         ];
 
+        // Quads are assembled on the CPU so that each square is a single texture upload
         let paint_quad = |quad: [Tex; 4], canvas: &mut TextureHandle| {
-            for (tex, sub_loc) in quad.into_iter().zip(
-                [
-                    [0, 0],
-                    [measures.inner_tile_width_px, 0],
-                    [measures.inner_tile_width_px, measures.inner_tile_height_px],
-                    [0, measures.inner_tile_height_px],
-                ]
-                .into_iter(),
-            ) {
-                canvas.set_partial(
-                    [dest_pos[0] + sub_loc[0], dest_pos[1] + sub_loc[1]],
-                    tex.slice_as_image(tileset),
-                    egui::TextureOptions::NEAREST,
-                );
+            let mut target =
+                ColorImage::new([tile_dims[0] * 2, tile_dims[1] * 2], Color32::TRANSPARENT);
+            for (tex, sub_loc) in quad.into_iter().zip([
+                [0, 0],
+                [tile_dims[0], 0],
+                [tile_dims[0], tile_dims[1]],
+                [0, tile_dims[1]],
+            ]) {
+                target.hard_overlay(&tex.slice_as_image(tileset), sub_loc);
             }
+            canvas.set_partial(dest_pos, target, egui::TextureOptions::NEAREST);
         };
 
         let erase = |img: &mut TextureHandle| {
@@ -854,7 +954,11 @@ impl MappedBoard {
                                         .saturating_add_signed(*y_offset),
                                 ];
 
-                                glyph.recolor(color);
+                                if *color == tex::TILE_LETTER {
+                                    glyph.recolor(&aesthetics.theme.tile_letter);
+                                } else {
+                                    glyph.recolor(color);
+                                }
                                 target.hard_overlay(&glyph, offset);
                             }
                         }
@@ -897,6 +1001,64 @@ impl MappedBoard {
         wants_repaint
     }
 
+    /// Finds the squares affected by board and interaction changes, along with
+    /// their neighbours since terrain is drawn to blend into its surroundings.
+    fn dirty_squares(
+        memory: &MapState,
+        board: &Board,
+        changes: &[Change],
+        interaction_coords: [Option<Coordinate>; 7],
+    ) -> HashSet<SignedCoordinate> {
+        let mut dirty = HashSet::new();
+        let mut mark = |coord: Coordinate| {
+            let coord = SignedCoordinate::new(coord.x as isize, coord.y as isize);
+            dirty.extend(coord.neighbors_8_iter());
+            dirty.insert(coord);
+        };
+
+        let prev_interaction_coords = [
+            memory.prev_selected_tile.map(|(c, _)| c),
+            memory.prev_selected_square.map(|(c, _)| c),
+            memory.prev_tile_hover.map(|(c, _)| c),
+            memory.prev_dragging.map(|(c, _)| c),
+            memory.prev_occupied_hover.as_ref().and_then(|h| h.coord),
+            memory.prev_square_hover.as_ref().and_then(|h| h.coord),
+            memory.prev_pending_square,
+        ];
+        for coord in interaction_coords
+            .into_iter()
+            .chain(prev_interaction_coords)
+            .flatten()
+        {
+            mark(coord);
+        }
+
+        for change in changes.iter().chain(memory.prev_changes.iter()) {
+            if let Change::Board(board_change) = change {
+                mark(board_change.detail.coordinate);
+            }
+        }
+
+        if memory.prev_board != *board {
+            for (y, (prev_row, row)) in memory
+                .prev_board
+                .squares
+                .iter()
+                .zip(board.squares.iter())
+                .enumerate()
+            {
+                for (x, (prev_square, square)) in prev_row.iter().zip(row.iter()).enumerate() {
+                    if prev_square != square {
+                        mark(Coordinate::new(x, y));
+                    }
+                }
+            }
+        }
+
+        dirty
+    }
+
+    /// Repaints whatever looks different since the last remap, returning how many squares were painted
     pub fn remap_texture(
         &mut self,
         ctx: &egui::Context,
@@ -905,7 +1067,8 @@ impl MappedBoard {
         interactions: Option<&InteractionDepot>,
         gameplay: Option<&GameplayDepot>,
         board: &Board,
-    ) {
+    ) -> usize {
+        let started = instant::Instant::now();
         let mut tick_eq = true;
         let selected_tile = interactions.map(|i| i.selected_tile_on_board).flatten();
         let selected_square = interactions.map(|i| i.selected_square_on_board).flatten();
@@ -917,8 +1080,18 @@ impl MappedBoard {
         let square_hover = interactions
             .map(|i| i.hovered_unoccupied_square_on_board.clone())
             .flatten();
+        let pending_square = interactions.and_then(|i| i.pending_square());
+        let highlights = interactions
+            .and_then(|i| i.highlight_squares.clone())
+            .unwrap_or_default();
         let generic_repaint_tick = self.generic_repaint_tick;
+        self.view = BoardView::for_player(board, self.for_player, self.rotation);
         let winner = gameplay.map(|g| g.winner).flatten();
+        let changes = gameplay.map(|g| g.changes.clone()).unwrap_or_default();
+
+        // Squares that may look different since the last remap,
+        // where `None` means that the whole board needs repainting.
+        let mut dirty_squares = None;
 
         if let Some(memory) = self.state_memory.as_mut() {
             let board_eq = memory.prev_board == *board;
@@ -928,6 +1101,7 @@ impl MappedBoard {
             let dragging_eq = memory.prev_dragging == dragging;
             let occupied_hover_eq = memory.prev_occupied_hover == occupied_hover;
             let square_hover_eq = memory.prev_square_hover == square_hover;
+            let pending_square_eq = memory.prev_pending_square == pending_square;
             let generic_tick_eq = memory.generic_tick == generic_repaint_tick;
             let winner_eq = memory.prev_winner == winner;
             if memory.prev_tick != aesthetics.qs_tick {
@@ -942,16 +1116,59 @@ impl MappedBoard {
                 && dragging_eq
                 && occupied_hover_eq
                 && square_hover_eq
+                && pending_square_eq
                 && generic_tick_eq
                 && winner_eq
             {
-                return;
+                return 0;
+            }
+
+            // Game endings touch every square, but animations and most interactions
+            // only affect the squares under them, so avoid walking large boards.
+            let same_dimensions = memory.prev_board.width() == board.width()
+                && memory.prev_board.height() == board.height();
+            if winner_eq && same_dimensions && !self.needs_full_repaint {
+                let mut dirty = Self::dirty_squares(
+                    memory,
+                    board,
+                    &changes,
+                    [
+                        selected_tile.map(|(c, _)| c),
+                        selected_square.map(|(c, _)| c),
+                        tile_hover.map(|(c, _)| c),
+                        dragging.map(|(c, _)| c),
+                        occupied_hover.as_ref().and_then(|h| h.coord),
+                        square_hover.as_ref().and_then(|h| h.coord),
+                        pending_square,
+                    ],
+                );
+                if !tick_eq || !generic_tick_eq {
+                    dirty.extend(self.animating.drain());
+                }
+                if !tick_eq {
+                    // Highlights flash and the selected square's spinner turns with the tick
+                    let flashing = self
+                        .prev_highlights
+                        .iter()
+                        .chain(highlights.iter())
+                        .chain(selected_square.as_ref().map(|(c, _)| c));
+                    dirty.extend(
+                        flashing.map(|c| SignedCoordinate::new(c.x as isize, c.y as isize)),
+                    );
+                }
+                dirty_squares = Some(dirty);
             }
 
             if !board_eq {
                 memory.prev_board = board.clone();
-                self.distance_to_land = board.flood_fill_water_from_land();
+                let distance_to_land = board.flood_fill_water_from_land();
+                // Water is drawn based on its distance to land, so a new coastline redraws everything
+                if distance_to_land != self.distance_to_land {
+                    dirty_squares = None;
+                }
+                self.distance_to_land = distance_to_land;
             }
+            memory.prev_changes = changes;
             if !selected_tile_eq {
                 memory.prev_selected_tile = selected_tile;
             }
@@ -970,6 +1187,9 @@ impl MappedBoard {
             if !square_hover_eq {
                 memory.prev_square_hover = square_hover;
             }
+            if !pending_square_eq {
+                memory.prev_pending_square = pending_square;
+            }
             if !tick_eq {
                 memory.prev_tick = aesthetics.qs_tick;
             }
@@ -989,22 +1209,35 @@ impl MappedBoard {
                 prev_dragging: dragging,
                 prev_occupied_hover: occupied_hover,
                 prev_square_hover: square_hover,
-                prev_changes: vec![],
+                prev_pending_square: pending_square,
+                prev_changes: changes,
                 generic_tick: 0,
                 prev_winner: winner,
             });
             tick_eq = false;
         }
 
+        // Grass sways with the wind blowing across the board diagonally,
+        // so only diagonals where the wind picked up or died down look any different
+        let mut gusting_diagonals = HashSet::new();
         if !tick_eq {
+            let prev_winds = self.winds.clone();
             self.wind_vane(aesthetics.qs_tick);
+            gusting_diagonals = prev_winds
+                .iter()
+                .zip(self.winds.iter())
+                .enumerate()
+                .filter(|(_, (prev, wind))| tex::wind_band(**prev) != tex::wind_band(**wind))
+                .map(|(diagonal, _)| diagonal)
+                .collect();
         }
 
         let measures = TEXTURE_MEASUREMENT
             .get()
             .expect("Base texture should have been measured");
-        let tileset = TEXTURE_IMAGE
-            .get()
+        let tileset_lock = TEXTURE_IMAGE.read().unwrap();
+        let tileset = tileset_lock
+            .as_ref()
             .expect("Base image should have been loaded");
         let glypher = GLYPHER.get().expect("Glypher should have been initialized");
 
@@ -1030,32 +1263,47 @@ impl MappedBoard {
                 vec![TexLayers::default(); board.width() + total_buffer];
                 board.height() + total_buffer
             ];
+            dirty_squares = None;
+        }
+        if dirty_squares.is_none() {
+            self.needs_full_repaint = false;
+            self.animating.clear();
+        }
+        if !tick_eq || dirty_squares.is_none() {
+            self.prev_highlights = highlights;
         }
 
+        let mut squares_repainted = 0;
         for dest_row in 0..(board.height() + total_buffer) {
             for dest_col in 0..(board.width() + total_buffer) {
-                let mut source_col = dest_col as isize - self.map_buffer as isize;
-                let mut source_row = dest_row as isize - self.map_buffer as isize;
-
-                if self.inverted {
-                    source_col = board.width() as isize - source_col - 1;
-                    source_row = board.height() as isize - source_row - 1;
-                }
-
-                let source_coord = SignedCoordinate::new(source_col, source_row);
+                let source_coord = self.view.to_board(SignedCoordinate::new(
+                    dest_col as isize - self.map_buffer as isize,
+                    dest_row as isize - self.map_buffer as isize,
+                ));
 
                 let square = source_coord
                     .real_coord()
                     .and_then(|c| board.get(c).ok())
-                    .unwrap_or_else(|| Square::Water { foggy: false });
+                    .unwrap_or(Square::Water { foggy: false });
+
+                let swaying = gusting_diagonals.contains(&(dest_col + dest_row))
+                    && !matches!(square, Square::Water { .. } | Square::Fog { .. });
+                if dirty_squares
+                    .as_ref()
+                    .is_some_and(|dirty: &HashSet<_>| !dirty.contains(&source_coord))
+                    && !swaying
+                {
+                    continue;
+                }
+                squares_repainted += 1;
 
                 let wants_repaint = self.paint_square_offscreen(
                     ctx,
                     board,
                     &aesthetics.player_colors,
                     aesthetics.qs_tick,
-                    source_row as _,
-                    source_col as _,
+                    source_coord.y,
+                    source_coord.x,
                     dest_row,
                     dest_col,
                     &square,
@@ -1071,9 +1319,13 @@ impl MappedBoard {
                 if wants_repaint {
                     ctx.request_repaint_after(Duration::from_millis(16));
                     self.generic_repaint_tick += 1;
+                    self.animating.insert(source_coord);
                 }
             }
         }
+        perf::record_board_remap(squares_repainted, started.elapsed());
+
+        squares_repainted
     }
 }
 
@@ -1114,6 +1366,11 @@ impl MappedTiles {
         }
     }
 
+    /// Forces the next remap to redraw every tile, e.g. after the atlas changes
+    pub fn invalidate(&mut self) {
+        self.slots.clear();
+    }
+
     #[must_use]
     fn reset_texture(capacity: usize, egui_ctx: &egui::Context) -> TextureHandle {
         let measures = TEXTURE_MEASUREMENT
@@ -1160,8 +1417,9 @@ impl MappedTiles {
         let measures = TEXTURE_MEASUREMENT
             .get()
             .expect("Base texture should have been measured");
-        let tileset = TEXTURE_IMAGE
-            .get()
+        let tileset_lock = TEXTURE_IMAGE.read().unwrap();
+        let tileset = tileset_lock
+            .as_ref()
             .expect("Base image should have been loaded");
         let glypher = GLYPHER.get().expect("Glypher should have been initialized");
 
@@ -1220,7 +1478,11 @@ impl MappedTiles {
                                 .saturating_add_signed(*y_offset),
                         ];
 
-                        glyph.recolor(color);
+                        if *color == tex::TILE_LETTER {
+                            glyph.recolor(&aesthetics.theme.tile_letter);
+                        } else {
+                            glyph.recolor(color);
+                        }
                         target.hard_overlay(&glyph, offset);
                     }
                 }
@@ -1251,3 +1513,68 @@ pub fn quickrand(mut n: usize) -> usize {
     n ^= n << 17;
     n % 100
 }
+
+#[cfg(test)]
+mod tests {
+    use truncate_core::board::Board;
+
+    use super::*;
+    use crate::{app_outer::load_textures, utils::Theme};
+
+    #[test]
+    fn ticks_repaint_a_fraction_of_large_boards() {
+        let ctx = egui::Context::default();
+        let glypher = Glypher::new();
+        let map_texture = load_textures(&ctx, &glypher, 0);
+        _ = GLYPHER.set(glypher);
+
+        let mut aesthetics = AestheticDepot {
+            theme: Theme::day(),
+            qs_tick: 0,
+            map_texture,
+            player_colors: vec![Color32::RED, Color32::BLUE],
+            destruction_tick: 0.05,
+            destruction_duration: 0.6,
+        };
+        let mut board = Board::new(60, 60);
+        let timing = TimingDepot::default();
+        let mut mapper = MappedBoard::new(&ctx, &aesthetics, &board, 2, 0, true);
+        let all_squares = (board.width() + 4) * (board.height() + 4);
+
+        // Nothing has changed, so nothing is painted
+        assert_eq!(
+            mapper.remap_texture(&ctx, &aesthetics, &timing, None, None, &board),
+            0
+        );
+
+        let mut repainted = vec![];
+        for tick in 1..=240 {
+            aesthetics.qs_tick = tick;
+            repainted.push(mapper.remap_texture(&ctx, &aesthetics, &timing, None, None, &board));
+        }
+        // Only the grass on diagonals where the wind changes sways, rather than the whole board
+        let most = *repainted.iter().max().unwrap();
+        assert!(
+            most * 8 < all_squares,
+            "A tick repainted {most} of {all_squares} squares"
+        );
+
+        // Placing a tile paints the tile and the terrain blending into it
+        board.set(Coordinate::new(30, 30), 0, 'A', None).unwrap();
+        assert_eq!(
+            mapper.remap_texture(&ctx, &aesthetics, &timing, None, None, &board),
+            9
+        );
+
+        // Invalidating paints everything once, and only once
+        mapper.invalidate();
+        assert_eq!(
+            mapper.remap_texture(&ctx, &aesthetics, &timing, None, None, &board),
+            all_squares
+        );
+        assert_eq!(
+            mapper.remap_texture(&ctx, &aesthetics, &timing, None, None, &board),
+            0
+        );
+    }
+}
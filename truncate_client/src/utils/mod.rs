@@ -1,11 +1,18 @@
+pub mod board_view;
+pub mod connection;
 pub mod control_devices;
+pub mod crash;
 pub mod daily;
 pub mod depot;
+pub mod embed;
 pub mod game_evals;
 pub mod glyph_utils;
 pub mod includes;
 pub mod macros;
 pub mod mapper;
+pub mod oauth;
+pub mod packs;
+pub mod perf;
 pub mod tex;
 pub mod text;
 pub mod theming;
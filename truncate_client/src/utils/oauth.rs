@@ -0,0 +1,181 @@
+use std::sync::{Mutex, RwLock};
+
+use truncate_core::messages::{OAuthProvider, PlayerMessage};
+
+/// The providers the server last said players can sign in with
+static PROVIDERS: RwLock<Vec<OAuthProvider>> = RwLock::new(Vec::new());
+/// A sign in that a provider has sent back, waiting to be passed on to the server
+static CALLBACK: Mutex<Option<SignInCallback>> = Mutex::new(None);
+
+/// What a provider sends the player back with after they've signed in
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignInCallback {
+    pub code: String,
+    pub state: String,
+}
+
+impl SignInCallback {
+    /// Reads the callback from a URL's query string, such as `code=abc&state=xyz`
+    fn from_query(query: &str) -> Option<Self> {
+        let mut code = None;
+        let mut state = None;
+        for pair in query.trim_start_matches('?').split('&') {
+            match pair.split_once('=') {
+                Some(("code", value)) => code = Some(decode_component(value)),
+                Some(("state", value)) => state = Some(decode_component(value)),
+                _ => {}
+            }
+        }
+        Some(Self {
+            code: code?,
+            state: state?,
+        })
+    }
+
+    pub fn into_message(self) -> PlayerMessage {
+        PlayerMessage::SignIn {
+            code: self.code,
+            state: self.state,
+        }
+    }
+}
+
+/// Undoes the percent-encoding of a query string value
+fn decode_component(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+pub fn set_providers(providers: Vec<OAuthProvider>) {
+    *PROVIDERS.write().unwrap() = providers;
+}
+
+pub fn providers() -> Vec<OAuthProvider> {
+    PROVIDERS.read().unwrap().clone()
+}
+
+/// Asks the server where to send the player to sign in, or `None` if
+/// there's nowhere for the provider to send them back to
+pub fn start_sign_in(provider: OAuthProvider) -> Option<PlayerMessage> {
+    Some(PlayerMessage::StartSignIn {
+        provider,
+        redirect_uri: redirect_uri()?,
+    })
+}
+
+/// Web players are sent back to the menu, which spots the callback and reopens the game
+#[cfg(target_arch = "wasm32")]
+fn redirect_uri() -> Option<String> {
+    let origin = web_sys::window()?.location().origin().ok()?;
+    Some(format!("{origin}/"))
+}
+
+/// Native players are sent back to a port on their own machine, which the game listens on
+#[cfg(not(target_arch = "wasm32"))]
+fn redirect_uri() -> Option<String> {
+    use std::sync::OnceLock;
+
+    static PORT: OnceLock<Option<u16>> = OnceLock::new();
+    let port = (*PORT.get_or_init(listen_for_callback))?;
+    Some(format!("http://127.0.0.1:{port}/"))
+}
+
+/// Answers the provider's redirect in the player's browser, holding onto the callback
+/// until the game next checks for one
+#[cfg(not(target_arch = "wasm32"))]
+fn listen_for_callback() -> Option<u16> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").ok()?;
+    let port = listener.local_addr().ok()?.port();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            // Only the request line matters, e.g. `GET /?code=abc&state=xyz HTTP/1.1`
+            let mut request_line = String::new();
+            if BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .is_err()
+            {
+                continue;
+            }
+            let callback = request_line
+                .split(' ')
+                .nth(1)
+                .and_then(|target| target.split_once('?'))
+                .and_then(|(_, query)| SignInCallback::from_query(query));
+
+            let body = if callback.is_some() {
+                "Signed in to Truncate! You can close this tab and return to the game."
+            } else {
+                "Truncate couldn't read that sign in, please try again from the game."
+            };
+            _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+
+            if callback.is_some() {
+                *CALLBACK.lock().unwrap() = callback;
+            }
+        }
+    });
+
+    Some(port)
+}
+
+/// Takes any sign in that a provider has sent the player back with
+pub fn take_callback() -> Option<SignInCallback> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        // The page's own URL only needs reading once
+        static READ_URL: AtomicBool = AtomicBool::new(false);
+        if !READ_URL.swap(true, Ordering::Relaxed) {
+            if let Some(callback) = read_url_callback() {
+                *CALLBACK.lock().unwrap() = Some(callback);
+            }
+        }
+    }
+
+    CALLBACK.lock().unwrap().take()
+}
+
+/// Reads the callback from the page's URL, then tidies it away so that a reload doesn't reuse it
+#[cfg(target_arch = "wasm32")]
+fn read_url_callback() -> Option<SignInCallback> {
+    let window = web_sys::window()?;
+    let callback = SignInCallback::from_query(&window.location().search().ok()?)?;
+
+    if let Ok(history) = window.history() {
+        let pathname = window.location().pathname().unwrap_or_else(|_| "/".into());
+        _ = history.replace_state_with_url(
+            &eframe::wasm_bindgen::JsValue::NULL,
+            "",
+            Some(&pathname),
+        );
+    }
+
+    Some(callback)
+}
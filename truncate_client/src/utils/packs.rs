@@ -0,0 +1,85 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use truncate_core::{
+    judge::WordDict,
+    packs::{DictionaryPack, PackRef},
+};
+
+/// Packs downloaded this session, by id
+static PACKS: Mutex<Option<HashMap<String, (DictionaryPack, WordDict)>>> = Mutex::new(None);
+
+fn cache_pack(pack: DictionaryPack, dictionary: WordDict) {
+    let mut packs = PACKS.lock().unwrap();
+    packs
+        .get_or_insert_with(HashMap::new)
+        .insert(pack.id.clone(), (pack, dictionary));
+}
+
+/// Checks for the referenced pack, restoring it from a previous session's download if possible
+pub fn has_pack(pack_ref: &PackRef) -> bool {
+    let cached = PACKS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|packs| packs.get(&pack_ref.id))
+        .is_some_and(|(pack, _)| pack.version == pack_ref.version);
+    if cached {
+        return true;
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+        let stored = local_storage
+            .get_item(&format!("truncate_pack_{}", pack_ref.id))
+            .ok()
+            .flatten()
+            .and_then(|stored| serde_json::from_str::<(DictionaryPack, String)>(&stored).ok());
+
+        if let Some((pack, words)) = stored {
+            if pack.version == pack_ref.version && pack.verify(&words) {
+                let dictionary = pack.dictionary(&words);
+                cache_pack(pack, dictionary);
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Keeps a downloaded pack for this session and, on the web, for later sessions
+pub fn store_pack(pack: DictionaryPack, words: String) -> Result<(), String> {
+    if !pack.verify(&words) {
+        return Err(format!(
+            "The {} dictionary didn't download correctly",
+            pack.name
+        ));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+        // Large packs can exceed the storage quota, in which case they're downloaded each session
+        if let Ok(stored) = serde_json::to_string(&(&pack, &words)) {
+            _ = local_storage.set_item(&format!("truncate_pack_{}", pack.id), &stored);
+        }
+    }
+
+    let dictionary = pack.dictionary(&words);
+    cache_pack(pack, dictionary);
+    Ok(())
+}
+
+/// Runs `f` with the referenced pack's dictionary, if it has been downloaded
+pub fn with_pack_dict<T>(pack_ref: &PackRef, f: impl FnOnce(&WordDict) -> T) -> Option<T> {
+    if !has_pack(pack_ref) {
+        return None;
+    }
+
+    let packs = PACKS.lock().unwrap();
+    packs
+        .as_ref()
+        .and_then(|packs| packs.get(&pack_ref.id))
+        .map(|(_, dictionary)| f(dictionary))
+}
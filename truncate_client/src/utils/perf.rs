@@ -0,0 +1,200 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use eframe::egui;
+use instant::Duration;
+use serde::Serialize;
+
+use crate::app_outer::{Backchannel, BackchannelMsg, ShareType};
+
+/// How many frames are kept for the overlay, and for a trace attached to a bug report
+const TRACE_LIMIT: usize = 600;
+/// How many of the latest frames the overlay summarises
+const SUMMARY_FRAMES: usize = 60;
+
+/// Set while the performance overlay is showing. Nothing is measured while it is hidden.
+static PERF_HUD: AtomicBool = AtomicBool::new(false);
+
+/// Everything measured over a single frame
+#[derive(Debug, Clone, Serialize)]
+pub struct FrameSample {
+    /// Seconds since the client started
+    pub at: f64,
+    /// CPU time spent on the previous frame, as reported by eframe
+    pub frame_ms: f32,
+    /// How many times a board's texture was repainted
+    pub board_remaps: u32,
+    /// How many squares those repaints drew
+    pub squares_repainted: u32,
+    /// Time spent on those repaints
+    pub remap_ms: f32,
+    pub round_trip_ms: Option<u64>,
+    /// Time taken by any NPC move that finished this frame
+    pub npc_ms: Option<u64>,
+}
+
+struct PerfTrace {
+    board_remaps: u32,
+    squares_repainted: u32,
+    remap_ms: f32,
+    npc_ms: Option<u64>,
+    frames: VecDeque<FrameSample>,
+}
+
+static PERF_TRACE: Mutex<PerfTrace> = Mutex::new(PerfTrace {
+    board_remaps: 0,
+    squares_repainted: 0,
+    remap_ms: 0.0,
+    npc_ms: None,
+    frames: VecDeque::new(),
+});
+
+pub fn hud_showing() -> bool {
+    PERF_HUD.load(Ordering::Relaxed)
+}
+
+/// Shows or hides the overlay, starting a fresh trace each time it is shown
+pub fn toggle_hud() {
+    let showing = !PERF_HUD.fetch_xor(true, Ordering::Relaxed);
+    if showing {
+        if let Ok(mut trace) = PERF_TRACE.lock() {
+            trace.frames.clear();
+        }
+    }
+}
+
+pub fn record_board_remap(squares_repainted: usize, took: Duration) {
+    if !hud_showing() {
+        return;
+    }
+    if let Ok(mut trace) = PERF_TRACE.lock() {
+        trace.board_remaps += 1;
+        trace.squares_repainted += squares_repainted as u32;
+        trace.remap_ms += took.as_secs_f32() * 1e3;
+    }
+}
+
+pub fn record_npc_compute(millis: u64) {
+    if !hud_showing() {
+        return;
+    }
+    if let Ok(mut trace) = PERF_TRACE.lock() {
+        trace.npc_ms = Some(millis);
+    }
+}
+
+/// Closes off everything recorded since the last frame ended
+pub fn end_frame(at: f64, frame_time: Option<f32>, round_trip_ms: Option<u64>) {
+    if !hud_showing() {
+        return;
+    }
+    let Ok(mut trace) = PERF_TRACE.lock() else {
+        return;
+    };
+
+    let sample = FrameSample {
+        at,
+        frame_ms: frame_time.unwrap_or_default() * 1e3,
+        board_remaps: std::mem::take(&mut trace.board_remaps),
+        squares_repainted: std::mem::take(&mut trace.squares_repainted),
+        remap_ms: std::mem::take(&mut trace.remap_ms),
+        round_trip_ms,
+        npc_ms: trace.npc_ms.take(),
+    };
+    if trace.frames.len() >= TRACE_LIMIT {
+        trace.frames.pop_front();
+    }
+    trace.frames.push_back(sample);
+}
+
+/// The recorded frames as JSON, for pasting into a bug report
+pub fn export_trace() -> String {
+    let frames: Vec<_> = match PERF_TRACE.lock() {
+        Ok(trace) => trace.frames.iter().cloned().collect(),
+        Err(_) => vec![],
+    };
+
+    #[derive(Serialize)]
+    struct Trace {
+        client_version: &'static str,
+        frames: Vec<FrameSample>,
+    }
+
+    serde_json::to_string(&Trace {
+        client_version: option_env!("TR_COMMIT").unwrap_or(env!("CARGO_PKG_VERSION")),
+        frames,
+    })
+    .unwrap_or_default()
+}
+
+pub fn render_hud(ctx: &egui::Context, backchannel: &Backchannel) {
+    if !hud_showing() {
+        return;
+    }
+
+    let (recent, last_npc_ms, last_round_trip_ms) = {
+        let Ok(trace) = PERF_TRACE.lock() else {
+            return;
+        };
+        let skip = trace.frames.len().saturating_sub(SUMMARY_FRAMES);
+        (
+            trace.frames.iter().skip(skip).cloned().collect::<Vec<_>>(),
+            trace.frames.iter().rev().find_map(|f| f.npc_ms),
+            trace.frames.back().and_then(|f| f.round_trip_ms),
+        )
+    };
+
+    let frame_times = recent.iter().map(|f| f.frame_ms);
+    let mean_ms = frame_times.clone().sum::<f32>() / recent.len().max(1) as f32;
+    let longest_ms = frame_times.fold(0.0, f32::max);
+    let remaps: u32 = recent.iter().map(|f| f.board_remaps).sum();
+    let squares: u32 = recent.iter().map(|f| f.squares_repainted).sum();
+    let longest_remap_ms = recent.iter().map(|f| f.remap_ms).fold(0.0, f32::max);
+
+    let mut open = true;
+    egui::Window::new("Performance")
+        .open(&mut open)
+        .default_pos(ctx.screen_rect().right_top() + egui::vec2(-240.0, 8.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "Frame time: {mean_ms:.2} ms mean, {longest_ms:.2} ms longest"
+            ));
+            ui.label(format!(
+                "Board repaints: {remaps} covering {squares} squares over {} frames",
+                recent.len()
+            ));
+            ui.label(format!(
+                "Board repaint time: {longest_remap_ms:.2} ms longest"
+            ));
+            ui.label(match last_round_trip_ms {
+                Some(ms) => format!("Round trip: {ms} ms"),
+                None => "Round trip: unmeasured".to_string(),
+            });
+            ui.label(match last_npc_ms {
+                Some(ms) => format!("NPC compute: {ms} ms"),
+                None => "NPC compute: none yet".to_string(),
+            });
+
+            if ui.button("Copy trace").clicked() {
+                let trace = export_trace();
+                if backchannel.is_open() {
+                    backchannel.send_msg(BackchannelMsg::Copy {
+                        text: trace,
+                        share: ShareType::None,
+                    });
+                } else {
+                    ui.ctx().output_mut(|o| o.copied_text = trace);
+                }
+            }
+        });
+
+    if !open {
+        toggle_hud();
+    }
+}
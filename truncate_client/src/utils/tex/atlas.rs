@@ -0,0 +1,80 @@
+use std::sync::{Mutex, OnceLock};
+
+use eframe::egui::TextureOptions;
+use epaint::{Color32, ColorImage, Hsva, TextureHandle};
+use truncate_core::season::Season;
+
+use crate::app_outer::TEXTURE_IMAGE;
+
+/// The atlas as it was loaded, kept so that seasons can be swapped in and out
+static CLASSIC_ATLAS: OnceLock<ColorImage> = OnceLock::new();
+/// The season the server last told us about, whether or not it's being shown
+static SCHEDULED_SEASON: Mutex<Option<Season>> = Mutex::new(None);
+
+/// Installs the atlas that everything is drawn from at startup
+pub fn load_classic_atlas(image: ColorImage) {
+    *TEXTURE_IMAGE.write().unwrap() = Some(image.clone());
+    _ = CLASSIC_ATLAS.set(image);
+}
+
+pub fn scheduled_season() -> Option<Season> {
+    *SCHEDULED_SEASON.lock().unwrap()
+}
+
+/// Swaps every board and tile over to the atlas for a season, or back to the classic atlas.
+/// Returns the season now being shown.
+pub fn apply_season(
+    map_texture: &mut TextureHandle,
+    season: Option<Season>,
+    classic_look: bool,
+) -> Option<Season> {
+    *SCHEDULED_SEASON.lock().unwrap() = season;
+
+    let Some(classic) = CLASSIC_ATLAS.get() else {
+        return None;
+    };
+    let shown = season.filter(|_| !classic_look);
+    let atlas = match shown {
+        Some(season) => seasonal_atlas(classic, season),
+        None => classic.clone(),
+    };
+
+    map_texture.set(atlas.clone(), TextureOptions::NEAREST);
+    *TEXTURE_IMAGE.write().unwrap() = Some(atlas);
+
+    shown
+}
+
+/// Seasons don't have hand drawn tilesets, so they recolour the classic one instead
+fn seasonal_atlas(classic: &ColorImage, season: Season) -> ColorImage {
+    let mut atlas = classic.clone();
+
+    for pixel in atlas.pixels.iter_mut() {
+        if pixel.a() == 0 {
+            continue;
+        }
+        let mut hsva = Hsva::from(*pixel);
+        let greenery = (0.2..0.45).contains(&hsva.h) && hsva.s > 0.2;
+        let water = (0.5..0.65).contains(&hsva.h) && hsva.s > 0.2;
+
+        match season {
+            Season::Winter if greenery => {
+                hsva.h = 0.55;
+                hsva.s *= 0.15;
+                hsva.v = hsva.v * 0.3 + 0.7;
+            }
+            Season::Winter if water => {
+                hsva.s *= 0.6;
+                hsva.v = (hsva.v * 1.1).min(1.0);
+            }
+            Season::Harvest if greenery => {
+                hsva.h = 0.09 + (hsva.h - 0.2) * 0.2;
+            }
+            _ => continue,
+        }
+
+        *pixel = Color32::from(hsva);
+    }
+
+    atlas
+}
@@ -9,6 +9,7 @@ use crate::{app_outer::TEXTURE_MEASUREMENT, regions::lobby::BoardEditingMode};
 
 use super::mapper::{quickrand, MappedTileVariant};
 
+pub mod atlas;
 pub mod tiles;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -17,6 +18,9 @@ pub struct Tex {
     tint: Option<Color32>,
 }
 
+/// Stands in for the theme's tile letter colour until the tile is painted
+pub const TILE_LETTER: Color32 = Color32::from_rgb(0x33, 0x33, 0x33);
+
 pub type TexQuad = [Tex; 4];
 pub type IsFlipped = bool;
 pub type YOffset = isize;
@@ -168,6 +172,16 @@ macro_rules! wind {
     };
 }
 
+/// Which set of grass a wind draws, so that changes within a set can go unpainted
+pub fn wind_band(wind: u8) -> u8 {
+    match wind {
+        calm!() => 0,
+        breeze!() => 1,
+        wind!() => 2,
+        _ => 3,
+    }
+}
+
 pub trait Tint {
     fn tint(self, color: Color32) -> Self;
 }
@@ -220,12 +234,7 @@ impl Tex {
                 tiles::quad::GAME_PIECE.tint(color.unwrap_or(Color32::WHITE)),
                 color,
             )
-            .with_piece_character(
-                character,
-                hex_color!("#333333"),
-                orientation != Direction::North,
-                -1,
-            );
+            .with_piece_character(character, TILE_LETTER, orientation != Direction::North, -1);
 
         if let Some(highlight) = highlight {
             layers =
@@ -2,11 +2,14 @@ use std::ops::Range;
 
 use eframe::egui::{self, Margin};
 use epaint::{hex_color, Color32, Hsva};
+use truncate_core::season::Season;
 
 #[derive(Debug, Clone)]
 pub struct Theme {
     pub use_old_art: bool, // TODO: Remove after art change has flushed through
     pub daytime: bool,
+    /// Whether the land and sea follow the seasonal atlas, which only the classic palettes do
+    pub seasonal: bool,
     pub water: Color32,
     pub grass: Color32,
     pub text: Color32,
@@ -22,6 +25,8 @@ pub struct Theme {
     pub word_valid: Color32,
     pub word_invalid: Color32,
     pub gold_medal: Color32,
+    /// Ink for the letters on tile faces
+    pub tile_letter: Color32,
     pub grid_size: f32,
     pub letter_size: f32,
     pub tile_margin: f32,
@@ -35,6 +40,7 @@ impl Theme {
         Self {
             use_old_art: false,
             daytime: true,
+            seasonal: true,
             water: hex_color!("#0BADFF"),
             grass: hex_color!("#7BCB69"),
             text: hex_color!("#333333"),
@@ -50,6 +56,7 @@ impl Theme {
             word_valid: hex_color!("#00A37D"),
             word_invalid: hex_color!("#89043D"),
             gold_medal: hex_color!("#E0A500"),
+            tile_letter: hex_color!("#333333"),
             grid_size: 50.0,
             letter_size: 25.0,
             tile_margin: 4.0,
@@ -63,6 +70,7 @@ impl Theme {
         Self {
             use_old_art: true,
             daytime: true,
+            seasonal: true,
             water: hex_color!("#50a7e8"),
             grass: hex_color!("#7BCB69"),
             text: hex_color!("#333333"),
@@ -78,6 +86,7 @@ impl Theme {
             word_valid: hex_color!("#00A37D"),
             word_invalid: hex_color!("#89043D"),
             gold_medal: hex_color!("#E0A500"),
+            tile_letter: hex_color!("#333333"),
             grid_size: 50.0,
             letter_size: 25.0,
             tile_margin: 4.0,
@@ -91,6 +100,7 @@ impl Theme {
         Self {
             use_old_art: false,
             daytime: true,
+            seasonal: false,
             water: hex_color!("#000000"),
             grass: hex_color!("#7BCB69"),
             text: hex_color!("#333333"),
@@ -106,6 +116,7 @@ impl Theme {
             word_valid: hex_color!("#00A37D"),
             word_invalid: hex_color!("#89043D"),
             gold_medal: hex_color!("#E0A500"),
+            tile_letter: hex_color!("#333333"),
             grid_size: 50.0,
             letter_size: 25.0,
             tile_margin: 4.0,
@@ -119,6 +130,7 @@ impl Theme {
         Self {
             use_old_art: false,
             daytime: false,
+            seasonal: false,
             water: hex_color!("#000000"),
             grass: hex_color!("#112b15"),
             text: hex_color!("#FFFFFF"),
@@ -134,6 +146,69 @@ impl Theme {
             word_valid: hex_color!("#00A37D"),
             word_invalid: hex_color!("#89043D"),
             gold_medal: hex_color!("#E0A500"),
+            tile_letter: hex_color!("#333333"),
+            grid_size: 50.0,
+            letter_size: 25.0,
+            tile_margin: 4.0,
+            rounding: 10.0,
+            animation_time: 0.05,
+            mobile_breakpoint: 800.0,
+        }
+    }
+
+    /// Dark land and sea, with light text on muted buttons
+    pub fn dark() -> Self {
+        Self {
+            use_old_art: false,
+            daytime: false,
+            seasonal: false,
+            water: hex_color!("#0B1D2E"),
+            grass: hex_color!("#2F4A35"),
+            text: hex_color!("#F2F2F2"),
+            faded: hex_color!("#9AA5B1"),
+            button_primary: hex_color!("#7A5C12"),
+            button_secondary: hex_color!("#1F4868"),
+            button_scary: hex_color!("#8A2E2E"),
+            ring_selected: hex_color!("#FFBE0B"),
+            ring_selected_hovered: hex_color!("#FFDE85"),
+            ring_hovered: hex_color!("#5FA8D3"),
+            ring_added: hex_color!("#0AFFC6"),
+            ring_modified: hex_color!("#FC3692"),
+            word_valid: hex_color!("#3DDC97"),
+            word_invalid: hex_color!("#FF6B8B"),
+            gold_medal: hex_color!("#E0A500"),
+            tile_letter: hex_color!("#1A1A1A"),
+            grid_size: 50.0,
+            letter_size: 25.0,
+            tile_margin: 4.0,
+            rounding: 10.0,
+            animation_time: 0.05,
+            mobile_breakpoint: 800.0,
+        }
+    }
+
+    /// Pure black sea and grey land, with white text, saturated highlights, and black letters on tiles
+    pub fn high_contrast() -> Self {
+        Self {
+            use_old_art: false,
+            daytime: false,
+            seasonal: false,
+            water: hex_color!("#000000"),
+            grass: hex_color!("#4D4D4D"),
+            text: hex_color!("#FFFFFF"),
+            faded: hex_color!("#D0D0D0"),
+            button_primary: hex_color!("#003B8E"),
+            button_secondary: hex_color!("#333333"),
+            button_scary: hex_color!("#B00020"),
+            ring_selected: hex_color!("#FFFF00"),
+            ring_selected_hovered: hex_color!("#FFFF99"),
+            ring_hovered: hex_color!("#00FFFF"),
+            ring_added: hex_color!("#00FF00"),
+            ring_modified: hex_color!("#FF00FF"),
+            word_valid: hex_color!("#00FF66"),
+            word_invalid: hex_color!("#FF4D4D"),
+            gold_medal: hex_color!("#FFD700"),
+            tile_letter: hex_color!("#000000"),
             grid_size: 50.0,
             letter_size: 25.0,
             tile_margin: 4.0,
@@ -145,6 +220,39 @@ impl Theme {
 }
 
 impl Theme {
+    /// Takes on every colour of another theme, keeping this theme's art and sizes
+    pub fn with_palette(self, palette: &Theme) -> Self {
+        Self {
+            use_old_art: self.use_old_art,
+            grid_size: self.grid_size,
+            letter_size: self.letter_size,
+            tile_margin: self.tile_margin,
+            rounding: self.rounding,
+            animation_time: self.animation_time,
+            mobile_breakpoint: self.mobile_breakpoint,
+            ..palette.clone()
+        }
+    }
+
+    /// Matches the land and sea to the seasonal atlas, or restores the classic palette
+    pub fn with_season(self, season: Option<Season>) -> Self {
+        if !self.seasonal {
+            return self;
+        }
+        let classic = Theme::day();
+        let (water, grass) = match season {
+            None => (classic.water, classic.grass),
+            Some(Season::Winter) => (hex_color!("#5FB4E0"), hex_color!("#E4EEF2")),
+            Some(Season::Harvest) => (classic.water, hex_color!("#D9A04A")),
+        };
+
+        Self {
+            water,
+            grass,
+            ..self
+        }
+    }
+
     pub fn calc_rescale(
         &self,
         avail_space: &egui::Rect,
@@ -186,6 +294,140 @@ impl Theme {
     }
 }
 
+/// The display preferences reported by the OS or browser
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemPreference {
+    pub dark: bool,
+    pub high_contrast: bool,
+}
+
+impl SystemPreference {
+    pub fn current(frame: &eframe::Frame) -> Self {
+        let dark = frame.info().system_theme == Some(eframe::Theme::Dark);
+
+        // Native platforms don't tell egui about contrast, so only browsers can ask for more
+        #[cfg(target_arch = "wasm32")]
+        let high_contrast = web_sys::window()
+            .and_then(|window| {
+                window
+                    .match_media("(prefers-contrast: more)")
+                    .ok()
+                    .flatten()
+            })
+            .is_some_and(|query| query.matches());
+        #[cfg(not(target_arch = "wasm32"))]
+        let high_contrast = false;
+
+        Self {
+            dark,
+            high_contrast,
+        }
+    }
+}
+
+/// The palette a player has picked in their settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThemeChoice {
+    /// Follows the OS's light, dark, and contrast preferences
+    #[default]
+    System,
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl ThemeChoice {
+    pub fn next(self) -> Self {
+        match self {
+            ThemeChoice::System => ThemeChoice::Light,
+            ThemeChoice::Light => ThemeChoice::Dark,
+            ThemeChoice::Dark => ThemeChoice::HighContrast,
+            ThemeChoice::HighContrast => ThemeChoice::System,
+        }
+    }
+
+    pub fn as_setting(self) -> &'static str {
+        match self {
+            ThemeChoice::System => "system",
+            ThemeChoice::Light => "light",
+            ThemeChoice::Dark => "dark",
+            ThemeChoice::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn from_setting(choice: &str) -> Option<Self> {
+        match choice {
+            "system" => Some(ThemeChoice::System),
+            "light" => Some(ThemeChoice::Light),
+            "dark" => Some(ThemeChoice::Dark),
+            "high_contrast" => Some(ThemeChoice::HighContrast),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeChoice::System => "THEME: SYSTEM",
+            ThemeChoice::Light => "THEME: LIGHT",
+            ThemeChoice::Dark => "THEME: DARK",
+            ThemeChoice::HighContrast => "THEME: HIGH CONTRAST",
+        }
+    }
+
+    /// The theme to play with, taking the art and sizes from `light`
+    pub fn resolve(self, light: &Theme, preference: SystemPreference) -> Theme {
+        let palette = match self {
+            ThemeChoice::Light => return light.clone(),
+            ThemeChoice::Dark => Theme::dark(),
+            ThemeChoice::HighContrast => Theme::high_contrast(),
+            ThemeChoice::System if preference.high_contrast => Theme::high_contrast(),
+            ThemeChoice::System if preference.dark => Theme::dark(),
+            ThemeChoice::System => return light.clone(),
+        };
+        light.clone().with_palette(&palette)
+    }
+
+    fn memory_id() -> egui::Id {
+        egui::Id::new("truncate_theme_choice")
+    }
+
+    /// The choice in effect, which settings menus anywhere in the app can change
+    pub fn current(ctx: &egui::Context) -> Self {
+        ctx.memory(|mem| mem.data.get_temp(Self::memory_id()))
+            .unwrap_or_default()
+    }
+
+    /// Puts this choice into effect, and remembers it for next time on the web
+    pub fn store(self, ctx: &egui::Context) {
+        ctx.memory_mut(|mem| mem.data.insert_temp(Self::memory_id(), self));
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            local_storage
+                .set_item("truncate_theme", self.as_setting())
+                .unwrap();
+        }
+    }
+
+    /// The choice saved from an earlier session, if any
+    pub fn load() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            if let Some(choice) = local_storage
+                .get_item("truncate_theme")
+                .unwrap()
+                .and_then(|choice| Self::from_setting(&choice))
+            {
+                return choice;
+            }
+        }
+
+        Self::default()
+    }
+}
+
 pub trait Diaphanize {
     fn diaphanize(&self) -> Self;
 }
@@ -1,5 +1,26 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use instant::Duration;
 
+/// Set while nothing on screen needs the regular quarter-second tick,
+/// letting the animation loop repaint less often to save power.
+static LOW_POWER: AtomicBool = AtomicBool::new(false);
+
 pub fn get_qs_tick(current_time: Duration) -> u64 {
     current_time.as_secs() * 4 + current_time.subsec_millis() as u64 / 250
 }
+
+pub fn set_low_power(engaged: bool) {
+    LOW_POWER.store(engaged, Ordering::Relaxed);
+}
+
+pub fn low_power_engaged() -> bool {
+    LOW_POWER.load(Ordering::Relaxed)
+}
+
+/// Milliseconds until the animation loop should next repaint. This lines up with the
+/// quarter-second tick, or with the whole second in low power mode so timers still tick over.
+pub fn until_next_repaint(current_time: Duration) -> u64 {
+    let period = if low_power_engaged() { 1000 } else { 250 };
+    period - (current_time.subsec_millis() as u64 % period)
+}
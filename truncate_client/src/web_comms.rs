@@ -11,7 +11,7 @@ use truncate_core::messages::{GameMessage, Nonce, NoncedPlayerMessage, PlayerMes
 use web_sys::console;
 use ws_stream_wasm::{WsMessage, WsMeta, WsStream};
 
-use crate::utils::macros::current_time;
+use crate::utils::{crash, macros::current_time};
 
 async fn websocket_connect(connect_addr: &String) -> Result<WsStream, ()> {
     console::log_1(&format!("Connecting to {connect_addr}").into());
@@ -57,6 +57,8 @@ pub async fn connect(
     };
 
     let mut outgoing_msg_stream = rx_player.map(|message| {
+        crash::record_sent(&message);
+
         // Store a token that we're interacting with, in case we need to
         // recreate the connection.
         if let PlayerMessage::RejoinGame(token) = &message {
@@ -71,6 +73,7 @@ pub async fn connect(
             // Avoid noncing pings since we don't care about any individual ping.
             // Avoid noncing pre-login methods, as nonces don't work if the player is not logged in.
             PlayerMessage::Ping
+            | PlayerMessage::LatencyPing(_)
             | PlayerMessage::Login { .. }
             | PlayerMessage::CreateAnonymousPlayer { .. } => (
                 None,
@@ -131,10 +134,16 @@ pub async fn connect(
                         serde_json::from_slice(&msg).expect("Was not valid JSON")
                     }
                 };
+                crash::record_received(&parsed_msg);
 
                 match &parsed_msg {
                     GameMessage::Ping => {
                         _ = tx_player.clone().send(PlayerMessage::Ping).await;
+                        let sent_at = current_time!().as_millis() as u64;
+                        _ = tx_player
+                            .clone()
+                            .send(PlayerMessage::LatencyPing(sent_at))
+                            .await;
                     }
                     GameMessage::PleaseLogin => {
                         requested_login.store(true, std::sync::atomic::Ordering::Relaxed);
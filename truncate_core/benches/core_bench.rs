@@ -1,8 +1,13 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use std::collections::HashMap;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use truncate_core::{
     bag::TileBag,
-    board::{Board, Coordinate},
+    board::{Board, Coordinate, Square},
     game::Game,
     judge::{Judge, WordData, WordDict},
     npc::{scoring::NPCParams, Caches},
@@ -10,11 +15,50 @@ use truncate_core::{
     rules::{self, GameRules},
 };
 
+/// Keeps a running total of the live allocations and the bytes they hold,
+/// so that the dictionary benches can report memory as well as time
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Builds something, along with how many allocations and bytes it still holds once built
+fn held_memory<T>(build: impl FnOnce() -> T) -> (T, usize, usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED.load(Ordering::Relaxed);
+    let built = build();
+    (
+        built,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED.load(Ordering::Relaxed) - bytes,
+    )
+}
+
 pub static TESTING_DICT: &str = include_str!("../../dict_builder/final_wordlist.txt");
 
 /// Build an (expensive) word dictionary using the real game data.
 fn dict() -> WordDict {
-    let mut valid_words = HashMap::new();
+    let mut valid_words = WordDict::new();
     let lines = TESTING_DICT.lines();
 
     for line in lines {
@@ -218,6 +262,47 @@ pub fn board_benches(c: &mut Criterion) {
     c.bench_function("get_word_strings", |b| {
         b.iter(|| board.word_strings(&coords))
     });
+
+    // A full 20x20 board, tiled in blocks of each player's five letter words
+    let mut scanned = Board::new(20, 20);
+    let land: Vec<_> = (0..scanned.height())
+        .flat_map(|y| (0..scanned.width()).map(move |x| Coordinate { x, y }))
+        .filter(|c| matches!(scanned.get(*c), Ok(Square::Land { .. })))
+        .collect();
+    for c in &land {
+        scanned.set(*c, (c.x / 5 + c.y / 5) % 2, 'A', None).unwrap();
+    }
+    let mut indexed = scanned.clone();
+    indexed.index_words();
+
+    c.bench_function("get_all_words_20x20", |b| {
+        b.iter(|| {
+            land.iter()
+                .for_each(|c| _ = black_box(scanned.get_words(*c)))
+        })
+    });
+
+    c.bench_function("get_all_words_20x20_indexed", |b| {
+        b.iter(|| {
+            land.iter()
+                .for_each(|c| _ = black_box(indexed.get_words(*c)))
+        })
+    });
+
+    let middle = Coordinate { x: 10, y: 10 };
+    c.bench_function("replace_tile_20x20", |b| {
+        b.iter(|| {
+            scanned.clear(middle, None);
+            scanned.set(middle, 1, 'A', None)
+        })
+    });
+
+    c.bench_function("replace_tile_20x20_indexed", |b| {
+        b.iter(|| {
+            indexed.clear(middle, None);
+            indexed.set(middle, 1, 'A', None)
+        })
+    });
 }
 
 pub fn judge_benches(c: &mut Criterion) {
@@ -257,5 +342,54 @@ pub fn judge_benches(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, npc_benches, board_benches, judge_benches);
+pub fn dictionary_benches(c: &mut Criterion) {
+    c.bench_function("dictionary_build", |b| b.iter(dict));
+
+    let (dict, dict_allocations, dict_bytes) = held_memory(dict);
+    // The same words keyed by a string each, as dictionaries were stored before `WordDict`
+    let (map, map_allocations, map_bytes) = held_memory(|| {
+        let mut map = HashMap::new();
+        for (word, data) in dict.iter() {
+            map.insert(word.to_string(), data.clone());
+        }
+        map
+    });
+    assert_eq!(map.len(), dict.len());
+    let mib = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+    println!(
+        "dictionary_memory: {} words held in {} allocations of {:.2} MiB as a WordDict, \
+         {} allocations of {:.2} MiB as a HashMap<String, WordData>",
+        dict.len(),
+        dict_allocations,
+        mib(dict_bytes),
+        map_allocations,
+        mib(map_bytes)
+    );
+    drop(map);
+
+    // A spread of hits and misses, as the judge and NPC would see them
+    let words: Vec<_> = dict
+        .keys()
+        .step_by(97)
+        .map(str::to_string)
+        .flat_map(|word| [format!("{word}q"), word])
+        .collect();
+
+    c.bench_function("dictionary_lookups", |b| {
+        b.iter(|| {
+            words
+                .iter()
+                .filter(|word| dict.contains_key(black_box(word)))
+                .count()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    npc_benches,
+    board_benches,
+    judge_benches,
+    dictionary_benches
+);
 criterion_main!(benches);
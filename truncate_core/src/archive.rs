@@ -0,0 +1,698 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    game::Game,
+    generation::{generate_board, BoardSeed},
+    judge::{Outcome, WordDict},
+    moves::{
+        packing::{pack_moves_separately, unpack_moves},
+        Move,
+    },
+    replay::Replay,
+    reporting::{BattleReport, BattleWord, Change},
+    rules::GameRules,
+};
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ArchiveError {
+    #[error("Malformed tag on line {line}")]
+    MalformedTag { line: usize },
+    #[error("Unrecognised move {token:?}")]
+    MalformedMove { token: String },
+    #[error("Unterminated annotation")]
+    UnterminatedAnnotation,
+    #[error("Result {result:?} should be a player number, \"draw\" or \"*\"")]
+    MalformedResult { result: String },
+    #[error("The archive has no {name} tag")]
+    MissingTag { name: &'static str },
+    #[error("The seed couldn't generate a board")]
+    UnplayableSeed,
+    #[error("The seed generates a {generated} board rather than {expected}")]
+    BoardMismatch { expected: String, generated: String },
+}
+
+/// How an archived game finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveResult {
+    Winner(usize),
+    Draw,
+    Unfinished,
+}
+
+impl fmt::Display for ArchiveResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveResult::Winner(player) => write!(f, "{player}"),
+            ArchiveResult::Draw => write!(f, "draw"),
+            ArchiveResult::Unfinished => write!(f, "*"),
+        }
+    }
+}
+
+impl FromStr for ArchiveResult {
+    type Err = ArchiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draw" => Ok(ArchiveResult::Draw),
+            "*" => Ok(ArchiveResult::Unfinished),
+            _ => s
+                .parse()
+                .map(ArchiveResult::Winner)
+                .map_err(|_| ArchiveError::MalformedResult {
+                    result: s.to_string(),
+                }),
+        }
+    }
+}
+
+/// A plain text record of a game, modelled after chess PGN.
+///
+/// ```text
+/// [Event "Daily Puzzle"]
+/// [Date "2024-10-16"]
+/// [Player0 "You"]
+/// [Player1 "Computer"]
+/// [Rules "2"]
+/// [Seed "0:1844"]
+/// [Result "0"]
+///
+/// 1. [0]1203A 2. 11B {Guarding the town} 3. <11/1009>
+/// ```
+///
+/// Moves use the packed notation from `moves::packing`, with a turn number
+/// before each and an optional `{annotation}` after. Any tags without a
+/// dedicated field are kept in `tags` so that they survive a round trip.
+///
+/// Rules are written as their generation where they match one, and in full as JSON
+/// otherwise, so games under custom rules are replayed under the rules they were played with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameArchive {
+    pub event: String,
+    pub date: Option<String>,
+    pub players: Vec<String>,
+    pub rules: Option<GameRules>,
+    /// The board generation and seed the game was played on
+    pub seed: Option<(u32, u32)>,
+    pub result: ArchiveResult,
+    pub tags: BTreeMap<String, String>,
+    pub moves: Vec<Move>,
+    /// Comments keyed by the index of the move they follow
+    pub annotations: BTreeMap<usize, String>,
+}
+
+impl GameArchive {
+    pub fn new(event: String, players: Vec<String>, moves: Vec<Move>) -> Self {
+        Self {
+            event,
+            date: None,
+            players,
+            rules: None,
+            seed: None,
+            result: ArchiveResult::Unfinished,
+            tags: BTreeMap::new(),
+            moves,
+            annotations: BTreeMap::new(),
+        }
+    }
+
+    /// Records a game played on a seeded board, noting the battles each move caused
+    pub fn from_game(
+        event: String,
+        game: &Game,
+        seed: &BoardSeed,
+        moves: &[Move],
+        dictionary: Option<&WordDict>,
+    ) -> Self {
+        let players = game.players.iter().map(|p| p.name.clone()).collect();
+        let mut archive = GameArchive::new(event, players, moves.to_vec());
+        archive.rules = Some(game.rules.clone());
+        archive.seed = Some((seed.generation, seed.seed));
+        archive.tags.insert(
+            "Board".to_string(),
+            format!("{}x{}", game.board.width(), game.board.height()),
+        );
+
+        let replay = Replay::from_game(game.clone(), moves.to_vec(), dictionary);
+        for (index, replayed) in replay.enumerate() {
+            // Moves that can't be played are still recorded, but have no battles to report
+            let Ok((_, changes)) = replayed else {
+                continue;
+            };
+            let battles: Vec<_> = changes
+                .iter()
+                .filter_map(|change| match change {
+                    Change::Battle(battle) => Some(describe_battle(battle)),
+                    _ => None,
+                })
+                .collect();
+            if !battles.is_empty() {
+                archive.annotations.insert(index, battles.join("; "));
+            }
+        }
+
+        archive
+    }
+
+    /// Sets up the game before its first move, regenerating its board from the seed
+    pub fn to_game(&self) -> Result<(Game, BoardSeed), ArchiveError> {
+        // Replaying under any other rules could play out a different game
+        let rules = self
+            .rules
+            .clone()
+            .ok_or(ArchiveError::MissingTag { name: "Rules" })?;
+        let (generation, seed) = self.seed.ok_or(ArchiveError::MissingTag { name: "Seed" })?;
+        let seed = BoardSeed::new_with_generation(generation, seed);
+
+        let mut board = generate_board(seed.clone())
+            .map_err(|_| ArchiveError::UnplayableSeed)?
+            .board;
+        board.cache_special_squares();
+
+        // Changes to board generation would otherwise replay the moves onto the wrong board
+        let generated = format!("{}x{}", board.width(), board.height());
+        if let Some(expected) = self.tags.get("Board") {
+            if *expected != generated {
+                return Err(ArchiveError::BoardMismatch {
+                    expected: expected.clone(),
+                    generated,
+                });
+            }
+        }
+
+        let mut game = Game::new(board.width(), board.height(), Some(seed.seed as u64), rules);
+        for name in &self.players {
+            game.add_player(name.clone());
+        }
+        game.board = board;
+
+        Ok((game, seed))
+    }
+
+    /// Reads every game from a file of archives separated by blank lines
+    pub fn parse_many(s: &str) -> Result<Vec<Self>, ArchiveError> {
+        let mut games = vec![];
+        let mut current = String::new();
+
+        for line in s.trim().lines() {
+            // A tag after some movetext marks the start of the next game
+            if Self::parse_tag(line.trim()).is_some() && Self::has_movetext(&current) {
+                games.push(current.parse()?);
+                current.clear();
+            }
+            current.push_str(line);
+            current.push('\n');
+        }
+        if !current.trim().is_empty() {
+            games.push(current.parse()?);
+        }
+
+        Ok(games)
+    }
+
+    fn has_movetext(s: &str) -> bool {
+        s.lines()
+            .any(|l| !l.trim().is_empty() && Self::parse_tag(l.trim()).is_none())
+    }
+
+    /// Turn order is inferred from the listed players, so the writer and reader must agree on this
+    fn player_count(&self) -> usize {
+        self.players.len().max(1)
+    }
+
+    pub(crate) fn parse_tag(line: &str) -> Option<(String, String)> {
+        let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+        let (name, value) = inner.split_once(' ')?;
+        let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+        let mut unescaped = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                unescaped.push(chars.next()?);
+            } else {
+                unescaped.push(c);
+            }
+        }
+
+        Some((name.to_string(), unescaped))
+    }
+}
+
+pub(crate) fn write_tag(f: &mut impl fmt::Write, name: &str, value: &str) -> fmt::Result {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    writeln!(f, "[{name} \"{escaped}\"]")
+}
+
+fn write_rules(rules: &GameRules) -> String {
+    match rules.generation {
+        Some(generation) if GameRules::checked_generation(generation).as_ref() == Some(rules) => {
+            generation.to_string()
+        }
+        _ => serde_json::to_string(rules).expect("Rules should be serializable"),
+    }
+}
+
+fn read_rules(value: &str) -> Option<GameRules> {
+    match value.parse() {
+        Ok(generation) => GameRules::checked_generation(generation),
+        Err(_) => serde_json::from_str(value).ok(),
+    }
+}
+
+fn describe_battle(battle: &BattleReport) -> String {
+    let words = |words: &[BattleWord]| {
+        words
+            .iter()
+            .map(|w| w.resolved_word.to_uppercase())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let outcome = match battle.outcome {
+        Outcome::AttackerWins(_) => "beat",
+        Outcome::DefenderWins => "lost to",
+    };
+    format!(
+        "{} {outcome} {}",
+        words(&battle.attackers),
+        words(&battle.defenders)
+    )
+}
+
+impl fmt::Display for GameArchive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_tag(f, "Event", &self.event)?;
+        if let Some(date) = &self.date {
+            write_tag(f, "Date", date)?;
+        }
+        for (index, player) in self.players.iter().enumerate() {
+            write_tag(f, &format!("Player{index}"), player)?;
+        }
+        if let Some(rules) = &self.rules {
+            write_tag(f, "Rules", &write_rules(rules))?;
+        }
+        if let Some((generation, seed)) = self.seed {
+            write_tag(f, "Seed", &format!("{generation}:{seed}"))?;
+        }
+        write_tag(f, "Result", &self.result.to_string())?;
+        for (name, value) in &self.tags {
+            write_tag(f, name, value)?;
+        }
+        writeln!(f)?;
+
+        let movetext: Vec<_> = pack_moves_separately(&self.moves, self.player_count())
+            .into_iter()
+            .enumerate()
+            .map(|(i, packed)| match self.annotations.get(&i) {
+                // Braces would end the annotation early, so they can't be kept
+                Some(note) => format!("{}. {packed} {{{}}}", i + 1, note.replace('}', ")")),
+                None => format!("{}. {packed}", i + 1),
+            })
+            .collect();
+        writeln!(f, "{}", movetext.join(" "))
+    }
+}
+
+impl FromStr for GameArchive {
+    type Err = ArchiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut archive = GameArchive::new(String::new(), vec![], vec![]);
+        let mut players = BTreeMap::new();
+        let mut movetext = String::new();
+        let mut in_movetext = false;
+
+        for (line_number, line) in s.trim_start().lines().enumerate() {
+            let line = line.trim();
+            // Everything after the tags is movetext, even if it looks like a tag
+            if in_movetext || !line.starts_with('[') {
+                in_movetext = true;
+                movetext.push_str(line);
+                movetext.push(' ');
+                continue;
+            }
+
+            let malformed = || ArchiveError::MalformedTag {
+                line: line_number + 1,
+            };
+            let (name, value) = Self::parse_tag(line).ok_or_else(malformed)?;
+
+            match name.as_str() {
+                "Event" => archive.event = value,
+                "Date" => archive.date = Some(value),
+                "Rules" => archive.rules = Some(read_rules(&value).ok_or_else(malformed)?),
+                "Seed" => {
+                    let (generation, seed) = value.split_once(':').ok_or_else(malformed)?;
+                    archive.seed = Some((
+                        generation.parse().map_err(|_| malformed())?,
+                        seed.parse().map_err(|_| malformed())?,
+                    ));
+                }
+                "Result" => archive.result = value.parse()?,
+                _ => match name.strip_prefix("Player").map(str::parse::<usize>) {
+                    Some(Ok(index)) => {
+                        players.insert(index, value);
+                    }
+                    _ => {
+                        archive.tags.insert(name, value);
+                    }
+                },
+            }
+        }
+        archive.players = players.into_values().collect();
+
+        // Split the movetext into packed moves, dropping turn numbers
+        // and attaching annotations to the move before them
+        let mut packed_moves: Vec<String> = vec![];
+        let mut turn_numbers = 0;
+        let mut awaiting_move = false;
+        let mut rest = movetext.as_str();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+
+            if let Some(annotated) = rest.strip_prefix('{') {
+                let (note, remainder) = annotated
+                    .split_once('}')
+                    .ok_or(ArchiveError::UnterminatedAnnotation)?;
+                rest = remainder;
+                // Older archives packed exchanges in braces, so braces where a move
+                // belongs are the move itself rather than an annotation
+                if awaiting_move {
+                    packed_moves.push(format!("{{{note}}}"));
+                    awaiting_move = false;
+                } else if let Some(index) = packed_moves.len().checked_sub(1) {
+                    archive.annotations.insert(index, note.trim().to_string());
+                }
+                continue;
+            }
+
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '{')
+                .unwrap_or(rest.len());
+            let (token, remainder) = rest.split_at(end);
+            rest = remainder;
+
+            let is_turn_number =
+                token.ends_with('.') && token[..token.len() - 1].chars().all(|c| c.is_numeric());
+            if is_turn_number {
+                if awaiting_move {
+                    return Err(ArchiveError::MalformedMove {
+                        token: token.to_string(),
+                    });
+                }
+                turn_numbers += 1;
+                awaiting_move = true;
+            } else {
+                packed_moves.push(token.to_string());
+                awaiting_move = false;
+            }
+        }
+
+        // Players are only marked where they change out of turn order,
+        // so the moves have to be unpacked together
+        archive.moves =
+            unpack_moves(&packed_moves.concat(), archive.player_count()).map_err(|_| {
+                ArchiveError::MalformedMove {
+                    token: packed_moves.join(" "),
+                }
+            })?;
+
+        // Each token should have produced exactly one move, and each numbered turn should have one
+        let numbered_moves_missing = turn_numbers > 0 && turn_numbers != archive.moves.len();
+        if archive.moves.len() != packed_moves.len() || numbered_moves_missing {
+            return Err(ArchiveError::MalformedMove {
+                token: packed_moves.join(" "),
+            });
+        }
+
+        Ok(archive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bag::BLANK, board::Coordinate};
+
+    fn sample() -> GameArchive {
+        let mut archive = GameArchive::new(
+            "Daily Puzzle".into(),
+            vec!["You".into(), "Computer \"Jet\"".into()],
+            vec![
+                Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x: 12, y: 3 },
+                },
+                Move::Place {
+                    player: 1,
+                    tile: 'B',
+                    position: Coordinate { x: 1, y: 1 },
+                },
+                Move::Swap {
+                    player: 0,
+                    positions: [Coordinate { x: 1, y: 1 }, Coordinate { x: 10, y: 9 }],
+                },
+                Move::Place {
+                    player: 0,
+                    tile: 'R',
+                    position: Coordinate { x: 3, y: 3 },
+                },
+            ],
+        );
+        archive.date = Some("2024-10-16".into());
+        archive.rules = Some(GameRules::generation(2));
+        archive.seed = Some((0, 1844));
+        archive.result = ArchiveResult::Winner(0);
+        archive.tags.insert("Day".into(), "293".into());
+        archive.annotations.insert(1, "Guarding the town".into());
+        archive
+    }
+
+    #[test]
+    fn writes_archives() {
+        assert_eq!(
+            sample().to_string(),
+            [
+                "[Event \"Daily Puzzle\"]",
+                "[Date \"2024-10-16\"]",
+                "[Player0 \"You\"]",
+                "[Player1 \"Computer \\\"Jet\\\"\"]",
+                "[Rules \"2\"]",
+                "[Seed \"0:1844\"]",
+                "[Result \"0\"]",
+                "[Day \"293\"]",
+                "",
+                "1. [0]1203A 2. 11B {Guarding the town} 3. <11/1009> 4. [0]33R",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn round_trips_archives() {
+        let archive = sample();
+        assert_eq!(archive.to_string().parse(), Ok(archive.clone()));
+
+        let mut other = GameArchive::new("Truncate".into(), vec![], vec![]);
+        other.result = ArchiveResult::Draw;
+        let both = format!("{archive}\n{other}");
+        assert_eq!(GameArchive::parse_many(&both), Ok(vec![archive, other]));
+    }
+
+    #[test]
+    fn round_trips_exchanges() {
+        let mut archive = sample();
+        archive.moves.insert(
+            1,
+            Move::Exchange {
+                player: 1,
+                tile: 'B',
+            },
+        );
+        archive.annotations.clear();
+        archive.annotations.insert(1, "Fishing for a vowel".into());
+
+        let written = archive.to_string();
+        assert!(written.contains("2. ^B {Fishing for a vowel} 3. [1]11B"));
+        assert_eq!(written.parse(), Ok(archive));
+    }
+
+    #[test]
+    fn rejects_bad_archives() {
+        assert_eq!(
+            "[Event Truncate]\n\n".parse::<GameArchive>(),
+            Err(ArchiveError::MalformedTag { line: 1 })
+        );
+        assert_eq!(
+            "[Result \"1\"]\n\n1. [0]1203A {Unclosed".parse::<GameArchive>(),
+            Err(ArchiveError::UnterminatedAnnotation)
+        );
+        assert!(matches!(
+            "[Result \"1\"]\n\n1. [0]12x03A".parse::<GameArchive>(),
+            Err(ArchiveError::MalformedMove { .. })
+        ));
+        assert!(matches!(
+            "[Result \"1\"]\n\n1. [0]1203A 2. 3. 11B".parse::<GameArchive>(),
+            Err(ArchiveError::MalformedMove { .. })
+        ));
+    }
+
+    #[test]
+    fn reads_braced_exchanges_as_moves() {
+        let archive: GameArchive =
+            "[Player0 \"You\"]\n[Player1 \"Computer\"]\n\n1. [0]1203A 2. {B} {Fishing} 3. [1]11B"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            archive.moves[1],
+            Move::Exchange {
+                player: 1,
+                tile: 'B'
+            }
+        );
+        assert_eq!(archive.moves.len(), 3);
+        assert_eq!(
+            archive.annotations.get(&1).map(String::as_str),
+            Some("Fishing")
+        );
+    }
+
+    fn seeded_game() -> (Game, BoardSeed, Vec<Move>) {
+        let seed = BoardSeed::new_with_generation(0, 1844);
+        let mut board = generate_board(seed.clone()).unwrap().board;
+        board.cache_special_squares();
+
+        let mut game = Game::new(
+            board.width(),
+            board.height(),
+            Some(seed.seed as u64),
+            GameRules::generation(2),
+        );
+        game.add_player("You".into());
+        game.add_player("Computer".into());
+        game.board = board;
+
+        let moves = vec![
+            Move::Mulligan { player: 1 },
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 4, y: 12 },
+            },
+            Move::Swap {
+                player: 1,
+                positions: [Coordinate { x: 1, y: 1 }, Coordinate { x: 10, y: 9 }],
+            },
+            Move::Place {
+                player: 0,
+                tile: BLANK,
+                position: Coordinate { x: 5, y: 12 },
+            },
+            Move::Exchange {
+                player: 1,
+                tile: 'Q',
+            },
+        ];
+
+        (game, seed, moves)
+    }
+
+    #[test]
+    fn round_trips_games() {
+        let (game, seed, moves) = seeded_game();
+        let written = GameArchive::from_game("Truncate".into(), &game, &seed, &moves, None);
+        assert!(written
+            .to_string()
+            .contains("[Rules \"2\"]\n[Seed \"0:1844\"]"));
+
+        let read: GameArchive = written.to_string().parse().unwrap();
+        assert_eq!(read.moves, moves);
+        let (read_game, read_seed) = read.to_game().unwrap();
+        assert_eq!(read_seed.seed, 1844);
+        assert_eq!(read_game.board, game.board);
+        assert_eq!(read_game.rules, game.rules);
+        assert_eq!(read_game.players[1].name, "Computer");
+        assert_eq!(read_game.players[0].hand, game.players[0].hand);
+    }
+
+    #[test]
+    fn round_trips_custom_rules() {
+        let (mut game, seed, moves) = seeded_game();
+        game.rules.hand_size = 9;
+        let written = GameArchive::from_game("Truncate".into(), &game, &seed, &moves, None);
+        assert!(!written.to_string().contains("[Rules \"2\"]"));
+
+        let read: GameArchive = written.to_string().parse().unwrap();
+        assert_eq!(read.to_game().unwrap().0.rules, game.rules);
+    }
+
+    #[test]
+    fn notes_battles() {
+        let mut bag = crate::bag::tests::trivial_bag();
+        let game = Game {
+            board: crate::board::Board::from_string(
+                "~~ |0 ~~\n\
+                 __ __ __\n\
+                 __ __ __\n\
+                 __ __ __\n\
+                 ~~ |1 ~~",
+            ),
+            players: vec![
+                crate::player::Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+                crate::player::Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+            ],
+            bag,
+            player_turn_count: vec![0, 0],
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        let place = |player, y| Move::Place {
+            player,
+            tile: 'A',
+            position: Coordinate { x: 1, y },
+        };
+        let moves = vec![place(0, 1), place(1, 3), place(0, 2)];
+
+        let archive =
+            GameArchive::from_game("Truncate".into(), &game, &BoardSeed::new(1), &moves, None);
+        assert_eq!(
+            archive.annotations,
+            BTreeMap::from([(2, "AA lost to A, A".to_string())])
+        );
+    }
+
+    #[test]
+    fn refuses_to_guess_how_games_were_played() {
+        let (game, seed, moves) = seeded_game();
+        let written = GameArchive::from_game("Truncate".into(), &game, &seed, &moves, None);
+
+        let mut without_rules = written.clone();
+        without_rules.rules = None;
+        let read: GameArchive = without_rules.to_string().parse().unwrap();
+        assert_eq!(
+            read.to_game().err(),
+            Some(ArchiveError::MissingTag { name: "Rules" })
+        );
+
+        let mut without_seed = written.clone();
+        without_seed.seed = None;
+        assert_eq!(
+            without_seed.to_game().err(),
+            Some(ArchiveError::MissingTag { name: "Seed" })
+        );
+
+        let mut resized = written;
+        resized.tags.insert("Board".into(), "1x1".into());
+        assert!(matches!(
+            resized.to_game(),
+            Err(ArchiveError::BoardMismatch { .. })
+        ));
+    }
+}
@@ -1,7 +1,11 @@
-use oorandom::Rand32;
-use std::fmt;
+use std::{collections::HashMap, fmt, ops::RangeInclusive};
 
-use crate::rules;
+use crate::{
+    judge::WordDict,
+    reporting::{BagChange, BagEvent},
+    rng::TruncateRng,
+    rules::Language,
+};
 
 /*
 INFO: Letter distributions in Truncate's dict
@@ -36,7 +40,10 @@ z: | 1.15   | 0.80   | 0.65
 
 */
 
-const TILE_GENERATIONS: [[usize; 26]; 2] = [
+/// How many of each letter, from A to Z, go into a bag
+pub type TileDistribution = [usize; 26];
+
+const TILE_GENERATIONS: [TileDistribution; 2] = [
     [
         13, // a
         3,  // b
@@ -95,11 +102,201 @@ const TILE_GENERATIONS: [[usize; 26]; 2] = [
     ],
 ];
 
+/// How many of each letter go into an English bag of the given generation
+pub fn generation_distribution(gen: u32) -> Option<TileDistribution> {
+    TILE_GENERATIONS.get(gen as usize).copied()
+}
+
+/// A tile that shows more than one letter, such as the Spanish CH, but plays as a single letter.
+/// Boards, hands, and dictionaries all hold it as its stand-in character.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Digraph {
+    pub tile: char,
+    pub letters: &'static str,
+}
+
+pub const DIGRAPHS: [Digraph; 3] = [
+    Digraph {
+        tile: '\u{E000}',
+        letters: "CH",
+    },
+    Digraph {
+        tile: '\u{E001}',
+        letters: "LL",
+    },
+    Digraph {
+        tile: '\u{E002}',
+        letters: "RR",
+    },
+];
+
+/// The letters a tile shows, when it shows more than one
+pub fn digraph_letters(tile: char) -> Option<&'static str> {
+    DIGRAPHS
+        .iter()
+        .find(|digraph| digraph.tile == tile)
+        .map(|digraph| digraph.letters)
+}
+
+/// Writes out a word made of tiles as the letters the tiles show, for displaying to players
+pub fn spell_tiles(word: &str) -> String {
+    word.chars()
+        .map(|c| match digraph_letters(c) {
+            Some(letters) => letters.to_string(),
+            None => c.to_uppercase().to_string(),
+        })
+        .collect()
+}
+
+/// A blank tile, which can be played as any letter.
+/// Blanks played without a letter stay wild, and are judged as whichever letter makes their words valid.
+pub const BLANK: char = '*';
+
+/// The tile a blank becomes when it is played as `letter`.
+/// It is kept in lowercase, so that it's judged as that letter but can be told apart from a drawn one.
+/// Returns `None` for tiles that don't have a lowercase, such as digraphs.
+pub fn blank_as(letter: char) -> Option<char> {
+    let mut lowercase = letter.to_lowercase();
+    match (lowercase.next(), lowercase.next()) {
+        (Some(blank), None) if blank != letter => Some(blank),
+        _ => None,
+    }
+}
+
+/// Whether a tile on the board started out as a blank
+pub fn is_blank(tile: char) -> bool {
+    tile == BLANK || tile.is_lowercase()
+}
+
+/// The tile that leaves a player's hand when they place `tile`
+pub fn hand_tile(tile: char) -> char {
+    if is_blank(tile) {
+        BLANK
+    } else {
+        tile
+    }
+}
+
+/// The tiles that go into a bag for each language other than English,
+/// which draws from `TILE_GENERATIONS` instead. Based on each language's Scrabble set, less the blanks.
+const LANGUAGE_TILES: [(Language, &[(char, usize)]); 3] = [
+    (
+        Language::Spanish,
+        &[
+            ('A', 12),
+            ('B', 2),
+            ('C', 4),
+            ('\u{E000}', 1), // CH
+            ('D', 5),
+            ('E', 12),
+            ('F', 1),
+            ('G', 2),
+            ('H', 2),
+            ('I', 6),
+            ('J', 1),
+            ('L', 4),
+            ('\u{E001}', 1), // LL
+            ('M', 2),
+            ('N', 5),
+            ('Ñ', 1),
+            ('O', 9),
+            ('P', 2),
+            ('Q', 1),
+            ('R', 5),
+            ('\u{E002}', 1), // RR
+            ('S', 6),
+            ('T', 4),
+            ('U', 5),
+            ('V', 1),
+            ('X', 1),
+            ('Y', 1),
+            ('Z', 1),
+        ],
+    ),
+    (
+        Language::French,
+        &[
+            ('A', 9),
+            ('B', 2),
+            ('C', 2),
+            ('D', 3),
+            ('E', 15),
+            ('F', 2),
+            ('G', 2),
+            ('H', 2),
+            ('I', 8),
+            ('J', 1),
+            ('K', 1),
+            ('L', 5),
+            ('M', 3),
+            ('N', 6),
+            ('O', 6),
+            ('P', 2),
+            ('Q', 1),
+            ('R', 6),
+            ('S', 6),
+            ('T', 6),
+            ('U', 6),
+            ('V', 2),
+            ('W', 1),
+            ('X', 1),
+            ('Y', 1),
+            ('Z', 1),
+        ],
+    ),
+    (
+        Language::German,
+        &[
+            ('A', 5),
+            ('Ä', 1),
+            ('B', 2),
+            ('C', 2),
+            ('D', 4),
+            ('E', 15),
+            ('F', 2),
+            ('G', 3),
+            ('H', 4),
+            ('I', 6),
+            ('J', 1),
+            ('K', 2),
+            ('L', 3),
+            ('M', 4),
+            ('N', 9),
+            ('O', 3),
+            ('Ö', 1),
+            ('P', 1),
+            ('Q', 1),
+            ('R', 6),
+            ('S', 7),
+            ('T', 6),
+            ('U', 6),
+            ('Ü', 1),
+            ('V', 1),
+            ('W', 1),
+            ('X', 1),
+            ('Y', 1),
+            ('Z', 1),
+        ],
+    ),
+];
+
+/// Every tile a language's bag can hold
+pub fn language_tiles(language: Language) -> Vec<char> {
+    match LANGUAGE_TILES.iter().find(|(l, _)| *l == language) {
+        Some((_, tiles)) => tiles.iter().map(|(tile, _)| *tile).collect(),
+        None => ('A'..='Z').collect(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TileBag {
     bag: Vec<char>,
-    rng: Rand32,
-    letter_distribution: Option<[usize; 26]>,
+    rng: TruncateRng,
+    /// How many of each tile the bag is filled with, if it refills at all
+    letter_distribution: Option<Vec<(char, usize)>>,
+    /// What has happened to the bag since the game last reported on it,
+    /// alongside how many tiles were left afterwards
+    events: Vec<(BagEvent, usize)>,
 }
 
 impl TileBag {
@@ -113,65 +310,260 @@ impl TileBag {
         )
     }
 
+    /// A bag of the language's letters. English bags use the given generation of tiles.
+    pub fn language(language: Language, gen: u32, seed: Option<u64>) -> Self {
+        match LANGUAGE_TILES.iter().find(|(l, _)| *l == language) {
+            Some((_, tiles)) => TileBag::from_tiles(tiles.to_vec(), seed),
+            None => TileBag::generation(gen, seed),
+        }
+    }
+
     pub fn latest(seed: Option<u64>) -> (u32, Self) {
         assert!(!TILE_GENERATIONS.is_empty());
         let generation = (TILE_GENERATIONS.len() - 1) as u32;
         (generation, TileBag::generation(generation as u32, seed))
     }
 
-    pub fn custom(letter_distribution: [usize; 26], seed: Option<u64>) -> Self {
+    pub fn custom(letter_distribution: TileDistribution, seed: Option<u64>) -> Self {
+        TileBag::from_tiles(
+            letter_distribution
+                .iter()
+                .enumerate()
+                .map(|(letter, count)| (((letter as u8) + 65) as char, *count))
+                .collect(),
+            seed,
+        )
+    }
+
+    fn from_tiles(letter_distribution: Vec<(char, usize)>, seed: Option<u64>) -> Self {
         let mut tile_bag = TileBag {
             bag: Vec::new(),
-            rng: Rand32::new(seed.unwrap_or_else(|| {
-                instant::SystemTime::now()
-                    .duration_since(instant::SystemTime::UNIX_EPOCH)
-                    .expect("Please don't play Truncate earlier than 1970")
-                    .as_secs()
-            })),
+            rng: Self::rng_for(seed),
             letter_distribution: Some(letter_distribution),
+            events: Vec::new(),
         };
         tile_bag.fill();
         tile_bag
     }
 
+    /// Adds `count` blanks to the bag, which it also refills with
+    pub fn with_blanks(mut self, count: usize) -> Self {
+        if count == 0 {
+            return self;
+        }
+
+        self.bag.extend([BLANK].repeat(count));
+        if let Some(letter_distribution) = &mut self.letter_distribution {
+            letter_distribution.push((BLANK, count));
+        }
+        self
+    }
+
     pub fn explicit(tiles: Vec<char>, seed: Option<u64>) -> Self {
         TileBag {
             bag: tiles,
-            rng: Rand32::new(seed.unwrap_or_else(|| {
-                instant::SystemTime::now()
-                    .duration_since(instant::SystemTime::UNIX_EPOCH)
-                    .expect("Please don't play Truncate earlier than 1970")
-                    .as_secs()
-            })),
+            rng: Self::rng_for(seed),
             letter_distribution: None,
+            events: Vec::new(),
         }
     }
 
+    fn rng_for(seed: Option<u64>) -> TruncateRng {
+        seed.map(TruncateRng::new)
+            .unwrap_or_else(TruncateRng::unseeded)
+    }
+
+    /// The seed the bag draws from, which is also the seed of the game it belongs to
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
     pub fn draw_tile(&mut self) -> char {
         if self.bag.is_empty() {
             self.fill();
+            if !self.bag.is_empty() {
+                self.events.push((BagEvent::Reshuffled, self.bag.len()));
+            }
         }
         let index = self.rng.rand_range(0..self.bag.len() as u32);
-        self.bag.swap_remove(index as usize)
+        let tile = self.bag.swap_remove(index as usize);
+
+        match self.events.last_mut() {
+            Some((BagEvent::Drawn { tiles }, remaining)) => {
+                *tiles += 1;
+                *remaining = self.bag.len();
+            }
+            _ => self
+                .events
+                .push((BagEvent::Drawn { tiles: 1 }, self.bag.len())),
+        }
+        if self.bag.is_empty() {
+            self.events.push((
+                BagEvent::Exhausted {
+                    refills: self.letter_distribution.is_some(),
+                },
+                0,
+            ));
+        }
+
+        tile
+    }
+
+    /// Reports everything that has happened to the bag since the last call,
+    /// attributed to the player who was drawing
+    pub fn take_changes(&mut self, player: usize) -> Vec<BagChange> {
+        self.events
+            .drain(..)
+            .map(|(event, remaining)| BagChange {
+                player,
+                event,
+                remaining,
+            })
+            .collect()
+    }
+
+    /// Tiles still waiting to be drawn from the bag
+    pub fn remaining_tiles(&self) -> &[char] {
+        &self.bag
+    }
+
+    /// Takes a particular tile out of the bag, if there is one left
+    pub fn take_tile(&mut self, c: char) -> bool {
+        match self.bag.iter().position(|t| *t == c) {
+            Some(index) => {
+                self.bag.swap_remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// How likely each tile is to be drawn from the bag when full,
+    /// or from what's left in bags that never refill
+    pub fn tile_odds(&self) -> HashMap<char, f64> {
+        let counts: Vec<(char, usize)> = match &self.letter_distribution {
+            Some(letter_distribution) => letter_distribution.clone(),
+            None => {
+                let mut counts: HashMap<char, usize> = HashMap::new();
+                for tile in &self.bag {
+                    *counts.entry(*tile).or_default() += 1;
+                }
+                counts.into_iter().collect()
+            }
+        };
+
+        let total: usize = counts.iter().map(|(_, count)| count).sum();
+        if total == 0 {
+            return HashMap::new();
+        }
+        counts
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(tile, count)| (tile, count as f64 / total as f64))
+            .collect()
+    }
+
+    /// Whether the bag fills back up once it runs out
+    pub fn refills(&self) -> bool {
+        self.letter_distribution.is_some()
     }
 
     // TODO: this doesn't stop us from returning tiles that weren't originally in the bag
     pub fn return_tile(&mut self, c: char) {
-        self.bag.push(c);
+        // Blanks go back as blanks, whatever letter they were played as
+        self.bag.push(hand_tile(c));
     }
 
     fn fill(&mut self) {
-        if let Some(letter_distribution) = self.letter_distribution {
+        if let Some(letter_distribution) = &self.letter_distribution {
             self.bag.extend(
                 letter_distribution
                     .iter()
-                    .enumerate()
-                    .flat_map(|(letter, count)| [((letter as u8) + 65) as char].repeat(*count)),
+                    .flat_map(|(tile, count)| [*tile].repeat(*count)),
             );
         }
     }
 }
 
+/// Whether a hand holds a vowel, counting blanks since they can be played as one
+pub fn has_vowel(hand: &[char]) -> bool {
+    hand.iter()
+        .any(|&tile| matches!(tile, 'A' | 'E' | 'I' | 'O' | 'U' | BLANK))
+}
+
+/// How far a hand strays from the odds of drawing each tile from the bag, in bits.
+/// This is the divergence of the hand's tiles from the bag's, so fairly drawn hands sit low,
+/// while hands of repeated or rare tiles score highly.
+pub fn hand_divergence(hand: &[char], odds: &HashMap<char, f64>) -> f64 {
+    if hand.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for tile in hand {
+        *counts.entry(*tile).or_default() += 1;
+    }
+
+    counts
+        .iter()
+        .map(|(tile, count)| {
+            let in_hand = *count as f64 / hand.len() as f64;
+            // Tiles the bag never holds, such as those handed out by the game, count as very rare
+            let in_bag = odds
+                .get(tile)
+                .copied()
+                .unwrap_or(MIN_TILE_ODDS)
+                .max(MIN_TILE_ODDS);
+            in_hand * (in_hand / in_bag).log2()
+        })
+        .sum()
+}
+
+/// The floor on any tile's odds of being drawn, so that a single odd tile can't dominate a hand's divergence
+const MIN_TILE_ODDS: f64 = 0.001;
+
+/// Builds a bag of `bag_size` tiles for a dictionary, with each letter appearing in proportion
+/// to how often it is used across the words with a length in `word_lengths`.
+/// Every letter that's used at all gets at least one tile, and letters outside of A-Z are ignored.
+pub fn balanced_distribution(
+    dict: &WordDict,
+    word_lengths: RangeInclusive<usize>,
+    bag_size: usize,
+) -> TileDistribution {
+    let mut usage = [0_usize; 26];
+    for word in dict.keys() {
+        if !word_lengths.contains(&word.chars().count()) {
+            continue;
+        }
+        for c in word.chars().filter(char::is_ascii_alphabetic) {
+            usage[(c.to_ascii_lowercase() as u8 - b'a') as usize] += 1;
+        }
+    }
+
+    let mut distribution = usage.map(|count| count.min(1));
+    let used_letters: usize = distribution.iter().sum();
+    let total_usage: usize = usage.iter().sum();
+    if total_usage == 0 || bag_size <= used_letters {
+        return distribution;
+    }
+
+    // Share out the rest of the bag by largest remainder, so it adds up to exactly `bag_size`
+    let to_share = bag_size - used_letters;
+    let mut remainders = Vec::with_capacity(26);
+    for (letter, count) in usage.iter().enumerate() {
+        let share = (to_share * count) as f64 / total_usage as f64;
+        distribution[letter] += share.floor() as usize;
+        remainders.push((share.fract(), letter));
+    }
+    let shared: usize = distribution.iter().sum();
+    remainders.sort_by(|(a, a_letter), (b, b_letter)| b.total_cmp(a).then(a_letter.cmp(b_letter)));
+    for (_, letter) in remainders.into_iter().take(bag_size - shared) {
+        distribution[letter] += 1;
+    }
+
+    distribution
+}
+
 impl PartialEq for TileBag {
     fn eq(&self, rhs: &Self) -> bool {
         self.bag == rhs.bag && self.letter_distribution == rhs.letter_distribution
@@ -196,6 +588,72 @@ pub mod tests {
         assert_eq!(drawn.filter(|&x| x == 'A').count(), 5);
     }
 
+    #[test]
+    fn reports_draws_and_refills() {
+        let mut bag = a_b_bag();
+        bag.draw_tile();
+        assert_eq!(
+            bag.take_changes(0),
+            vec![BagChange {
+                player: 0,
+                event: BagEvent::Drawn { tiles: 1 },
+                remaining: 1
+            }]
+        );
+
+        bag.draw_tile();
+        bag.draw_tile();
+        let events: Vec<_> = bag
+            .take_changes(1)
+            .into_iter()
+            .map(|c| (c.event, c.remaining))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (BagEvent::Drawn { tiles: 1 }, 0),
+                (BagEvent::Exhausted { refills: true }, 0),
+                (BagEvent::Reshuffled, 2),
+                (BagEvent::Drawn { tiles: 1 }, 1),
+            ]
+        );
+        assert!(bag.take_changes(1).is_empty());
+
+        let mut bag = TileBag::explicit(vec!['A', 'B'], Some(1));
+        bag.draw_tile();
+        bag.draw_tile();
+        let events: Vec<_> = bag.take_changes(0).into_iter().map(|c| c.event).collect();
+        assert_eq!(
+            events,
+            vec![
+                BagEvent::Drawn { tiles: 2 },
+                BagEvent::Exhausted { refills: false }
+            ]
+        );
+    }
+
+    #[test]
+    fn blanks_are_drawn_and_spelt() {
+        let mut bag = a_b_bag().with_blanks(2);
+        assert_eq!(bag.remaining_tiles().len(), 4);
+        let drawn: Vec<_> = (0..8).map(|_| bag.draw_tile()).collect();
+        assert_eq!(
+            drawn.iter().filter(|t| **t == BLANK).count(),
+            4,
+            "Blanks should come back when the bag refills"
+        );
+
+        assert_eq!(blank_as('E'), Some('e'));
+        assert_eq!(blank_as('Ñ'), Some('ñ'));
+        assert_eq!(blank_as('\u{E000}'), None);
+        assert_eq!(blank_as(BLANK), None);
+
+        assert!(is_blank('e') && is_blank(BLANK) && !is_blank('E'));
+        assert_eq!(hand_tile('e'), BLANK);
+        assert_eq!(hand_tile('E'), 'E');
+        assert_eq!(spell_tiles("Ba*\u{E000}"), "BA*CH");
+    }
+
     // Util functions
     pub fn a_b_bag() -> TileBag {
         let mut dist = [0; 26];
@@ -209,4 +667,119 @@ pub mod tests {
         dist[0] = 1;
         TileBag::custom(dist, Some(12345))
     }
+
+    #[test]
+    fn balances_to_dictionary() {
+        let dict: WordDict = ["eel", "tee", "ate", "tea", "zebra"]
+            .iter()
+            .map(|w| {
+                (
+                    w.to_string(),
+                    crate::judge::WordData {
+                        extensions: 0,
+                        rel_freq: 1.0,
+                        objectionable: false,
+                    },
+                )
+            })
+            .collect();
+        let count =
+            |distribution: &TileDistribution, c: char| distribution[(c as u8 - b'a') as usize];
+
+        // 6 E, 3 T, 2 A and 1 L across the short words
+        let short = balanced_distribution(&dict, 2..=3, 20);
+        assert_eq!(short.iter().sum::<usize>(), 20);
+        assert_eq!(
+            ['e', 't', 'a', 'l', 'z'].map(|c| count(&short, c)),
+            [9, 5, 4, 2, 0]
+        );
+
+        // Rare letters still make it into a small bag
+        let all = balanced_distribution(&dict, 2..=5, 8);
+        assert_eq!(all.iter().sum::<usize>(), 8);
+        assert_eq!(count(&all, 'e'), 2);
+        assert_eq!(count(&all, 'z'), 1);
+    }
+
+    #[test]
+    fn odd_hands_diverge_from_the_bag() {
+        let odds = TileBag::generation(0, Some(1)).tile_odds();
+        let total: f64 = odds.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        let fair = hand_divergence(&['E', 'T', 'A', 'R', 'S', 'N', 'O'], &odds);
+        let repeated = hand_divergence(&['E'; 7], &odds);
+        let rare = hand_divergence(&['Q', 'Z', 'X', 'J', 'K', 'V', 'W'], &odds);
+        assert!(fair < repeated);
+        assert!(fair < rare);
+
+        assert!(has_vowel(&['T', 'R', 'E']));
+        assert!(has_vowel(&['T', 'R', BLANK]));
+        assert!(!has_vowel(&['T', 'R', 'S']));
+    }
+
+    #[test]
+    fn same_seed_same_draws() {
+        let draws = |seed| {
+            let mut bag = TileBag::latest(Some(seed)).1;
+            (0..30).map(|_| bag.draw_tile()).collect::<String>()
+        };
+        assert_eq!(draws(42), draws(42));
+        assert_ne!(draws(42), draws(43));
+    }
+
+    #[test]
+    fn bags_hold_a_languages_letters() {
+        let draws = |language| {
+            let mut bag = TileBag::language(language, 1, Some(42));
+            let size = bag.remaining_tiles().len();
+            (0..size).map(|_| bag.draw_tile()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(draws(Language::English), {
+            let mut bag = TileBag::generation(1, Some(42));
+            (0..150).map(|_| bag.draw_tile()).collect::<Vec<_>>()
+        });
+
+        let spanish = draws(Language::Spanish);
+        assert_eq!(spanish.len(), 98);
+        assert_eq!(spanish.iter().filter(|t| **t == 'Ñ').count(), 1);
+        assert_eq!(spanish.iter().filter(|t| **t == '\u{E000}').count(), 1);
+        assert!(spanish
+            .iter()
+            .all(|t| language_tiles(Language::Spanish).contains(t)));
+
+        let german = draws(Language::German);
+        assert!(german.contains(&'Ü'));
+        assert!(!german.iter().any(|t| digraph_letters(*t).is_some()));
+    }
+
+    #[test]
+    fn spells_out_digraphs() {
+        assert_eq!(spell_tiles("\u{E000}ICO"), "CHICO");
+        assert_eq!(spell_tiles("CA\u{E001}E"), "CALLE");
+        assert_eq!(spell_tiles("NIÑO"), "NIÑO");
+    }
+
+    #[test]
+    fn switching_language_redeals_hands() {
+        use crate::{game::Game, rules::GameRules};
+
+        let mut game = Game::new(9, 9, Some(7), GameRules::generation(2));
+        game.add_player("A".into());
+        game.add_player("B".into());
+
+        game.set_language(Language::German);
+        assert_eq!(game.rules.language, Language::German);
+        let german = language_tiles(Language::German);
+        for player in &game.players {
+            assert_eq!(player.hand.len(), 7);
+            assert!(player.hand.iter().all(|t| german.contains(t)));
+        }
+
+        // Nothing changes once the game is underway
+        game.start();
+        game.set_language(Language::Spanish);
+        assert_eq!(game.rules.language, Language::German);
+    }
 }
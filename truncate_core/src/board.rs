@@ -1,16 +1,16 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::array::IntoIter;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::iter::{FilterMap, Flatten};
 use std::ops::Sub;
 use std::slice::Iter;
 
 use super::reporting::{BoardChange, BoardChangeAction, BoardChangeDetail};
-use crate::bag::TileBag;
+use crate::bag::{TileBag, BLANK};
 use crate::error::GamePlayError;
-use crate::judge::WordDict;
+use crate::judge::{Judge, WordDict};
 use crate::reporting::Change;
 use crate::rules::{ArtifactDefense, BoardOrientation, GameRules, WinCondition};
 use crate::{player, rules};
@@ -29,12 +29,12 @@ pub enum Direction {
 
 impl Direction {
     // Returns whether vertical words should be read from top to bottom if played by a player on this side of the board
-    fn read_top_to_bottom(self) -> bool {
+    pub(crate) fn read_top_to_bottom(self) -> bool {
         matches!(self, Direction::South) || matches!(self, Direction::West)
     }
 
     // Returns whether horizontal words should be read from left to right if played by a player on this side of the board
-    fn read_left_to_right(self) -> bool {
+    pub(crate) fn read_left_to_right(self) -> bool {
         matches!(self, Direction::South) || matches!(self, Direction::East)
     }
 
@@ -61,16 +61,363 @@ struct RedundantEdges {
     left: usize,
 }
 
+/// The playable part of a board, without the water around it or any artifacts docked out in that water
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Land {
+    /// Where the top left corner of the land sits on the board
+    pub origin: Coordinate,
+    pub width: usize,
+    pub height: usize,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Board {
     pub squares: Vec<Vec<Square>>,
     pub artifacts: Vec<Coordinate>,
     pub towns: Vec<Coordinate>,
     pub obelisks: Vec<Coordinate>,
+    /// Only tracked for games played with word aging
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aging: Option<WordAges>,
+    /// Only tracked for games played on elevated terrain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevation: Option<Elevation>,
+    /// Only tracked for games played with withering truncation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withering: Option<Withering>,
+    /// Only tracked once `index_words` is called, such as on boards the NPC searches
+    #[serde(skip)]
+    word_index: Option<WordIndex>,
+    /// Tracked alongside the word index, so that searches can hash boards without rescanning them
+    #[serde(skip)]
+    zobrist: Option<ZobristHash>,
     pub orientations: Vec<Direction>, // The side of the board that the player is sitting at, and the direction that their vertical words go in
                                       // TODO: Move orientations off the Board and have them tagged against specific players
 }
 
+/// When each square last changed, for rules where words weaken as they sit unchanged
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct WordAges {
+    /// The turn the game is currently on
+    pub turn: u32,
+    pub grace_turns: u32,
+    modified_turns: Vec<Vec<u32>>,
+}
+
+impl WordAges {
+    pub fn new(width: usize, height: usize, grace_turns: u32) -> Self {
+        Self {
+            turn: 0,
+            grace_turns,
+            modified_turns: vec![vec![0; width]; height],
+        }
+    }
+
+    pub fn mark_modified(&mut self, position: Coordinate) {
+        if let Some(turn) = self
+            .modified_turns
+            .get_mut(position.y)
+            .and_then(|row| row.get_mut(position.x))
+        {
+            *turn = self.turn;
+        }
+    }
+
+    /// How many turns it has been since any tile in the word changed
+    pub fn age(&self, word: &[Coordinate]) -> u32 {
+        let modified = word
+            .iter()
+            .filter_map(|c| self.modified_turns.get(c.y)?.get(c.x))
+            .max()
+            .copied()
+            .unwrap_or_default();
+        self.turn.saturating_sub(modified)
+    }
+
+    /// How many letters shorter the word defends as, having outlived its grace period
+    pub fn weakness(&self, word: &[Coordinate]) -> usize {
+        self.age(word).saturating_sub(self.grace_turns) as usize
+    }
+
+    fn trim(&mut self, trim: &RedundantEdges) {
+        self.modified_turns.drain(..trim.top);
+        self.modified_turns
+            .truncate(self.modified_turns.len().saturating_sub(trim.bottom));
+        for row in &mut self.modified_turns {
+            row.drain(..trim.left);
+            row.truncate(row.len().saturating_sub(trim.right));
+        }
+    }
+
+    fn rotate(&mut self) {
+        self.modified_turns.reverse();
+        self.modified_turns.iter_mut().for_each(|row| row.reverse());
+    }
+}
+
+/// Which squares sit on high ground, for rules where words built on hills defend more strongly
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Elevation {
+    high_ground: Vec<Vec<bool>>,
+}
+
+impl Elevation {
+    pub fn new(high_ground: Vec<Vec<bool>>) -> Self {
+        Self { high_ground }
+    }
+
+    pub fn is_high(&self, position: Coordinate) -> bool {
+        self.high_ground
+            .get(position.y)
+            .and_then(|row| row.get(position.x))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// How many letters longer the word defends as, having a tile on high ground
+    pub fn strength(&self, word: &[Coordinate]) -> usize {
+        if word.iter().any(|c| self.is_high(*c)) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn trim(&mut self, trim: &RedundantEdges) {
+        self.high_ground.drain(..trim.top);
+        self.high_ground
+            .truncate(self.high_ground.len().saturating_sub(trim.bottom));
+        for row in &mut self.high_ground {
+            row.drain(..trim.left);
+            row.truncate(row.len().saturating_sub(trim.right));
+        }
+    }
+
+    fn grow(&mut self) {
+        for row in &mut self.high_ground {
+            row.insert(0, false);
+            row.push(false);
+        }
+        let width = self.high_ground.first().map(Vec::len).unwrap_or_default();
+        self.high_ground.insert(0, vec![false; width]);
+        self.high_ground.push(vec![false; width]);
+    }
+
+    fn rotate(&mut self) {
+        self.high_ground.reverse();
+        self.high_ground.iter_mut().for_each(|row| row.reverse());
+    }
+}
+
+/// Which tiles are cut off from their artifact, for rules where they wither for a turn before being truncated
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Withering {
+    withering: Vec<Vec<bool>>,
+}
+
+impl Withering {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            withering: vec![vec![false; width]; height],
+        }
+    }
+
+    pub fn is_withering(&self, position: Coordinate) -> bool {
+        self.withering
+            .get(position.y)
+            .and_then(|row| row.get(position.x))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn set(&mut self, position: Coordinate, withering: bool) {
+        if let Some(square) = self
+            .withering
+            .get_mut(position.y)
+            .and_then(|row| row.get_mut(position.x))
+        {
+            *square = withering;
+        }
+    }
+
+    fn trim(&mut self, trim: &RedundantEdges) {
+        self.withering.drain(..trim.top);
+        self.withering
+            .truncate(self.withering.len().saturating_sub(trim.bottom));
+        for row in &mut self.withering {
+            row.drain(..trim.left);
+            row.truncate(row.len().saturating_sub(trim.right));
+        }
+    }
+
+    fn grow(&mut self) {
+        for row in &mut self.withering {
+            row.insert(0, false);
+            row.push(false);
+        }
+        let width = self.withering.first().map(Vec::len).unwrap_or_default();
+        self.withering.insert(0, vec![false; width]);
+        self.withering.push(vec![false; width]);
+    }
+
+    fn rotate(&mut self) {
+        self.withering.reverse();
+        self.withering.iter_mut().for_each(|row| row.reverse());
+    }
+}
+
+/// A running `Board::zobrist` hash, updated from the keys of each square as it changes
+#[derive(Clone, Copy, Debug)]
+struct ZobristHash(u64);
+
+/// The hash only caches the squares, so never makes two boards differ
+impl PartialEq for ZobristHash {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ZobristHash {}
+
+/// The run of tiles that each tile sits in down and across the board, so that
+/// `get_words` can read off a tile's words without scanning for where they end
+#[derive(Clone, Debug)]
+struct WordIndex {
+    width: usize,
+    /// For each square, the first and last row of its vertical run,
+    /// then the first and last column of its horizontal run
+    runs: Vec<[[u16; 2]; 2]>,
+}
+
+/// The index only caches the squares, so never makes two boards differ
+impl PartialEq for WordIndex {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for WordIndex {}
+
+impl WordIndex {
+    const VERTICAL: usize = 0;
+    const HORIZONTAL: usize = 1;
+
+    fn new(squares: &[Vec<Square>]) -> Self {
+        let height = squares.len();
+        let width = squares.first().map(Vec::len).unwrap_or_default();
+        let mut index = Self {
+            width,
+            runs: vec![[[0; 2]; 2]; width * height],
+        };
+
+        for x in 0..width {
+            index.rescan(
+                squares,
+                Coordinate { x, y: 0 },
+                Self::VERTICAL,
+                0,
+                height.saturating_sub(1),
+            );
+        }
+        for y in 0..height {
+            index.rescan(
+                squares,
+                Coordinate { x: 0, y },
+                Self::HORIZONTAL,
+                0,
+                width.saturating_sub(1),
+            );
+        }
+
+        index
+    }
+
+    fn owner(squares: &[Vec<Square>], position: Coordinate) -> Option<usize> {
+        match squares.get(position.y).and_then(|row| row.get(position.x)) {
+            Some(Square::Occupied { player, .. }) => Some(*player),
+            _ => None,
+        }
+    }
+
+    /// The square `along` the line running through `position` on the given axis
+    fn along(position: Coordinate, axis: usize, along: usize) -> Coordinate {
+        if axis == Self::VERTICAL {
+            Coordinate {
+                x: position.x,
+                y: along,
+            }
+        } else {
+            Coordinate {
+                x: along,
+                y: position.y,
+            }
+        }
+    }
+
+    fn run(&self, position: Coordinate, axis: usize) -> [usize; 2] {
+        self.runs[position.to_1d(self.width)][axis].map(usize::from)
+    }
+
+    /// Reindexes the runs through a square that has just changed.
+    /// Only the runs either side of it can have joined or split, so nothing else is rescanned.
+    fn update(&mut self, squares: &[Vec<Square>], position: Coordinate) {
+        for axis in [Self::VERTICAL, Self::HORIZONTAL] {
+            let (along, len) = if axis == Self::VERTICAL {
+                (position.y, squares.len())
+            } else {
+                (position.x, self.width)
+            };
+
+            let mut first = along;
+            if along > 0 {
+                let before = Self::along(position, axis, along - 1);
+                if Self::owner(squares, before).is_some() {
+                    first = self.run(before, axis)[0];
+                }
+            }
+            let mut last = along;
+            if along + 1 < len {
+                let after = Self::along(position, axis, along + 1);
+                if Self::owner(squares, after).is_some() {
+                    last = self.run(after, axis)[1];
+                }
+            }
+
+            self.rescan(squares, position, axis, first, last);
+        }
+    }
+
+    /// Records the runs of each player's tiles between two squares on a line
+    fn rescan(
+        &mut self,
+        squares: &[Vec<Square>],
+        position: Coordinate,
+        axis: usize,
+        first: usize,
+        last: usize,
+    ) {
+        let mut start = first;
+        for along in first..=last {
+            let owner = Self::owner(squares, Self::along(position, axis, along));
+            if owner.is_none() {
+                start = along + 1;
+                continue;
+            }
+
+            let next_owner = (along < last)
+                .then(|| Self::owner(squares, Self::along(position, axis, along + 1)))
+                .flatten();
+            if next_owner != owner {
+                for tile in start..=along {
+                    let index = Self::along(position, axis, tile).to_1d(self.width);
+                    self.runs[index][axis] = [start as u16, along as u16];
+                }
+                start = along + 1;
+            }
+        }
+    }
+}
+
 // TODO: provide a way to validate the board
 //  - the empty squares are fully connected
 //  - there are at least 2 roots
@@ -97,37 +444,35 @@ impl Board {
             towns: vec![],
             obelisks: vec![],
             orientations: vec![Direction::North, Direction::South],
+            aging: None,
+            elevation: None,
+            withering: None,
+            word_index: None,
+            zobrist: None,
         };
 
         let north_towns = [
             Coordinate::new(board_width - 4, 1),
             Coordinate::new(board_width - 2, 3),
         ];
-        for town in north_towns {
-            board
-                .set_square(town, Square::town(0))
-                .expect("Town square should exist");
-        }
-        // North artifact
-        board
-            .set_square(Coordinate::new(board_width - 2, 1), Square::artifact(0))
-            .expect("Artifact square should exist");
-
         let south_towns = [
             Coordinate::new(1, board_height - 4),
             Coordinate::new(3, board_height - 2),
         ];
-        for town in south_towns {
-            board
-                .set_square(town, Square::town(1))
-                .expect("Town square should exist");
-        }
-        // South artifact
-        board
-            .set_square(Coordinate::new(1, board_height - 2), Square::artifact(1))
-            .expect("Artifact square should exist");
+        let setup = north_towns
+            .into_iter()
+            .map(|town| (town, Square::town(0)))
+            // North artifact
+            .chain([(Coordinate::new(board_width - 2, 1), Square::artifact(0))])
+            .chain(south_towns.into_iter().map(|town| (town, Square::town(1))))
+            // South artifact
+            .chain([(Coordinate::new(1, board_height - 2), Square::artifact(1))])
+            .collect();
 
-        board.cache_special_squares();
+        // Also caches the towns and artifacts
+        board
+            .apply_batch(setup)
+            .expect("Town and artifact squares should exist");
 
         board
     }
@@ -152,52 +497,55 @@ impl Board {
             towns: vec![],
             obelisks: vec![],
             orientations: vec![Direction::North, Direction::South],
+            aging: None,
+            elevation: None,
+            withering: None,
+            word_index: None,
+            zobrist: None,
         };
 
         let artifact_x = board_width / 2;
 
         let north_towns = (1..=land_width)
             .filter(|x| *x != artifact_x)
-            .map(|x| Coordinate { x, y: 1 });
-        for town in north_towns {
-            board
-                .set_square(town, Square::town(0))
-                .expect("Town square should exist on the land");
-        }
+            .map(|x| (Coordinate { x, y: 1 }, Square::town(0)));
         // North artifact
-        board
-            .set_square(
+        let north_artifact = (
+            Coordinate {
+                x: artifact_x,
+                y: 0,
+            },
+            Square::artifact(0),
+        );
+
+        let south_towns = (1..=land_width).filter(|x| *x != artifact_x).map(|x| {
+            (
                 Coordinate {
-                    x: artifact_x,
-                    y: 0,
+                    x,
+                    y: board_height - 2,
                 },
-                Square::artifact(0),
+                Square::town(1),
             )
-            .expect("Artifact square should exist in the sea");
-
-        let south_towns = (1..=land_width)
-            .filter(|x| *x != artifact_x)
-            .map(|x| Coordinate {
-                x,
-                y: board_height - 2,
-            });
-        for town in south_towns {
-            board
-                .set_square(town, Square::town(1))
-                .expect("Town square should exist on the land");
-        }
+        });
         // South artifact
+        let south_artifact = (
+            Coordinate {
+                x: artifact_x,
+                y: board_height - 1,
+            },
+            Square::artifact(1),
+        );
+
+        // Also caches the towns and artifacts
         board
-            .set_square(
-                Coordinate {
-                    x: artifact_x,
-                    y: board_height - 1,
-                },
-                Square::artifact(1),
+            .apply_batch(
+                north_towns
+                    .chain([north_artifact])
+                    .chain(south_towns)
+                    .chain([south_artifact])
+                    .collect(),
             )
-            .expect("Artifact square should exist in the sea");
-
-        board.cache_special_squares();
+            .expect("Town and artifact squares should exist");
 
         board
     }
@@ -206,12 +554,44 @@ impl Board {
         &self.orientations
     }
 
+    /// Where the land sits on the board, or `None` if there's nothing but water.
+    /// Legacy boards dock their artifacts out in the water rows beyond the land,
+    /// so the land is found from the squares rather than assumed to sit inside one ring of water,
+    /// which keeps boards saved in either shape reading the same.
+    pub fn land(&self) -> Option<Land> {
+        let on_land = |s: &Square| {
+            !matches!(
+                s,
+                Square::Water { .. } | Square::Fog { .. } | Square::Artifact { .. }
+            )
+        };
+
+        let top = self
+            .squares
+            .iter()
+            .position(|row| row.iter().any(on_land))?;
+        let bottom = self
+            .squares
+            .iter()
+            .rposition(|row| row.iter().any(on_land))?;
+        let left =
+            (0..self.width()).position(|x| self.squares.iter().any(|row| on_land(&row[x])))?;
+        let right =
+            (0..self.width()).rposition(|x| self.squares.iter().any(|row| on_land(&row[x])))?;
+
+        Some(Land {
+            origin: Coordinate::new(left, top),
+            width: right - left + 1,
+            height: bottom - top + 1,
+        })
+    }
+
     pub fn land_width(&self) -> usize {
-        unimplemented!("Need to calculate the playable dimensions")
+        self.land().map(|land| land.width).unwrap_or_default()
     }
 
     pub fn land_height(&self) -> usize {
-        unimplemented!("Need to calculate the playable dimensions")
+        self.land().map(|land| land.height).unwrap_or_default()
     }
 
     pub fn width(&self) -> usize {
@@ -239,50 +619,32 @@ impl Board {
 
         self.squares.insert(0, vec![Square::water(); self.width()]);
         self.squares.push(vec![Square::water(); self.width()]);
+        if let Some(elevation) = &mut self.elevation {
+            elevation.grow();
+        }
+        if let Some(withering) = &mut self.withering {
+            withering.grow();
+        }
 
         self.cache_special_squares();
     }
 
-    /// Returns the number of rows/columns
+    /// Returns the number of rows/columns outside the ring of water around the land
     fn redundant_edges(&self) -> RedundantEdges {
-        let redundant = |s: &Square| {
-            matches!(
-                s,
-                Square::Water { .. } | Square::Fog { .. } | Square::Artifact { .. }
-            )
+        let Some(land) = self.land() else {
+            return RedundantEdges {
+                top: 0,
+                right: 0,
+                bottom: 0,
+                left: 0,
+            };
         };
 
-        let top = self
-            .squares
-            .iter()
-            .position(|row| row.iter().any(|s| !redundant(s)))
-            .unwrap_or_default()
-            .saturating_sub(1);
-
-        let bottom = self
-            .squares
-            .iter()
-            .rev()
-            .position(|row| row.iter().any(|s| !redundant(s)))
-            .unwrap_or_default()
-            .saturating_sub(1);
-
-        let left = (0..self.width())
-            .position(|i| self.squares.iter().any(|row| !redundant(&row[i])))
-            .unwrap_or_default()
-            .saturating_sub(1);
-
-        let right = (0..self.width())
-            .rev()
-            .position(|i| self.squares.iter().any(|row| !redundant(&row[i])))
-            .unwrap_or_default()
-            .saturating_sub(1);
-
         RedundantEdges {
-            top,
-            right,
-            bottom,
-            left,
+            top: land.origin.y.saturating_sub(1),
+            right: (self.width() - land.origin.x - land.width).saturating_sub(1),
+            bottom: (self.height() - land.origin.y - land.height).saturating_sub(1),
+            left: land.origin.x.saturating_sub(1),
         }
     }
 
@@ -304,6 +666,15 @@ impl Board {
                 row.remove(row.len() - 1);
             }
         }
+        if let Some(aging) = &mut self.aging {
+            aging.trim(&trim);
+        }
+        if let Some(elevation) = &mut self.elevation {
+            elevation.trim(&trim);
+        }
+        if let Some(withering) = &mut self.withering {
+            withering.trim(&trim);
+        }
         self.cache_special_squares();
     }
 
@@ -317,6 +688,10 @@ impl Board {
 
         self.artifacts.clear();
         self.towns.clear();
+        self.obelisks.clear();
+        if self.word_index.is_some() {
+            self.index_words();
+        }
 
         for coord in coords {
             match self.get(coord) {
@@ -377,11 +752,69 @@ impl Board {
             return Err(GamePlayError::OutSideBoardDimensions { position });
         };
 
-        *square = new_square;
+        let before = std::mem::replace(square, new_square);
+        self.update_indexes(position, before);
 
         Ok(())
     }
 
+    /// Replaces many squares at once, such as when painting in the editor.
+    /// Every position is checked before anything changes, so either the whole batch applies or none of it does,
+    /// and the cached towns, artifacts, and words are brought up to date once at the end rather than per square.
+    /// Returns the squares that ended up different, in the order they were first given.
+    pub fn apply_batch(
+        &mut self,
+        batch: Vec<(Coordinate, Square)>,
+    ) -> Result<Vec<BoardChangeDetail>, GamePlayError> {
+        if let Some((position, _)) = batch.iter().find(|(p, _)| self.get(*p).is_err()) {
+            return Err(GamePlayError::OutSideBoardDimensions {
+                position: *position,
+            });
+        }
+
+        let mut originals = vec![];
+        let mut seen = HashSet::new();
+        for (position, new_square) in batch {
+            let square = &mut self.squares[position.y][position.x];
+            if seen.insert(position) {
+                originals.push((position, *square));
+            }
+            *square = new_square;
+        }
+
+        let is_special = |square: &Square| {
+            matches!(
+                square,
+                Square::Town { .. } | Square::Artifact { .. } | Square::Obelisk { .. }
+            )
+        };
+        let mut specials_changed = false;
+        let changes: Vec<_> = originals
+            .into_iter()
+            .filter_map(|(coordinate, before)| {
+                let square = self.squares[coordinate.y][coordinate.x];
+                if square == before {
+                    return None;
+                }
+                specials_changed |= is_special(&before) || is_special(&square);
+                if let Some(ZobristHash(hash)) = &mut self.zobrist {
+                    *hash ^= Board::zobrist_key(coordinate, &before)
+                        ^ Board::zobrist_key(coordinate, &square);
+                }
+                Some(BoardChangeDetail { square, coordinate })
+            })
+            .collect();
+
+        if specials_changed {
+            // Also rebuilds the word index
+            self.cache_special_squares();
+        } else if self.word_index.is_some() && !changes.is_empty() {
+            self.word_index = Some(WordIndex::new(&self.squares));
+        }
+
+        Ok(changes)
+    }
+
     pub fn set(
         &mut self,
         position: Coordinate,
@@ -393,23 +826,26 @@ impl Board {
             return Err(GamePlayError::NonExistentPlayer { index: player });
         }
 
-        match self
+        let before = match self
             .squares
             .get_mut(position.y)
             .and_then(|row| row.get_mut(position.x))
         {
             Some(square) if matches!(square, Square::Land { .. } | Square::Occupied { .. }) => {
-                *square = Square::Occupied {
-                    player,
-                    tile,
-                    validity: SquareValidity::Unknown,
-                    foggy: false,
-                };
-                Ok(())
+                Ok(std::mem::replace(
+                    square,
+                    Square::Occupied {
+                        player,
+                        tile,
+                        validity: SquareValidity::Unknown,
+                        foggy: false,
+                    },
+                ))
             }
             Some(_) => Err(GamePlayError::InvalidPosition { position }),
             None => Err(GamePlayError::OutSideBoardDimensions { position }),
         }?;
+        self.update_indexes(position, before);
 
         self.mark_validity(position, ref_dict);
 
@@ -502,7 +938,8 @@ impl Board {
                     square: *square,
                     coordinate: position,
                 });
-                *square = Square::land();
+                let before = std::mem::replace(square, Square::land());
+                self.update_indexes(position, before);
 
                 self.neighbouring_squares(position)
                     .into_iter()
@@ -515,45 +952,45 @@ impl Board {
         None
     }
 
+    /// Clears the tiles and revives the towns, such as when setting up a rematch on the same board
     pub fn reset(&mut self) {
-        let rows = self.height();
-        let cols = self.width();
-        // TODO: Implement iterators for board and pull this out
-        let coords = (0..rows)
-            .flat_map(|y| (0..cols).zip(std::iter::repeat(y)))
-            .map(|(x, y)| Coordinate { x, y });
+        let cleared = self
+            .squares
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter().enumerate().filter_map(move |(x, sq)| {
+                    let cleared = match sq {
+                        Square::Occupied { .. } => Square::land(),
+                        Square::Town { player, .. } => Square::Town {
+                            player: *player,
+                            defeated: false,
+                            foggy: false,
+                        },
+                        _ => return None,
+                    };
+                    Some((Coordinate { x, y }, cleared))
+                })
+            })
+            .collect();
 
-        for coord in coords {
-            let Ok(sq) = self.get_mut(coord) else {
-                unreachable!("Iterating over the board should not return invalid positions");
-            };
-            match sq {
-                Square::Occupied { .. } => *sq = Square::land(),
-                Square::Town { player, .. } => {
-                    *sq = Square::Town {
-                        player: player.clone(),
-                        defeated: false,
-                        foggy: false,
-                    }
-                }
-                _ => {}
-            }
-        }
+        self.apply_batch(cleared)
+            .expect("Iterating over the board should not return invalid positions");
     }
 
     pub fn defeat_player(&mut self, player_to_defeat: usize) {
         let towns = self.towns.clone();
         for town in towns {
-            let Ok(sq) = self.get_mut(town) else {
-                continue;
-            };
-            match sq {
-                Square::Town { player, .. } if *player == player_to_defeat => {
-                    *sq = Square::Town {
-                        player: player_to_defeat,
-                        defeated: true,
-                        foggy: false,
-                    }
+            match self.get(town) {
+                Ok(Square::Town { player, .. }) if player == player_to_defeat => {
+                    _ = self.set_square(
+                        town,
+                        Square::Town {
+                            player: player_to_defeat,
+                            defeated: true,
+                            foggy: false,
+                        },
+                    );
                 }
                 _ => {}
             }
@@ -578,14 +1015,62 @@ impl Board {
     }
 }
 
+/// Whether the dictionary holds a word of tiles,
+/// with any wild blanks standing in for whichever letters make it a word
+fn dictionary_has(ref_dict: &WordDict, word: &str) -> bool {
+    if !word.contains(BLANK) {
+        return ref_dict.contains_key(&word.to_lowercase());
+    }
+
+    // Blanks are resolved letter by letter the same way battles resolve them,
+    // rather than by comparing the word against everything in the dictionary
+    let win_condition = WinCondition::Destination {
+        town_defense: rules::TownDefense::BeatenByValidity,
+        artifact_defense: ArtifactDefense::Invincible,
+    };
+    Judge::default()
+        .valid(word, &win_condition, Some(ref_dict), None, &mut None)
+        .is_some()
+}
+
 impl Board {
     pub fn mark_all_validity(&mut self, ref_dict: Option<&WordDict>) {
-        let rows = self.height();
-        let cols = self.width();
-        let squares = (0..rows).flat_map(|y| (0..cols).zip(std::iter::repeat(y)));
+        let Some(ref_dict) = ref_dict else {
+            return;
+        };
+
+        // A square is only as valid as every word running through it
+        let mut validities: HashMap<Coordinate, SquareValidity> = HashMap::new();
+        for player in 0..self.orientations.len() {
+            let coords = self.words_of_player(player);
+            let Ok(words) = self.word_strings(&coords) else {
+                continue;
+            };
+
+            for (coords, word) in coords.into_iter().zip(words) {
+                let word_validity = if dictionary_has(ref_dict, &word) {
+                    SquareValidity::Valid
+                } else {
+                    SquareValidity::Invalid
+                };
+
+                for coord in coords {
+                    validities
+                        .entry(coord)
+                        .and_modify(|v| {
+                            if *v != word_validity {
+                                *v = SquareValidity::Partial;
+                            }
+                        })
+                        .or_insert(word_validity);
+                }
+            }
+        }
 
-        for (x, y) in squares {
-            self.mark_validity(Coordinate::new(x, y), ref_dict);
+        for (coord, square_validity) in validities {
+            if let Ok(Square::Occupied { validity, .. }) = self.get_mut(coord) {
+                *validity = square_validity;
+            }
         }
     }
 
@@ -600,8 +1085,7 @@ impl Board {
         };
 
         for (coords, word) in coords.into_iter().zip(words.into_iter()) {
-            // TODO: Use the full judge here to handle, e.g., wildcards
-            let main_word_valid = ref_dict.contains_key(&word.to_ascii_lowercase());
+            let main_word_valid = dictionary_has(ref_dict, &word);
             let ideal_validity = if main_word_valid {
                 SquareValidity::Valid
             } else {
@@ -619,7 +1103,7 @@ impl Board {
                     };
                     let valid_words: Vec<_> = words
                         .into_iter()
-                        .map(|w| ref_dict.contains_key(&w.to_ascii_lowercase()))
+                        .map(|w| dictionary_has(ref_dict, &w))
                         .collect();
                     if main_word_valid && valid_words.contains(&false) {
                         square_validity = SquareValidity::Partial;
@@ -667,6 +1151,200 @@ impl Board {
             .collect()
     }
 
+    /// Marks tiles cut off from their artifact as withering, and truncates the player's own tiles
+    /// that were already withering before their turn and still haven't been reconnected
+    pub fn wither(
+        &mut self,
+        player: usize,
+        bag: &mut TileBag,
+        ref_dict: Option<&WordDict>,
+    ) -> Vec<Change> {
+        let mut attatched = HashSet::new();
+        for root in self.artifacts.iter() {
+            attatched.extend(self.depth_first_search(*root));
+        }
+
+        let (width, height) = (self.width(), self.height());
+        let mut withering = self
+            .withering
+            .take()
+            .unwrap_or_else(|| Withering::new(width, height));
+        let mut changes = vec![];
+
+        for (x, y) in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+            let c = Coordinate { x, y };
+            match self.get(c) {
+                Ok(
+                    square @ Square::Occupied {
+                        player: owner,
+                        tile,
+                        ..
+                    },
+                ) if !attatched.contains(&c) => {
+                    if !withering.is_withering(c) {
+                        withering.set(c, true);
+                        changes.push(Change::Board(BoardChange {
+                            detail: BoardChangeDetail {
+                                coordinate: c,
+                                square,
+                            },
+                            action: BoardChangeAction::Withered,
+                        }));
+                    } else if owner == player {
+                        withering.set(c, false);
+                        bag.return_tile(tile);
+                        changes.extend(self.clear(c, ref_dict).map(|detail| {
+                            Change::Board(BoardChange {
+                                detail,
+                                action: BoardChangeAction::Truncated,
+                            })
+                        }));
+                    }
+                }
+                // Reconnected, or no longer a tile at all
+                _ => withering.set(c, false),
+            }
+        }
+
+        self.withering = Some(withering);
+        changes
+    }
+
+    /// Slides tiles towards their owner's side of the board to close gaps,
+    /// one square at a time, as long as every tile stays connected to an artifact.
+    pub fn compact(&mut self, ref_dict: Option<&WordDict>) -> Vec<Change> {
+        // Where each tile that has moved is now, mapped to where it started
+        let mut origins: HashMap<Coordinate, Coordinate> = HashMap::new();
+
+        loop {
+            let mut moved = false;
+
+            for (player, home) in self.orientations.clone().into_iter().enumerate() {
+                let mut tiles: Vec<_> = self
+                    .squares
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(y, row)| {
+                        row.iter().enumerate().filter_map(move |(x, sq)| match sq {
+                            Square::Occupied { player: p, .. } if *p == player => {
+                                Some(Coordinate::new(x, y))
+                            }
+                            _ => None,
+                        })
+                    })
+                    .collect();
+
+                // Move the tiles nearest to home first so that the ones behind can follow
+                tiles.sort_by_key(|c| {
+                    let distance = match home {
+                        Direction::South => -(c.y as isize),
+                        Direction::East => -(c.x as isize),
+                        Direction::West => c.x as isize,
+                        _ => c.y as isize,
+                    };
+                    (distance, *c)
+                });
+
+                for lead in tiles {
+                    // Earlier slides this pass may have already carried this tile along
+                    match self.get(lead) {
+                        Ok(Square::Occupied { player: p, .. }) if p == player => {}
+                        _ => continue,
+                    }
+                    let Some(to) = lead.add(home) else {
+                        continue;
+                    };
+                    if !matches!(self.get(to), Ok(Square::Land { .. })) {
+                        continue;
+                    }
+
+                    // Tiles lined up behind this one are carried along, like a falling column
+                    let mut run = vec![lead];
+                    while let Some(next) = run.last().and_then(|c| c.add(home.opposite())) {
+                        match self.get(next) {
+                            Ok(Square::Occupied { player: p, .. }) if p == player => run.push(next),
+                            _ => break,
+                        }
+                    }
+                    let destinations: Vec<_> = std::iter::once(to)
+                        .chain(run.iter().copied())
+                        .take(run.len())
+                        .collect();
+                    let squares: Vec<_> = run
+                        .iter()
+                        .map(|c| self.get(*c).expect("tile was found on the board"))
+                        .collect();
+
+                    let rooted = self.rooted_tiles(player);
+                    for (dest, square) in destinations.iter().zip(&squares) {
+                        self.set_square(*dest, *square)
+                            .expect("square was found on the board");
+                    }
+                    self.set_square(*run.last().unwrap(), Square::land())
+                        .expect("tile was found on the board");
+
+                    // Tiles can't slide away from the tiles holding them up, or strand others
+                    if self.rooted_tiles(player) >= rooted {
+                        let slid: Vec<_> = run
+                            .iter()
+                            .zip(&destinations)
+                            .map(|(c, dest)| (*dest, origins.remove(c).unwrap_or(*c)))
+                            .collect();
+                        origins.extend(slid);
+                        moved = true;
+                    } else {
+                        for (c, square) in run.iter().zip(&squares) {
+                            self.set_square(*c, *square)
+                                .expect("tile was found on the board");
+                        }
+                        self.set_square(to, Square::land())
+                            .expect("square was found on the board");
+                    }
+                }
+            }
+
+            if !moved {
+                break;
+            }
+        }
+
+        if origins.is_empty() {
+            return vec![];
+        }
+        self.mark_all_validity(ref_dict);
+
+        let mut slides: Vec<_> = origins.into_iter().collect();
+        slides.sort();
+        slides
+            .into_iter()
+            .map(|(to, from)| {
+                Change::Board(BoardChange {
+                    detail: BoardChangeDetail {
+                        square: self.get(to).expect("tile was moved onto the board"),
+                        coordinate: to,
+                    },
+                    action: BoardChangeAction::Slid { from },
+                })
+            })
+            .collect()
+    }
+
+    /// How many of a player's tiles are connected to one of their artifacts
+    fn rooted_tiles(&self, player: usize) -> usize {
+        let mut attached = HashSet::new();
+        for root in self.artifacts.iter() {
+            attached.extend(self.depth_first_search(*root));
+        }
+
+        attached
+            .into_iter()
+            .filter(|c| match self.get(*c) {
+                Ok(Square::Occupied { player: p, .. }) => p == player,
+                _ => false,
+            })
+            .count()
+    }
+
     // TODO: return iterator or rename since it doesn't matter that this is depth first when we return a HashSet
     pub fn depth_first_search(&self, position: Coordinate) -> HashSet<Coordinate> {
         let mut visited = HashSet::new();
@@ -1090,6 +1768,67 @@ impl Board {
         proximities
     }
 
+    /// Number of tiles the given player currently has on the board
+    pub fn territory(&self, player_index: usize) -> usize {
+        self.squares
+            .iter()
+            .flatten()
+            .filter(|sq| matches!(sq, Square::Occupied { player, .. } if *player == player_index))
+            .count()
+    }
+
+    /// A Zobrist hash of the board's squares, combining a key for each square's position and contents.
+    /// Boards holding the same squares hash the same however they were reached,
+    /// ignoring the validity and fog that follow from the rest of the board.
+    /// Boards with their words indexed keep the hash up to date as squares change,
+    /// and others scan every square for it.
+    pub fn zobrist(&self) -> u64 {
+        match self.zobrist {
+            Some(ZobristHash(hash)) => hash,
+            None => self.scan_zobrist(),
+        }
+    }
+
+    fn scan_zobrist(&self) -> u64 {
+        self.squares
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(move |(x, square)| Board::zobrist_key(Coordinate { x, y }, square))
+            })
+            .fold(0, |hash, key| hash ^ key)
+    }
+
+    /// The key a square contributes to `Board::zobrist`, so that a hash can be updated as
+    /// squares change by combining it with the keys of the old and new contents.
+    /// Keys are derived rather than drawn from a table, so are the same on every platform.
+    pub fn zobrist_key(position: Coordinate, square: &Square) -> u64 {
+        let contents = match *square {
+            Square::Water { .. } => 0,
+            Square::Land { .. } => 1,
+            Square::Fog {} => 2,
+            Square::Obelisk { .. } => 3,
+            Square::Town {
+                player, defeated, ..
+            } => 4 | (defeated as u64) << 4 | (player as u64) << 8,
+            Square::Artifact {
+                player, defeated, ..
+            } => 5 | (defeated as u64) << 4 | (player as u64) << 8,
+            Square::Occupied { player, tile, .. } => 6 | (player as u64) << 8 | (tile as u64) << 32,
+        };
+        let place = (position.x as u64) << 32 | position.y as u64;
+
+        // The finaliser from splitmix64, spreading each input across all of the key's bits
+        let mix = |mut z: u64| {
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        mix(mix(place) ^ contents)
+    }
+
     pub fn get_shape(&self) -> Vec<u64> {
         let width = self.width();
         let num_buckets = Coordinate {
@@ -1115,6 +1854,25 @@ impl Board {
         out
     }
 
+    /// Keeps an index of where each tile's words start and end, updated as tiles are placed and removed,
+    /// for boards that have `get_words` called on them over and over.
+    /// The board's `zobrist` hash is kept up to date alongside it.
+    /// Boards changed through `squares` directly need indexing again afterwards.
+    pub fn index_words(&mut self) {
+        self.word_index = Some(WordIndex::new(&self.squares));
+        self.zobrist = Some(ZobristHash(self.scan_zobrist()));
+    }
+
+    fn update_indexes(&mut self, position: Coordinate, before: Square) {
+        if let Some(index) = &mut self.word_index {
+            index.update(&self.squares, position);
+        }
+        if let Some(ZobristHash(hash)) = &mut self.zobrist {
+            *hash ^= Board::zobrist_key(position, &before)
+                ^ Board::zobrist_key(position, &self.squares[position.y][position.x]);
+        }
+    }
+
     pub fn get_words(&self, position: Coordinate) -> Vec<Vec<Coordinate>> {
         let mut words: Vec<Vec<Coordinate>> = Vec::new();
         let owner = match self.get(position) {
@@ -1124,37 +1882,52 @@ impl Board {
             _ => return words,
         };
 
-        let axes = [
-            [Direction::South, Direction::North],
-            [Direction::East, Direction::West],
-        ];
-
-        // Build each of the two possible words from either side
-        for axis in axes {
-            let mut word = vec![position];
-            for direction in axis {
-                let fowards = direction == Direction::South || direction == Direction::East;
-                let mut location = position.add(direction);
-
-                if let Some(location) = location.as_mut() {
-                    while let Ok(Square::Occupied { player, .. }) = self.get(*location) {
-                        if player != owner {
-                            break;
-                        }
-                        if fowards {
-                            word.push(*location);
-                        } else {
-                            word.insert(0, *location);
-                        }
-                        if let Some(next_location) = location.add(direction) {
-                            *location = next_location;
-                        } else {
-                            break;
+        if let Some(index) = &self.word_index {
+            let [top, bottom] = index.run(position, WordIndex::VERTICAL);
+            words.push(
+                (top..=bottom)
+                    .map(|y| Coordinate { x: position.x, y })
+                    .collect(),
+            );
+            let [left, right] = index.run(position, WordIndex::HORIZONTAL);
+            words.push(
+                (left..=right)
+                    .map(|x| Coordinate { x, y: position.y })
+                    .collect(),
+            );
+        } else {
+            let axes = [
+                [Direction::South, Direction::North],
+                [Direction::East, Direction::West],
+            ];
+
+            // Build each of the two possible words from either side
+            for axis in axes {
+                let mut word = vec![position];
+                for direction in axis {
+                    let fowards = direction == Direction::South || direction == Direction::East;
+                    let mut location = position.add(direction);
+
+                    if let Some(location) = location.as_mut() {
+                        while let Ok(Square::Occupied { player, .. }) = self.get(*location) {
+                            if player != owner {
+                                break;
+                            }
+                            if fowards {
+                                word.push(*location);
+                            } else {
+                                word.insert(0, *location);
+                            }
+                            if let Some(next_location) = location.add(direction) {
+                                *location = next_location;
+                            } else {
+                                break;
+                            }
                         }
                     }
                 }
+                words.push(word);
             }
-            words.push(word);
         }
 
         // Reverse words based on the player's orientation
@@ -1166,12 +1939,206 @@ impl Board {
             words[1].reverse();
         }
 
-        // 1 letter words don't count except when there's only one tile, in which case it does count as a word
-        if words.iter().all(|w| w.len() == 1) {
-            words
-        } else {
-            words.into_iter().filter(|word| word.len() > 1).collect()
+        // 1 letter words don't count except when there's only one tile, in which case it does count as a word
+        if words.iter().all(|w| w.len() == 1) {
+            words
+        } else {
+            words.into_iter().filter(|word| word.len() > 1).collect()
+        }
+    }
+
+    /// Every word a player has on the board, found in a single sweep rather than
+    /// calling `get_words` on each of their tiles. Each word appears once, read in
+    /// the player's orientation, and a lone tile counts as a one letter word.
+    pub fn words_of_player(&self, player: usize) -> Vec<Vec<Coordinate>> {
+        let owned = |position: Option<Coordinate>| {
+            position.is_some_and(
+                |p| matches!(self.get(p), Ok(Square::Occupied { player: p, .. }) if p == player),
+            )
+        };
+        let orientation = self.orientations[player];
+        let mut words = vec![];
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let position = Coordinate { x, y };
+                if !owned(Some(position)) {
+                    continue;
+                }
+
+                let mut lone_tile = true;
+                for (backwards, forwards, reversed) in [
+                    (
+                        Direction::North,
+                        Direction::South,
+                        !orientation.read_top_to_bottom(),
+                    ),
+                    (
+                        Direction::West,
+                        Direction::East,
+                        !orientation.read_left_to_right(),
+                    ),
+                ] {
+                    let has_neighbour =
+                        owned(position.add(backwards)) || owned(position.add(forwards));
+                    lone_tile &= !has_neighbour;
+
+                    // Only start a word from its first tile, so each is collected once
+                    if owned(position.add(backwards)) || !has_neighbour {
+                        continue;
+                    }
+
+                    let mut word = vec![position];
+                    let mut next = position.add(forwards);
+                    while let Some(location) = next.filter(|l| owned(Some(*l))) {
+                        word.push(location);
+                        next = location.add(forwards);
+                    }
+                    if reversed {
+                        word.reverse();
+                    }
+                    words.push(word);
+                }
+
+                if lone_tile {
+                    words.push(vec![position]);
+                }
+            }
+        }
+
+        words
+    }
+
+    /// Empty land where a player's tiles meet a living opponent square,
+    /// i.e. every spot where placing a tile would start a battle.
+    pub fn frontier(&self, player: usize) -> Vec<Coordinate> {
+        let mut edges = vec![];
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let position = Coordinate { x, y };
+                if !matches!(self.get(position), Ok(Square::Land { .. })) {
+                    continue;
+                }
+
+                let neighbours = self.neighbouring_squares(position);
+                let touches_player = neighbours.iter().any(
+                    |(_, sq)| matches!(sq, Square::Occupied { player: p, .. } if *p == player),
+                );
+                let touches_opponent = neighbours.iter().any(|(_, sq)| match sq {
+                    Square::Occupied { player: p, .. } => *p != player,
+                    Square::Town {
+                        player: p,
+                        defeated,
+                        ..
+                    }
+                    | Square::Artifact {
+                        player: p,
+                        defeated,
+                        ..
+                    } => *p != player && !defeated,
+                    _ => false,
+                });
+
+                if touches_player && touches_opponent {
+                    edges.push(position);
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// Tiles that are the only link between some of a player's tiles and their artifacts,
+    /// each mapped to the tiles that would be truncated if it were lost.
+    pub fn cut_tiles(&self, player: usize) -> HashMap<Coordinate, HashSet<Coordinate>> {
+        // Finds articulation points with Tarjan's algorithm, treating all of the player's
+        // artifacts as a single root node (`None`) since tiles only need to reach one of them.
+        struct Search {
+            player: usize,
+            discovered: HashMap<Option<Coordinate>, usize>,
+            order: Vec<Coordinate>,
+            cuts: HashMap<Coordinate, HashSet<Coordinate>>,
+        }
+
+        fn links(b: &Board, player: usize, node: Option<Coordinate>) -> Vec<Option<Coordinate>> {
+            let mut links = vec![];
+            let neighbours = match node {
+                Some(position) => b.neighbouring_squares(position),
+                None => b
+                    .artifacts
+                    .iter()
+                    .filter(|a| match b.get(**a) {
+                        Ok(Square::Artifact { player: p, .. }) => p == player,
+                        _ => false,
+                    })
+                    .flat_map(|a| b.neighbouring_squares(*a))
+                    .collect(),
+            };
+
+            for (position, square) in neighbours {
+                let link = match square {
+                    Square::Occupied { player: p, .. } if p == player => Some(position),
+                    Square::Artifact { player: p, .. } if p == player => None,
+                    _ => continue,
+                };
+                if link != node && !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+            links
+        }
+
+        fn visit(
+            b: &Board,
+            node: Option<Coordinate>,
+            parent: Option<Option<Coordinate>>,
+            search: &mut Search,
+        ) -> usize {
+            let discovered_at = search.discovered.len();
+            search.discovered.insert(node, discovered_at);
+            if let Some(position) = node {
+                search.order.push(position);
+            }
+            let mut low = discovered_at;
+
+            for link in links(b, search.player, node) {
+                if Some(link) == parent {
+                    continue;
+                }
+                if let Some(seen_at) = search.discovered.get(&link) {
+                    low = low.min(*seen_at);
+                    continue;
+                }
+
+                let subtree_start = search.order.len();
+                let link_low = visit(b, link, Some(node), search);
+                low = low.min(link_low);
+
+                // Nothing below this link can reach the root without going through us
+                if let Some(position) = node {
+                    if link_low >= discovered_at {
+                        search
+                            .cuts
+                            .entry(position)
+                            .or_default()
+                            .extend(search.order[subtree_start..].iter().copied());
+                    }
+                }
+            }
+
+            low
         }
+
+        let mut search = Search {
+            player,
+            discovered: HashMap::new(),
+            order: vec![],
+            cuts: HashMap::new(),
+        };
+        visit(self, None, None, &mut search);
+
+        search.cuts
     }
 
     pub fn collect_combanants(
@@ -1258,7 +2225,7 @@ impl Board {
     ) -> HashSet<Coordinate> {
         let mut playable_squares = HashSet::new();
         match truncation {
-            rules::Truncation::Root => {
+            rules::Truncation::Root | rules::Truncation::Withering => {
                 for artifact in &self.artifacts {
                     let sq = self.get(*artifact).unwrap();
                     if !matches!(sq, Square::Artifact{ player, .. } if player == for_player) {
@@ -1308,6 +2275,10 @@ impl Board {
     ) -> Self {
         let mut visible_coords: HashSet<Coordinate> = HashSet::new();
         let mut all_towns: HashSet<Coordinate> = HashSet::new();
+        let home_vision = match visibility {
+            rules::Visibility::Mystery => 2,
+            _ => 6,
+        };
 
         let rows = self.height();
         let cols = self.width();
@@ -1327,7 +2298,7 @@ impl Board {
                     let mut sqs = HashSet::new();
                     sqs.insert(coord);
 
-                    for _ in 0..6 {
+                    for _ in 0..home_vision {
                         let pts = sqs.iter().cloned().collect::<Vec<_>>();
                         for pt in pts {
                             sqs.extend(pt.neighbors_4_iter());
@@ -1417,7 +2388,9 @@ impl Board {
                     }
                 }
             }
-            rules::Visibility::LandFog | rules::Visibility::OnlyHouseFog => {
+            rules::Visibility::LandFog
+            | rules::Visibility::OnlyHouseFog
+            | rules::Visibility::Mystery => {
                 for (x, y) in squares {
                     let c = Coordinate { x, y };
                     if matches!(visibility, rules::Visibility::OnlyHouseFog) {
@@ -1467,7 +2440,9 @@ impl Board {
         seen_tiles: &HashSet<Coordinate>,
     ) -> Coordinate {
         match visibility {
-            rules::Visibility::Standard | rules::Visibility::TileFog => {
+            rules::Visibility::Standard
+            | rules::Visibility::TileFog
+            | rules::Visibility::Mystery => {
                 // In these modes, the player knows the full coordinate space, so no fog remapping is required.
                 match player_index {
                     0 => match board_orientation {
@@ -1527,7 +2502,9 @@ impl Board {
         seen_tiles: &HashSet<Coordinate>,
     ) -> Option<Coordinate> {
         match visibility {
-            rules::Visibility::Standard | rules::Visibility::TileFog => {
+            rules::Visibility::Standard
+            | rules::Visibility::TileFog
+            | rules::Visibility::Mystery => {
                 // In these modes, the player knows the full coordinate space, so no remapping is required.
                 Some(match player_index {
                     0 => match board_orientation {
@@ -1587,6 +2564,15 @@ impl Board {
     pub fn rotate_in_place(&mut self) {
         self.squares.reverse();
         self.squares.iter_mut().for_each(|s| s.reverse());
+        if let Some(aging) = &mut self.aging {
+            aging.rotate();
+        }
+        if let Some(elevation) = &mut self.elevation {
+            elevation.rotate();
+        }
+        if let Some(withering) = &mut self.withering {
+            withering.rotate();
+        }
         self.cache_special_squares();
     }
 
@@ -1607,10 +2593,11 @@ impl Board {
                 rules::Visibility::Standard => self.clone(),
                 rules::Visibility::TileFog
                 | rules::Visibility::LandFog
-                | rules::Visibility::OnlyHouseFog => {
+                | rules::Visibility::OnlyHouseFog
+                | rules::Visibility::Mystery => {
                     let mut foggy = self.fog_of_war(player_index, visibility, seen_tiles);
 
-                    if trim_coords {
+                    if trim_coords && !matches!(visibility, rules::Visibility::Mystery) {
                         // Remove extraneous water, so the client doesn't know the dimensions of the play area
                         foggy.trim();
                     }
@@ -1695,12 +2682,22 @@ impl Board {
             panic!("Tried to make a jagged board");
         }
 
+        Board::from_squares(squares)
+    }
+
+    /// Builds a board from its rows of squares, with the players sitting at the north and south
+    pub fn from_squares(squares: Vec<Vec<Square>>) -> Board {
         let mut board = Board {
             squares,
             towns: vec![],
             artifacts: vec![],
             obelisks: vec![],
             orientations: vec![Direction::North, Direction::South],
+            aging: None,
+            elevation: None,
+            withering: None,
+            word_index: None,
+            zobrist: None,
         };
         board.cache_special_squares();
 
@@ -1897,7 +2894,7 @@ impl SignedCoordinate {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SquareValidity {
     Unknown,
     Valid,
@@ -1905,7 +2902,7 @@ pub enum SquareValidity {
     Partial,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Square {
     Water {
         foggy: bool,
@@ -2006,7 +3003,7 @@ impl fmt::Display for Square {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct BoardDistances {
     pub board_width: usize,
     pub attackable: Vec<Option<usize>>,
@@ -2123,6 +3120,128 @@ fn reciprocal_coordinate_within(coord: Coordinate, width: usize, height: usize)
     }
 }
 
+/// Boards with less land than this leave too little room to play a game on
+pub const MIN_EDITOR_LAND: usize = 12;
+
+/// Something about a board, usually a custom one from the editor, that stops it being played well
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardWarning {
+    NotEnoughLand {
+        squares: usize,
+    },
+    /// Land is split into islands that players can't cross between
+    SplitLand {
+        islands: usize,
+    },
+    MissingTown {
+        player: usize,
+    },
+    MissingDock {
+        player: usize,
+    },
+    /// A dock with no land beside it, so nothing could ever be played from it
+    StrandedDock {
+        player: usize,
+    },
+}
+
+impl fmt::Display for BoardWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardWarning::NotEnoughLand { squares } => write!(
+                f,
+                "There are {squares} squares of land, but the board needs at least {MIN_EDITOR_LAND}"
+            ),
+            BoardWarning::SplitLand { islands } => write!(
+                f,
+                "The land is split into {islands} islands, but needs to be joined up"
+            ),
+            BoardWarning::MissingTown { player } => {
+                write!(f, "Player {} needs at least one town", player + 1)
+            }
+            BoardWarning::MissingDock { player } => {
+                write!(f, "Player {} needs a dock", player + 1)
+            }
+            BoardWarning::StrandedDock { player } => {
+                write!(f, "Player {}'s dock needs to be beside land", player + 1)
+            }
+        }
+    }
+}
+
+/// Checks custom boards for anything that would leave a game unplayable
+pub struct BoardValidator;
+
+impl BoardValidator {
+    pub fn check(board: &Board, player_count: usize) -> Vec<BoardWarning> {
+        let mut warnings = vec![];
+
+        let is_land = |coord: Coordinate| {
+            matches!(
+                board.get(coord),
+                Ok(Square::Land { .. }
+                    | Square::Town { .. }
+                    | Square::Obelisk { .. }
+                    | Square::Occupied { .. })
+            )
+        };
+        let coords: Vec<_> = (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| Coordinate { x, y }))
+            .collect();
+
+        let land: HashSet<_> = coords.iter().copied().filter(|c| is_land(*c)).collect();
+        if land.len() < MIN_EDITOR_LAND {
+            warnings.push(BoardWarning::NotEnoughLand {
+                squares: land.len(),
+            });
+        }
+
+        let mut unvisited = land.clone();
+        let mut islands = 0;
+        while let Some(&start) = unvisited.iter().next() {
+            islands += 1;
+            unvisited.remove(&start);
+            let mut queue = VecDeque::from([start]);
+            while let Some(coord) = queue.pop_front() {
+                for neighbour in coord.neighbors_4_iter() {
+                    if unvisited.remove(&neighbour) {
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+        if islands > 1 {
+            warnings.push(BoardWarning::SplitLand { islands });
+        }
+
+        for player in 0..player_count {
+            let has_town = coords.iter().any(
+                |c| matches!(board.get(*c), Ok(Square::Town { player: p, .. }) if p == player),
+            );
+            if !has_town {
+                warnings.push(BoardWarning::MissingTown { player });
+            }
+
+            let docks: Vec<_> = coords
+                .iter()
+                .filter(|c| {
+                    matches!(board.get(**c), Ok(Square::Artifact { player: p, .. }) if p == player)
+                })
+                .collect();
+            if docks.is_empty() {
+                warnings.push(BoardWarning::MissingDock { player });
+            } else if !docks
+                .iter()
+                .any(|dock| dock.neighbors_4_iter().any(|n| land.contains(&n)))
+            {
+                warnings.push(BoardWarning::StrandedDock { player });
+            }
+        }
+
+        warnings
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::{judge::Judge, rules::SwapPenalty};
@@ -2149,6 +3268,15 @@ pub mod tests {
         assert_eq!(coord, Coordinate::from_1d(flat, 51));
     }
 
+    #[test]
+    fn blanks_stand_in_for_any_letter() {
+        let dict = short_dict();
+        assert!(dictionary_has(&dict, "B*G"));
+        assert!(dictionary_has(&dict, "*OLL*"));
+        assert!(!dictionary_has(&dict, "B*T"));
+        assert!(!dictionary_has(&dict, "FAT*"));
+    }
+
     fn default_swap_rules() -> SwapPenalty {
         SwapPenalty::Disallowed { allowed_swaps: 1 }
     }
@@ -2187,6 +3315,39 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn validates_playable_boards() {
+        let b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             __ __ __ __ __\n\
+             __ #1 __ __ __\n\
+             __ __ __ #0 __\n\
+             __ __ __ __ __\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        assert_eq!(BoardValidator::check(&b, 2), vec![]);
+    }
+
+    #[test]
+    fn validates_broken_boards() {
+        let b = Board::from_string(
+            "|0 ~~ ~~ ~~ ~~\n\
+             ~~ __ __ ~~ __\n\
+             ~~ __ #0 ~~ __\n\
+             ~~ ~~ ~~ ~~ ~~",
+        );
+        assert_eq!(
+            BoardValidator::check(&b, 2),
+            vec![
+                BoardWarning::NotEnoughLand { squares: 6 },
+                BoardWarning::SplitLand { islands: 2 },
+                BoardWarning::StrandedDock { player: 0 },
+                BoardWarning::MissingTown { player: 1 },
+                BoardWarning::MissingDock { player: 1 },
+            ]
+        );
+    }
+
     #[test]
     fn trim_board() {
         let mut b = Board::from_string(
@@ -2292,6 +3453,30 @@ pub mod tests {
         let b = Board::new(6, 3);
         assert_eq!(b.width(), 8);
         assert_eq!(b.height(), 5);
+        assert_eq!(b.land_width(), 6);
+        assert_eq!(b.land_height(), 3);
+
+        // Legacy boards dock their artifacts out in the water rows, which aren't land
+        let legacy = Board::new_legacy(3, 1);
+        assert_eq!(legacy.height(), 3);
+        assert_eq!(legacy.land_width(), 3);
+        assert_eq!(legacy.land_height(), 1);
+    }
+
+    #[test]
+    fn land_position() {
+        let land = Board::new(6, 3).land().unwrap();
+        assert_eq!(land.origin, Coordinate::new(1, 1));
+        assert_eq!((land.width, land.height), (6, 3));
+
+        // A board saved with its land further in still has the same land
+        let mut grown = Board::new(6, 3);
+        grown.grow();
+        let grown_land = grown.land().unwrap();
+        assert_eq!(grown_land.origin, Coordinate::new(2, 2));
+        assert_eq!((grown_land.width, grown_land.height), (6, 3));
+
+        assert_eq!(Board::from_string("~~ ~~\n~~ ~~").land(), None);
     }
 
     #[test]
@@ -2442,7 +3627,7 @@ pub mod tests {
 
     #[test]
     fn set_changes_get() {
-        let mut b = Board::new(3, 3); // Note, height is 3 from home rows
+        let mut b = Board::new(3, 3); // Note, the land starts at (1, 1) inside the water
         assert_eq!(b.get(Coordinate { x: 2, y: 2 }), Ok(Square::land()));
         assert_eq!(
             b.set(Coordinate { x: 2, y: 2 }, 0, 'a', Some(&short_dict())),
@@ -2467,6 +3652,172 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn applies_batches_all_at_once() {
+        let mut b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        b.index_words();
+        let before = b.clone();
+
+        let out_of_bounds = Coordinate { x: 5, y: 0 };
+        assert_eq!(
+            b.apply_batch(vec![
+                (Coordinate { x: 0, y: 1 }, Square::town(0)),
+                (out_of_bounds, Square::land()),
+            ]),
+            Err(GamePlayError::OutSideBoardDimensions {
+                position: out_of_bounds
+            })
+        );
+        assert_eq!(b, before);
+
+        let occupied = Square::Occupied {
+            player: 0,
+            tile: 'A',
+            validity: SquareValidity::Unknown,
+            foggy: false,
+        };
+        let changes = b
+            .apply_batch(vec![
+                (Coordinate { x: 0, y: 1 }, Square::town(0)),
+                (Coordinate { x: 1, y: 1 }, Square::land()),
+                (Coordinate { x: 2, y: 1 }, occupied),
+                (Coordinate { x: 3, y: 1 }, occupied),
+                (Coordinate { x: 4, y: 1 }, occupied),
+                (Coordinate { x: 4, y: 1 }, Square::land()),
+            ])
+            .unwrap();
+
+        // Squares that didn't end up any different aren't reported
+        assert_eq!(
+            changes,
+            vec![
+                BoardChangeDetail {
+                    square: Square::town(0),
+                    coordinate: Coordinate { x: 0, y: 1 },
+                },
+                BoardChangeDetail {
+                    square: occupied,
+                    coordinate: Coordinate { x: 2, y: 1 },
+                },
+                BoardChangeDetail {
+                    square: occupied,
+                    coordinate: Coordinate { x: 3, y: 1 },
+                },
+            ]
+        );
+        assert_eq!(b.towns().collect::<Vec<_>>(), [&Coordinate { x: 0, y: 1 }]);
+        // Player 0 reads from the north, so right to left
+        assert_eq!(
+            b.get_words(Coordinate { x: 2, y: 1 }),
+            vec![vec![Coordinate { x: 3, y: 1 }, Coordinate { x: 2, y: 1 }]]
+        );
+    }
+
+    #[test]
+    fn resets_to_the_starting_board() {
+        let start = Board::new(5, 5);
+        let mut b = start.clone();
+        b.set(Coordinate { x: 2, y: 2 }, 0, 'A', None).unwrap();
+        b.set(Coordinate { x: 2, y: 3 }, 1, 'B', None).unwrap();
+        b.defeat_player(1);
+        assert_ne!(b, start);
+
+        b.reset();
+        assert_eq!(b, start);
+    }
+
+    #[test]
+    fn zobrist_hashes_match_for_the_same_squares() {
+        let mut b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        let empty = b.zobrist();
+        let (first, second) = (Coordinate { x: 2, y: 1 }, Coordinate { x: 3, y: 1 });
+
+        let mut reordered = b.clone();
+        b.set(first, 0, 'A', None).unwrap();
+        b.set(second, 0, 'B', None).unwrap();
+        reordered.set(second, 0, 'B', None).unwrap();
+        reordered.set(first, 0, 'A', Some(&short_dict())).unwrap();
+        assert_ne!(b, reordered, "Only one board has had its words judged");
+        assert_eq!(b.zobrist(), reordered.zobrist());
+
+        // Keys can be swapped in and out as squares change
+        let placed = Square::Occupied {
+            player: 0,
+            tile: 'A',
+            validity: SquareValidity::Unknown,
+            foggy: false,
+        };
+        b.clear(first, None);
+        assert_eq!(
+            b.zobrist(),
+            reordered.zobrist()
+                ^ Board::zobrist_key(first, &placed)
+                ^ Board::zobrist_key(first, &Square::land())
+        );
+
+        b.clear(second, None);
+        assert_eq!(b.zobrist(), empty);
+        b.set(first, 1, 'A', None).unwrap();
+        assert_ne!(b.zobrist(), reordered.zobrist());
+    }
+
+    #[test]
+    fn tracked_zobrist_hashes_match_a_rescan() {
+        let mut tracked = Board::new(12, 12);
+        tracked.index_words();
+        let mut rng = oorandom::Rand32::new(11);
+
+        let land: Vec<_> = (0..tracked.height())
+            .flat_map(|y| (0..tracked.width()).map(move |x| Coordinate { x, y }))
+            .filter(|c| matches!(tracked.get(*c), Ok(Square::Land { .. })))
+            .collect();
+
+        for turn in 0..300 {
+            let position = land[rng.rand_range(0..land.len() as u32) as usize];
+            let player = rng.rand_range(0..2) as usize;
+            match rng.rand_range(0..4) {
+                0 => {
+                    tracked.clear(position, None);
+                }
+                1 => {
+                    let other = land[rng.rand_range(0..land.len() as u32) as usize];
+                    _ = tracked.swap(
+                        player,
+                        [position, other],
+                        &rules::Swapping::Universal(SwapPenalty::Disallowed { allowed_swaps: 0 }),
+                        None,
+                    );
+                }
+                _ => {
+                    let tile = (b'A' + rng.rand_range(0..26) as u8) as char;
+                    tracked.set(position, player, tile, None).unwrap();
+                }
+            }
+            if turn % 50 == 49 {
+                tracked.truncate(&mut TileBag::generation(0, Some(1)), None);
+            }
+
+            assert_eq!(tracked.zobrist(), tracked.scan_zobrist(), "on turn {turn}");
+        }
+
+        tracked.defeat_player(1);
+        assert_eq!(tracked.zobrist(), tracked.scan_zobrist());
+        tracked.reset();
+        assert_eq!(tracked.zobrist(), tracked.scan_zobrist());
+        tracked.rotate_in_place();
+        assert_eq!(tracked.zobrist(), tracked.scan_zobrist());
+    }
+
     #[test]
     fn depth_first_search() {
         let mut b = Board::from_string(
@@ -3118,6 +4469,174 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn indexed_words_match_scanned_words() {
+        let mut indexed = Board::new(12, 12);
+        indexed.index_words();
+        let mut rng = oorandom::Rand32::new(7);
+
+        let land: Vec<_> = (0..indexed.height())
+            .flat_map(|y| (0..indexed.width()).map(move |x| Coordinate { x, y }))
+            .filter(|c| matches!(indexed.get(*c), Ok(Square::Land { .. })))
+            .collect();
+
+        for _ in 0..500 {
+            let position = land[rng.rand_range(0..land.len() as u32) as usize];
+            // Placing more often than clearing, so that long words build up
+            if rng.rand_range(0..3) == 0 {
+                indexed.clear(position, None);
+            } else {
+                let player = rng.rand_range(0..2) as usize;
+                indexed.set(position, player, 'A', None).unwrap();
+            }
+
+            let mut scanned = indexed.clone();
+            scanned.word_index = None;
+            for y in 0..indexed.height() {
+                for x in 0..indexed.width() {
+                    let c = Coordinate { x, y };
+                    assert_eq!(indexed.get_words(c), scanned.get_words(c), "at {c}");
+                }
+            }
+        }
+
+        // Changes to the board's size are indexed too
+        indexed.grow();
+        indexed.rotate_in_place();
+        indexed.trim();
+        let mut scanned = indexed.clone();
+        scanned.word_index = None;
+        for y in 0..indexed.height() {
+            for x in 0..indexed.width() {
+                let c = Coordinate { x, y };
+                assert_eq!(indexed.get_words(c), scanned.get_words(c));
+            }
+        }
+    }
+
+    #[test]
+    fn words_of_player() {
+        let b = Board::from_string(
+            "~~ ~~ ~~ |0 ~~ ~~ ~~\n\
+             ~~ N0 U0 B0 #0 __ ~~\n\
+             ~~ E0 __ __ __ G1 ~~\n\
+             ~~ B0 __ __ __ A1 ~~\n\
+             ~~ __ #1 Z1 E1 N1 ~~\n\
+             ~~ ~~ ~~ |1 ~~ ~~ ~~",
+        );
+
+        for player in 0..2 {
+            // Should find the same words as checking each tile individually
+            let mut individually: Vec<_> = (0..b.height())
+                .flat_map(|y| (0..b.width()).map(move |x| Coordinate { x, y }))
+                .filter(
+                    |c| matches!(b.get(*c), Ok(Square::Occupied { player: p, .. }) if p == player),
+                )
+                .flat_map(|c| b.get_words(c))
+                .collect();
+            individually.sort();
+            individually.dedup();
+
+            let mut swept = b.words_of_player(player);
+            swept.sort();
+            assert_eq!(swept, individually);
+        }
+
+        let mut words = b.word_strings(&b.words_of_player(1)).unwrap();
+        words.sort();
+        assert_eq!(words, vec!["GAN", "ZEN"]);
+
+        let b = Board::from_string(
+            "A0 B0 __ C1\n\
+             __ __ __ __\n\
+             D0 __ E1 __",
+        );
+        assert_eq!(
+            b.word_strings(&b.words_of_player(0)).unwrap(),
+            vec!["BA", "D"]
+        );
+        assert_eq!(
+            b.frontier(0),
+            vec![Coordinate { x: 2, y: 0 }, Coordinate { x: 1, y: 2 }]
+        );
+        assert_eq!(b.frontier(0), b.frontier(1));
+    }
+
+    #[test]
+    fn finds_cut_tiles() {
+        let b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~
+             ~~ __ A0 __ ~~
+             ~~ D0 B0 E0 ~~
+             ~~ F0 __ G0 ~~
+             ~~ H0 I0 J0 ~~
+             ~~ __ K0 __ ~~
+             ~~ ~~ |1 ~~ ~~",
+        );
+        let cuts = b.cut_tiles(0);
+
+        // Everything hangs off A and B, but the loop below them keeps every other tile supported
+        assert_eq!(cuts[&Coordinate::new(2, 1)].len(), 9);
+        assert_eq!(cuts[&Coordinate::new(2, 2)].len(), 8);
+        assert_eq!(
+            cuts[&Coordinate::new(2, 4)],
+            HashSet::from([Coordinate::new(2, 5)])
+        );
+        assert!(!cuts.contains_key(&Coordinate::new(1, 3)));
+        assert!(!cuts.contains_key(&Coordinate::new(2, 5)));
+        assert_eq!(cuts.len(), 3);
+
+        // Player one's artifact has no tiles of theirs around it
+        assert!(b.cut_tiles(1).is_empty());
+    }
+
+    #[test]
+    fn compacts_towards_root() {
+        let mut b = Board::from_string(
+            "~~ ~~ |0 ~~ ~~
+             __ __ A0 B0 __
+             __ __ __ C0 D0
+             __ __ __ __ E0
+             __ __ __ __ __
+             ~~ ~~ |1 ~~ ~~",
+        );
+        let changes = b.compact(None);
+
+        // E would be stranded if D slid alone, so they move up together
+        assert_eq!(
+            b.to_string(),
+            Board::from_string(
+                "~~ ~~ |0 ~~ ~~
+                 __ __ A0 B0 D0
+                 __ __ __ C0 E0
+                 __ __ __ __ __
+                 __ __ __ __ __
+                 ~~ ~~ |1 ~~ ~~",
+            )
+            .to_string()
+        );
+        let slides: Vec<_> = changes
+            .into_iter()
+            .filter_map(|c| match c {
+                Change::Board(BoardChange {
+                    detail,
+                    action: BoardChangeAction::Slid { from },
+                }) => Some((from, detail.coordinate)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            slides,
+            vec![
+                (Coordinate::new(4, 2), Coordinate::new(4, 1)),
+                (Coordinate::new(4, 3), Coordinate::new(4, 2)),
+            ]
+        );
+
+        // Nothing is left to slide
+        assert!(b.compact(None).is_empty());
+    }
+
     #[test]
     fn apply_rotation() {
         let board = Board::from_string(
@@ -3248,6 +4767,90 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn reveal_mystery_board() {
+        let board = Board::from_string(
+            "~~ ~~ ~~ |0 ~~ ~~ ~~\n\
+             __ __ __ __ __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             ~~ ~~ ~~ |1 ~~ ~~ ~~",
+        );
+        let expanded = Board::from_string(
+            "~~ ~~ ~~ |0 ~~ ~~ ~~\n\
+             __ __ __ A0 __ __ __\n\
+             __ __ __ A0 __ __ __\n\
+             __ __ __ __ __ __ __\n\
+             ~~ ~~ ~~ |1 ~~ ~~ ~~",
+        );
+        let mystery = rules::Visibility::Mystery;
+
+        let foggy = board.fog_of_war(0, &mystery, &HashSet::new());
+        assert_eq!(
+            foggy.to_string(),
+            "░░ ~~ ~~ |0 ~~ ~~ ░░\n\
+             ░░ ░░ __ __ __ ░░ ░░\n\
+             ░░ ░░ ░░ __ ░░ ░░ ░░\n\
+             ░░ ░░ ░░ ░░ ░░ ░░ ░░\n\
+             ░░ ░░ ░░ ░░ ░░ ░░ ░░",
+        );
+
+        let foggy = expanded.fog_of_war(0, &mystery, &HashSet::new());
+        assert_eq!(
+            foggy.to_string(),
+            "░░ ~~ ~~ |0 ~~ ~~ ░░\n\
+             ░░ __ __ A0 __ __ ░░\n\
+             ░░ __ __ A0 __ __ ░░\n\
+             ░░ ░░ __ __ __ ░░ ░░\n\
+             ░░ ░░ ░░ |1 ░░ ░░ ░░",
+        );
+
+        // Land stays revealed after the tiles that uncovered it are gone
+        let seen: HashSet<_> = foggy
+            .squares
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, sq)| !matches!(sq, Square::Fog {}))
+                    .map(move |(x, _)| Coordinate::new(x, y))
+            })
+            .collect();
+        let foggy = board.fog_of_war(0, &mystery, &seen);
+        assert_eq!(
+            foggy.to_string(),
+            "░░ ~~ ~~ |0 ~~ ~~ ░░\n\
+             ░░ __ __ __ __ __ ░░\n\
+             ░░ __ __ __ __ __ ░░\n\
+             ░░ ░░ __ __ __ ░░ ░░\n\
+             ░░ ░░ ░░ |1 ░░ ░░ ░░",
+        );
+        assert!(foggy.get(Coordinate::new(1, 1)).unwrap().is_foggy());
+        assert!(!foggy.get(Coordinate::new(3, 2)).unwrap().is_foggy());
+
+        // The board is never trimmed, so the player's coordinates match the game's
+        let filtered = board.filter_to_player(
+            0,
+            &mystery,
+            &rules::BoardOrientation::Standard,
+            &None,
+            &HashSet::new(),
+            true,
+        );
+        assert_eq!((filtered.width(), filtered.height()), (7, 5));
+        assert_eq!(
+            board.map_player_coord_to_game(
+                0,
+                Coordinate::new(2, 1),
+                &mystery,
+                &rules::BoardOrientation::Standard,
+                &HashSet::new(),
+            ),
+            Coordinate::new(2, 1)
+        );
+    }
+
     #[test]
     fn remap_foggy_coordinates() {
         let board = Board::from_string(
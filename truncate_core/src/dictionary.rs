@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{BuildHasherDefault, Hasher},
+};
+
+use xxhash_rust::xxh3::{xxh3_64, xxh3_64_with_seed};
+
+use crate::rng::TruncateRng;
+
+#[derive(Debug, Clone)]
+pub struct WordData {
+    pub extensions: u32,
+    pub rel_freq: f32,
+    pub objectionable: bool,
+}
+
+/// How many words a daily pick looks at before giving up
+const PICK_TRIES: usize = 50;
+
+/// Marks the end of a chain of words that share a hash
+const NO_ENTRY: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+struct WordEntry {
+    start: u32,
+    len: u32,
+    /// The next entry with the same hash as this one, if any
+    next: u32,
+    data: WordData,
+}
+
+/// Keys in the index are already xxh3 hashes, so they're used as-is rather than hashed again
+#[derive(Default)]
+struct PrehashedHasher(u64);
+
+impl Hasher for PrehashedHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 << 8) | *byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, hash: u64) {
+        self.0 = hash;
+    }
+}
+
+/// A word list held in a single buffer, rather than as a string allocation per word.
+/// Words are found through an index of their hashes, and are handed out as slices of the buffer.
+#[derive(Clone, Default)]
+pub struct WordDict {
+    /// Every word in the dictionary, back to back
+    buffer: String,
+    /// Each word's place in the buffer, in the order they were added
+    entries: Vec<WordEntry>,
+    /// The first entry for each word hash
+    index: HashMap<u64, u32, BuildHasherDefault<PrehashedHasher>>,
+}
+
+impl WordDict {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes room up front for roughly this many words, of this many bytes in total
+    pub fn with_capacity(words: usize, bytes: usize) -> Self {
+        Self {
+            buffer: String::with_capacity(bytes),
+            entries: Vec::with_capacity(words),
+            index: HashMap::with_capacity_and_hasher(words, Default::default()),
+        }
+    }
+
+    fn word_at(&self, entry: &WordEntry) -> &str {
+        &self.buffer[entry.start as usize..(entry.start + entry.len) as usize]
+    }
+
+    fn position(&self, word: &str) -> Option<usize> {
+        let mut position = *self.index.get(&xxh3_64(word.as_bytes()))?;
+        while position != NO_ENTRY {
+            let entry = &self.entries[position as usize];
+            if self.word_at(entry) == word {
+                return Some(position as usize);
+            }
+            position = entry.next;
+        }
+        None
+    }
+
+    /// Adds a word, returning the data it replaced if it was already in the dictionary
+    pub fn insert<S: AsRef<str>>(&mut self, word: S, data: WordData) -> Option<WordData> {
+        let word = word.as_ref();
+        if let Some(position) = self.position(word) {
+            return Some(std::mem::replace(&mut self.entries[position].data, data));
+        }
+
+        let position = self.entries.len() as u32;
+        let next = self
+            .index
+            .insert(xxh3_64(word.as_bytes()), position)
+            .unwrap_or(NO_ENTRY);
+        self.entries.push(WordEntry {
+            start: self.buffer.len() as u32,
+            len: word.len() as u32,
+            next,
+            data,
+        });
+        self.buffer.push_str(word);
+
+        None
+    }
+
+    pub fn get(&self, word: &str) -> Option<&WordData> {
+        self.position(word)
+            .map(|position| &self.entries[position].data)
+    }
+
+    pub fn contains_key(&self, word: &str) -> bool {
+        self.position(word).is_some()
+    }
+
+    /// Every word and its data, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &WordData)> {
+        self.entries
+            .iter()
+            .map(|entry| (self.word_at(entry), &entry.data))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| self.word_at(entry))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Every word matching `filter`, sorted so that picks made from the list
+    /// don't depend on the order words were added in
+    pub fn sorted_words(&self, filter: impl Fn(&str, &WordData) -> bool) -> Vec<String> {
+        let mut words: Vec<_> = self
+            .iter()
+            .filter(|(word, data)| filter(word, data))
+            .map(|(word, _)| word.to_string())
+            .collect();
+        words.sort();
+        words
+    }
+
+    /// A fingerprint of the words in the dictionary, which changes whenever a word is added,
+    /// so that anything picked from the dictionary can be tied to the list it was picked from
+    pub fn version(&self) -> u64 {
+        xxh3_64_with_seed(self.buffer.as_bytes(), self.entries.len() as u64)
+    }
+}
+
+/// Draws random words from `words` until `attempt` accepts one, giving up after a handful of tries.
+/// The generator is handed on to `attempt`, so anything else it picks stays deterministic.
+pub fn pick_with_retries<T>(
+    words: &[String],
+    rng: &mut TruncateRng,
+    mut attempt: impl FnMut(&String, &mut TruncateRng) -> Option<T>,
+) -> Option<T> {
+    if words.is_empty() {
+        return None;
+    }
+    for _ in 0..PICK_TRIES {
+        let word = &words[rng.rand_range(0..words.len() as u32) as usize];
+        if let Some(picked) = attempt(word, rng) {
+            return Some(picked);
+        }
+    }
+    None
+}
+
+impl<S: AsRef<str>> FromIterator<(S, WordData)> for WordDict {
+    fn from_iter<I: IntoIterator<Item = (S, WordData)>>(iter: I) -> Self {
+        let mut dict = WordDict::new();
+        dict.extend(iter);
+        dict
+    }
+}
+
+impl<S: AsRef<str>> Extend<(S, WordData)> for WordDict {
+    fn extend<I: IntoIterator<Item = (S, WordData)>>(&mut self, iter: I) {
+        for (word, data) in iter {
+            self.insert(word, data);
+        }
+    }
+}
+
+impl fmt::Debug for WordDict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(rel_freq: f32) -> WordData {
+        WordData {
+            extensions: 0,
+            rel_freq,
+            objectionable: false,
+        }
+    }
+
+    #[test]
+    fn finds_words_in_the_buffer() {
+        let mut dict: WordDict = [("big", data(0.1)), ("fat", data(0.2))]
+            .into_iter()
+            .collect();
+
+        assert!(dict.contains_key("big"));
+        assert!(!dict.contains_key("bi"));
+        assert!(!dict.contains_key("bigfat"));
+        assert_eq!(dict.get("fat").map(|d| d.rel_freq), Some(0.2));
+
+        assert!(dict.insert("big", data(0.3)).is_some());
+        assert_eq!(dict.len(), 2);
+        assert_eq!(dict.get("big").map(|d| d.rel_freq), Some(0.3));
+        assert_eq!(dict.keys().collect::<Vec<_>>(), vec!["big", "fat"]);
+    }
+
+    #[test]
+    fn words_sharing_a_hash_are_told_apart() {
+        // Force every word into a single chain, as if their hashes collided
+        let mut dict = WordDict::new();
+        for (i, word) in ["and", "arts", "folk"].into_iter().enumerate() {
+            dict.entries.push(WordEntry {
+                start: dict.buffer.len() as u32,
+                len: word.len() as u32,
+                next: i.checked_sub(1).map_or(NO_ENTRY, |prev| prev as u32),
+                data: data(i as f32),
+            });
+            dict.buffer.push_str(word);
+        }
+        dict.index.insert(xxh3_64(b"and"), 2);
+
+        assert_eq!(dict.get("and").map(|d| d.rel_freq), Some(0.0));
+        assert_eq!(dict.len(), 3);
+    }
+
+    #[test]
+    fn picks_ignore_insertion_order() {
+        let forwards: WordDict = [("cat", data(0.9)), ("dog", data(0.1)), ("ant", data(0.9))]
+            .into_iter()
+            .collect();
+        let backwards: WordDict = [("ant", data(0.9)), ("dog", data(0.1)), ("cat", data(0.9))]
+            .into_iter()
+            .collect();
+
+        let common = |_: &str, data: &WordData| data.rel_freq > 0.5;
+        assert_eq!(forwards.sorted_words(common), vec!["ant", "cat"]);
+        assert_eq!(
+            forwards.sorted_words(common),
+            backwards.sorted_words(common)
+        );
+
+        let pick = |dict: &WordDict| {
+            pick_with_retries(
+                &dict.sorted_words(common),
+                &mut TruncateRng::new(4),
+                |w, _| Some(w.clone()),
+            )
+        };
+        assert_eq!(pick(&forwards), pick(&backwards));
+        assert_eq!(
+            pick_with_retries(&[], &mut TruncateRng::new(4), |w, _| Some(w.clone())),
+            None
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Days with fewer players than this are too noisy to calibrate against
+pub const MIN_CALIBRATION_PLAYERS: u32 = 10;
+
+/// How players fared on one day's puzzle, aggregated across everyone who played it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PuzzleDifficulty {
+    pub day: u32,
+    pub players: u32,
+    pub attempts: u32,
+    pub winners: u32,
+    pub average_winning_moves: Option<f32>,
+}
+
+impl PuzzleDifficulty {
+    pub fn win_rate(&self) -> f32 {
+        if self.players == 0 {
+            return 0.0;
+        }
+        self.winners as f32 / self.players as f32
+    }
+
+    pub fn attempts_per_player(&self) -> f32 {
+        if self.players == 0 {
+            return 0.0;
+        }
+        self.attempts as f32 / self.players as f32
+    }
+
+    /// Scores the day from 0.0 (everyone won first try) to 1.0 (nobody won, after many tries)
+    pub fn score(&self) -> f32 {
+        let losses = 1.0 - self.win_rate();
+        let retries = ((self.attempts_per_player() - 1.0) / 4.0).clamp(0.0, 1.0);
+
+        (losses * 0.6 + retries * 0.4).clamp(0.0, 1.0)
+    }
+
+    pub fn is_significant(&self) -> bool {
+        self.players >= MIN_CALIBRATION_PLAYERS
+    }
+}
+
+impl fmt::Display for PuzzleDifficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Day {}: {} players, {:.0}% won, {:.1} attempts each, difficulty {:.2}",
+            self.day,
+            self.players,
+            self.win_rate() * 100.0,
+            self.attempts_per_player(),
+            self.score()
+        )?;
+        if let Some(moves) = self.average_winning_moves {
+            write!(f, ", {moves:.1} moves to win")?;
+        }
+        Ok(())
+    }
+}
+
+/// The range of difficulty scores that generated puzzles should land within
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyBand {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl DifficultyBand {
+    pub fn contains(&self, score: f32) -> bool {
+        self.min <= score && score <= self.max
+    }
+
+    /// How far a score sits outside of the band, or zero if it is inside
+    pub fn distance(&self, score: f32) -> f32 {
+        (self.min - score).max(score - self.max).max(0.0)
+    }
+}
+
+/// Predicts how difficult players will find a puzzle from how long the NPC took to win it,
+/// as a straight line fitted against the difficulty of past puzzles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyModel {
+    pub intercept: f32,
+    pub slope: f32,
+}
+
+impl DifficultyModel {
+    /// Fits the model to pairs of (NPC turns to win, observed difficulty score)
+    pub fn fit(samples: &[(f32, f32)]) -> Option<Self> {
+        if samples.len() < 2 {
+            return None;
+        }
+
+        let count = samples.len() as f32;
+        let mean_x = samples.iter().map(|(x, _)| x).sum::<f32>() / count;
+        let mean_y = samples.iter().map(|(_, y)| y).sum::<f32>() / count;
+
+        let covariance: f32 = samples
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        let variance: f32 = samples.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+        if variance == 0.0 {
+            return None;
+        }
+
+        let slope = covariance / variance;
+        Some(Self {
+            intercept: mean_y - slope * mean_x,
+            slope,
+        })
+    }
+
+    pub fn predict(&self, npc_turns: f32) -> f32 {
+        (self.intercept + self.slope * npc_turns).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(players: u32, attempts: u32, winners: u32) -> PuzzleDifficulty {
+        PuzzleDifficulty {
+            day: 0,
+            players,
+            attempts,
+            winners,
+            average_winning_moves: None,
+        }
+    }
+
+    #[test]
+    fn scores_harder_days_higher() {
+        let easy = day(20, 20, 20);
+        let medium = day(20, 50, 14);
+        let hard = day(20, 120, 2);
+
+        assert_eq!(easy.score(), 0.0);
+        assert!(easy.score() < medium.score());
+        assert!(medium.score() < hard.score());
+        assert!(hard.score() <= 1.0);
+        assert!(!day(0, 0, 0).is_significant());
+    }
+
+    #[test]
+    fn fits_difficulty_model() {
+        let samples = [(10.0, 0.2), (20.0, 0.4), (30.0, 0.6)];
+        let model = DifficultyModel::fit(&samples).unwrap();
+
+        assert!((model.predict(25.0) - 0.5).abs() < 0.001);
+        assert_eq!(model.predict(100.0), 1.0);
+
+        assert_eq!(DifficultyModel::fit(&samples[..1]), None);
+        assert_eq!(DifficultyModel::fit(&[(10.0, 0.2), (10.0, 0.6)]), None);
+
+        let band = DifficultyBand { min: 0.3, max: 0.5 };
+        assert!(band.contains(model.predict(20.0)));
+        assert!((band.distance(model.predict(30.0)) - 0.1).abs() < 0.001);
+    }
+}
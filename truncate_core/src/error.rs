@@ -36,4 +36,50 @@ pub enum GamePlayError {
 
     #[error("Player {player:?} doesn't have a '{tile:?}' tile")]
     PlayerDoesNotHaveTile { player: usize, tile: char },
+
+    #[error("Tiles can only be exchanged in games with action points")]
+    ExchangeUnavailable,
+    #[error("That costs {cost} action points, and you have {remaining} left")]
+    NotEnoughActionPoints { cost: u32, remaining: u32 },
+
+    #[error("Only a poor starting hand can be redrawn, and only once")]
+    MulliganUnavailable,
+
+    #[error("Game is already over")]
+    GameOver,
+    #[error("Only the next player can play")]
+    NotPlayersTurn { player: usize },
+    #[error("Player's turn has not yet started")]
+    TurnNotStarted { player: usize },
+
+    #[error("You have no appeals left")]
+    NoAppealsLeft,
+    #[error("There's no battle to appeal")]
+    NothingToAppeal,
+    #[error("None of your words were ruled invalid")]
+    NoInvalidWords,
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum LadderWinError {
+    #[error("There is no rung {rung} on the ladder")]
+    NoSuchRung { rung: u32 },
+    #[error("That opponent hasn't been unlocked yet")]
+    Locked,
+    #[error("There is no player {player} in a ladder game")]
+    NoSuchPlayer { player: usize },
+    #[error("The board for this game couldn't be generated")]
+    BoardNotGenerated,
+    #[error("Move {index} was played after the game ended")]
+    MoveAfterEnd { index: usize },
+    #[error("Move {index} couldn't be played: {error}")]
+    IllegalMove { index: usize, error: GamePlayError },
+    #[error("These moves don't end in a win")]
+    NotAWin,
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum OpeningBookError {
+    #[error("Opening book line {line} is malformed")]
+    MalformedLine { line: usize },
 }
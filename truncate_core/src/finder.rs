@@ -0,0 +1,192 @@
+use crate::{
+    board::{Board, Coordinate, Square},
+    judge::WordDict,
+    rules::Truncation,
+};
+
+/// Words that can be spelled from some of the given letters, strongest first.
+/// Longer words win battles, so strength is length, with more common words first among equals.
+pub fn suggest_words(letters: &str, dict: &WordDict, limit: usize) -> Vec<String> {
+    let Some(available) = letter_counts(letters) else {
+        return vec![];
+    };
+
+    let mut suggestions: Vec<_> = dict
+        .iter()
+        .filter(|(_, data)| !data.objectionable)
+        .filter(|(word, _)| {
+            letter_counts(word).is_some_and(|needed| {
+                needed
+                    .iter()
+                    .zip(available.iter())
+                    .all(|(needed, available)| needed <= available)
+            })
+        })
+        .collect();
+
+    suggestions.sort_by(|(a_word, a_data), (b_word, b_data)| {
+        b_word
+            .len()
+            .cmp(&a_word.len())
+            .then(b_data.rel_freq.total_cmp(&a_data.rel_freq))
+            .then(a_word.cmp(b_word))
+    });
+
+    suggestions
+        .into_iter()
+        .take(limit)
+        .map(|(word, _)| word.to_string())
+        .collect()
+}
+
+/// How many of each letter from A to Z are in the text, or `None` if it has anything else
+fn letter_counts(text: &str) -> Option<[usize; 26]> {
+    let mut counts = [0; 26];
+    for c in text.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        counts[(c.to_ascii_lowercase() as u8 - b'a') as usize] += 1;
+    }
+    Some(counts)
+}
+
+/// Whether `player` has room to play a word of this length on the board.
+/// This needs a straight run of empty land that the player can reach,
+/// which doesn't run into their own tiles at either end and join up with another word.
+pub fn fits_on_board(
+    word_length: usize,
+    board: &Board,
+    player: usize,
+    truncation: &Truncation,
+) -> bool {
+    if word_length == 0 {
+        return false;
+    }
+
+    let playable = board.playable_positions(player, truncation);
+    let is_land = |coord: Coordinate| matches!(board.get(coord), Ok(Square::Land { .. }));
+    let is_own_tile = |coord: Coordinate| match board.get(coord) {
+        Ok(Square::Occupied { player: owner, .. }) => owner == player,
+        _ => false,
+    };
+
+    for start in &playable {
+        for horizontal in [true, false] {
+            // Try every run of squares that passes through this playable square
+            for offset in 0..word_length {
+                let run: Option<Vec<_>> = (0..word_length)
+                    .map(|i| {
+                        let (x, y) = if horizontal {
+                            ((start.x + i).checked_sub(offset)?, start.y)
+                        } else {
+                            (start.x, (start.y + i).checked_sub(offset)?)
+                        };
+                        Some(Coordinate { x, y })
+                    })
+                    .collect();
+                let Some(run) = run else {
+                    continue;
+                };
+                if !run.iter().all(|c| is_land(*c)) {
+                    continue;
+                }
+
+                let (first, last) = (run[0], run[word_length - 1]);
+                let before = if horizontal {
+                    first.x.checked_sub(1).map(|x| Coordinate { x, y: first.y })
+                } else {
+                    first.y.checked_sub(1).map(|y| Coordinate { x: first.x, y })
+                };
+                let after = if horizontal {
+                    Coordinate {
+                        x: last.x + 1,
+                        y: last.y,
+                    }
+                } else {
+                    Coordinate {
+                        x: last.x,
+                        y: last.y + 1,
+                    }
+                };
+                if before.is_some_and(is_own_tile) || is_own_tile(after) {
+                    continue;
+                }
+
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::judge::WordData;
+
+    fn dict(words: &[(&str, f32)]) -> WordDict {
+        words
+            .iter()
+            .map(|(word, rel_freq)| {
+                (
+                    word.to_string(),
+                    WordData {
+                        extensions: 0,
+                        rel_freq: *rel_freq,
+                        objectionable: false,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn suggests_strongest_words() {
+        let dict = dict(&[
+            ("tea", 0.5),
+            ("eat", 0.9),
+            ("teat", 0.1),
+            ("seat", 0.8),
+            ("at", 0.9),
+            ("tee", 0.9),
+        ]);
+
+        assert_eq!(
+            suggest_words("TAET", &dict, 10),
+            vec!["teat", "eat", "tea", "at"]
+        );
+        assert_eq!(suggest_words("taet", &dict, 2), vec!["teat", "eat"]);
+        assert!(suggest_words("t?e", &dict, 10).is_empty());
+    }
+
+    #[test]
+    fn finds_room_on_board() {
+        let board = Board::from_string(
+            r###"
+            ~~ ~~ |0 ~~ ~~
+            __ __ __ ~~ ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ |1 ~~ ~~
+            "###,
+        );
+
+        assert!(fits_on_board(3, &board, 0, &Truncation::Root));
+        assert!(!fits_on_board(4, &board, 0, &Truncation::Root));
+        assert!(!fits_on_board(0, &board, 0, &Truncation::Root));
+
+        // Running into our own tile would make a different word
+        let board = Board::from_string(
+            r###"
+            ~~ ~~ ~~ |0 ~~
+            __ __ __ A0 ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ ~~ |1 ~~
+            "###,
+        );
+        assert!(fits_on_board(2, &board, 0, &Truncation::Root));
+        assert!(!fits_on_board(3, &board, 0, &Truncation::Root));
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rng::TruncateRng;
+
+/// Experiments that the server switches on for some sessions and not others,
+/// so that changes to the client can be rolled out gradually and compared.
+/// Flags missing from older servers are read as off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Flags {
+    /// Slides battle reports into place rather than showing them all at once
+    #[serde(default)]
+    pub new_battle_animation: bool,
+}
+
+/// The percentage of sessions that each experiment is switched on for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rollout {
+    pub new_battle_animation: u32,
+}
+
+impl Flags {
+    /// Buckets a session into every experiment from its seed.
+    /// Each experiment draws from its own stream, so the buckets are independent of each other.
+    pub fn assign(seed: u64, rollout: &Rollout) -> Self {
+        let rng = TruncateRng::new(seed);
+        let enabled = |stream: u64, percent: u32| rng.fork(stream).rand_range(0..100) < percent;
+
+        Self {
+            new_battle_animation: enabled(1, rollout.new_battle_animation),
+        }
+    }
+}
+
+/// Lists every flag and its value, which is stored alongside analytics events
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "new_battle_animation={}",
+            self.new_battle_animation as u8
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assigns_by_rollout() {
+        let off = Rollout::default();
+        let on = Rollout {
+            new_battle_animation: 100,
+        };
+        let half = Rollout {
+            new_battle_animation: 50,
+        };
+
+        assert!((0..100).all(|seed| !Flags::assign(seed, &off).new_battle_animation));
+        assert!((0..100).all(|seed| Flags::assign(seed, &on).new_battle_animation));
+
+        let enabled = (0..1000)
+            .filter(|seed| Flags::assign(*seed, &half).new_battle_animation)
+            .count();
+        assert!((400..600).contains(&enabled), "{enabled} of 1000 enabled");
+
+        assert_eq!(Flags::assign(7, &half), Flags::assign(7, &half));
+        assert_eq!(Flags::assign(7, &on).to_string(), "new_battle_animation=1");
+    }
+}
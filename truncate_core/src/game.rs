@@ -1,20 +1,28 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::ops::Sub;
 
+use serde::{Deserialize, Serialize};
 use time::Duration;
 use xxhash_rust::xxh3;
 
-use crate::bag::TileBag;
-use crate::board::{Coordinate, Square};
+use crate::bag::{hand_divergence, hand_tile, has_vowel, TileBag, TileDistribution};
+use crate::board::{Coordinate, Square, WordAges};
 use crate::error::GamePlayError;
+use crate::generation::generate_elevation;
 use crate::judge::{Outcome, WordDict};
-use crate::reporting::{self, BoardChange, BoardChangeAction, BoardChangeDetail, TimeChange};
-use crate::rules::{self, GameRules, OvertimeRule};
+use crate::packs::DictionaryPack;
+use crate::reporting::{
+    self, BattleWord, BoardChange, BoardChangeAction, BoardChangeDetail, HandChange, MovePreview,
+    ThinkChange, TimeChange,
+};
+use crate::rules::{self, GameRules, Language, OvertimeRule, Tiebreaker};
 
 use super::board::Board;
 use super::judge::Judge;
 use super::moves::Move;
-use super::player::Player;
+use super::player::{Hand, Player};
 use super::reporting::Change;
 
 pub const GAME_COLOR_BLUE: (u8, u8, u8) = (80_u8, 167_u8, 232_u8);
@@ -31,6 +39,74 @@ pub const GAME_COLORS: [(u8, u8, u8); 5] = [
     GAME_COLOR_YELLOW,
 ];
 
+/// Reasons a game can be brought to an end without a winner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Adjudication {
+    /// The same position was reached too many times
+    Repetition { occurrences: usize },
+    /// No player gained any tiles for too many turns
+    NoProgress { turns: usize },
+    /// Nobody played a move for too long
+    Inactivity,
+    /// The player to move had no tiles left to place, and nobody held more territory
+    OutOfTiles,
+}
+
+impl fmt::Display for Adjudication {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Adjudication::Repetition { occurrences } => write!(
+                f,
+                "Draw by repetition, the same position was reached {occurrences} times"
+            ),
+            Adjudication::NoProgress { turns } => write!(
+                f,
+                "Draw by lack of progress, no tiles were gained in {turns} turns"
+            ),
+            Adjudication::Inactivity => {
+                write!(f, "Draw by inactivity, no moves were played for too long")
+            }
+            Adjudication::OutOfTiles => {
+                write!(
+                    f,
+                    "Draw on territory, a player ran out of tiles with the territory level"
+                )
+            }
+        }
+    }
+}
+
+/// How a finished game was decided
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameOutcome {
+    /// A player won, either outright or on a tiebreaker when the game would otherwise have been drawn
+    Won {
+        winner: usize,
+        tiebreaker: Option<Tiebreaker>,
+    },
+    /// Nobody won, for the reason given
+    Draw(Adjudication),
+}
+
+impl fmt::Display for GameOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameOutcome::Won {
+                winner,
+                tiebreaker: None,
+            } => write!(f, "Player {winner} won"),
+            GameOutcome::Won {
+                winner,
+                tiebreaker: Some(tiebreaker),
+            } => write!(
+                f,
+                "Player {winner} won on {tiebreaker}, in a game that would have been drawn"
+            ),
+            GameOutcome::Draw(adjudication) => write!(f, "{adjudication}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Game {
     pub rules: GameRules,
@@ -42,11 +118,29 @@ pub struct Game {
     pub turn_count: u32,
     pub player_turn_count: Vec<u32>,
     pub recent_changes: Vec<Change>,
+    /// How long each turn so far was thought over, in the order they were played
+    pub think_times: Vec<ThinkChange>,
     pub started_at: Option<u64>,
     pub game_ends_at: Option<u64>,
     pub next_player: Option<usize>,
     pub paused: bool,
     pub winner: Option<usize>,
+    pub adjudication: Option<Adjudication>,
+    /// What decided the winner of a game that would otherwise have been drawn
+    pub tiebreaker: Option<Tiebreaker>,
+    /// How many times each position has been seen, keyed by `position_hash`
+    pub position_history: HashMap<u64, usize>,
+    /// Consecutive turns in which the moving player didn't gain any tiles
+    pub no_progress_turns: usize,
+    /// The last turn, if its battle can still be appealed
+    pub appealable: Option<Box<AppealableTurn>>,
+}
+
+/// A turn that fought a battle, with the game as it was beforehand so that the battle can be re-judged
+#[derive(Debug, Clone)]
+pub struct AppealableTurn {
+    pub before: Game,
+    pub played: Move,
 }
 
 // TODO: Move this to a helper file somewhere
@@ -70,17 +164,24 @@ impl Game {
         Self {
             players: Vec::with_capacity(2),
             board,
-            bag: TileBag::generation(rules.tile_generation, tile_seed),
-            judge: Judge::default(),
+            bag: TileBag::language(rules.language, rules.tile_generation, tile_seed)
+                .with_blanks(rules.blank_tiles),
+            judge: Judge::for_language(rules.language),
             battle_count: 0,
             turn_count: 0,
             player_turn_count: Vec::with_capacity(2),
             recent_changes: vec![],
+            think_times: vec![],
             started_at: None,
             game_ends_at: None,
             next_player,
             paused: false,
             winner: None,
+            adjudication: None,
+            tiebreaker: None,
+            position_history: HashMap::new(),
+            no_progress_turns: 0,
+            appealable: None,
             rules,
         }
     }
@@ -102,31 +203,47 @@ impl Game {
         Self {
             players: Vec::with_capacity(2),
             board,
-            bag: TileBag::generation(rules.tile_generation, tile_seed),
-            judge: Judge::default(),
+            bag: TileBag::language(rules.language, rules.tile_generation, tile_seed)
+                .with_blanks(rules.blank_tiles),
+            judge: Judge::for_language(rules.language),
             battle_count: 0,
             turn_count: 0,
             player_turn_count: Vec::with_capacity(2),
             recent_changes: vec![],
+            think_times: vec![],
             started_at: None,
             game_ends_at: None,
             next_player,
             paused: false,
             winner: None,
+            adjudication: None,
+            tiebreaker: None,
+            position_history: HashMap::new(),
+            no_progress_turns: 0,
+            appealable: None,
             rules,
         }
     }
 
-    pub fn add_player(&mut self, name: String) {
-        let time_allowance = match self.rules.timing {
-            rules::Timing::PerPlayer {
-                time_allowance,
-                overtime_rule: _,
-            } => Some(Duration::new(time_allowance as i64, 0)),
+    /// The seed all of the game's randomness comes from,
+    /// so the same seed and moves will always play out the same way
+    pub fn seed(&self) -> u64 {
+        self.bag.seed()
+    }
+
+    fn time_allowance(&self) -> Option<Duration> {
+        match self.rules.timing {
+            rules::Timing::PerPlayer { time_allowance, .. } => {
+                Some(Duration::new(time_allowance as i64, 0))
+            }
             rules::Timing::None => None,
             rules::Timing::Periodic { .. } => None,
             _ => unimplemented!(),
-        };
+        }
+    }
+
+    pub fn add_player(&mut self, name: String) {
+        let time_allowance = self.time_allowance();
         self.players.push(Player::new(
             name,
             self.players.len(),
@@ -135,9 +252,123 @@ impl Game {
             time_allowance,
             GAME_COLORS[self.players.len()],
         ));
+        // Opening hands are dealt before any turn, so there's nothing to report them in
+        self.bag.take_changes(self.players.len() - 1);
         self.player_turn_count.push(0);
     }
 
+    /// Takes a player out of a game that hasn't started, returning their hand to the bag.
+    /// Everyone after them moves up a seat, taking on that seat's colour.
+    pub fn remove_player(&mut self, index: usize) {
+        if self.started_at.is_some() || index >= self.players.len() {
+            return;
+        }
+
+        let removed = self.players.remove(index);
+        for tile in removed.hand.0 {
+            self.bag.return_tile(tile);
+        }
+        self.player_turn_count.remove(index);
+
+        for (seat, player) in self.players.iter_mut().enumerate().skip(index) {
+            player.index = seat;
+            player.color = GAME_COLORS[seat];
+        }
+    }
+
+    /// Switches a game that hasn't started to another language,
+    /// refilling the bag with that language's letters and dealing everyone a fresh hand from it.
+    /// Words go back to being judged by the language's usual dictionary.
+    pub fn set_language(&mut self, language: Language) {
+        if self.started_at.is_some() {
+            return;
+        }
+
+        self.rules.language = language;
+        self.rules.dictionary_pack = None;
+        self.judge = Judge::for_language(language);
+        self.refill_bag();
+    }
+
+    /// Judges the game's words against a downloadable pack, playing with the letters of the pack's language,
+    /// or goes back to the language's usual dictionary
+    pub fn set_dictionary_pack(&mut self, pack: Option<&DictionaryPack>) {
+        if self.started_at.is_some() {
+            return;
+        }
+
+        if let Some(pack) = pack {
+            self.set_language(pack.language);
+        }
+        self.rules.dictionary_pack = pack.map(DictionaryPack::reference);
+    }
+
+    /// Sets how many blanks are in the bag of a game that hasn't started,
+    /// dealing everyone a fresh hand from the new bag
+    pub fn set_blank_tiles(&mut self, blank_tiles: usize) {
+        if self.started_at.is_some() {
+            return;
+        }
+
+        self.rules.blank_tiles = blank_tiles;
+        self.refill_bag();
+    }
+
+    /// Replaces the rules of a game that hasn't started, keeping the game's language, dictionary, and board.
+    /// Everyone's clock is reset and they're dealt a fresh hand, to suit the new rules.
+    pub fn set_rules(&mut self, rules: GameRules) {
+        if self.started_at.is_some() {
+            return;
+        }
+
+        self.rules = GameRules {
+            language: self.rules.language,
+            dictionary_pack: self.rules.dictionary_pack.clone(),
+            ..rules
+        };
+        let time_allowance = self.time_allowance();
+        for player in self.players.iter_mut() {
+            player.hand_capacity = self.rules.hand_size;
+            player.allotted_time = time_allowance;
+            player.time_remaining = time_allowance;
+        }
+        self.refill_bag();
+    }
+
+    /// Rebuilds the bag from the rules, with the same seed, and redeals every hand from it
+    fn refill_bag(&mut self) {
+        self.bag = TileBag::language(
+            self.rules.language,
+            self.rules.tile_generation,
+            Some(self.seed()),
+        )
+        .with_blanks(self.rules.blank_tiles);
+        self.redeal_hands();
+    }
+
+    /// Fills the bag with a set number of each letter rather than the rules' usual bag,
+    /// with the same seed, and redeals every hand from it. Does nothing once a move has been played.
+    pub fn use_tile_distribution(&mut self, distribution: TileDistribution) {
+        if self.turn_count > 0 {
+            return;
+        }
+
+        self.bag =
+            TileBag::custom(distribution, Some(self.seed())).with_blanks(self.rules.blank_tiles);
+        self.redeal_hands();
+    }
+
+    fn redeal_hands(&mut self) {
+        for player in self.players.iter_mut() {
+            player.hand = Hand(
+                (0..player.hand_capacity)
+                    .map(|_| self.bag.draw_tile())
+                    .collect(),
+            );
+            self.bag.take_changes(player.index);
+        }
+    }
+
     pub fn get_player(&self, player: usize) -> Option<&Player> {
         // TODO: Lookup player by `index` field rather than vec position
         self.players.get(player)
@@ -147,6 +378,13 @@ impl Game {
         let now = now();
         self.started_at = Some(now);
 
+        if self.rules.terrain == rules::Terrain::Elevated && self.board.elevation.is_none() {
+            // Seed the hills from the board itself, so every copy of this game raises the same ones
+            let mut hasher = xxh3::Xxh3::new();
+            self.board.squares.hash(&mut hasher);
+            self.board.elevation = Some(generate_elevation(&self.board, hasher.finish() as u32));
+        }
+
         match self.rules.timing {
             rules::Timing::PerPlayer { .. } | rules::Timing::None => {
                 self.players[self.next_player.unwrap()].turn_starts_no_later_than = Some(now);
@@ -163,6 +401,8 @@ impl Game {
             }),
             _ => unimplemented!(),
         }
+
+        self.track_repetition();
     }
 
     pub fn any_player_is_overtime(&self) -> Option<usize> {
@@ -173,7 +413,9 @@ impl Game {
                 continue;
             };
             if let Some(turn_starts) = player.turn_starts_no_later_than {
-                let elapsed_time = now().saturating_sub(turn_starts);
+                let elapsed_time = now()
+                    .saturating_sub(turn_starts)
+                    .saturating_sub(self.clock_delay());
                 time_remaining -= Duration::seconds(elapsed_time as i64);
             }
 
@@ -191,6 +433,34 @@ impl Game {
         most_overtime_player.map(|(_, player_number)| player_number)
     }
 
+    /// Seconds at the start of each turn that don't come off the player's clock
+    pub fn clock_delay(&self) -> u64 {
+        match self.rules.timing {
+            rules::Timing::PerPlayer { delay, .. } => delay as u64,
+            _ => 0,
+        }
+    }
+
+    /// When the player must move by, if the rules limit how long a single turn can take
+    pub fn move_deadline(&self, player: usize) -> Option<u64> {
+        let rules::Timing::PerPlayer {
+            move_limit: Some(move_limit),
+            ..
+        } = self.rules.timing
+        else {
+            return None;
+        };
+        let turn_starts = self.players.get(player)?.turn_starts_no_later_than?;
+        Some(turn_starts + move_limit as u64)
+    }
+
+    /// The player whose turn has run on past the rules' limit for a single move
+    pub fn player_over_move_limit(&self) -> Option<usize> {
+        let player = self.next_player?;
+        let deadline = self.move_deadline(player)?;
+        (now() > deadline).then_some(player)
+    }
+
     pub fn game_is_overtime(&self) -> bool {
         let Some(started_at) = self.started_at else {
             return false;
@@ -236,6 +506,14 @@ impl Game {
             }
         }
 
+        if let Some(slow_player) = self.player_over_move_limit() {
+            if self.winner.is_none() {
+                println!("{slow_player} took too long over their move! Defeating player.");
+            }
+            self.board.defeat_player(slow_player);
+            self.winner = Some((slow_player + 1) % 2);
+        }
+
         if self.game_is_overtime() {
             match &self.rules.win_metric {
                 rules::WinMetric::TownProximity | rules::WinMetric::ObeliskProximity => {
@@ -315,6 +593,163 @@ impl Game {
         }
     }
 
+    /// Whether the game has concluded, either with a winner or by adjudication
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some() || self.adjudication.is_some()
+    }
+
+    /// Whether the bag has run out for good, leaving only the tiles already in hand to play
+    pub fn in_endgame(&self) -> bool {
+        !self.bag.refills() && self.bag.remaining_tiles().is_empty()
+    }
+
+    /// How the game was decided, once it's over
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        match (self.winner, &self.adjudication) {
+            (Some(winner), _) => Some(GameOutcome::Won {
+                winner,
+                tiebreaker: self.tiebreaker,
+            }),
+            (None, Some(adjudication)) => Some(GameOutcome::Draw(adjudication.clone())),
+            (None, None) => None,
+        }
+    }
+
+    /// Ends the game without a winner, unless one of the rules' tiebreakers can pick one
+    pub fn adjudicate(&mut self, adjudication: Adjudication) {
+        if self.is_over() {
+            return;
+        }
+
+        if let Some((winner, tiebreaker)) = self.break_tie() {
+            (0..self.players.len())
+                .filter(|p| *p != winner)
+                .for_each(|p| self.board.defeat_player(p));
+            self.winner = Some(winner);
+            self.tiebreaker = Some(tiebreaker);
+            return;
+        }
+
+        self.adjudication = Some(adjudication);
+    }
+
+    /// How much a player has of what a tiebreaker measures
+    pub fn tiebreak_score(&self, tiebreaker: Tiebreaker, player: usize) -> usize {
+        match tiebreaker {
+            Tiebreaker::Territory => self.board.territory(player),
+            Tiebreaker::TilesRemaining => self.players.get(player).map_or(0, |p| p.hand.len()),
+            Tiebreaker::WordLength => self
+                .board
+                .words_of_player(player)
+                .iter()
+                .map(|word| word.len())
+                .sum(),
+        }
+    }
+
+    /// The first of the rules' tiebreakers that puts a single player ahead of the rest
+    fn break_tie(&self) -> Option<(usize, Tiebreaker)> {
+        self.rules.tiebreakers.iter().find_map(|tiebreaker| {
+            let scores: Vec<_> = (0..self.players.len())
+                .map(|p| self.tiebreak_score(*tiebreaker, p))
+                .collect();
+            let best = scores.iter().max().copied()?;
+            let mut leaders = (0..self.players.len()).filter(|p| scores[*p] == best);
+
+            match (leaders.next(), leaders.next()) {
+                (Some(winner), None) => Some((winner, *tiebreaker)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Identifies the current position by the board contents and who is to play next,
+    /// hashing equally for the same position however it was reached
+    pub fn position_hash(&self) -> u64 {
+        let mut hasher = xxh3::Xxh3::new();
+        self.board.zobrist().hash(&mut hasher);
+        self.next_player.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn track_repetition(&mut self) {
+        let occurrences = self
+            .position_history
+            .entry(self.position_hash())
+            .or_default();
+        *occurrences += 1;
+        let occurrences = *occurrences;
+
+        if let rules::RepetitionRule::Limit {
+            occurrences: allowed,
+            outcome,
+        } = &self.rules.repetition
+        {
+            if occurrences >= *allowed {
+                let outcome = outcome.clone();
+                self.resolve_stalemate(outcome, Adjudication::Repetition { occurrences });
+            }
+        }
+    }
+
+    fn track_progress(&mut self, player: usize, territory_before: usize) {
+        if self.board.territory(player) > territory_before {
+            self.no_progress_turns = 0;
+            return;
+        }
+        self.no_progress_turns += 1;
+
+        if let rules::NullMoveRule::Limit { turns, outcome } = &self.rules.null_moves {
+            if self.no_progress_turns >= *turns {
+                let outcome = outcome.clone();
+                let turns = self.no_progress_turns;
+                self.resolve_stalemate(outcome, Adjudication::NoProgress { turns });
+            }
+        }
+    }
+
+    /// Without redraws, the game can't carry on once there is nothing left to place
+    fn track_tiles(&mut self) {
+        let out_of_tiles = match self.next_player {
+            Some(next_player) => self.players[next_player].hand.is_empty(),
+            None => self.players.iter().all(|p| p.hand.is_empty()),
+        };
+
+        if out_of_tiles {
+            self.resolve_stalemate(
+                rules::StalemateOutcome::MostTerritory,
+                Adjudication::OutOfTiles,
+            );
+        }
+    }
+
+    fn resolve_stalemate(&mut self, outcome: rules::StalemateOutcome, adjudication: Adjudication) {
+        if self.is_over() {
+            return;
+        }
+
+        match outcome {
+            rules::StalemateOutcome::Draw => self.adjudicate(adjudication),
+            rules::StalemateOutcome::MostTerritory => {
+                let territories: Vec<_> = (0..self.players.len())
+                    .map(|p| self.board.territory(p))
+                    .collect();
+                let best = territories.iter().max().copied().unwrap_or_default();
+                let mut leaders = (0..self.players.len()).filter(|p| territories[*p] == best);
+
+                match (leaders.next(), leaders.next()) {
+                    (Some(winner), None) => {
+                        (0..self.players.len())
+                            .filter(|p| *p != winner)
+                            .for_each(|p| self.board.defeat_player(p));
+                        self.winner = Some(winner);
+                    }
+                    _ => self.adjudicate(adjudication),
+                }
+            }
+        }
+    }
+
     pub fn resign_player(&mut self, resigning_player: usize) {
         self.board.defeat_player(resigning_player);
         self.winner = Some((resigning_player + 1) % 2);
@@ -379,16 +814,25 @@ impl Game {
         attacker_dictionary: Option<&WordDict>,
         defender_dictionary: Option<&WordDict>,
         cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
-    ) -> Result<Option<usize>, String> {
-        if self.winner.is_some() {
-            return Err("Game is already over".into());
+    ) -> Result<Option<usize>, GamePlayError> {
+        if self.is_over() {
+            return Err(GamePlayError::GameOver);
         }
 
         let player = match next_move {
             Move::Place { player, .. } => player,
             Move::Swap { player, .. } => player,
+            Move::Exchange { player, .. } => player,
+            Move::Mulligan { player } => player,
         };
 
+        // Starting hands are redrawn before the player's first turn, so needn't wait for it
+        if matches!(next_move, Move::Mulligan { .. }) {
+            self.recent_changes =
+                self.make_move(next_move, attacker_dictionary, defender_dictionary, None)?;
+            return Ok(self.winner);
+        }
+
         self.calculate_game_over(Some(player));
         if self.winner.is_some() {
             return Ok(self.winner);
@@ -398,19 +842,35 @@ impl Game {
             rules::Timing::Periodic { .. } => { /* All players can play */ }
             _ => {
                 if player != self.next_player.unwrap() {
-                    return Err("Only the next player can play".into());
+                    return Err(GamePlayError::NotPlayersTurn { player });
                 }
             }
         }
 
         if let Some(turn_start) = self.players[player].turn_starts_no_sooner_than {
             if turn_start > now() {
-                return Err("Player's turn has not yet started".into());
+                return Err(GamePlayError::TurnNotStarted { player });
             }
         } else {
-            return Err("Player's turn has not yet started".into());
+            return Err(GamePlayError::TurnNotStarted { player });
+        }
+
+        let cost = next_move
+            .action_cost(&self.rules.turn_economy)
+            .ok_or(GamePlayError::ExchangeUnavailable)?;
+        let spent = self.players[player].action_points_spent;
+        let remaining = self.rules.turn_economy.budget().saturating_sub(spent);
+        if cost > remaining {
+            return Err(GamePlayError::NotEnoughActionPoints { cost, remaining });
         }
 
+        let territory_before = self.board.territory(player);
+
+        // Keep the game as it was before this turn, in case its battle is appealed
+        let previous_appeal = self.appealable.take();
+        let before = (!matches!(self.rules.appeals, rules::Appeals::Disallowed))
+            .then(|| (self.clone(), next_move.clone()));
+
         self.recent_changes = match self.make_move(
             next_move,
             attacker_dictionary,
@@ -418,12 +878,22 @@ impl Game {
             cached_word_judgements,
         ) {
             Ok(changes) => changes,
-            Err(msg) => {
-                println!("Error in game: {}", msg);
-                return Err(format!("{msg}"));
+            Err(e) => {
+                println!("Error in game: {}", e);
+                self.appealable = previous_appeal;
+                return Err(e);
             }
         };
 
+        if self
+            .recent_changes
+            .iter()
+            .any(|c| matches!(c, Change::Battle(_)))
+        {
+            self.appealable =
+                before.map(|(before, played)| Box::new(AppealableTurn { before, played }));
+        }
+
         // Track any new tiles that the player may have gained vision of from this turn
         {
             let seen = &mut self.players[player].seen_tiles;
@@ -446,8 +916,45 @@ impl Game {
             }
         }
 
+        // With action points, the turn carries on while the player can afford to place another tile
+        let spent = spent + cost;
+        let economy = &self.rules.turn_economy;
+        if economy.budget().saturating_sub(spent) >= economy.place_cost()
+            && Judge::winner(&self.board).is_none()
+        {
+            self.players[player].action_points_spent = spent;
+            return Ok(self.winner);
+        }
+        self.players[player].action_points_spent = 0;
+        self.players[player].drawn_this_turn = 0;
+
+        // Tiles cut off this turn start withering, and the player's own withered tiles are truncated
+        if matches!(self.rules.truncation, rules::Truncation::Withering) {
+            let withered = self
+                .board
+                .wither(player, &mut self.bag, attacker_dictionary);
+            self.recent_changes.extend(withered);
+        }
+
         self.turn_count += 1;
         self.player_turn_count[player] += 1;
+        let this_player = &self.players[player];
+        if let Some(turn_start) = this_player
+            .turn_starts_no_later_than
+            .or(this_player.turn_starts_no_sooner_than)
+        {
+            let think = ThinkChange {
+                player,
+                turn: self.turn_count,
+                secs: now().saturating_sub(turn_start),
+            };
+            self.think_times.push(think.clone());
+            self.recent_changes.push(Change::Think(think));
+        }
+        if let Some(aging) = &mut self.board.aging {
+            aging.turn = self.turn_count;
+        }
+        self.swap_hands_if_due();
 
         // Check for winning via defeated towns or artifacts
         if let Some(winner) = Judge::winner(&(self.board)) {
@@ -476,6 +983,32 @@ impl Game {
 
             *time_remaining -= Duration::seconds(turn_duration as i64);
 
+            let (increment, delay) = match &self.rules.timing {
+                rules::Timing::PerPlayer {
+                    increment, delay, ..
+                } => (*increment as u64, *delay as u64),
+                _ => (0, 0),
+            };
+            // The delay is handed back once the turn is over, so it never counts against the player
+            let delayed = turn_duration.min(delay);
+            if delayed > 0 {
+                *time_remaining += Duration::seconds(delayed as i64);
+                self.recent_changes.push(Change::Time(TimeChange {
+                    player,
+                    time_change: delayed as isize,
+                    reason: "Delay".into(),
+                }));
+            }
+            // Players who have already run out of time don't get an increment to rescue them
+            if increment > 0 && !time_remaining.is_negative() {
+                *time_remaining += Duration::seconds(increment as i64);
+                self.recent_changes.push(Change::Time(TimeChange {
+                    player,
+                    time_change: increment as isize,
+                    reason: "Increment".into(),
+                }));
+            }
+
             let overtime_rule = match &self.rules.timing {
                 rules::Timing::PerPlayer { overtime_rule, .. } => Some(overtime_rule),
                 _ => None,
@@ -534,7 +1067,198 @@ impl Game {
             }
         }
 
-        Ok(None)
+        self.track_progress(player, territory_before);
+        self.track_repetition();
+        self.track_tiles();
+
+        Ok(self.winner)
+    }
+
+    /// Re-judges the battle from the last turn, checking the appellant's words against a wider dictionary.
+    /// The appeal is used up whether or not the outcome changes.
+    pub fn appeal(
+        &mut self,
+        player: usize,
+        dictionary: Option<&WordDict>,
+        extended_dictionary: &WordDict,
+    ) -> Result<Option<usize>, GamePlayError> {
+        if self.is_over() {
+            return Err(GamePlayError::GameOver);
+        }
+
+        let allowed = match self.rules.appeals {
+            rules::Appeals::Disallowed => 0,
+            rules::Appeals::Allowed { per_player } => per_player,
+        };
+        let Some(appellant) = self.players.get(player) else {
+            return Err(GamePlayError::NonExistentPlayer { index: player });
+        };
+        if appellant.appeals_used >= allowed {
+            return Err(GamePlayError::NoAppealsLeft);
+        }
+
+        let (Some(turn), Some(original)) = (
+            self.appealable.as_deref(),
+            self.recent_changes.iter().find_map(|c| match c {
+                Change::Battle(battle) => Some(battle.clone()),
+                _ => None,
+            }),
+        ) else {
+            return Err(GamePlayError::NothingToAppeal);
+        };
+
+        let attacker = match turn.played {
+            Move::Place { player, .. }
+            | Move::Swap { player, .. }
+            | Move::Exchange { player, .. }
+            | Move::Mulligan { player } => player,
+        };
+        let disputed = if player == attacker {
+            &original.attackers
+        } else {
+            &original.defenders
+        };
+        if !disputed.iter().any(|word| word.valid == Some(false)) {
+            return Err(GamePlayError::NoInvalidWords);
+        }
+
+        let (attacker_dictionary, defender_dictionary) = if player == attacker {
+            (Some(extended_dictionary), dictionary)
+        } else {
+            (dictionary, Some(extended_dictionary))
+        };
+        let mut rejudged = turn.before.clone();
+        rejudged.play_turn(
+            turn.played.clone(),
+            attacker_dictionary,
+            defender_dictionary,
+            None,
+        )?;
+
+        let rejudged_outcome = rejudged.recent_changes.iter().find_map(|c| match c {
+            Change::Battle(battle) => Some(battle.outcome.clone()),
+            _ => None,
+        });
+        let amendment = reporting::BattleAmendment {
+            appellant: player,
+            overturned: rejudged_outcome != Some(original.outcome.clone()),
+        };
+
+        if amendment.overturned {
+            // The clocks have kept running since the turn was first played
+            for (replayed, current) in rejudged.players.iter_mut().zip(&self.players) {
+                replayed.time_remaining = current.time_remaining;
+                replayed.turn_starts_no_later_than = current.turn_starts_no_later_than;
+                replayed.turn_starts_no_sooner_than = current.turn_starts_no_sooner_than;
+                replayed.appeals_used = current.appeals_used;
+            }
+            // The same tiles were drawn the first time around, so only the board and battle are news
+            rejudged
+                .recent_changes
+                .retain(|c| !matches!(c, Change::Hand(_)));
+            for change in &mut rejudged.recent_changes {
+                if let Change::Battle(battle) = change {
+                    battle.amendment = Some(amendment.clone());
+                }
+            }
+            *self = rejudged;
+        } else {
+            self.recent_changes = vec![Change::Battle(reporting::BattleReport {
+                amendment: Some(amendment),
+                ..original
+            })];
+        }
+
+        self.appealable = None;
+        self.players[player].appeals_used += 1;
+
+        Ok(self.winner)
+    }
+
+    /// Works out what a move would do by making it on a copy of the game, leaving this one untouched.
+    /// Swaps never start battles, so a swap's preview only shows its words changing.
+    pub fn preview_move(
+        &self,
+        game_move: Move,
+        attacker_dictionary: Option<&WordDict>,
+        defender_dictionary: Option<&WordDict>,
+    ) -> Result<MovePreview, GamePlayError> {
+        let (player, player_reported_positions) = match &game_move {
+            Move::Place {
+                player, position, ..
+            } => (*player, vec![*position]),
+            Move::Swap { player, positions } => (*player, positions.to_vec()),
+            Move::Exchange { player, .. } | Move::Mulligan { player } => (*player, vec![]),
+        };
+        let Some(seen_tiles) = self.get_player(player).map(|p| &p.seen_tiles) else {
+            return Err(GamePlayError::NonExistentPlayer { index: player });
+        };
+
+        let positions: Vec<_> = player_reported_positions
+            .into_iter()
+            .map(|position| {
+                self.board.map_player_coord_to_game(
+                    player,
+                    position,
+                    &self.rules.visibility,
+                    &self.rules.board_orientation,
+                    seen_tiles,
+                )
+            })
+            .collect();
+
+        let mut dry_run = self.clone();
+        let changes =
+            dry_run.make_move(game_move, attacker_dictionary, defender_dictionary, None)?;
+
+        Ok(MovePreview {
+            before: self.words_through(&positions, attacker_dictionary),
+            after: dry_run.words_through(&positions, attacker_dictionary),
+            battles: changes
+                .into_iter()
+                .filter_map(|change| match change {
+                    Change::Battle(battle) => Some(battle),
+                    _ => None,
+                })
+                .collect(),
+        })
+    }
+
+    /// Judges every word of two or more letters that runs through the given squares
+    fn words_through(
+        &self,
+        positions: &[Coordinate],
+        dictionary: Option<&WordDict>,
+    ) -> Vec<BattleWord> {
+        let mut words: Vec<Vec<Coordinate>> = vec![];
+        for position in positions {
+            for word in self.board.get_words(*position) {
+                if word.len() > 1 && !words.contains(&word) {
+                    words.push(word);
+                }
+            }
+        }
+
+        self.board
+            .word_strings(&words)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|word| {
+                let valid = self.judge.valid(
+                    &word,
+                    &self.rules.win_condition,
+                    dictionary,
+                    None,
+                    &mut None,
+                );
+                BattleWord {
+                    valid: Some(valid.is_some()),
+                    meanings: None,
+                    resolved_word: valid.unwrap_or_else(|| word.clone()),
+                    original_word: word,
+                }
+            })
+            .collect()
     }
 
     pub fn make_move(
@@ -578,7 +1302,8 @@ impl Game {
                     return Err(GamePlayError::NonAdjacentPlace);
                 }
 
-                if !self.players[player].has_tile(tile) {
+                // Blanks are taken from the hand, whichever letter they're played as
+                if !self.players[player].has_tile(hand_tile(tile)) {
                     return Err(GamePlayError::PlayerDoesNotHaveTile { player, tile });
                 }
 
@@ -588,7 +1313,13 @@ impl Game {
                         .set(position, player, tile, attacker_dictionary)?,
                     action: BoardChangeAction::Added,
                 }));
-                changes.push(self.players[player].use_tile(tile, &mut self.bag)?);
+                self.mark_modified(&[position]);
+                changes.push(self.players[player].use_tile(
+                    hand_tile(tile),
+                    &mut self.bag,
+                    &self.rules.tile_draw,
+                )?);
+                changes.extend(self.bag.take_changes(player).into_iter().map(Change::Bag));
 
                 self.resolve_attack(
                     player,
@@ -687,9 +1418,145 @@ impl Game {
                     }
                     None => {}
                 }
+                self.mark_modified(&positions);
 
                 Ok(swap_result)
             }
+            Move::Exchange { player, tile } => {
+                let Some(exchanger) = self.players.get_mut(player) else {
+                    return Err(GamePlayError::NonExistentPlayer { index: player });
+                };
+                let Some(index) = exchanger.hand.find(tile) else {
+                    return Err(GamePlayError::PlayerDoesNotHaveTile { player, tile });
+                };
+
+                // Draw before returning, so the same tile can't come straight back
+                let drawn = self.bag.draw_tile();
+                self.bag.return_tile(tile);
+                exchanger.hand.replace(index, drawn);
+
+                changes.push(Change::Hand(HandChange {
+                    player,
+                    removed: vec![tile],
+                    added: vec![drawn],
+                }));
+                changes.extend(self.bag.take_changes(player).into_iter().map(Change::Bag));
+
+                Ok(changes)
+            }
+            Move::Mulligan { player } => {
+                if self.get_player(player).is_none() {
+                    return Err(GamePlayError::NonExistentPlayer { index: player });
+                }
+                if !self.can_mulligan(player) {
+                    return Err(GamePlayError::MulliganUnavailable);
+                }
+
+                // Draw before returning, so the same hand can't come straight back
+                let returned = self.players[player].hand.0.clone();
+                let drawn: Vec<_> = returned.iter().map(|_| self.bag.draw_tile()).collect();
+                for tile in &returned {
+                    self.bag.return_tile(*tile);
+                }
+                self.players[player].hand = Hand(drawn.clone());
+                self.players[player].mulliganed = true;
+
+                changes.push(Change::Hand(HandChange {
+                    player,
+                    removed: returned,
+                    added: drawn,
+                }));
+                changes.extend(self.bag.take_changes(player).into_iter().map(Change::Bag));
+
+                Ok(changes)
+            }
+        }
+    }
+
+    /// How far a player's hand strays from the odds of drawing each tile from the bag, in bits
+    pub fn hand_divergence(&self, player: usize) -> Option<f32> {
+        let hand = &self.get_player(player)?.hand;
+        Some(hand_divergence(&hand.0, &self.bag.tile_odds()) as f32)
+    }
+
+    /// Whether a player can still redraw their starting hand, which the rules
+    /// allow once for poor hands, before the player has taken a turn
+    pub fn can_mulligan(&self, player: usize) -> bool {
+        let Some(this_player) = self.get_player(player) else {
+            return false;
+        };
+        if this_player.mulliganed
+            || self
+                .player_turn_count
+                .get(player)
+                .is_some_and(|turns| *turns > 0)
+        {
+            return false;
+        }
+        // Bags that never refill might not hold enough to redraw from
+        if !self.bag.refills() && self.bag.remaining_tiles().len() < this_player.hand.len() {
+            return false;
+        }
+
+        match self.rules.mulligan {
+            rules::Mulligan::None => false,
+            rules::Mulligan::NoVowels => !has_vowel(&this_player.hand.0),
+            rules::Mulligan::Entropy {
+                max_divergence_bits,
+            } => {
+                !has_vowel(&this_player.hand.0)
+                    || self
+                        .hand_divergence(player)
+                        .is_some_and(|bits| bits > max_divergence_bits)
+            }
+        }
+    }
+
+    /// How many more turns will be played before hands are next swapped, for rules that swap them
+    pub fn turns_until_hand_swap(&self) -> Option<u64> {
+        let rules::HandSwaps::Every { turns } = self.rules.hand_swaps else {
+            return None;
+        };
+        if turns == 0 {
+            return None;
+        }
+        Some((turns - self.turn_count % turns) as u64)
+    }
+
+    /// Passes every hand along to the next player, if the rules have scheduled a swap for this turn
+    pub(crate) fn swap_hands_if_due(&mut self) {
+        let rules::HandSwaps::Every { turns } = self.rules.hand_swaps else {
+            return;
+        };
+        if turns == 0 || self.turn_count == 0 || !self.turn_count.is_multiple_of(turns) {
+            return;
+        }
+
+        let hands: Vec<_> = self.players.iter().map(|p| p.hand.clone()).collect();
+        for (index, player) in self.players.iter_mut().enumerate() {
+            let incoming = hands[(index + 1) % hands.len()].clone();
+            self.recent_changes.push(Change::Hand(HandChange {
+                player: index,
+                removed: player.hand.0.clone(),
+                added: incoming.0.clone(),
+            }));
+            player.hand = incoming;
+        }
+    }
+
+    /// Notes that squares changed this turn, for rules where words weaken as they sit unchanged
+    fn mark_modified(&mut self, positions: &[Coordinate]) {
+        let rules::WordAging::Weaken { grace_turns } = self.rules.word_aging else {
+            return;
+        };
+        let (width, height) = (self.board.width(), self.board.height());
+        let aging = self
+            .board
+            .aging
+            .get_or_insert_with(|| WordAges::new(width, height, grace_turns));
+        aging.turn = self.turn_count;
+        for position in positions {
+            aging.mark_modified(*position);
         }
     }
 
@@ -718,10 +1585,19 @@ impl Game {
             .board
             .word_strings(&defenders)
             .expect("Words were just found and should be valid");
+        let defender_weakness: Vec<_> = defenders
+            .iter()
+            .map(|word| {
+                let aged = self.board.aging.as_ref().map(|a| a.weakness(word));
+                let elevated = self.board.elevation.as_ref().map(|e| e.strength(word));
+                aged.unwrap_or_default() as isize - elevated.unwrap_or_default() as isize
+            })
+            .collect();
 
         if let Some(mut battle) = self.judge.battle(
             attacking_words,
             defending_words,
+            &defender_weakness,
             &self.rules.battle_rules,
             &self.rules.win_condition,
             attacker_dictionary,
@@ -867,7 +1743,8 @@ impl Game {
                     .into_iter(),
             ),
             rules::Truncation::Larger => unimplemented!(),
-            rules::Truncation::None => {}
+            // Cut off tiles are left to wither once the whole turn has been played
+            rules::Truncation::None | rules::Truncation::Withering => {}
         }
 
         match self.board.get(position) {
@@ -886,6 +1763,13 @@ impl Game {
             }
             _ => {}
         }
+
+        match self.rules.gravity {
+            rules::Gravity::TowardsRoot => {
+                changes.extend(self.board.compact(attacker_dictionary))
+            }
+            rules::Gravity::None => {}
+        }
     }
 
     pub fn next(&self) -> Option<usize> {
@@ -4,22 +4,22 @@ use std::{
 };
 
 use noise::{NoiseFn, Simplex};
-use oorandom::Rand32;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    board::{Board, BoardDistances, Coordinate, Square, SquareValidity},
+    board::{Board, BoardDistances, Coordinate, Elevation, Square, SquareValidity},
     game::Game,
+    rng::TruncateRng,
 };
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ArtifactType {
     IslandV1,
     Coastal,
     Continental,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Symmetry {
     SmoothTwoFoldRotational,
     TwoFoldRotational,
@@ -33,20 +33,20 @@ pub struct BoardElements {
     pub obelisk: bool,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BoardNoiseParams {
     pub dispersion: [f64; 2],
     pub island_influence: f64,
     pub symmetric: Symmetry,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WaterLayer {
     pub params: BoardNoiseParams,
     pub density: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BoardParams {
     pub land_layer: BoardNoiseParams,
     pub water_layer: Option<WaterLayer>,
@@ -108,10 +108,12 @@ const BOARD_GENERATIONS: [BoardParams; 2] = [
 
 impl BoardParams {
     pub fn generation(gen: u32) -> Self {
-        BOARD_GENERATIONS
-            .get(gen as usize)
-            .expect("Board generation should exist")
-            .clone()
+        Self::checked_generation(gen).expect("Board generation should exist")
+    }
+
+    /// The parameters of a generation, if there is one by that number
+    pub fn checked_generation(gen: u32) -> Option<Self> {
+        BOARD_GENERATIONS.get(gen as usize).cloned()
     }
 
     pub fn latest() -> (u32, Self) {
@@ -182,14 +184,14 @@ impl BoardSeed {
     }
 
     fn internal_reroll(&mut self) {
-        let mut rng = Rand32::new(self.seed as u64);
+        let mut rng = TruncateRng::new(self.seed as u64);
         let r = rng.rand_u32();
         self.seed = r;
         self.current_iteration += 1;
     }
 
     pub fn external_reroll(&mut self) {
-        let mut rng = Rand32::new(self.seed as u64);
+        let mut rng = TruncateRng::new(self.seed as u64);
         // If externally rerolling, advance this RNG state and pick a later number.
         // otherwise, the external reroll might do nothing if the previous seed
         // was internally rerolled.
@@ -292,7 +294,7 @@ pub fn generate_board(
     // Remove extraneous water
     board.trim();
 
-    let mut width_diff = board.width() as isize - (ideal_land_dimensions[0] + 2) as isize;
+    let mut width_diff = board.land_width() as isize - ideal_land_dimensions[0] as isize;
 
     // Raise or lower water slightly to try and hit the target island width
     if width_diff.is_negative() {
@@ -302,10 +304,11 @@ pub fn generate_board(
             board_seed.width_resize_state = Some(PreviousBoardResize::Enlarged);
             return generate_board(board_seed);
         } else {
-            let mut rng = Rand32::new(seed as u64);
-            while width_diff < 0 {
-                // Pick a random column to duplicate
-                let col = rng.rand_range(1..(board.squares[0].len() as u32 - 1)) as usize;
+            let mut rng = TruncateRng::new(seed as u64);
+            while let Some(land) = board.land().filter(|_| width_diff < 0) {
+                // Pick a random column of the land to duplicate
+                let col = rng.rand_range(land.origin.x as u32..(land.origin.x + land.width) as u32)
+                    as usize;
 
                 for row in board.squares.iter_mut() {
                     row.insert(col, row[col]);
@@ -320,13 +323,17 @@ pub fn generate_board(
         return generate_board(board_seed);
     }
 
-    let mut height_diff = board.height() as isize - (ideal_land_dimensions[1] + 2) as isize;
+    let mut height_diff = board.land_height() as isize - ideal_land_dimensions[1] as isize;
 
     if height_diff != 0 {
-        let mut rng = Rand32::new(seed as u64);
-        while height_diff != 0 && board.squares.len() > 2 {
-            // Pick a random row to duplicate or delete
-            let row = rng.rand_range(1..(board.squares.len() as u32 - 2)) as usize;
+        let mut rng = TruncateRng::new(seed as u64);
+        while let Some(land) = board
+            .land()
+            .filter(|land| height_diff != 0 && land.height > 1)
+        {
+            // Pick a random row of the land, other than its last, to duplicate or delete
+            let row = rng.rand_range(land.origin.y as u32..(land.origin.y + land.height - 1) as u32)
+                as usize;
 
             if height_diff.is_negative() {
                 board.squares.insert(row, board.squares[row].clone());
@@ -791,7 +798,7 @@ impl BoardGenerator for Board {
     // An old implementation of artifact placement that should be avoided when possible.
     // Retained so that past puzzles generate correctly.
     fn drop_island_v1_artifacts(&mut self, seed: u32) -> Result<(), ()> {
-        let mut rng = Rand32::new(seed as u64);
+        let mut rng = TruncateRng::new(seed as u64);
         let mut visited: HashSet<Coordinate> = HashSet::from([Coordinate { x: 0, y: 0 }]);
         let mut coastal_water: HashSet<Coordinate> = HashSet::new();
 
@@ -927,7 +934,7 @@ impl BoardGenerator for Board {
         artifact_type: ArtifactType,
         symmetric: Symmetry,
     ) -> Result<(), ()> {
-        let mut rng = Rand32::new(seed as u64);
+        let mut rng = TruncateRng::new(seed as u64);
         let mut viable_water: HashSet<Coordinate> = HashSet::new();
 
         match artifact_type {
@@ -1059,7 +1066,7 @@ impl BoardGenerator for Board {
             return Err(());
         };
 
-        let mut town_seed = Rand32::new(seed as u64);
+        let mut town_seed = TruncateRng::new(seed as u64);
 
         let town_distance = ((main_road.len() as f64) * maximum_town_distance) as usize;
 
@@ -1161,6 +1168,39 @@ impl BoardGenerator for Board {
     }
 }
 
+/// How spread out the hills are when a board is given elevation
+const ELEVATION_DISPERSION: f64 = 4.0;
+/// How high the terrain must reach, from 0 to 1, to count as high ground
+const HIGH_GROUND_LEVEL: f64 = 0.6;
+
+/// Raises patches of land into high ground, mirrored so that both players get the same hills
+pub fn generate_elevation(board: &Board, seed: u32) -> Elevation {
+    let simplex = Simplex::new(seed);
+    let (width, height) = (board.width(), board.height());
+    let terrain_height = |coord: Coordinate| {
+        let ni = coord.x as f64 / width as f64;
+        let nj = coord.y as f64 / height as f64;
+        (simplex.get([ni * ELEVATION_DISPERSION, nj * ELEVATION_DISPERSION, 0.0]) + 1.0) / 2.0
+    };
+
+    let high_ground = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    let coord = Coordinate { x, y };
+                    if !matches!(board.get(coord), Ok(Square::Land { .. })) {
+                        return false;
+                    }
+                    let recip = board.reciprocal_coordinate(coord);
+                    (terrain_height(coord) + terrain_height(recip)) / 2.0 > HIGH_GROUND_LEVEL
+                })
+                .collect()
+        })
+        .collect();
+
+    Elevation::new(high_ground)
+}
+
 pub fn get_game_verification(game: &Game) -> String {
     let mut digest = chksum_hash_sha2::sha2_256::default();
 
@@ -1172,10 +1212,82 @@ pub fn get_game_verification(game: &Game) -> String {
     digest.digest().to_hex_lowercase()
 }
 
+/// The seeds fingerprinted by `board_fingerprints`, spread between the small seeds
+/// of daily puzzles and the large seeds of free play
+const FINGERPRINT_SEEDS: [u32; 8] = [0, 1, 42, 365, 1337, 12345, 243985690, u32::MAX];
+/// How many times each fingerprinted seed is rerolled, as daily puzzles can be
+const FINGERPRINT_REROLLS: [usize; 2] = [0, 2];
+
+/// Everything that goes into a board's fingerprint, being the generated board and its hills.
+/// Only what generation decides is included, so that unrelated fields added to `Board` leave it alone.
+fn fingerprint_source(generation: u32, seed: u32, rerolls: usize) -> String {
+    let mut board_seed = BoardSeed::new_with_generation(generation, seed);
+    for _ in 0..rerolls {
+        board_seed.external_reroll();
+    }
+
+    let (generated, result) = match generate_board(board_seed) {
+        Ok(result) => (true, result),
+        Err(result) => (false, result),
+    };
+    let elevation = generate_elevation(&result.board, seed);
+
+    let board = &result.board;
+    format!(
+        "{generated} {} {:?} {:?} {:?} {:?} {:?} {:?}",
+        result.iterations,
+        board.squares,
+        board.artifacts,
+        board.towns,
+        board.obelisks,
+        board.orientations,
+        elevation
+    )
+}
+
+/// A line for each board generation, seed, and reroll, with a hash of every detail of the board generated.
+///
+/// Daily puzzles are generated separately by the client and the server, so these should match
+/// exactly on every platform, which `board_fingerprints.txt` records.
+pub fn board_fingerprints() -> String {
+    let mut lines = vec![];
+    for generation in 0..BOARD_GENERATIONS.len() as u32 {
+        for seed in FINGERPRINT_SEEDS {
+            for rerolls in FINGERPRINT_REROLLS {
+                let mut digest = chksum_hash_sha2::sha2_256::default();
+                digest.update(fingerprint_source(generation, seed, rerolls));
+                lines.push(format!(
+                    "{generation} {seed} {rerolls} {}",
+                    digest.digest().to_hex_lowercase()
+                ));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn boards_match_fingerprints() {
+        let expected = include_str!("board_fingerprints.txt")
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Generating again in the same process catches anything depending on hash ordering
+        assert_eq!(board_fingerprints(), board_fingerprints());
+        assert_eq!(
+            board_fingerprints(),
+            expected,
+            "Boards no longer generate as they did. If this is intentional, add a new board generation \
+            rather than changing an existing one, as existing daily puzzles would change"
+        );
+    }
+
     #[test]
     fn reroll_test() {
         let mut seed = BoardSeed::new(12345);
@@ -1191,4 +1303,32 @@ mod tests {
             "Board 1 from {bare_seed_1}:\n{board_one}\n\nrerolled to {bare_seed_2}:\n{board_two}"
         ));
     }
+
+    #[test]
+    fn elevation_is_mirrored_land() {
+        let board = generate_board(BoardSeed::new(12345))
+            .expect("Board can be resolved")
+            .board;
+
+        let found_high_ground = (0..6).any(|seed| {
+            let elevation = generate_elevation(&board, seed);
+            let mut any_high = false;
+            for y in 0..board.height() {
+                for x in 0..board.width() {
+                    let coord = Coordinate { x, y };
+                    if elevation.is_high(coord) {
+                        any_high = true;
+                        assert!(matches!(board.get(coord), Ok(Square::Land { .. })));
+                        let recip = board.reciprocal_coordinate(coord);
+                        assert_eq!(
+                            elevation.is_high(recip),
+                            matches!(board.get(recip), Ok(Square::Land { .. }))
+                        );
+                    }
+                }
+            }
+            any_high
+        });
+        assert!(found_high_ground);
+    }
 }
@@ -2,8 +2,9 @@ use serde::{Deserialize, Serialize};
 use xxhash_rust::xxh3;
 
 use crate::{
+    bag::{language_tiles, DIGRAPHS},
     reporting::{BattleReport, BattleWord},
-    rules,
+    rules::{self, Language},
 };
 
 use super::board::{Board, Square};
@@ -12,13 +13,7 @@ use std::{
     fmt::{self, Display},
 };
 
-#[derive(Debug, Clone)]
-pub struct WordData {
-    pub extensions: u32,
-    pub rel_freq: f32,
-    pub objectionable: bool,
-}
-pub type WordDict = HashMap<String, WordData>;
+pub use crate::dictionary::{WordData, WordDict};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Outcome {
@@ -41,20 +36,61 @@ impl fmt::Display for Outcome {
 pub struct Judge {
     pub builtin_dictionary: WordDict,
     aliases: HashMap<char, Vec<char>>,
+    /// Every lowercase letter that a wildcard could stand for
+    alphabet: Vec<char>,
 }
 
 impl Default for Judge {
     fn default() -> Self {
         Self {
-            builtin_dictionary: HashMap::new(),
+            builtin_dictionary: WordDict::new(),
             aliases: HashMap::new(),
+            alphabet: ('a'..='z').collect(),
         }
     }
 }
 
+/// Rewrites a word from a language's word list as the tiles that would spell it,
+/// so that letters played as one tile, like the Spanish CH, are looked up as that tile.
+/// Dictionaries for languages with digraph tiles need their words passed through this.
+pub fn tiles_for_word(word: &str, language: Language) -> String {
+    let tiles = language_tiles(language);
+    DIGRAPHS
+        .iter()
+        .filter(|digraph| tiles.contains(&digraph.tile))
+        .fold(word.to_lowercase(), |word, digraph| {
+            word.replace(&digraph.letters.to_lowercase(), &digraph.tile.to_string())
+        })
+}
+
+/// Reads a language's word list, with one word per line and objectionable words starting with a `*`,
+/// spelling each word with the language's tiles
+pub fn language_words(contents: &str, language: Language) -> WordDict {
+    let mut words = WordDict::new();
+    for line in contents.lines() {
+        let Some(mut word) = line.split_whitespace().next() else {
+            continue;
+        };
+        let objectionable = word.starts_with('*');
+        if objectionable {
+            word = &word[1..];
+        }
+
+        words.insert(
+            tiles_for_word(word, language),
+            WordData {
+                extensions: 0,
+                rel_freq: 0.0,
+                objectionable,
+            },
+        );
+    }
+    words
+}
+
 impl Judge {
     pub fn new(words: Vec<String>) -> Self {
-        let mut dictionary = HashMap::new();
+        let mut dictionary = WordDict::new();
         for word in words {
             dictionary.insert(
                 word.to_lowercase(),
@@ -67,7 +103,18 @@ impl Judge {
         }
         Self {
             builtin_dictionary: dictionary,
-            aliases: HashMap::new(),
+            ..Self::default()
+        }
+    }
+
+    /// A judge whose wildcards stand in for the letters of the given language
+    pub fn for_language(language: Language) -> Self {
+        Self {
+            alphabet: language_tiles(language)
+                .into_iter()
+                .flat_map(char::to_lowercase)
+                .collect(),
+            ..Self::default()
         }
     }
 
@@ -121,10 +168,13 @@ impl Judge {
     // Otherwise the attacker wins
     //
     // There is a defender's advantage, so an attacking word has to be at least 2 letters longer than a defending word to be stronger than it.
+    // Defending words can be weakened, e.g. by sitting unchanged under word aging rules, and defend as if they were that many letters shorter.
+    // A negative weakness strengthens the word instead, such as for words built on high ground.
     pub fn battle<S: AsRef<str> + Clone + Display>(
         &self,
         attackers: Vec<S>,
         defenders: Vec<S>,
+        defender_weakness: &[isize],
         battle_rules: &rules::BattleRules,
         win_rules: &rules::WinCondition,
         attacker_dictionary: Option<&WordDict>,
@@ -141,13 +191,15 @@ impl Judge {
             attackers: attackers
                 .iter()
                 .map(|w| {
-                    let valid = self.valid(
-                        w,
-                        win_rules,
-                        attacker_dictionary,
-                        None,
-                        &mut cached_word_judgements,
-                    );
+                    let valid = self
+                        .valid(
+                            w,
+                            win_rules,
+                            attacker_dictionary,
+                            None,
+                            &mut cached_word_judgements,
+                        )
+                        .or_else(|| self.lenient_match(w, battle_rules, attacker_dictionary));
                     BattleWord {
                         original_word: w.to_string(),
                         valid: Some(valid.is_some()),
@@ -166,6 +218,7 @@ impl Judge {
                 })
                 .collect(),
             outcome: Outcome::DefenderWins,
+            amendment: None,
         };
 
         // The defender wins if any attacking word is invalid
@@ -179,13 +232,17 @@ impl Judge {
         }
 
         for defense in &mut battle_report.defenders {
-            let valid = self.valid(
-                &*defense.resolved_word,
-                win_rules,
-                defender_dictionary,
-                None,
-                &mut cached_word_judgements,
-            );
+            let valid = self
+                .valid(
+                    &*defense.resolved_word,
+                    win_rules,
+                    defender_dictionary,
+                    None,
+                    &mut cached_word_judgements,
+                )
+                .or_else(|| {
+                    self.lenient_match(&defense.resolved_word, battle_rules, defender_dictionary)
+                });
             if let Some(valid) = valid {
                 defense.resolved_word = valid;
                 defense.valid = Some(true);
@@ -200,7 +257,7 @@ impl Judge {
             .iter()
             .filter_map(|word| {
                 if word.valid == Some(true) {
-                    Some(&word.original_word)
+                    Some(&word.resolved_word)
                 } else {
                     None
                 }
@@ -241,9 +298,11 @@ impl Judge {
 
         let weak_word_defenders: Vec<_> = actually_words
             .iter()
-            .filter(|(_, word)| {
+            .filter(|(index, word)| {
+                let weakness = defender_weakness.get(*index).copied().unwrap_or_default();
                 word.valid != Some(true)
                     || word.resolved_word.len() as isize + battle_rules.length_delta as isize
+                        - weakness
                         <= longest_attacker.len() as isize
             })
             .map(|(index, _)| *index)
@@ -297,6 +356,95 @@ impl Judge {
         Some(battle_report)
     }
 
+    /// Reads a word that isn't in the dictionary as a close misspelling, for lenient spelling rules.
+    /// Returns the dictionary word it was read as.
+    fn lenient_match<S: AsRef<str>>(
+        &self,
+        word: S,
+        battle_rules: &rules::BattleRules,
+        external_dictionary: Option<&WordDict>,
+    ) -> Option<String> {
+        if !matches!(battle_rules.spelling, rules::Spelling::Lenient) {
+            return None;
+        }
+        // Two letter words are a letter away from too many others to be forgiving
+        let word = word.as_ref().to_lowercase();
+        if word.chars().count() < 3 {
+            return None;
+        }
+
+        Self::close_match(
+            &word,
+            &self.alphabet,
+            external_dictionary.unwrap_or(&self.builtin_dictionary),
+        )
+        .map(|matched| matched.to_uppercase())
+    }
+
+    /// Finds the most common dictionary word one letter away from the given lowercase word, by looking
+    /// up every word that a single added, removed, or changed letter of the `alphabet` could make.
+    /// Ties between equally common words go to whichever comes first alphabetically.
+    pub fn close_match(word: &str, alphabet: &[char], dictionary: &WordDict) -> Option<String> {
+        // Where each letter starts, and where the word ends
+        let bounds: Vec<usize> = word
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([word.len()])
+            .collect();
+        // Every candidate is spelled out in this one buffer, and only copied if it's the best so far
+        let mut candidate = String::with_capacity(word.len() + 4);
+        let mut best: Option<(String, f32)> = None;
+        let mut consider = |candidate: &str| {
+            let Some(data) = dictionary.get(candidate) else {
+                return;
+            };
+            let better = match &best {
+                _ if data.objectionable => false,
+                None => true,
+                Some((best_word, best_freq)) => {
+                    data.rel_freq > *best_freq
+                        || (data.rel_freq == *best_freq && candidate < best_word.as_str())
+                }
+            };
+            if better {
+                best = Some((candidate.to_string(), data.rel_freq));
+            }
+        };
+
+        for (i, &start) in bounds.iter().enumerate() {
+            let head = &word[..start];
+            let rest = bounds
+                .get(i + 1)
+                .map(|&end| (&word[start..end], &word[end..]));
+
+            if let Some((_, tail)) = rest {
+                candidate.clear();
+                candidate.push_str(head);
+                candidate.push_str(tail);
+                consider(&candidate);
+            }
+            for &c in alphabet {
+                candidate.clear();
+                candidate.push_str(head);
+                candidate.push(c);
+                candidate.push_str(&word[start..]);
+                consider(&candidate);
+
+                if let Some((letter, tail)) = rest {
+                    if !letter.starts_with(c) {
+                        candidate.clear();
+                        candidate.push_str(head);
+                        candidate.push(c);
+                        candidate.push_str(tail);
+                        consider(&candidate);
+                    }
+                }
+            }
+        }
+
+        best.map(|(word, _)| word)
+    }
+
     /// Returns the string that was matched if word was a wildcard
     pub fn valid<S: AsRef<str>>(
         &self,
@@ -386,10 +534,10 @@ impl Judge {
             if word.as_ref().contains('*') {
                 // Try all letters in the first wildcard spot
                 // TODO: find a fun way to optimize this to not be 26^wildcard_count (regex?)
-                let valid = (97..=122_u8).find_map(|c| {
+                let valid = judge.alphabet.iter().find_map(|c| {
                     valid_inner(
                         judge,
-                        word.as_ref().replacen('*', &(c as char).to_string(), 1),
+                        word.as_ref().replacen('*', &c.to_string(), 1),
                         win_rules,
                         external_dictionary,
                         used_aliases.clone(),
@@ -436,7 +584,10 @@ mod tests {
     use super::*;
 
     fn test_battle_rules() -> rules::BattleRules {
-        rules::BattleRules { length_delta: 2 }
+        rules::BattleRules {
+            length_delta: 2,
+            spelling: rules::Spelling::Strict,
+        }
     }
 
     fn test_win_rules() -> rules::WinCondition {
@@ -453,6 +604,7 @@ mod tests {
             j.battle(
                 vec!["WORD"],
                 vec![],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -465,6 +617,7 @@ mod tests {
             j.battle(
                 vec![],
                 vec!["WORD"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -478,6 +631,7 @@ mod tests {
             j.battle::<&'static str>(
                 vec![],
                 vec![],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -495,6 +649,7 @@ mod tests {
             j.battle(
                 vec!["XYZ"],
                 vec!["BIG"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -509,6 +664,7 @@ mod tests {
             j.battle(
                 vec!["XYZXYZXYZ"],
                 vec!["BIG"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -523,6 +679,7 @@ mod tests {
             j.battle(
                 vec!["XYZ", "JOLLY"],
                 vec!["BIG"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -537,6 +694,7 @@ mod tests {
             j.battle(
                 vec!["BIG", "XYZ"],
                 vec!["BIG"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -551,6 +709,7 @@ mod tests {
             j.battle(
                 vec!["BIG", "XYZXYZXYZ"],
                 vec!["BIG"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -565,6 +724,7 @@ mod tests {
             j.battle(
                 vec!["XYZ", "BIG"],
                 vec!["BIG"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -584,6 +744,7 @@ mod tests {
             j.battle(
                 vec!["BIG"],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -598,6 +759,7 @@ mod tests {
             j.battle(
                 vec!["BIG"],
                 vec!["XYZXYZXYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -612,6 +774,7 @@ mod tests {
             j.battle(
                 vec!["BIG"],
                 vec!["BIG", "XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -626,6 +789,7 @@ mod tests {
             j.battle(
                 vec!["BIG"],
                 vec!["XYZ", "BIG"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -645,6 +809,7 @@ mod tests {
             j.battle(
                 vec!["JOLLY"],
                 vec!["FOLK"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -659,6 +824,7 @@ mod tests {
             j.battle(
                 vec!["JOLLY", "BIG"],
                 vec!["FOLK"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -678,6 +844,7 @@ mod tests {
             j.battle(
                 vec!["JOLLY"],
                 vec!["FAT"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -692,6 +859,7 @@ mod tests {
             j.battle(
                 vec!["JOLLY", "BIG"],
                 vec!["FAT"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -706,6 +874,7 @@ mod tests {
             j.battle(
                 vec!["JOLLY"],
                 vec!["FAT", "BIG", "JOLLY", "FOLK", "XYZXYZXYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -718,6 +887,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn aged_defenders_weaken() {
+        let j = short_dict();
+        let outcome = |weakness: &[isize]| {
+            j.battle(
+                vec!["JOLLY"],
+                vec!["FOLK", "FAT"],
+                weakness,
+                &test_battle_rules(),
+                &test_win_rules(),
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+            .outcome
+        };
+
+        assert_eq!(outcome(&[]), Outcome::AttackerWins(vec![1]));
+        assert_eq!(outcome(&[0, 0]), Outcome::AttackerWins(vec![1]));
+        // FOLK now defends as if it were three letters long
+        assert_eq!(outcome(&[1, 0]), Outcome::AttackerWins(vec![0, 1]));
+        // FAT on high ground defends as if it were four letters long, and holds
+        assert_eq!(outcome(&[0, -1]), Outcome::DefenderWins);
+    }
+
     #[test]
     fn different_dicts() {
         let j = short_dict();
@@ -727,6 +922,7 @@ mod tests {
             j.battle(
                 vec!["BAG"],
                 vec!["FAT"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 Some(&short_dict().builtin_dictionary),
@@ -743,6 +939,7 @@ mod tests {
             j.battle(
                 vec!["JOLLY"],
                 vec!["FAT"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 Some(&b_dict().builtin_dictionary),
@@ -755,6 +952,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lenient_spelling() {
+        let j = short_dict();
+        let lenient_rules = rules::BattleRules {
+            spelling: rules::Spelling::Lenient,
+            ..test_battle_rules()
+        };
+        let battle = |rules: &rules::BattleRules, attackers: Vec<&str>| {
+            j.battle(
+                attackers,
+                vec!["FAT"],
+                &[],
+                rules,
+                &test_win_rules(),
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            battle(&test_battle_rules(), vec!["JOLY"]).outcome,
+            Outcome::DefenderWins
+        );
+
+        // Read as JOLLY, which is long enough to win
+        let report = battle(&lenient_rules, vec!["JOLY"]);
+        assert_eq!(report.outcome, Outcome::AttackerWins(vec![0]));
+        assert_eq!(report.attackers[0].resolved_word, "JOLLY");
+        assert!(report.attackers[0].misspelled());
+        assert!(!report.defenders[0].misspelled());
+
+        // Only one letter can be off, and short words must be exact
+        assert_eq!(
+            battle(&lenient_rules, vec!["JOYY"]).outcome,
+            Outcome::DefenderWins
+        );
+        assert_eq!(
+            battle(&lenient_rules, vec!["BI"]).outcome,
+            Outcome::DefenderWins
+        );
+    }
+
+    #[test]
+    fn lenient_spelling_breaks_ties() {
+        // Every word is as common as the next, as in the languages' word lists
+        let j = Judge::new(vec![
+            "cart".into(),
+            "card".into(),
+            "care".into(),
+            "fat".into(),
+        ]);
+        let lenient_rules = rules::BattleRules {
+            spelling: rules::Spelling::Lenient,
+            ..test_battle_rules()
+        };
+
+        let report = j
+            .battle(
+                vec!["CARX"],
+                vec!["FAT"],
+                &[],
+                &lenient_rules,
+                &test_win_rules(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(report.attackers[0].resolved_word, "CARD");
+    }
+
+    #[test]
+    fn played_blanks() {
+        let j = short_dict();
+        let report = j
+            .battle(
+                vec!["BiG"],
+                vec!["XYZ"],
+                &[],
+                &test_battle_rules(),
+                &test_win_rules(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(report.outcome, Outcome::AttackerWins(vec![0]));
+        assert_eq!(report.attackers[0].resolved_word, "BIG");
+
+        // Once played as a letter, a blank isn't wild any more
+        assert_eq!(
+            j.battle(
+                vec!["BoG"],
+                vec!["XYZ"],
+                &[],
+                &test_battle_rules(),
+                &test_win_rules(),
+                None,
+                None,
+                None
+            )
+            .unwrap()
+            .outcome,
+            Outcome::DefenderWins
+        );
+    }
+
     #[test]
     fn wildcards() {
         let j = short_dict();
@@ -762,6 +1068,7 @@ mod tests {
             j.battle(
                 vec!["B*G"],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -776,6 +1083,7 @@ mod tests {
             j.battle(
                 vec!["R*G"],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -790,6 +1098,7 @@ mod tests {
             j.battle(
                 vec!["ARTS"],
                 vec!["JALL*"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -804,6 +1113,7 @@ mod tests {
             j.battle(
                 vec!["BAG"],
                 vec!["JOLL*"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -827,6 +1137,7 @@ mod tests {
             j.battle(
                 vec![format!("B{a_or_b}G").as_str()],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -841,6 +1152,7 @@ mod tests {
             j.battle(
                 vec![format!("B{b_or_c}G").as_str()],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -855,6 +1167,7 @@ mod tests {
             j.battle(
                 vec![format!("{b_or_c}{a_or_b}G").as_str()],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -870,6 +1183,7 @@ mod tests {
             j.battle(
                 vec![format!("{a_or_b}RTS").as_str()],
                 vec!["FOLK"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -884,6 +1198,7 @@ mod tests {
             j.battle(
                 vec![format!("{a_or_b}RTS").as_str()],
                 vec!["BAG"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -908,6 +1223,7 @@ mod tests {
             j.battle(
                 vec![format!("JO{o_or_l}{o_or_l}Y").as_str()],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -923,6 +1239,7 @@ mod tests {
             j.battle(
                 vec![format!("JO{two_ls}{two_ls}Y").as_str()],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -938,6 +1255,7 @@ mod tests {
             j.battle(
                 vec![format!("J{o_or_l}L{o_or_l}Y").as_str()],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -957,6 +1275,7 @@ mod tests {
             j.battle(
                 vec!["B*G"],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -977,13 +1296,15 @@ mod tests {
                     meanings: None,
                     valid: Some(false)
                 }],
-                outcome: Outcome::AttackerWins(vec![0])
+                outcome: Outcome::AttackerWins(vec![0]),
+                amendment: None
             })
         );
         assert_eq!(
             j.battle(
                 vec!["R*G"],
                 vec!["XYZ"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -1004,7 +1325,8 @@ mod tests {
                     meanings: None,
                     valid: None
                 }],
-                outcome: Outcome::DefenderWins
+                outcome: Outcome::DefenderWins,
+                amendment: None
             })
         );
 
@@ -1012,6 +1334,7 @@ mod tests {
             j.battle(
                 vec!["ARTS"],
                 vec!["JALL*"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -1032,13 +1355,15 @@ mod tests {
                     meanings: None,
                     valid: Some(false)
                 }],
-                outcome: Outcome::AttackerWins(vec![0])
+                outcome: Outcome::AttackerWins(vec![0]),
+                amendment: None
             })
         );
         assert_eq!(
             j.battle(
                 vec!["BAG"],
                 vec!["JOLL*"],
+                &[],
                 &test_battle_rules(),
                 &test_win_rules(),
                 None,
@@ -1059,11 +1384,76 @@ mod tests {
                     meanings: None,
                     valid: Some(true)
                 }],
-                outcome: Outcome::DefenderWins
+                outcome: Outcome::DefenderWins,
+                amendment: None
             })
         );
     }
 
+    #[test]
+    fn spells_with_a_languages_tiles() {
+        assert_eq!(tiles_for_word("Chico", Language::Spanish), "\u{E000}ico");
+        assert_eq!(tiles_for_word("perro", Language::Spanish), "pe\u{E002}o");
+        assert_eq!(tiles_for_word("chico", Language::English), "chico");
+
+        let mut j = Judge::for_language(Language::Spanish);
+        j.builtin_dictionary = language_words("chico\nniño\n*calle\n\n", Language::Spanish);
+        assert_eq!(j.builtin_dictionary.len(), 3);
+        assert!(
+            j.builtin_dictionary
+                .get("ca\u{E001}e")
+                .unwrap()
+                .objectionable
+        );
+        let valid = |word: &str| j.valid(word, &test_win_rules(), None, None, &mut None);
+
+        assert_eq!(valid("\u{E000}ICO"), Some("\u{E000}ICO".into()));
+        assert_eq!(
+            valid("CHICO"),
+            None,
+            "A C and an H aren't the CH tile in Spanish"
+        );
+
+        // Wildcards stand in for accented letters and digraphs too
+        assert_eq!(valid("NI*O"), Some("NIÑO".into()));
+        assert_eq!(valid("CA*E"), Some("CA\u{E001}E".into()));
+        assert_eq!(
+            Judge::new(vec!["niño".into()]).valid("NI*O", &test_win_rules(), None, None, &mut None),
+            None
+        );
+    }
+
+    #[test]
+    fn lenient_spelling_in_other_languages() {
+        let mut j = Judge::for_language(Language::Spanish);
+        j.builtin_dictionary = language_words("niño\nperro\nsol\n", Language::Spanish);
+        let lenient_rules = rules::BattleRules {
+            spelling: rules::Spelling::Lenient,
+            ..test_battle_rules()
+        };
+        let read_as = |attacker: &str| {
+            j.battle(
+                vec![attacker],
+                vec!["SOL"],
+                &[],
+                &lenient_rules,
+                &test_win_rules(),
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+            .attackers[0]
+                .resolved_word
+                .clone()
+        };
+
+        assert_eq!(read_as("NINO"), "NIÑO");
+        assert_eq!(read_as("NIÑOS"), "NIÑO");
+        // A missing digraph tile is one letter away, like any other
+        assert_eq!(read_as("PEO"), "PE\u{E002}O");
+    }
+
     // #[test]
     // fn main_dict() {
     //     let j = Judge::default();
@@ -0,0 +1,269 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{dictionary::pick_with_retries, judge::WordDict, rng::TruncateRng};
+
+/// Length of the words used in the daily ladder
+const LADDER_WORD_LENGTH: usize = 4;
+/// Only well known words are picked as the start and target of a ladder
+const LADDER_MIN_FREQ: f32 = 0.998;
+const LADDER_MIN_STEPS: usize = 3;
+const LADDER_MAX_STEPS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LadderError {
+    WrongStart,
+    WrongLength(String),
+    NotAWord(String),
+    TooManyChanges(String, String),
+    Unfinished,
+}
+
+impl std::fmt::Display for LadderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LadderError::WrongStart => write!(f, "The ladder must begin at the starting word"),
+            LadderError::WrongLength(word) => {
+                write!(
+                    f,
+                    "{} is not {LADDER_WORD_LENGTH} letters long",
+                    word.to_uppercase()
+                )
+            }
+            LadderError::NotAWord(word) => write!(f, "{} is not a word", word.to_uppercase()),
+            LadderError::TooManyChanges(from, to) => write!(
+                f,
+                "Only one letter can change between {} and {}",
+                from.to_uppercase(),
+                to.to_uppercase()
+            ),
+            LadderError::Unfinished => write!(f, "The ladder hasn't reached the target yet"),
+        }
+    }
+}
+
+/// A puzzle where one word is turned into another by changing one letter at a time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordLadder {
+    pub day: u32,
+    pub start: String,
+    pub target: String,
+    /// Fewest steps needed to reach the target
+    pub par: usize,
+}
+
+impl WordLadder {
+    /// Picks the ladder for a given day, which is the same for everyone with the same dictionary
+    pub fn daily(day: u32, dict: &WordDict) -> Option<Self> {
+        let common_words = dict.sorted_words(|word, data| {
+            word.len() == LADDER_WORD_LENGTH
+                && word.chars().all(|c| c.is_ascii_lowercase())
+                && data.rel_freq > LADDER_MIN_FREQ
+                && !data.objectionable
+        });
+        let common_set: HashSet<_> = common_words.iter().cloned().collect();
+
+        let mut rng = TruncateRng::new(day as u64);
+        // Not every word has an interesting ladder, so try a handful of starting points
+        pick_with_retries(&common_words, &mut rng, |start, rng| {
+            let distances = Self::distances_from(start, &common_set);
+            let mut candidates: Vec<_> = distances
+                .iter()
+                .filter(|(_, steps)| (LADDER_MIN_STEPS..=LADDER_MAX_STEPS).contains(*steps))
+                .collect();
+            if candidates.is_empty() {
+                return None;
+            }
+            candidates.sort();
+
+            let (target, _) = candidates[rng.rand_range(0..candidates.len() as u32) as usize];
+
+            // Par is measured against every valid word, not only the common ones
+            let all_words: HashSet<_> = dict
+                .keys()
+                .filter(|w| w.len() == LADDER_WORD_LENGTH)
+                .map(str::to_string)
+                .collect();
+            let par = Self::distances_from(start, &all_words)
+                .get(target)
+                .copied()
+                .expect("Target was reachable through common words");
+
+            Some(Self {
+                day,
+                start: start.clone(),
+                target: target.clone(),
+                par,
+            })
+        })
+    }
+
+    /// Checks a single rung of the ladder
+    pub fn valid_step(from: &str, to: &str, dict: &WordDict) -> Result<(), LadderError> {
+        if to.len() != from.len() {
+            return Err(LadderError::WrongLength(to.to_string()));
+        }
+        if !dict.contains_key(to) {
+            return Err(LadderError::NotAWord(to.to_string()));
+        }
+        let changes = from.chars().zip(to.chars()).filter(|(a, b)| a != b).count();
+        if changes != 1 {
+            return Err(LadderError::TooManyChanges(
+                from.to_string(),
+                to.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks a full ladder from start to target, returning the number of steps taken
+    pub fn validate(&self, words: &[String], dict: &WordDict) -> Result<usize, LadderError> {
+        if words.first() != Some(&self.start) {
+            return Err(LadderError::WrongStart);
+        }
+        for rung in words.windows(2) {
+            Self::valid_step(&rung[0], &rung[1], dict)?;
+        }
+        if words.last() != Some(&self.target) {
+            return Err(LadderError::Unfinished);
+        }
+        Ok(words.len() - 1)
+    }
+
+    fn distances_from(start: &str, words: &HashSet<String>) -> HashMap<String, usize> {
+        let mut distances = HashMap::from([(start.to_string(), 0)]);
+        let mut queue = VecDeque::from([start.to_string()]);
+
+        while let Some(word) = queue.pop_front() {
+            let steps = distances[&word];
+            for i in 0..word.len() {
+                for c in 'a'..='z' {
+                    let mut next: Vec<_> = word.chars().collect();
+                    if next[i] == c {
+                        continue;
+                    }
+                    next[i] = c;
+                    let next: String = next.into_iter().collect();
+                    if words.contains(&next) && !distances.contains_key(&next) {
+                        distances.insert(next.clone(), steps + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+/// A player's solved ladders, as the number of steps taken keyed by day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LadderStats {
+    pub solved: BTreeMap<u32, u32>,
+}
+
+impl LadderStats {
+    /// Consecutive days solved up to today, not counting today against
+    /// the streak if it hasn't been played yet
+    pub fn streak(&self, today: u32) -> u32 {
+        let latest = if self.solved.contains_key(&today) {
+            today
+        } else {
+            today.saturating_sub(1)
+        };
+
+        (0..=latest)
+            .rev()
+            .take_while(|day| self.solved.contains_key(day))
+            .count() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::judge::WordData;
+
+    fn dict(words: &[&str]) -> WordDict {
+        words
+            .iter()
+            .map(|w| {
+                (
+                    w.to_string(),
+                    WordData {
+                        extensions: 0,
+                        rel_freq: 1.0,
+                        objectionable: false,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn validates_ladders() {
+        let words = dict(&["cold", "cord", "card", "ward", "warm", "word", "worm"]);
+        let ladder = WordLadder {
+            day: 0,
+            start: "cold".into(),
+            target: "warm".into(),
+            par: 4,
+        };
+        let to_vec = |s: &[&str]| s.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            ladder.validate(&to_vec(&["cold", "cord", "card", "ward", "warm"]), &words),
+            Ok(4)
+        );
+        assert_eq!(
+            ladder.validate(&to_vec(&["cold", "cord", "word", "worm", "warm"]), &words),
+            Ok(4)
+        );
+        assert_eq!(
+            ladder.validate(&to_vec(&["cord", "card"]), &words),
+            Err(LadderError::WrongStart)
+        );
+        assert_eq!(
+            ladder.validate(&to_vec(&["cold", "cord", "card"]), &words),
+            Err(LadderError::Unfinished)
+        );
+        assert_eq!(
+            ladder.validate(&to_vec(&["cold", "ward"]), &words),
+            Err(LadderError::TooManyChanges("cold".into(), "ward".into()))
+        );
+        assert_eq!(
+            ladder.validate(&to_vec(&["cold", "colt"]), &words),
+            Err(LadderError::NotAWord("colt".into()))
+        );
+    }
+
+    #[test]
+    fn streaks() {
+        let stats = LadderStats {
+            solved: BTreeMap::from([(3, 4), (5, 4), (6, 3), (7, 5)]),
+        };
+        assert_eq!(stats.streak(7), 3);
+        assert_eq!(stats.streak(8), 3);
+        assert_eq!(stats.streak(9), 0);
+        assert_eq!(stats.streak(4), 1);
+        assert_eq!(LadderStats::default().streak(0), 0);
+    }
+
+    #[test]
+    fn daily_ladders_are_stable_and_solvable() {
+        let words = dict(&[
+            "cold", "cord", "card", "ward", "warm", "word", "worm", "wore", "core", "care",
+        ]);
+
+        let ladder = WordLadder::daily(12, &words).expect("A ladder should exist");
+        assert_eq!(Some(ladder.clone()), WordLadder::daily(12, &words));
+        assert!((LADDER_MIN_STEPS..=LADDER_MAX_STEPS).contains(&ladder.par));
+
+        let distances = WordLadder::distances_from(
+            &ladder.start,
+            &words.keys().map(str::to_string).collect::<HashSet<_>>(),
+        );
+        assert_eq!(distances.get(&ladder.target), Some(&ladder.par));
+    }
+}
@@ -1,13 +1,29 @@
+pub mod archive;
 pub mod bag;
 pub mod board;
+pub mod dictionary;
+pub mod difficulty;
 pub mod emojification;
 pub mod error;
+pub mod finder;
+pub mod flags;
 pub mod game;
 pub mod generation;
 pub mod judge;
+pub mod ladder;
+pub mod mastery;
 pub mod messages;
 pub mod moves;
 pub mod npc;
+pub mod packs;
 pub mod player;
+pub mod prediction;
+pub mod replay;
 pub mod reporting;
+pub mod rng;
+pub mod rotation;
 pub mod rules;
+pub mod rush;
+pub mod schedule;
+pub mod season;
+pub mod word_of_day;
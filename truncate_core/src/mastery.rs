@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    judge::Outcome,
+    reporting::{BattleReport, BattleWord},
+};
+
+/// Battles need to have been fought with a letter this many times before its win rate says much
+pub const MASTERY_MIN_BATTLES: u32 = 5;
+
+/// Which side of a battle a player was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BattleSide {
+    Attacker,
+    Defender,
+}
+
+/// How many battles a player has won and lost with words containing a letter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LetterRecord {
+    pub won: u32,
+    pub lost: u32,
+}
+
+impl LetterRecord {
+    pub fn battles(&self) -> u32 {
+        self.won + self.lost
+    }
+
+    pub fn win_rate(&self) -> Option<f32> {
+        match self.battles() {
+            0 => None,
+            battles => Some(self.won as f32 / battles as f32),
+        }
+    }
+}
+
+/// How effectively a player uses each letter, from the battles fought with words containing it.
+/// A letter counts once per battle, however many of the player's words in that battle held it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LetterMastery {
+    pub letters: BTreeMap<char, LetterRecord>,
+}
+
+impl LetterMastery {
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    /// Counts a battle towards every letter in the words the player fought it with
+    pub fn record_battle(&mut self, battle: &BattleReport, side: BattleSide) {
+        let (words, won) = match side {
+            BattleSide::Attacker => (
+                &battle.attackers,
+                matches!(battle.outcome, Outcome::AttackerWins(_)),
+            ),
+            BattleSide::Defender => (
+                &battle.defenders,
+                matches!(battle.outcome, Outcome::DefenderWins),
+            ),
+        };
+
+        for letter in Self::letters_of(words) {
+            let record = self.letters.entry(letter).or_default();
+            if won {
+                record.won += 1;
+            } else {
+                record.lost += 1;
+            }
+        }
+    }
+
+    /// Adds another set of battles, such as a finished game, onto these
+    pub fn merge(&mut self, other: &LetterMastery) {
+        for (letter, record) in &other.letters {
+            let existing = self.letters.entry(*letter).or_default();
+            existing.won += record.won;
+            existing.lost += record.lost;
+        }
+    }
+
+    /// Letters fought with often enough to judge, from the highest win rate to the lowest
+    pub fn ranked(&self) -> Vec<(char, f32)> {
+        let mut ranked: Vec<_> = self
+            .letters
+            .iter()
+            .filter(|(_, record)| record.battles() >= MASTERY_MIN_BATTLES)
+            .filter_map(|(letter, record)| Some((*letter, record.win_rate()?)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked
+    }
+
+    fn letters_of(words: &[BattleWord]) -> BTreeSet<char> {
+        words
+            .iter()
+            .flat_map(|word| word.resolved_word.chars())
+            .filter(|c| c.is_alphabetic())
+            .flat_map(|c| c.to_uppercase())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(word: &str) -> BattleWord {
+        BattleWord {
+            original_word: word.into(),
+            resolved_word: word.into(),
+            meanings: None,
+            valid: Some(true),
+        }
+    }
+
+    fn battle(attackers: &[&str], defenders: &[&str], outcome: Outcome) -> BattleReport {
+        BattleReport {
+            battle_number: None,
+            attackers: attackers.iter().map(|w| word(w)).collect(),
+            defenders: defenders.iter().map(|w| word(w)).collect(),
+            outcome,
+            amendment: None,
+        }
+    }
+
+    #[test]
+    fn letters_count_once_per_battle() {
+        let mut attacker = LetterMastery::default();
+        let mut defender = LetterMastery::default();
+        let report = battle(&["BIG", "BAG"], &["TO"], Outcome::AttackerWins(vec![0]));
+        attacker.record_battle(&report, BattleSide::Attacker);
+        defender.record_battle(&report, BattleSide::Defender);
+
+        assert_eq!(attacker.letters[&'B'], LetterRecord { won: 1, lost: 0 });
+        assert_eq!(attacker.letters[&'G'], LetterRecord { won: 1, lost: 0 });
+        assert_eq!(attacker.letters.len(), 4);
+        assert_eq!(defender.letters[&'T'], LetterRecord { won: 0, lost: 1 });
+    }
+
+    #[test]
+    fn rank_letters_by_win_rate() {
+        let mut mastery = LetterMastery::default();
+        for _ in 0..4 {
+            mastery.record_battle(
+                &battle(&["AX"], &["Q"], Outcome::AttackerWins(vec![0])),
+                BattleSide::Attacker,
+            );
+        }
+        for _ in 0..4 {
+            mastery.record_battle(
+                &battle(&["AZ"], &["Q"], Outcome::DefenderWins),
+                BattleSide::Attacker,
+            );
+        }
+
+        let mut other = LetterMastery::default();
+        other.record_battle(
+            &battle(&["X"], &["Q"], Outcome::AttackerWins(vec![0])),
+            BattleSide::Attacker,
+        );
+        mastery.merge(&other);
+
+        // Z hasn't been fought with enough to be ranked
+        assert_eq!(mastery.ranked(), vec![('X', 1.0), ('A', 0.5)]);
+    }
+}
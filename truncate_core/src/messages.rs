@@ -8,16 +8,66 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     board::{Board, Coordinate},
-    game::Game,
+    difficulty::PuzzleDifficulty,
+    flags::Flags,
+    game::{Adjudication, Game, GameOutcome},
+    ladder::LadderStats,
+    mastery::LetterMastery,
     moves::Move,
+    npc::progression::NpcProgress,
+    packs::DictionaryPack,
     player::{Hand, Player},
-    reporting::{Change, WordMeaning},
+    prediction::PredictionStats,
+    reporting::{Change, ThinkChange, WordMeaning},
+    rotation::PuzzleCategory,
+    rules::{Appeals, GameRules, Language, RulesPreset, RulesWarning},
+    rush::{PuzzleRush, RushLeaderboard},
+    schedule::ScheduledGame,
+    season::Season,
+    word_of_day::WordOfTheDay,
 };
 
 pub type RoomCode = String;
 pub type PlayerNumber = u64;
 pub type TruncateToken = String;
 
+/// Which conversation within a room a chat message belongs to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub enum ChatChannel {
+    Players,
+    /// Kept apart from the players so that nobody can be coached mid-game
+    Observers,
+}
+
+impl fmt::Display for ChatChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChatChannel::Players => write!(f, "players"),
+            ChatChannel::Observers => write!(f, "observers"),
+        }
+    }
+}
+
+/// An outside account that players can sign in with, in place of a stored token
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub enum OAuthProvider {
+    GitHub,
+    Google,
+}
+
+impl OAuthProvider {
+    pub const ALL: [OAuthProvider; 2] = [OAuthProvider::GitHub, OAuthProvider::Google];
+}
+
+impl fmt::Display for OAuthProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OAuthProvider::GitHub => write!(f, "GitHub"),
+            OAuthProvider::Google => write!(f, "Google"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct Nonce {
     pub generated_at: u64,
@@ -30,14 +80,42 @@ pub struct NoncedPlayerMessage {
     pub message: PlayerMessage,
 }
 
+/// What a client could capture about its own crash, without anything identifying the player
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CrashReport {
+    pub client_version: String,
+    /// The part of the client that was open, e.g. the tutorial or an active game
+    pub region: String,
+    pub panic_message: String,
+    /// The kinds of message most recently sent and received, oldest first
+    pub recent_messages: Vec<String>,
+    /// Hash of the last board the client was sent, to match against server logs
+    pub board_hash: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum PlayerMessage {
     Ping,
+    /// Asks the server to echo back when (in milliseconds) we sent this, to measure our latency
+    LatencyPing(u64),
     NewGame {
         player_name: String,
         effective_day: u32,
+        /// Opens the room with these rules rather than the rules of the day
+        #[serde(default)]
+        rules: Option<GameRules>,
     },
     JoinGame(RoomCode, String, Option<TruncateToken>),
+    /// Waits for a random opponent who wants to play the same rules, opening a room for the two of them
+    FindMatch {
+        player_name: String,
+        effective_day: u32,
+        ruleset: RulesPreset,
+        /// Our round trip to the server, so that we're paired with someone nearby
+        latency_ms: Option<u64>,
+    },
+    /// Stops waiting for a random opponent
+    CancelMatch,
     RejoinGame(TruncateToken),
     EditBoard(Board),
     EditName(String),
@@ -45,6 +123,12 @@ pub enum PlayerMessage {
     Resign,
     Place(Coordinate, char),
     Swap(Coordinate, Coordinate),
+    /// Trades a tile in hand for one from the bag, when the rules allow it
+    Exchange(char),
+    /// Redraws a poor starting hand, when the rules allow it
+    Mulligan,
+    /// Asks for the last battle to be re-judged against a wider dictionary
+    Appeal,
     Rematch,
     Pause,
     Unpause,
@@ -63,33 +147,254 @@ pub enum PlayerMessage {
         user_agent: String,
         referrer: String,
     },
-    LoadDailyPuzzle(TruncateToken, u32),
+    /// Asks where to send the player to sign in with a provider,
+    /// who will be sent back to `redirect_uri` afterwards
+    StartSignIn {
+        provider: OAuthProvider,
+        redirect_uri: String,
+    },
+    /// Finishes signing in with the code and state a provider sent the player back with.
+    /// Accounts signing in for the first time are linked to the player already logged in.
+    SignIn {
+        code: String,
+        state: String,
+    },
+    /// Loads any partial attempt at the day's puzzle in the given language
+    LoadDailyPuzzle(TruncateToken, u32, Language),
+    /// Loads any partial attempt at one of a category's puzzles, such as the weekly puzzle,
+    /// where `period` counts the category's puzzles
+    LoadCategoryPuzzle {
+        player_token: TruncateToken,
+        category: PuzzleCategory,
+        period: u32,
+    },
     PersistPuzzleMoves {
         player_token: TruncateToken,
+        /// The day of a daily puzzle, or the period of a puzzle from another category
         day: u32,
         human_player: u32,
         moves: Vec<Move>,
+        /// Seconds spent on each of the moves
+        think_secs: Vec<u32>,
         won: bool,
+        #[serde(default)]
+        category: PuzzleCategory,
+        /// Daily puzzles are played per language, each with their own results
+        #[serde(default)]
+        language: Language,
+        /// The rules the puzzle was played under, kept so the attempt can be archived
+        #[serde(default)]
+        rules_generation: Option<u32>,
+        /// The generation and seed of the puzzle's board, kept so the attempt can be archived
+        #[serde(default)]
+        board_seed: Option<(u32, u32)>,
+    },
+    /// Requests the player's daily puzzle history in the given language, along with their other stats
+    RequestStats(TruncateToken, Language),
+    LoadWordLadder(TruncateToken),
+    PersistWordLadder {
+        player_token: TruncateToken,
+        day: u32,
+        words: Vec<String>,
     },
-    RequestStats(TruncateToken),
+    LoadNpcProgress(TruncateToken),
+    /// Records a win against a rung of the NPC ladder, which the server replays before storing
+    PersistNpcWin {
+        player_token: TruncateToken,
+        rung: u32,
+        moves: Vec<Move>,
+        rules_generation: u32,
+        /// The generation and seed of the board the game was played on
+        board_seed: (u32, u32),
+        /// Which player the human was, as they alternate who starts across rematches
+        human_player: usize,
+    },
+    /// Requests the day's puzzle rush leaderboard, including a personal best when logged in
+    LoadPuzzleRush {
+        player_token: Option<TruncateToken>,
+        day: u32,
+        /// Each language has its own leaderboard
+        #[serde(default)]
+        language: Language,
+    },
+    /// Asks the server for a seed to start a ranked puzzle rush from, which also starts its clock
+    StartPuzzleRush(TruncateToken),
+    PersistPuzzleRush {
+        player_token: TruncateToken,
+        day: u32,
+        rush: PuzzleRush,
+        #[serde(default)]
+        language: Language,
+    },
+    /// Adds a game of predicting an opponent's moves onto the player's prediction accuracy
+    PersistPredictions {
+        player_token: TruncateToken,
+        stats: PredictionStats,
+    },
+    /// Requests the word of the day, with its definitions
+    LoadWordOfTheDay(u32),
     LoadReplay(String),
+    /// Requests every stored game for the player, in the `archive` text format
+    ExportGames(TruncateToken),
     MarkChangelogRead(String),
     GenericEvent {
         name: String,
     },
+    /// Sends a crash from a previous session, once the player has agreed to share it
+    ReportCrash(CrashReport),
+    /// Lets the other players know whether this player appears to be away
+    Presence {
+        away: bool,
+    },
+    Chat {
+        channel: ChatChannel,
+        text: String,
+    },
+    /// Flashes a marker on a square for the other players, from this player's view of the board
+    Point(Coordinate),
+    /// Stops (or resumes) delivery of a chat channel to this player
+    MuteChat {
+        channel: ChatChannel,
+        muted: bool,
+    },
+    /// Confirms that every turn's changes up to this sequence number have arrived
+    AckEvents(u64),
+    /// Asks for the changes from every turn after this sequence number, e.g. after reconnecting
+    RequestEventsSince(u64),
+    /// Asks for aggregate difficulty stats for a range of daily puzzles, for operators only
+    RequestPuzzleDifficulty {
+        admin_key: String,
+        from_day: u32,
+        to_day: u32,
+        #[serde(default)]
+        language: Language,
+    },
+    /// Has the server post a room's start and end to a webhook, e.g. for tournament brackets.
+    /// For operators only.
+    FlagRoom {
+        admin_key: String,
+        room_code: RoomCode,
+        webhook_url: String,
+    },
+    /// Steps into a game in progress as a tournament arbiter, with a reason that both players are shown.
+    /// For operators only.
+    Arbitrate {
+        admin_key: String,
+        room_code: RoomCode,
+        action: ArbiterAction,
+        reason: String,
+    },
+    /// Watches a room without playing in it, trailing behind the players by the room's delay
+    SpectateGame(RoomCode),
+    /// Sets how many seconds spectators trail behind the game, before the game starts
+    SetSpectatorDelay(u64),
+    /// Swaps everyone's hands every this many turns, before the game starts. Zero turns it off.
+    SetHandSwaps(u32),
+    /// Accepts words a letter away from a real word, for rooms of young players, before the game starts
+    SetLenientSpelling(bool),
+    /// Switches the room to another language's letters and dictionary, before the game starts
+    SetLanguage(Language),
+    /// Judges the room's words against one of the server's dictionary packs, before the game starts.
+    /// `None` goes back to the language's usual dictionary.
+    SetDictionaryPack(Option<String>),
+    /// Asks which dictionary packs the server offers
+    RequestPacks,
+    /// Downloads the word list of one of the server's dictionary packs
+    DownloadPack(String),
+    /// Downloads a language's word list and daily puzzles, for playing the daily puzzle in that language
+    DownloadLanguage(Language),
+    /// Sets how many blank tiles are in the bag, before the game starts
+    SetBlankTiles(usize),
+    /// Replaces the room's rules, such as with one of the presets, before the game starts.
+    /// The room keeps its language.
+    SetRules(GameRules),
+    /// Saves a board from the editor into a named slot, replacing any board already in that slot
+    SaveBoard {
+        player_token: TruncateToken,
+        name: String,
+        board: Board,
+    },
+    LoadSavedBoards(TruncateToken),
+    DeleteSavedBoard {
+        player_token: TruncateToken,
+        board_id: String,
+    },
+    /// Submits a saved board to the public gallery, where it waits on a moderator
+    ShareBoard {
+        player_token: TruncateToken,
+        board_id: String,
+    },
+    /// Lets a board shared to the gallery be seen publicly, or keeps it out. For operators only.
+    ModerateBoard {
+        admin_key: String,
+        board_id: String,
+        approved: bool,
+    },
+    /// Requests the best rated boards in the public gallery
+    LoadGallery,
+    /// Sets up the lobby with a board from the gallery, which counts as a play once the game starts
+    UseGalleryBoard(String),
+    /// Rates a gallery board from one to five stars, replacing any earlier rating from this player
+    RateBoard {
+        player_token: TruncateToken,
+        board_id: String,
+        stars: u8,
+    },
+    RequestFairPlay(TruncateToken),
+    /// Disputes a recorded abandonment, e.g. for a dropped connection, for moderators to review
+    AppealAbandonment {
+        player_token: TruncateToken,
+        abandonment_id: String,
+        reason: String,
+    },
+    /// Gives another player in the lobby a role, for the room's host only
+    PromotePlayer {
+        player: usize,
+        role: RoomRole,
+    },
+    /// Removes a player from the lobby before the game starts, for the room's host and co-hosts
+    KickPlayer(usize),
+    /// Arranges a game for a later time, as seconds since the unix epoch,
+    /// which another player joins by accepting its invite
+    ScheduleGame {
+        player_token: TruncateToken,
+        player_name: String,
+        effective_day: u32,
+        starts_at: u64,
+        ruleset: RulesPreset,
+    },
+    AcceptScheduledGame {
+        player_token: TruncateToken,
+        player_name: String,
+        invite_code: String,
+    },
+    /// Requests the player's games that haven't been played yet
+    LoadScheduledGames(TruncateToken),
+    CancelScheduledGame {
+        player_token: TruncateToken,
+        invite_code: String,
+    },
 }
 
 impl fmt::Display for PlayerMessage {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             PlayerMessage::Ping => write!(f, "Player ping"),
+            PlayerMessage::LatencyPing(sent_at) => write!(f, "Latency ping sent at {sent_at}"),
             PlayerMessage::NewGame {
                 player_name,
                 effective_day,
+                ..
             } => write!(
                 f,
                 "Create a new game as player {player_name} at day {effective_day}"
             ),
+            PlayerMessage::FindMatch {
+                player_name,
+                ruleset,
+                ..
+            } => write!(f, "Find a {ruleset} match as player {player_name}"),
+            PlayerMessage::CancelMatch => write!(f, "Stop looking for a match"),
             PlayerMessage::JoinGame(room, name, token) => {
                 write!(
                     f,
@@ -105,6 +410,9 @@ impl fmt::Display for PlayerMessage {
             PlayerMessage::Resign => write!(f, "Resign"),
             PlayerMessage::Place(coord, tile) => write!(f, "Place {} at {}", tile, coord),
             PlayerMessage::Swap(a, b) => write!(f, "Swap the tiles at {} and {}", a, b),
+            PlayerMessage::Exchange(tile) => write!(f, "Exchange {}", tile),
+            PlayerMessage::Mulligan => write!(f, "Mulligan"),
+            PlayerMessage::Appeal => write!(f, "Appeal the last battle"),
             PlayerMessage::Rematch => write!(f, "Rematch!"),
             PlayerMessage::Pause => write!(f, "Pause!"),
             PlayerMessage::Unpause => write!(f, "Unpause!"),
@@ -115,22 +423,229 @@ impl fmt::Display for PlayerMessage {
             PlayerMessage::Login { .. } => {
                 write!(f, "Login as an existing player")
             }
-            PlayerMessage::LoadDailyPuzzle(_token, day) => {
-                write!(f, "Load any partial puzzle for day {day:?}")
+            PlayerMessage::StartSignIn { provider, .. } => {
+                write!(f, "Start signing in with {provider}")
+            }
+            PlayerMessage::SignIn { .. } => write!(f, "Finish signing in with a provider"),
+            PlayerMessage::LoadDailyPuzzle(_token, day, language) => {
+                write!(f, "Load any partial {language} puzzle for day {day:?}")
+            }
+            PlayerMessage::LoadCategoryPuzzle {
+                category, period, ..
+            } => {
+                write!(
+                    f,
+                    "Load any partial {category:?} puzzle for period {period}"
+                )
             }
             PlayerMessage::PersistPuzzleMoves {
                 player_token: _,
                 human_player: _,
                 day,
                 moves,
+                think_secs: _,
                 won: _,
+                category,
+                language,
+                rules_generation: _,
+                board_seed: _,
             } => {
-                write!(f, "Persist {} move(s) for day {day:?}", moves.len())
+                write!(
+                    f,
+                    "Persist {} move(s) for {language} {category:?} puzzle {day:?}",
+                    moves.len()
+                )
+            }
+            PlayerMessage::RequestStats(_token, language) => {
+                write!(f, "Requesting {language} daily puzzle stats!")
+            }
+            PlayerMessage::LoadWordLadder(_token) => write!(f, "Requesting word ladder stats!"),
+            PlayerMessage::PersistWordLadder { day, words, .. } => {
+                write!(f, "Persist a {} word ladder for day {day}", words.len())
+            }
+            PlayerMessage::LoadNpcProgress(_token) => write!(f, "Requesting NPC ladder progress!"),
+            PlayerMessage::PersistNpcWin { rung, moves, .. } => {
+                write!(
+                    f,
+                    "Persist a win over NPC rung {rung} in {} move(s)",
+                    moves.len()
+                )
+            }
+            PlayerMessage::LoadPuzzleRush { day, language, .. } => {
+                write!(
+                    f,
+                    "Requesting the {language} puzzle rush leaderboard for day {day}!"
+                )
+            }
+            PlayerMessage::StartPuzzleRush(_) => write!(f, "Starting a puzzle rush"),
+            PlayerMessage::PersistPuzzleRush { day, rush, .. } => {
+                write!(
+                    f,
+                    "Persist a puzzle rush of {} drill(s) for day {day}",
+                    rush.solved()
+                )
+            }
+            PlayerMessage::PersistPredictions { stats, .. } => {
+                write!(f, "Persist {} move prediction(s)", stats.predictions)
+            }
+            PlayerMessage::LoadWordOfTheDay(day) => {
+                write!(f, "Requesting the word of the day for day {day}!")
             }
-            PlayerMessage::RequestStats(_token) => write!(f, "Requesting daily puzzle stats!"),
             PlayerMessage::LoadReplay(id) => write!(f, "Requesting the replay for {id}!"),
+            PlayerMessage::ExportGames(_token) => write!(f, "Requesting an archive of all games"),
             PlayerMessage::MarkChangelogRead(id) => write!(f, "Marked changelog {id} as read"),
             PlayerMessage::GenericEvent { name } => write!(f, "Tracking a {name} event"),
+            PlayerMessage::ReportCrash(report) => {
+                write!(f, "Reporting a client crash in {}", report.region)
+            }
+            PlayerMessage::Presence { away } => write!(f, "Player is away: {away}"),
+            PlayerMessage::Chat { channel, text } => {
+                write!(f, "Chatting to the {channel}: {text}")
+            }
+            PlayerMessage::Point(coord) => write!(f, "Pointing at {coord}"),
+            PlayerMessage::MuteChat { channel, muted } => {
+                write!(f, "Muting chat from the {channel}: {muted}")
+            }
+            PlayerMessage::AckEvents(seq) => write!(f, "Received events up to #{seq}"),
+            PlayerMessage::RequestEventsSince(seq) => {
+                write!(f, "Requesting events since #{seq}")
+            }
+            PlayerMessage::RequestPuzzleDifficulty {
+                from_day, to_day, ..
+            } => {
+                write!(
+                    f,
+                    "Requesting puzzle difficulty for days {from_day} to {to_day}"
+                )
+            }
+            PlayerMessage::FlagRoom { room_code, .. } => {
+                write!(f, "Flagging room {room_code} for webhooks")
+            }
+            PlayerMessage::Arbitrate {
+                room_code, action, ..
+            } => write!(f, "Arbitrating room {room_code}: {action}"),
+            PlayerMessage::SpectateGame(room_code) => write!(f, "Spectating room {room_code}"),
+            PlayerMessage::SetSpectatorDelay(secs) => {
+                write!(f, "Delaying spectators by {secs} seconds")
+            }
+            PlayerMessage::SetHandSwaps(turns) => write!(f, "Swapping hands every {turns} turns"),
+            PlayerMessage::SetLenientSpelling(lenient) => {
+                write!(f, "Setting lenient spelling to {lenient}")
+            }
+            PlayerMessage::SetLanguage(language) => write!(f, "Playing in {language}"),
+            PlayerMessage::SetDictionaryPack(Some(id)) => write!(f, "Playing with the {id} pack"),
+            PlayerMessage::SetDictionaryPack(None) => {
+                write!(f, "Playing with the usual dictionary")
+            }
+            PlayerMessage::RequestPacks => write!(f, "Request dictionary packs"),
+            PlayerMessage::DownloadPack(id) => write!(f, "Download the {id} pack"),
+            PlayerMessage::DownloadLanguage(language) => write!(f, "Download {language}"),
+            PlayerMessage::SetBlankTiles(count) => write!(f, "Putting {count} blanks in the bag"),
+            PlayerMessage::SetRules(_) => write!(f, "Changing the room's rules"),
+            PlayerMessage::SaveBoard { name, board, .. } => {
+                write!(f, "Save a board as {name}:\n{board}")
+            }
+            PlayerMessage::LoadSavedBoards(_token) => write!(f, "Requesting saved boards"),
+            PlayerMessage::DeleteSavedBoard { board_id, .. } => {
+                write!(f, "Delete saved board {board_id}")
+            }
+            PlayerMessage::ShareBoard { board_id, .. } => {
+                write!(f, "Share board {board_id} to the gallery")
+            }
+            PlayerMessage::ModerateBoard {
+                board_id, approved, ..
+            } => write!(f, "Moderate board {board_id}, approved: {approved}"),
+            PlayerMessage::LoadGallery => write!(f, "Requesting the board gallery"),
+            PlayerMessage::UseGalleryBoard(board_id) => {
+                write!(f, "Set board to gallery board {board_id}")
+            }
+            PlayerMessage::RateBoard {
+                board_id, stars, ..
+            } => write!(f, "Rate board {board_id} {stars} star(s)"),
+            PlayerMessage::RequestFairPlay(_) => write!(f, "Requesting fair play record"),
+            PlayerMessage::AppealAbandonment { abandonment_id, .. } => {
+                write!(f, "Appealing abandonment {abandonment_id}")
+            }
+            PlayerMessage::PromotePlayer { player, role } => {
+                write!(f, "Make player {player} a {role}")
+            }
+            PlayerMessage::KickPlayer(player) => write!(f, "Remove player {player} from the room"),
+            PlayerMessage::ScheduleGame {
+                starts_at, ruleset, ..
+            } => write!(f, "Schedule a {ruleset} game for {starts_at}"),
+            PlayerMessage::AcceptScheduledGame { invite_code, .. } => {
+                write!(f, "Accept the scheduled game {invite_code}")
+            }
+            PlayerMessage::LoadScheduledGames(_) => write!(f, "Requesting scheduled games"),
+            PlayerMessage::CancelScheduledGame { invite_code, .. } => {
+                write!(f, "Cancel the scheduled game {invite_code}")
+            }
+        }
+    }
+}
+
+/// What a tournament arbiter can do to a game in progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArbiterAction {
+    /// Stops every clock and move until the game is resumed
+    Freeze,
+    Resume,
+    /// Gives a player more time on their clock, or takes some away if negative
+    AdjustClock {
+        player: PlayerNumber,
+        secs: i64,
+    },
+    /// Takes back the most recent move, as if it had never been played
+    AnnulLastMove,
+}
+
+impl fmt::Display for ArbiterAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArbiterAction::Freeze => write!(f, "Game frozen"),
+            ArbiterAction::Resume => write!(f, "Game resumed"),
+            ArbiterAction::AdjustClock { player, secs } if *secs >= 0 => {
+                write!(f, "{secs}s added to player {player}'s clock")
+            }
+            ArbiterAction::AdjustClock { player, secs } => {
+                write!(
+                    f,
+                    "{}s taken from player {player}'s clock",
+                    secs.unsigned_abs()
+                )
+            }
+            ArbiterAction::AnnulLastMove => write!(f, "Last move annulled"),
+        }
+    }
+}
+
+/// What a player is allowed to change about a room before its game starts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoomRole {
+    /// Whoever opened the room, who can do anything a co-host can and hand out roles
+    Host,
+    /// Can edit the board, change the rules, start the game, and remove players
+    CoHost,
+    #[default]
+    Player,
+    /// Watches the game without a hand, and can't play or change anything in the room
+    Spectator,
+}
+
+impl RoomRole {
+    /// Whether this role can set up the room's game
+    pub fn can_manage(&self) -> bool {
+        matches!(self, RoomRole::Host | RoomRole::CoHost)
+    }
+}
+
+impl fmt::Display for RoomRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoomRole::Host => write!(f, "host"),
+            RoomRole::CoHost => write!(f, "co-host"),
+            RoomRole::Player => write!(f, "player"),
+            RoomRole::Spectator => write!(f, "spectator"),
         }
     }
 }
@@ -140,6 +655,8 @@ pub struct LobbyPlayerMessage {
     pub name: String,
     pub index: usize,
     pub color: (u8, u8, u8),
+    #[serde(default)]
+    pub role: RoomRole,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -150,11 +667,28 @@ pub struct GamePlayerMessage {
     pub allotted_time: Option<Duration>,
     pub time_remaining: Option<Duration>,
     pub turn_starts_no_later_than: Option<u64>,
+    /// Seconds at the start of each turn before this player's clock starts running
+    #[serde(default)]
+    pub clock_delay: u64,
+    /// When this player loses on time if they haven't moved, in games that limit each move
+    #[serde(default)]
+    pub move_deadline: Option<u64>,
     pub paused_turn_delta: Option<i64>,
+    pub appeals_remaining: usize,
+    /// Tiles this player has left to place, if the rules stop them drawing new ones
+    pub tiles_remaining: Option<usize>,
+    /// Action points this player has left this turn, if the rules give them several actions
+    #[serde(default)]
+    pub action_points_remaining: Option<u32>,
 }
 
 impl GamePlayerMessage {
-    pub fn new(p: &Player, _game: &Game) -> Self {
+    pub fn new(p: &Player, game: &Game) -> Self {
+        let appeals_allowed = match game.rules.appeals {
+            Appeals::Disallowed => 0,
+            Appeals::Allowed { per_player } => per_player,
+        };
+
         Self {
             name: p.name.clone(),
             index: p.index,
@@ -162,7 +696,12 @@ impl GamePlayerMessage {
             allotted_time: p.allotted_time,
             time_remaining: p.time_remaining,
             turn_starts_no_later_than: p.turn_starts_no_later_than,
+            clock_delay: game.clock_delay(),
+            move_deadline: game.move_deadline(p.index),
             paused_turn_delta: p.paused_turn_delta,
+            appeals_remaining: appeals_allowed.saturating_sub(p.appeals_used),
+            tiles_remaining: p.tiles_remaining(&game.rules.tile_draw),
+            action_points_remaining: p.action_points_remaining(&game.rules.turn_economy),
         }
     }
 }
@@ -176,11 +715,34 @@ pub struct GameStateMessage {
     pub board: Board,
     pub hand: Hand,
     pub changes: Vec<Change>,
+    /// Which turn `changes` came from, counting up from one.
+    /// Zero for games that aren't relayed through the server.
+    pub event_seq: u64,
     pub game_ends_at: Option<u64>,
     pub remaining_turns: Option<u64>,
+    /// Turns left until hands are swapped, in games that swap them
+    pub hand_swap_in: Option<u64>,
     pub paused: bool,
 }
 
+/// One turn's worth of changes, numbered so that a reconnecting player can catch up on them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequencedChanges {
+    pub seq: u64,
+    pub changes: Vec<Change>,
+}
+
+impl GameStateMessage {
+    /// The same view with every hand hidden, including the tiles drawn and played from them,
+    /// for sending to spectators
+    pub fn for_spectators(mut self) -> Self {
+        self.hand = Hand(vec![]);
+        self.changes
+            .retain(|change| !matches!(change, Change::Hand(_)));
+        self
+    }
+}
+
 impl fmt::Display for GameStateMessage {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -198,11 +760,68 @@ impl fmt::Display for GameStateMessage {
     }
 }
 
+/// Information hidden from players during play, revealed once the game is over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameEndStateMessage {
+    /// Final hands of every player, indexed by player number
+    pub hands: Vec<Hand>,
+    pub bag: Vec<char>,
+    /// How long every turn of the game was thought over
+    pub think_times: Vec<ThinkChange>,
+    /// Who won, and whether it came down to a tiebreaker, or why the game was drawn
+    pub outcome: GameOutcome,
+}
+
+impl GameEndStateMessage {
+    pub fn new(game: &Game) -> Option<Self> {
+        let outcome = game.outcome()?;
+
+        Some(Self {
+            hands: game.players.iter().map(|p| p.hand.clone()).collect(),
+            bag: game.bag.remaining_tiles().to_vec(),
+            think_times: game.think_times.clone(),
+            outcome,
+        })
+    }
+}
+
+impl fmt::Display for GameEndStateMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "• {}", self.outcome)?;
+        for (player, hand) in self.hands.iter().enumerate() {
+            writeln!(f, "• Player {player} held: {hand}")?;
+        }
+        write!(
+            f,
+            "• Left in the bag: {}",
+            self.bag.iter().collect::<String>()
+        )
+    }
+}
+
+/// A finished online game from the replay archive, with everything needed to play it back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameReplayMessage {
+    pub player_names: Vec<String>,
+    pub rules: GameRules,
+    /// Seeds the bag, so that players are dealt the same tiles as they were in the game
+    pub bag_seed: u64,
+    pub board: Board,
+    pub moves: Vec<Move>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailyStateMessage {
+    /// The day of a daily puzzle, or the period of a puzzle from another category
     pub puzzle_day: u32,
     pub attempt: u32,
     pub current_moves: Vec<Move>,
+    /// Seconds spent on each of the moves, where they were recorded
+    pub think_secs: Vec<u32>,
+    #[serde(default)]
+    pub category: PuzzleCategory,
+    #[serde(default)]
+    pub language: Language,
 }
 
 impl fmt::Display for DailyStateMessage {
@@ -235,6 +854,12 @@ pub struct DailyResult {
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DailyStats {
     pub days: BTreeMap<u32, DailyResult>,
+    /// How the player has fared with each letter across their online games
+    #[serde(default)]
+    pub letter_mastery: LetterMastery,
+    /// How well the player has guessed their opponents' moves in prediction training
+    #[serde(default)]
+    pub prediction_stats: PredictionStats,
 }
 
 impl DailyStats {
@@ -251,13 +876,103 @@ impl DailyStats {
             }
         }
     }
+
+    /// How many of the latest days played have been won in a row
+    pub fn streak_length(&self) -> usize {
+        self.days
+            .values()
+            .rev()
+            .enumerate()
+            .find_map(|(streak_length, day)| {
+                if day.attempts.iter().any(|a| a.won) {
+                    None
+                } else {
+                    Some(streak_length)
+                }
+            })
+            .unwrap_or(self.days.len())
+    }
+}
+
+/// Where a saved board stands in the public gallery
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GalleryStatus {
+    /// Shared, but not yet seen by a moderator
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl fmt::Display for GalleryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GalleryStatus::Pending => write!(f, "pending"),
+            GalleryStatus::Approved => write!(f, "approved"),
+            GalleryStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+/// A board a player has built in the editor and kept on their account
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedBoard {
+    pub id: String,
+    pub name: String,
+    pub board: Board,
+    /// Only set once the board has been shared to the gallery
+    pub gallery: Option<GalleryStatus>,
+}
+
+/// A board from the public gallery, with how it's been received
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GalleryBoard {
+    pub id: String,
+    pub name: String,
+    pub author: Option<String>,
+    pub board: Board,
+    pub plays: u32,
+    /// The average of every rating, out of five stars
+    pub rating: Option<f32>,
+    pub ratings: u32,
+}
+
+/// A game the player left partway through, without coming back before the grace window ran out
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Abandonment {
+    pub id: String,
+    pub room_code: RoomCode,
+    /// Seconds since the unix epoch
+    pub abandoned_at: u64,
+    pub appealed: bool,
+}
+
+/// How reliably a player sees their online games through to the end
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FairPlayRecord {
+    /// Out of 100, dropping with each game abandoned in the last thirty days
+    pub rating: u8,
+    pub recent_abandonments: Vec<Abandonment>,
+    /// How long the player must wait before starting or joining another game
+    pub penalty_secs: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GameMessage {
     Ping,
+    /// Reply to a `PlayerMessage::LatencyPing`, with the time it was sent at
+    LatencyPong(u64),
     Ack(Nonce),
     PleaseLogin,
+    /// The server is full, so a new room will be opened once space frees up
+    Queued {
+        position: usize,
+        estimated_wait_secs: Option<u64>,
+    },
+    /// Looking for a random opponent, alongside this many players wanting the same rules
+    FindingMatch {
+        ruleset: RulesPreset,
+        waiting: usize,
+    },
     JoinedLobby(
         PlayerNumber,
         RoomCode,
@@ -269,7 +984,22 @@ pub enum GameMessage {
     StartedGame(GameStateMessage),
     GameTimingUpdate(GameStateMessage),
     GameUpdate(GameStateMessage),
-    GameEnd(GameStateMessage, PlayerNumber),
+    GameEnd(GameStateMessage, PlayerNumber, GameEndStateMessage),
+    GameDrawn(GameStateMessage, Adjudication, GameEndStateMessage),
+    PlayerPresence {
+        player: PlayerNumber,
+        away: bool,
+    },
+    /// Another player is pointing at a square, given from the recipient's view of the board
+    Pointed {
+        player: PlayerNumber,
+        coordinate: Coordinate,
+    },
+    Chat {
+        channel: ChatChannel,
+        sender: String,
+        text: String,
+    },
     GameError(RoomCode, PlayerNumber, String),
     GenericError(String),
     SupplyDefinitions(Vec<(String, Option<Vec<WordMeaning>>)>),
@@ -277,17 +1007,99 @@ pub enum GameMessage {
         token: TruncateToken,
         unread_changelogs: Vec<String>,
     },
+    ActiveSeason(Option<Season>),
+    /// The providers this server lets players sign in with
+    SignInProviders(Vec<OAuthProvider>),
+    /// Where to send the player to sign in with a provider
+    SignInRedirect(String),
+    /// Which experiments are switched on for this connection
+    SessionFlags(Flags),
+    MissedEvents(Vec<SequencedChanges>),
     ResumeDailyPuzzle(DailyStateMessage, Option<DailyStateMessage>), // (latest, best)
     DailyStats(DailyStats),
+    WordLadderStats(LadderStats),
+    NpcProgress(NpcProgress),
+    PuzzleRushLeaderboard(RushLeaderboard),
+    /// The seed for a ranked puzzle rush, whose clock started when it was sent
+    PuzzleRushStarted(u32),
+    WordOfTheDay(WordOfTheDay),
     LoadDailyReplay(DailyStateMessage),
+    LoadGameReplay(GameReplayMessage),
+    GameArchive(String),
+    PuzzleDifficulty(Vec<PuzzleDifficulty>),
+    /// How many seconds spectators of the room will trail behind, sent to the players in the lobby
+    SpectatorDelay(u64),
+    /// How many turns pass between hand swaps in the room, sent to the players in the lobby
+    HandSwaps(u32),
+    /// Whether the room accepts close misspellings, sent to the players in the lobby
+    LenientSpelling(bool),
+    /// The language the room is played in, and those the server has dictionaries for,
+    /// sent to the players in the lobby
+    RoomLanguage {
+        language: Language,
+        available: Vec<Language>,
+    },
+    /// How many blank tiles are in the room's bag, sent to the players in the lobby
+    BlankTiles(usize),
+    /// The rules the room will be played with, sent to the players in the lobby
+    RoomRules(GameRules),
+    /// Anything odd about the room's rules for the players in it, sent whenever either changes
+    RulesWarnings(Vec<RulesWarning>),
+    /// The dictionary packs the server offers
+    AvailablePacks(Vec<DictionaryPack>),
+    /// A pack's word list, to be checked against the pack's checksum before it's used
+    PackContents(DictionaryPack, String),
+    /// A language's word list and the notes for its daily puzzles,
+    /// or `None` for each that the server doesn't have
+    LanguageContents {
+        language: Language,
+        words: Option<String>,
+        seed_notes: Option<String>,
+    },
+    /// The game as it stood `delay_secs` ago, for spectators. Hands are left out.
+    SpectatorUpdate {
+        state: GameStateMessage,
+        delay_secs: u64,
+    },
+    /// Every board the player has saved, sent whenever they change
+    SavedBoards(Vec<SavedBoard>),
+    Gallery(Vec<GalleryBoard>),
+    /// The game that just started is on a gallery board, which players can rate afterwards
+    PlayingGalleryBoard {
+        board_id: String,
+        name: String,
+    },
+    FairPlay(FairPlayRecord),
+    /// The player was removed from the room's lobby by its host or a co-host
+    Kicked(RoomCode),
+    /// A tournament arbiter has stepped into the game, leaving it as it now stands
+    ArbiterNotice {
+        state: GameStateMessage,
+        action: ArbiterAction,
+        reason: String,
+    },
+    /// The player's starting hand is poor enough to be redrawn,
+    /// with how far it strays from the odds of the bag in bits
+    MulliganOffer {
+        divergence_bits: f32,
+    },
+    /// The player's upcoming games, soonest first, sent whenever they change
+    ScheduledGames(Vec<ScheduledGame>),
 }
 
 impl fmt::Display for GameMessage {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             GameMessage::Ping => write!(f, "Game ping"),
+            GameMessage::LatencyPong(sent_at) => write!(f, "Latency pong for {sent_at}"),
             GameMessage::Ack(_) => write!(f, "ACK"),
             GameMessage::PleaseLogin => write!(f, "Server is requesting player to login"),
+            GameMessage::Queued { position, .. } => {
+                write!(f, "Waiting for space on the server, #{position} in line")
+            }
+            GameMessage::FindingMatch { ruleset, waiting } => {
+                write!(f, "Looking for a {ruleset} match, with {waiting} waiting")
+            }
             GameMessage::JoinedLobby(player, room, players, board, _token) => write!(
                 f,
                 "Joined lobby {} as player {} with players {}. Board is:\n{}",
@@ -315,9 +1127,27 @@ impl fmt::Display for GameMessage {
             GameMessage::StartedGame(game) => write!(f, "Started game:\n{}", game),
             GameMessage::GameTimingUpdate(game) => write!(f, "Update to timing:\n{}", game),
             GameMessage::GameUpdate(game) => write!(f, "Update to game:\n{}", game),
-            GameMessage::GameEnd(game, winner) => {
-                write!(f, "Conclusion of game, winner was {}:\n{}", winner, game)
+            GameMessage::GameEnd(game, winner, end_state) => write!(
+                f,
+                "Conclusion of game, winner was {}:\n{}\n{}",
+                winner, game, end_state
+            ),
+            GameMessage::GameDrawn(game, adjudication, end_state) => write!(
+                f,
+                "Game was drawn, {}:\n{}\n{}",
+                adjudication, game, end_state
+            ),
+            GameMessage::PlayerPresence { player, away } => {
+                write!(f, "Player {player} is away: {away}")
+            }
+            GameMessage::Pointed { player, coordinate } => {
+                write!(f, "Player {player} is pointing at {coordinate}")
             }
+            GameMessage::Chat {
+                channel,
+                sender,
+                text,
+            } => write!(f, "{sender} to the {channel}: {text}"),
             GameMessage::GameError(_, _, msg) => write!(f, "Error in game: {}", msg),
             GameMessage::GenericError(msg) => write!(f, "Generic error: {}", msg),
             GameMessage::SupplyDefinitions(_) => {
@@ -326,11 +1156,97 @@ impl fmt::Display for GameMessage {
             GameMessage::LoggedInAs { .. } => {
                 write!(f, "Logged in as a player")
             }
+            GameMessage::ActiveSeason(Some(season)) => {
+                write!(f, "The {season} season is running")
+            }
+            GameMessage::ActiveSeason(None) => write!(f, "No season is running"),
+            GameMessage::SignInProviders(providers) => {
+                write!(f, "Players can sign in with {providers:?}")
+            }
+            GameMessage::SignInRedirect(_) => write!(f, "Sign in with a provider"),
+            GameMessage::SessionFlags(flags) => write!(f, "Session flags are {flags}"),
+            GameMessage::MissedEvents(events) => {
+                write!(f, "Catching up on {} missed turns", events.len())
+            }
             GameMessage::ResumeDailyPuzzle(puzzle, _best) => {
                 write!(f, "Starting puzzle:\n{}", puzzle)
             }
             GameMessage::DailyStats(stats) => write!(f, "Stats for {} days", stats.days.len()),
+            GameMessage::WordLadderStats(stats) => {
+                write!(f, "Word ladder stats for {} days", stats.solved.len())
+            }
+            GameMessage::NpcProgress(progress) => {
+                write!(f, "Beaten {} NPC ladder rungs", progress.beaten.len())
+            }
+            GameMessage::PuzzleRushLeaderboard(leaderboard) => write!(
+                f,
+                "Puzzle rush leaderboard of {} for day {}",
+                leaderboard.top.len(),
+                leaderboard.day
+            ),
+            GameMessage::PuzzleRushStarted(seed) => {
+                write!(f, "Puzzle rush started with seed {seed}")
+            }
+            GameMessage::WordOfTheDay(daily) => {
+                write!(f, "The word of the day {} is {}", daily.day, daily.word)
+            }
             GameMessage::LoadDailyReplay(puzzle) => write!(f, "Loading puzzle replay:\n{}", puzzle),
+            GameMessage::LoadGameReplay(replay) => write!(
+                f,
+                "Loading replay of {} in {} move(s)",
+                replay.player_names.join(" vs "),
+                replay.moves.len()
+            ),
+            GameMessage::GameArchive(archive) => write!(f, "Game archive:\n{archive}"),
+            GameMessage::PuzzleDifficulty(days) => {
+                write!(f, "Difficulty stats for {} daily puzzles", days.len())
+            }
+            GameMessage::SpectatorDelay(secs) => {
+                write!(f, "Spectators will trail by {secs} seconds")
+            }
+            GameMessage::HandSwaps(turns) => write!(f, "Hands will swap every {turns} turns"),
+            GameMessage::LenientSpelling(lenient) => write!(f, "Lenient spelling is {lenient}"),
+            GameMessage::RoomLanguage { language, .. } => {
+                write!(f, "The room is playing in {language}")
+            }
+            GameMessage::BlankTiles(count) => write!(f, "The bag has {count} blanks"),
+            GameMessage::RoomRules(_) => write!(f, "The room's rules changed"),
+            GameMessage::RulesWarnings(warnings) => {
+                write!(f, "The room's rules have {} warning(s)", warnings.len())
+            }
+            GameMessage::AvailablePacks(packs) => write!(f, "{} dictionary pack(s)", packs.len()),
+            GameMessage::PackContents(pack, _) => {
+                write!(
+                    f,
+                    "Word list for the {} pack, version {}",
+                    pack.id, pack.version
+                )
+            }
+            GameMessage::LanguageContents { language, .. } => {
+                write!(f, "Word list and daily puzzles for {language}")
+            }
+            GameMessage::SpectatorUpdate { state, delay_secs } => write!(
+                f,
+                "Spectator view of {} from {delay_secs} seconds ago",
+                state.room_code
+            ),
+            GameMessage::SavedBoards(boards) => write!(f, "{} saved board(s)", boards.len()),
+            GameMessage::Gallery(boards) => write!(f, "{} board(s) in the gallery", boards.len()),
+            GameMessage::PlayingGalleryBoard { name, .. } => {
+                write!(f, "Playing the gallery board {name}")
+            }
+            GameMessage::FairPlay(record) => write!(f, "Fair play rating of {}", record.rating),
+            GameMessage::Kicked(room) => write!(f, "Removed from room {room}"),
+            GameMessage::ArbiterNotice { action, reason, .. } => {
+                write!(f, "Arbiter: {action} ({reason})")
+            }
+            GameMessage::MulliganOffer { divergence_bits } => {
+                write!(
+                    f,
+                    "Hand can be redrawn, diverging by {divergence_bits:.2} bits"
+                )
+            }
+            GameMessage::ScheduledGames(games) => write!(f, "{} scheduled game(s)", games.len()),
         }
     }
 }
@@ -0,0 +1,185 @@
+//! Applies arbitrary sequences of moves to generated boards, checking that the game
+//! never panics and never ends up in a state that normal play shouldn't be able to reach.
+//! Run with e.g. `PROPTEST_CASES=10000` for a longer search than the default test run.
+
+use proptest::prelude::*;
+
+use crate::{
+    board::{Coordinate, Square},
+    game::Game,
+    generation::{generate_board, BoardSeed},
+    judge::Judge,
+    rules::GameRules,
+};
+
+use super::Move;
+
+/// The raw material for one move, turned into a move against the current game by `to_move`.
+/// Most seeds pick from squares the player could sensibly use, so that games actually progress,
+/// while wild seeds point anywhere on the board to exercise the rejection paths.
+#[derive(Debug, Clone)]
+struct MoveSeed {
+    wild: bool,
+    swap: bool,
+    tile: usize,
+    first: usize,
+    second: usize,
+}
+
+impl MoveSeed {
+    fn to_move(&self, game: &Game, player: usize) -> Move {
+        let width = game.board.width();
+        let height = game.board.height();
+        let wild = |index: usize| Coordinate {
+            x: index % width,
+            y: (index / width) % height,
+        };
+        let pick = |candidates: &[Coordinate], index: usize| {
+            let coord = candidates[index % candidates.len()];
+            game.board
+                .map_game_coord_to_player(
+                    player,
+                    coord,
+                    &game.rules.visibility,
+                    &game.rules.board_orientation,
+                    &game.players[player].seen_tiles,
+                )
+                .unwrap_or(coord)
+        };
+
+        if self.swap {
+            let mut owned: Vec<_> = (0..width * height)
+                .map(wild)
+                .filter(|c| {
+                    matches!(game.board.get(*c), Ok(Square::Occupied { player: p, .. }) if p == player)
+                })
+                .collect();
+            owned.sort();
+
+            let positions = if self.wild || owned.len() < 2 {
+                [wild(self.first), wild(self.second)]
+            } else {
+                [pick(&owned, self.first), pick(&owned, self.second)]
+            };
+            Move::Swap { player, positions }
+        } else {
+            let mut playable: Vec<_> = game
+                .board
+                .playable_positions(player, &game.rules.truncation)
+                .into_iter()
+                .collect();
+            playable.sort();
+
+            // An empty hand has nothing to place, so offer a tile the game should turn down
+            let hand = &game.players[player].hand.0;
+            let tile = match hand.len() {
+                0 => 'A',
+                len => hand[self.tile % len],
+            };
+            Move::Place {
+                player,
+                tile,
+                position: if self.wild || playable.is_empty() {
+                    wild(self.first)
+                } else {
+                    pick(&playable, self.first)
+                },
+            }
+        }
+    }
+}
+
+fn move_seed() -> impl Strategy<Value = MoveSeed> {
+    (
+        prop::bool::weighted(0.1),
+        prop::bool::weighted(0.25),
+        0..16usize,
+        0..1024usize,
+        0..1024usize,
+    )
+        .prop_map(|(wild, swap, tile, first, second)| MoveSeed {
+            wild,
+            swap,
+            tile,
+            first,
+            second,
+        })
+}
+
+fn fuzz_game(seed: u32) -> Option<Game> {
+    let mut board = generate_board(BoardSeed::new(seed)).ok()?.board;
+    board.cache_special_squares();
+
+    let (_, rules) = GameRules::latest(None);
+    let mut game = Game::new(9, 9, Some(seed as u64), rules);
+    game.add_player("A".into());
+    game.add_player("B".into());
+    game.board = board;
+    game.judge = Judge::new(
+        [
+            "BIG", "FAT", "AND", "ART", "ARTS", "TEA", "EAT", "SEA", "RATS", "STAR",
+        ]
+        .into_iter()
+        .map(Into::into)
+        .collect(),
+    );
+    game.start();
+
+    Some(game)
+}
+
+/// Every tile left on the board after a move must still connect back to one of its owner's artifacts
+fn assert_rooted(game: &Game) {
+    let mut rooted = std::collections::HashSet::new();
+    for artifact in game.board.artifacts() {
+        rooted.extend(game.board.depth_first_search(*artifact));
+    }
+
+    for (y, row) in game.board.squares.iter().enumerate() {
+        for (x, square) in row.iter().enumerate() {
+            if let Square::Occupied { .. } = square {
+                assert!(
+                    rooted.contains(&Coordinate { x, y }),
+                    "Tile at ({x}, {y}) was left floating after truncation:\n{}",
+                    game.board
+                );
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn arbitrary_moves_keep_the_game_coherent(
+        seed in 0..100_000u32,
+        seeds in prop::collection::vec(move_seed(), 1..60),
+    ) {
+        let Some(mut game) = fuzz_game(seed) else {
+            return Ok(());
+        };
+        let hand_sizes: Vec<_> = game.players.iter().map(|p| p.hand.len()).collect();
+
+        for (turn, seed) in seeds.iter().enumerate() {
+            let player = turn % game.players.len();
+            let next_move = seed.to_move(&game, player);
+            let board_before = game.board.clone();
+
+            match game.make_move(next_move.clone(), None, None, None) {
+                Ok(_) => assert_rooted(&game),
+                Err(e) => prop_assert_eq!(
+                    &game.board,
+                    &board_before,
+                    "Rejected move {:?} ({}) still changed the board",
+                    next_move,
+                    e
+                ),
+            }
+
+            for (player, size) in game.players.iter().zip(&hand_sizes) {
+                prop_assert_eq!(player.hand.len(), *size, "Hand size changed for {}", player.name);
+            }
+        }
+    }
+}
@@ -1,8 +1,11 @@
+#[cfg(test)]
+mod fuzz;
 pub mod packing;
 
 use serde::{Deserialize, Serialize};
 
 use super::board::Coordinate;
+use crate::rules::TurnEconomy;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Move {
@@ -15,6 +18,27 @@ pub enum Move {
         player: usize,
         positions: [Coordinate; 2],
     },
+    /// Returns a tile from the hand to the bag and draws another, in games with action points
+    Exchange { player: usize, tile: char },
+    /// Returns a poor starting hand to the bag and draws a new one, before the player's first turn
+    Mulligan { player: usize },
+}
+
+impl Move {
+    /// The action points this move spends from the player's turn,
+    /// or `None` if the move can't be made under these rules
+    pub fn action_cost(&self, economy: &TurnEconomy) -> Option<u32> {
+        match (self, economy) {
+            (Move::Mulligan { .. }, _) => Some(0),
+            (Move::Place { .. } | Move::Swap { .. }, TurnEconomy::SingleAction) => Some(1),
+            (Move::Exchange { .. }, TurnEconomy::SingleAction) => None,
+            (Move::Place { .. }, TurnEconomy::ActionPoints { place_cost, .. }) => Some(*place_cost),
+            (Move::Swap { .. }, TurnEconomy::ActionPoints { swap_cost, .. }) => Some(*swap_cost),
+            (Move::Exchange { .. }, TurnEconomy::ActionPoints { exchange_cost, .. }) => {
+                Some(*exchange_cost)
+            }
+        }
+    }
 }
 
 impl PartialEq for Move {
@@ -46,6 +70,19 @@ impl PartialEq for Move {
                     && (l_positions == r_positions
                         || (l_positions[0] == r_positions[1] && l_positions[1] == r_positions[0]))
             }
+            (
+                Self::Exchange {
+                    player: l_player,
+                    tile: l_tile,
+                },
+                Self::Exchange {
+                    player: r_player,
+                    tile: r_tile,
+                },
+            ) => l_player == r_player && l_tile == r_tile,
+            (Self::Mulligan { player: l_player }, Self::Mulligan { player: r_player }) => {
+                l_player == r_player
+            }
             _ => false,
         }
     }
@@ -53,15 +90,19 @@ impl PartialEq for Move {
 
 #[cfg(test)]
 mod tests {
-    use crate::bag::TileBag;
-    use crate::board::{Board, Coordinate, Square, SquareValidity};
+    use crate::bag::{TileBag, BLANK};
+    use crate::board::{Board, Coordinate, Elevation, Square, SquareValidity};
     use crate::error::GamePlayError;
-    use crate::game::Game;
-    use crate::judge::Judge;
-    use crate::player::Player;
+    use crate::game::{now, Adjudication, Game, GameOutcome};
+    use crate::judge::{Judge, Outcome};
+    use crate::player::{Hand, Player};
     use crate::reporting::*;
     use crate::reporting::{BoardChange, BoardChangeAction};
-    use crate::rules::GameRules;
+    use crate::rules::{
+        Appeals, GameRules, HandSwaps, Mulligan, NullMoveRule, OvertimeRule, RepetitionRule,
+        StalemateOutcome, SwapPenalty, Swapping, Tiebreaker, Timing, Truncation, TurnEconomy,
+        WordAging,
+    };
 
     use super::super::bag::tests as TileUtils;
     use super::*;
@@ -353,6 +394,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn play_blanks() {
+        let dict = short_dict().builtin_dictionary;
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut game = Game {
+            board: Board::from_string(
+                "__ __ |0 __ __\n\
+                 __ __ G0 __ __\n\
+                 __ __ __ __ __\n\
+                 __ __ __ __ __\n\
+                 __ __ |1 __ __",
+            ),
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        game.players[0].hand = Hand(vec![BLANK, BLANK]);
+
+        // One blank is played as an I, and the other is left wild
+        for (tile, y) in [('i', 2), (BLANK, 3)] {
+            game.make_move(
+                Move::Place {
+                    player: 0,
+                    tile,
+                    position: Coordinate { x: 2, y },
+                },
+                Some(&dict),
+                Some(&dict),
+                None,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            game.board.to_string(),
+            "__ __ |0 __ __\n\
+             __ __ G0 __ __\n\
+             __ __ i0 __ __\n\
+             __ __ *0 __ __\n\
+             __ __ |1 __ __",
+        );
+        assert!(
+            matches!(
+                game.board.get(Coordinate { x: 2, y: 3 }),
+                Ok(Square::Occupied {
+                    validity: SquareValidity::Valid,
+                    ..
+                })
+            ),
+            "The wild blank should make BIG, read from player 0's side"
+        );
+        assert!(!game.players[0].hand.0.contains(&BLANK));
+
+        // Playing a blank needs a blank in hand
+        assert_eq!(
+            game.make_move(
+                Move::Place {
+                    player: 0,
+                    tile: 'e',
+                    position: Coordinate { x: 1, y: 2 },
+                },
+                None,
+                None,
+                None
+            ),
+            Err(GamePlayError::PlayerDoesNotHaveTile {
+                player: 0,
+                tile: 'e'
+            })
+        );
+    }
+
     #[test]
     fn collect_combanants() {
         let middle = Coordinate { x: 2, y: 2 };
@@ -570,6 +690,124 @@ mod tests {
         )
     }
 
+    #[test]
+    fn aged_words_weaken() {
+        let attack_on_turn = |turn_count| {
+            let b = Board::from_string(
+                "__ S0 X0 |0 __\n\
+                 __ T0 __ __ __\n\
+                 __ R0 __ __ __\n\
+                 __ __ F1 A1 T1\n\
+                 __ __ __ |1 __",
+            );
+            let mut bag = TileUtils::trivial_bag();
+            let players = vec![
+                Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+                Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+            ];
+
+            let mut rules = GameRules::generation(0);
+            rules.word_aging = WordAging::Weaken { grace_turns: 2 };
+
+            let mut game = Game {
+                board: b,
+                bag,
+                players,
+                player_turn_count: vec![0, 0],
+                turn_count,
+                judge: short_dict(),
+                ..Game::new_legacy(1, 1, None, rules)
+            };
+
+            game.make_move(
+                Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x: 1, y: 3 },
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+            game.board.to_string()
+        };
+
+        // FAT is still fresh enough to hold off ARTS
+        assert_eq!(
+            attack_on_turn(2),
+            "__ __ X0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ F1 A1 T1\n\
+             __ __ __ |1 __",
+        );
+        // A turn later it has weakened enough to fall
+        assert_eq!(
+            attack_on_turn(3),
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ A0 __ __ __\n\
+             __ __ __ |1 __",
+        );
+    }
+
+    #[test]
+    fn high_ground_strengthens_words() {
+        let b = Board::from_string(
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ __ F1 A1 T1\n\
+             __ __ __ |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.word_aging = WordAging::Weaken { grace_turns: 2 };
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            turn_count: 3,
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        let mut high_ground = vec![vec![false; 5]; 5];
+        high_ground[3][3] = true;
+        game.board.elevation = Some(Elevation::new(high_ground));
+
+        game.make_move(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 1, y: 3 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // FAT has aged enough to fall to ARTS, but holding the hill keeps it standing
+        assert_eq!(
+            game.board.to_string(),
+            "__ __ X0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ F1 A1 T1\n\
+             __ __ __ |1 __",
+        );
+    }
+
     #[test]
     fn resolve_truncation() {
         let b = Board::from_string(
@@ -632,6 +870,120 @@ mod tests {
         );
     }
 
+    #[test]
+    fn withered_tiles_can_be_reconnected() {
+        let b = Board::from_string(
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ X1 __\n\
+             __ __ B1 X1 __\n\
+             __ __ I1 X1 __\n\
+             __ __ G1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.truncation = Truncation::Withering;
+        rules.battle_delay = 0;
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 1, y: 3 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // The top X tile is cut off, but withers rather than being truncated straight away
+        assert_eq!(
+            game.board.to_string(),
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ X1 __\n\
+             __ A0 __ __ __\n\
+             __ __ I1 X1 __\n\
+             __ __ G1 |1 __",
+        );
+        let withered: Vec<_> = game
+            .recent_changes
+            .iter()
+            .filter_map(|c| match c {
+                Change::Board(BoardChange {
+                    detail,
+                    action: BoardChangeAction::Withered,
+                }) => Some(detail.coordinate),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(withered, vec![Coordinate { x: 3, y: 2 }]);
+
+        // Reconnecting it saves it
+        let mut reconnected = game.clone();
+        reconnected
+            .play_turn(
+                Move::Place {
+                    player: 1,
+                    tile: 'A',
+                    position: Coordinate { x: 3, y: 3 },
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            reconnected.board.to_string(),
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ X1 __\n\
+             __ A0 __ A1 __\n\
+             __ __ I1 X1 __\n\
+             __ __ G1 |1 __",
+        );
+        let withering = reconnected.board.withering.as_ref().unwrap();
+        assert!(!withering.is_withering(Coordinate { x: 3, y: 2 }));
+
+        // Playing elsewhere leaves it to be truncated
+        game.play_turn(
+            Move::Place {
+                player: 1,
+                tile: 'A',
+                position: Coordinate { x: 4, y: 5 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            game.board.to_string(),
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ A0 __ __ __\n\
+             __ __ I1 X1 __\n\
+             __ __ G1 |1 A1",
+        );
+    }
+
     #[test]
     fn resolve_explosion() {
         let b = Board::from_string(
@@ -1179,4 +1531,740 @@ mod tests {
              __ __ __ |1 __",
         )
     }
+
+    #[test]
+    fn draw_by_repetition() {
+        let b = Board::from_string(
+            "__ A0 B0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ A1 B1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.repetition = RepetitionRule::Limit {
+            occurrences: 3,
+            outcome: StalemateOutcome::Draw,
+        };
+        rules.swapping = Swapping::Contiguous(SwapPenalty::Time {
+            swap_threshold: 100,
+            penalties: vec![],
+        });
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+
+        let swaps = [
+            (0, [Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 0 }]),
+            (1, [Coordinate { x: 1, y: 3 }, Coordinate { x: 2, y: 3 }]),
+        ];
+
+        // Every four swaps returns to the starting position
+        for _ in 0..4 {
+            for (player, positions) in swaps {
+                assert_eq!(game.adjudication, None);
+                game.play_turn(Move::Swap { player, positions }, None, None, None)
+                    .unwrap();
+            }
+        }
+
+        assert_eq!(
+            game.adjudication,
+            Some(Adjudication::Repetition { occurrences: 3 })
+        );
+        assert!(game.is_over());
+        assert_eq!(game.winner, None);
+        assert!(game
+            .play_turn(
+                Move::Swap {
+                    player: 0,
+                    positions: swaps[0].1
+                },
+                None,
+                None,
+                None
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn tiebreak_repetition() {
+        let b = Board::from_string(
+            "__ A0 B0 C0 |0\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ A1 B1 |1",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.repetition = RepetitionRule::Limit {
+            occurrences: 3,
+            outcome: StalemateOutcome::Draw,
+        };
+        rules.swapping = Swapping::Contiguous(SwapPenalty::Time {
+            swap_threshold: 100,
+            penalties: vec![],
+        });
+        // Both players hold the same number of tiles, so this falls through to word length
+        rules.tiebreakers = vec![Tiebreaker::TilesRemaining, Tiebreaker::WordLength];
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+
+        let swaps = [
+            (0, [Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 0 }]),
+            (1, [Coordinate { x: 2, y: 3 }, Coordinate { x: 3, y: 3 }]),
+        ];
+
+        for _ in 0..4 {
+            for (player, positions) in swaps {
+                if game.is_over() {
+                    break;
+                }
+                game.play_turn(Move::Swap { player, positions }, None, None, None)
+                    .unwrap();
+            }
+        }
+
+        assert!(game.is_over());
+        assert_eq!(game.adjudication, None);
+        assert_eq!(game.tiebreak_score(Tiebreaker::WordLength, 0), 3);
+        assert_eq!(game.tiebreak_score(Tiebreaker::WordLength, 1), 2);
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome::Won {
+                winner: 0,
+                tiebreaker: Some(Tiebreaker::WordLength)
+            })
+        );
+    }
+
+    #[test]
+    fn null_moves_award_territory() {
+        let b = Board::from_string(
+            "__ A0 B0 C0 |0\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ A1 B1 __ |1",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.swapping = Swapping::Contiguous(SwapPenalty::Time {
+            swap_threshold: 100,
+            penalties: vec![],
+        });
+        rules.repetition = RepetitionRule::None;
+        rules.null_moves = NullMoveRule::Limit {
+            turns: 2,
+            outcome: StalemateOutcome::MostTerritory,
+        };
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+
+        let first = game.play_turn(
+            Move::Swap {
+                player: 0,
+                positions: [Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 0 }],
+            },
+            None,
+            None,
+            None,
+        );
+        assert_eq!(first, Ok(None));
+        assert_eq!(game.no_progress_turns, 1);
+
+        let second = game.play_turn(
+            Move::Swap {
+                player: 1,
+                positions: [Coordinate { x: 1, y: 3 }, Coordinate { x: 2, y: 3 }],
+            },
+            None,
+            None,
+            None,
+        );
+        assert_eq!(second, Ok(Some(0)));
+        assert_eq!(game.adjudication, None);
+    }
+
+    #[test]
+    fn redraw_hands_without_vowels() {
+        let b = Board::from_string(
+            "__ A0 B0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ A1 B1 |1 __",
+        );
+        let mut bag = TileUtils::a_b_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 2, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 2, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.mulligan = Mulligan::NoVowels;
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.players[0].hand = Hand(vec!['A', 'B']);
+        game.players[1].hand = Hand(vec!['B', 'B']);
+        game.start();
+
+        assert_eq!(
+            game.play_turn(Move::Mulligan { player: 0 }, None, None, None),
+            Err(GamePlayError::MulliganUnavailable),
+            "Hands with a vowel are kept"
+        );
+
+        // Hands are redrawn ahead of the player's turn, without taking one up
+        game.play_turn(Move::Mulligan { player: 1 }, None, None, None)
+            .unwrap();
+        assert!(game.players[1].mulliganed);
+        assert_eq!(game.players[1].hand.len(), 2);
+        assert_eq!(game.players[1].drawn_this_turn, 0);
+        assert_eq!(game.next_player, Some(0));
+        assert_eq!(game.turn_count, 0);
+        assert!(game.recent_changes.iter().any(|c| matches!(
+            c,
+            Change::Hand(HandChange { player: 1, removed, .. }) if removed == &vec!['B', 'B']
+        )));
+
+        game.players[1].hand = Hand(vec!['B', 'B']);
+        assert_eq!(
+            game.play_turn(Move::Mulligan { player: 1 }, None, None, None),
+            Err(GamePlayError::MulliganUnavailable),
+            "Hands can only be redrawn once"
+        );
+
+        game.rules.mulligan = Mulligan::None;
+        game.players[0].hand = Hand(vec!['B', 'B']);
+        assert_eq!(
+            game.play_turn(Move::Mulligan { player: 0 }, None, None, None),
+            Err(GamePlayError::MulliganUnavailable),
+            "Only rules with mulligans allow them"
+        );
+    }
+
+    #[test]
+    fn spend_action_points() {
+        let b = Board::from_string(
+            "__ A0 B0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ A1 B1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.swapping = Swapping::Contiguous(SwapPenalty::Time {
+            swap_threshold: 100,
+            penalties: vec![],
+        });
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.players[0].hand = Hand(vec!['A', 'Q']);
+        game.start();
+
+        let exchange = Move::Exchange {
+            player: 0,
+            tile: 'Q',
+        };
+        assert_eq!(
+            game.play_turn(exchange.clone(), None, None, None),
+            Err(GamePlayError::ExchangeUnavailable),
+            "Exchanging needs action points"
+        );
+
+        game.rules.turn_economy = TurnEconomy::action_points();
+        assert_eq!(
+            game.players[0].action_points_remaining(&game.rules.turn_economy),
+            Some(4)
+        );
+
+        // A swap leaves enough for a placement, so the turn carries on
+        game.play_turn(
+            Move::Swap {
+                player: 0,
+                positions: [Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 0 }],
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(game.next_player, Some(0));
+        assert_eq!(
+            game.players[0].action_points_remaining(&game.rules.turn_economy),
+            Some(3)
+        );
+
+        // Exchanging then spends the rest
+        game.play_turn(exchange, None, None, None).unwrap();
+        assert_eq!(game.players[0].hand, Hand(vec!['A', 'A']));
+        assert!(game.bag.remaining_tiles().contains(&'Q'));
+        assert_eq!(game.next_player, Some(1));
+        assert_eq!(game.turn_count, 1);
+        assert_eq!(
+            game.players[0].action_points_remaining(&game.rules.turn_economy),
+            Some(4)
+        );
+
+        game.play_turn(
+            Move::Place {
+                player: 1,
+                tile: 'A',
+                position: Coordinate { x: 0, y: 3 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            game.play_turn(
+                Move::Exchange {
+                    player: 1,
+                    tile: 'A'
+                },
+                None,
+                None,
+                None
+            ),
+            Err(GamePlayError::NotEnoughActionPoints {
+                cost: 3,
+                remaining: 2
+            })
+        );
+        game.play_turn(
+            Move::Place {
+                player: 1,
+                tile: 'A',
+                position: Coordinate { x: 0, y: 2 },
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(game.next_player, Some(0));
+        assert_eq!(game.turn_count, 2);
+    }
+
+    #[test]
+    fn hands_swap_on_schedule() {
+        let b = Board::from_string(
+            "__ A0 B0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ A1 B1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.swapping = Swapping::Contiguous(SwapPenalty::Time {
+            swap_threshold: 100,
+            penalties: vec![],
+        });
+        rules.hand_swaps = HandSwaps::Every { turns: 2 };
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+        game.players[0].hand = Hand(vec!['X', 'Y']);
+        game.players[1].hand = Hand(vec!['Z']);
+        let swap = |player, y| Move::Swap {
+            player,
+            positions: [Coordinate { x: 1, y }, Coordinate { x: 2, y }],
+        };
+
+        assert_eq!(game.turns_until_hand_swap(), Some(2));
+        game.play_turn(swap(0, 0), None, None, None).unwrap();
+        assert_eq!(game.turns_until_hand_swap(), Some(1));
+        assert_eq!(game.players[0].hand, Hand(vec!['X', 'Y']));
+
+        game.play_turn(swap(1, 3), None, None, None).unwrap();
+        assert_eq!(game.turns_until_hand_swap(), Some(2));
+        assert_eq!(game.players[0].hand, Hand(vec!['Z']));
+        assert_eq!(game.players[1].hand, Hand(vec!['X', 'Y']));
+        assert!(game.recent_changes.contains(&Change::Hand(HandChange {
+            player: 0,
+            removed: vec!['X', 'Y'],
+            added: vec!['Z'],
+        })));
+    }
+
+    #[test]
+    fn think_times_are_recorded() {
+        let b = Board::from_string(
+            "__ A0 B0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ A1 B1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.swapping = Swapping::Contiguous(SwapPenalty::Time {
+            swap_threshold: 100,
+            penalties: vec![],
+        });
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+        game.players[0].turn_starts_no_later_than = Some(now() - 30);
+        game.players[0].turn_starts_no_sooner_than = Some(now() - 30);
+
+        game.play_turn(
+            Move::Swap {
+                player: 0,
+                positions: [Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 0 }],
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let [think] = game.think_times.as_slice() else {
+            panic!("Expected one think time, got {:?}", game.think_times);
+        };
+        assert_eq!((think.player, think.turn), (0, 1));
+        // Allow for the clock ticking over while the turn is played
+        assert!((30..=31).contains(&think.secs));
+        assert!(game.recent_changes.contains(&Change::Think(think.clone())));
+    }
+
+    #[test]
+    fn clocks_apply_increments_delays_and_move_limits() {
+        let b = Board::from_string(
+            "__ A0 B0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ A1 B1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.swapping = Swapping::Contiguous(SwapPenalty::Time {
+            swap_threshold: 100,
+            penalties: vec![],
+        });
+        rules.timing = Timing::PerPlayer {
+            time_allowance: 100,
+            overtime_rule: OvertimeRule::Elimination,
+            increment: 5,
+            delay: 10,
+            move_limit: Some(60),
+        };
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+        for player in &mut game.players {
+            player.time_remaining = Some(time::Duration::seconds(100));
+        }
+        game.players[0].turn_starts_no_later_than = Some(now() - 30);
+        game.players[0].turn_starts_no_sooner_than = Some(now() - 30);
+
+        game.play_turn(
+            Move::Swap {
+                player: 0,
+                positions: [Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 0 }],
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // 30 seconds were spent, 10 of them handed back by the delay, then 5 added on
+        let remaining = game.players[0].time_remaining.unwrap().whole_seconds();
+        assert!((84..=85).contains(&remaining));
+        let reasons: Vec<_> = game
+            .recent_changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Time(TimeChange {
+                    time_change,
+                    reason,
+                    ..
+                }) => Some((*time_change, reason.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(reasons, vec![(10, "Delay"), (5, "Increment")]);
+
+        // Plenty of time is left on the clock, but the move has gone on too long
+        assert_eq!(game.player_over_move_limit(), None);
+        game.players[1].turn_starts_no_later_than = Some(now() - 61);
+        game.calculate_game_over(None);
+        assert_eq!(game.winner, Some(0));
+    }
+
+    #[test]
+    fn appeal_overturns_battle() {
+        let b = Board::from_string(
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ __ A1 __ __\n\
+             __ __ R1 __ __\n\
+             __ __ T1 __ __\n\
+             __ __ S1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut rules = GameRules::generation(0);
+        rules.appeals = Appeals::Allowed { per_player: 1 };
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, rules)
+        };
+        game.start();
+
+        let dict = b_dict().builtin_dictionary;
+        let extended = short_dict().builtin_dictionary;
+
+        assert_eq!(
+            game.appeal(0, Some(&dict), &extended),
+            Err(GamePlayError::NothingToAppeal)
+        );
+
+        game.play_turn(
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 1, y: 3 },
+            },
+            Some(&dict),
+            Some(&dict),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            game.board.to_string(),
+            "__ __ X0 |0 __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ A1 __ __\n\
+             __ __ R1 __ __\n\
+             __ __ T1 __ __\n\
+             __ __ S1 |1 __",
+        );
+
+        // Player 1's words were never judged, so they have nothing to appeal
+        assert_eq!(
+            game.appeal(1, Some(&dict), &extended),
+            Err(GamePlayError::NoInvalidWords)
+        );
+
+        assert_eq!(game.appeal(0, Some(&dict), &extended), Ok(None));
+        assert_eq!(
+            game.board.to_string(),
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ A0 __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ __ __\n\
+             __ __ __ |1 __",
+        );
+        let amendment = game.recent_changes.iter().find_map(|c| match c {
+            Change::Battle(battle) => battle.amendment.clone(),
+            _ => None,
+        });
+        assert_eq!(
+            amendment,
+            Some(BattleAmendment {
+                appellant: 0,
+                overturned: true
+            })
+        );
+        assert_eq!(game.next_player, Some(1));
+
+        assert_eq!(
+            game.appeal(0, Some(&dict), &extended),
+            Err(GamePlayError::NoAppealsLeft)
+        );
+    }
+
+    #[test]
+    fn preview_move_leaves_game_untouched() {
+        let b = Board::from_string(
+            "__ S0 X0 |0 __\n\
+             __ T0 __ __ __\n\
+             __ R0 __ __ __\n\
+             __ __ A1 __ __\n\
+             __ __ R1 __ __\n\
+             __ __ T1 __ __\n\
+             __ __ S1 |1 __",
+        );
+        let mut bag = TileUtils::trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        let mut game = Game {
+            board: b,
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            judge: short_dict(),
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        game.start();
+        let board_before = game.board.to_string();
+
+        let words = |words: &Vec<BattleWord>| {
+            words
+                .iter()
+                .map(|w| (w.original_word.clone(), w.valid))
+                .collect::<Vec<_>>()
+        };
+
+        let swap = game
+            .preview_move(
+                Move::Swap {
+                    player: 0,
+                    positions: [Coordinate { x: 1, y: 0 }, Coordinate { x: 1, y: 2 }],
+                },
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            words(&swap.before),
+            vec![("RTS".into(), Some(false)), ("XS".into(), Some(false))]
+        );
+        assert_eq!(
+            words(&swap.after),
+            vec![("STR".into(), Some(false)), ("XR".into(), Some(false))]
+        );
+        assert!(swap.battles.is_empty());
+
+        let place = game
+            .preview_move(
+                Move::Place {
+                    player: 0,
+                    tile: 'A',
+                    position: Coordinate { x: 1, y: 3 },
+                },
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(place.before.is_empty());
+        assert_eq!(place.battles.len(), 1);
+        assert_eq!(place.battles[0].outcome, Outcome::DefenderWins);
+
+        assert_eq!(
+            game.preview_move(
+                Move::Swap {
+                    player: 0,
+                    positions: [Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 3 }],
+                },
+                None,
+                None,
+            ),
+            Err(GamePlayError::UnownedSwap)
+        );
+
+        assert_eq!(game.board.to_string(), board_before);
+        assert_eq!(game.battle_count, 0);
+        assert_eq!(game.next_player, Some(0));
+    }
 }
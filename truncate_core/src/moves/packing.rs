@@ -1,4 +1,7 @@
-use crate::board::Coordinate;
+use crate::{
+    bag::{digraph_letters, BLANK},
+    board::Coordinate,
+};
 
 use super::Move;
 
@@ -19,63 +22,59 @@ fn unpack_coord(packed_coord: &String) -> Result<Coordinate, ()> {
     })
 }
 
-pub fn pack_moves(moves: &Vec<Move>, player_count: usize) -> String {
-    let mut packed = String::with_capacity(moves.len() * 3);
-
-    let mut next_player: usize = 0;
-
-    let incr_player = |p: &mut usize| {
-        let r = *p;
-        *p = (*p + 1) % player_count;
-        r
-    };
-
-    if let Some(first_move) = moves.first() {
-        next_player = match first_move {
-            Move::Place { player, .. } => *player,
-            Move::Swap { player, .. } => *player,
-        };
-        packed.push_str(&format!("[{next_player}]"));
-    };
-
-    for m in moves {
-        match m {
-            Move::Place {
-                player,
-                tile,
-                position,
-            } => {
-                if *player != next_player {
-                    next_player = *player;
-                    packed.push_str(&format!("[{player}]"));
-                }
-
-                packed.push_str(&pack_coord(*position));
-                packed.push(*tile);
-
-                incr_player(&mut next_player);
-            }
-            Move::Swap {
-                player,
-                positions: [from, to],
-            } => {
-                if *player != next_player {
-                    next_player = *player;
-                    packed.push_str(&format!("[{player}]"));
-                }
+/// Packs a single move without any record of the player who made it
+pub fn pack_move(m: &Move) -> String {
+    match m {
+        Move::Place { tile, position, .. } => format!("{}{tile}", pack_coord(*position)),
+        Move::Swap {
+            positions: [from, to],
+            ..
+        } => format!("<{}/{}>", pack_coord(*from), pack_coord(*to)),
+        // Braces would be read as an annotation in archives, so exchanges are marked with a caret
+        Move::Exchange { tile, .. } => format!("^{tile}"),
+        Move::Mulligan { .. } => "~".to_string(),
+    }
+}
 
-                packed.push('<');
-                packed.push_str(&pack_coord(*from));
-                packed.push('/');
-                packed.push_str(&pack_coord(*to));
-                packed.push('>');
+pub fn pack_moves(moves: &[Move], player_count: usize) -> String {
+    pack_moves_separately(moves, player_count).concat()
+}
 
-                incr_player(&mut next_player);
+/// Packs each move on its own, prefixing the player wherever they
+/// can't be inferred from the turn order. Concatenating the output
+/// gives the same result as `pack_moves`.
+pub fn pack_moves_separately(moves: &[Move], player_count: usize) -> Vec<String> {
+    let mut next_player: Option<usize> = None;
+
+    moves
+        .iter()
+        .map(|m| {
+            let player = match m {
+                Move::Place { player, .. } => *player,
+                Move::Swap { player, .. } => *player,
+                Move::Exchange { player, .. } => *player,
+                Move::Mulligan { player } => *player,
+            };
+
+            let mut packed = String::new();
+            if next_player != Some(player) {
+                packed.push_str(&format!("[{player}]"));
             }
-        }
-    }
+            packed.push_str(&pack_move(m));
+
+            // Redrawing a hand doesn't end the turn
+            next_player = match m {
+                Move::Mulligan { .. } => Some(player),
+                _ => Some((player + 1) % player_count),
+            };
+            packed
+        })
+        .collect()
+}
 
-    packed
+/// Tiles are packed as themselves, being letters, blanks, or digraphs
+fn is_packed_tile(c: char) -> bool {
+    c.is_alphabetic() || c == BLANK || digraph_letters(c).is_some()
 }
 
 pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Move>, ()> {
@@ -87,6 +86,10 @@ pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Mo
         Place(String),
         SwapFrom(String),
         SwapTo(Coordinate, String),
+        Exchange,
+        /// Exchanges packed before the caret were wrapped in braces
+        BracedExchange,
+        ExchangeEnd,
     }
 
     let mut i = packed_moves.chars();
@@ -106,8 +109,15 @@ pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Mo
                     state = State::Place(c.to_string());
                 } else if c == '<' {
                     state = State::SwapFrom(String::new());
+                } else if c == '^' {
+                    state = State::Exchange;
+                } else if c == '{' {
+                    state = State::BracedExchange;
                 } else if c == '[' {
                     state = State::SetPlayer(String::new());
+                } else if c == '~' {
+                    // ~ redraws the player's starting hand, without ending their turn
+                    moves.push(Move::Mulligan { player });
                 } else {
                     return Err(());
                 }
@@ -127,7 +137,7 @@ pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Mo
             State::Place(s) => {
                 if c.is_numeric() {
                     s.push(c);
-                } else if c.is_alphabetic() {
+                } else if is_packed_tile(c) {
                     let position = unpack_coord(s)?;
                     moves.push(Move::Place {
                         player: incr_player(&mut player),
@@ -165,6 +175,36 @@ pub fn unpack_moves(packed_moves: &String, player_count: usize) -> Result<Vec<Mo
                     return Err(());
                 }
             }
+            // ^A exchanges tile 'A' for one from the bag
+            State::Exchange => {
+                if is_packed_tile(c) {
+                    moves.push(Move::Exchange {
+                        player: incr_player(&mut player),
+                        tile: c,
+                    });
+                    state = State::None;
+                } else {
+                    return Err(());
+                }
+            }
+            State::BracedExchange => {
+                if is_packed_tile(c) {
+                    moves.push(Move::Exchange {
+                        player: incr_player(&mut player),
+                        tile: c,
+                    });
+                    state = State::ExchangeEnd;
+                } else {
+                    return Err(());
+                }
+            }
+            State::ExchangeEnd => {
+                if c == '}' {
+                    state = State::None;
+                } else {
+                    return Err(());
+                }
+            }
         }
     }
 
@@ -213,6 +253,31 @@ mod tests {
         assert_eq!(unpacked, Ok(moves));
     }
 
+    #[test]
+    fn test_packing_blanks() {
+        let moves = vec![
+            Move::Place {
+                player: 0,
+                tile: BLANK,
+                position: Coordinate { x: 1, y: 2 },
+            },
+            Move::Place {
+                player: 1,
+                tile: 'e',
+                position: Coordinate { x: 3, y: 4 },
+            },
+            Move::Place {
+                player: 0,
+                tile: '\u{E000}',
+                position: Coordinate { x: 5, y: 6 },
+            },
+        ];
+
+        let packed = pack_moves(&moves, 2);
+        assert_eq!(packed, "[0]12*34e56\u{E000}".to_string());
+        assert_eq!(unpack_moves(&packed, 2), Ok(moves));
+    }
+
     #[test]
     fn test_packing_three_players() {
         let moves = vec![
@@ -301,4 +366,58 @@ mod tests {
 
         assert_eq!(unpacked, Ok(moves));
     }
+
+    #[test]
+    fn test_packing_exchanges() {
+        let moves = vec![
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 12, y: 3 },
+            },
+            Move::Exchange {
+                player: 0,
+                tile: 'Q',
+            },
+            Move::Place {
+                player: 1,
+                tile: 'B',
+                position: Coordinate { x: 1, y: 1 },
+            },
+        ];
+
+        let packed = pack_moves(&moves, 2);
+
+        assert_eq!(packed, "[0]1203A[0]^Q11B".to_string());
+
+        let unpacked = unpack_moves(&packed, 2);
+
+        assert_eq!(unpacked, Ok(moves.clone()));
+        assert_eq!(unpack_moves(&"[0]1203A[0]{Q}11B".to_string(), 2), Ok(moves));
+    }
+
+    #[test]
+    fn test_packing_mulligans() {
+        let moves = vec![
+            Move::Mulligan { player: 1 },
+            Move::Place {
+                player: 0,
+                tile: 'A',
+                position: Coordinate { x: 12, y: 3 },
+            },
+            Move::Place {
+                player: 1,
+                tile: 'B',
+                position: Coordinate { x: 1, y: 1 },
+            },
+        ];
+
+        let packed = pack_moves(&moves, 2);
+
+        assert_eq!(packed, "[1]~[0]1203A11B".to_string());
+
+        let unpacked = unpack_moves(&packed, 2);
+
+        assert_eq!(unpacked, Ok(moves));
+    }
 }
@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    board::{Coordinate, Square},
+    game::Game,
+    judge::{Outcome, WordDict},
+    moves::Move,
+    player::Hand,
+    reporting::Change,
+};
+
+use super::bitboard::{BitBoard, Referee};
+
+/// The tiles a player can't see, being those left in the bag and those in their opponents' hands
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnseenTiles {
+    counts: BTreeMap<char, usize>,
+    total: usize,
+    /// How many of the unseen tiles the opponent is holding
+    hand_size: usize,
+}
+
+impl UnseenTiles {
+    pub fn new(tiles: impl IntoIterator<Item = char>, hand_size: usize) -> Self {
+        let mut counts = BTreeMap::new();
+        let mut total = 0;
+        for tile in tiles {
+            *counts.entry(tile).or_default() += 1;
+            total += 1;
+        }
+
+        Self {
+            counts,
+            total,
+            hand_size: hand_size.min(total),
+        }
+    }
+
+    /// Everything `player` can't see, with `opponent` holding some of it
+    pub fn for_player(game: &Game, player: usize, opponent: usize) -> Self {
+        let hands = game
+            .players
+            .iter()
+            .filter(|p| p.index != player)
+            .flat_map(|p| p.hand.iter().cloned());
+
+        Self::new(
+            game.bag.remaining_tiles().iter().cloned().chain(hands),
+            game.players[opponent].hand.len(),
+        )
+    }
+
+    /// Each letter that could be in the opponent's hand
+    pub fn letters(&self) -> impl Iterator<Item = char> + '_ {
+        self.counts.keys().cloned()
+    }
+
+    /// The chance that the opponent holds at least one of `letters`,
+    /// treating their hand as drawn at random from the unseen tiles
+    pub fn chance_of_any(&self, letters: &BTreeSet<char>) -> f32 {
+        let matching: usize = letters
+            .iter()
+            .map(|l| self.counts.get(l).copied().unwrap_or_default())
+            .sum();
+        let missing = self.total - matching;
+
+        // Draw the hand one tile at a time, missing every matching letter on each draw
+        let chance_of_none = (0..self.hand_size).fold(1.0, |chance, drawn| {
+            if drawn >= missing {
+                0.0
+            } else {
+                chance * (missing - drawn) as f32 / (self.total - drawn) as f32
+            }
+        });
+
+        1.0 - chance_of_none
+    }
+}
+
+/// How likely an attack is to still be standing once the defender has had their turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttackOdds {
+    pub position: Coordinate,
+    pub tile: char,
+    pub defender: usize,
+    /// Letters the defender could play next turn to take the square back
+    pub counters: BTreeSet<char>,
+    pub success: f32,
+}
+
+impl Game {
+    /// Estimates whether placing `tile` at `position` wins its battle and survives the defender's reply,
+    /// for ranking hints and deciding how boldly to attack.
+    /// Returns `None` if the placement isn't playable or doesn't start a battle.
+    pub fn attack_odds(
+        &self,
+        position: Coordinate,
+        tile: char,
+        attacker_dictionary: Option<&WordDict>,
+        defender_dictionary: Option<&WordDict>,
+    ) -> Option<AttackOdds> {
+        let attacker = self.next_player?;
+        let defender = self
+            .board
+            .neighbouring_squares(position)
+            .into_iter()
+            .find_map(|(_, square)| match square {
+                Square::Occupied { player, .. } | Square::Town { player, .. }
+                    if player != attacker =>
+                {
+                    Some(player)
+                }
+                _ => None,
+            })?;
+
+        let mut attacked = self.clone();
+        // Remove timing concerns and tile draws from the simulated turns
+        attacked.rules.battle_delay = 0;
+        for player in attacked.players.iter_mut() {
+            player.hand_capacity = 0;
+        }
+
+        attacked
+            .play_turn(
+                Move::Place {
+                    player: attacker,
+                    tile,
+                    position,
+                },
+                attacker_dictionary,
+                defender_dictionary,
+                None,
+            )
+            .ok()?;
+
+        let won_battle = attacked
+            .recent_changes
+            .iter()
+            .find_map(|change| match change {
+                Change::Battle(battle) => Some(matches!(battle.outcome, Outcome::AttackerWins(_))),
+                _ => None,
+            })?;
+
+        let mut odds = AttackOdds {
+            position,
+            tile,
+            defender,
+            counters: BTreeSet::new(),
+            success: 0.0,
+        };
+        if !won_battle {
+            return Some(odds);
+        }
+        if attacked.winner == Some(attacker) {
+            odds.success = 1.0;
+            return Some(odds);
+        }
+
+        let unseen = UnseenTiles::for_player(self, attacker, defender);
+        let replies = attacked
+            .board
+            .playable_positions(defender, &attacked.rules.truncation);
+        // Replies are cheaper to simulate on a mirror of the board than on a copy of the whole game
+        let mirror = BitBoard::supports(&attacked.rules)
+            .then(|| BitBoard::from_board(&attacked.board))
+            .flatten();
+        // The defender's reply makes them the attacker
+        let referee = Referee {
+            judge: &attacked.judge,
+            rules: &attacked.rules,
+            attacker_dictionary: defender_dictionary,
+            defender_dictionary: attacker_dictionary,
+        };
+
+        odds.counters = unseen
+            .letters()
+            .filter(|letter| {
+                replies.iter().any(|reply| {
+                    if let Some(mirror) = &mirror {
+                        let mut countered = mirror.clone();
+                        return countered
+                            .play(defender, *reply, *letter, &referee, None)
+                            .is_ok()
+                            && (countered.winner() == Some(defender)
+                                || countered.tile(position).map(|(player, _)| player)
+                                    != Some(attacker));
+                    }
+
+                    let mut countered = attacked.clone();
+                    countered.players[defender].hand = Hand(vec![*letter]);
+
+                    countered
+                        .play_turn(
+                            Move::Place {
+                                player: defender,
+                                tile: *letter,
+                                position: *reply,
+                            },
+                            defender_dictionary,
+                            attacker_dictionary,
+                            None,
+                        )
+                        .is_ok()
+                        && (countered.winner == Some(defender)
+                            || !matches!(
+                                countered.board.get(position),
+                                Ok(Square::Occupied { player, .. }) if player == attacker
+                            ))
+                })
+            })
+            .collect();
+        odds.success = 1.0 - unseen.chance_of_any(&odds.counters);
+
+        Some(odds)
+    }
+
+    /// Every attack the next player could make, most likely to hold first
+    pub fn ranked_attacks(
+        &self,
+        attacker_dictionary: Option<&WordDict>,
+        defender_dictionary: Option<&WordDict>,
+    ) -> Vec<AttackOdds> {
+        let mut attacks: Vec<_> = self
+            .possible_moves()
+            .into_iter()
+            .filter_map(|(position, tile)| {
+                self.attack_odds(position, tile, attacker_dictionary, defender_dictionary)
+            })
+            .collect();
+
+        attacks.sort_by(|a, b| b.success.total_cmp(&a.success));
+        attacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{bag::TileBag, board::Board, judge::Judge, player::Player, rules::GameRules};
+
+    fn dict() -> WordDict {
+        Judge::new(vec!["BIG".into(), "O".into()]).builtin_dictionary
+    }
+
+    /// Player 1 is about to attack the lone Z beside player 0's dock, holding `hand`.
+    /// Player 0 holds a Z, and anything they might draw comes from `bag`.
+    fn attack_game(hand: &str, bag: &str) -> Game {
+        let mut dealer = TileBag::latest(None).1;
+        let players = vec![
+            Player::new("A".into(), 0, 1, &mut dealer, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 1, &mut dealer, None, (0, 0, 0)),
+        ];
+
+        let mut game = Game {
+            board: Board::from_string(
+                r###"
+                ~~ ~~ |0 ~~ ~~
+                __ __ Z0 __ X1
+                __ __ __ I1 X1
+                __ __ __ G1 X1
+                __ __ __ __ X1
+                ~~ ~~ ~~ ~~ |1
+                "###,
+            ),
+            bag: TileBag::explicit(bag.chars().collect(), Some(1)),
+            players,
+            player_turn_count: vec![0, 0],
+            next_player: Some(1),
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        game.players[0].hand = Hand(vec!['Z']);
+        game.players[1].hand = Hand(hand.chars().collect());
+        game.start();
+
+        game
+    }
+
+    #[test]
+    fn chance_of_holding_letters() {
+        let counters = BTreeSet::from(['O']);
+
+        let unseen = UnseenTiles::new("OOXX".chars(), 1);
+        assert_eq!(unseen.chance_of_any(&counters), 0.5);
+        assert_eq!(unseen.chance_of_any(&BTreeSet::new()), 0.0);
+
+        let unseen = UnseenTiles::new("OOXX".chars(), 2);
+        assert!((unseen.chance_of_any(&counters) - 5.0 / 6.0).abs() < 0.0001);
+
+        // A hand bigger than the unseen tiles holds all of them
+        let unseen = UnseenTiles::new("OX".chars(), 7);
+        assert_eq!(unseen.chance_of_any(&counters), 1.0);
+        assert_eq!(
+            unseen.chance_of_any(&BTreeSet::from(['Q'])),
+            0.0,
+            "Letters that aren't unseen can't be held"
+        );
+    }
+
+    #[test]
+    fn counts_unseen_tiles_from_the_attackers_side() {
+        let game = attack_game("B", "OXX");
+        let unseen = UnseenTiles::for_player(&game, 1, 0);
+
+        assert_eq!(unseen, UnseenTiles::new("OXXZ".chars(), 1));
+        assert_eq!(unseen.letters().collect::<String>(), "OXZ");
+    }
+
+    #[test]
+    fn attacks_hold_unless_countered() {
+        let dict = dict();
+        let attack = Coordinate { x: 3, y: 1 };
+
+        // Beating the Z leaves a B in reach of player 0's dock,
+        // where a valid O next to the invalid BX would take it back
+        let game = attack_game("B", "OXX");
+        let odds = game
+            .attack_odds(attack, 'B', Some(&dict), Some(&dict))
+            .unwrap();
+        assert_eq!(odds.defender, 0);
+        assert_eq!(odds.counters, BTreeSet::from(['O']));
+        assert_eq!(odds.success, 0.75);
+
+        // Without any Os left to draw, the attack is safe
+        let game = attack_game("B", "XXX");
+        let odds = game
+            .attack_odds(attack, 'B', Some(&dict), Some(&dict))
+            .unwrap();
+        assert!(odds.counters.is_empty());
+        assert_eq!(odds.success, 1.0);
+
+        // A losing attack never holds
+        let game = attack_game("Q", "OXX");
+        let odds = game
+            .attack_odds(attack, 'Q', Some(&dict), Some(&dict))
+            .unwrap();
+        assert_eq!(odds.success, 0.0);
+
+        // Placements that don't touch the opponent aren't attacks
+        assert_eq!(
+            game.attack_odds(Coordinate { x: 3, y: 4 }, 'Q', Some(&dict), Some(&dict)),
+            None
+        );
+    }
+
+    #[test]
+    fn ranks_safer_attacks_first() {
+        let dict = dict();
+        let game = attack_game("BQ", "OXX");
+
+        let ranked = game.ranked_attacks(Some(&dict), Some(&dict));
+        assert_eq!(
+            (ranked[0].position, ranked[0].tile, ranked[0].success),
+            (Coordinate { x: 3, y: 1 }, 'B', 0.75)
+        );
+        // Every other attack makes an invalid word, and so loses its battle
+        assert!(ranked.len() > 1);
+        assert!(ranked[1..].iter().all(|odds| odds.success == 0.0));
+    }
+}
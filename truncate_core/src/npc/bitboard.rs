@@ -0,0 +1,734 @@
+//! A compact mirror of a `Board` for the NPC to clone and play tiles onto while it searches,
+//! holding each player's tiles as a bitset rather than a grid of squares.
+//!
+//! Only the standard rules are mirrored, see `BitBoard::supports`.
+
+use std::collections::HashMap;
+
+use xxhash_rust::xxh3;
+
+use crate::{
+    board::{Board, Coordinate, Direction, Square, SquareValidity},
+    error::GamePlayError,
+    judge::{Judge, Outcome, WordDict},
+    reporting::BattleReport,
+    rules::{self, ArtifactDefense, GameRules, TownDefense, WinCondition},
+};
+
+/// What a `BitBoard` needs to judge the battles that follow a tile being played
+pub struct Referee<'a> {
+    pub judge: &'a Judge,
+    pub rules: &'a GameRules,
+    pub attacker_dictionary: Option<&'a WordDict>,
+    pub defender_dictionary: Option<&'a WordDict>,
+}
+
+/// One bit for each square of a board, in the order of `Coordinate::to_1d`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Bits(Vec<u64>);
+
+impl Bits {
+    fn new(squares: usize) -> Self {
+        Self(vec![0; squares.div_ceil(64)])
+    }
+
+    fn get(&self, index: usize) -> bool {
+        self.0[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    fn set(&mut self, index: usize, on: bool) {
+        if on {
+            self.0[index / 64] |= 1 << (index % 64);
+        } else {
+            self.0[index / 64] &= !(1 << (index % 64));
+        }
+    }
+
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    fn and(mut self, other: &Bits) -> Self {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= b);
+        self
+    }
+
+    fn and_not(mut self, other: &Bits) -> Self {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= !b);
+        self
+    }
+
+    fn or(mut self, other: &Bits) -> Self {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a |= b);
+        self
+    }
+
+    /// Moves every bit `by` places towards the end
+    fn shifted_up(&self, by: usize) -> Self {
+        let (words, bits) = (by / 64, by % 64);
+        let shifted = (0..self.0.len())
+            .map(|i| {
+                let word = i.checked_sub(words).map_or(0, |j| self.0[j] << bits);
+                match i.checked_sub(words + 1) {
+                    Some(j) if bits > 0 => word | self.0[j] >> (64 - bits),
+                    _ => word,
+                }
+            })
+            .collect();
+        Self(shifted)
+    }
+
+    /// Moves every bit `by` places towards the start
+    fn shifted_down(&self, by: usize) -> Self {
+        let (words, bits) = (by / 64, by % 64);
+        let shifted = (0..self.0.len())
+            .map(|i| {
+                let word = self.0.get(i + words).map_or(0, |w| w >> bits);
+                match self.0.get(i + words + 1) {
+                    Some(next) if bits > 0 => word | next << (64 - bits),
+                    _ => word,
+                }
+            })
+            .collect();
+        Self(shifted)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitBoard {
+    width: usize,
+    height: usize,
+    /// Squares that tiles can be placed on, whether or not one has been
+    land: Bits,
+    /// Each player's tiles
+    tiles: Vec<Bits>,
+    /// The letter of the tile on each square, only meaningful where there is a tile
+    letters: Vec<char>,
+    towns: Vec<Bits>,
+    artifacts: Vec<Bits>,
+    obelisks: Bits,
+    /// Towns and artifacts that have been defeated
+    defeated: Bits,
+    orientations: Vec<Direction>,
+    /// Squares in the leftmost and rightmost columns, which tiles can't spread across
+    left_edge: Bits,
+    right_edge: Bits,
+}
+
+impl BitBoard {
+    /// Mirrors a board, or returns `None` for boards holding anything the mirror doesn't,
+    /// such as fog or the state kept by word aging and elevation.
+    /// The validity of each tile isn't mirrored.
+    pub fn from_board(board: &Board) -> Option<Self> {
+        if board.aging.is_some() || board.elevation.is_some() || board.withering.is_some() {
+            return None;
+        }
+
+        let width = board.width();
+        let height = board.height();
+        let squares = width * height;
+        let players = board
+            .squares
+            .iter()
+            .flatten()
+            .filter_map(|square| match square {
+                Square::Town { player, .. }
+                | Square::Artifact { player, .. }
+                | Square::Occupied { player, .. } => Some(player + 1),
+                _ => None,
+            })
+            .chain([board.orientations.len()])
+            .max()
+            .unwrap_or_default();
+
+        let mut mirror = Self {
+            width,
+            height,
+            land: Bits::new(squares),
+            tiles: vec![Bits::new(squares); players],
+            letters: vec![' '; squares],
+            towns: vec![Bits::new(squares); players],
+            artifacts: vec![Bits::new(squares); players],
+            obelisks: Bits::new(squares),
+            defeated: Bits::new(squares),
+            orientations: board.orientations.clone(),
+            left_edge: Bits::new(squares),
+            right_edge: Bits::new(squares),
+        };
+
+        for (y, row) in board.squares.iter().enumerate() {
+            for (x, square) in row.iter().enumerate() {
+                let i = Coordinate { x, y }.to_1d(width);
+                mirror.left_edge.set(i, x == 0);
+                mirror.right_edge.set(i, x + 1 == width);
+
+                if square.is_foggy() {
+                    return None;
+                }
+                match *square {
+                    Square::Water { .. } | Square::Fog {} => {}
+                    Square::Land { .. } => mirror.land.set(i, true),
+                    Square::Occupied { player, tile, .. } => {
+                        mirror.land.set(i, true);
+                        mirror.tiles[player].set(i, true);
+                        mirror.letters[i] = tile;
+                    }
+                    Square::Town {
+                        player, defeated, ..
+                    } => {
+                        mirror.towns[player].set(i, true);
+                        mirror.defeated.set(i, defeated);
+                    }
+                    Square::Artifact {
+                        player, defeated, ..
+                    } => {
+                        mirror.artifacts[player].set(i, true);
+                        mirror.defeated.set(i, defeated);
+                    }
+                    Square::Obelisk { .. } => mirror.obelisks.set(i, true),
+                }
+            }
+        }
+
+        Some(mirror)
+    }
+
+    /// Builds the board this mirrors, with the validity of every tile unknown
+    pub fn to_board(&self) -> Board {
+        let squares = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| self.square(Coordinate { x, y }))
+                    .collect()
+            })
+            .collect();
+
+        let mut board = Board::from_squares(squares);
+        board.orientations = self.orientations.clone();
+        board
+    }
+
+    /// Whether moves played under these rules resolve the same on the mirror as on a `Board`
+    pub fn supports(rules: &GameRules) -> bool {
+        matches!(
+            rules.truncation,
+            rules::Truncation::Root | rules::Truncation::None
+        ) && matches!(rules.gravity, rules::Gravity::None)
+            && matches!(rules.word_aging, rules::WordAging::None)
+            && matches!(rules.win_condition, WinCondition::Destination { .. })
+    }
+
+    fn index(&self, position: Coordinate) -> Option<usize> {
+        (position.x < self.width && position.y < self.height).then(|| position.to_1d(self.width))
+    }
+
+    fn owner(&self, index: usize) -> Option<usize> {
+        self.tiles.iter().position(|tiles| tiles.get(index))
+    }
+
+    fn square(&self, position: Coordinate) -> Square {
+        let Some(i) = self.index(position) else {
+            return Square::water();
+        };
+        let defeated = self.defeated.get(i);
+
+        if let Some(player) = self.owner(i) {
+            Square::Occupied {
+                player,
+                tile: self.letters[i],
+                validity: SquareValidity::Unknown,
+                foggy: false,
+            }
+        } else if let Some(player) = self.towns.iter().position(|towns| towns.get(i)) {
+            Square::Town {
+                player,
+                defeated,
+                foggy: false,
+            }
+        } else if let Some(player) = self.artifacts.iter().position(|a| a.get(i)) {
+            Square::Artifact {
+                player,
+                defeated,
+                foggy: false,
+            }
+        } else if self.obelisks.get(i) {
+            Square::obelisk()
+        } else if self.land.get(i) {
+            Square::land()
+        } else {
+            Square::water()
+        }
+    }
+
+    /// The player and letter of the tile at a position, if there is one
+    pub fn tile(&self, position: Coordinate) -> Option<(usize, char)> {
+        let i = self.index(position)?;
+        self.owner(i).map(|player| (player, self.letters[i]))
+    }
+
+    /// Whoever has defeated an opponent's town or artifact, as `Judge::winner` finds on a `Board`
+    pub fn winner(&self) -> Option<usize> {
+        let defeated_owner = |places: &[Bits]| {
+            self.defeated
+                .ones()
+                .filter_map(|i| places.iter().position(|p| p.get(i)).map(|p| (i, p)))
+                .min()
+                .map(|(_, player)| (player + 1) % 2)
+        };
+        defeated_owner(&self.towns).or_else(|| defeated_owner(&self.artifacts))
+    }
+
+    /// Grows a set of squares by one square in each direction, without wrapping between rows
+    fn spread(&self, bits: &Bits) -> Bits {
+        let east = bits.shifted_up(1).and_not(&self.left_edge);
+        let west = bits.shifted_down(1).and_not(&self.right_edge);
+        let south = bits.shifted_up(self.width);
+        let north = bits.shifted_down(self.width);
+        bits.clone().or(&east).or(&west).or(&south).or(&north)
+    }
+
+    /// The player's tiles still connected to one of their artifacts, along with the artifacts
+    fn rooted(&self, player: usize) -> Bits {
+        let reachable = self.tiles[player].clone().or(&self.artifacts[player]);
+        let mut rooted = self.artifacts[player].clone();
+        loop {
+            let grown = self.spread(&rooted).and(&reachable);
+            if grown == rooted {
+                return rooted;
+            }
+            rooted = grown;
+        }
+    }
+
+    /// Every empty square the player could place a tile on, sorted
+    pub fn playable_positions(
+        &self,
+        player: usize,
+        truncation: &rules::Truncation,
+    ) -> Vec<Coordinate> {
+        let Some(tiles) = self.tiles.get(player) else {
+            return vec![];
+        };
+        let connected = match truncation {
+            rules::Truncation::Root | rules::Truncation::Withering => self.rooted(player),
+            rules::Truncation::None => tiles.clone().or(&self.artifacts[player]),
+            rules::Truncation::Larger => unimplemented!(),
+        };
+        let occupied = self
+            .tiles
+            .iter()
+            .fold(Bits::new(self.land.0.len() * 64), |all, tiles| {
+                all.or(tiles)
+            });
+
+        let mut playable: Vec<_> = self
+            .spread(&connected)
+            .and(&self.land)
+            .and_not(&occupied)
+            .ones()
+            .map(|i| Coordinate::from_1d(i, self.width))
+            .collect();
+        playable.sort();
+        playable
+    }
+
+    /// The words running through a square, as `Board::get_words` reads them
+    fn get_words(&self, position: Coordinate) -> Vec<Vec<Coordinate>> {
+        let Some(i) = self.index(position) else {
+            return vec![];
+        };
+        let Some(owner) = self.owner(i) else {
+            let combatant = self.towns.iter().chain(&self.artifacts).any(|p| p.get(i));
+            return if combatant {
+                vec![vec![position]]
+            } else {
+                vec![]
+            };
+        };
+
+        let tiles = &self.tiles[owner];
+        let run = |step: fn(Coordinate) -> Option<Coordinate>| {
+            let mut run = vec![];
+            let mut next = step(position);
+            while let Some(c) = next.filter(|c| self.index(*c).is_some_and(|i| tiles.get(i))) {
+                run.push(c);
+                next = step(c);
+            }
+            run
+        };
+        let read = |backwards: Vec<Coordinate>, forwards: Vec<Coordinate>, reversed: bool| {
+            let mut word: Vec<_> = backwards
+                .into_iter()
+                .rev()
+                .chain([position])
+                .chain(forwards)
+                .collect();
+            if reversed {
+                word.reverse();
+            }
+            word
+        };
+
+        let orientation = self.orientations[owner];
+        let words = vec![
+            read(
+                run(|c| c.add(Direction::North)),
+                run(|c| c.add(Direction::South)),
+                !orientation.read_top_to_bottom(),
+            ),
+            read(
+                run(|c| c.add(Direction::West)),
+                run(|c| c.add(Direction::East)),
+                !orientation.read_left_to_right(),
+            ),
+        ];
+
+        // 1 letter words don't count except when there's only one tile, in which case it does count as a word
+        if words.iter().all(|w| w.len() == 1) {
+            words
+        } else {
+            words.into_iter().filter(|word| word.len() > 1).collect()
+        }
+    }
+
+    fn word_strings(&self, words: &[Vec<Coordinate>]) -> Vec<String> {
+        words
+            .iter()
+            .map(|word| {
+                word.iter()
+                    .map(|c| match self.square(*c) {
+                        Square::Occupied { tile, .. } => tile,
+                        Square::Town { .. } => '#',
+                        Square::Artifact { .. } => '|',
+                        _ => '_',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn clear(&mut self, position: Coordinate) {
+        if let Some(i) = self.index(position) {
+            self.tiles.iter_mut().for_each(|tiles| tiles.set(i, false));
+            self.letters[i] = ' ';
+        }
+    }
+
+    /// Places a tile and resolves any battle and truncation that follows,
+    /// as `Game::make_move` would for the same rules.
+    /// Nothing keeps track of hands, so removed tiles aren't returned to any bag.
+    pub fn play(
+        &mut self,
+        player: usize,
+        position: Coordinate,
+        tile: char,
+        referee: &Referee,
+        cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
+    ) -> Result<Option<BattleReport>, GamePlayError> {
+        if player >= self.tiles.len() {
+            return Err(GamePlayError::NonExistentPlayer { index: player });
+        }
+        let Some(i) = self.index(position) else {
+            return Err(GamePlayError::OutSideBoardDimensions { position });
+        };
+        if self.owner(i).is_some() {
+            return Err(GamePlayError::OccupiedPlace);
+        }
+        let adjacent = position.neighbors_4_iter().any(|n| {
+            self.index(n)
+                .is_some_and(|n| self.tiles[player].get(n) || self.artifacts[player].get(n))
+        });
+        if !adjacent {
+            return Err(GamePlayError::NonAdjacentPlace);
+        }
+        if !self.land.get(i) {
+            return Err(GamePlayError::InvalidPosition { position });
+        }
+
+        self.tiles[player].set(i, true);
+        self.letters[i] = tile;
+
+        let battle = self.resolve_attack(player, position, referee, cached_word_judgements);
+
+        if matches!(referee.rules.truncation, rules::Truncation::Root) {
+            for player in 0..self.tiles.len() {
+                let truncated = self.tiles[player].clone().and_not(&self.rooted(player));
+                for i in truncated.ones() {
+                    self.clear(Coordinate::from_1d(i, self.width));
+                }
+            }
+        }
+
+        if self.tile(position) == Some((player, '¤')) {
+            self.clear(position);
+        }
+
+        Ok(battle)
+    }
+
+    fn resolve_attack(
+        &mut self,
+        player: usize,
+        position: Coordinate,
+        referee: &Referee,
+        cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
+    ) -> Option<BattleReport> {
+        let Referee {
+            judge,
+            rules,
+            attacker_dictionary,
+            defender_dictionary,
+        } = *referee;
+        let attackers = self.get_words(position);
+        let artifacts_are_combatants = matches!(
+            rules.win_condition,
+            WinCondition::Destination {
+                artifact_defense: ArtifactDefense::BeatenWithDefenseStrength(_),
+                ..
+            }
+        );
+        let defenders: Vec<_> = position
+            .neighbors_4_iter()
+            .filter(|n| {
+                let Some(i) = self.index(*n) else {
+                    return false;
+                };
+                let opposing = |places: &[Bits]| {
+                    places
+                        .iter()
+                        .enumerate()
+                        .any(|(owner, p)| owner != player && p.get(i))
+                };
+                opposing(&self.tiles)
+                    || (!self.defeated.get(i)
+                        && (opposing(&self.towns)
+                            || (artifacts_are_combatants && opposing(&self.artifacts))))
+            })
+            .flat_map(|n| self.get_words(n))
+            .collect();
+
+        let battle = judge.battle(
+            self.word_strings(&attackers),
+            self.word_strings(&defenders),
+            &vec![0; defenders.len()],
+            &rules.battle_rules,
+            &rules.win_condition,
+            attacker_dictionary,
+            defender_dictionary,
+            cached_word_judgements,
+        )?;
+
+        match &battle.outcome {
+            Outcome::DefenderWins => {
+                // When in BeatenByValidity mode, tiles can touch towns without being removed from the board.
+                let remove_attackers = !matches!(
+                    rules.win_condition,
+                    WinCondition::Destination {
+                        town_defense: TownDefense::BeatenByValidity,
+                        ..
+                    }
+                );
+                if remove_attackers {
+                    for square in attackers.iter().flatten() {
+                        self.clear(*square);
+                    }
+                }
+            }
+            Outcome::AttackerWins(losers) => {
+                for square in losers.iter().flat_map(|loser| &defenders[*loser]) {
+                    if let Some(i) = self.index(*square) {
+                        if self.towns.iter().chain(&self.artifacts).any(|p| p.get(i)) {
+                            self.defeated.set(i, true);
+                        }
+                    }
+                    self.clear(*square);
+                }
+
+                // explode adjacent letters belonging to opponents
+                for neighbour in position.neighbors_4_iter() {
+                    if self
+                        .tile(neighbour)
+                        .is_some_and(|(owner, _)| owner != player)
+                    {
+                        self.clear(neighbour);
+                    }
+                }
+            }
+        }
+
+        Some(battle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        game::Game,
+        generation::{generate_board, BoardSeed},
+        moves::Move,
+    };
+
+    #[test]
+    fn mirrors_a_board() {
+        let board = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             #0 __ S0 __ #0\n\
+             __ __ T0 __ __\n\
+             __ __ __ A1 __\n\
+             #1 __ B1 E1 #1\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        let mirror = BitBoard::from_board(&board).unwrap();
+
+        assert_eq!(mirror.to_board(), board);
+        assert_eq!(mirror.tile(Coordinate { x: 3, y: 3 }), Some((1, 'A')));
+        assert_eq!(mirror.tile(Coordinate { x: 1, y: 3 }), None);
+
+        for player in 0..2 {
+            let mut playable: Vec<_> = board
+                .playable_positions(player, &rules::Truncation::Root)
+                .into_iter()
+                .collect();
+            playable.sort();
+            assert_eq!(
+                mirror.playable_positions(player, &rules::Truncation::Root),
+                playable
+            );
+        }
+    }
+
+    #[test]
+    fn spreads_without_wrapping() {
+        // Wide enough that rows straddle the 64 bit words
+        let mirror = BitBoard::from_board(&Board::new(70, 3)).unwrap();
+        let width = mirror.width;
+        let spread = |position: Coordinate| {
+            let mut bits = Bits::new(width * mirror.height);
+            bits.set(position.to_1d(width), true);
+            mirror
+                .spread(&bits)
+                .ones()
+                .map(|i| Coordinate::from_1d(i, width))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            spread(Coordinate { x: width - 1, y: 1 }),
+            vec![
+                Coordinate { x: width - 1, y: 0 },
+                Coordinate { x: width - 2, y: 1 },
+                Coordinate { x: width - 1, y: 1 },
+                Coordinate { x: width - 1, y: 2 },
+            ]
+        );
+        assert_eq!(
+            spread(Coordinate { x: 0, y: 1 }),
+            vec![
+                Coordinate { x: 0, y: 0 },
+                Coordinate { x: 0, y: 1 },
+                Coordinate { x: 1, y: 1 },
+                Coordinate { x: 0, y: 2 },
+            ]
+        );
+    }
+
+    fn mirrored_game(seed: u32, truncation: rules::Truncation) -> Option<Game> {
+        let mut board = generate_board(BoardSeed::new(seed)).ok()?.board;
+        board.cache_special_squares();
+
+        let (_, mut rules) = GameRules::latest(None);
+        rules.truncation = truncation;
+        rules.visibility = rules::Visibility::Standard;
+        rules.board_orientation = rules::BoardOrientation::Standard;
+        let mut game = Game::new(9, 9, Some(seed as u64), rules);
+        game.add_player("A".into());
+        game.add_player("B".into());
+        game.board = board;
+        // Every pair of letters is a word, so that attacks are often won too
+        let pairs = ('A'..='Z').flat_map(|a| ('A'..='Z').map(move |b| format!("{a}{b}")));
+        game.judge = Judge::new(
+            [
+                "BIG", "FAT", "AND", "ART", "ARTS", "TEA", "EAT", "SEA", "RATS", "STAR",
+            ]
+            .into_iter()
+            .map(Into::into)
+            .chain(pairs)
+            .collect(),
+        );
+        game.start();
+
+        Some(game)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn moves_resolve_the_same_as_on_a_board(
+            seed in 0..100_000u32,
+            root_truncation in any::<bool>(),
+            picks in prop::collection::vec((0..1024usize, 0..16usize), 1..60),
+        ) {
+            let truncation = if root_truncation {
+                rules::Truncation::Root
+            } else {
+                rules::Truncation::None
+            };
+            let Some(mut game) = mirrored_game(seed, truncation) else {
+                return Ok(());
+            };
+            let mut mirror = BitBoard::from_board(&game.board).unwrap();
+
+            for (turn, (square, letter)) in picks.into_iter().enumerate() {
+                let player = turn % game.players.len();
+                let playable = mirror.playable_positions(player, &game.rules.truncation);
+                let mut on_board: Vec<_> = game
+                    .board
+                    .playable_positions(player, &game.rules.truncation)
+                    .into_iter()
+                    .collect();
+                on_board.sort();
+                prop_assert_eq!(&playable, &on_board);
+                if playable.is_empty() {
+                    break;
+                }
+
+                let position = playable[square % playable.len()];
+                let hand = &game.players[player].hand.0;
+                let tile = hand[letter % hand.len()];
+
+                let played = game.make_move(
+                    Move::Place { player, tile, position },
+                    None,
+                    None,
+                    None,
+                );
+                let referee = Referee {
+                    judge: &game.judge,
+                    rules: &game.rules,
+                    attacker_dictionary: None,
+                    defender_dictionary: None,
+                };
+                let mirrored = mirror.play(player, position, tile, &referee, None);
+                prop_assert_eq!(played.is_ok(), mirrored.is_ok());
+
+                let board = BitBoard::from_board(&game.board).unwrap();
+                prop_assert_eq!(&board, &mirror, "after {} at {}:\n{}", tile, position, game.board);
+                prop_assert_eq!(crate::judge::Judge::winner(&game.board), mirror.winner());
+            }
+        }
+    }
+}
@@ -0,0 +1,578 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use xxhash_rust::xxh3;
+
+use crate::{
+    board::{Coordinate, Square},
+    game::Game,
+    judge::WordDict,
+    messages::PlayerMessage,
+    moves::Move,
+    player::Hand,
+    reporting::Change,
+    rules::Appeals,
+};
+
+/// What an exhaustive search proved about a position, for the player about to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Proof {
+    /// The player to move wins however the opponent plays and whatever is drawn,
+    /// within this many of their own turns
+    Win { turns: usize },
+    /// The opponent wins however the player to move plays,
+    /// within this many of the player's turns
+    Loss { turns: usize },
+    /// The search couldn't settle it, or the position leads to a draw
+    Unproven,
+}
+
+impl Proof {
+    pub fn is_proven(&self) -> bool {
+        !matches!(self, Proof::Unproven)
+    }
+}
+
+/// How small a position has to be before it is worth solving outright
+#[derive(Debug, Clone, Copy)]
+pub struct EndgameLimits {
+    /// Empty land squares left on the board
+    pub max_empty_squares: usize,
+    /// Tiles left to draw, each of which is a branch of the search
+    pub max_bag_tiles: usize,
+    /// Turns to look ahead, counting both players
+    pub max_depth: usize,
+    /// Positions to visit before giving up on a proof
+    pub max_positions: usize,
+}
+
+impl Default for EndgameLimits {
+    fn default() -> Self {
+        Self {
+            max_empty_squares: 12,
+            max_bag_tiles: 4,
+            max_depth: 6,
+            max_positions: 20_000,
+        }
+    }
+}
+
+impl EndgameLimits {
+    /// Whether the game is far enough along for the solver to take it on
+    pub fn admits(&self, game: &Game) -> bool {
+        if game.is_over() || game.next_player.is_none() {
+            return false;
+        }
+
+        let empty_squares = game
+            .board
+            .squares
+            .iter()
+            .flatten()
+            .filter(|sq| matches!(sq, Square::Land { .. }))
+            .count();
+
+        empty_squares <= self.max_empty_squares
+            && game.bag.remaining_tiles().len() <= self.max_bag_tiles
+    }
+}
+
+/// Exhaustively searches small endgames for forced wins and losses.
+/// Draws from the bag are treated as chance, so a proof has to hold for every tile that could come out.
+pub struct EndgameSolver<'a> {
+    limits: EndgameLimits,
+    /// The words each player knows, by player index
+    dictionaries: [Option<&'a WordDict>; 2],
+    /// The player whose opponent's tiles are unknown to them, if we are solving on their behalf
+    hidden_opponent_of: Option<usize>,
+    visited: usize,
+    proven: HashMap<u64, Proof, xxh3::Xxh3Builder>,
+    cached_words: HashMap<String, bool, xxh3::Xxh3Builder>,
+}
+
+impl<'a> EndgameSolver<'a> {
+    pub fn new(limits: EndgameLimits, dictionaries: [Option<&'a WordDict>; 2]) -> Self {
+        Self {
+            limits,
+            dictionaries,
+            hidden_opponent_of: None,
+            visited: 0,
+            proven: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
+            cached_words: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
+        }
+    }
+
+    /// How many positions the solver has looked at so far
+    pub fn visited(&self) -> usize {
+        self.visited
+    }
+
+    /// Solves the position for the player to move, who can see every hand
+    pub fn solve(&mut self, game: &Game) -> Proof {
+        if !self.limits.admits(game) {
+            return Proof::Unproven;
+        }
+        self.search(&Self::prepared(game), self.limits.max_depth)
+    }
+
+    /// Labels every move available to the player to move,
+    /// with the proof of the position it leaves them in
+    pub fn label_moves(&mut self, game: &Game) -> Vec<((Coordinate, char), Proof)> {
+        if !self.limits.admits(game) {
+            return vec![];
+        }
+        let game = Self::prepared(game);
+        let depth = self.limits.max_depth;
+
+        game.possible_moves()
+            .into_iter()
+            .map(|(position, tile)| {
+                let proof = self.search_move(&game, position, tile, depth);
+                ((position, tile), proof)
+            })
+            .collect()
+    }
+
+    /// Finds a move that is proven to win for the player to move, without looking at their opponent's hand.
+    /// The opponent is assumed to hold tiles that can be any letter, so any win found holds whatever they have.
+    pub fn winning_move(&mut self, game: &Game) -> Option<PlayerMessage> {
+        if !self.limits.admits(game) {
+            return None;
+        }
+        let player = game.next_player?;
+        let game = self.hide_opponent(&Self::prepared(game), player);
+        let depth = self.limits.max_depth;
+
+        let mut best: Option<((Coordinate, char), usize)> = None;
+        for (position, tile) in game.possible_moves() {
+            if let Proof::Win { turns } = self.search_move(&game, position, tile, depth) {
+                if best.is_none_or(|(_, fastest)| turns < fastest) {
+                    best = Some(((position, tile), turns));
+                }
+            }
+        }
+
+        best.map(|((position, tile), _)| PlayerMessage::Place(position, tile))
+    }
+
+    /// Strips out anything that slows down the search without affecting the outcome,
+    /// and indexes the board's words for the many lookups ahead
+    fn prepared(game: &Game) -> Game {
+        let mut game = game.clone();
+        game.rules.battle_delay = 0;
+        game.rules.appeals = Appeals::Disallowed;
+        game.board.index_words();
+        game
+    }
+
+    /// Swaps the opponent's hand for wildcards, and puts their real tiles back among the unknowns
+    fn hide_opponent(&mut self, game: &Game, player: usize) -> Game {
+        self.hidden_opponent_of = Some(player);
+
+        let mut game = game.clone();
+        let opponent = (player + 1) % game.players.len();
+        let hidden = std::mem::take(&mut game.players[opponent].hand.0);
+        for tile in &hidden {
+            game.bag.return_tile(*tile);
+        }
+        game.players[opponent].hand = Hand(vec!['*'; hidden.len()]);
+        game
+    }
+
+    fn search(&mut self, game: &Game, depth: usize) -> Proof {
+        let Some(player) = game.next_player else {
+            return Proof::Unproven;
+        };
+        if let Some(winner) = game.winner {
+            return if winner == player {
+                Proof::Win { turns: 0 }
+            } else {
+                Proof::Loss { turns: 0 }
+            };
+        }
+        if game.is_over() || depth == 0 || self.visited >= self.limits.max_positions {
+            return Proof::Unproven;
+        }
+        self.visited += 1;
+
+        let key = Self::position_key(game);
+        if let Some(proof) = self.proven.get(&key) {
+            return *proof;
+        }
+
+        let mut fastest_win = None;
+        let mut slowest_loss = Some(0);
+        let possible_moves = game.possible_moves();
+        if possible_moves.is_empty() {
+            return Proof::Unproven;
+        }
+
+        for (position, tile) in possible_moves {
+            match self.search_move(game, position, tile, depth) {
+                Proof::Win { turns } => {
+                    fastest_win = Some(fastest_win.map_or(turns, |f: usize| f.min(turns)));
+                    // Nothing beats a win next turn
+                    if turns == 1 {
+                        break;
+                    }
+                }
+                Proof::Loss { turns } => {
+                    slowest_loss = slowest_loss.map(|s: usize| s.max(turns));
+                }
+                Proof::Unproven => slowest_loss = None,
+            }
+        }
+
+        let proof = match (fastest_win, slowest_loss) {
+            (Some(turns), _) => Proof::Win { turns },
+            (None, Some(turns)) => Proof::Loss { turns },
+            (None, None) => Proof::Unproven,
+        };
+        if proof.is_proven() {
+            self.proven.insert(key, proof);
+        }
+        proof
+    }
+
+    /// Plays a move and solves every position it could lead to, depending on what gets drawn
+    fn search_move(
+        &mut self,
+        game: &Game,
+        position: Coordinate,
+        tile: char,
+        depth: usize,
+    ) -> Proof {
+        let player = game.next_player.expect("Endgames are solved turn by turn");
+        if depth == 0 {
+            return Proof::Unproven;
+        }
+
+        let mut played = game.clone();
+        let attacker_dictionary = self.dictionaries[player % 2];
+        let defender_dictionary = self.dictionaries[(player + 1) % 2];
+        if played
+            .play_turn(
+                Move::Place {
+                    player,
+                    tile,
+                    position,
+                },
+                attacker_dictionary,
+                defender_dictionary,
+                Some(&mut self.cached_words),
+            )
+            .is_err()
+        {
+            return Proof::Unproven;
+        }
+
+        if let Some(winner) = played.winner {
+            return if winner == player {
+                Proof::Win { turns: 1 }
+            } else {
+                Proof::Loss { turns: 1 }
+            };
+        }
+
+        let mut outcomes = vec![];
+        for branch in self.draw_branches(game, played, player, tile) {
+            let Some(branch) = branch else {
+                return Proof::Unproven;
+            };
+            // The next position is solved for the opponent, so their wins are our losses
+            let proof = match self.search(&branch, depth - 1) {
+                Proof::Win { turns } => Proof::Loss { turns: turns + 1 },
+                Proof::Loss { turns } => Proof::Win { turns: turns + 1 },
+                Proof::Unproven => return Proof::Unproven,
+            };
+            outcomes.push(proof);
+        }
+
+        // Every draw has to lead to the same result for it to be proven
+        if outcomes.iter().all(|p| matches!(p, Proof::Win { .. })) {
+            let turns = outcomes
+                .iter()
+                .map(|p| match p {
+                    Proof::Win { turns } => *turns,
+                    _ => unreachable!(),
+                })
+                .max();
+            turns.map_or(Proof::Unproven, |turns| Proof::Win { turns })
+        } else if outcomes.iter().all(|p| matches!(p, Proof::Loss { .. })) {
+            let turns = outcomes
+                .iter()
+                .map(|p| match p {
+                    Proof::Loss { turns } => *turns,
+                    _ => unreachable!(),
+                })
+                .min();
+            turns.map_or(Proof::Unproven, |turns| Proof::Loss { turns })
+        } else {
+            Proof::Unproven
+        }
+    }
+
+    /// Every game that could follow a move, one for each tile that could have been drawn.
+    /// `None` stands in for draws that are too many to search, such as from a refilled bag.
+    fn draw_branches(
+        &self,
+        before: &Game,
+        mut played: Game,
+        player: usize,
+        tile: char,
+    ) -> Vec<Option<Game>> {
+        let drawn = played
+            .recent_changes
+            .iter()
+            .find_map(|change| match change {
+                Change::Hand(hand) if hand.player == player && hand.removed == [tile] => {
+                    hand.added.first().copied()
+                }
+                _ => None,
+            });
+        let Some(drawn) = drawn else {
+            return vec![Some(played)];
+        };
+
+        // The opponent of whoever we're solving for always draws another unknown tile,
+        // and what they really drew could still come out of the bag as far as we know
+        if self.hidden_opponent_of.is_some_and(|p| p != player) {
+            played.bag.return_tile(drawn);
+            Self::swap_drawn(&mut played, player, drawn, '*');
+            return vec![Some(played)];
+        }
+
+        let mut possible: Vec<_> = before.bag.remaining_tiles().to_vec();
+        if possible.is_empty() && before.bag.refills() {
+            return vec![None];
+        }
+        possible.sort();
+        possible.dedup();
+
+        possible
+            .into_iter()
+            .map(|instead| {
+                let mut branch = played.clone();
+                if instead != drawn {
+                    branch.bag.return_tile(drawn);
+                    if !branch.bag.take_tile(instead) {
+                        return None;
+                    }
+                    Self::swap_drawn(&mut branch, player, drawn, instead);
+                }
+                Some(branch)
+            })
+            .collect()
+    }
+
+    fn swap_drawn(game: &mut Game, player: usize, drawn: char, instead: char) {
+        let hand = &mut game.players[player].hand.0;
+        if let Some(index) = hand.iter().position(|t| *t == drawn) {
+            hand[index] = instead;
+        }
+    }
+
+    /// Identifies a position by everything that can change its outcome
+    fn position_key(game: &Game) -> u64 {
+        let mut hasher = xxh3::Xxh3::new();
+        game.position_hash().hash(&mut hasher);
+        for player in &game.players {
+            let mut hand = player.hand.0.clone();
+            hand.sort();
+            hand.hash(&mut hasher);
+        }
+        let mut bag = game.bag.remaining_tiles().to_vec();
+        bag.sort();
+        bag.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl Game {
+    /// Once the bag is empty every tile left is in someone's hand, so an endgame
+    /// small enough to solve outright is decided as soon as the solver proves who wins it
+    pub fn resolve_endgame(&mut self, dictionaries: [Option<&WordDict>; 2]) -> Option<usize> {
+        if !self.in_endgame() || self.players.len() != 2 || self.is_over() {
+            return None;
+        }
+        let player = self.next_player?;
+
+        let winner = match EndgameSolver::new(EndgameLimits::default(), dictionaries).solve(self) {
+            Proof::Win { .. } => player,
+            Proof::Loss { .. } => (player + 1) % 2,
+            Proof::Unproven => return None,
+        };
+        self.board.defeat_player((winner + 1) % 2);
+        self.winner = Some(winner);
+        Some(winner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bag::TileBag,
+        board::Board,
+        judge::WordData,
+        npc::{scoring::NPCParams, Arborist},
+        player::Player,
+        rules::GameRules,
+    };
+
+    fn dict(words: &[&str]) -> WordDict {
+        words
+            .iter()
+            .map(|word| {
+                (
+                    word.to_string(),
+                    WordData {
+                        extensions: 0,
+                        rel_freq: 0.0,
+                        objectionable: false,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn endgame(board: &str, hands: [&str; 2], bag: &str) -> Game {
+        let mut bag = TileBag::explicit(bag.chars().collect(), Some(1));
+        let players = hands
+            .iter()
+            .enumerate()
+            .map(|(index, hand)| {
+                // Without any capacity the players never draw, so the bag stays as given
+                let mut player =
+                    Player::new(format!("P{index}"), index, 0, &mut bag, None, (0, 0, 0));
+                player.hand = Hand(hand.chars().collect());
+                player
+            })
+            .collect();
+
+        let mut game = Game {
+            board: Board::from_string(board),
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            next_player: Some(0),
+            ..Game::new_legacy(3, 1, None, GameRules::generation(0))
+        };
+        game.start();
+        game
+    }
+
+    #[test]
+    fn proves_a_win_next_turn() {
+        let dict = dict(&["at", "ate"]);
+        let game = endgame(
+            r###"
+            ~~ ~~ |0 ~~ ~~
+            ~~ ~~ T0 ~~ ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ #1 ~~ ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ |1 ~~ ~~
+            "###,
+            ["A", "Q"],
+            "",
+        );
+
+        let mut solver = EndgameSolver::new(EndgameLimits::default(), [Some(&dict), Some(&dict)]);
+        assert_eq!(solver.solve(&game), Proof::Win { turns: 1 });
+
+        let labels = solver.label_moves(&game);
+        assert_eq!(
+            labels,
+            vec![((Coordinate { x: 2, y: 2 }, 'A'), Proof::Win { turns: 1 })]
+        );
+    }
+
+    #[test]
+    fn npc_solves_endgames_once_the_bag_is_empty() {
+        let dict = dict(&["at", "ate"]);
+        let board = r###"
+            ~~ ~~ |0 ~~ ~~
+            ~~ ~~ T0 ~~ ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ #1 ~~ ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ |1 ~~ ~~
+            "###;
+        let best_move = |game: &Game| {
+            let mut arb = Arborist::pruning();
+            let (best, _) = Game::best_move(
+                game,
+                Some(&dict),
+                Some(&dict),
+                3,
+                Some(&mut arb),
+                false,
+                &NPCParams::default(),
+            );
+            (best, arb.assessed())
+        };
+
+        let game = endgame(board, ["A", "Q"], "");
+        assert!(game.in_endgame());
+        assert_eq!(
+            best_move(&game),
+            (PlayerMessage::Place(Coordinate { x: 2, y: 2 }, 'A'), 0),
+            "The win is proven without searching"
+        );
+
+        // With tiles still to come, the NPC goes back to searching
+        let game = endgame(board, ["A", "Q"], "E");
+        assert!(!game.in_endgame());
+        let (_, assessed) = best_move(&game);
+        assert!(assessed > 0);
+    }
+
+    #[test]
+    fn decides_proven_endgames() {
+        let dict = dict(&["at", "ate"]);
+        let board = r###"
+            ~~ ~~ |0 ~~ ~~
+            ~~ ~~ T0 ~~ ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ #1 ~~ ~~
+            ~~ ~~ __ ~~ ~~
+            ~~ ~~ |1 ~~ ~~
+            "###;
+
+        let mut game = endgame(board, ["A", "Q"], "");
+        assert_eq!(game.resolve_endgame([Some(&dict), Some(&dict)]), Some(0));
+        assert_eq!(game.winner, Some(0));
+
+        let mut game = endgame(board, ["A", "Q"], "E");
+        assert_eq!(game.resolve_endgame([Some(&dict), Some(&dict)]), None);
+        assert!(!game.is_over());
+    }
+
+    #[test]
+    fn leaves_large_positions_alone() {
+        let dict = dict(&["at"]);
+        let game = endgame(
+            r###"
+            ~~ ~~ |0 ~~
+            __ __ A0 __
+            __ __ __ __
+            __ #1 __ __
+            __ __ __ __
+            ~~ ~~ |1 ~~
+            "###,
+            ["T", "Q"],
+            "",
+        );
+
+        let limits = EndgameLimits {
+            max_empty_squares: 4,
+            ..EndgameLimits::default()
+        };
+        let mut solver = EndgameSolver::new(limits, [Some(&dict), Some(&dict)]);
+        assert_eq!(solver.solve(&game), Proof::Unproven);
+        assert_eq!(solver.visited(), 0);
+    }
+}
@@ -0,0 +1,287 @@
+//! Finds puzzles in generated games, where the player to move can force a win
+//! in a set number of turns however their opponent defends.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::Board,
+    game::Game,
+    generation::{generate_board, BoardSeed},
+    judge::WordDict,
+    moves::Move,
+    player::Hand,
+    rules::GameRules,
+};
+
+use super::{
+    endgame::{EndgameLimits, EndgameSolver, Proof},
+    opening_search::{self_play, self_play_game},
+    scoring::NPCParams,
+};
+
+/// How many of the winner's turns before the earliest possible one are also checked for a puzzle
+const LOOKBACK_TURNS: usize = 3;
+/// Positions the solver visits before giving up on a puzzle
+const MAX_PUZZLE_POSITIONS: usize = 50_000;
+
+/// A position where the player to move can force a win within `turns` of their own turns,
+/// but no sooner. Every hand is in view, and nobody draws any more tiles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForcedWinPuzzle {
+    pub board: Board,
+    pub rules_generation: u32,
+    /// Each player's hand, by player index
+    pub hands: Vec<Hand>,
+    /// The player solving the puzzle, who moves first
+    pub player: usize,
+    pub turns: usize,
+}
+
+impl ForcedWinPuzzle {
+    /// Has the NPC play itself on the board from `board_seed`, then looks back over the winner's
+    /// last turns for a position they could have forced a win from in exactly `turns`
+    pub fn generate(
+        board_seed: BoardSeed,
+        rules_generation: u32,
+        turns: usize,
+        dict: &WordDict,
+        npc_params: &NPCParams,
+    ) -> Option<Self> {
+        let mut board = generate_board(board_seed.clone()).ok()?.board;
+        board.cache_special_squares();
+
+        let rules = GameRules::generation(rules_generation);
+        let (moves, winner) = self_play(&board, board_seed.seed, rules.clone(), dict, npc_params);
+        let winner = winner?;
+
+        let mut game = self_play_game(&board, board_seed.seed, rules);
+        let mut winners_turns = vec![];
+        for next_move in moves {
+            if game.next_player == Some(winner) {
+                winners_turns.push(game.clone());
+            }
+            game.play_turn(next_move, Some(dict), Some(dict), None)
+                .ok()?;
+        }
+
+        // The winner's last turn won outright, so a forced win in `turns` can start no later
+        // than that many of their turns from the end
+        winners_turns
+            .iter()
+            .rev()
+            .skip(turns.checked_sub(1)?)
+            .take(LOOKBACK_TURNS + 1)
+            .find_map(|position| Self::from_position(position, turns, dict))
+    }
+
+    /// Makes a puzzle of the position if the player to move can force a win in exactly `turns`
+    pub fn from_position(game: &Game, turns: usize, dict: &WordDict) -> Option<Self> {
+        if turns == 0 || game.is_over() {
+            return None;
+        }
+
+        let puzzle = Self {
+            board: game.board.clone(),
+            rules_generation: game.rules.generation?,
+            hands: game.players.iter().map(|p| p.hand.clone()).collect(),
+            player: game.next_player?,
+            turns,
+        };
+
+        // Proofs count the turns of both players, up to and including the winning one
+        let plies = turns * 2 - 1;
+        let mut solver = EndgameSolver::new(Self::limits(plies), [Some(dict); 2]);
+        (solver.solve(&puzzle.game()) == Proof::Win { turns: plies }).then_some(puzzle)
+    }
+
+    /// A fresh game in the puzzle's position, where nobody draws tiles after playing them
+    pub fn game(&self) -> Game {
+        let mut game = Game::new(
+            self.board.width(),
+            self.board.height(),
+            None,
+            GameRules::generation(self.rules_generation),
+        );
+        for index in 0..self.hands.len() {
+            game.add_player(if index == self.player {
+                "You".into()
+            } else {
+                "Defender".into()
+            });
+        }
+        game.board = self.board.clone();
+        for (player, hand) in game.players.iter_mut().zip(&self.hands) {
+            player.hand = hand.clone();
+            player.hand_capacity = 0;
+        }
+        game.next_player = Some(self.player);
+        game.start();
+        game
+    }
+
+    /// The moves that keep a forced win within the puzzle's turns,
+    /// from a game in progress on the puzzle
+    pub fn winning_moves(&self, game: &Game, dict: &WordDict) -> Vec<Move> {
+        let turns_left = self.turns_left(game);
+        if game.next_player != Some(self.player) || turns_left == 0 {
+            return vec![];
+        }
+
+        let plies = turns_left * 2 - 1;
+        let mut solver = EndgameSolver::new(Self::limits(plies), [Some(dict); 2]);
+        solver
+            .label_moves(game)
+            .into_iter()
+            .filter(|(_, proof)| matches!(proof, Proof::Win { turns } if *turns <= plies))
+            .map(|((position, tile), _)| Move::Place {
+                player: self.player,
+                tile,
+                position,
+            })
+            .collect()
+    }
+
+    /// The defender's reply that holds out the longest, from a game in progress on the puzzle
+    pub fn defence(&self, game: &Game, dict: &WordDict) -> Option<Move> {
+        let defender = game.next_player.filter(|p| *p != self.player)?;
+        let turns_left = self.turns_left(game);
+
+        let mut solver = EndgameSolver::new(Self::limits(turns_left * 2), [Some(dict); 2]);
+        solver
+            .label_moves(game)
+            .into_iter()
+            .max_by_key(|(_, proof)| match proof {
+                Proof::Win { turns } => (2, usize::MAX - turns),
+                Proof::Unproven => (1, 0),
+                Proof::Loss { turns } => (0, *turns),
+            })
+            .map(|((position, tile), _)| Move::Place {
+                player: defender,
+                tile,
+                position,
+            })
+    }
+
+    /// Turns the solver has left to win in
+    fn turns_left(&self, game: &Game) -> usize {
+        let played = game
+            .player_turn_count
+            .get(self.player)
+            .copied()
+            .unwrap_or_default();
+        self.turns.saturating_sub(played as usize)
+    }
+
+    /// Puzzles are searched across the whole board, relying on the small number of turns to stay quick
+    fn limits(max_depth: usize) -> EndgameLimits {
+        EndgameLimits {
+            max_empty_squares: usize::MAX,
+            max_bag_tiles: usize::MAX,
+            max_depth,
+            max_positions: MAX_PUZZLE_POSITIONS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Coordinate, judge::Judge};
+
+    fn dict() -> WordDict {
+        Judge::new(vec!["AT".into(), "EAT".into()]).builtin_dictionary
+    }
+
+    /// Player 0's T leads down towards player 1's town, which player 1 can't reach to defend
+    fn puzzle_game(hands: [&str; 2]) -> Game {
+        ForcedWinPuzzle {
+            board: Board::from_string(
+                r###"
+                ~~ ~~ |0 ~~ ~~
+                ~~ ~~ T0 ~~ ~~
+                ~~ ~~ __ ~~ ~~
+                ~~ ~~ __ ~~ ~~
+                ~~ ~~ #1 ~~ ~~
+                ~~ ~~ ~~ ~~ ~~
+                __ __ __ __ ~~
+                ~~ ~~ |1 ~~ ~~
+                "###,
+            ),
+            rules_generation: 0,
+            hands: hands.iter().map(|h| Hand(h.chars().collect())).collect(),
+            player: 0,
+            turns: 0,
+        }
+        .game()
+    }
+
+    #[test]
+    fn finds_wins_of_exactly_the_given_length() {
+        let dict = dict();
+        let game = puzzle_game(["AEZ", "QZ"]);
+
+        assert_eq!(ForcedWinPuzzle::from_position(&game, 1, &dict), None);
+        let puzzle = ForcedWinPuzzle::from_position(&game, 2, &dict).unwrap();
+        assert_eq!(puzzle.player, 0);
+        assert_eq!(
+            puzzle.hands,
+            vec![Hand(vec!['A', 'E', 'Z']), Hand(vec!['Q', 'Z'])]
+        );
+        assert_eq!(
+            ForcedWinPuzzle::from_position(&game, 3, &dict),
+            None,
+            "Puzzles can't be won any faster than they say"
+        );
+
+        // Without the A to lead with, the town is out of reach
+        let game = puzzle_game(["EEZ", "QZ"]);
+        assert_eq!(ForcedWinPuzzle::from_position(&game, 2, &dict), None);
+    }
+
+    #[test]
+    fn plays_out_a_puzzle() {
+        let dict = dict();
+        let puzzle = ForcedWinPuzzle::from_position(&puzzle_game(["AEZ", "QZ"]), 2, &dict).unwrap();
+        let mut game = puzzle.game();
+
+        let lead = Move::Place {
+            player: 0,
+            tile: 'A',
+            position: Coordinate { x: 2, y: 2 },
+        };
+        assert_eq!(puzzle.winning_moves(&game, &dict), vec![lead.clone()]);
+        game.play_turn(lead, Some(&dict), Some(&dict), None)
+            .unwrap();
+        assert_eq!(
+            game.players[0].hand,
+            Hand(vec!['Z', 'E']),
+            "Nothing is drawn"
+        );
+
+        let defence = puzzle.defence(&game, &dict).unwrap();
+        assert!(matches!(
+            defence,
+            Move::Place {
+                player: 1,
+                position: Coordinate { x: 2, y: 6 },
+                ..
+            }
+        ));
+        game.play_turn(defence, Some(&dict), Some(&dict), None)
+            .unwrap();
+
+        let finish = puzzle.winning_moves(&game, &dict);
+        assert_eq!(
+            finish,
+            vec![Move::Place {
+                player: 0,
+                tile: 'E',
+                position: Coordinate { x: 2, y: 3 },
+            }]
+        );
+        assert_eq!(
+            game.play_turn(finish[0].clone(), Some(&dict), Some(&dict), None),
+            Ok(Some(0))
+        );
+    }
+}
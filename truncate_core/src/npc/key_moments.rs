@@ -0,0 +1,234 @@
+//! Finds the turning points of a finished game, being the moves that swung
+//! the NPC's evaluation of the board the furthest.
+
+use crate::{
+    board::Square,
+    game::Game,
+    judge::{Outcome, WordDict},
+    moves::Move,
+    replay::Replay,
+    reporting::{BoardChange, BoardChangeAction, BoardChangeDetail, Change},
+};
+
+use super::{scoring::NPCParams, Caches};
+
+/// Larger than any evaluation of a board, so that the move winning the game is always a key moment
+const WIN_ADVANTAGE: f32 = 1000.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMoment {
+    /// The index of the move within the game, in the order they were played
+    pub turn: usize,
+    /// Who played the move
+    pub player: usize,
+    /// How far the move moved the evaluation in favour of the player who played it,
+    /// being negative for a move that gave the opponent the advantage
+    pub swing: f32,
+    /// A one line summary of what happened on the move
+    pub explanation: String,
+}
+
+/// Scores how well a game is going for the first player, with the evaluation the NPC searches with
+fn advantage(
+    game: &Game,
+    dictionary: Option<&WordDict>,
+    caches: &mut Caches,
+    npc_params: &NPCParams,
+) -> f32 {
+    match game.winner {
+        Some(0) => WIN_ADVANTAGE,
+        Some(_) => -WIN_ADVANTAGE,
+        None => game
+            .static_eval(dictionary, 0, 0, caches, npc_params)
+            .rank(),
+    }
+}
+
+fn mover(played: &Move) -> usize {
+    match played {
+        Move::Place { player, .. }
+        | Move::Swap { player, .. }
+        | Move::Exchange { player, .. }
+        | Move::Mulligan { player } => *player,
+    }
+}
+
+fn explain(game: &Game, player: usize, swing: f32, changes: &[Change]) -> String {
+    let name = |p: usize| {
+        game.get_player(p)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| format!("Player {}", p + 1))
+    };
+
+    if let Some(winner) = game.winner {
+        return format!("{} won the game", name(winner));
+    }
+
+    let battle = changes.iter().find_map(|change| match change {
+        Change::Battle(report) => Some(report),
+        _ => None,
+    });
+    if let Some(battle) = battle {
+        let attack = battle
+            .attackers
+            .iter()
+            .map(|word| word.resolved_word.to_uppercase())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return match battle.outcome {
+            Outcome::AttackerWins(_) => format!("{} won a battle with {attack}", name(player)),
+            Outcome::DefenderWins => format!("{} lost a battle with {attack}", name(player)),
+        };
+    }
+
+    let truncated = changes
+        .iter()
+        .filter_map(|change| match change {
+            Change::Board(BoardChange {
+                action: BoardChangeAction::Truncated,
+                detail:
+                    BoardChangeDetail {
+                        square: Square::Occupied { player, .. },
+                        ..
+                    },
+            }) => Some(*player),
+            _ => None,
+        })
+        .fold([0; 2], |mut counts, owner| {
+            if let Some(count) = counts.get_mut(owner) {
+                *count += 1;
+            }
+            counts
+        });
+    if let Some((owner, count)) = truncated
+        .into_iter()
+        .enumerate()
+        .max_by_key(|(_, count)| *count)
+        .filter(|(_, count)| *count > 0)
+    {
+        return format!("{} lost {count} tiles to truncation", name(owner));
+    }
+
+    if swing > 0.0 {
+        format!("{} raced closer to the towns", name(player))
+    } else {
+        format!("{} left a town open to attack", name(player))
+    }
+}
+
+/// Plays through a recorded game, returning up to `count` of the moves that
+/// swung the evaluation the most, in the order they were played.
+///
+/// Analysis stops at the first move of the replay that can't be played.
+pub fn key_moments(
+    replay: Replay,
+    dictionary: Option<&WordDict>,
+    npc_params: &NPCParams,
+    count: usize,
+) -> Vec<KeyMoment> {
+    let mut caches = Caches::new();
+    let moves = replay.moves().to_vec();
+    let mut before = advantage(replay.game(), dictionary, &mut caches, npc_params);
+
+    let mut moments = vec![];
+    for (turn, played) in replay.enumerate() {
+        let Ok((game, changes)) = played else {
+            break;
+        };
+        let after = advantage(&game, dictionary, &mut caches, npc_params);
+        let player = mover(&moves[turn]);
+        let swing = if player == 0 {
+            after - before
+        } else {
+            before - after
+        };
+        before = after;
+
+        if swing.abs() < f32::EPSILON {
+            continue;
+        }
+        moments.push(KeyMoment {
+            turn,
+            player,
+            swing,
+            explanation: explain(&game, player, swing, &changes),
+        });
+    }
+
+    moments.sort_by(|a, b| b.swing.abs().total_cmp(&a.swing.abs()));
+    moments.truncate(count);
+    moments.sort_by_key(|moment| moment.turn);
+    moments
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bag::tests::trivial_bag,
+        board::{Board, Coordinate},
+        player::Player,
+        rules::GameRules,
+    };
+
+    use super::*;
+
+    fn docked_game() -> Game {
+        let mut bag = trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        Game {
+            board: Board::from_string(
+                "~~ |0 ~~\n\
+                 __ __ __\n\
+                 #0 __ #0\n\
+                 __ __ __\n\
+                 #1 __ #1\n\
+                 __ __ __\n\
+                 ~~ |1 ~~",
+            ),
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        }
+    }
+
+    fn place(player: usize, x: usize, y: usize) -> Move {
+        Move::Place {
+            player,
+            tile: 'A',
+            position: Coordinate { x, y },
+        }
+    }
+
+    #[test]
+    fn picks_the_largest_swings_in_order() {
+        let moves = vec![
+            place(0, 1, 1),
+            place(1, 1, 5),
+            place(0, 1, 2),
+            place(1, 0, 5),
+        ];
+        let replay = Replay::from_game(docked_game(), moves.clone(), None);
+        let moments = key_moments(replay, None, &NPCParams::default(), 2);
+
+        assert!(moments.len() <= 2);
+        assert!(moments.windows(2).all(|w| w[0].turn < w[1].turn));
+        for moment in &moments {
+            assert_eq!(moment.player, mover(&moves[moment.turn]));
+            assert!(!moment.explanation.is_empty());
+        }
+    }
+
+    #[test]
+    fn stops_at_an_illegal_move() {
+        let moves = vec![place(0, 1, 1), place(0, 1, 2), place(1, 1, 5)];
+        let replay = Replay::from_game(docked_game(), moves, None);
+        let moments = key_moments(replay, None, &NPCParams::default(), 3);
+
+        assert!(moments.iter().all(|moment| moment.turn == 0));
+    }
+}
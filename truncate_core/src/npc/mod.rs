@@ -1,8 +1,16 @@
 use std::{
     collections::{HashMap, HashSet},
     ops::Div,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
 
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+
 use crate::{
     board::{BoardDistances, Coordinate, Square},
     game::Game,
@@ -10,20 +18,189 @@ use crate::{
     messages::PlayerMessage,
     moves::Move,
     player::Hand,
+    rng::{TruncateRng, BLUNDER_STREAM, NPC_STREAM},
 };
 
+pub mod battle_odds;
+pub mod bitboard;
+pub mod endgame;
+pub mod forced_wins;
+pub mod key_moments;
+pub mod opening_book;
+pub mod opening_search;
+pub mod progression;
 pub mod scoring;
 
 use scoring::BoardScore;
 use xxhash_rust::xxh3;
 
-use self::scoring::NPCParams;
+use self::{
+    endgame::{EndgameLimits, EndgameSolver},
+    opening_book::OpeningBook,
+    scoring::NPCParams,
+};
+
+/// How many boards are assessed between each report of a search's progress
+const PROGRESS_REPORT_INTERVAL: usize = 4096;
+
+/// How far along a search is, as last reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchReport {
+    pub assessed: usize,
+    /// The deepest search that has finished, if any has
+    pub depth: usize,
+}
+
+/// Tracks how far along a search is, so that it can be shown while the search is running elsewhere
+#[derive(Debug, Default)]
+pub struct SearchProgress {
+    assessed: AtomicUsize,
+    depth: AtomicUsize,
+    /// Called every so often with the latest progress, for hosts that can't read it directly
+    on_report: Option<fn(SearchReport)>,
+}
+
+impl SearchProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reporting_to(on_report: fn(SearchReport)) -> Self {
+        Self {
+            on_report: Some(on_report),
+            ..Self::default()
+        }
+    }
+
+    pub fn report(&self) -> SearchReport {
+        SearchReport {
+            assessed: self.assessed.load(Ordering::Relaxed),
+            depth: self.depth.load(Ordering::Relaxed),
+        }
+    }
+
+    fn assessed_one(&self) {
+        let assessed = self.assessed.fetch_add(1, Ordering::Relaxed) + 1;
+        if assessed.is_multiple_of(PROGRESS_REPORT_INTERVAL) {
+            if let Some(on_report) = self.on_report {
+                on_report(self.report());
+            }
+        }
+    }
+
+    fn reached(&self, depth: usize) {
+        self.depth.fetch_max(depth, Ordering::Relaxed);
+        if let Some(on_report) = self.on_report {
+            on_report(self.report());
+        }
+    }
+}
+
+/// One of several searches that each look at their own share of the first layer of moves,
+/// such as searches running on separate web workers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchShare {
+    pub index: usize,
+    pub of: usize,
+}
+
+impl SearchShare {
+    /// The whole search, as a single share
+    pub fn whole() -> Self {
+        Self { index: 0, of: 1 }
+    }
+
+    /// Keeps only this share's moves, dealt out in the order of `Game::possible_moves`
+    /// so that every share splits them the same way. A share left with no moves of its
+    /// own searches all of them, so that it still has a move to suggest.
+    fn keep_own(&self, game: &Game, moves: &mut Vec<(Coordinate, char)>) {
+        if self.of <= 1 {
+            return;
+        }
+        let own: HashSet<_> = game
+            .possible_moves()
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| i % self.of == self.index)
+            .map(|(_, m)| m)
+            .collect();
+        if !own.is_empty() {
+            moves.retain(|m| own.contains(m));
+        }
+    }
+}
+
+/// What a search over one share of the moves found, to be combined with the other shares
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareResult {
+    /// The move this share would play by itself
+    pub chosen: PlayerMessage,
+    /// The best score and move in this share for each depth that it finished
+    pub depths: Vec<(BoardScore, Option<(Coordinate, char)>)>,
+}
+
+impl ShareResult {
+    /// Picks the best move across every share, from the deepest search that all of them finished
+    pub fn combine(results: &[ShareResult]) -> Option<PlayerMessage> {
+        let depth = results.iter().map(|r| r.depths.len()).min()?;
+
+        let mut best: Option<&(BoardScore, Option<(Coordinate, char)>)> = None;
+        if depth > 0 {
+            for found in results.iter().map(|r| &r.depths[depth - 1]) {
+                if found.1.is_some() && best.is_none_or(|best| found.0 > best.0) {
+                    best = Some(found);
+                }
+            }
+        }
+
+        match best {
+            Some((_, Some((position, tile)))) => Some(PlayerMessage::Place(*position, *tile)),
+            // Moves found without searching, such as from the opening book, are the same for every share
+            _ => results.first().map(|r| r.chosen.clone()),
+        }
+    }
+}
+
+/// The pool threaded searches run on, kept between searches as building one starts every thread
+#[cfg(feature = "parallel")]
+static SEARCH_POOL: std::sync::Mutex<Option<Arc<rayon::ThreadPool>>> = std::sync::Mutex::new(None);
+
+/// The search pool with `threads` threads, only rebuilding it if the number of threads has changed
+#[cfg(feature = "parallel")]
+fn search_pool(threads: usize) -> Arc<rayon::ThreadPool> {
+    let mut pool = SEARCH_POOL.lock().unwrap();
+    if let Some(pool) = pool.as_ref().filter(|p| p.current_num_threads() == threads) {
+        return pool.clone();
+    }
+
+    let built = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("NPC threads should be available"),
+    );
+    *pool = Some(built.clone());
+    built
+}
 
 #[derive(Debug)]
 pub struct Arborist {
     assessed: usize,
     prune: bool,
     cap: usize,
+    /// When the search started, and how long it may run for
+    budget: Option<(Instant, Duration)>,
+    /// Whether a complete search has finished, so it's safe to stop on the budget
+    has_fallback: bool,
+    /// How many threads the first layer of moves is shared between
+    threads: usize,
+    /// Boards assessed across every thread of a threaded search
+    shared: Option<Arc<AtomicUsize>>,
+    progress: Option<Arc<SearchProgress>>,
+    /// Which of the first layer of moves this search looks at, if not all of them
+    share: Option<SearchShare>,
+    /// The best score and move for each depth finished, kept while searching a share
+    share_depths: Vec<(BoardScore, Option<(Coordinate, char)>)>,
 }
 impl Arborist {
     pub fn pruning() -> Self {
@@ -31,6 +208,13 @@ impl Arborist {
             assessed: 0,
             prune: true,
             cap: std::usize::MAX,
+            budget: None,
+            has_fallback: false,
+            threads: 1,
+            shared: None,
+            progress: None,
+            share: None,
+            share_depths: vec![],
         }
     }
 
@@ -42,6 +226,65 @@ impl Arborist {
         self.cap = cap;
     }
 
+    /// Stops searching deeper once `budget` has passed, starting the clock now.
+    /// The shallowest search always runs to completion so that a move can be chosen.
+    pub fn budgeted(&mut self, budget: Duration) {
+        self.budget = Some((Instant::now(), budget));
+    }
+
+    /// Shares the first layer of moves between `threads` threads, which only
+    /// takes effect with the `parallel` feature on. Threaded searches evaluate
+    /// more boards to reach the same depth, as each move is searched without
+    /// the bounds found by the others.
+    pub fn threaded(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Keeps `progress` up to date as the search runs
+    pub fn reporting(&mut self, progress: Arc<SearchProgress>) {
+        self.progress = Some(progress);
+    }
+
+    /// Only searches `share` of the first layer of moves, keeping what it finds at each depth
+    /// so that it can be combined with the other shares through `share_result`
+    pub fn sharing(&mut self, share: SearchShare) {
+        self.share = Some(share);
+    }
+
+    /// What this search found in its share, once it has chosen its move
+    pub fn share_result(&mut self, chosen: PlayerMessage) -> ShareResult {
+        ShareResult {
+            chosen,
+            depths: std::mem::take(&mut self.share_depths),
+        }
+    }
+
+    fn reached(&mut self, depth: usize, found: &(BoardScore, Option<(Coordinate, char)>)) {
+        if let Some(progress) = &self.progress {
+            progress.reached(depth);
+        }
+        if self.share.is_some() {
+            self.share_depths.push(found.clone());
+        }
+    }
+
+    /// An arborist for one thread of a threaded search, counting towards the same cap and budget
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    fn worker(&self, shared: Arc<AtomicUsize>) -> Self {
+        Self {
+            assessed: 0,
+            prune: self.prune,
+            cap: self.cap,
+            budget: self.budget,
+            has_fallback: self.has_fallback,
+            threads: 1,
+            shared: Some(shared),
+            progress: self.progress.clone(),
+            share: None,
+            share_depths: vec![],
+        }
+    }
+
     /// Useful for testing, dead in production code
     #[allow(dead_code)]
     fn exhaustive() -> Self {
@@ -49,6 +292,13 @@ impl Arborist {
             assessed: 0,
             prune: false,
             cap: std::usize::MAX,
+            budget: None,
+            has_fallback: false,
+            threads: 1,
+            shared: None,
+            progress: None,
+            share: None,
+            share_depths: vec![],
         }
     }
 
@@ -57,7 +307,78 @@ impl Arborist {
     }
 
     fn tick(&mut self) {
-        self.assessed += 1
+        self.assessed += 1;
+        if let Some(shared) = &self.shared {
+            shared.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(progress) = &self.progress {
+            progress.assessed_one();
+        }
+    }
+
+    fn total_assessed(&self) -> usize {
+        match &self.shared {
+            Some(shared) => shared.load(Ordering::Relaxed),
+            None => self.assessed,
+        }
+    }
+
+    fn out_of_time(&self) -> bool {
+        self.has_fallback
+            && self
+                .budget
+                .is_some_and(|(started, budget)| started.elapsed() > budget)
+    }
+
+    fn interrupted(&self) -> bool {
+        self.total_assessed() > self.cap || self.out_of_time()
+    }
+}
+
+/// How many separately locked parts the transposition table is split into,
+/// so that the threads of a threaded search rarely wait on one another
+const TRANSPOSITION_SHARDS: usize = 16;
+
+/// A position's `Board::zobrist` hash, the depth, the player evaluated for, and any winner
+type TranspositionKey = (u64, usize, usize, Option<usize>);
+
+/// Evaluations at the bottom of the search, shared by every thread searching the same move
+pub struct TranspositionTable {
+    shards: Vec<Mutex<HashMap<TranspositionKey, BoardScore, xxh3::Xxh3Builder>>>,
+}
+
+impl TranspositionTable {
+    fn new() -> Self {
+        Self {
+            shards: (0..TRANSPOSITION_SHARDS)
+                .map(|_| Mutex::new(HashMap::with_hasher(xxh3::Xxh3Builder::new())))
+                .collect(),
+        }
+    }
+
+    fn shard(
+        &self,
+        key: &TranspositionKey,
+    ) -> std::sync::MutexGuard<'_, HashMap<TranspositionKey, BoardScore, xxh3::Xxh3Builder>> {
+        self.shards[key.0 as usize % self.shards.len()]
+            .lock()
+            .unwrap()
+    }
+
+    fn get(&self, key: &TranspositionKey) -> Option<BoardScore> {
+        self.shard(key).get(key).cloned()
+    }
+
+    fn insert(&self, key: TranspositionKey, score: BoardScore) {
+        self.shard(&key).insert(key, score);
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|s| s.lock().unwrap().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
@@ -65,6 +386,7 @@ pub struct Caches {
     cached_floods: HashMap<Vec<u64>, (BoardDistances, BoardDistances), xxh3::Xxh3Builder>,
     cached_scores: HashMap<(Coordinate, char, usize), usize, xxh3::Xxh3Builder>,
     cached_words: HashMap<String, bool, xxh3::Xxh3Builder>,
+    transpositions: Arc<TranspositionTable>,
 }
 
 impl Caches {
@@ -73,6 +395,15 @@ impl Caches {
             cached_floods: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
             cached_scores: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
             cached_words: HashMap::with_hasher(xxh3::Xxh3Builder::new()),
+            transpositions: Arc::new(TranspositionTable::new()),
+        }
+    }
+
+    /// Empty caches for another thread of the same search, sharing only the transposition table
+    pub fn sharing_transpositions(&self) -> Self {
+        Self {
+            transpositions: self.transpositions.clone(),
+            ..Self::new()
         }
     }
 }
@@ -91,6 +422,54 @@ impl Game {
             .next_player
             .expect("Minimax only works in non-periodic playmodes");
 
+        if npc_params.opening_book {
+            if let Some(book_move) = OpeningBook::shipped().suggest(game) {
+                if log {
+                    println!("Bot played {book_move} from its opening book");
+                }
+                return (book_move, BoardScore::default());
+            }
+        }
+
+        if npc_params.blunder_rate > 0.0 {
+            let mut rng = TruncateRng::new(game.seed())
+                .fork(BLUNDER_STREAM)
+                .fork(game.turn_count as u64);
+            let roll = rng.rand_u32() as f32 / u32::MAX as f32;
+            let possible_moves = if roll < npc_params.blunder_rate {
+                game.possible_moves()
+            } else {
+                vec![]
+            };
+            if !possible_moves.is_empty() {
+                let pick = rng.rand_range(0..possible_moves.len() as u32);
+                let (position, tile) = possible_moves[pick as usize];
+                if log {
+                    println!("Bot blundered into playing {tile} at {position}");
+                }
+                return (PlayerMessage::Place(position, tile), BoardScore::default());
+            }
+        }
+
+        // With nothing left to draw, small enough endgames can be solved outright rather than estimated
+        if game.in_endgame() && game.players.len() == 2 {
+            let mut dictionaries = [opponent_dictionary; 2];
+            dictionaries[evaluation_player] = self_dictionary;
+            let mut solver = EndgameSolver::new(EndgameLimits::default(), dictionaries);
+            if let Some(winning_move) = solver.winning_move(game) {
+                if log {
+                    println!("Bot found a forced win in the endgame: {winning_move}");
+                }
+                return (winning_move, BoardScore::default());
+            }
+        }
+
+        // Every board searched reads words off the board many times over,
+        // so they all carry along an index of the words
+        let mut indexed = game.clone();
+        indexed.board.index_words();
+        let game = &indexed;
+
         let mut internal_arborist = if npc_params.pruning {
             Arborist::pruning()
         } else {
@@ -99,6 +478,19 @@ impl Game {
         let mut caches = Caches::new();
 
         let mut run_mini = |partial_depth: usize, arborist: &mut Arborist| {
+            #[cfg(feature = "parallel")]
+            if arborist.threads > 1 {
+                return Game::threaded_minimax(
+                    game,
+                    self_dictionary,
+                    opponent_dictionary,
+                    partial_depth,
+                    arborist,
+                    &mut caches,
+                    npc_params,
+                );
+            }
+
             Game::minimax(
                 game.clone(),
                 self_dictionary,
@@ -120,19 +512,22 @@ impl Game {
 
         let arborist = counter.unwrap_or_else(|| &mut internal_arborist);
         for d in 1..depth {
-            let maybelatest = Some(run_mini(d, arborist));
+            let found = run_mini(d, arborist);
 
-            if arborist.assessed > arborist.cap {
+            if arborist.interrupted() {
                 break;
             }
-            latest = maybelatest;
+            arborist.reached(d, &found);
+            latest = Some(found);
             looked = d;
+            arborist.has_fallback = true;
         }
 
-        if arborist.assessed < arborist.cap {
-            let maybelatest = Some(run_mini(depth, arborist));
-            if arborist.assessed < arborist.cap {
-                latest = maybelatest;
+        if arborist.assessed < arborist.cap && !arborist.out_of_time() {
+            let found = run_mini(depth, arborist);
+            if arborist.assessed < arborist.cap && !arborist.out_of_time() {
+                arborist.reached(depth, &found);
+                latest = Some(found);
                 looked = depth;
             }
         }
@@ -174,26 +569,22 @@ impl Game {
         game.instrument_unknown_game_state(for_player, total_depth, depth);
         let pruning = arborist.prune();
 
-        if depth == 0 || game.winner.is_some() {
+        if depth == 0 || game.is_over() {
             return (
-                game.static_eval(self_dictionary, for_player, depth, caches, npc_params),
+                game.transposed_eval(self_dictionary, for_player, depth, caches, npc_params),
                 None,
             );
         }
 
-        let mut possible_moves = game.possible_moves();
-        possible_moves.sort_by_cached_key(|(position, tile)| {
-            std::usize::MAX
-                - caches
-                    .cached_scores
-                    .get(&(*position, *tile, layer))
-                    .unwrap_or(&std::usize::MAX)
-        });
+        let mut possible_moves = game.ordered_moves(layer, caches, npc_params);
+        if let Some(share) = arborist.share.filter(|_| layer == 0) {
+            share.keep_own(&game, &mut possible_moves);
+        }
 
         let mut turn_score =
             |game: &Game, tile: char, position: Coordinate, alpha: BoardScore, beta: BoardScore| {
                 arborist.tick();
-                if arborist.assessed > arborist.cap {
+                if arborist.interrupted() {
                     return None;
                 }
                 let mut next_turn = game.clone();
@@ -305,6 +696,128 @@ impl Game {
         }
     }
 
+    /// Searches each of the first layer of moves on its own thread from the arborist's pool,
+    /// choosing between them in the same order as `minimax` would
+    #[cfg(feature = "parallel")]
+    fn threaded_minimax(
+        game: &Game,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        depth: usize,
+        arborist: &mut Arborist,
+        caches: &mut Caches,
+        npc_params: &NPCParams,
+    ) -> (BoardScore, Option<(Coordinate, char)>) {
+        use rayon::prelude::*;
+
+        let for_player = game
+            .next_player
+            .expect("Minimax only works in non-periodic playmodes");
+        let mut game = game.clone();
+        game.instrument_unknown_game_state(for_player, depth, depth);
+        if game.is_over() {
+            return (
+                game.transposed_eval(self_dictionary, for_player, depth, caches, npc_params),
+                None,
+            );
+        }
+
+        let mut possible_moves = game.ordered_moves(0, caches, npc_params);
+        if let Some(share) = arborist.share {
+            share.keep_own(&game, &mut possible_moves);
+        }
+        let shared = Arc::new(AtomicUsize::new(arborist.assessed));
+
+        let pool = search_pool(arborist.threads);
+        let scores: Vec<_> = pool.install(|| {
+            possible_moves
+                .par_iter()
+                .map(|(position, tile)| {
+                    let mut worker = arborist.worker(shared.clone());
+                    worker.tick();
+                    if worker.interrupted() {
+                        return None;
+                    }
+                    let mut worker_caches = caches.sharing_transpositions();
+
+                    let mut next_turn = game.clone();
+                    next_turn
+                        .play_turn(
+                            Move::Place {
+                                player: for_player,
+                                tile: *tile,
+                                position: *position,
+                            },
+                            self_dictionary,
+                            opponent_dictionary,
+                            Some(&mut worker_caches.cached_words),
+                        )
+                        .expect("Should be exploring valid turns");
+
+                    let (score, _) = Game::minimax(
+                        next_turn,
+                        self_dictionary,
+                        opponent_dictionary,
+                        depth,
+                        depth - 1,
+                        1,
+                        BoardScore::neg_inf(),
+                        BoardScore::inf(),
+                        for_player,
+                        &mut worker,
+                        &mut worker_caches,
+                        npc_params,
+                    );
+                    Some(score)
+                })
+                .collect()
+        });
+        arborist.assessed = shared.load(Ordering::Relaxed);
+
+        let mut max_score = BoardScore::neg_inf();
+        let mut relevant_move = None;
+        for ((position, tile), score) in possible_moves.into_iter().zip(scores) {
+            let Some(score) = score else {
+                continue;
+            };
+            caches
+                .cached_scores
+                .insert((position, tile, 0), score.usize_rank());
+            if score > max_score {
+                max_score = score;
+                relevant_move = Some((position, tile));
+            }
+        }
+
+        (max_score, relevant_move)
+    }
+
+    /// The moves to search at a layer, best first according to the previous, shallower search
+    fn ordered_moves(
+        &self,
+        layer: usize,
+        caches: &Caches,
+        npc_params: &NPCParams,
+    ) -> Vec<(Coordinate, char)> {
+        let mut possible_moves = self.possible_moves();
+        if layer == 0 && npc_params.seeded_ties {
+            // The first of the best moves found is played, so shuffling before the
+            // (stable) sort below picks between ties reproducibly for this game and turn
+            TruncateRng::new(self.seed())
+                .fork(NPC_STREAM)
+                .fork(self.turn_count as u64)
+                .shuffle(&mut possible_moves);
+        }
+        possible_moves.sort_by_cached_key(|(position, tile)| {
+            usize::MAX
+                - caches
+                    .cached_scores
+                    .get(&(*position, *tile, layer))
+                    .unwrap_or(&usize::MAX)
+        });
+        possible_moves
+    }
+
     fn possible_moves(&self) -> Vec<(Coordinate, char)> {
         let mut playable_tiles: Vec<_> = self
             .players
@@ -370,7 +883,7 @@ impl Game {
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct WordQualityScores {
     word_length: f32,
     word_validity: f32,
@@ -397,6 +910,26 @@ pub enum DefenceEvalType {
 // Evaluation functions
 impl Game {
     /// Top-most evaluation function for looking at the game and calculating a score
+    /// Evaluates a position at the bottom of the search, reusing the evaluation of
+    /// the same position when the search reaches it again through another order of moves
+    fn transposed_eval(
+        &self,
+        external_dictionary: Option<&WordDict>,
+        for_player: usize,
+        depth: usize,
+        caches: &mut Caches,
+        npc_params: &NPCParams,
+    ) -> BoardScore {
+        let key = (self.board.zobrist(), depth, for_player, self.winner);
+        if let Some(score) = caches.transpositions.get(&key) {
+            return score;
+        }
+
+        let score = self.static_eval(external_dictionary, for_player, depth, caches, npc_params);
+        caches.transpositions.insert(key, score.clone());
+        score
+    }
+
     pub fn static_eval(
         &self,
         external_dictionary: Option<&WordDict>,
@@ -540,49 +1073,31 @@ impl Game {
         player: usize,
         caches: &mut Caches,
     ) -> WordQualityScores {
-        let mut assessed_tiles: HashSet<Coordinate> = HashSet::new();
-        let mut num_words = 0;
-
         let mut word_scores = WordQualityScores::default();
 
-        for (y, row) in self.board.squares.iter().enumerate() {
-            for (x, sq) in row.iter().enumerate() {
-                if matches!(sq, Square::Occupied{ player: p, ..} if player == *p) {
-                    if assessed_tiles.contains(&Coordinate { x, y }) {
-                        continue;
-                    }
+        let word_coords = self.board.words_of_player(player);
+        let words = self
+            .board
+            .word_strings(&word_coords)
+            .expect("There should be words from a tile");
+        let num_words = words.len();
 
-                    let word_coords = self.board.get_words(Coordinate { x, y });
-                    assessed_tiles.extend(word_coords.iter().flatten());
-
-                    let words = self
-                        .board
-                        .word_strings(&word_coords)
-                        .expect("There should be words from a tile");
-
-                    num_words += words.len();
-                    for word in words {
-                        let resolved = self.judge.valid(
-                            word,
-                            &crate::rules::WinCondition::Elimination,
-                            Some(external_dictionary),
-                            None,
-                            &mut Some(&mut caches.cached_words),
-                        );
-                        if let Some(resolved_word) = resolved {
-                            if let Some(word_data) =
-                                external_dictionary.get(&resolved_word.to_lowercase())
-                            {
-                                word_scores.word_length +=
-                                    (((resolved_word.len() - 1) as f32) / 5.0).min(1.0);
-
-                                word_scores.word_extensibility +=
-                                    (word_data.extensions as f32).sqrt().min(100.0) / 100.0;
-
-                                word_scores.word_validity += 1.0;
-                            }
-                        }
-                    }
+        for word in words {
+            let resolved = self.judge.valid(
+                word,
+                &crate::rules::WinCondition::Elimination,
+                Some(external_dictionary),
+                None,
+                &mut Some(&mut caches.cached_words),
+            );
+            if let Some(resolved_word) = resolved {
+                if let Some(word_data) = external_dictionary.get(&resolved_word.to_lowercase()) {
+                    word_scores.word_length += (((resolved_word.len() - 1) as f32) / 5.0).min(1.0);
+
+                    word_scores.word_extensibility +=
+                        (word_data.extensions as f32).sqrt().min(100.0) / 100.0;
+
+                    word_scores.word_validity += 1.0;
                 }
             }
         }
@@ -605,7 +1120,7 @@ mod tests {
 
     /// Build an (expensive) word dictionary using the real game data.
     fn dict() -> WordDict {
-        let mut valid_words = HashMap::new();
+        let mut valid_words = WordDict::new();
         let lines = TESTING_DICT.lines();
 
         for line in lines {
@@ -814,6 +1329,54 @@ mod tests {
         });
     }
 
+    #[test]
+    fn reuses_evaluations_of_transposed_positions() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ ~~ |0 ~~ ~~ ~~
+            __ __ S0 O0 __ __ __
+            __ __ T0 __ __ __ __
+            __ __ R0 __ __ __ __
+            __ __ __ T1 __ __ __
+            __ __ __ A1 __ __ __
+            __ __ __ R1 A1 T1 __
+            ~~ ~~ ~~ |1 ~~ ~~ ~~
+            "###,
+            "A",
+        );
+        let first = Coordinate { x: 5, y: 5 };
+        let second = Coordinate { x: 6, y: 6 };
+
+        let mut one_way = game.clone();
+        one_way.board.set(first, 1, 'A', None).unwrap();
+        one_way.board.set(second, 1, 'E', None).unwrap();
+        let mut other_way = game.clone();
+        other_way.board.set(second, 1, 'E', None).unwrap();
+        other_way.board.set(first, 1, 'A', None).unwrap();
+
+        let params = NPCParams::default();
+        let mut caches = Caches::new();
+        let score = one_way.transposed_eval(Some(&dict), 1, 0, &mut caches, &params);
+        assert_eq!(caches.transpositions.len(), 1);
+
+        let transposed = other_way.transposed_eval(Some(&dict), 1, 0, &mut caches, &params);
+        assert_eq!(caches.transpositions.len(), 1);
+        assert!(transposed == score);
+        assert!(
+            transposed == other_way.static_eval(Some(&dict), 1, 0, &mut Caches::new(), &params)
+        );
+
+        // The same board further from the search's horizon scores differently
+        one_way.transposed_eval(Some(&dict), 1, 1, &mut caches, &params);
+        assert_eq!(caches.transpositions.len(), 2);
+
+        // Other threads of the same search reuse what has been evaluated
+        let mut worker = caches.sharing_transpositions();
+        other_way.transposed_eval(Some(&dict), 1, 1, &mut worker, &params);
+        assert_eq!(caches.transpositions.len(), 2);
+    }
+
     #[test]
     fn defense_scoring_tests() {
         let game_a = test_game(
@@ -963,6 +1526,277 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_npc_seeded_ties() {
+        let dict = dict();
+
+        let eval = |seed| {
+            let mut game = test_game(
+                r###"
+                ~~ ~~ |0 ~~
+                ~~ S0 O0 ~~
+                ~~ T0 A0 Y0
+                ~~ A0 ~~ ~~
+                ~~ R0 __ ~~
+                ~~ __ A1 |1
+                ~~ ~~ |1 ~~
+                ~~ ~~ ~~ ~~
+                "###,
+                "XZF",
+            );
+            game.bag = TileBag::latest(Some(seed)).1;
+            let (best_move, _) = Game::best_move(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                2,
+                None,
+                false,
+                &NPCParams {
+                    seeded_ties: true,
+                    ..NPCParams::default()
+                },
+            );
+
+            best_move
+        };
+
+        for seed in 0..10 {
+            let base = eval(seed);
+            assert!(matches!(base, PlayerMessage::Place(..)));
+            for _ in 0..20 {
+                assert_eq!(eval(seed), base);
+            }
+        }
+    }
+
+    #[test]
+    fn test_npc_blunders() {
+        let dict = dict();
+
+        let eval = |seed, blunder_rate| {
+            let mut game = test_game(
+                r###"
+                ~~ ~~ |0 ~~
+                ~~ S0 O0 ~~
+                ~~ T0 A0 Y0
+                ~~ A0 ~~ ~~
+                ~~ R0 __ ~~
+                ~~ __ A1 |1
+                ~~ ~~ |1 ~~
+                ~~ ~~ ~~ ~~
+                "###,
+                "XZF",
+            );
+            game.bag = TileBag::latest(Some(seed)).1;
+            let (best_move, _) = Game::best_move(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                2,
+                None,
+                false,
+                &NPCParams {
+                    blunder_rate,
+                    ..NPCParams::default()
+                },
+            );
+
+            (best_move, game.possible_moves())
+        };
+
+        let mut blunders = 0;
+        for seed in 0..10 {
+            let (best, _) = eval(seed, 0.0);
+            let (blunder, possible) = eval(seed, 1.0);
+            let PlayerMessage::Place(position, tile) = blunder else {
+                panic!("Blunders should still be placements");
+            };
+            assert!(possible.contains(&(position, tile)));
+            assert_eq!(eval(seed, 1.0).0, blunder);
+            if blunder != best {
+                blunders += 1;
+            }
+        }
+        assert!(blunders > 0, "Blundering should stray from the best move");
+    }
+
+    #[test]
+    fn test_npc_budget() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            ~~ ~~ ~~ ~~
+            "###,
+            "XZF",
+        );
+
+        let best_at = |depth: usize, budget: Option<Duration>| {
+            let mut arborist = Arborist::pruning();
+            if let Some(budget) = budget {
+                arborist.budgeted(budget);
+            }
+            let (best_move, _) = Game::best_move(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                depth,
+                Some(&mut arborist),
+                false,
+                &NPCParams::default(),
+            );
+            best_move
+        };
+
+        // With no time at all, the bot still finishes its shallowest search
+        assert_eq!(best_at(3, Some(Duration::ZERO)), best_at(1, None));
+        assert_eq!(best_at(3, Some(Duration::from_secs(600))), best_at(3, None));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_npc_threaded() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            ~~ ~~ ~~ ~~
+            "###,
+            "XZF",
+        );
+
+        let best_with = |threads: usize| {
+            let mut arborist = Arborist::pruning();
+            arborist.threaded(threads);
+            Game::best_move(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                3,
+                Some(&mut arborist),
+                false,
+                &NPCParams::default(),
+            )
+        };
+
+        // Equally good moves can be found in a different order, but never a worse one
+        let (_, single_score) = best_with(1);
+        let (threaded_move, threaded_score) = best_with(4);
+        assert!(matches!(threaded_move, PlayerMessage::Place(..)));
+        assert_eq!(threaded_score.usize_rank(), single_score.usize_rank());
+    }
+
+    #[test]
+    fn test_npc_shared() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            ~~ ~~ ~~ ~~
+            "###,
+            "XZF",
+        );
+
+        let share_of = |share: SearchShare| {
+            let mut arborist = Arborist::pruning();
+            arborist.sharing(share);
+            let (chosen, _) = Game::best_move(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                3,
+                Some(&mut arborist),
+                false,
+                &NPCParams::default(),
+            );
+            arborist.share_result(chosen)
+        };
+
+        let (whole_move, whole_score) = Game::best_move(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            3,
+            Some(&mut Arborist::pruning()),
+            false,
+            &NPCParams::default(),
+        );
+        assert_eq!(
+            ShareResult::combine(&[share_of(SearchShare::whole())]),
+            Some(whole_move)
+        );
+
+        // Equally good moves can be found in a different order, but never a worse one
+        let shares: Vec<_> = (0..3)
+            .map(|index| share_of(SearchShare { index, of: 3 }))
+            .collect();
+        let combined = ShareResult::combine(&shares);
+        assert!(matches!(combined, Some(PlayerMessage::Place(..))));
+        let best_shared = shares
+            .iter()
+            .map(|share| share.depths.last().unwrap().0.usize_rank())
+            .max();
+        assert_eq!(best_shared, Some(whole_score.usize_rank()));
+    }
+
+    #[test]
+    fn test_npc_reports_progress() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~
+            ~~ S0 O0 ~~
+            ~~ T0 A0 Y0
+            ~~ A0 ~~ ~~
+            ~~ R0 __ ~~
+            ~~ __ A1 |1
+            ~~ ~~ |1 ~~
+            ~~ ~~ ~~ ~~
+            "###,
+            "XZF",
+        );
+
+        let progress = Arc::new(SearchProgress::new());
+        let mut arborist = Arborist::pruning();
+        arborist.reporting(progress.clone());
+        Game::best_move(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            3,
+            Some(&mut arborist),
+            false,
+            &NPCParams::default(),
+        );
+
+        assert_eq!(
+            progress.report(),
+            SearchReport {
+                assessed: arborist.assessed(),
+                depth: 3
+            }
+        );
+    }
+
     #[test]
     fn generic_npc_tests() {
         let dict = dict();
@@ -1120,7 +1954,7 @@ mod tests {
                 insta::assert_snapshot!(result, @r###"
                 Evaluating:
                   - 1611 possible leaves
-                  - 481 after pruning
+                  - 473 after pruning
                   - Move: Place T at (1, 5)
 
                 ~~ ~~ |0 ~~ ~~
@@ -1161,7 +1995,7 @@ mod tests {
                 insta::assert_snapshot!(result, @r###"
                 Evaluating:
                   - 1656 possible leaves
-                  - 455 after pruning
+                  - 418 after pruning
                   - Move: Place E at (3, 6)
 
                 ~~ ~~ |0 ~~ ~~
@@ -1205,7 +2039,7 @@ mod tests {
                 insta::assert_snapshot!(result, @r###"
                 Evaluating:
                   - 13594 possible leaves
-                  - 1671 after pruning
+                  - 1630 after pruning
                   - Move: Place S at (3, 9)
 
                 ~~ ~~ |0 ~~ ~~ ~~ ~~
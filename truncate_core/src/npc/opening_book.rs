@@ -0,0 +1,549 @@
+use std::{collections::HashMap, fmt::Write, sync::OnceLock};
+
+use crate::{
+    board::{Board, Coordinate, Direction, Square, SquareValidity},
+    error::OpeningBookError,
+    game::Game,
+    messages::PlayerMessage,
+    moves::Move,
+};
+
+/// How many of each player's first placements the book covers
+pub const BOOK_TURNS: usize = 4;
+/// How far from the player's artifact the board is matched against the book.
+/// Openings rarely reach further than this before the book runs out.
+const BOOK_RADIUS: isize = 3;
+
+/// Regenerated from the replay archive by `truncate_server build-opening-book`
+static SHIPPED_BOOK: &str = include_str!("opening_book.txt");
+/// Regenerated from the NPC playing itself by `truncate_dueller build-opening-book`
+static SEARCHED_BOOK: &str = include_str!("searched_book.txt");
+static BOOK: OnceLock<OpeningBook> = OnceLock::new();
+
+/// A neighbourhood key, and a square's offset from the player's artifact
+type BookSquare = (String, (isize, isize));
+
+/// A placement that has done well from a given neighbourhood
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookMove {
+    /// Where to play, relative to the player's artifact as seen from their side of the board
+    pub offset: (isize, isize),
+    /// Letters that won from this square, best first
+    pub letters: Vec<char>,
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl BookMove {
+    /// Orders moves by how often they won, then by how often they were seen
+    fn cmp_strength(&self, other: &Self) -> std::cmp::Ordering {
+        let win_rate = |m: &Self, against: &Self| m.wins as u64 * against.games as u64;
+        win_rate(other, self)
+            .cmp(&win_rate(self, other))
+            .then(other.games.cmp(&self.games))
+            .then(self.offset.cmp(&other.offset))
+    }
+}
+
+/// Strong early placements for each kind of neighbourhood around a player's artifact,
+/// mined from finished online games.
+///
+/// Neighbourhoods are described from the player's side of the board, so that
+/// openings learned on one side of a board apply to the other, and to any board
+/// with the same shape around the artifact.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpeningBook {
+    /// Moves for each neighbourhood, best first
+    lines: HashMap<String, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    /// The book built into this release, combining what players and the NPC have learned
+    pub fn shipped() -> &'static OpeningBook {
+        BOOK.get_or_init(|| {
+            let mut book =
+                OpeningBook::parse(SHIPPED_BOOK).expect("Shipped opening book should parse");
+            book.merge(
+                OpeningBook::parse(SEARCHED_BOOK).expect("Searched opening book should parse"),
+            );
+            book
+        })
+    }
+
+    /// Adds another book's moves to this one, pooling the games and letters of any shared square
+    pub fn merge(&mut self, other: OpeningBook) {
+        for (key, moves) in other.lines {
+            let ours = self.lines.entry(key).or_default();
+            for book_move in moves {
+                match ours.iter_mut().find(|m| m.offset == book_move.offset) {
+                    Some(existing) => {
+                        existing.games += book_move.games;
+                        existing.wins += book_move.wins;
+                        for letter in book_move.letters {
+                            if !existing.letters.contains(&letter) {
+                                existing.letters.push(letter);
+                            }
+                        }
+                    }
+                    None => ours.push(book_move),
+                }
+            }
+            ours.sort_by(BookMove::cmp_strength);
+        }
+    }
+
+    /// How many neighbourhoods the book has moves for
+    pub fn neighbourhoods(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Reads a book written by `to_text`, where each line is
+    /// `<neighbourhood> <x>,<y> <letters> <games> <wins>`
+    pub fn parse(text: &str) -> Result<Self, OpeningBookError> {
+        let mut lines: HashMap<String, Vec<BookMove>> = HashMap::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad_line = || OpeningBookError::MalformedLine { line: i + 1 };
+
+            let mut parts = line.split(' ');
+            let (Some(key), Some(offset), Some(letters), Some(games), Some(wins), None) = (
+                parts.next(),
+                parts.next(),
+                parts.next(),
+                parts.next(),
+                parts.next(),
+                parts.next(),
+            ) else {
+                return Err(bad_line());
+            };
+
+            let (x, y) = offset.split_once(',').ok_or_else(bad_line)?;
+            let book_move = BookMove {
+                offset: (
+                    x.parse().map_err(|_| bad_line())?,
+                    y.parse().map_err(|_| bad_line())?,
+                ),
+                letters: letters.chars().collect(),
+                games: games.parse().map_err(|_| bad_line())?,
+                wins: wins.parse().map_err(|_| bad_line())?,
+            };
+            lines.entry(key.to_string()).or_default().push(book_move);
+        }
+
+        for moves in lines.values_mut() {
+            moves.sort_by(BookMove::cmp_strength);
+        }
+
+        Ok(Self { lines })
+    }
+
+    /// Writes the book out in a stable order, so regenerating it gives a readable diff
+    pub fn to_text(&self) -> String {
+        let mut keys: Vec<_> = self.lines.keys().collect();
+        keys.sort();
+
+        let mut text = String::new();
+        for key in keys {
+            for book_move in &self.lines[key] {
+                let letters: String = book_move.letters.iter().collect();
+                _ = writeln!(
+                    text,
+                    "{key} {},{} {letters} {} {}",
+                    book_move.offset.0, book_move.offset.1, book_move.games, book_move.wins
+                );
+            }
+        }
+        text
+    }
+
+    /// The strongest book move the player to move can make, while their opening is still in the book
+    pub fn suggest(&self, game: &Game) -> Option<PlayerMessage> {
+        let player = game.next_player?;
+        if placed_tiles(&game.board, player) >= BOOK_TURNS {
+            return None;
+        }
+
+        let (artifact, orientation, key) = neighbourhood(&game.board, player)?;
+        let moves = self.lines.get(&key)?;
+        let playable = game.possible_moves();
+
+        for book_move in moves {
+            let Some(position) =
+                to_board(orientation, book_move.offset).and_then(|o| offset_from(artifact, o))
+            else {
+                continue;
+            };
+            if let Some(letter) = book_move
+                .letters
+                .iter()
+                .find(|letter| playable.contains(&(position, **letter)))
+            {
+                return Some(PlayerMessage::Place(position, *letter));
+            }
+        }
+
+        None
+    }
+}
+
+/// Tallies the early placements of finished games, to be built into an `OpeningBook`
+#[derive(Debug, Default)]
+pub struct OpeningBookBuilder {
+    /// Games played and won for each letter on each square of each neighbourhood
+    tallies: HashMap<(BookSquare, char), (u32, u32)>,
+    games: usize,
+}
+
+impl OpeningBookBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Games recorded so far
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    /// Replays the opening of a finished game from its starting board,
+    /// crediting each early placement with whether its player went on to win.
+    ///
+    /// Only the squares each player holds are tracked, not battles, so a game is
+    /// followed until a placement lands somewhere a battle must have changed.
+    pub fn record_game(&mut self, starting_board: &Board, moves: &[Move], winner: Option<usize>) {
+        let mut board = starting_board.clone();
+        let mut placed = vec![0; board.orientations.len()];
+        self.games += 1;
+
+        for game_move in moves {
+            // Swaps and passes don't change which squares are held
+            let &Move::Place {
+                player,
+                tile,
+                position,
+            } = game_move
+            else {
+                continue;
+            };
+            let Some(count) = placed.get_mut(player) else {
+                return;
+            };
+            if !matches!(board.get(position), Ok(Square::Land { .. })) {
+                return;
+            }
+
+            if *count < BOOK_TURNS {
+                if let Some((artifact, orientation, key)) = neighbourhood(&board, player) {
+                    let on_board = (
+                        position.x as isize - artifact.x as isize,
+                        position.y as isize - artifact.y as isize,
+                    );
+                    if let Some(offset) = from_board(orientation, on_board) {
+                        let tally = self.tallies.entry(((key, offset), tile)).or_default();
+                        tally.0 += 1;
+                        if winner == Some(player) {
+                            tally.1 += 1;
+                        }
+                    }
+                }
+                *count += 1;
+            }
+
+            if placed.iter().all(|count| *count >= BOOK_TURNS) {
+                return;
+            }
+            _ = board.set_square(
+                position,
+                Square::Occupied {
+                    player,
+                    tile,
+                    validity: SquareValidity::Unknown,
+                    foggy: false,
+                },
+            );
+        }
+    }
+
+    /// Keeps the squares seen in at least `min_games` games that won at least `min_win_rate` of them
+    pub fn build(self, min_games: u32, min_win_rate: f32) -> OpeningBook {
+        let mut squares: HashMap<BookSquare, Vec<(char, u32, u32)>> = HashMap::new();
+        for ((square, letter), (games, wins)) in self.tallies {
+            squares
+                .entry(square)
+                .or_default()
+                .push((letter, games, wins));
+        }
+
+        let mut lines: HashMap<String, Vec<BookMove>> = HashMap::new();
+        for ((key, offset), mut letters) in squares {
+            let games: u32 = letters.iter().map(|(_, games, _)| games).sum();
+            let wins: u32 = letters.iter().map(|(_, _, wins)| wins).sum();
+            if games < min_games || (wins as f32 / games as f32) < min_win_rate {
+                continue;
+            }
+
+            letters.retain(|(_, _, wins)| *wins > 0);
+            letters.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)).then(a.0.cmp(&b.0)));
+            lines.entry(key).or_default().push(BookMove {
+                offset,
+                letters: letters.into_iter().map(|(letter, ..)| letter).collect(),
+                games,
+                wins,
+            });
+        }
+
+        for moves in lines.values_mut() {
+            moves.sort_by(BookMove::cmp_strength);
+        }
+
+        OpeningBook { lines }
+    }
+}
+
+/// Turns an offset seen from the player's side of the board into one on the board itself
+fn to_board(orientation: Direction, (x, y): (isize, isize)) -> Option<(isize, isize)> {
+    match orientation {
+        Direction::South => Some((x, y)),
+        Direction::North => Some((-x, -y)),
+        Direction::East => Some((y, -x)),
+        Direction::West => Some((-y, x)),
+        _ => None,
+    }
+}
+
+/// Turns an offset on the board into one seen from the player's side of the board
+fn from_board(orientation: Direction, (x, y): (isize, isize)) -> Option<(isize, isize)> {
+    match orientation {
+        Direction::South => Some((x, y)),
+        Direction::North => Some((-x, -y)),
+        Direction::East => Some((-y, x)),
+        Direction::West => Some((y, -x)),
+        _ => None,
+    }
+}
+
+fn offset_from(origin: Coordinate, (x, y): (isize, isize)) -> Option<Coordinate> {
+    let x = usize::try_from(origin.x as isize + x).ok()?;
+    let y = usize::try_from(origin.y as isize + y).ok()?;
+    Some(Coordinate::new(x, y))
+}
+
+fn placed_tiles(board: &Board, player: usize) -> usize {
+    board
+        .squares
+        .iter()
+        .flatten()
+        .filter(|sq| matches!(sq, Square::Occupied { player: p, .. } if *p == player))
+        .count()
+}
+
+/// The player's artifact, their side of the board, and the squares around the artifact
+/// as a key into the book, read row by row from the player's side of the board
+fn neighbourhood(board: &Board, player: usize) -> Option<(Coordinate, Direction, String)> {
+    let artifact = board.artifacts.iter().copied().find(
+        |coord| matches!(board.get(*coord), Ok(Square::Artifact { player: p, .. }) if p == player),
+    )?;
+    let orientation = *board.orientations.get(player)?;
+
+    let mut key = String::new();
+    for y in -BOOK_RADIUS..=BOOK_RADIUS {
+        for x in -BOOK_RADIUS..=BOOK_RADIUS {
+            let square = to_board(orientation, (x, y))
+                .and_then(|o| offset_from(artifact, o))
+                .and_then(|coord| board.get(coord).ok());
+            let ours = |p: usize| p == player;
+            key.push(match square {
+                None | Some(Square::Water { .. }) => '~',
+                Some(Square::Fog {}) => '?',
+                Some(Square::Land { .. }) => '.',
+                Some(Square::Obelisk { .. }) => 'o',
+                Some(Square::Town { player: p, .. }) if ours(p) => 'T',
+                Some(Square::Town { .. }) => 't',
+                Some(Square::Artifact { player: p, .. }) if ours(p) => 'A',
+                Some(Square::Artifact { .. }) => 'a',
+                Some(Square::Occupied { player: p, .. }) if ours(p) => 'X',
+                Some(Square::Occupied { .. }) => 'x',
+            });
+        }
+    }
+
+    Some((artifact, orientation, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bag::TileBag,
+        npc::opening_search::{default_boards, self_play_game},
+        player::{Hand, Player},
+        rules::GameRules,
+    };
+
+    fn place(player: usize, tile: char, x: usize, y: usize) -> Move {
+        Move::Place {
+            player,
+            tile,
+            position: Coordinate::new(x, y),
+        }
+    }
+
+    fn game_on(board: Board, next_player: usize, hand: &str) -> Game {
+        let mut game = Game::new(9, 9, None, GameRules::generation(0));
+        let mut bag = TileBag::latest(None).1;
+        game.board = board;
+        game.players = (0..2)
+            .map(|i| {
+                let mut player = Player::new(format!("p{i}"), i, 0, &mut bag, None, (0, 0, 0));
+                player.hand = Hand(hand.chars().collect());
+                player
+            })
+            .collect();
+        game.player_turn_count = vec![0; 2];
+        game.next_player = Some(next_player);
+        game
+    }
+
+    #[test]
+    fn learns_winning_openings_from_both_sides() {
+        let board = Board::new(9, 9);
+        let south_artifact = Coordinate::new(1, board.height() - 2);
+        let north_artifact = Coordinate::new(board.width() - 2, 1);
+
+        let mut builder = OpeningBookBuilder::new();
+        // The south player wins by opening above their artifact
+        for _ in 0..3 {
+            builder.record_game(
+                &board,
+                &[place(1, 'E', south_artifact.x, south_artifact.y - 1)],
+                Some(1),
+            );
+        }
+        // The north player wins by opening below theirs, the same square from their side
+        builder.record_game(
+            &board,
+            &[place(0, 'A', north_artifact.x, north_artifact.y + 1)],
+            Some(0),
+        );
+        // Opening beside the artifact only ever loses
+        builder.record_game(
+            &board,
+            &[place(1, 'S', south_artifact.x + 1, south_artifact.y)],
+            Some(0),
+        );
+        assert_eq!(builder.games(), 5);
+
+        let book = builder.build(2, 0.5);
+        assert_eq!(book.neighbourhoods(), 1);
+
+        let reread = OpeningBook::parse(&book.to_text()).unwrap();
+        assert_eq!(reread, book);
+
+        let suggest = |next_player: usize, hand: &str| {
+            book.suggest(&game_on(board.clone(), next_player, hand))
+        };
+        assert_eq!(
+            suggest(1, "SAEQ"),
+            Some(PlayerMessage::Place(
+                Coordinate::new(south_artifact.x, south_artifact.y - 1),
+                'E'
+            ))
+        );
+        assert_eq!(
+            suggest(0, "AS"),
+            Some(PlayerMessage::Place(
+                Coordinate::new(north_artifact.x, north_artifact.y + 1),
+                'A'
+            ))
+        );
+        // Nothing in the book can be played from this hand
+        assert_eq!(suggest(1, "SQ"), None);
+    }
+
+    #[test]
+    fn leaves_the_book_after_the_opening() {
+        let mut board = Board::new(9, 9);
+        let artifact = Coordinate::new(1, board.height() - 2);
+
+        let mut builder = OpeningBookBuilder::new();
+        for _ in 0..2 {
+            builder.record_game(
+                &board,
+                &[place(1, 'E', artifact.x, artifact.y - 1)],
+                Some(1),
+            );
+        }
+        let book = builder.build(1, 0.5);
+
+        for i in 0..BOOK_TURNS {
+            board
+                .set_square(
+                    Coordinate::new(3 + i, 3),
+                    Square::Occupied {
+                        player: 1,
+                        tile: 'X',
+                        validity: SquareValidity::Unknown,
+                        foggy: false,
+                    },
+                )
+                .unwrap();
+        }
+        assert_eq!(book.suggest(&game_on(board, 1, "E")), None);
+    }
+
+    #[test]
+    fn merges_books_by_square() {
+        let board = Board::new(9, 9);
+        let artifact = Coordinate::new(1, board.height() - 2);
+        let book_of = |tile: char, x: usize, wins: bool| {
+            let mut builder = OpeningBookBuilder::new();
+            builder.record_game(
+                &board,
+                &[place(1, tile, x, artifact.y - 1)],
+                Some(if wins { 1 } else { 0 }),
+            );
+            builder.build(1, 0.0)
+        };
+
+        let mut book = book_of('E', artifact.x, true);
+        book.merge(book_of('A', artifact.x, true));
+        book.merge(book_of('S', artifact.x + 1, false));
+        assert_eq!(book.neighbourhoods(), 1);
+
+        let moves = book.lines.values().next().unwrap();
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[0].letters, vec!['E', 'A']);
+        assert_eq!((moves[0].games, moves[0].wins), (2, 2));
+        assert_eq!((moves[1].games, moves[1].wins), (1, 0));
+    }
+
+    #[test]
+    fn shipped_book_parses() {
+        OpeningBook::shipped();
+    }
+
+    #[test]
+    fn searched_book_opens_on_searched_boards() {
+        let book = OpeningBook::parse(SEARCHED_BOOK).unwrap();
+        assert!(book.neighbourhoods() > 0);
+
+        // Deal the same hands the book was searched with, on the boards it was searched on
+        let rules = GameRules::latest(None).1;
+        let opened = default_boards(40)
+            .iter()
+            .filter(|(board_seed, board)| {
+                (0..8).any(|game| {
+                    let game = self_play_game(board, board_seed * 1000 + game, rules.clone());
+                    book.suggest(&game).is_some()
+                })
+            })
+            .count();
+        assert!(
+            opened > 0,
+            "The book should open on some of the boards it was searched on"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Precomputes openings by having the NPC play itself on the boards players see most,
+//! so the opening book covers them before the replay archive has seen enough games.
+
+use crate::{
+    board::Board,
+    game::Game,
+    generation::{generate_board, BoardSeed},
+    judge::WordDict,
+    messages::PlayerMessage,
+    moves::Move,
+    rules::GameRules,
+};
+
+use super::{scoring::NPCParams, Arborist};
+
+/// Games longer than this are scored as a draw rather than played to the end
+const MAX_SEARCH_TURNS: u32 = 200;
+
+/// The boards the searched book is built for: the lobby's default board,
+/// then generated boards from the latest board generation
+pub fn default_boards(generated: u32) -> Vec<(u32, Board)> {
+    let mut boards = vec![(0, Board::new(9, 9))];
+    for seed in 0..generated {
+        let Ok(mut generated) = generate_board(BoardSeed::new(seed)) else {
+            continue;
+        };
+        generated.board.cache_special_squares();
+        boards.push((seed, generated.board));
+    }
+    boards
+}
+
+/// Has the NPC play itself from a starting board, returning every move played and the winner,
+/// ready to be recorded by an `OpeningBookBuilder`. Each seed draws different hands,
+/// so the same board yields a range of openings.
+///
+/// The opening book is turned off while searching, so the book only learns from
+/// openings the NPC found for itself.
+pub fn self_play(
+    board: &Board,
+    seed: u32,
+    rules: GameRules,
+    dict: &WordDict,
+    npc_params: &NPCParams,
+) -> (Vec<Move>, Option<usize>) {
+    let npc_params = NPCParams {
+        blunder_rate: 0.0,
+        ..*npc_params
+    };
+
+    play_between(board, seed, rules, dict, [&npc_params; 2])
+}
+
+/// Plays out a game between two NPCs, such as two rungs of the ladder, in the same way as `self_play`.
+/// Each keeps its own blunder rate, so weaker players make the mistakes that players make.
+pub fn play_between(
+    board: &Board,
+    seed: u32,
+    rules: GameRules,
+    dict: &WordDict,
+    players: [&NPCParams; 2],
+) -> (Vec<Move>, Option<usize>) {
+    let players = players.map(|npc_params| NPCParams {
+        opening_book: false,
+        ..*npc_params
+    });
+
+    let mut game = self_play_game(board, seed, rules);
+
+    let mut moves = vec![];
+    while game.turn_count < MAX_SEARCH_TURNS {
+        let Some(player) = game.next_player else {
+            break;
+        };
+        let npc_params = &players[player];
+
+        let mut arb = Arborist::pruning();
+        arb.capped(npc_params.evaluation_cap);
+        let (best_move, _) = Game::best_move(
+            &game,
+            Some(dict),
+            Some(dict),
+            npc_params.max_depth,
+            Some(&mut arb),
+            false,
+            npc_params,
+        );
+
+        let next_move = match best_move {
+            PlayerMessage::Place(position, tile) => Move::Place {
+                player,
+                tile,
+                position,
+            },
+            PlayerMessage::Swap(from, to) => Move::Swap {
+                player,
+                positions: [from, to],
+            },
+            _ => break,
+        };
+
+        moves.push(next_move.clone());
+        match game.play_turn(next_move, Some(dict), Some(dict), None) {
+            Ok(Some(winner)) => return (moves, Some(winner)),
+            // Drawn games are just as over, and have no moves left to search
+            Ok(None) if game.is_over() => break,
+            Ok(None) => {}
+            Err(_) => break,
+        }
+    }
+
+    (moves, None)
+}
+
+/// The game that `self_play` starts from, for replaying the moves it returns
+pub fn self_play_game(board: &Board, seed: u32, rules: GameRules) -> Game {
+    let mut game = Game::new(9, 9, Some(seed as u64), rules);
+    game.add_player("P1".into());
+    game.add_player("P2".into());
+    game.board = board.clone();
+    game.rules.battle_delay = 0;
+    game.start();
+    game
+}
@@ -0,0 +1,279 @@
+use std::{collections::BTreeSet, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::LadderWinError,
+    game::{Game, GAME_COLOR_BLUE, GAME_COLOR_PINK, GAME_COLOR_PURPLE, GAME_COLOR_YELLOW},
+    generation::{generate_board, BoardSeed},
+    judge::WordDict,
+    moves::Move,
+    rules::GameRules,
+};
+
+use super::scoring::NPCPersonality;
+
+/// Colours a player can choose for their own tiles, earned by climbing the NPC ladder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TileSkin {
+    Classic,
+    Moss,
+    Rose,
+    Amethyst,
+    Gold,
+}
+
+impl TileSkin {
+    pub fn color(&self) -> (u8, u8, u8) {
+        match self {
+            TileSkin::Classic => GAME_COLOR_BLUE,
+            TileSkin::Moss => (76, 160, 82),
+            TileSkin::Rose => GAME_COLOR_PINK,
+            TileSkin::Amethyst => GAME_COLOR_PURPLE,
+            TileSkin::Gold => GAME_COLOR_YELLOW,
+        }
+    }
+
+    pub fn from_id(id: impl AsRef<str>) -> Option<Self> {
+        match id.as_ref() {
+            "classic" => Some(TileSkin::Classic),
+            "moss" => Some(TileSkin::Moss),
+            "rose" => Some(TileSkin::Rose),
+            "amethyst" => Some(TileSkin::Amethyst),
+            "gold" => Some(TileSkin::Gold),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TileSkin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TileSkin::Classic => write!(f, "classic"),
+            TileSkin::Moss => write!(f, "moss"),
+            TileSkin::Rose => write!(f, "rose"),
+            TileSkin::Amethyst => write!(f, "amethyst"),
+            TileSkin::Gold => write!(f, "gold"),
+        }
+    }
+}
+
+/// One opponent on the ladder, and what beating them earns
+#[derive(Clone)]
+pub struct Rung {
+    pub title: &'static str,
+    pub personality: NPCPersonality,
+    pub reward: Option<TileSkin>,
+}
+
+/// Every opponent on the ladder, from easiest to hardest, with each searching deeper,
+/// knowing more words, and blundering less often than the last.
+/// Rungs are stored by index, and daily puzzles can be played against them,
+/// so new opponents should only ever be added to the end.
+pub fn rungs() -> Vec<Rung> {
+    vec![
+        Rung {
+            title: "The Beginner",
+            personality: NPCPersonality::pebble().blundering(0.3),
+            reward: None,
+        },
+        Rung {
+            title: "The Wordsmith",
+            personality: NPCPersonality::mellite().blundering(0.15),
+            reward: Some(TileSkin::Moss),
+        },
+        Rung {
+            title: "The Raider",
+            personality: NPCPersonality::flint().blundering(0.1),
+            reward: None,
+        },
+        Rung {
+            title: "The Fortress",
+            personality: NPCPersonality::basalt().blundering(0.05),
+            reward: Some(TileSkin::Rose),
+        },
+        Rung {
+            title: "The Strategist",
+            personality: NPCPersonality::jet(),
+            reward: Some(TileSkin::Amethyst),
+        },
+        Rung {
+            title: "The Champion",
+            personality: NPCPersonality::opal(),
+            reward: Some(TileSkin::Gold),
+        },
+    ]
+}
+
+/// The opponent for a daily puzzle, which is the usual one unless the puzzle was made for a rung of the ladder
+pub fn puzzle_opponent(rung: Option<u32>) -> NPCPersonality {
+    rung.and_then(|rung| rungs().into_iter().nth(rung as usize))
+        .map(|rung| rung.personality)
+        .unwrap_or_else(NPCPersonality::jet)
+}
+
+/// Which rungs of the NPC ladder a player has beaten
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NpcProgress {
+    pub beaten: BTreeSet<u32>,
+}
+
+impl NpcProgress {
+    /// The first rung is always open, and beating a rung opens the next
+    pub fn is_unlocked(&self, rung: u32) -> bool {
+        rung == 0 || self.beaten.contains(&(rung - 1))
+    }
+
+    /// Records a win against a rung, refusing rungs that don't exist or haven't been reached
+    pub fn record_win(&mut self, rung: u32) -> Result<(), LadderWinError> {
+        if rung as usize >= rungs().len() {
+            return Err(LadderWinError::NoSuchRung { rung });
+        }
+        if !self.is_unlocked(rung) {
+            return Err(LadderWinError::Locked);
+        }
+        self.beaten.insert(rung);
+        Ok(())
+    }
+
+    /// Combines progress from elsewhere, e.g. games played offline with those stored on the server
+    pub fn merge(&mut self, other: &NpcProgress) {
+        self.beaten.extend(other.beaten.iter().copied());
+    }
+
+    /// Every tile skin the player can use
+    pub fn skins(&self) -> Vec<TileSkin> {
+        let earned = rungs()
+            .into_iter()
+            .enumerate()
+            .filter(|(rung, _)| self.beaten.contains(&(*rung as u32)))
+            .filter_map(|(_, rung)| rung.reward);
+
+        std::iter::once(TileSkin::Classic).chain(earned).collect()
+    }
+}
+
+/// Replays a game against the ladder from its board, checking that every move is legal
+/// and that the human player won, so that wins sent to the server can't be made up
+pub fn replay_win(
+    board_seed: BoardSeed,
+    rules_generation: u32,
+    human_player: usize,
+    moves: &[Move],
+    dict: Option<&WordDict>,
+) -> Result<(), LadderWinError> {
+    if human_player > 1 {
+        return Err(LadderWinError::NoSuchPlayer {
+            player: human_player,
+        });
+    }
+    let Ok(generated) = generate_board(board_seed.clone()) else {
+        return Err(LadderWinError::BoardNotGenerated);
+    };
+    let mut board = generated.board;
+    board.cache_special_squares();
+
+    // Set up the same way as a single player game in the client
+    let mut game = Game::new(
+        9,
+        9,
+        Some(board_seed.seed as u64),
+        GameRules::generation(rules_generation),
+    );
+    for player in 0..2 {
+        let name = if player == human_player {
+            "You"
+        } else {
+            "Computer"
+        };
+        game.add_player(name.into());
+    }
+    game.board = board;
+    game.start();
+
+    let mut winner = None;
+    for (index, next_move) in moves.iter().enumerate() {
+        if winner.is_some() {
+            return Err(LadderWinError::MoveAfterEnd { index });
+        }
+        winner = game
+            .play_turn(next_move.clone(), dict, dict, None)
+            .map_err(|error| LadderWinError::IllegalMove { index, error })?
+            .or_else(|| game.resolve_endgame([dict; 2]));
+    }
+
+    match winner {
+        Some(winner) if winner == human_player => Ok(()),
+        _ => Err(LadderWinError::NotAWin),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::board::Coordinate;
+
+    #[test]
+    fn climbing_the_ladder() {
+        let mut progress = NpcProgress::default();
+        assert!(progress.is_unlocked(0));
+        assert!(!progress.is_unlocked(1));
+        assert!(progress.record_win(2).is_err());
+        assert!(progress.record_win(rungs().len() as u32).is_err());
+
+        progress.record_win(0).unwrap();
+        progress.record_win(1).unwrap();
+        assert!(progress.is_unlocked(2));
+        assert_eq!(progress.skins(), vec![TileSkin::Classic, TileSkin::Moss]);
+
+        let mut offline = NpcProgress::default();
+        offline.merge(&progress);
+        offline.record_win(2).unwrap();
+        progress.merge(&offline);
+        assert_eq!(progress.beaten, BTreeSet::from([0, 1, 2]));
+    }
+
+    #[test]
+    fn ladder_personalities_are_distinct() {
+        let names: BTreeSet<_> = rungs().into_iter().map(|r| r.personality.name).collect();
+        assert_eq!(names.len(), rungs().len());
+        for name in names {
+            assert!(NPCPersonality::from_id(&name).is_some());
+        }
+        for skin in rungs().into_iter().filter_map(|r| r.reward) {
+            assert_eq!(TileSkin::from_id(skin.to_string()), Some(skin));
+        }
+    }
+
+    #[test]
+    fn ladder_gets_harder() {
+        let params: Vec<_> = rungs().into_iter().map(|r| r.personality.params).collect();
+        assert!(params.len() >= 5);
+        for pair in params.windows(2) {
+            assert!(pair[0].blunder_rate >= pair[1].blunder_rate);
+            assert!(pair[0].evaluation_cap <= pair[1].evaluation_cap);
+        }
+        assert_eq!(params.last().map(|p| p.blunder_rate), Some(0.0));
+    }
+
+    #[test]
+    fn replaying_ladder_wins() {
+        let seed = || BoardSeed::new_with_generation(0, 1234);
+        let rules = GameRules::latest(None).0;
+
+        assert!(replay_win(seed(), rules, 0, &[], None).is_err());
+        assert!(replay_win(seed(), rules, 2, &[], None).is_err());
+
+        // The computer can't play on the human's turn
+        let swap = Move::Swap {
+            player: 1,
+            positions: [Coordinate::new(0, 0), Coordinate::new(0, 1)],
+        };
+        let err = replay_win(seed(), rules, 0, &[swap], None).unwrap_err();
+        assert!(
+            matches!(err, LadderWinError::IllegalMove { index: 0, .. }),
+            "{err}"
+        );
+    }
+}
@@ -28,6 +28,17 @@ pub struct NPCParams {
     pub word_validity: f32,
     pub word_length: f32,
     pub word_extensibility: f32,
+    /// Breaks ties between equally good moves using the game's seed,
+    /// rather than always taking the first one in board order
+    #[serde(default)]
+    pub seeded_ties: bool,
+    /// Plays strong openings learned from online games for the first few turns, rather than searching
+    #[serde(default)]
+    pub opening_book: bool,
+    /// Chance of deliberately playing a random move instead of the best one found,
+    /// drawn from the game's seed so the same turn always plays the same way
+    #[serde(default)]
+    pub blunder_rate: f32,
 }
 
 #[derive(Clone)]
@@ -56,6 +67,9 @@ impl Default for NPCParams {
             word_validity: 3.0,
             word_length: 1.0,
             word_extensibility: 1.0,
+            seeded_ties: false,
+            opening_book: false,
+            blunder_rate: 0.0,
         }
     }
 }
@@ -95,17 +109,70 @@ impl NPCPersonality {
         }
     }
 
+    /// Barely looks ahead and hardly minds whether its words are real
+    pub fn pebble() -> Self {
+        Self {
+            name: "pebble".to_string(),
+            params: NPCParams {
+                evaluation_cap: 1000,
+                max_depth: 1,
+                vocab: NPCVocab::Small,
+                word_validity: 1.0,
+                ..NPCParams::default()
+            },
+        }
+    }
+
+    /// Races for the opponent's towns, leaving its own exposed
+    pub fn flint() -> Self {
+        Self {
+            name: "flint".to_string(),
+            params: NPCParams {
+                evaluation_cap: 5000,
+                max_depth: 2,
+                raced_defense: 2.0,
+                raced_attack: 6.0,
+                direct_attack: 3.0,
+                ..NPCParams::default()
+            },
+        }
+    }
+
+    /// Walls off its towns before thinking about attacking
+    pub fn basalt() -> Self {
+        Self {
+            name: "basalt".to_string(),
+            params: NPCParams {
+                evaluation_cap: 8000,
+                max_depth: 2,
+                raced_defense: 9.0,
+                self_defense: 3.0,
+                direct_defence: 3.0,
+                ..NPCParams::default()
+            },
+        }
+    }
+
+    /// This personality, but throwing away this fraction of its turns on a random move
+    pub fn blundering(mut self, blunder_rate: f32) -> Self {
+        self.params.blunder_rate = blunder_rate;
+        self
+    }
+
     pub fn from_id(id: impl AsRef<str>) -> Option<Self> {
         match id.as_ref() {
             "opal" => Some(Self::opal()),
             "jet" => Some(Self::jet()),
             "mellite" => Some(Self::mellite()),
+            "pebble" => Some(Self::pebble()),
+            "flint" => Some(Self::flint()),
+            "basalt" => Some(Self::basalt()),
             _ => None,
         }
     }
 }
 
-#[derive(Clone, Default, PartialEq)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct BoardScore {
     infinity: bool,
     neg_infinity: bool,
@@ -120,6 +187,8 @@ pub struct BoardScore {
     self_win: bool,
     opponent_win: bool,
     npc_params: NPCParams,
+    /// Only kept for logging, so it's left behind when a score is sent elsewhere
+    #[serde(skip)]
     pub board: Option<Board>,
 }
 
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    judge::{language_words, WordDict},
+    rules::Language,
+};
+
+/// Which pack a room's words are judged against, and the version of it the room was set up with
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PackRef {
+    pub id: String,
+    pub version: u32,
+}
+
+/// A word list offered by the server on top of its built-in dictionaries, such as a tournament's
+/// official list. Clients download a pack when they join a room playing with it, rather than
+/// every pack being bundled into the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DictionaryPack {
+    /// Names the pack in rules and caches, so is kept to lowercase letters, digits, `-` and `_`
+    pub id: String,
+    pub name: String,
+    pub version: u32,
+    /// The language whose letters fill the bag when playing with the pack
+    pub language: Language,
+    pub word_count: usize,
+    /// SHA-256 of the word list, in lowercase hex
+    pub checksum: String,
+}
+
+impl DictionaryPack {
+    /// Describes a word list in the same format as the languages' lists,
+    /// with one word per line and objectionable words starting with a `*`
+    pub fn new(id: String, name: String, version: u32, language: Language, words: &str) -> Self {
+        Self {
+            id,
+            name,
+            version,
+            language,
+            word_count: words.lines().filter(|l| !l.trim().is_empty()).count(),
+            checksum: pack_checksum(words),
+        }
+    }
+
+    pub fn reference(&self) -> PackRef {
+        PackRef {
+            id: self.id.clone(),
+            version: self.version,
+        }
+    }
+
+    /// Whether a downloaded word list is the one this pack describes
+    pub fn verify(&self, words: &str) -> bool {
+        pack_checksum(words) == self.checksum
+    }
+
+    pub fn dictionary(&self, words: &str) -> WordDict {
+        language_words(words, self.language)
+    }
+}
+
+pub fn pack_checksum(words: &str) -> String {
+    let mut digest = chksum_hash_sha2::sha2_256::default();
+    digest.update(words);
+    digest.digest().to_hex_lowercase()
+}
+
+pub fn valid_pack_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::Game, rules::GameRules};
+
+    const WORDS: &str = "cat\ndog\n*rude\n\n";
+
+    #[test]
+    fn packs_check_their_word_lists() {
+        let pack = DictionaryPack::new(
+            "club-list".into(),
+            "Club List".into(),
+            2,
+            Language::English,
+            WORDS,
+        );
+        assert_eq!(pack.word_count, 3);
+        assert!(pack.verify(WORDS));
+        assert!(!pack.verify("cat\ndog\n"), "Truncated downloads are caught");
+        assert_eq!(
+            pack.reference(),
+            PackRef {
+                id: "club-list".into(),
+                version: 2
+            }
+        );
+
+        let dictionary = pack.dictionary(WORDS);
+        assert!(dictionary.contains_key("dog"));
+        assert!(dictionary.get("rude").is_some_and(|w| w.objectionable));
+    }
+
+    #[test]
+    fn rooms_play_with_the_pack_language() {
+        let pack = DictionaryPack::new("liga".into(), "Liga".into(), 1, Language::Spanish, WORDS);
+        let mut game = Game::new(9, 9, Some(1), GameRules::latest(None).1);
+        game.add_player("A".into());
+
+        game.set_dictionary_pack(Some(&pack));
+        assert_eq!(game.rules.language, Language::Spanish);
+        assert_eq!(game.rules.dictionary_pack, Some(pack.reference()));
+
+        game.set_rules(GameRules::latest(None).1);
+        assert_eq!(
+            game.rules.dictionary_pack,
+            Some(pack.reference()),
+            "Presets keep the pack"
+        );
+
+        game.set_language(Language::French);
+        assert_eq!(game.rules.dictionary_pack, None);
+    }
+
+    #[test]
+    fn pack_ids_are_safe_to_store() {
+        assert!(valid_pack_id("nwl_2023-v2"));
+        assert!(!valid_pack_id(""));
+        assert!(!valid_pack_id("../defs"));
+        assert!(!valid_pack_id("Club List"));
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
     board::Coordinate,
     error::GamePlayError,
     reporting::{Change, HandChange},
+    rules::{TileDraw, TurnEconomy},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -32,6 +33,10 @@ impl Hand {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn get(&self, index: usize) -> Option<&char> {
         self.0.get(index)
     }
@@ -77,6 +82,15 @@ pub struct Player {
     pub paused_turn_delta: Option<i64>,
     pub swap_count: usize,
     pub penalties_incurred: usize,
+    pub appeals_used: usize,
+    /// Tiles drawn so far on this player's current turn
+    pub drawn_this_turn: usize,
+    /// Action points spent so far on this player's current turn
+    #[serde(default)]
+    pub action_points_spent: u32,
+    /// Whether this player has already redrawn their starting hand
+    #[serde(default)]
+    pub mulliganed: bool,
     pub color: (u8, u8, u8),
     /// Tracked when in a fog of war game,
     /// to provide persistent vision of terrain and structures
@@ -104,6 +118,10 @@ impl Player {
             paused_turn_delta: None,
             swap_count: 0,
             penalties_incurred: 0,
+            appeals_used: 0,
+            drawn_this_turn: 0,
+            action_points_spent: 0,
+            mulliganed: false,
             color,
             seen_tiles: HashSet::new(),
         }
@@ -113,15 +131,26 @@ impl Player {
         self.hand.0.contains(&tile)
     }
 
-    pub fn use_tile(&mut self, tile: char, bag: &mut TileBag) -> Result<Change, GamePlayError> {
+    pub fn use_tile(
+        &mut self,
+        tile: char,
+        bag: &mut TileBag,
+        draw: &TileDraw,
+    ) -> Result<Change, GamePlayError> {
         match self.hand.iter().position(|t| t == &tile) {
             None => Err(GamePlayError::PlayerDoesNotHaveTile {
                 player: self.index,
                 tile,
             }),
             Some(index) => {
-                if self.hand.len() > self.hand_capacity {
-                    // They have too many tiles, so we don't give them a new one
+                let can_draw = match draw {
+                    TileDraw::FullHand => true,
+                    TileDraw::Fixed { draws } => self.drawn_this_turn < *draws,
+                    TileDraw::None => false,
+                };
+
+                if self.hand.len() > self.hand_capacity || !can_draw {
+                    // They have too many tiles, or have used up this turn's draws, so we don't give them a new one
                     self.hand.remove(index);
                     Ok(Change::Hand(HandChange {
                         player: self.index,
@@ -130,6 +159,7 @@ impl Player {
                     }))
                 } else {
                     self.hand.replace(index, bag.draw_tile());
+                    self.drawn_this_turn += 1;
                     Ok(Change::Hand(HandChange {
                         player: self.index,
                         removed: vec![tile],
@@ -140,6 +170,26 @@ impl Player {
         }
     }
 
+    /// How many more tiles this player can place over the rest of the game,
+    /// or `None` if they will keep drawing new tiles forever
+    pub fn tiles_remaining(&self, draw: &TileDraw) -> Option<usize> {
+        match draw {
+            TileDraw::FullHand => None,
+            TileDraw::Fixed { draws: 0 } | TileDraw::None => Some(self.hand.len()),
+            TileDraw::Fixed { .. } => None,
+        }
+    }
+
+    /// How many action points this player has left to spend this turn, in games that use them
+    pub fn action_points_remaining(&self, economy: &TurnEconomy) -> Option<u32> {
+        match economy {
+            TurnEconomy::SingleAction => None,
+            TurnEconomy::ActionPoints { per_turn, .. } => {
+                Some(per_turn.saturating_sub(self.action_points_spent))
+            }
+        }
+    }
+
     pub fn add_special_tile(&mut self, tile: char) -> Change {
         self.hand.add(tile);
         Change::Hand(HandChange {
@@ -156,7 +206,7 @@ mod tests {
 
     #[test]
     fn default() {
-        let mut bag = TileBag::latest(None).1;
+        let mut bag = TileBag::latest(Some(42)).1;
         let player = Player::new(
             "Liam Gallagher".into(),
             0,
@@ -166,17 +216,40 @@ mod tests {
             (255, 0, 0),
         );
         assert_eq!(player.hand.len(), 7);
-        // Note, this relies on randomness, so could fail even though it generally passes
-        // TODO(liam): Disabling this check for as the randomness has changed and may change again
-        // TODO(liam): Moved this from hand.rs and haven't updated it
-        // TODO: Change all game logic to run off a seeded RNG to make this deterministic,
-        //       (also makes game seeds replayable, if we ever want that)
-        // for player in h.hands {
-        //     for tile in player {
-        //         let num = tile as u8;
-        //         assert!((65..=90_u8).contains(&num)); // A-Z
-        //     }
-        // }
+        for tile in player.hand.iter() {
+            assert!(tile.is_ascii_uppercase());
+        }
+
+        // Hands are drawn from the seeded bag, so the same seed deals the same hand
+        let mut same_bag = TileBag::latest(Some(42)).1;
+        let same_player = Player::new("Noel".into(), 0, 7, &mut same_bag, None, (255, 0, 0));
+        assert_eq!(player.hand, same_player.hand);
+    }
+
+    #[test]
+    fn limits_fixed_draws_each_turn() -> Result<(), GamePlayError> {
+        let mut bag = crate::bag::tests::a_b_bag();
+        let mut player = Player::new("Mani".into(), 0, 3, &mut bag, None, (255, 0, 0));
+        let draw = TileDraw::Fixed { draws: 1 };
+        assert_eq!(player.tiles_remaining(&draw), None);
+
+        player.use_tile(player.hand.0[0], &mut bag, &draw)?;
+        assert_eq!(player.hand.len(), 3);
+
+        player.use_tile(player.hand.0[0], &mut bag, &draw)?;
+        assert_eq!(player.hand.len(), 2, "Only one draw is allowed each turn");
+
+        // The limit starts over on their next turn
+        player.drawn_this_turn = 0;
+        player.use_tile(player.hand.0[0], &mut bag, &draw)?;
+        assert_eq!(player.hand.len(), 2);
+
+        assert_eq!(
+            player.tiles_remaining(&TileDraw::Fixed { draws: 0 }),
+            Some(2)
+        );
+        assert_eq!(player.tiles_remaining(&TileDraw::FullHand), None);
+        Ok(())
     }
 
     // TODO(liam): Redo / re-enable tests
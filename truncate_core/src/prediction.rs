@@ -0,0 +1,354 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::Coordinate,
+    game::Game,
+    judge::WordDict,
+    moves::Move,
+    replay::{replay_move, ReplayError},
+};
+
+/// The most predictions the server will record from a single game
+pub const MAX_PREDICTIONS_PER_GAME: u32 = 200;
+
+/// How closely a guess matched the move the opponent went on to make
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredictionGrade {
+    /// The same tile on the same square, or a swap of the same two squares
+    Exact,
+    /// The right square with the wrong tile, the right tile beside the right square,
+    /// or a swap sharing one of its squares
+    Close,
+    Missed,
+}
+
+impl PredictionGrade {
+    pub fn grade(guess: &Move, actual: &Move) -> Self {
+        let exact = match (guess, actual) {
+            (
+                Move::Swap {
+                    player: guessed_player,
+                    positions: guessed,
+                },
+                Move::Swap { player, positions },
+            ) => {
+                // Either square can be named first for the same swap
+                let sorted = |mut positions: [Coordinate; 2]| {
+                    positions.sort();
+                    positions
+                };
+                guessed_player == player && sorted(*guessed) == sorted(*positions)
+            }
+            _ => guess == actual,
+        };
+        if exact {
+            return PredictionGrade::Exact;
+        }
+
+        let beside = |a: &Coordinate, b: &Coordinate| a.x.abs_diff(b.x) + a.y.abs_diff(b.y) == 1;
+        let close = match (guess, actual) {
+            (
+                Move::Place {
+                    tile: guessed_tile,
+                    position: guessed,
+                    ..
+                },
+                Move::Place { tile, position, .. },
+            ) => guessed == position || (guessed_tile == tile && beside(guessed, position)),
+            (
+                Move::Swap {
+                    positions: guessed, ..
+                },
+                Move::Swap { positions, .. },
+            ) => guessed.iter().any(|square| positions.contains(square)),
+            _ => false,
+        };
+
+        if close {
+            PredictionGrade::Close
+        } else {
+            PredictionGrade::Missed
+        }
+    }
+}
+
+/// How well a player has read their opponents' moves in training
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PredictionStats {
+    pub predictions: u32,
+    pub exact: u32,
+    pub close: u32,
+}
+
+impl PredictionStats {
+    pub fn record(&mut self, grade: PredictionGrade) {
+        self.predictions += 1;
+        match grade {
+            PredictionGrade::Exact => self.exact += 1,
+            PredictionGrade::Close => self.close += 1,
+            PredictionGrade::Missed => {}
+        }
+    }
+
+    pub fn add(&mut self, other: &PredictionStats) {
+        self.predictions += other.predictions;
+        self.exact += other.exact;
+        self.close += other.close;
+    }
+
+    /// Whether the stats could have come from a single game of training
+    pub fn is_plausible(&self) -> bool {
+        self.predictions <= MAX_PREDICTIONS_PER_GAME
+            && self.exact.saturating_add(self.close) <= self.predictions
+    }
+
+    /// The share of predictions that were right, with close predictions counting for half
+    pub fn accuracy(&self) -> Option<f32> {
+        match self.predictions {
+            0 => None,
+            predictions => Some((self.exact as f32 + self.close as f32 / 2.0) / predictions as f32),
+        }
+    }
+}
+
+/// Plays back a recorded game, pausing before each of the opponent's tile placements and swaps
+/// so that the player can guess it before it's revealed.
+///
+/// Like `ReplayComparison`, this holds no dictionary so that it can live in UI state.
+#[derive(Debug, Clone)]
+pub struct PredictionTrainer {
+    game: Game,
+    moves: Vec<Move>,
+    played: usize,
+    as_player: usize,
+    pub stats: PredictionStats,
+    error: Option<ReplayError>,
+}
+
+impl PredictionTrainer {
+    /// Sets up training from the side of `as_player`, whose opponents' moves are guessed
+    pub fn new(mut game: Game, moves: Vec<Move>, as_player: usize) -> Self {
+        if game.started_at.is_none() {
+            game.start();
+        }
+
+        Self {
+            game,
+            moves,
+            played: 0,
+            as_player,
+            stats: PredictionStats::default(),
+            error: None,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn as_player(&self) -> usize {
+        self.as_player
+    }
+
+    /// The move that couldn't be played, if the game stopped early
+    pub fn error(&self) -> Option<&ReplayError> {
+        self.error.as_ref()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.error.is_some() || self.played >= self.moves.len()
+    }
+
+    /// Whether the next move is one of the opponent's that can be guessed
+    pub fn awaiting_guess(&self) -> bool {
+        !self.is_finished()
+            && matches!(
+                &self.moves[self.played],
+                Move::Place { player, .. } | Move::Swap { player, .. } if *player != self.as_player
+            )
+    }
+
+    /// Plays moves until the next one to be guessed, or the end of the game
+    pub fn advance(&mut self, dictionary: Option<&WordDict>) {
+        while !self.is_finished() && !self.awaiting_guess() {
+            self.play_next(dictionary);
+        }
+    }
+
+    /// Grades a guess at the opponent's next move, then plays the move they actually made
+    pub fn guess(
+        &mut self,
+        guess: &Move,
+        dictionary: Option<&WordDict>,
+    ) -> Option<(PredictionGrade, Move)> {
+        if !self.awaiting_guess() {
+            return None;
+        }
+
+        let actual = self.moves[self.played].clone();
+        let grade = PredictionGrade::grade(guess, &actual);
+        self.stats.record(grade);
+        self.play_next(dictionary);
+        Some((grade, actual))
+    }
+
+    /// Reveals the opponent's next move without counting a guess
+    pub fn skip(&mut self, dictionary: Option<&WordDict>) -> Option<Move> {
+        if !self.awaiting_guess() {
+            return None;
+        }
+
+        let actual = self.moves[self.played].clone();
+        self.play_next(dictionary);
+        Some(actual)
+    }
+
+    fn play_next(&mut self, dictionary: Option<&WordDict>) {
+        let Some(next_move) = self.moves.get(self.played).cloned() else {
+            return;
+        };
+        // Opponents' moves are always played as they were recorded, whatever was guessed
+        let turn = self.played;
+        self.played += 1;
+        if let Err(e) = replay_move(&mut self.game, next_move, turn, dictionary) {
+            self.error = Some(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bag::tests::trivial_bag,
+        board::{Board, Square},
+        player::Player,
+        rules::GameRules,
+    };
+
+    fn place(player: usize, tile: char, x: usize, y: usize) -> Move {
+        Move::Place {
+            player,
+            tile,
+            position: Coordinate { x, y },
+        }
+    }
+
+    #[test]
+    fn grades_guesses() {
+        let actual = place(1, 'A', 1, 4);
+        assert_eq!(
+            PredictionGrade::grade(&place(1, 'A', 1, 4), &actual),
+            PredictionGrade::Exact
+        );
+        assert_eq!(
+            PredictionGrade::grade(&place(1, 'B', 1, 4), &actual),
+            PredictionGrade::Close
+        );
+        assert_eq!(
+            PredictionGrade::grade(&place(1, 'A', 2, 4), &actual),
+            PredictionGrade::Close
+        );
+        assert_eq!(
+            PredictionGrade::grade(&place(1, 'B', 2, 4), &actual),
+            PredictionGrade::Missed
+        );
+
+        let swap = |a: (usize, usize), b: (usize, usize)| Move::Swap {
+            player: 1,
+            positions: [Coordinate::new(a.0, a.1), Coordinate::new(b.0, b.1)],
+        };
+        assert_eq!(
+            PredictionGrade::grade(&swap((1, 1), (1, 2)), &swap((1, 2), (1, 1))),
+            PredictionGrade::Exact
+        );
+        assert_eq!(
+            PredictionGrade::grade(&swap((1, 2), (1, 1)), &swap((1, 1), (1, 2))),
+            PredictionGrade::Exact
+        );
+        assert_eq!(
+            PredictionGrade::grade(&swap((1, 1), (1, 2)), &swap((1, 1), (1, 2))),
+            PredictionGrade::Exact
+        );
+        assert_eq!(
+            PredictionGrade::grade(&swap((1, 1), (0, 1)), &swap((1, 2), (1, 1))),
+            PredictionGrade::Close
+        );
+        assert_eq!(
+            PredictionGrade::grade(&place(1, 'A', 1, 1), &swap((1, 2), (1, 1))),
+            PredictionGrade::Missed
+        );
+    }
+
+    #[test]
+    fn stats_weigh_close_guesses() {
+        let mut stats = PredictionStats::default();
+        assert_eq!(stats.accuracy(), None);
+
+        stats.record(PredictionGrade::Exact);
+        stats.record(PredictionGrade::Close);
+        stats.record(PredictionGrade::Missed);
+        stats.record(PredictionGrade::Missed);
+        assert_eq!(stats.accuracy(), Some(0.375));
+        assert!(stats.is_plausible());
+
+        stats.exact = 10;
+        assert!(!stats.is_plausible());
+    }
+
+    #[test]
+    fn pauses_before_each_opponent_move() {
+        let mut bag = trivial_bag();
+        let game = Game {
+            board: Board::from_string(
+                "~~ |0 ~~\n\
+                 __ __ __\n\
+                 #0 __ #0\n\
+                 __ __ __\n\
+                 #1 __ #1\n\
+                 __ __ __\n\
+                 ~~ |1 ~~",
+            ),
+            players: vec![
+                Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+                Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+            ],
+            bag,
+            player_turn_count: vec![0, 0],
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        let moves = vec![
+            place(0, 'A', 1, 1),
+            place(1, 'A', 1, 5),
+            place(0, 'A', 1, 2),
+            place(1, 'A', 1, 4),
+        ];
+
+        let mut trainer = PredictionTrainer::new(game, moves, 0);
+        trainer.advance(None);
+        assert!(trainer.awaiting_guess());
+        assert!(matches!(
+            trainer.game().board.get(Coordinate::new(1, 1)),
+            Ok(Square::Occupied { player: 0, .. })
+        ));
+
+        let (grade, actual) = trainer.guess(&place(1, 'A', 1, 5), None).unwrap();
+        assert_eq!(grade, PredictionGrade::Exact);
+        assert_eq!(actual, place(1, 'A', 1, 5));
+        assert!(!trainer.awaiting_guess(), "Our own moves aren't guessed");
+
+        trainer.advance(None);
+        assert_eq!(trainer.skip(None), Some(place(1, 'A', 1, 4)));
+        trainer.advance(None);
+        assert!(trainer.is_finished());
+        assert_eq!(trainer.error(), None);
+        assert_eq!(
+            trainer.stats,
+            PredictionStats {
+                predictions: 1,
+                exact: 1,
+                close: 0
+            }
+        );
+    }
+}
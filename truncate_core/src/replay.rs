@@ -0,0 +1,459 @@
+use thiserror::Error;
+
+use crate::{
+    error::GamePlayError,
+    game::{now, Game},
+    generation::{generate_board, BoardSeed},
+    judge::WordDict,
+    moves::{packing::unpack_moves, Move},
+    reporting::Change,
+    rules::GameRules,
+};
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ReplayError {
+    #[error("The seed couldn't generate a board")]
+    UnplayableSeed,
+    #[error("The moves couldn't be unpacked")]
+    BadPacking,
+    #[error("Move {turn} couldn't be played: {source}")]
+    IllegalMove { turn: usize, source: GamePlayError },
+}
+
+/// Plays a recorded game back from the start, one move at a time.
+///
+/// Iterating yields the game after each move alongside the changes that move made,
+/// and stops after the first move that can't be played.
+#[derive(Debug, Clone)]
+pub struct Replay<'a> {
+    game: Game,
+    moves: Vec<Move>,
+    next_move: usize,
+    dictionary: Option<&'a WordDict>,
+}
+
+impl<'a> Replay<'a> {
+    /// Sets up a two player game on the board and tile bag the seed generates,
+    /// as the daily puzzle and single player games are
+    pub fn from_seed(
+        seed: &BoardSeed,
+        rules: GameRules,
+        packed_moves: &String,
+        dictionary: Option<&'a WordDict>,
+    ) -> Result<Self, ReplayError> {
+        let mut board = generate_board(seed.clone())
+            .map_err(|_| ReplayError::UnplayableSeed)?
+            .board;
+        board.cache_special_squares();
+
+        let mut game = Game::new(9, 9, Some(seed.seed as u64), rules);
+        game.add_player("Player 1".into());
+        game.add_player("Player 2".into());
+        game.board = board;
+
+        let moves = unpack_moves(packed_moves, 2).map_err(|_| ReplayError::BadPacking)?;
+
+        Ok(Self::from_game(game, moves, dictionary))
+    }
+
+    /// Replays moves onto a game that has already been set up, starting it if need be
+    pub fn from_game(mut game: Game, moves: Vec<Move>, dictionary: Option<&'a WordDict>) -> Self {
+        if game.started_at.is_none() {
+            game.start();
+        }
+
+        Self {
+            game,
+            moves,
+            next_move: 0,
+            dictionary,
+        }
+    }
+
+    /// The game as it stands after the moves replayed so far
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Every move being replayed, including those already played
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Plays every remaining move, returning the game as it ended up
+    pub fn finish(mut self) -> Result<Game, ReplayError> {
+        while let Some(played) = self.play_next() {
+            played?;
+        }
+        Ok(self.game)
+    }
+
+    /// Plays every remaining move, returning who won, if anyone.
+    /// Endgames that the client settles with the endgame solver once the last move is played are settled here too.
+    pub fn winner(self) -> Result<Option<usize>, ReplayError> {
+        let dictionary = self.dictionary;
+        let mut game = self.finish()?;
+        Ok(game
+            .winner
+            .or_else(|| game.resolve_endgame([dictionary; 2])))
+    }
+
+    fn play_next(&mut self) -> Option<Result<(), ReplayError>> {
+        let next_move = self.moves.get(self.next_move)?.clone();
+        let turn = self.next_move;
+        self.next_move += 1;
+
+        let played = replay_move(&mut self.game, next_move, turn, self.dictionary);
+        if played.is_err() {
+            // Nothing after an illegal move can be trusted, so stop here
+            self.next_move = self.moves.len();
+        }
+        Some(played)
+    }
+}
+
+pub(crate) fn replay_move(
+    game: &mut Game,
+    next_move: Move,
+    turn: usize,
+    dictionary: Option<&WordDict>,
+) -> Result<(), ReplayError> {
+    // Replays don't wait out the delays between turns that live games do
+    let mover = match next_move {
+        Move::Place { player, .. }
+        | Move::Swap { player, .. }
+        | Move::Exchange { player, .. }
+        | Move::Mulligan { player } => player,
+    };
+    if let Some(start) = game
+        .players
+        .get_mut(mover)
+        .and_then(|p| p.turn_starts_no_sooner_than.as_mut())
+    {
+        *start = (*start).min(now());
+    }
+
+    game.play_turn(next_move, dictionary, dictionary, None)
+        .map(|_| ())
+        .map_err(|source| ReplayError::IllegalMove { turn, source })
+}
+
+/// One side of a comparison, which holds no dictionary so that it can live in UI state
+#[derive(Debug, Clone)]
+struct ComparedGame {
+    start: Game,
+    game: Game,
+    moves: Vec<Move>,
+    played: usize,
+    error: Option<ReplayError>,
+}
+
+impl ComparedGame {
+    fn new(mut game: Game, moves: Vec<Move>) -> Self {
+        if game.started_at.is_none() {
+            game.start();
+        }
+
+        Self {
+            start: game.clone(),
+            game,
+            moves,
+            played: 0,
+            error: None,
+        }
+    }
+
+    fn step(&mut self, dictionary: Option<&WordDict>) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+        let Some(next_move) = self.moves.get(self.played).cloned() else {
+            return false;
+        };
+
+        let turn = self.played;
+        self.played += 1;
+        match replay_move(&mut self.game, next_move, turn, dictionary) {
+            Ok(_) => true,
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+
+    fn rewind(&mut self) {
+        self.game = self.start.clone();
+        self.played = 0;
+        self.error = None;
+    }
+}
+
+/// Two recorded games played back side by side in lockstep by turn number,
+/// for comparing different attempts at the same game.
+///
+/// A side that runs out of moves, or reaches a move it can't play, stays as it was
+/// while the other side carries on.
+#[derive(Debug, Clone)]
+pub struct ReplayComparison {
+    sides: [ComparedGame; 2],
+    turn: usize,
+}
+
+impl ReplayComparison {
+    pub fn new(left: (Game, Vec<Move>), right: (Game, Vec<Move>)) -> Self {
+        Self {
+            sides: [
+                ComparedGame::new(left.0, left.1),
+                ComparedGame::new(right.0, right.1),
+            ],
+            turn: 0,
+        }
+    }
+
+    /// How many turns both sides have been stepped through
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// The length of the longer of the two games
+    pub fn total_turns(&self) -> usize {
+        self.sides[0].moves.len().max(self.sides[1].moves.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.turn >= self.total_turns()
+    }
+
+    /// Either side's game as it stands at the current turn, `0` being the left
+    pub fn game(&self, side: usize) -> &Game {
+        &self.sides[side].game
+    }
+
+    /// The move that couldn't be played on a side, if it stopped early
+    pub fn error(&self, side: usize) -> Option<&ReplayError> {
+        self.sides[side].error.as_ref()
+    }
+
+    /// Plays the next turn on both sides, returning which of them moved
+    pub fn step(&mut self, dictionary: Option<&WordDict>) -> [bool; 2] {
+        if self.is_finished() {
+            return [false, false];
+        }
+
+        self.turn += 1;
+        [
+            self.sides[0].step(dictionary),
+            self.sides[1].step(dictionary),
+        ]
+    }
+
+    /// Moves both sides to the given turn, replaying from the start to go backwards
+    pub fn seek(&mut self, turn: usize, dictionary: Option<&WordDict>) {
+        let turn = turn.min(self.total_turns());
+        if turn < self.turn {
+            for side in self.sides.iter_mut() {
+                side.rewind();
+            }
+            self.turn = 0;
+        }
+
+        while self.turn < turn {
+            self.step(dictionary);
+        }
+    }
+}
+
+impl<'a> Iterator for Replay<'a> {
+    type Item = Result<(Game, Vec<Change>), ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.play_next()?
+                .map(|_| (self.game.clone(), self.game.recent_changes.clone())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bag::tests::trivial_bag,
+        board::{Board, Coordinate, Square},
+        moves::packing::pack_moves,
+        player::Player,
+    };
+
+    use super::*;
+
+    fn docked_game() -> Game {
+        let mut bag = trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+
+        Game {
+            board: Board::from_string(
+                "~~ |0 ~~\n\
+                 __ __ __\n\
+                 #0 __ #0\n\
+                 __ __ __\n\
+                 #1 __ #1\n\
+                 __ __ __\n\
+                 ~~ |1 ~~",
+            ),
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        }
+    }
+
+    fn place(player: usize, x: usize, y: usize) -> Move {
+        Move::Place {
+            player,
+            tile: 'A',
+            position: Coordinate { x, y },
+        }
+    }
+
+    #[test]
+    fn replays_every_move() {
+        let moves = vec![place(0, 1, 1), place(1, 1, 5), place(0, 1, 2)];
+        let snapshots: Vec<_> = Replay::from_game(docked_game(), moves.clone(), None)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(snapshots.len(), 3);
+        for ((game, changes), next_move) in snapshots.iter().zip(&moves) {
+            let Move::Place { position, .. } = next_move else {
+                unreachable!()
+            };
+            assert!(matches!(
+                game.board.get(*position),
+                Ok(Square::Occupied { tile: 'A', .. })
+            ));
+            assert!(changes.iter().any(|c| matches!(c, Change::Board(_))));
+        }
+
+        let finished = Replay::from_game(docked_game(), moves, None)
+            .finish()
+            .unwrap();
+        assert_eq!(finished.board, snapshots[2].0.board);
+        assert_eq!(finished.turn_count, 3);
+    }
+
+    #[test]
+    fn finds_the_winner() {
+        let mut bag = trivial_bag();
+        let players = vec![
+            Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+            Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+        ];
+        let game = Game {
+            board: Board::from_string(
+                "~~ |0 ~~\n\
+                 __ __ __\n\
+                 __ #1 __\n\
+                 ~~ |1 ~~",
+            ),
+            bag,
+            players,
+            player_turn_count: vec![0, 0],
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+
+        let moves = vec![place(0, 1, 1)];
+        assert_eq!(
+            Replay::from_game(game.clone(), moves, None).winner(),
+            Ok(Some(0))
+        );
+        assert_eq!(Replay::from_game(game, vec![], None).winner(), Ok(None));
+    }
+
+    #[test]
+    fn stops_at_an_illegal_move() {
+        let moves = vec![place(0, 1, 1), place(0, 1, 2), place(1, 1, 5)];
+        let mut replay = Replay::from_game(docked_game(), moves, None);
+
+        assert!(replay.next().unwrap().is_ok());
+        assert_eq!(
+            replay.next().unwrap().map(|_| ()),
+            Err(ReplayError::IllegalMove {
+                turn: 1,
+                source: GamePlayError::NotPlayersTurn { player: 0 }
+            })
+        );
+        assert!(replay.next().is_none());
+    }
+
+    #[test]
+    fn seeds_replay_identically() {
+        let rules = GameRules::generation(GameRules::latest(None).0);
+        let seed = BoardSeed::new(1234);
+        let packed = pack_moves(&[], 2);
+
+        let first = Replay::from_seed(&seed, rules.clone(), &packed, None).unwrap();
+        let second = Replay::from_seed(&seed, rules, &packed, None).unwrap();
+        assert_eq!(first.game().board, second.game().board);
+        for (a, b) in first.game().players.iter().zip(&second.game().players) {
+            assert_eq!(a.hand, b.hand);
+        }
+
+        assert_eq!(
+            Replay::from_seed(&seed, GameRules::generation(0), &"?".to_string(), None).map(|_| ()),
+            Err(ReplayError::BadPacking)
+        );
+    }
+
+    #[test]
+    fn compares_games_in_lockstep() {
+        let tile_at = |comparison: &ReplayComparison, side: usize, x: usize, y: usize| {
+            comparison
+                .game(side)
+                .board
+                .get(Coordinate { x, y })
+                .map(|square| matches!(square, Square::Occupied { .. }))
+                .unwrap()
+        };
+
+        let mut comparison = ReplayComparison::new(
+            (
+                docked_game(),
+                vec![place(0, 1, 1), place(1, 1, 5), place(0, 1, 2)],
+            ),
+            (docked_game(), vec![place(0, 1, 1), place(0, 1, 2)]),
+        );
+        assert_eq!(comparison.total_turns(), 3);
+
+        assert_eq!(comparison.step(None), [true, true]);
+        assert!(tile_at(&comparison, 0, 1, 1) && tile_at(&comparison, 1, 1, 1));
+
+        // The right side tries to move out of turn, and stays put from then on
+        assert_eq!(comparison.step(None), [true, false]);
+        assert_eq!(
+            comparison.error(1),
+            Some(&ReplayError::IllegalMove {
+                turn: 1,
+                source: GamePlayError::NotPlayersTurn { player: 0 }
+            })
+        );
+        assert_eq!(comparison.step(None), [true, false]);
+        assert!(comparison.is_finished());
+        assert_eq!(comparison.step(None), [false, false]);
+        assert_eq!(comparison.game(0).turn_count, 3);
+        assert_eq!(comparison.game(1).turn_count, 1);
+
+        // Seeking backwards replays from the start
+        comparison.seek(1, None);
+        assert_eq!(comparison.turn(), 1);
+        assert!(tile_at(&comparison, 0, 1, 1));
+        assert!(!tile_at(&comparison, 0, 1, 5));
+        assert_eq!(comparison.error(1), None);
+
+        comparison.seek(10, None);
+        assert_eq!(comparison.turn(), 3);
+        assert!(tile_at(&comparison, 0, 1, 2));
+    }
+}
@@ -3,10 +3,16 @@ use std::{collections::HashSet, fmt};
 
 use crate::{
     board::{Board, Coordinate, Square},
+    emojification::{SQ_GREEN, SQ_RED, SQ_WHITE, SQ_YELLOW},
     judge::Outcome,
+    moves::Move,
+    replay::Replay,
     rules,
 };
 
+/// How many moves are shown on each line of a shared summary
+const SHARE_GRID_WIDTH: usize = 5;
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum BoardChangeAction {
     Added,
@@ -15,6 +21,12 @@ pub enum BoardChangeAction {
     Defeated,
     Truncated,
     Exploded,
+    /// Cut off from its artifact, and will be truncated unless its owner reconnects it this turn
+    Withered,
+    /// Moved to close a gap, from the given square to the one in the detail
+    Slid {
+        from: Coordinate,
+    },
 }
 
 impl fmt::Display for BoardChangeAction {
@@ -26,6 +38,8 @@ impl fmt::Display for BoardChangeAction {
             BoardChangeAction::Defeated => write!(f, "Defeated"),
             BoardChangeAction::Truncated => write!(f, "Truncated"),
             BoardChangeAction::Exploded => write!(f, "Exploded"),
+            BoardChangeAction::Withered => write!(f, "Withered"),
+            BoardChangeAction::Slid { from } => write!(f, "Slid from {from}"),
         }
     }
 }
@@ -85,6 +99,15 @@ pub struct BattleWord {
     pub valid: Option<bool>,
 }
 
+impl BattleWord {
+    /// Whether this word was only valid as a close misspelling of its resolved word, under lenient spelling
+    pub fn misspelled(&self) -> bool {
+        self.valid == Some(true)
+            && self.original_word.chars().all(|c| c.is_alphabetic())
+            && !self.original_word.eq_ignore_ascii_case(&self.resolved_word)
+    }
+}
+
 impl fmt::Display for BattleWord {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -106,13 +129,22 @@ pub struct BattleReport {
     pub attackers: Vec<BattleWord>,
     pub defenders: Vec<BattleWord>,
     pub outcome: Outcome,
+    pub amendment: Option<BattleAmendment>,
+}
+
+/// A record of a battle being re-judged on appeal
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BattleAmendment {
+    pub appellant: usize,
+    /// Whether the wider dictionary changed the outcome of the battle
+    pub overturned: bool,
 }
 
 impl fmt::Display for BattleReport {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "Battle Report\nAttackers: {}\nDefenders: {}\nOutcome: {}",
+            "Battle Report\nAttackers: {}\nDefenders: {}\nOutcome: {}{}",
             self.attackers
                 .iter()
                 .map(|w| format!("{w}"))
@@ -123,11 +155,32 @@ impl fmt::Display for BattleReport {
                 .map(|w| format!("{w}"))
                 .collect::<Vec<_>>()
                 .join(", "),
-            self.outcome
+            self.outcome,
+            match &self.amendment {
+                Some(BattleAmendment {
+                    appellant,
+                    overturned: true,
+                }) => format!("\nOverturned on appeal by player {appellant}"),
+                Some(BattleAmendment {
+                    appellant,
+                    overturned: false,
+                }) => format!("\nUpheld on appeal by player {appellant}"),
+                None => String::new(),
+            }
         )
     }
 }
 
+/// What a move would do, worked out without playing it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MovePreview {
+    /// Words running through the moved squares as the board stands
+    pub before: Vec<BattleWord>,
+    /// Words running through the same squares once the move is made
+    pub after: Vec<BattleWord>,
+    pub battles: Vec<BattleReport>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TimeChange {
     pub player: usize,
@@ -144,12 +197,74 @@ impl fmt::Display for TimeChange {
     }
 }
 
+/// How long a player spent deciding on one of their turns
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThinkChange {
+    pub player: usize,
+    /// The game's turn count once this turn was played
+    pub turn: u32,
+    pub secs: u64,
+}
+
+impl fmt::Display for ThinkChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Player {} thought for {} seconds on turn {}",
+            self.player, self.secs, self.turn
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BagEvent {
+    /// Tiles were drawn into the player's hand
+    Drawn { tiles: usize },
+    /// The last tile was drawn. Bags that refill will be reshuffled on the next draw,
+    /// otherwise nobody can draw for the rest of the game.
+    Exhausted { refills: bool },
+    /// The bag ran dry and was refilled with a fresh set of tiles
+    Reshuffled,
+}
+
+/// Something that happened to the tile bag. Everyone can see these,
+/// as they only say how many tiles were drawn and never which.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BagChange {
+    /// The player whose draw caused this
+    pub player: usize,
+    pub event: BagEvent,
+    /// How many tiles were left in the bag afterwards
+    pub remaining: usize,
+}
+
+impl fmt::Display for BagChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.event {
+            BagEvent::Drawn { tiles } => write!(
+                f,
+                "Player {} drew {tiles} tile(s), leaving {} in the bag",
+                self.player, self.remaining
+            ),
+            BagEvent::Exhausted { refills: true } => {
+                write!(f, "The bag is empty, and will be reshuffled")
+            }
+            BagEvent::Exhausted { refills: false } => write!(f, "The bag is empty"),
+            BagEvent::Reshuffled => {
+                write!(f, "The bag was reshuffled with {} tiles", self.remaining)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Change {
     Board(BoardChange),
     Hand(HandChange),
     Battle(BattleReport),
     Time(TimeChange),
+    Bag(BagChange),
+    Think(ThinkChange),
 }
 
 impl fmt::Display for Change {
@@ -159,10 +274,113 @@ impl fmt::Display for Change {
             Change::Hand(c) => write!(f, "{c}"),
             Change::Battle(c) => write!(f, "{c}"),
             Change::Time(c) => write!(f, "{c}"),
+            Change::Bag(c) => write!(f, "{c}"),
+            Change::Think(c) => write!(f, "{c}"),
         }
     }
 }
 
+/// The battles fought over one of a player's moves, counting both those the move started
+/// and those started by the opponent's reply
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MoveBattles {
+    pub won: u32,
+    pub lost: u32,
+}
+
+impl MoveBattles {
+    fn emoji(&self) -> &'static str {
+        match (self.won, self.lost) {
+            (0, 0) => SQ_WHITE,
+            (_, 0) => SQ_GREEN,
+            (0, _) => SQ_RED,
+            _ => SQ_YELLOW,
+        }
+    }
+}
+
+/// How a finished attempt went for one player, shared without giving away the words they played
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttemptSummary {
+    pub won: bool,
+    /// One entry for each of the player's moves
+    pub moves: Vec<MoveBattles>,
+}
+
+impl AttemptSummary {
+    /// Plays through an attempt, crediting each battle to whichever side of it the player was on
+    pub fn from_replay(replay: Replay, player: usize) -> Self {
+        let movers: Vec<_> = replay
+            .moves()
+            .iter()
+            .map(|played| match played {
+                Move::Mulligan { .. } => None,
+                Move::Place { player, .. }
+                | Move::Swap { player, .. }
+                | Move::Exchange { player, .. } => Some(*player),
+            })
+            .collect();
+
+        let mut moves: Vec<MoveBattles> = vec![];
+        let mut winner = None;
+        for (mover, turn) in movers.into_iter().zip(replay) {
+            let Ok((game, changes)) = turn else {
+                break;
+            };
+            winner = game.winner;
+
+            let Some(mover) = mover else {
+                continue;
+            };
+            if mover == player {
+                moves.push(MoveBattles::default());
+            }
+            // Battles before the player's first move have no move of theirs to belong to
+            let Some(current) = moves.last_mut() else {
+                continue;
+            };
+
+            for change in changes {
+                let Change::Battle(battle) = change else {
+                    continue;
+                };
+                let attacker_won = matches!(battle.outcome, Outcome::AttackerWins(_));
+                if attacker_won == (mover == player) {
+                    current.won += 1;
+                } else {
+                    current.lost += 1;
+                }
+            }
+        }
+
+        Self {
+            won: winner == Some(player),
+            moves,
+        }
+    }
+
+    /// A grid with a square for each move, green where the player won every battle around it,
+    /// red where they lost every battle, yellow where they did both, and white where none were fought
+    pub fn share_text(&self, title: &str) -> String {
+        let move_count = self.moves.len();
+        let mut lines = vec![
+            title.to_string(),
+            format!(
+                "{} in {} move{}",
+                if self.won { "Won" } else { "Lost" },
+                move_count,
+                if move_count == 1 { "" } else { "s" }
+            ),
+        ];
+        lines.extend(
+            self.moves
+                .chunks(SHARE_GRID_WIDTH)
+                .map(|row| row.iter().map(MoveBattles::emoji).collect::<String>()),
+        );
+        lines.join("\n")
+    }
+}
+
 pub(crate) fn filter_to_player(
     changes: &Vec<Change>,
     full_board: &Board,
@@ -200,6 +418,20 @@ pub(crate) fn filter_to_player(
                 ) else {
                     return None;
                 };
+                let action = match action {
+                    BoardChangeAction::Slid { from } => BoardChangeAction::Slid {
+                        from: full_board
+                            .map_game_coord_to_player(
+                                player_index,
+                                *from,
+                                visibility,
+                                board_orientation,
+                                seen_tiles,
+                            )
+                            .unwrap_or(relative_coord),
+                    },
+                    action => action.clone(),
+                };
                 let relative_change = Change::Board(BoardChange {
                     detail: BoardChangeDetail {
                         square: square.clone(),
@@ -213,10 +445,11 @@ pub(crate) fn filter_to_player(
                     return Some(relative_change);
                 }
 
-                if action == &BoardChangeAction::Victorious
-                    || action == &BoardChangeAction::Defeated
-                    || action == &BoardChangeAction::Truncated
-                    || action == &BoardChangeAction::Exploded
+                if action == BoardChangeAction::Victorious
+                    || action == BoardChangeAction::Defeated
+                    || action == BoardChangeAction::Truncated
+                    || action == BoardChangeAction::Exploded
+                    || action == BoardChangeAction::Withered
                 {
                     return Some(relative_change);
                 }
@@ -224,7 +457,8 @@ pub(crate) fn filter_to_player(
                     rules::Visibility::Standard => Some(relative_change),
                     rules::Visibility::TileFog
                     | rules::Visibility::LandFog
-                    | rules::Visibility::OnlyHouseFog => match visible_board.get(relative_coord) {
+                    | rules::Visibility::OnlyHouseFog
+                    | rules::Visibility::Mystery => match visible_board.get(relative_coord) {
                         Ok(Square::Occupied { .. }) => Some(relative_change),
                         _ => None,
                     },
@@ -232,6 +466,74 @@ pub(crate) fn filter_to_player(
             }
             Change::Battle(_) => Some(change.clone()),
             Change::Time(_) => Some(change.clone()),
+            Change::Bag(_) => Some(change.clone()),
+            Change::Think(_) => Some(change.clone()),
         })
         .collect::<Vec<_>>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bag::tests::trivial_bag, game::Game, player::Player, rules::GameRules};
+
+    #[test]
+    fn summaries_wrap_a_square_per_move() {
+        let battles = |won, lost| MoveBattles { won, lost };
+        let summary = AttemptSummary {
+            won: true,
+            moves: vec![
+                battles(0, 0),
+                battles(1, 0),
+                battles(0, 2),
+                battles(1, 1),
+                battles(0, 0),
+                battles(3, 0),
+            ],
+        };
+
+        assert_eq!(
+            summary.share_text("Truncate Town Day #12"),
+            "Truncate Town Day #12\nWon in 6 moves\n⬜🟩🟥🟨⬜\n🟩"
+        );
+    }
+
+    #[test]
+    fn summaries_credit_battles_to_either_side() {
+        let mut bag = trivial_bag();
+        let game = Game {
+            board: Board::from_string(
+                "~~ |0 ~~\n\
+                 __ __ __\n\
+                 __ __ __\n\
+                 __ __ __\n\
+                 ~~ |1 ~~",
+            ),
+            players: vec![
+                Player::new("A".into(), 0, 7, &mut bag, None, (0, 0, 0)),
+                Player::new("B".into(), 1, 7, &mut bag, None, (0, 0, 0)),
+            ],
+            bag,
+            player_turn_count: vec![0, 0],
+            ..Game::new_legacy(1, 1, None, GameRules::generation(0))
+        };
+        let place = |player, y| Move::Place {
+            player,
+            tile: 'A',
+            position: Coordinate { x: 1, y },
+        };
+
+        // Player A's second tile attacks player B's, and the defender holds
+        let moves = vec![place(0, 1), place(1, 3), place(0, 2)];
+        let attacker =
+            AttemptSummary::from_replay(Replay::from_game(game.clone(), moves.clone(), None), 0);
+        assert_eq!(
+            attacker.moves,
+            vec![MoveBattles::default(), MoveBattles { won: 0, lost: 1 }]
+        );
+
+        let defender = AttemptSummary::from_replay(Replay::from_game(game, moves, None), 1);
+        assert_eq!(defender.moves, vec![MoveBattles { won: 1, lost: 0 }]);
+        assert!(defender.share_text("Puzzle").ends_with("in 1 move\n🟩"));
+    }
+}
@@ -0,0 +1,123 @@
+use std::ops::Range;
+
+use oorandom::Rand32;
+
+/// Stream for the NPC to break ties between equally scored moves
+pub const NPC_STREAM: u64 = 1;
+/// Stream for the NPC to decide when to deliberately blunder, and which move to blunder with
+pub const BLUNDER_STREAM: u64 = 2;
+
+/// The source of all randomness in core game logic.
+///
+/// Everything random in a game is drawn from a generator built from one seed,
+/// so a game can be replayed exactly by knowing that seed and the moves played.
+#[derive(Debug, Clone)]
+pub struct TruncateRng {
+    seed: u64,
+    rng: Rand32,
+}
+
+impl TruncateRng {
+    /// Produces the same sequence as `Rand32::new(seed)`,
+    /// which past boards and tile bags were generated from
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Rand32::new(seed),
+        }
+    }
+
+    /// Seeds from the clock, for games that don't need to be reproduced.
+    /// The seed is still kept, so they can be reproduced anyway.
+    pub fn unseeded() -> Self {
+        Self::new(
+            instant::SystemTime::now()
+                .duration_since(instant::SystemTime::UNIX_EPOCH)
+                .expect("Please don't play Truncate earlier than 1970")
+                .as_secs(),
+        )
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Splits off an independent generator, so that one part of the game
+    /// (e.g. how many tiles were drawn) can't change another (e.g. how the NPC plays).
+    /// Forks depend only on the seed and the stream, not on how much this generator has been used.
+    pub fn fork(&self, stream: u64) -> Self {
+        let seed = self.seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        Self {
+            seed,
+            rng: Rand32::new_inc(seed, stream),
+        }
+    }
+
+    pub fn rand_u32(&mut self) -> u32 {
+        self.rng.rand_u32()
+    }
+
+    pub fn rand_range(&mut self, range: Range<u32>) -> u32 {
+        self.rng.rand_range(range)
+    }
+
+    /// Fisher-Yates shuffle driven by this generator
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.rand_range(0..(i as u32 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_past_generation() {
+        let mut ours = TruncateRng::new(1234);
+        let mut theirs = Rand32::new(1234);
+        for _ in 0..20 {
+            assert_eq!(ours.rand_u32(), theirs.rand_u32());
+        }
+        assert_eq!(ours.rand_range(0..10), theirs.rand_range(0..10));
+    }
+
+    #[test]
+    fn forks_are_independent() {
+        let mut rng = TruncateRng::new(99);
+        let fresh: Vec<_> = {
+            let mut fork = rng.fork(NPC_STREAM);
+            (0..10).map(|_| fork.rand_u32()).collect()
+        };
+
+        // Using the parent doesn't move the fork
+        for _ in 0..50 {
+            rng.rand_u32();
+        }
+        let mut fork = rng.fork(NPC_STREAM);
+        let used: Vec<_> = (0..10).map(|_| fork.rand_u32()).collect();
+        assert_eq!(fresh, used);
+
+        let mut other = rng.fork(NPC_STREAM + 1);
+        let other: Vec<_> = (0..10).map(|_| other.rand_u32()).collect();
+        assert_ne!(fresh, other);
+    }
+
+    #[test]
+    fn shuffles_reproducibly() {
+        let shuffled = |seed| {
+            let mut items: Vec<_> = (0..20).collect();
+            TruncateRng::new(seed).shuffle(&mut items);
+            items
+        };
+
+        assert_eq!(shuffled(5), shuffled(5));
+        assert_ne!(shuffled(5), shuffled(6));
+
+        let mut sorted = shuffled(5);
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,238 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bag::{generation_distribution, TileDistribution},
+    generation::BoardSeed,
+    npc::progression::rungs,
+};
+
+/// The day since the unix epoch of the first daily puzzle, January 29, 2024
+pub const DAILY_PUZZLE_DAY_ZERO: u32 = 19751;
+/// Players' clocks run from twelve hours behind UTC to fourteen hours ahead of it
+const EARLIEST_UTC_OFFSET_SECS: u64 = 12 * 60 * 60;
+const LATEST_UTC_OFFSET_SECS: u64 = 14 * 60 * 60;
+const SECS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// How many days each themed event runs for before the next theme takes over
+pub const THEME_EVENT_DAYS: u32 = 7;
+/// Keeps the seeds of each category's puzzles clear of the daily puzzle's, which are seeded by day
+const WEEKLY_SEED_BASE: u32 = 1 << 24;
+const THEMED_SEED_BASE: u32 = 1 << 25;
+
+const VOWELS: [usize; 5] = [0, 4, 8, 14, 20];
+
+/// A twist on the bag that a themed event's puzzles are played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PuzzleTheme {
+    /// Twice as many vowels in the bag
+    VowelHeavy,
+    /// Half as many vowels in the bag
+    ConsonantHeavy,
+}
+
+impl PuzzleTheme {
+    /// Themes take turns running, in this order
+    pub const ALL: [PuzzleTheme; 2] = [PuzzleTheme::VowelHeavy, PuzzleTheme::ConsonantHeavy];
+
+    /// The theme's bag, based on the given generation of English tiles
+    pub fn tile_distribution(&self, tile_generation: u32) -> Option<TileDistribution> {
+        let mut distribution = generation_distribution(tile_generation)?;
+        for vowel in VOWELS {
+            distribution[vowel] = match self {
+                PuzzleTheme::VowelHeavy => distribution[vowel] * 2,
+                PuzzleTheme::ConsonantHeavy => (distribution[vowel] / 2).max(1),
+            };
+        }
+        Some(distribution)
+    }
+}
+
+/// Which run of puzzles a puzzle belongs to. Each category counts its puzzles by its own periods,
+/// in days for the daily puzzle and weeks for the weekly puzzle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PuzzleCategory {
+    #[default]
+    Daily,
+    /// A puzzle for the whole week, against the top rung of the NPC ladder
+    WeeklyHard,
+    /// A puzzle for each themed event, played with the theme's bag
+    Themed(PuzzleTheme),
+}
+
+impl PuzzleCategory {
+    /// The name attempts in the category are stored under, which must never change
+    pub fn key(&self) -> &'static str {
+        match self {
+            PuzzleCategory::Daily => "daily",
+            PuzzleCategory::WeeklyHard => "weekly_hard",
+            PuzzleCategory::Themed(PuzzleTheme::VowelHeavy) => "themed_vowel_heavy",
+            PuzzleCategory::Themed(PuzzleTheme::ConsonantHeavy) => "themed_consonant_heavy",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [PuzzleCategory::Daily, PuzzleCategory::WeeklyHard]
+            .into_iter()
+            .chain(PuzzleTheme::ALL.map(PuzzleCategory::Themed))
+            .find(|category| category.key() == key)
+    }
+
+    /// Which of the category's puzzles is being played on the given puzzle day
+    pub fn period(&self, day: u32) -> u32 {
+        match self {
+            PuzzleCategory::Daily => day,
+            PuzzleCategory::WeeklyHard => day / 7,
+            PuzzleCategory::Themed(_) => day / THEME_EVENT_DAYS,
+        }
+    }
+
+    /// The first puzzle day of one of the category's periods, which its rules are picked for
+    pub fn first_day(&self, period: u32) -> u32 {
+        match self {
+            PuzzleCategory::Daily => period,
+            PuzzleCategory::WeeklyHard => period * 7,
+            PuzzleCategory::Themed(_) => period * THEME_EVENT_DAYS,
+        }
+    }
+
+    /// The board for one of the category's puzzles. Daily puzzles are instead picked
+    /// by the dueller, which checks each day's board can be won.
+    pub fn board_seed(&self, period: u32) -> Option<BoardSeed> {
+        let seed = match self {
+            PuzzleCategory::Daily => return None,
+            PuzzleCategory::WeeklyHard => WEEKLY_SEED_BASE + period,
+            PuzzleCategory::Themed(theme) => {
+                THEMED_SEED_BASE + period * PuzzleTheme::ALL.len() as u32 + *theme as u32
+            }
+        };
+        Some(BoardSeed::new(seed).day(period))
+    }
+
+    /// The rung of the NPC ladder that plays the category's puzzles, if not the usual opponent
+    pub fn npc_rung(&self) -> Option<u32> {
+        match self {
+            PuzzleCategory::WeeklyHard => Some(rungs().len().saturating_sub(1) as u32),
+            PuzzleCategory::Daily | PuzzleCategory::Themed(_) => None,
+        }
+    }
+
+    pub fn tile_distribution(&self, tile_generation: u32) -> Option<TileDistribution> {
+        match self {
+            PuzzleCategory::Themed(theme) => theme.tile_distribution(tile_generation),
+            PuzzleCategory::Daily | PuzzleCategory::WeeklyHard => None,
+        }
+    }
+
+    /// Every category with a puzzle on the given day, alongside which of its puzzles is running
+    pub fn running(day: u32) -> Vec<(PuzzleCategory, u32)> {
+        let event = day / THEME_EVENT_DAYS;
+        let theme = PuzzleTheme::ALL[event as usize % PuzzleTheme::ALL.len()];
+
+        [
+            PuzzleCategory::Daily,
+            PuzzleCategory::WeeklyHard,
+            PuzzleCategory::Themed(theme),
+        ]
+        .into_iter()
+        .map(|category| (category, category.period(day)))
+        .collect()
+    }
+}
+
+/// The puzzle days it is somewhere in the world at the given time, in seconds since the unix epoch.
+/// Puzzles follow each player's local day, so up to three can be live at once.
+pub fn live_puzzle_days(now_secs: u64) -> RangeInclusive<u32> {
+    let puzzle_day = |secs: u64| (secs / SECS_PER_DAY).saturating_sub(DAILY_PUZZLE_DAY_ZERO as u64);
+    let earliest = puzzle_day(now_secs.saturating_sub(EARLIEST_UTC_OFFSET_SECS));
+    let latest = puzzle_day(now_secs + LATEST_UTC_OFFSET_SECS);
+    earliest as u32..=latest as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bag::has_vowel, game::Game, rules::GameRules};
+
+    #[test]
+    fn live_puzzle_days_follow_the_time_zones() {
+        let hour = 60 * 60;
+        let tenth_day = (DAILY_PUZZLE_DAY_ZERO as u64 + 10) * SECS_PER_DAY;
+
+        // In the morning UTC, the Americas are still on the day before
+        assert_eq!(live_puzzle_days(tenth_day + 9 * hour), 9..=10);
+        // In the afternoon UTC, the Pacific has moved on to the next day
+        assert_eq!(live_puzzle_days(tenth_day + 13 * hour), 10..=11);
+        // Around midday UTC, both are true at once
+        assert_eq!(live_puzzle_days(tenth_day + 11 * hour), 9..=11);
+    }
+
+    #[test]
+    fn categories_are_stored_by_key() {
+        for (category, _) in PuzzleCategory::running(0)
+            .into_iter()
+            .chain(PuzzleCategory::running(THEME_EVENT_DAYS))
+        {
+            assert_eq!(PuzzleCategory::from_key(category.key()), Some(category));
+        }
+        assert_eq!(PuzzleCategory::from_key("puzzle"), None);
+    }
+
+    #[test]
+    fn weekly_puzzles_last_the_week() {
+        let category = PuzzleCategory::WeeklyHard;
+        assert_eq!(category.period(7), category.period(13));
+        assert_ne!(category.period(13), category.period(14));
+        assert_eq!(category.first_day(category.period(13)), 7);
+
+        let seed = |period| category.board_seed(period).unwrap().seed;
+        assert_ne!(seed(1), seed(2));
+        assert_ne!(
+            seed(1),
+            PuzzleCategory::Themed(PuzzleTheme::VowelHeavy)
+                .board_seed(1)
+                .unwrap()
+                .seed
+        );
+        assert!(PuzzleCategory::Daily.board_seed(1).is_none());
+    }
+
+    #[test]
+    fn themes_take_turns() {
+        let theme_on = |day| {
+            PuzzleCategory::running(day)
+                .into_iter()
+                .find_map(|(category, _)| match category {
+                    PuzzleCategory::Themed(theme) => Some(theme),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        assert_eq!(theme_on(0), theme_on(THEME_EVENT_DAYS - 1));
+        assert_ne!(theme_on(0), theme_on(THEME_EVENT_DAYS));
+        assert_eq!(theme_on(0), theme_on(THEME_EVENT_DAYS * 2));
+    }
+
+    #[test]
+    fn themed_bags_change_the_vowels() {
+        let rules = GameRules::latest(None).1;
+        let vowels_dealt = |theme: PuzzleTheme| {
+            let mut vowels = 0;
+            for seed in 0..50 {
+                let mut game = Game::new(9, 9, Some(seed), rules.clone());
+                game.add_player("A".into());
+                game.use_tile_distribution(theme.tile_distribution(rules.tile_generation).unwrap());
+                vowels += game.players[0]
+                    .hand
+                    .iter()
+                    .filter(|tile| has_vowel(&[**tile]))
+                    .count();
+            }
+            vowels
+        };
+
+        assert!(vowels_dealt(PuzzleTheme::VowelHeavy) > vowels_dealt(PuzzleTheme::ConsonantHeavy));
+    }
+}
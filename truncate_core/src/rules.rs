@@ -1,5 +1,3 @@
-// TODO: Maximum consecutive swaps / stalemate rule
-
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -7,23 +5,24 @@ use crate::{
     generation::{
         ArtifactType, BoardElements, BoardNoiseParams, BoardParams, BoardSeed, Symmetry, WaterLayer,
     },
+    packs::PackRef,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TownDefense {
     BeatenByContact,
     BeatenByValidity,
     BeatenWithDefenseStrength(usize),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ArtifactDefense {
     Invincible,
     BeatenWithDefenseStrength(usize),
 }
 
 /// Conditions which, when hit, end the game and mark a winner
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WinCondition {
     Destination {
         town_defense: TownDefense,
@@ -33,34 +32,49 @@ pub enum WinCondition {
 }
 
 /// Metrics to used to assign a winner when no condition was hit
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WinMetric {
     TownProximity,
     ObeliskProximity,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Visibility {
     Standard,
     TileFog,
     LandFog,
     OnlyHouseFog,
+    /// Land fog that starts close around the player's dock, and lifts as their tiles spread.
+    /// The board keeps its full size, so coordinates are never remapped.
+    Mystery,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BoardOrientation {
     Standard,
     FacingPlayer,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Truncation {
     Root,
     Larger, // TODO: Implement
     None,
+    /// Tiles cut off from their artifact wither rather than being truncated straight away,
+    /// and are only truncated if their owner's next turn doesn't reconnect them
+    Withering,
+}
+
+/// Experimental: whether tiles move to fill gaps left behind by truncation
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Gravity {
+    #[default]
+    None,
+    /// Tiles slide back towards their own artifact, one square at a time, while they stay rooted
+    TowardsRoot,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OvertimeRule {
     FreeWildcard { period: usize },
     Bomb { period: usize },
@@ -68,11 +82,20 @@ pub enum OvertimeRule {
     Elimination,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Timing {
     PerPlayer {
         time_allowance: usize,
         overtime_rule: OvertimeRule,
+        /// Seconds added to a player's clock after each of their turns (a Fischer increment)
+        #[serde(default)]
+        increment: usize,
+        /// Seconds at the start of each turn that don't come off the clock (a Bronstein delay)
+        #[serde(default)]
+        delay: usize,
+        /// The longest any one turn can take, in seconds, before the player loses on time
+        #[serde(default)]
+        move_limit: Option<usize>,
     },
     PerTurn {
         // TODO: Implement
@@ -85,25 +108,49 @@ pub enum Timing {
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TileBagBehaviour {
     Standard,
     Infinite, // TODO: Implement
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How players restock their hand after placing a tile
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TileDraw {
+    #[default]
+    /// Draw back up to a full hand after every placement
+    FullHand,
+    /// Draw a replacement for each tile placed, up to this many each turn
+    Fixed { draws: usize },
+    /// Never draw, so each player only has their starting hand to play with
+    None,
+}
+
+/// How closely a word needs to match the dictionary to be valid
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Spelling {
+    #[default]
+    Strict,
+    /// Words one letter away from a dictionary word (a letter added, removed, or changed)
+    /// are read as that word. Intended for young players.
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BattleRules {
     pub length_delta: isize,
+    #[serde(default)]
+    pub spelling: Spelling,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Swapping {
     Contiguous(SwapPenalty),
     Universal(SwapPenalty),
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SwapPenalty {
     Time {
         swap_threshold: usize,
@@ -114,7 +161,220 @@ pub enum SwapPenalty {
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How a game is resolved once it is found to be going nowhere
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StalemateOutcome {
+    Draw,
+    /// The player with the most tiles on the board wins, or the game is drawn on a tie
+    MostTerritory,
+}
+
+/// Stops games from cycling through the same positions forever
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RepetitionRule {
+    /// The game is resolved once any position has been reached this many times
+    Limit {
+        occurrences: usize,
+        outcome: StalemateOutcome,
+    },
+    #[default]
+    None,
+}
+
+/// Stops games where players keep taking turns without gaining any tiles
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NullMoveRule {
+    /// The game is resolved after this many consecutive turns without progress
+    Limit {
+        turns: usize,
+        outcome: StalemateOutcome,
+    },
+    #[default]
+    None,
+}
+
+/// Whether players can have a battle re-judged against a wider dictionary
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Appeals {
+    #[default]
+    Disallowed,
+    /// Each player may appeal this many battles, whether or not the appeal succeeds
+    Allowed { per_player: usize },
+}
+
+/// Whether words lose defensive strength by sitting unchanged on the board
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WordAging {
+    #[default]
+    None,
+    /// Once a word has gone this many turns without any of its tiles changing,
+    /// it defends as if it were a letter shorter for every further turn
+    Weaken { grace_turns: u32 },
+}
+
+/// A measure that picks a winner from a game that would otherwise be drawn,
+/// going to whichever player alone has the most of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tiebreaker {
+    /// Tiles on the board
+    Territory,
+    /// Tiles left in hand, still to be played
+    TilesRemaining,
+    /// Letters across every word on the board
+    WordLength,
+}
+
+impl Tiebreaker {
+    pub const ALL: [Tiebreaker; 3] = [
+        Tiebreaker::Territory,
+        Tiebreaker::TilesRemaining,
+        Tiebreaker::WordLength,
+    ];
+}
+
+impl std::fmt::Display for Tiebreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tiebreaker::Territory => write!(f, "territory"),
+            Tiebreaker::TilesRemaining => write!(f, "tiles remaining"),
+            Tiebreaker::WordLength => write!(f, "total word length"),
+        }
+    }
+}
+
+/// Whether the board has hills that strengthen the words built on them
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Terrain {
+    #[default]
+    Flat,
+    /// Patches of land are raised into high ground when the game starts,
+    /// and words with a tile on high ground defend as if they were a letter longer
+    Elevated,
+}
+
+/// Whether a player can redraw a poor starting hand, once and without penalty, before their first turn
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Mulligan {
+    #[default]
+    None,
+    /// Hands without a vowel can be redrawn
+    NoVowels,
+    /// Hands without a vowel, or that stray further than this many bits from the odds of the bag,
+    /// such as hands full of repeated or rare tiles, can be redrawn
+    Entropy { max_divergence_bits: f32 },
+}
+
+impl Mulligan {
+    /// Redraws hands that are missing vowels, or that lean heavily on repeated or rare tiles
+    pub fn entropy() -> Self {
+        Mulligan::Entropy {
+            max_divergence_bits: 2.5,
+        }
+    }
+}
+
+/// Chaos rule for casual rooms, where hands change owners on a fixed schedule
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HandSwaps {
+    #[default]
+    None,
+    /// After every this many turns, each hand is passed along to the next player
+    Every { turns: u32 },
+}
+
+/// How much a player can do with each of their turns
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TurnEconomy {
+    /// Each placement or swap ends the turn
+    #[default]
+    SingleAction,
+    /// Experimental: each turn grants a budget of points, which the player spends on as many actions as it covers.
+    /// The turn ends once the points left can't pay for a placement.
+    ActionPoints {
+        per_turn: u32,
+        place_cost: u32,
+        swap_cost: u32,
+        /// Returning a tile from the hand to the bag for a new one, which can only be done with action points
+        exchange_cost: u32,
+    },
+}
+
+impl TurnEconomy {
+    pub fn action_points() -> Self {
+        TurnEconomy::ActionPoints {
+            per_turn: 4,
+            place_cost: 2,
+            swap_cost: 1,
+            exchange_cost: 3,
+        }
+    }
+
+    /// The points each player has to spend on their turn
+    pub fn budget(&self) -> u32 {
+        match self {
+            TurnEconomy::SingleAction => 1,
+            TurnEconomy::ActionPoints { per_turn, .. } => *per_turn,
+        }
+    }
+
+    /// What placing a tile costs. A turn carries on for as long as the player can afford another placement,
+    /// so that a player is never left holding points they can't spend.
+    pub fn place_cost(&self) -> u32 {
+        match self {
+            TurnEconomy::SingleAction => 1,
+            TurnEconomy::ActionPoints { place_cost, .. } => *place_cost,
+        }
+    }
+}
+
+/// The language a game is played in, which decides the letters in the bag
+/// and the dictionary that words are checked against
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    French,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::English,
+        Language::Spanish,
+        Language::French,
+        Language::German,
+    ];
+
+    /// The ISO 639-1 code for the language, as used to name its word list
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+            Language::French => "fr",
+            Language::German => "de",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        Language::ALL.into_iter().find(|l| l.code() == code)
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::English => write!(f, "English"),
+            Language::Spanish => write!(f, "Español"),
+            Language::French => write!(f, "Français"),
+            Language::German => write!(f, "Deutsch"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BoardGenesis {
     Passthrough,
     SpecificBoard(Board),
@@ -122,7 +382,7 @@ pub enum BoardGenesis {
     Random(BoardParams),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameRules {
     pub generation: Option<u32>,
     pub win_condition: WinCondition,
@@ -130,21 +390,484 @@ pub struct GameRules {
     pub visibility: Visibility,
     pub board_orientation: BoardOrientation,
     pub truncation: Truncation,
+    #[serde(default)]
+    pub gravity: Gravity,
     pub timing: Timing,
     pub hand_size: usize,
     pub tile_generation: u32,
+    /// Games from before languages were added are all English
+    #[serde(default)]
+    pub language: Language,
+    /// A downloadable word list to judge words against, rather than the language's usual dictionary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_pack: Option<PackRef>,
+    /// How many blank tiles are added to the bag, which can be played as any letter
+    #[serde(default)]
+    pub blank_tiles: usize,
     pub tile_bag_behaviour: TileBagBehaviour,
+    #[serde(default)]
+    pub tile_draw: TileDraw,
     pub battle_rules: BattleRules,
     pub swapping: Swapping,
     pub battle_delay: u64,
     pub max_turns: Option<u64>,
+    #[serde(default)]
+    pub repetition: RepetitionRule,
+    #[serde(default)]
+    pub null_moves: NullMoveRule,
+    #[serde(default)]
+    pub appeals: Appeals,
+    #[serde(default)]
+    pub word_aging: WordAging,
+    #[serde(default)]
+    pub terrain: Terrain,
+    #[serde(default)]
+    pub hand_swaps: HandSwaps,
+    #[serde(default)]
+    pub turn_economy: TurnEconomy,
+    /// Tried in order whenever the game would be drawn, with the game only drawn if none separate the players
+    #[serde(default)]
+    pub tiebreakers: Vec<Tiebreaker>,
+    #[serde(default)]
+    pub mulligan: Mulligan,
     pub board_genesis: BoardGenesis,
 }
 
+/// Something about a set of rules that breaks the game, or makes it a chore to play
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RulesWarning {
+    /// Players would never have a tile to place
+    EmptyHand,
+    /// A player's clock would run out before they could move
+    NoTimeAllowed,
+    /// The game would end before anyone took a turn
+    NoTurnsAllowed,
+    /// Every position counts as repeated, ending the game on the first move
+    RepetitionLimitTooLow { occurrences: usize },
+    /// The game would end as soon as anyone failed to gain a tile
+    NullMoveLimitTooLow,
+    /// The game doesn't support this rule yet
+    Unimplemented(String),
+    /// Battles would be won by the shorter word
+    ShorterWordsWin,
+    /// Tiles on the board can never be swapped
+    NoSwapsAllowed,
+    /// Hands are set to be swapped, but never would be
+    NoHandSwaps,
+    /// A turn's action points wouldn't cover placing a tile
+    UnaffordableActions,
+    /// A setting is beyond what the server is willing to host
+    TooLarge { rule: String, max: u64 },
+}
+
+impl RulesWarning {
+    /// Whether a game with these rules can't be played at all, rather than just being odd
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            RulesWarning::ShorterWordsWin
+                | RulesWarning::NoSwapsAllowed
+                | RulesWarning::NoHandSwaps
+        )
+    }
+}
+
+impl std::fmt::Display for RulesWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulesWarning::EmptyHand => write!(f, "Hands need at least one tile"),
+            RulesWarning::NoTimeAllowed => write!(f, "Players need some time on their clock"),
+            RulesWarning::NoTurnsAllowed => {
+                write!(f, "The turn limit must allow at least one turn")
+            }
+            RulesWarning::RepetitionLimitTooLow { occurrences } => write!(
+                f,
+                "A repetition limit of {occurrences} would end the game immediately"
+            ),
+            RulesWarning::NullMoveLimitTooLow => {
+                write!(f, "The null move limit must allow at least one turn")
+            }
+            RulesWarning::Unimplemented(rule) => write!(f, "{rule} isn't supported yet"),
+            RulesWarning::ShorterWordsWin => write!(f, "Shorter words will win battles"),
+            RulesWarning::NoSwapsAllowed => write!(f, "Tiles can never be swapped"),
+            RulesWarning::NoHandSwaps => write!(f, "Hands would never be swapped"),
+            RulesWarning::UnaffordableActions => {
+                write!(f, "Each turn needs enough action points to place a tile")
+            }
+            RulesWarning::TooLarge { rule, max } => write!(f, "{rule} can be at most {max}"),
+        }
+    }
+}
+
+/// The most tiles a hand can hold
+pub const MAX_HAND_SIZE: usize = 20;
+/// The longest any clock setting can be, in seconds, which leaves room for correspondence games
+pub const MAX_TIME_ALLOWANCE: usize = 30 * 24 * 60 * 60;
+/// The widest or tallest a board can be, in squares
+pub const MAX_BOARD_DIMENSION: usize = 200;
+/// The highest turn limit a game can have
+pub const MAX_TURNS: u64 = 5000;
+/// The longest pause after a battle, in seconds
+pub const MAX_BATTLE_DELAY: u64 = 10;
+/// The most action points a turn can grant
+pub const MAX_ACTION_POINTS: u32 = 20;
+
+/// Checks custom rule combinations for anything that would make a game unplayable
+pub struct RulesValidator;
+
+impl RulesValidator {
+    pub fn check(rules: &GameRules) -> Vec<RulesWarning> {
+        let mut warnings = Self::check_limits(rules);
+        let mut unimplemented =
+            |rule: &str| warnings.push(RulesWarning::Unimplemented(rule.into()));
+
+        if matches!(rules.win_condition, WinCondition::Elimination) {
+            unimplemented("Elimination");
+        }
+        if matches!(rules.truncation, Truncation::Larger) {
+            unimplemented("Larger truncation");
+        }
+        if matches!(rules.tile_bag_behaviour, TileBagBehaviour::Infinite) {
+            unimplemented("An infinite tile bag");
+        }
+        match &rules.timing {
+            Timing::PerTurn { .. } => unimplemented("Per turn timing"),
+            Timing::PerPlayer {
+                overtime_rule: OvertimeRule::RemoveTiles { .. },
+                ..
+            } => unimplemented("Removing tiles in overtime"),
+            _ => {}
+        }
+
+        if rules.hand_size == 0 {
+            warnings.push(RulesWarning::EmptyHand);
+        }
+
+        match &rules.timing {
+            Timing::PerPlayer {
+                time_allowance: 0, ..
+            }
+            | Timing::PerPlayer {
+                move_limit: Some(0),
+                ..
+            }
+            | Timing::Periodic {
+                total_time_allowance: 0,
+                ..
+            } => warnings.push(RulesWarning::NoTimeAllowed),
+            _ => {}
+        }
+
+        if rules.max_turns == Some(0) {
+            warnings.push(RulesWarning::NoTurnsAllowed);
+        }
+
+        if let RepetitionRule::Limit { occurrences, .. } = rules.repetition {
+            if occurrences < 2 {
+                warnings.push(RulesWarning::RepetitionLimitTooLow { occurrences });
+            }
+        }
+
+        if let NullMoveRule::Limit { turns: 0, .. } = rules.null_moves {
+            warnings.push(RulesWarning::NullMoveLimitTooLow);
+        }
+
+        if rules.battle_rules.length_delta < 0 {
+            warnings.push(RulesWarning::ShorterWordsWin);
+        }
+
+        match &rules.swapping {
+            Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 0 })
+            | Swapping::Universal(SwapPenalty::Disallowed { allowed_swaps: 0 })
+            | Swapping::None => warnings.push(RulesWarning::NoSwapsAllowed),
+            _ => {}
+        }
+
+        if let HandSwaps::Every { turns: 0 } = rules.hand_swaps {
+            warnings.push(RulesWarning::NoHandSwaps);
+        }
+
+        if rules.turn_economy.budget() < rules.turn_economy.place_cost() {
+            warnings.push(RulesWarning::UnaffordableActions);
+        }
+
+        warnings
+    }
+
+    pub fn is_playable(rules: &GameRules) -> bool {
+        !Self::check(rules).iter().any(RulesWarning::is_fatal)
+    }
+
+    /// Flags every setting that would have the server hold or simulate more than it should
+    fn check_limits(rules: &GameRules) -> Vec<RulesWarning> {
+        let mut warnings = vec![];
+        let mut cap = |rule: &str, value: u64, max: u64| {
+            if value > max {
+                warnings.push(RulesWarning::TooLarge {
+                    rule: rule.into(),
+                    max,
+                });
+            }
+        };
+
+        cap("Hand size", rules.hand_size as u64, MAX_HAND_SIZE as u64);
+        cap("Battle delay", rules.battle_delay, MAX_BATTLE_DELAY);
+        cap("Turn limit", rules.max_turns.unwrap_or(0), MAX_TURNS);
+
+        let max_time = MAX_TIME_ALLOWANCE as u64;
+        match &rules.timing {
+            Timing::PerPlayer {
+                time_allowance,
+                increment,
+                delay,
+                move_limit,
+                ..
+            } => {
+                cap("Time allowance", *time_allowance as u64, max_time);
+                cap("Increment", *increment as u64, max_time);
+                cap("Delay", *delay as u64, max_time);
+                cap("Move limit", move_limit.unwrap_or(0) as u64, max_time);
+            }
+            Timing::PerTurn { time_allowance } => {
+                cap("Time allowance", *time_allowance as u64, max_time);
+            }
+            Timing::Periodic {
+                turn_delay,
+                total_time_allowance,
+            } => {
+                cap("Turn delay", *turn_delay as u64, max_time);
+                cap("Time allowance", *total_time_allowance as u64, max_time);
+            }
+            Timing::None => {}
+        }
+
+        let max_dimension = MAX_BOARD_DIMENSION as u64;
+        let dimensions = match &rules.board_genesis {
+            BoardGenesis::Passthrough => vec![],
+            BoardGenesis::SpecificBoard(board) => vec![board.width(), board.height()],
+            BoardGenesis::Classic(width, height) => vec![*width, *height],
+            BoardGenesis::Random(params) => params
+                .land_dimensions
+                .into_iter()
+                .chain(params.canvas_dimensions)
+                .collect(),
+        };
+        for dimension in dimensions {
+            cap("Board size", dimension as u64, max_dimension);
+        }
+
+        if let TurnEconomy::ActionPoints { per_turn, .. } = rules.turn_economy {
+            cap("Action points", per_turn as u64, MAX_ACTION_POINTS as u64);
+        }
+
+        warnings
+    }
+}
+
+/// Builds a custom set of rules on top of an existing one, checking that the result can be played
+#[derive(Debug, Clone)]
+pub struct GameRulesBuilder {
+    rules: GameRules,
+}
+
+impl GameRulesBuilder {
+    pub fn new(base: GameRules) -> Self {
+        Self { rules: base }
+    }
+
+    pub fn hand_size(mut self, hand_size: usize) -> Self {
+        self.rules.hand_size = hand_size;
+        self
+    }
+
+    /// How many letters longer an attacking word must be than a defending word to beat it,
+    /// so a delta of zero gives ties to the attacker and a delta of one gives them to the defender
+    pub fn battle_length_delta(mut self, length_delta: isize) -> Self {
+        self.rules.battle_rules.length_delta = length_delta;
+        self
+    }
+
+    pub fn spelling(mut self, spelling: Spelling) -> Self {
+        self.rules.battle_rules.spelling = spelling;
+        self
+    }
+
+    pub fn truncation(mut self, truncation: Truncation) -> Self {
+        self.rules.truncation = truncation;
+        self
+    }
+
+    pub fn swapping(mut self, swapping: Swapping) -> Self {
+        self.rules.swapping = swapping;
+        self
+    }
+
+    pub fn timing(mut self, timing: Timing) -> Self {
+        self.rules.timing = timing;
+        self
+    }
+
+    /// Sets what happens once a player's clock runs out.
+    /// Only games timed per player have an overtime, so this does nothing for other timings.
+    pub fn overtime(mut self, overtime: OvertimeRule) -> Self {
+        if let Timing::PerPlayer { overtime_rule, .. } = &mut self.rules.timing {
+            *overtime_rule = overtime;
+        }
+        self
+    }
+
+    /// Sets the seconds given back to a player after each turn.
+    /// Like the overtime, this only applies to games timed per player.
+    pub fn increment(mut self, secs: usize) -> Self {
+        if let Timing::PerPlayer { increment, .. } = &mut self.rules.timing {
+            *increment = secs;
+        }
+        self
+    }
+
+    pub fn delay(mut self, secs: usize) -> Self {
+        if let Timing::PerPlayer { delay, .. } = &mut self.rules.timing {
+            *delay = secs;
+        }
+        self
+    }
+
+    pub fn move_limit(mut self, secs: Option<usize>) -> Self {
+        if let Timing::PerPlayer { move_limit, .. } = &mut self.rules.timing {
+            *move_limit = secs;
+        }
+        self
+    }
+
+    pub fn win_condition(mut self, win_condition: WinCondition) -> Self {
+        self.rules.win_condition = win_condition;
+        self
+    }
+
+    pub fn turn_economy(mut self, turn_economy: TurnEconomy) -> Self {
+        self.rules.turn_economy = turn_economy;
+        self
+    }
+
+    pub fn terrain(mut self, terrain: Terrain) -> Self {
+        self.rules.terrain = terrain;
+        self
+    }
+
+    pub fn tiebreakers(mut self, tiebreakers: Vec<Tiebreaker>) -> Self {
+        self.rules.tiebreakers = tiebreakers;
+        self
+    }
+
+    pub fn mulligan(mut self, mulligan: Mulligan) -> Self {
+        self.rules.mulligan = mulligan;
+        self
+    }
+
+    /// The finished rules, or everything that makes them unplayable
+    pub fn build(self) -> Result<GameRules, Vec<RulesWarning>> {
+        let fatal: Vec<_> = RulesValidator::check(&self.rules)
+            .into_iter()
+            .filter(RulesWarning::is_fatal)
+            .collect();
+
+        if fatal.is_empty() {
+            Ok(self.rules)
+        } else {
+            Err(fatal)
+        }
+    }
+}
+
+/// Named rulesets that rooms can start from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RulesPreset {
+    /// The rules of the day, as played in every room by default
+    Classic,
+    /// A smaller hand and a short clock, for quick games
+    Blitz,
+    /// Forgiving rules for new and young players, where nothing is cut off the board
+    Gentle,
+    /// A day to make each move, for games played out over a couple of weeks
+    Correspondence,
+}
+
+impl RulesPreset {
+    pub const ALL: [RulesPreset; 4] = [
+        RulesPreset::Classic,
+        RulesPreset::Blitz,
+        RulesPreset::Gentle,
+        RulesPreset::Correspondence,
+    ];
+
+    pub fn builder(&self) -> GameRulesBuilder {
+        let classic = GameRulesBuilder::new(GameRules::latest(None).1);
+
+        match self {
+            RulesPreset::Classic => classic,
+            RulesPreset::Blitz => classic.hand_size(5).timing(Timing::PerPlayer {
+                time_allowance: 3 * 60,
+                overtime_rule: OvertimeRule::Elimination,
+                increment: 2,
+                delay: 0,
+                move_limit: None,
+            }),
+            RulesPreset::Gentle => classic
+                .battle_length_delta(2)
+                .spelling(Spelling::Lenient)
+                .truncation(Truncation::None)
+                .swapping(Swapping::Contiguous(SwapPenalty::Disallowed {
+                    allowed_swaps: 3,
+                }))
+                .win_condition(WinCondition::Destination {
+                    town_defense: TownDefense::BeatenWithDefenseStrength(0),
+                    artifact_defense: ArtifactDefense::Invincible,
+                }),
+            RulesPreset::Correspondence => classic.timing(Timing::PerPlayer {
+                time_allowance: 14 * 24 * 60 * 60,
+                overtime_rule: OvertimeRule::Elimination,
+                increment: 0,
+                delay: 0,
+                move_limit: Some(24 * 60 * 60),
+            }),
+        }
+    }
+
+    pub fn rules(&self) -> GameRules {
+        self.builder().build().expect("presets should be playable")
+    }
+
+    /// The preset that a room's rules were set from, if they haven't been changed since.
+    /// The language and blank tiles are chosen separately, so they aren't compared.
+    pub fn matching(rules: &GameRules) -> Option<RulesPreset> {
+        Self::ALL.into_iter().find(|preset| {
+            let mut preset_rules = preset.rules();
+            preset_rules.generation = rules.generation;
+            preset_rules.language = rules.language;
+            preset_rules.blank_tiles = rules.blank_tiles;
+            preset_rules == *rules
+        })
+    }
+}
+
+impl std::fmt::Display for RulesPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulesPreset::Classic => write!(f, "Classic"),
+            RulesPreset::Blitz => write!(f, "Blitz"),
+            RulesPreset::Gentle => write!(f, "Gentle"),
+            RulesPreset::Correspondence => write!(f, "Correspondence"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct EffectiveRuleDay(u32);
 
-const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 3] = [
+// Do not modify any published generations.
+// Add a new generation, effective from a future day, to change the rules.
+// Updating an existing generation changes how old puzzles and replays play out.
+const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 4] = [
     (
         None,
         GameRules {
@@ -157,14 +880,31 @@ const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 3] = [
             visibility: Visibility::Standard,
             board_orientation: BoardOrientation::Standard,
             truncation: Truncation::Root,
+            gravity: Gravity::None,
             timing: Timing::None,
             hand_size: 7,
             tile_generation: 0,
+            language: Language::English,
+            dictionary_pack: None,
+            blank_tiles: 0,
             tile_bag_behaviour: TileBagBehaviour::Standard,
-            battle_rules: BattleRules { length_delta: 2 },
+            tile_draw: TileDraw::FullHand,
+            battle_rules: BattleRules {
+                length_delta: 2,
+                spelling: Spelling::Strict,
+            },
             swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
             battle_delay: 2,
             max_turns: None,
+            repetition: RepetitionRule::None,
+            null_moves: NullMoveRule::None,
+            appeals: Appeals::Disallowed,
+            word_aging: WordAging::None,
+            terrain: Terrain::Flat,
+            hand_swaps: HandSwaps::None,
+            turn_economy: TurnEconomy::SingleAction,
+            tiebreakers: Vec::new(),
+            mulligan: Mulligan::None,
             board_genesis: BoardGenesis::Passthrough,
         },
     ),
@@ -180,14 +920,31 @@ const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 3] = [
             visibility: Visibility::Standard,
             board_orientation: BoardOrientation::Standard,
             truncation: Truncation::Root,
+            gravity: Gravity::None,
             timing: Timing::None,
             hand_size: 7,
             tile_generation: 1,
+            language: Language::English,
+            dictionary_pack: None,
+            blank_tiles: 0,
             tile_bag_behaviour: TileBagBehaviour::Standard,
-            battle_rules: BattleRules { length_delta: 2 },
+            tile_draw: TileDraw::FullHand,
+            battle_rules: BattleRules {
+                length_delta: 2,
+                spelling: Spelling::Strict,
+            },
             swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
             battle_delay: 2,
             max_turns: None,
+            repetition: RepetitionRule::None,
+            null_moves: NullMoveRule::None,
+            appeals: Appeals::Disallowed,
+            word_aging: WordAging::None,
+            terrain: Terrain::Flat,
+            hand_swaps: HandSwaps::None,
+            turn_economy: TurnEconomy::SingleAction,
+            tiebreakers: Vec::new(),
+            mulligan: Mulligan::None,
             board_genesis: BoardGenesis::Passthrough,
         },
     ),
@@ -203,14 +960,77 @@ const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 3] = [
             visibility: Visibility::Standard,
             board_orientation: BoardOrientation::Standard,
             truncation: Truncation::Root,
+            gravity: Gravity::None,
+            timing: Timing::None,
+            hand_size: 7,
+            tile_generation: 1,
+            language: Language::English,
+            dictionary_pack: None,
+            blank_tiles: 0,
+            tile_bag_behaviour: TileBagBehaviour::Standard,
+            tile_draw: TileDraw::FullHand,
+            battle_rules: BattleRules {
+                length_delta: 1,
+                spelling: Spelling::Strict,
+            },
+            swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
+            battle_delay: 2,
+            max_turns: None,
+            repetition: RepetitionRule::None,
+            null_moves: NullMoveRule::None,
+            appeals: Appeals::Disallowed,
+            word_aging: WordAging::None,
+            terrain: Terrain::Flat,
+            hand_swaps: HandSwaps::None,
+            turn_economy: TurnEconomy::SingleAction,
+            tiebreakers: Vec::new(),
+            mulligan: Mulligan::None,
+            board_genesis: BoardGenesis::Passthrough,
+        },
+    ),
+    (
+        Some(EffectiveRuleDay(1000)),
+        GameRules {
+            generation: None, // hydrated on fetch
+            win_condition: WinCondition::Destination {
+                town_defense: TownDefense::BeatenWithDefenseStrength(0),
+                artifact_defense: ArtifactDefense::BeatenWithDefenseStrength(0),
+            },
+            win_metric: WinMetric::TownProximity,
+            visibility: Visibility::Standard,
+            board_orientation: BoardOrientation::Standard,
+            truncation: Truncation::Root,
+            gravity: Gravity::None,
             timing: Timing::None,
             hand_size: 7,
             tile_generation: 1,
+            language: Language::English,
+            dictionary_pack: None,
+            blank_tiles: 0,
             tile_bag_behaviour: TileBagBehaviour::Standard,
-            battle_rules: BattleRules { length_delta: 1 },
+            tile_draw: TileDraw::FullHand,
+            battle_rules: BattleRules {
+                length_delta: 1,
+                spelling: Spelling::Strict,
+            },
             swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
             battle_delay: 2,
             max_turns: None,
+            repetition: RepetitionRule::Limit {
+                occurrences: 3,
+                outcome: StalemateOutcome::Draw,
+            },
+            null_moves: NullMoveRule::Limit {
+                turns: 30,
+                outcome: StalemateOutcome::MostTerritory,
+            },
+            appeals: Appeals::Disallowed,
+            word_aging: WordAging::None,
+            terrain: Terrain::Flat,
+            hand_swaps: HandSwaps::None,
+            turn_economy: TurnEconomy::SingleAction,
+            tiebreakers: Vec::new(),
+            mulligan: Mulligan::None,
             board_genesis: BoardGenesis::Passthrough,
         },
     ),
@@ -218,12 +1038,14 @@ const RULE_GENERATIONS: [(Option<EffectiveRuleDay>, GameRules); 3] = [
 
 impl GameRules {
     pub fn generation(gen: u32) -> Self {
-        let (_, mut rules) = RULE_GENERATIONS
-            .get(gen as usize)
-            .expect("rule generation should exist")
-            .clone();
+        Self::checked_generation(gen).expect("rule generation should exist")
+    }
+
+    /// The rules of a generation, if there is one by that number
+    pub fn checked_generation(gen: u32) -> Option<Self> {
+        let (_, mut rules) = RULE_GENERATIONS.get(gen as usize)?.clone();
         rules.generation = Some(gen);
-        rules
+        Some(rules)
     }
 
     pub fn latest(effective_date: Option<u32>) -> (u32, Self) {
@@ -255,17 +1077,43 @@ impl GameRules {
             visibility: Visibility::LandFog,
             board_orientation: BoardOrientation::Standard,
             truncation: Truncation::None,
+            gravity: Gravity::None,
             timing: Timing::PerPlayer {
                 time_allowance: 75 * 60,
                 overtime_rule: OvertimeRule::Elimination,
+                increment: 0,
+                delay: 0,
+                move_limit: None,
             },
             hand_size: 7,
             tile_generation: 1,
+            language: Language::English,
+            dictionary_pack: None,
+            blank_tiles: 0,
             tile_bag_behaviour: TileBagBehaviour::Standard,
-            battle_rules: BattleRules { length_delta: 1 },
+            tile_draw: TileDraw::FullHand,
+            battle_rules: BattleRules {
+                length_delta: 1,
+                spelling: Spelling::Strict,
+            },
             swapping: Swapping::Contiguous(SwapPenalty::Disallowed { allowed_swaps: 1 }),
             battle_delay: 2,
             max_turns: Some(1050),
+            repetition: RepetitionRule::Limit {
+                occurrences: 3,
+                outcome: StalemateOutcome::Draw,
+            },
+            null_moves: NullMoveRule::Limit {
+                turns: 30,
+                outcome: StalemateOutcome::MostTerritory,
+            },
+            appeals: Appeals::Allowed { per_player: 1 },
+            word_aging: WordAging::None,
+            terrain: Terrain::Flat,
+            hand_swaps: HandSwaps::None,
+            turn_economy: TurnEconomy::SingleAction,
+            tiebreakers: Vec::new(),
+            mulligan: Mulligan::None,
             board_genesis: BoardGenesis::Random(BoardParams {
                 land_layer: BoardNoiseParams {
                     dispersion: [3.0, 3.0],
@@ -296,3 +1144,230 @@ impl GameRules {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_rules_are_playable() {
+        for gen in 0..RULE_GENERATIONS.len() as u32 {
+            assert_eq!(RulesValidator::check(&GameRules::generation(gen)), vec![]);
+        }
+        assert!(RulesValidator::is_playable(&GameRules::tuesday()));
+    }
+
+    #[test]
+    fn stalemate_rules_start_from_a_new_generation() {
+        for gen in 0..3 {
+            let rules = GameRules::generation(gen);
+            assert_eq!(rules.repetition, RepetitionRule::None);
+            assert_eq!(rules.null_moves, NullMoveRule::None);
+        }
+
+        assert_eq!(GameRules::latest(Some(999)).0, 2);
+        let (gen, rules) = GameRules::latest(Some(1000));
+        assert_eq!(gen, 3);
+        assert!(matches!(rules.repetition, RepetitionRule::Limit { .. }));
+        assert!(matches!(rules.null_moves, NullMoveRule::Limit { .. }));
+    }
+
+    #[test]
+    fn rules_stored_before_newer_fields_still_load() {
+        let mut stored = serde_json::to_value(GameRules::generation(0)).unwrap();
+        let fields = stored.as_object_mut().unwrap();
+        for added in [
+            "repetition",
+            "null_moves",
+            "appeals",
+            "word_aging",
+            "hand_swaps",
+            "tile_draw",
+            "gravity",
+        ] {
+            fields.remove(added);
+        }
+        let battle_rules = fields["battle_rules"].as_object_mut().unwrap();
+        battle_rules.remove("spelling");
+
+        let loaded: GameRules = serde_json::from_value(stored).unwrap();
+        assert_eq!(loaded, GameRules::generation(0));
+    }
+
+    #[test]
+    fn flags_degenerate_rules() {
+        let mut rules = GameRules::generation(2);
+        rules.hand_size = 0;
+        rules.swapping = Swapping::None;
+        rules.repetition = RepetitionRule::Limit {
+            occurrences: 1,
+            outcome: StalemateOutcome::Draw,
+        };
+
+        assert_eq!(
+            RulesValidator::check(&rules),
+            vec![
+                RulesWarning::EmptyHand,
+                RulesWarning::RepetitionLimitTooLow { occurrences: 1 },
+                RulesWarning::NoSwapsAllowed,
+            ]
+        );
+        assert!(!RulesValidator::is_playable(&rules));
+
+        // Odd but playable
+        let mut rules = GameRules::generation(2);
+        rules.battle_rules.length_delta = -1;
+        rules.hand_swaps = HandSwaps::Every { turns: 0 };
+        assert_eq!(
+            RulesValidator::check(&rules),
+            vec![RulesWarning::ShorterWordsWin, RulesWarning::NoHandSwaps]
+        );
+        assert!(RulesValidator::is_playable(&rules));
+
+        let mut rules = GameRules::generation(2);
+        rules.turn_economy = TurnEconomy::ActionPoints {
+            per_turn: 1,
+            place_cost: 2,
+            swap_cost: 1,
+            exchange_cost: 3,
+        };
+        assert_eq!(
+            RulesValidator::check(&rules),
+            vec![RulesWarning::UnaffordableActions]
+        );
+        assert!(!RulesValidator::is_playable(&rules));
+    }
+
+    #[test]
+    fn rejects_oversized_rules() {
+        let too_large = |rules: &GameRules| {
+            RulesValidator::check(rules)
+                .into_iter()
+                .filter(|w| matches!(w, RulesWarning::TooLarge { .. }))
+                .count()
+        };
+        let base = GameRules::generation(2);
+
+        let mut rules = base.clone();
+        rules.hand_size = MAX_HAND_SIZE;
+        assert_eq!(too_large(&rules), 0);
+        rules.hand_size = MAX_HAND_SIZE + 1;
+        assert_eq!(too_large(&rules), 1);
+        assert!(!RulesValidator::is_playable(&rules));
+
+        let mut rules = base.clone();
+        rules.battle_delay = MAX_BATTLE_DELAY + 1;
+        assert_eq!(too_large(&rules), 1);
+
+        let mut rules = base.clone();
+        rules.max_turns = Some(MAX_TURNS + 1);
+        assert_eq!(too_large(&rules), 1);
+
+        let mut rules = base.clone();
+        rules.timing = Timing::PerPlayer {
+            time_allowance: MAX_TIME_ALLOWANCE + 1,
+            overtime_rule: OvertimeRule::Elimination,
+            increment: MAX_TIME_ALLOWANCE + 1,
+            delay: MAX_TIME_ALLOWANCE + 1,
+            move_limit: Some(MAX_TIME_ALLOWANCE + 1),
+        };
+        assert_eq!(too_large(&rules), 4);
+
+        let mut rules = base.clone();
+        rules.timing = Timing::Periodic {
+            turn_delay: MAX_TIME_ALLOWANCE + 1,
+            total_time_allowance: MAX_TIME_ALLOWANCE + 1,
+        };
+        assert_eq!(too_large(&rules), 2);
+
+        let mut rules = base.clone();
+        rules.board_genesis = BoardGenesis::Classic(MAX_BOARD_DIMENSION, MAX_BOARD_DIMENSION + 1);
+        assert_eq!(too_large(&rules), 1);
+
+        let mut rules = base.clone();
+        rules.turn_economy = TurnEconomy::ActionPoints {
+            per_turn: MAX_ACTION_POINTS + 1,
+            place_cost: 2,
+            swap_cost: 1,
+            exchange_cost: 3,
+        };
+        assert_eq!(too_large(&rules), 1);
+
+        assert_eq!(too_large(&GameRules::tuesday()), 0);
+    }
+
+    #[test]
+    fn builds_rules_from_presets() {
+        for preset in RulesPreset::ALL {
+            assert_eq!(RulesValidator::check(&preset.rules()), vec![]);
+            assert_eq!(RulesPreset::matching(&preset.rules()), Some(preset));
+        }
+
+        let blitz = RulesPreset::Blitz.rules();
+        assert_eq!(blitz.hand_size, 5);
+        assert!(matches!(
+            blitz.timing,
+            Timing::PerPlayer {
+                overtime_rule: OvertimeRule::Elimination,
+                ..
+            }
+        ));
+
+        // Changing anything but the language or blanks moves a room off its preset
+        let mut rules = RulesPreset::Gentle.rules();
+        rules.language = Language::French;
+        rules.blank_tiles = 2;
+        assert_eq!(RulesPreset::matching(&rules), Some(RulesPreset::Gentle));
+        rules.hand_size = 9;
+        assert_eq!(RulesPreset::matching(&rules), None);
+
+        let custom = RulesPreset::Blitz
+            .builder()
+            .overtime(OvertimeRule::FreeWildcard { period: 30 })
+            .battle_length_delta(0)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            custom.timing,
+            Timing::PerPlayer {
+                overtime_rule: OvertimeRule::FreeWildcard { period: 30 },
+                ..
+            }
+        ));
+        assert_eq!(RulesPreset::matching(&custom), None);
+
+        let rapid = RulesPreset::Blitz
+            .builder()
+            .increment(0)
+            .delay(5)
+            .move_limit(Some(30))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            rapid.timing,
+            Timing::PerPlayer {
+                increment: 0,
+                delay: 5,
+                move_limit: Some(30),
+                ..
+            }
+        ));
+        assert_eq!(
+            RulesPreset::Blitz
+                .builder()
+                .move_limit(Some(0))
+                .build()
+                .unwrap_err(),
+            vec![RulesWarning::NoTimeAllowed]
+        );
+
+        assert_eq!(
+            RulesPreset::Classic
+                .builder()
+                .hand_size(0)
+                .build()
+                .unwrap_err(),
+            vec![RulesWarning::EmptyHand]
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Board, Coordinate},
+    dictionary::pick_with_retries,
+    game::Game,
+    judge::WordDict,
+    moves::Move,
+    player::Hand,
+    rng::TruncateRng,
+    rules::GameRules,
+};
+
+/// How long a puzzle rush lasts before any mistakes are taken off
+pub const RUSH_DURATION_SECS: u64 = 180;
+/// Time lost for each move that doesn't win its drill
+pub const RUSH_MISTAKE_PENALTY_SECS: u64 = 10;
+/// Leeway for a finished rush to reach the server after its clock runs out
+pub const RUSH_SUBMIT_GRACE_SECS: u64 = 15;
+/// Only well known words are used, so drills are about spotting the win rather than vocabulary
+const DRILL_MIN_FREQ: f32 = 0.99;
+const DRILL_MIN_WORD_LENGTH: usize = 3;
+const DRILL_MAX_WORD_LENGTH: usize = 7;
+const DRILL_WIDTH: usize = 5;
+const DRILL_COLUMN: usize = 2;
+
+/// Which side of the square that starts the word the opponent's town sits on
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TownSide {
+    Below,
+    West,
+    East,
+}
+
+/// A small position where the player has a single move that captures the opponent's town.
+/// The player's word runs out from their artifact, and starting it next to the town wins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drill {
+    pub board: Board,
+    pub hand: Hand,
+    solution: Move,
+}
+
+/// The words drills are built around, grouped by length.
+/// Gathered once per rush, rather than scanning the dictionary for every drill.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrillWords {
+    /// Sorted words of each length, starting from `DRILL_MIN_WORD_LENGTH`
+    by_length: Vec<Vec<String>>,
+}
+
+impl DrillWords {
+    pub fn new(dict: &WordDict) -> Self {
+        let lengths = DRILL_MIN_WORD_LENGTH..=DRILL_MAX_WORD_LENGTH;
+        let mut by_length = vec![vec![]; lengths.clone().count()];
+        let words = dict.sorted_words(|word, data| {
+            lengths.contains(&word.len())
+                && word.chars().all(|c| c.is_ascii_lowercase())
+                && data.rel_freq > DRILL_MIN_FREQ
+                && !data.objectionable
+        });
+        for word in words {
+            by_length[word.len() - DRILL_MIN_WORD_LENGTH].push(word);
+        }
+        Self { by_length }
+    }
+
+    fn of_length(&self, length: usize) -> &[String] {
+        &self.by_length[length - DRILL_MIN_WORD_LENGTH]
+    }
+}
+
+impl Drill {
+    /// Builds the drill at `index` of the rush started with `seed`, which is the same
+    /// for everyone with the same dictionary. Drills get longer words and more
+    /// distracting tiles as the rush goes on.
+    pub fn generate(seed: u32, index: usize, words: &DrillWords, dict: &WordDict) -> Option<Self> {
+        let word_length = (DRILL_MIN_WORD_LENGTH + index / 4).min(DRILL_MAX_WORD_LENGTH);
+        let hand_size = (3 + index / 3).min(7);
+
+        let mut rng = TruncateRng::new(((seed as u64) << 32) | index as u64);
+        // Not every word leaves enough letters to mislead with, so try a handful
+        pick_with_retries(words.of_length(word_length), &mut rng, |word, rng| {
+            Self::around_word(word, hand_size, rng, dict)
+        })
+    }
+
+    /// Builds a drill that is won by placing the first letter of `word`,
+    /// the same way for everyone with the same dictionary and seed
+    pub fn for_word(word: &str, seed: u32, dict: &WordDict) -> Option<Self> {
+        let hand_size = word.len().clamp(3, 7);
+        let mut rng = TruncateRng::new(seed as u64);
+        (0..10).find_map(|_| Self::around_word(word, hand_size, &mut rng, dict))
+    }
+
+    fn around_word(
+        word: &str,
+        hand_size: usize,
+        rng: &mut TruncateRng,
+        dict: &WordDict,
+    ) -> Option<Self> {
+        let (first, rest) = word.split_at(1);
+        let first = first.chars().next()?;
+
+        // Other tiles in hand must not start the word some other way
+        let mut distractors: Vec<_> = ('a'..='z')
+            .filter(|c| !dict.contains_key(&format!("{c}{rest}")))
+            .collect();
+        if distractors.len() < hand_size - 1 {
+            return None;
+        }
+        let mut hand = vec![first];
+        for _ in 1..hand_size {
+            let pick = rng.rand_range(0..distractors.len() as u32) as usize;
+            hand.push(distractors.swap_remove(pick));
+        }
+        let swap_with = rng.rand_range(0..hand.len() as u32) as usize;
+        hand.swap(0, swap_with);
+
+        let town_side = match rng.rand_range(0..3) {
+            0 => TownSide::Below,
+            1 => TownSide::West,
+            _ => TownSide::East,
+        };
+
+        let drill = Self {
+            board: Self::build_board(rest, town_side),
+            hand: Hand(hand.iter().map(|c| c.to_ascii_uppercase()).collect()),
+            solution: Move::Place {
+                player: 0,
+                tile: first.to_ascii_uppercase(),
+                position: Coordinate {
+                    x: DRILL_COLUMN,
+                    y: word.len(),
+                },
+            },
+        };
+        drill
+            .is_solved_by(drill.solution.clone(), dict)
+            .then_some(drill)
+    }
+
+    /// Player 0 sits at the top of the board and reads words upwards,
+    /// so the word is laid out backwards from the artifact
+    fn build_board(rest: &str, town_side: TownSide) -> Board {
+        let row = |squares: [&str; DRILL_WIDTH]| squares.join(" ");
+        let mut rows = vec![row(["~~", "~~", "|0", "~~", "~~"])];
+        for c in rest.chars().rev() {
+            let tile = format!("{}0", c.to_ascii_uppercase());
+            rows.push(row(["~~", "__", &tile, "__", "~~"]));
+        }
+        rows.push(match town_side {
+            TownSide::Below => row(["__", "__", "__", "__", "__"]),
+            TownSide::West => row(["__", "#1", "__", "__", "__"]),
+            TownSide::East => row(["__", "__", "__", "#1", "__"]),
+        });
+        rows.push(match town_side {
+            TownSide::Below => row(["__", "__", "#1", "__", "__"]),
+            _ => row(["__", "__", "__", "__", "__"]),
+        });
+        // Kept clear of the town, as a player with nowhere to play loses outright
+        rows.push(row(["~~", "|1", "~~", "~~", "~~"]));
+
+        Board::from_string(rows.join("\n"))
+    }
+
+    /// A fresh game in the drill's position, with the player to move as player 0
+    pub fn game(&self) -> Game {
+        let (generation, _) = GameRules::latest(None);
+        let mut game = Game::new(
+            self.board.width(),
+            self.board.height(),
+            None,
+            GameRules::generation(generation),
+        );
+        game.add_player("You".into());
+        game.add_player("Defender".into());
+        game.board = self.board.clone();
+        game.players[0].hand = self.hand.clone();
+        game.start();
+        game
+    }
+
+    /// Whether playing the move captures the town
+    pub fn is_solved_by(&self, next_move: Move, dict: &WordDict) -> bool {
+        let mut game = self.game();
+        matches!(
+            game.play_turn(next_move, Some(dict), Some(dict), None),
+            Ok(Some(0))
+        )
+    }
+}
+
+/// A run through as many drills as possible before the clock runs out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PuzzleRush {
+    pub seed: u32,
+    /// The winning move played on each drill, in order
+    pub solutions: Vec<Move>,
+    pub mistakes: usize,
+    /// Drills solved in a row without a mistake
+    pub streak: usize,
+    pub best_streak: usize,
+}
+
+impl PuzzleRush {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            solutions: vec![],
+            mistakes: 0,
+            streak: 0,
+            best_streak: 0,
+        }
+    }
+
+    pub fn solved(&self) -> usize {
+        self.solutions.len()
+    }
+
+    pub fn current_drill(&self, words: &DrillWords, dict: &WordDict) -> Option<Drill> {
+        Drill::generate(self.seed, self.solved(), words, dict)
+    }
+
+    /// Time left on the clock after `elapsed`, with mistakes taken off
+    pub fn time_left(&self, elapsed: Duration) -> Duration {
+        let penalty = RUSH_MISTAKE_PENALTY_SECS * self.mistakes as u64;
+        Duration::from_secs(RUSH_DURATION_SECS.saturating_sub(penalty)).saturating_sub(elapsed)
+    }
+
+    /// Whether a rush submitted `elapsed` after it started could have finished in time
+    pub fn finished_in_time(&self, elapsed: Duration) -> bool {
+        let allowed = self.time_left(Duration::ZERO) + Duration::from_secs(RUSH_SUBMIT_GRACE_SECS);
+        elapsed <= allowed
+    }
+
+    /// Plays a move against the current drill, returning whether it won
+    pub fn attempt(&mut self, drill: &Drill, next_move: Move, dict: &WordDict) -> bool {
+        if drill.is_solved_by(next_move.clone(), dict) {
+            self.solutions.push(next_move);
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+            true
+        } else {
+            self.mistakes += 1;
+            self.streak = 0;
+            false
+        }
+    }
+
+    /// Checks every solution against freshly generated drills,
+    /// returning the number solved if they all hold up
+    pub fn verify(&self, dict: &WordDict) -> Option<usize> {
+        let words = DrillWords::new(dict);
+        for (index, solution) in self.solutions.iter().enumerate() {
+            let drill = Drill::generate(self.seed, index, &words, dict)?;
+            if !drill.is_solved_by(solution.clone(), dict) {
+                return None;
+            }
+        }
+        Some(self.solved())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RushScore {
+    pub name: Option<String>,
+    pub solved: u32,
+}
+
+/// The best puzzle rushes of a day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RushLeaderboard {
+    pub day: u32,
+    /// Each player's best run, highest first
+    pub top: Vec<RushScore>,
+    /// The requesting player's best run of the day, if they're logged in and have played
+    pub personal_best: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::judge::WordData;
+
+    fn dict(words: &[&str]) -> WordDict {
+        words
+            .iter()
+            .map(|w| {
+                (
+                    w.to_string(),
+                    WordData {
+                        extensions: 0,
+                        rel_freq: 1.0,
+                        objectionable: false,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn drills_are_stable_and_winnable() {
+        let words = dict(&["car", "cat", "dog", "cart", "card", "bird", "word"]);
+        let drill_words = DrillWords::new(&words);
+
+        for index in 0..8 {
+            let drill =
+                Drill::generate(7, index, &drill_words, &words).expect("A drill should exist");
+            assert_eq!(
+                Some(drill.clone()),
+                Drill::generate(7, index, &drill_words, &words)
+            );
+            assert!(drill.is_solved_by(drill.solution.clone(), &words));
+
+            // Only the starting tile wins, every other tile in hand makes a non-word
+            let Move::Place { position, tile, .. } = drill.solution else {
+                panic!("Drills are solved by placing a tile");
+            };
+            for other in drill.hand.iter().filter(|t| **t != tile) {
+                let wrong = Move::Place {
+                    player: 0,
+                    tile: *other,
+                    position,
+                };
+                assert!(!drill.is_solved_by(wrong, &words));
+            }
+        }
+    }
+
+    #[test]
+    fn rush_scoring() {
+        let words = dict(&["car", "cat", "dog", "cart", "card", "bird", "word"]);
+        let drill_words = DrillWords::new(&words);
+        let mut rush = PuzzleRush::new(3);
+        assert_eq!(rush.time_left(Duration::from_secs(30)).as_secs(), 150);
+
+        let drill = rush.current_drill(&drill_words, &words).unwrap();
+        assert!(rush.attempt(&drill, drill.solution.clone(), &words));
+        let drill = rush.current_drill(&drill_words, &words).unwrap();
+        assert!(rush.attempt(&drill, drill.solution.clone(), &words));
+        assert_eq!((rush.solved(), rush.streak, rush.best_streak), (2, 2, 2));
+
+        let drill = rush.current_drill(&drill_words, &words).unwrap();
+        let nowhere = Move::Place {
+            player: 0,
+            tile: drill.hand.0[0],
+            position: Coordinate { x: 1, y: 1 },
+        };
+        assert!(!rush.attempt(&drill, nowhere, &words));
+        assert_eq!((rush.solved(), rush.streak, rush.best_streak), (2, 0, 2));
+        assert_eq!(rush.time_left(Duration::from_secs(30)).as_secs(), 140);
+        assert_eq!(rush.time_left(Duration::from_secs(500)).as_secs(), 0);
+        assert!(rush.finished_in_time(Duration::from_secs(170)));
+        assert!(!rush.finished_in_time(Duration::from_secs(170 + RUSH_SUBMIT_GRACE_SECS + 1)));
+
+        assert_eq!(rush.verify(&words), Some(2));
+        rush.solutions[0] = Move::Place {
+            player: 0,
+            tile: 'Q',
+            position: Coordinate { x: 1, y: 1 },
+        };
+        assert_eq!(rush.verify(&words), None);
+    }
+}
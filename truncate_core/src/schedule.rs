@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{messages::TruncateToken, rules::RulesPreset};
+
+/// Calendar lines longer than this many bytes are folded onto the next
+const ICS_LINE_LIMIT: usize = 75;
+
+/// A game two players have arranged to play at a set time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledGame {
+    /// Shared with the opponent so that they can accept the invite
+    pub invite_code: String,
+    /// Seconds since the unix epoch
+    pub scheduled_at: u64,
+    pub starts_at: u64,
+    pub ruleset: RulesPreset,
+    pub host: String,
+    /// Unset until someone accepts the invite
+    pub guest: Option<String>,
+    /// Where the opponent can accept the invite, and where both players can find the game
+    pub link: String,
+    /// Joins the room for whoever this was sent to, once the server has opened it
+    pub room_token: Option<TruncateToken>,
+}
+
+impl ScheduledGame {
+    /// Roughly how long the game will run, for blocking out a calendar
+    pub fn expected_secs(&self) -> u64 {
+        match self.ruleset {
+            RulesPreset::Blitz => 15 * 60,
+            RulesPreset::Classic | RulesPreset::Gentle => 30 * 60,
+            // Moves are made over days, so only the start is worth a slot in the calendar
+            RulesPreset::Correspondence => 15 * 60,
+        }
+    }
+
+    /// The game as an iCalendar event, for players to add to their calendar of choice
+    pub fn calendar_invite(&self) -> String {
+        let title = match &self.guest {
+            Some(guest) => format!("Truncate: {} vs {}", self.host, guest),
+            None => format!("Truncate with {}", self.host),
+        };
+
+        let lines = [
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//Truncate//Scheduled Games//EN".to_string(),
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@truncate.town", self.invite_code),
+            format!("DTSTAMP:{}", ics_time(self.scheduled_at)),
+            format!("DTSTART:{}", ics_time(self.starts_at)),
+            format!("DTEND:{}", ics_time(self.starts_at + self.expected_secs())),
+            format!("SUMMARY:{}", ics_text(&title)),
+            format!(
+                "DESCRIPTION:{}",
+                ics_text(&format!("A {} game of Truncate", self.ruleset))
+            ),
+            format!("URL:{}", self.link),
+            "END:VEVENT".to_string(),
+            "END:VCALENDAR".to_string(),
+        ];
+
+        lines.iter().map(|line| fold_line(line)).collect()
+    }
+}
+
+fn ics_time(unix_secs: u64) -> String {
+    let time =
+        OffsetDateTime::from_unix_timestamp(unix_secs as i64).unwrap_or(OffsetDateTime::UNIX_EPOCH);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// Escapes the characters that mean something within an iCalendar value
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Ends the line for a calendar, continuing anything too long onto lines starting with a space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > ICS_LINE_LIMIT {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduled(guest: Option<&str>) -> ScheduledGame {
+        ScheduledGame {
+            invite_code: "abc123".into(),
+            scheduled_at: 1_700_000_000,
+            // 2023-11-15 18:30:00 UTC
+            starts_at: 1_700_073_000,
+            ruleset: RulesPreset::Blitz,
+            host: "Ana".into(),
+            guest: guest.map(Into::into),
+            link: "https://truncate.town/join/?j=SCHEDULED:ABC123".into(),
+            room_token: None,
+        }
+    }
+
+    #[test]
+    fn invites_span_the_expected_game() {
+        let invite = scheduled(Some("Bo, the bold")).calendar_invite();
+        let lines: Vec<_> = invite.split("\r\n").collect();
+
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert!(lines.contains(&"UID:abc123@truncate.town"));
+        assert!(lines.contains(&"DTSTAMP:20231114T221320Z"));
+        assert!(lines.contains(&"DTSTART:20231115T183000Z"));
+        assert!(lines.contains(&"DTEND:20231115T184500Z"));
+        assert!(
+            lines.contains(&"SUMMARY:Truncate: Ana vs Bo\\, the bold"),
+            "Commas are escaped"
+        );
+        assert_eq!(lines.last(), Some(&""), "Every line ends with CRLF");
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let mut game = scheduled(None);
+        game.link = format!("https://truncate.town/{}", "a".repeat(100));
+        let invite = game.calendar_invite();
+
+        assert!(invite
+            .split("\r\n")
+            .all(|line| line.len() <= ICS_LINE_LIMIT));
+        assert_eq!(
+            invite.replace("\r\n ", ""),
+            scheduled(None)
+                .calendar_invite()
+                .replace("https://truncate.town/join/?j=SCHEDULED:ABC123", &game.link),
+            "Unfolding gives back the original lines"
+        );
+    }
+}
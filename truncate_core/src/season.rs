@@ -0,0 +1,86 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
+
+/// Cosmetic themes that run for part of each year
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Season {
+    Harvest,
+    Winter,
+}
+
+/// A day of the year, as a month and a day of that month
+type DayOfYear = (Month, u8);
+
+/// When each season starts and ends, inclusive of both days
+const SCHEDULE: [(Season, DayOfYear, DayOfYear); 2] = [
+    (
+        Season::Harvest,
+        (Month::September, 22),
+        (Month::November, 30),
+    ),
+    (Season::Winter, (Month::December, 1), (Month::February, 29)),
+];
+
+impl Season {
+    /// Which season is running on a given date, if any
+    pub fn scheduled_on(date: Date) -> Option<Self> {
+        let today = (date.month() as u8, date.day());
+
+        SCHEDULE
+            .iter()
+            .find(|(_, (start_month, start_day), (end_month, end_day))| {
+                let start = (*start_month as u8, *start_day);
+                let end = (*end_month as u8, *end_day);
+                if start <= end {
+                    start <= today && today <= end
+                } else {
+                    // Seasons can wrap around the new year
+                    today >= start || today <= end
+                }
+            })
+            .map(|(season, _, _)| *season)
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Season::Harvest => write!(f, "harvest"),
+            Season::Winter => write!(f, "winter"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn schedules_seasons() {
+        assert_eq!(Season::scheduled_on(date(2024, Month::July, 4)), None);
+        assert_eq!(Season::scheduled_on(date(2024, Month::September, 21)), None);
+        assert_eq!(
+            Season::scheduled_on(date(2024, Month::September, 22)),
+            Some(Season::Harvest)
+        );
+        assert_eq!(
+            Season::scheduled_on(date(2024, Month::December, 31)),
+            Some(Season::Winter)
+        );
+        assert_eq!(
+            Season::scheduled_on(date(2025, Month::January, 1)),
+            Some(Season::Winter)
+        );
+        assert_eq!(
+            Season::scheduled_on(date(2024, Month::February, 29)),
+            Some(Season::Winter)
+        );
+        assert_eq!(Season::scheduled_on(date(2025, Month::March, 1)), None);
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dictionary::pick_with_retries, judge::WordDict, reporting::WordMeaning, rng::TruncateRng,
+    rush::Drill,
+};
+
+/// Long enough to be interesting, short enough to fit a practice drill
+const WORD_LENGTHS: std::ops::RangeInclusive<usize> = 4..=7;
+/// Familiar enough to be fair, without being a word everyone already plays
+const WORD_MIN_FREQ: f32 = 0.95;
+const WORD_MAX_FREQ: f32 = 0.99;
+
+/// A word shown on the menu each day, with a drill for practising placing it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordOfTheDay {
+    pub day: u32,
+    pub word: String,
+    /// Empty if the server has no definitions loaded
+    pub definitions: Vec<WordMeaning>,
+}
+
+impl WordOfTheDay {
+    /// Picks the word for a given day. The pick is seeded by the dictionary's version as well as the day,
+    /// so it is the same for everyone until the dictionary changes.
+    pub fn pick(day: u32, dict: &WordDict) -> Option<String> {
+        let candidates = dict.sorted_words(|word, data| {
+            WORD_LENGTHS.contains(&word.len())
+                && word.chars().all(|c| c.is_ascii_lowercase())
+                && data.rel_freq > WORD_MIN_FREQ
+                && data.rel_freq <= WORD_MAX_FREQ
+                && !data.objectionable
+        });
+
+        let mut rng = TruncateRng::new(dict.version() ^ day as u64);
+        // Not every word can be drilled, so try a handful
+        pick_with_retries(&candidates, &mut rng, |word, _| {
+            Drill::for_word(word, day, dict).map(|_| word.clone())
+        })
+    }
+
+    /// The practice drill for the word, which is won by placing its first letter
+    pub fn drill(&self, dict: &WordDict) -> Option<Drill> {
+        Drill::for_word(&self.word, self.day, dict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::judge::WordData;
+
+    fn dict(words: &[&str]) -> WordDict {
+        words
+            .iter()
+            .map(|w| {
+                (
+                    w.to_string(),
+                    WordData {
+                        extensions: 0,
+                        rel_freq: 0.97,
+                        objectionable: false,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn daily_word_is_stable_and_drillable() {
+        let words = dict(&["cart", "card", "bird", "word", "ward", "lamp"]);
+
+        let word = WordOfTheDay::pick(12, &words).expect("A word should be picked");
+        assert_eq!(Some(word.clone()), WordOfTheDay::pick(12, &words));
+
+        let daily = WordOfTheDay {
+            day: 12,
+            word,
+            definitions: vec![],
+        };
+        let drill = daily.drill(&words).expect("The word should have a drill");
+        assert_eq!(Some(drill), daily.drill(&words));
+    }
+
+    #[test]
+    fn daily_word_skips_common_and_rare_words() {
+        let mut words = dict(&["lamp"]);
+        for (word, rel_freq) in [("the", 1.0), ("aalii", 0.0), ("zzzz", 0.999)] {
+            words.insert(
+                word,
+                WordData {
+                    extensions: 0,
+                    rel_freq,
+                    objectionable: false,
+                },
+            );
+        }
+
+        assert_eq!(WordOfTheDay::pick(3, &words), Some("lamp".to_string()));
+    }
+}
@@ -33,49 +33,55 @@ pub fn get_dicts() -> Dicts {
 pub static TOTAL_DICT: Mutex<Option<WordDict>> = Mutex::new(None);
 pub static RESTRICTED_DICT: Mutex<Option<WordDict>> = Mutex::new(None);
 
+/// Reads a word list in the format produced by the `dict_builder`
+pub fn parse_dict(source: &str) -> WordDict {
+    let mut words = WordDict::new();
+
+    for line in source.lines() {
+        let mut chunks = line.split(' ');
+
+        let mut word = chunks.next().unwrap().to_string();
+        let extensions = chunks.next().unwrap().parse().unwrap();
+        let rel_freq = chunks.next().unwrap().parse().unwrap();
+
+        let objectionable = word.chars().next() == Some('*');
+        if objectionable {
+            word.remove(0);
+        }
+
+        words.insert(
+            word,
+            WordData {
+                extensions,
+                rel_freq,
+                objectionable,
+            },
+        );
+    }
+
+    words
+}
+
+/// Has the NPC play in another language, knowing every word in its list as there are no frequencies to go by
+pub fn use_language_dicts(words: WordDict) {
+    *RESTRICTED_DICT.lock().unwrap() = Some(words.clone());
+    *TOTAL_DICT.lock().unwrap() = Some(words);
+}
+
 pub fn ensure_dicts() {
     let mut total_dict = TOTAL_DICT.lock().unwrap();
     let mut restricted_dict = RESTRICTED_DICT.lock().unwrap();
 
     if total_dict.is_none() {
-        let mut valid_words = std::collections::HashMap::new();
-        let mut restricted_words = std::collections::HashMap::new();
-        let lines = TRUNCATE_DICT.lines();
-
-        for line in lines {
-            let mut chunks = line.split(' ');
-
-            let mut word = chunks.next().unwrap().to_string();
-            let extensions = chunks.next().unwrap().parse().unwrap();
-            let rel_freq = chunks.next().unwrap().parse().unwrap();
-
-            let objectionable = word.chars().next() == Some('*');
-            if objectionable {
-                word.remove(0);
-            }
-
-            valid_words.insert(
-                word.clone(),
-                WordData {
-                    extensions,
-                    rel_freq,
-                    objectionable,
-                },
-            );
-
-            // These are the words the NPC will think it recognizes,
-            // and won't challenge if they're on the board.
-            if rel_freq > 0.90 {
-                restricted_words.insert(
-                    word,
-                    WordData {
-                        extensions,
-                        rel_freq,
-                        objectionable,
-                    },
-                );
-            }
-        }
+        let valid_words = parse_dict(TRUNCATE_DICT);
+
+        // These are the words the NPC will think it recognizes,
+        // and won't challenge if they're on the board.
+        let restricted_words = valid_words
+            .iter()
+            .filter(|(_, data)| data.rel_freq > 0.90)
+            .map(|(word, data)| (word, data.clone()))
+            .collect();
 
         _ = total_dict.insert(valid_words);
         _ = restricted_dict.insert(restricted_words);
@@ -1,20 +1,48 @@
-use dicts::{get_dicts, Dicts};
+use dicts::{get_dicts, parse_dict, Dicts, TRUNCATE_DICT};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use storage::{load_file, write_file, SeedNote};
+use storage::{load_difficulty_report, load_file, write_file, NotesFile, SeedNote};
 use truncate_core::{
+    bag::{balanced_distribution, TileBag},
+    difficulty::{DifficultyBand, DifficultyModel},
     game::Game,
     generation::{generate_board, get_game_verification, BoardSeed},
+    judge::language_words,
     messages::PlayerMessage,
     moves::Move,
-    npc::scoring::{NPCParams, NPCPersonality},
-    rules::GameRules,
+    npc::{
+        forced_wins::ForcedWinPuzzle,
+        opening_book::OpeningBookBuilder,
+        opening_search::{default_boards, play_between, self_play},
+        progression::{puzzle_opponent, rungs},
+        scoring::{NPCParams, NPCPersonality},
+    },
+    rules::{GameRules, Language},
 };
 
-use crate::dicts::ensure_dicts;
+use crate::dicts::{ensure_dicts, use_language_dicts};
 
 mod dicts;
 mod storage;
 
+/// The difficulty that new puzzles should aim for, once there's enough data to predict it
+const TARGET_DIFFICULTY: DifficultyBand = DifficultyBand {
+    min: 0.35,
+    max: 0.65,
+};
+/// How many winnable boards to try for a day before settling for the closest to the target
+const MAX_CALIBRATION_ATTEMPTS: usize = 8;
+/// Words that are likely to be played, used to weigh letters when balancing a tile bag
+const BALANCED_WORD_LENGTHS: std::ops::RangeInclusive<usize> = 2..=7;
+/// Generated boards to search openings on, alongside the lobby's default board
+const BOOK_BOARDS: u32 = 40;
+/// Games the NPC plays against itself (or the ladder plays out) on each board when searching openings
+const BOOK_GAMES_PER_BOARD: u32 = 8;
+/// Squares need to have been played this often, and won this often, to make the searched book
+const BOOK_MIN_GAMES: u32 = 3;
+const BOOK_MIN_WIN_RATE: f32 = 0.55;
+/// Generated boards to play out when looking for forced win puzzles
+const PUZZLE_BOARDS: u32 = 40;
+
 fn best_move(game: &Game, npc_params: &NPCParams, dicts: &Dicts) -> PlayerMessage {
     ensure_dicts();
 
@@ -39,13 +67,15 @@ fn evaluate_single_seed(
     seed: BoardSeed,
     log: bool,
     latest_rules_generation: u32,
+    npc_rung: Option<u32>,
+    language: Language,
 ) -> Option<SeedNote> {
     let maximum_turns = 200;
 
-    let mut game = get_game_for_seed(seed.clone(), latest_rules_generation);
+    let mut game = get_game_for_seed(seed.clone(), latest_rules_generation, language);
 
     let verification = get_game_verification(&game);
-    let npc_params = NPCPersonality::jet().params;
+    let npc_params = puzzle_opponent(npc_rung).params;
     let mut dicts = get_dicts();
 
     while game.turn_count < maximum_turns {
@@ -84,6 +114,9 @@ fn evaluate_single_seed(
                     board_generation: seed.generation,
                     rules_generation: latest_rules_generation,
                     verification,
+                    npc_turns: Some(game.turn_count),
+                    mystery: false,
+                    npc_rung,
                 });
             }
             Ok(None) => {
@@ -127,7 +160,7 @@ fn evaluate_single_seed(
     None
 }
 
-fn get_game_for_seed(seed: BoardSeed, rules_generation: u32) -> Game {
+fn get_game_for_seed(seed: BoardSeed, rules_generation: u32, language: Language) -> Game {
     let mut board = generate_board(seed.clone())
         .expect("Generation should be possible from this seed")
         .board;
@@ -141,6 +174,10 @@ fn get_game_for_seed(seed: BoardSeed, rules_generation: u32) -> Game {
     );
     game.add_player("P1".into());
     game.add_player("P2".into());
+    // Left alone for English, so that existing puzzles are dealt the same hands
+    if language != Language::English {
+        game.set_language(language);
+    }
 
     game.board = board.clone();
     game.rules.battle_delay = 0;
@@ -149,39 +186,147 @@ fn get_game_for_seed(seed: BoardSeed, rules_generation: u32) -> Game {
     game
 }
 
-fn evaluate_seed(mut seed: BoardSeed, log: bool, latest_rules_generation: u32) -> (u32, SeedNote) {
+fn evaluate_seed(
+    mut seed: BoardSeed,
+    log: bool,
+    latest_rules_generation: u32,
+    model: Option<&DifficultyModel>,
+    npc_rung: Option<u32>,
+    language: Language,
+) -> (u32, SeedNote) {
     let core_seed = seed.seed;
 
-    seed.external_reroll();
-    let mut rerolls = 1;
-
-    let mut seed_result = None;
+    let mut rerolls = 0;
+    let mut winnable_attempts = 0;
+    let mut closest: Option<(f32, SeedNote)> = None;
 
     println!("-----> Starting on seed {core_seed}");
 
-    while seed_result.is_none() {
-        seed_result = evaluate_single_seed(seed.clone(), log, latest_rules_generation);
-        if seed_result.is_none() {
-            rerolls += 1;
-            seed.external_reroll();
+    loop {
+        rerolls += 1;
+        seed.external_reroll();
+
+        let Some(mut seed_notes) = evaluate_single_seed(
+            seed.clone(),
+            log,
+            latest_rules_generation,
+            npc_rung,
+            language,
+        ) else {
+            continue;
+        };
+        seed_notes.rerolls = rerolls;
+        winnable_attempts += 1;
+
+        // Without a model, any winnable board will do
+        let distance = match (model, seed_notes.npc_turns) {
+            (Some(model), Some(turns)) => TARGET_DIFFICULTY.distance(model.predict(turns as f32)),
+            _ => 0.0,
+        };
+
+        if closest.as_ref().is_none_or(|(best, _)| distance < *best) {
+            closest = Some((distance, seed_notes));
+        }
+
+        if distance == 0.0 || winnable_attempts >= MAX_CALIBRATION_ATTEMPTS {
+            break;
         }
     }
 
-    println!("Evaluated notes for {core_seed} with {rerolls} reroll(s)");
+    let (distance, seed_notes) = closest.expect("At least one board should have been winnable");
 
-    let mut seed_notes = seed_result.unwrap();
-    seed_notes.rerolls = rerolls;
+    println!(
+        "Evaluated notes for {core_seed} with {} reroll(s), {distance:.2} outside the difficulty target",
+        seed_notes.rerolls
+    );
     println!("........ {core_seed}, {seed_notes:#?}");
     (core_seed, seed_notes)
 }
 
-fn verify_note(seed: &u32, note: &SeedNote) -> bool {
+/// Fits a difficulty model from how players fared on past puzzles, if enough have been reported.
+/// The report only covers English puzzles, so other languages accept any winnable board.
+fn calibrate(notes: &NotesFile, language: Language) -> Option<DifficultyModel> {
+    if language != Language::English {
+        return None;
+    }
+    let reported: Vec<_> = load_difficulty_report()
+        .into_iter()
+        .filter(|day| day.is_significant())
+        .collect();
+    let samples: Vec<_> = reported
+        .iter()
+        .filter_map(|day| {
+            let turns = notes.notes.get(&day.day)?.npc_turns?;
+            Some((turns as f32, day.score()))
+        })
+        .collect();
+
+    // Puzzles from before NPC turns were noted can't be calibrated against until they're backfilled
+    let unnoted = reported.len() - samples.len();
+    if unnoted > 0 {
+        println!(
+            "{unnoted} reported puzzle(s) have no NPC turns noted, run `backfill-npc-turns` to include them"
+        );
+    }
+
+    let model = DifficultyModel::fit(&samples);
+    match &model {
+        Some(model) => println!(
+            "Calibrated difficulty from {} puzzles: {:.3} + {:.4} per NPC turn",
+            samples.len(),
+            model.intercept,
+            model.slope
+        ),
+        None => println!("Not enough difficulty data to calibrate, accepting any winnable board"),
+    }
+    model
+}
+
+/// Plays out puzzles noted before NPC turns were recorded, so that their player results can be
+/// calibrated against. Puzzles the NPCs no longer win for the same side are left without turns.
+fn backfill_npc_turns(notes: &mut NotesFile, language: Language) {
+    let backfilled: Vec<_> = notes
+        .notes
+        .iter()
+        .filter(|(_, note)| note.npc_turns.is_none())
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(seed, note)| {
+            let mut board_seed = BoardSeed::new_with_generation(note.board_generation, *seed);
+            for _ in 0..(note.rerolls) {
+                board_seed.external_reroll();
+            }
+
+            let replayed = evaluate_single_seed(
+                board_seed,
+                false,
+                note.rules_generation,
+                note.npc_rung,
+                language,
+            )
+            .filter(|replayed| replayed.best_player == note.best_player);
+            match &replayed {
+                Some(replayed) => println!("Seed {seed} took {:?} turns", replayed.npc_turns),
+                None => eprintln!("Seed {seed} no longer plays out the same, leaving it unnoted"),
+            }
+            (*seed, replayed.and_then(|replayed| replayed.npc_turns))
+        })
+        .collect();
+
+    for (seed, turns) in backfilled {
+        if let Some(note) = notes.notes.get_mut(&seed) {
+            note.npc_turns = turns;
+        }
+    }
+}
+
+fn verify_note(seed: &u32, note: &SeedNote, language: Language) -> bool {
     let mut board_seed = BoardSeed::new_with_generation(note.board_generation, *seed);
     for _ in 0..(note.rerolls) {
         board_seed.external_reroll();
     }
 
-    let game = get_game_for_seed(board_seed, note.rules_generation);
+    let game = get_game_for_seed(board_seed, note.rules_generation, language);
 
     println!("{}", game.board);
 
@@ -196,47 +341,237 @@ fn verify_note(seed: &u32, note: &SeedNote) -> bool {
     verification == note.verification
 }
 
+/// Prints a tile distribution balanced for a word list, ready to be added to the tile generations
+fn balance_bag(word_list: Option<&String>) {
+    let source = match word_list {
+        Some(path) => std::fs::read_to_string(path).expect("Word list should be readable"),
+        None => TRUNCATE_DICT.to_string(),
+    };
+    let dict = parse_dict(&source);
+    // Keep the bag the same size as the one we play with now
+    let bag_size = TileBag::latest(None).1.remaining_tiles().len();
+
+    let distribution = balanced_distribution(&dict, BALANCED_WORD_LENGTHS, bag_size);
+    println!("Balanced {bag_size} tiles for {} words:", dict.len());
+    println!("[");
+    for (letter, count) in distribution.iter().enumerate() {
+        let count = format!("{count},");
+        println!("    {count:<4}// {}", (b'a' + letter as u8) as char);
+    }
+    println!("]");
+}
+
+/// Has the NPC play itself on the default boards, and writes the openings that won to a book.
+/// With `ladder`, rungs of the NPC ladder play each other instead, blunders and all,
+/// standing in for players' games until the replay archive has enough of them.
+fn build_opening_book(path: Option<&String>, ladder: bool) {
+    let path = path.expect("A path to write the opening book to is required");
+    ensure_dicts();
+    let dicts = get_dicts();
+    let rules = GameRules::latest(None).1;
+    let npc_params = NPCPersonality::jet().params;
+    let rungs = rungs();
+
+    let boards = default_boards(BOOK_BOARDS);
+    let games: Vec<_> = boards
+        .iter()
+        .flat_map(|(board_seed, board)| {
+            (0..BOOK_GAMES_PER_BOARD).map(move |game| (board_seed * 1000 + game, board))
+        })
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(seed, board)| {
+            let (moves, winner) = if ladder {
+                let rung = |n: usize| &rungs[n % rungs.len()].personality.params;
+                let players = [rung(seed as usize), rung(seed as usize / rungs.len())];
+                play_between(board, seed, rules.clone(), &dicts.total, players)
+            } else {
+                self_play(board, seed, rules.clone(), &dicts.total, &npc_params)
+            };
+            (board, moves, winner)
+        })
+        .collect();
+
+    let mut builder = OpeningBookBuilder::new();
+    for (board, moves, winner) in games {
+        builder.record_game(board, &moves, winner);
+    }
+    let played = builder.games();
+    let book = builder.build(BOOK_MIN_GAMES, BOOK_MIN_WIN_RATE);
+
+    let header: String = std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .take_while(|line| line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    std::fs::write(path, header + &book.to_text()).expect("Opening book should be writable");
+    println!(
+        "Searched {played} games on {} boards for {} neighbourhoods",
+        boards.len(),
+        book.neighbourhoods()
+    );
+}
+
+/// Has the NPC play itself on generated boards, and writes any positions
+/// that could be won by force in `turns` to a puzzle file
+fn find_puzzles(turns: Option<&String>, path: Option<&String>) {
+    let turns: usize = turns
+        .and_then(|turns| turns.parse().ok())
+        .expect("The number of turns to win in is required");
+    let path = path.expect("A path to write the puzzles to is required");
+    ensure_dicts();
+    let dicts = get_dicts();
+    let rules_generation = GameRules::latest(None).0;
+    let npc_params = NPCPersonality::jet().params;
+
+    let puzzles: Vec<_> = (0..PUZZLE_BOARDS)
+        .into_par_iter()
+        .filter_map(|seed| {
+            ForcedWinPuzzle::generate(
+                BoardSeed::new(seed),
+                rules_generation,
+                turns,
+                &dicts.total,
+                &npc_params,
+            )
+        })
+        .collect();
+
+    let yaml = serde_yaml::to_string(&puzzles).expect("Puzzles should serialize");
+    std::fs::write(path, yaml).expect("Puzzle file should be writable");
+    println!(
+        "Found {} puzzles won in {turns} on {PUZZLE_BOARDS} boards",
+        puzzles.len()
+    );
+}
+
+/// Takes `--rung <index>` out of the arguments, which has new puzzles played against
+/// that rung of the NPC ladder rather than the usual opponent
+fn take_rung_arg(args: &mut Vec<String>) -> Option<u32> {
+    let flag = args.iter().position(|arg| arg == "--rung")?;
+    let rung: u32 = args
+        .get(flag + 1)
+        .and_then(|rung| rung.parse().ok())
+        .expect("--rung should be followed by a rung of the NPC ladder");
+    let ladder = rungs();
+    let Some(details) = ladder.get(rung as usize) else {
+        panic!("There is no rung {rung} on the NPC ladder");
+    };
+    println!("Playing puzzles against {}", details.title);
+
+    args.drain(flag..=flag + 1);
+    Some(rung)
+}
+
+/// Takes `--language <code> <word list>` out of the arguments, which has new puzzles played
+/// in that language, with the word list in the same format as the server's
+fn take_language_arg(args: &mut Vec<String>) -> Language {
+    let Some(flag) = args.iter().position(|arg| arg == "--language") else {
+        return Language::English;
+    };
+    let code = args
+        .get(flag + 1)
+        .expect("--language should be followed by a language code, such as es");
+    let Some(language) = Language::from_code(code) else {
+        panic!("There is no language with the code {code}");
+    };
+    if language != Language::English {
+        let path = args
+            .get(flag + 2)
+            .expect("--language should be followed by a path to the language's word list");
+        let contents = std::fs::read_to_string(path).expect("Word list should be readable");
+        let words = language_words(&contents, language);
+        println!("Playing puzzles in {language} with {} words", words.len());
+        use_language_dicts(words);
+        args.drain(flag..=flag + 2);
+    } else {
+        args.drain(flag..=flag + 1);
+    }
+
+    language
+}
+
 fn main() {
     let quantity = 30;
+    let mut args = std::env::args().collect::<Vec<_>>();
+    let npc_rung = take_rung_arg(&mut args);
+    let language = take_language_arg(&mut args);
+
+    if args.get(1).is_some_and(|command| command == "balance-bag") {
+        balance_bag(args.get(2));
+        return;
+    }
 
-    let mut current_notes = load_file();
+    if args
+        .get(1)
+        .is_some_and(|command| command == "build-opening-book")
+    {
+        build_opening_book(args.get(2), false);
+        return;
+    }
+
+    if args
+        .get(1)
+        .is_some_and(|command| command == "seed-opening-book")
+    {
+        build_opening_book(args.get(2), true);
+        return;
+    }
+
+    if args.get(1).is_some_and(|command| command == "find-puzzles") {
+        find_puzzles(args.get(2), args.get(3));
+        return;
+    }
+
+    let mut current_notes = load_file(language);
     ensure_dicts();
 
     let verifies: Vec<_> = current_notes
         .notes
         .iter()
-        .map(|(seed, note)| verify_note(seed, note))
+        .map(|(seed, note)| verify_note(seed, note, language))
         .collect();
 
     if verifies.contains(&false) {
         panic!("One or more seeds failed to verify");
     }
 
-    let args = std::env::args().collect::<Vec<_>>();
+    if args
+        .get(1)
+        .is_some_and(|command| command == "backfill-npc-turns")
+    {
+        backfill_npc_turns(&mut current_notes, language);
+        write_file(current_notes, language);
+        return;
+    }
 
     if let Some(seed) = args.get(1) {
         let day = seed.parse().expect("Seed should be a number");
         let seed = BoardSeed::new(day);
         let rules = GameRules::latest(Some(day)).0;
-        let result = evaluate_seed(seed, true, rules);
+        let model = calibrate(&current_notes, language);
+        let result = evaluate_seed(seed, true, rules, model.as_ref(), npc_rung, language);
         println!("{result:#?}");
         return;
     };
 
-    write_file(load_file());
+    write_file(load_file(language), language);
 
     let mut starting_day = 0;
     while current_notes.notes.contains_key(&starting_day) {
         starting_day += 1;
     }
 
+    let model = calibrate(&current_notes, language);
+
     let results: Vec<_> = (0..quantity)
         .into_par_iter()
         .map(|offset| {
             let day = starting_day + offset;
             let seed = BoardSeed::new(day);
             let rules = GameRules::latest(Some(day)).0;
-            evaluate_seed(seed, false, rules)
+            evaluate_seed(seed, false, rules, model.as_ref(), npc_rung, language)
         })
         .collect();
 
@@ -244,5 +579,5 @@ fn main() {
         current_notes.notes.insert(seed, notes);
     }
 
-    write_file(current_notes);
+    write_file(current_notes, language);
 }
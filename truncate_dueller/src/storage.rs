@@ -1,9 +1,19 @@
 use std::{collections::BTreeMap, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
+use truncate_core::{difficulty::PuzzleDifficulty, rules::Language};
 
-fn note_file() -> PathBuf {
-    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("seed_notes.yml")
+/// English puzzles keep the original file, with each other language's puzzles kept separately
+fn note_file(language: Language) -> PathBuf {
+    let name = match language {
+        Language::English => "seed_notes.yml".to_string(),
+        language => format!("seed_notes_{}.yml", language.code()),
+    };
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(name)
+}
+
+fn difficulty_file() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("difficulty_report.yml")
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -13,6 +23,15 @@ pub struct SeedNote {
     pub board_generation: u32,
     pub rules_generation: u32,
     pub verification: String,
+    /// How many turns the NPCs took to finish the puzzle, used to predict its difficulty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub npc_turns: Option<u32>,
+    /// Marks the puzzle to be played in mystery mode, where the board is revealed as the player's tiles spread
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mystery: bool,
+    /// Plays the puzzle against this rung of the NPC ladder, rather than the usual opponent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub npc_rung: Option<u32>,
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -20,8 +39,8 @@ pub struct NotesFile {
     pub notes: BTreeMap<u32, SeedNote>,
 }
 
-pub fn load_file() -> NotesFile {
-    let notes = std::fs::read_to_string(note_file())
+pub fn load_file(language: Language) -> NotesFile {
+    let notes = std::fs::read_to_string(note_file(language))
         .map(|file| {
             serde_yaml::from_str(&file)
                 .expect("If the file exists, it should match the notes format")
@@ -31,7 +50,17 @@ pub fn load_file() -> NotesFile {
     notes
 }
 
-pub fn write_file(notes: NotesFile) {
+pub fn write_file(notes: NotesFile, language: Language) {
     let output_content = serde_yaml::to_string(&notes).unwrap();
-    std::fs::write(note_file(), output_content).expect("Writing notes should succeed");
+    std::fs::write(note_file(language), output_content).expect("Writing notes should succeed");
+}
+
+/// Loads the puzzle difficulty report exported from the server, if one has been saved
+pub fn load_difficulty_report() -> Vec<PuzzleDifficulty> {
+    std::fs::read_to_string(difficulty_file())
+        .map(|file| {
+            serde_yaml::from_str(&file)
+                .expect("If the file exists, it should match the difficulty report format")
+        })
+        .unwrap_or_default()
 }
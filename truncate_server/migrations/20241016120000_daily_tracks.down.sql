@@ -0,0 +1,18 @@
+-- Only the original puzzle track existed before this migration
+DELETE FROM daily_puzzle_attempts
+WHERE result_id IN (
+    SELECT result_id FROM daily_puzzle_results WHERE track <> 'puzzle'
+);
+
+DELETE FROM daily_puzzle_results
+WHERE track <> 'puzzle';
+
+ALTER TABLE daily_puzzle_results
+    DROP CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_track_key;
+
+ALTER TABLE daily_puzzle_results
+    ADD CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_key
+    UNIQUE (player_id, daily_puzzle);
+
+ALTER TABLE daily_puzzle_results
+    DROP COLUMN track;
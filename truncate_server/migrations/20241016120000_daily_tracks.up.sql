@@ -0,0 +1,10 @@
+-- Daily results are now split into tracks, so side-challenges can share these tables
+ALTER TABLE daily_puzzle_results
+    ADD COLUMN track VARCHAR(32) NOT NULL DEFAULT 'puzzle';
+
+ALTER TABLE daily_puzzle_results
+    DROP CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_key;
+
+ALTER TABLE daily_puzzle_results
+    ADD CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_track_key
+    UNIQUE (player_id, daily_puzzle, track);
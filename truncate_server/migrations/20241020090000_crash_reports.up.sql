@@ -0,0 +1,10 @@
+-- Crash reports sent in by clients, which carry nothing that identifies the player
+CREATE TABLE crash_reports (
+    report_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    client_version TEXT NOT NULL,
+    region TEXT NOT NULL,
+    panic_message TEXT NOT NULL,
+    recent_messages TEXT NOT NULL,
+    board_hash BIGINT,
+    reported_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
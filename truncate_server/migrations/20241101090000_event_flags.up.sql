@@ -0,0 +1,2 @@
+-- The experiment flags a session was seeing when each event happened
+ALTER TABLE events ADD COLUMN flags TEXT;
@@ -0,0 +1,12 @@
+-- Custom boards that players have saved from the editor, one per named slot
+CREATE TABLE custom_boards (
+    board_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    player_id UUID NOT NULL REFERENCES players(player_id),
+    board_name VARCHAR(64) NOT NULL,
+    board TEXT NOT NULL,
+    -- NULL while private, otherwise 'pending', 'approved', or 'rejected' for the public gallery
+    gallery_status VARCHAR(16),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (player_id, board_name)
+);
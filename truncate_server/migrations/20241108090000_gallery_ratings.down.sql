@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS custom_board_ratings;
+ALTER TABLE custom_boards DROP COLUMN play_count;
@@ -0,0 +1,10 @@
+-- How often each gallery board has been played, and what players thought of it
+ALTER TABLE custom_boards ADD COLUMN play_count INT NOT NULL DEFAULT 0;
+
+CREATE TABLE custom_board_ratings (
+    board_id UUID NOT NULL REFERENCES custom_boards(board_id) ON DELETE CASCADE,
+    player_id UUID NOT NULL REFERENCES players(player_id),
+    stars SMALLINT NOT NULL CHECK (stars BETWEEN 1 AND 5),
+    rated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (board_id, player_id)
+);
@@ -0,0 +1,10 @@
+-- Online games that players left partway through, and didn't return to within the grace window
+CREATE TABLE abandonments (
+    abandonment_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    player_id UUID NOT NULL REFERENCES players(player_id),
+    room_code VARCHAR(32) NOT NULL,
+    abandoned_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    -- The player's side of the story, for moderators to review
+    appeal TEXT,
+    appealed_at TIMESTAMP WITH TIME ZONE
+);
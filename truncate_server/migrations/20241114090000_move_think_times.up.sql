@@ -0,0 +1,3 @@
+-- Seconds spent on each move of an attempt, so replays can show where the time went
+ALTER TABLE daily_puzzle_attempts
+    ADD COLUMN think_secs INT[] NOT NULL DEFAULT '{}';
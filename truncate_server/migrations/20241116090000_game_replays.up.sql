@@ -0,0 +1,18 @@
+-- Finished online games, kept compressed so they can be replayed and mined later
+CREATE TABLE game_replays (
+    replay_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_code VARCHAR(32) NOT NULL,
+    player_count INT NOT NULL,
+    -- Rated games outlive casual ones under the retention policy
+    rated BOOLEAN NOT NULL,
+    winner INT,
+    move_count INT NOT NULL,
+    -- The starting board as JSON, and the moves in packed notation, each compressed with zstd
+    board BYTEA NOT NULL,
+    moves BYTEA NOT NULL,
+    -- Size of the board and moves before compression, to keep an eye on how well it's working
+    raw_bytes INT NOT NULL,
+    finished_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX game_replays_retention_idx ON game_replays (rated, finished_at);
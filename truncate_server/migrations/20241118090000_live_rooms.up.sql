@@ -0,0 +1,11 @@
+-- Rooms open on the server, republished every minute so operators can list them from the database
+CREATE TABLE live_rooms (
+    room_code VARCHAR(32) PRIMARY KEY,
+    players TEXT[] NOT NULL,
+    spectators INT NOT NULL,
+    started BOOLEAN NOT NULL,
+    finished BOOLEAN NOT NULL,
+    turn_count INT NOT NULL,
+    idle_secs BIGINT NOT NULL,
+    published_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,22 @@
+-- Correspondence games, saved as they're played so that they outlive server restarts and their players' connections
+CREATE TABLE games (
+    room_code VARCHAR(32) PRIMARY KEY,
+    -- What's needed to set the game up again: its rules as JSON, the players in turn order,
+    -- the seed its tiles are drawn with, and the board as JSON as the game started on it
+    rules TEXT NOT NULL,
+    player_names TEXT[] NOT NULL,
+    tile_seed BIGINT NOT NULL,
+    board TEXT NOT NULL,
+    -- Every move played so far in packed notation, which are replayed to reach the current position
+    moves TEXT NOT NULL,
+    move_count INT NOT NULL,
+    -- Each player's clock as JSON as of the last save, which replaying the moves can't recover
+    clocks TEXT NOT NULL,
+    -- The day the room was opened on, which picks the rules for any rematch
+    effective_day INT NOT NULL,
+    started_at BIGINT NOT NULL,
+    finished BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX games_unfinished_idx ON games (finished);
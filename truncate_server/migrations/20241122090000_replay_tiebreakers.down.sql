@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE game_replays
+    DROP COLUMN tiebreaker;
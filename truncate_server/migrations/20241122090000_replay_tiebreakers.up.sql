@@ -0,0 +1,3 @@
+-- The tiebreaker that decided a game which would otherwise have been drawn
+ALTER TABLE game_replays
+    ADD COLUMN tiebreaker VARCHAR(32);
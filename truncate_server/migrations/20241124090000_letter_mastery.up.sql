@@ -0,0 +1,9 @@
+-- Battles won and lost by each player with words containing each letter, across their online games
+CREATE TABLE letter_mastery (
+    player_id UUID NOT NULL REFERENCES players(player_id),
+    letter VARCHAR(4) NOT NULL,
+    battles_won INT NOT NULL DEFAULT 0,
+    battles_lost INT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (player_id, letter)
+);
@@ -0,0 +1,12 @@
+-- Every ruling a tournament arbiter has made on a game in progress, for reviewing disputes afterwards
+CREATE TABLE arbiter_actions (
+    action_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    room_code VARCHAR(32) NOT NULL,
+    action TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    -- How far into the game the ruling was made
+    turn_count INT NOT NULL,
+    acted_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX arbiter_actions_room_code ON arbiter_actions (room_code);
@@ -0,0 +1,11 @@
+-- Accounts with an OAuth provider that players sign in with, each linked to one player
+CREATE TABLE oauth_identities (
+    -- The provider's name, such as github or google
+    provider TEXT NOT NULL,
+    -- The provider's own ID for the account, which never changes
+    subject TEXT NOT NULL,
+    player_id UUID NOT NULL REFERENCES players(player_id),
+    linked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (provider, subject),
+    UNIQUE (player_id, provider)
+);
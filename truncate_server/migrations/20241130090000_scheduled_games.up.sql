@@ -0,0 +1,21 @@
+-- Games arranged between two players for a later time, which the server opens a room for when they're due
+CREATE TABLE scheduled_games (
+    -- Shared with the opponent to accept the invite
+    invite_code VARCHAR(32) PRIMARY KEY,
+    host_id UUID NOT NULL REFERENCES players(player_id),
+    host_name TEXT NOT NULL,
+    -- Unset until someone accepts the invite
+    guest_id UUID REFERENCES players(player_id),
+    guest_name TEXT,
+    -- The name of one of the rules presets, and the day whose rules it builds on
+    ruleset TEXT NOT NULL,
+    effective_day INT NOT NULL,
+    -- Seconds since the unix epoch
+    starts_at BIGINT NOT NULL,
+    reminded BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Set once the room has been opened
+    room_code VARCHAR(32),
+    scheduled_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX scheduled_games_starts_at_idx ON scheduled_games (starts_at);
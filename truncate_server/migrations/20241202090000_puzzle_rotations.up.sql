@@ -0,0 +1,21 @@
+-- Weekly and themed puzzles keep their attempts apart from the daily puzzle's,
+-- counted by each category's own periods rather than by day
+CREATE TABLE rotation_puzzle_results (
+    result_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    player_id UUID REFERENCES players(player_id),
+    category VARCHAR(32) NOT NULL,
+    period INT NOT NULL,
+    human_player INT NOT NULL,
+    UNIQUE(player_id, category, period)
+);
+
+CREATE TABLE rotation_puzzle_attempts (
+    attempt_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    result_id UUID REFERENCES rotation_puzzle_results(result_id) ON DELETE CASCADE,
+    sequence_of_moves TEXT NOT NULL DEFAULT '',
+    move_count INT NOT NULL DEFAULT 0,
+    won BOOLEAN NOT NULL DEFAULT false,
+    think_secs INT[] NOT NULL DEFAULT '{}',
+    attempt_number INT NOT NULL,
+    attempt_started TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
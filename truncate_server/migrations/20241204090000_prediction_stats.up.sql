@@ -0,0 +1,8 @@
+-- How well each player has guessed their opponents' moves in prediction training
+CREATE TABLE prediction_stats (
+    player_id UUID PRIMARY KEY REFERENCES players(player_id),
+    predictions INT NOT NULL DEFAULT 0,
+    exact INT NOT NULL DEFAULT 0,
+    close INT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
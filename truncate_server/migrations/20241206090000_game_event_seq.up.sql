@@ -0,0 +1,2 @@
+-- The last turn number sent to a saved game's players, so numbering carries on after a restart
+ALTER TABLE games ADD COLUMN event_seq BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,18 @@
+-- Only English dailies existed before this migration
+DELETE FROM daily_puzzle_attempts
+WHERE result_id IN (
+    SELECT result_id FROM daily_puzzle_results WHERE language <> 'en'
+);
+
+DELETE FROM daily_puzzle_results
+WHERE language <> 'en';
+
+ALTER TABLE daily_puzzle_results
+    DROP CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_track_language_key;
+
+ALTER TABLE daily_puzzle_results
+    ADD CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_track_key
+    UNIQUE (player_id, daily_puzzle, track);
+
+ALTER TABLE daily_puzzle_results
+    DROP COLUMN language;
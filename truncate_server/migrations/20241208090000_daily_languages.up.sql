@@ -0,0 +1,10 @@
+-- Daily puzzles and puzzle rushes are played per language, each with their own results and leaderboards
+ALTER TABLE daily_puzzle_results
+    ADD COLUMN language VARCHAR(8) NOT NULL DEFAULT 'en';
+
+ALTER TABLE daily_puzzle_results
+    DROP CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_track_key;
+
+ALTER TABLE daily_puzzle_results
+    ADD CONSTRAINT daily_puzzle_results_player_id_daily_puzzle_track_language_key
+    UNIQUE (player_id, daily_puzzle, track, language);
@@ -0,0 +1,4 @@
+ALTER TABLE daily_puzzle_attempts
+    DROP COLUMN rules_generation,
+    DROP COLUMN board_generation,
+    DROP COLUMN board_seed;
@@ -0,0 +1,6 @@
+-- Kept so that attempts can be replayed outside the game, such as from an exported archive.
+-- Attempts from before this are left without them, as the puzzle they were played on isn't known here.
+ALTER TABLE daily_puzzle_attempts
+    ADD COLUMN rules_generation INT,
+    ADD COLUMN board_generation INT,
+    ADD COLUMN board_seed INT;
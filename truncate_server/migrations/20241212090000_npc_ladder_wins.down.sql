@@ -0,0 +1,20 @@
+INSERT INTO daily_puzzle_results (player_id, daily_puzzle, human_player, track)
+SELECT DISTINCT player_id, rung, 0, 'npc_ladder'
+FROM npc_ladder_wins;
+
+INSERT INTO daily_puzzle_attempts (result_id, attempt_number, sequence_of_moves, move_count, won, attempt_started)
+SELECT
+    dpr.result_id,
+    (ROW_NUMBER() OVER (PARTITION BY dpr.result_id ORDER BY nlw.won_at) - 1)::INT,
+    nlw.sequence_of_moves,
+    nlw.move_count,
+    true,
+    nlw.won_at
+FROM
+    npc_ladder_wins nlw
+JOIN
+    daily_puzzle_results dpr ON dpr.player_id = nlw.player_id
+        AND dpr.daily_puzzle = nlw.rung
+        AND dpr.track = 'npc_ladder';
+
+DROP TABLE npc_ladder_wins;
@@ -0,0 +1,38 @@
+-- Wins over the NPC ladder get their own table, rather than posing as daily puzzles on a separate track
+CREATE TABLE npc_ladder_wins (
+    win_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    player_id UUID NOT NULL REFERENCES players(player_id),
+    rung INT NOT NULL,
+    sequence_of_moves TEXT NOT NULL,
+    move_count INT NOT NULL,
+    -- The board and rules the win was replayed on before it was stored
+    rules_generation INT,
+    board_generation INT,
+    board_seed INT,
+    won_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX npc_ladder_wins_player_id_idx ON npc_ladder_wins (player_id);
+
+-- Wins from before this weren't replayed, and don't know their board, but still count towards progress
+INSERT INTO npc_ladder_wins (player_id, rung, sequence_of_moves, move_count, won_at)
+SELECT
+    dpr.player_id,
+    dpr.daily_puzzle,
+    dpa.sequence_of_moves,
+    dpa.move_count,
+    COALESCE(dpa.attempt_started, CURRENT_TIMESTAMP)
+FROM
+    daily_puzzle_results dpr
+JOIN
+    daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
+WHERE
+    dpr.track = 'npc_ladder' AND dpa.won = true AND dpr.player_id IS NOT NULL;
+
+DELETE FROM daily_puzzle_attempts
+WHERE result_id IN (
+    SELECT result_id FROM daily_puzzle_results WHERE track = 'npc_ladder'
+);
+
+DELETE FROM daily_puzzle_results
+WHERE track = 'npc_ladder';
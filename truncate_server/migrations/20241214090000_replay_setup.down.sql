@@ -0,0 +1,4 @@
+ALTER TABLE game_replays
+    DROP COLUMN bag_seed,
+    DROP COLUMN rules,
+    DROP COLUMN player_names;
@@ -0,0 +1,6 @@
+-- Kept so that archived games can be played back from a link, such as one posted to a room's webhook.
+-- Replays from before this are left without them, and can't be watched.
+ALTER TABLE game_replays
+    ADD COLUMN bag_seed BIGINT,
+    ADD COLUMN rules TEXT,
+    ADD COLUMN player_names TEXT[];
@@ -0,0 +1,272 @@
+use std::{collections::BTreeMap, env, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use truncate_core::{
+    game::Game,
+    generation::{generate_board, get_game_verification, BoardSeed},
+    judge::WordDict,
+    messages::PlayerMessage,
+    moves::Move,
+    npc::{progression::puzzle_opponent, Arborist},
+    rules::{GameRules, Language},
+};
+use uuid::Uuid;
+
+use crate::{
+    connect_database,
+    definitions::read_defs,
+    storage::{accounts, daily, rooms},
+};
+
+const USAGE: &str = "Usage: truncate_server admin <command>
+    rooms                               List the rooms open on the server
+    streak <player id>                  Show a player's daily puzzle streak
+    reroll-day <day> [seed notes path]  Pick a new board for a day's puzzle
+    revoke-tokens <player id>           Stop a player's existing tokens from logging in";
+
+/// Where the dueller keeps its notes on each day's puzzle, relative to `truncate_server`
+const DEFAULT_SEED_NOTES: &str = "../truncate_dueller/seed_notes.yml";
+/// Rerolled puzzles that the NPCs can't finish in this many turns are skipped
+const MAX_PUZZLE_TURNS: u32 = 200;
+/// How many boards to try for a day before giving up
+const MAX_REROLLS: usize = 20;
+
+/// Matches the notes file written by `truncate_dueller`
+#[derive(Debug, Serialize, Deserialize)]
+struct SeedNote {
+    rerolls: usize,
+    best_player: usize,
+    board_generation: u32,
+    rules_generation: u32,
+    verification: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    npc_turns: Option<u32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    mystery: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    npc_rung: Option<u32>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct NotesFile {
+    notes: BTreeMap<u32, SeedNote>,
+}
+
+/// Runs one of the `truncate_server admin` commands, printing its results to stdout for scripts to read.
+/// Exits with a failure code if the command couldn't be completed.
+pub async fn run_admin_command(args: Vec<String>) {
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["rooms"] => list_rooms(&database().await).await,
+        ["streak", player] => show_streak(&database().await, player).await,
+        ["reroll-day", day] => reroll_day(day, DEFAULT_SEED_NOTES),
+        ["reroll-day", day, path] => reroll_day(day, path),
+        ["revoke-tokens", player] => revoke_tokens(&database().await, player).await,
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(e) = result {
+        error!("admin {} failed: {e}", args.join(" "));
+        std::process::exit(1);
+    }
+}
+
+async fn database() -> PgPool {
+    let Ok(db_url) = env::var("DATABASE_URL") else {
+        error!("admin commands need DATABASE_URL to be set");
+        std::process::exit(1);
+    };
+    connect_database(&db_url).await
+}
+
+fn parse_player(player: &str) -> Result<Uuid, String> {
+    Uuid::parse_str(player).map_err(|_| format!("{player} isn't a player ID"))
+}
+
+/// Prints a tab separated line for each room the server last published
+async fn list_rooms(pool: &PgPool) -> Result<(), String> {
+    let listed = rooms::load_rooms(pool).await.map_err(|e| e.to_string())?;
+
+    if let Some((_, published_at)) = listed.first() {
+        info!(%published_at, "Rooms were last published by the server");
+    } else {
+        warn!("No rooms are open, or the server isn't connected to this database");
+    }
+
+    println!("room\tstate\tturns\tidle_secs\tspectators\tplayers");
+    for (room, _) in listed {
+        let state = match (room.started, room.finished) {
+            (_, true) => "finished",
+            (true, false) => "playing",
+            (false, false) => "lobby",
+        };
+        println!(
+            "{}\t{state}\t{}\t{}\t{}\t{}",
+            room.room_code,
+            room.turn_count,
+            room.idle_secs,
+            room.spectators,
+            room.players.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+async fn show_streak(pool: &PgPool, player: &str) -> Result<(), String> {
+    let player_id = parse_player(player)?;
+    let stats = daily::load_player_stats(pool, player_id, Language::English)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let won_days = stats
+        .days
+        .values()
+        .filter(|day| day.attempts.iter().any(|a| a.won))
+        .count();
+
+    println!("player\t{player_id}");
+    println!("streak\t{}", stats.streak_length());
+    println!("days_played\t{}", stats.days.len());
+    println!("days_won\t{won_days}");
+    if let Some(latest_day) = stats.days.keys().last() {
+        println!("latest_day\t{latest_day}");
+    }
+
+    Ok(())
+}
+
+async fn revoke_tokens(pool: &PgPool, player: &str) -> Result<(), String> {
+    let player_id = parse_player(player)?;
+    accounts::revoke_tokens(pool, player_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!("revoked\t{player_id}");
+
+    Ok(())
+}
+
+/// Moves a day's puzzle on to the next board that the NPCs can win, and records it in the seed notes.
+/// Clients pick up the new board once they're rebuilt with the updated notes.
+fn reroll_day(day: &str, path: &str) -> Result<(), String> {
+    let day: u32 = day
+        .parse()
+        .map_err(|_| format!("{day} isn't a puzzle day"))?;
+    let path = PathBuf::from(path);
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+    let mut notes_file: NotesFile = serde_yaml::from_str(&contents)
+        .map_err(|e| format!("{} isn't a seed notes file: {e}", path.display()))?;
+
+    let current = notes_file.notes.get(&day);
+    let board_generation = current
+        .map(|note| note.board_generation)
+        .unwrap_or_else(|| BoardSeed::new(day).generation);
+    let rules_generation = GameRules::latest(Some(day)).0;
+    let first_reroll = current.map(|note| note.rerolls + 1).unwrap_or(1);
+    let mystery = current.is_some_and(|note| note.mystery);
+    let npc_rung = current.and_then(|note| note.npc_rung);
+
+    let dict = read_defs().valid_words;
+
+    let note = (first_reroll..first_reroll + MAX_REROLLS)
+        .find_map(|rerolls| {
+            let mut seed = BoardSeed::new_with_generation(board_generation, day);
+            for _ in 0..rerolls {
+                seed.external_reroll();
+            }
+
+            let game = puzzle_game(seed, rules_generation)?;
+            let verification = get_game_verification(&game);
+            let (best_player, npc_turns) = play_out(game, &dict, npc_rung)?;
+
+            Some(SeedNote {
+                rerolls,
+                best_player,
+                board_generation,
+                rules_generation,
+                verification,
+                npc_turns: Some(npc_turns),
+                mystery,
+                npc_rung,
+            })
+        })
+        .ok_or_else(|| format!("no winnable board in {MAX_REROLLS} rerolls"))?;
+
+    println!("day\t{day}");
+    println!("rerolls\t{}", note.rerolls);
+    println!("best_player\t{}", note.best_player);
+    println!("verification\t{}", note.verification);
+
+    notes_file.notes.insert(day, note);
+    let output = serde_yaml::to_string(&notes_file).map_err(|e| e.to_string())?;
+    std::fs::write(&path, output).map_err(|e| format!("couldn't write {}: {e}", path.display()))
+}
+
+/// Sets up a puzzle the same way the dueller and client do
+fn puzzle_game(seed: BoardSeed, rules_generation: u32) -> Option<Game> {
+    let mut board = generate_board(seed.clone()).ok()?.board;
+    board.cache_special_squares();
+
+    let mut game = Game::new(
+        9,
+        9,
+        Some(seed.seed as u64),
+        GameRules::generation(rules_generation),
+    );
+    game.add_player("P1".into());
+    game.add_player("P2".into());
+
+    game.board = board;
+    game.rules.battle_delay = 0;
+    game.start();
+
+    Some(game)
+}
+
+/// Plays the puzzle's NPC against itself, returning who won and after how many turns
+fn play_out(mut game: Game, dict: &WordDict, npc_rung: Option<u32>) -> Option<(usize, u32)> {
+    let npc_params = puzzle_opponent(npc_rung).params;
+
+    while game.turn_count < MAX_PUZZLE_TURNS {
+        let player = game.next_player?;
+
+        let mut arborist = Arborist::pruning();
+        arborist.capped(npc_params.evaluation_cap);
+        let (best_move, _) = Game::best_move(
+            &game,
+            Some(dict),
+            Some(dict),
+            npc_params.max_depth,
+            Some(&mut arborist),
+            false,
+            &npc_params,
+        );
+
+        let next_move = match best_move {
+            PlayerMessage::Place(position, tile) => Move::Place {
+                player,
+                tile,
+                position,
+            },
+            PlayerMessage::Swap(from, to) => Move::Swap {
+                player,
+                positions: [from, to],
+            },
+            _ => return None,
+        };
+
+        match game.play_turn(next_move, Some(dict), Some(dict), None) {
+            Ok(Some(winner)) => return Some((winner, game.turn_count)),
+            Ok(None) => {}
+            Err(_) => return None,
+        }
+    }
+
+    None
+}
@@ -0,0 +1,159 @@
+use std::{collections::VecDeque, env, net::SocketAddr, time::Duration};
+
+use tracing::warn;
+use truncate_core::{game, rules::GameRules};
+
+/// Rooms without any activity for this long stop counting towards the caps
+pub const LIVE_ROOM_SECS: u64 = 60 * 30;
+/// Seats held back for each new room, as most rooms are waiting on an opponent
+const SEATS_PER_ROOM: usize = 2;
+/// How late the queue watcher can wake up before we stop opening new rooms
+const OVERLOADED_LAG_MS: f64 = 500.0;
+/// How many recent admissions are used to estimate waiting times
+const ADMISSION_HISTORY: usize = 10;
+
+/// Fixed limits for the server, configured through `MAX_ROOMS` and `MAX_PLAYERS`
+#[derive(Debug, Default, Clone)]
+pub struct CapacityLimits {
+    pub max_rooms: Option<usize>,
+    pub max_players: Option<usize>,
+}
+
+impl CapacityLimits {
+    pub fn from_env() -> Self {
+        fn read(var: &str) -> Option<usize> {
+            let value = env::var(var).ok().filter(|v| !v.is_empty())?;
+            match value.parse() {
+                Ok(limit) => Some(limit),
+                Err(_) => {
+                    warn!(%var, %value, "Ignoring a capacity limit that isn't a number");
+                    None
+                }
+            }
+        }
+
+        Self {
+            max_rooms: read("MAX_ROOMS"),
+            max_players: read("MAX_PLAYERS"),
+        }
+    }
+}
+
+/// What the server is currently running
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Usage {
+    pub rooms: usize,
+    pub players: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueuedPlayer {
+    pub addr: SocketAddr,
+    pub player_name: String,
+    pub effective_day: u32,
+    pub rules: Option<GameRules>,
+}
+
+/// Holds back new rooms once the server is full, letting players in as space frees up
+#[derive(Debug, Default)]
+pub struct Capacity {
+    limits: CapacityLimits,
+    /// Players waiting to open a room, in the order they arrived
+    queue: VecDeque<QueuedPlayer>,
+    /// When players were recently let in from the queue
+    admissions: VecDeque<u64>,
+    /// Smoothed delay of the queue watcher waking up, as a measure of how loaded the server is
+    lag_ms: f64,
+}
+
+impl Capacity {
+    pub fn new(limits: CapacityLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// The most rooms allowed right now, which shrinks to what's already running
+    /// while the server is struggling to keep up
+    fn room_cap(&self, usage: &Usage) -> Option<usize> {
+        let overloaded = self.lag_ms > OVERLOADED_LAG_MS;
+        match self.limits.max_rooms {
+            Some(cap) if overloaded => Some(cap.min(usage.rooms)),
+            None if overloaded => Some(usage.rooms),
+            cap => cap,
+        }
+    }
+
+    fn has_space(&self, usage: &Usage) -> bool {
+        let rooms_free = self.room_cap(usage).map_or(true, |cap| usage.rooms < cap);
+        let seats_free = self
+            .limits
+            .max_players
+            .map_or(true, |cap| usage.players + SEATS_PER_ROOM <= cap);
+        rooms_free && seats_free
+    }
+
+    /// Whether a new room has to wait, which it also does behind anyone already queued
+    pub fn must_wait(&self, usage: &Usage) -> bool {
+        !self.queue.is_empty() || !self.has_space(usage)
+    }
+
+    /// Adds a player to the back of the queue, returning their position from one
+    pub fn enqueue(&mut self, player: QueuedPlayer) -> usize {
+        self.queue.retain(|queued| queued.addr != player.addr);
+        self.queue.push_back(player);
+        self.queue.len()
+    }
+
+    /// Takes the next player off the queue if there is space for their room
+    pub fn admit_next(&mut self, usage: &Usage) -> Option<QueuedPlayer> {
+        if !self.has_space(usage) {
+            return None;
+        }
+        let player = self.queue.pop_front()?;
+
+        self.admissions.push_back(game::now());
+        if self.admissions.len() > ADMISSION_HISTORY {
+            self.admissions.pop_front();
+        }
+        Some(player)
+    }
+
+    /// Takes a player out of the queue, returning whether they were in it
+    pub fn leave(&mut self, addr: &SocketAddr) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|queued| &queued.addr != addr);
+        self.queue.len() != before
+    }
+
+    /// Drops anyone from the queue who is no longer connected
+    pub fn retain_connected(&mut self, connected: impl Fn(&SocketAddr) -> bool) {
+        self.queue.retain(|queued| connected(&queued.addr));
+    }
+
+    pub fn queued(&self) -> impl Iterator<Item = (usize, &QueuedPlayer)> {
+        self.queue.iter().enumerate().map(|(i, p)| (i + 1, p))
+    }
+
+    /// Guesses how long the player at `position` will wait, based on how quickly
+    /// recent players got through the queue
+    pub fn estimated_wait_secs(&self, position: usize) -> Option<u64> {
+        if self.admissions.len() < 2 {
+            return None;
+        }
+        let (first, last) = (self.admissions.front()?, self.admissions.back()?);
+
+        let per_admission = (last - first) / (self.admissions.len() as u64 - 1);
+        Some(per_admission * position as u64)
+    }
+
+    /// Records how late the queue watcher woke up
+    pub fn record_lag(&mut self, lag: Duration) {
+        self.lag_ms = self.lag_ms * 0.8 + lag.as_secs_f64() * 1000.0 * 0.2;
+    }
+
+    pub fn lag_ms(&self) -> f64 {
+        self.lag_ms
+    }
+}
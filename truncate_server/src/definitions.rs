@@ -2,16 +2,28 @@ use std::collections::{HashMap, HashSet};
 
 use rand::seq::SliceRandom;
 use rusqlite::Connection;
+use serde::Deserialize;
+use tracing::{info, warn};
 use truncate_core::{
-    judge::{WordData, WordDict},
+    judge::{language_words, WordData, WordDict},
+    packs::{valid_pack_id, DictionaryPack},
     reporting::WordMeaning,
+    rules::{GameRules, Language},
 };
 
 pub static TRUNCATE_DICT: &str = include_str!("../../dict_builder/final_wordlist.txt");
+/// Words struck from the main dictionary, which can still win a battle on appeal
+pub static APPEAL_DICT: &str = include_str!("../../dict_builder/support_data/tranche_3_del.txt");
 
 pub struct WordDB {
     pub conn: Option<Connection>,
     pub valid_words: WordDict,
+    /// The main dictionary plus the words accepted on appeal
+    pub appeal_words: WordDict,
+    /// The languages other than English that this server has word lists for
+    pub languages: HashMap<Language, LoadedLanguage>,
+    /// Word lists that clients download as they need them, by pack ID
+    pub packs: HashMap<String, LoadedPack>,
     pub room_codes: Vec<String>,
     pub allocated_room_codes: HashSet<String>,
 }
@@ -36,6 +48,65 @@ impl WordDB {
             .flatten()
     }
 
+    /// The words a game in the given language is judged against, if this server has them
+    pub fn dictionary(&self, language: Language) -> Option<&WordDict> {
+        match language {
+            Language::English => Some(&self.valid_words),
+            language => self
+                .languages
+                .get(&language)
+                .map(|loaded| &loaded.dictionary),
+        }
+    }
+
+    /// The words a game with the given rules is judged against, preferring the room's dictionary pack.
+    /// Should a pack no longer be on the server, its games fall back to the language's usual dictionary.
+    pub fn rules_dictionary(&self, rules: &GameRules) -> Option<&WordDict> {
+        rules
+            .dictionary_pack
+            .as_ref()
+            .and_then(|pack| self.packs.get(&pack.id))
+            .map(|loaded| &loaded.dictionary)
+            .or_else(|| self.dictionary(rules.language))
+    }
+
+    /// Packs have nothing wider to appeal to, so appeals are judged against the pack itself
+    pub fn rules_appeal_dictionary(&self, rules: &GameRules) -> Option<&WordDict> {
+        rules
+            .dictionary_pack
+            .as_ref()
+            .and_then(|pack| self.packs.get(&pack.id))
+            .map(|loaded| &loaded.dictionary)
+            .or_else(|| self.appeal_dictionary(rules.language))
+    }
+
+    /// Every pack a room can be played with, in the order they're listed
+    pub fn available_packs(&self) -> Vec<DictionaryPack> {
+        let mut packs: Vec<_> = self.packs.values().map(|p| p.pack.clone()).collect();
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        packs
+    }
+
+    /// Only English has a wider list of words to appeal to,
+    /// so appeals in other languages are judged against their usual dictionary
+    pub fn appeal_dictionary(&self, language: Language) -> Option<&WordDict> {
+        match language {
+            Language::English => Some(&self.appeal_words),
+            language => self
+                .languages
+                .get(&language)
+                .map(|loaded| &loaded.dictionary),
+        }
+    }
+
+    /// Every language a room can be switched to
+    pub fn languages(&self) -> Vec<Language> {
+        Language::ALL
+            .into_iter()
+            .filter(|language| self.dictionary(*language).is_some())
+            .collect()
+    }
+
     fn rand_code(&self) -> String {
         self.room_codes
             .choose(&mut rand::thread_rng())
@@ -43,7 +114,6 @@ impl WordDB {
             .expect("No words in dataset")
     }
 
-    // TODO: Reclaim codes after use
     pub fn get_free_code(&mut self) -> String {
         let mut word = self.rand_code();
         while self.allocated_room_codes.get(&word).is_some() {
@@ -52,14 +122,23 @@ impl WordDB {
         self.allocated_room_codes.insert(word.clone());
         word
     }
+
+    /// Holds onto a code that a restored room is using, so it isn't given out again
+    pub fn reserve_code(&mut self, code: &str) {
+        self.allocated_room_codes.insert(code.to_lowercase());
+    }
+
+    pub fn release_code(&mut self, code: &str) {
+        self.allocated_room_codes.remove(&code.to_lowercase());
+    }
 }
 
 pub fn read_defs() -> WordDB {
-    println!("Loading word definitions...");
+    info!("Loading word definitions...");
 
     let defs_file = option_env!("TR_DEFS_FILE").unwrap_or_else(|| "/truncate/defs.db");
 
-    let mut valid_words = HashMap::new();
+    let mut valid_words = WordDict::new();
     let lines = TRUNCATE_DICT.lines();
 
     for line in lines {
@@ -81,26 +160,172 @@ pub fn read_defs() -> WordDB {
         );
     }
 
+    let mut appeal_words = valid_words.clone();
+    for word in APPEAL_DICT.lines().map(str::trim).filter(|w| !w.is_empty()) {
+        let word = word.to_lowercase();
+        if !appeal_words.contains_key(&word) {
+            appeal_words.insert(
+                word,
+                WordData {
+                    extensions: 0,
+                    rel_freq: 0.0,
+                    objectionable: false,
+                },
+            );
+        }
+    }
+
+    let languages = read_languages();
+    let packs = read_packs();
+
     let word_db_connection = Connection::open(defs_file).ok();
     if word_db_connection.is_some() {
-        println!("Connected to the word definition database at {defs_file}");
+        info!("Connected to the word definition database at {defs_file}");
     } else {
-        println!("No word definitions available at {defs_file}. Set a TR_DEFS_FILE environment variable to point to a word db.");
+        warn!("No word definitions available at {defs_file}. Set a TR_DEFS_FILE environment variable to point to a word db.");
     }
 
     let room_codes: Vec<_> = valid_words
         .iter()
         .filter(|(word, data)| word.len() < 6 && !data.objectionable)
-        .map(|(word, _)| word)
-        .cloned()
+        .map(|(word, _)| word.to_string())
         .collect();
 
-    println!("There are {} room codes available", room_codes.len());
+    info!("There are {} room codes available", room_codes.len());
 
     WordDB {
         conn: word_db_connection,
         room_codes,
         valid_words,
+        appeal_words,
+        languages,
+        packs,
         allocated_room_codes: HashSet::new(),
     }
 }
+
+/// A language's word list, as sent to clients, and the words as judged,
+/// along with the notes for its daily puzzles if the dueller has made them
+pub struct LoadedLanguage {
+    pub words: String,
+    pub dictionary: WordDict,
+    pub seed_notes: Option<String>,
+}
+
+/// Loads the word list for each language other than English from `<code>.txt` in the languages directory,
+/// with one word per line and objectionable words starting with a `*`.
+/// The dueller's `seed_notes_<code>.yml` for the language is read from the same directory.
+fn read_languages() -> HashMap<Language, LoadedLanguage> {
+    let languages_dir = option_env!("TR_LANGUAGES_DIR").unwrap_or_else(|| "/truncate/languages");
+
+    let mut by_language = HashMap::new();
+    for language in Language::ALL {
+        if language == Language::English {
+            continue;
+        }
+
+        let path = format!("{languages_dir}/{}.txt", language.code());
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let dictionary = language_words(&contents, language);
+        info!("Loaded {} {language} words from {path}", dictionary.len());
+
+        let notes_path = format!("{languages_dir}/seed_notes_{}.yml", language.code());
+        let seed_notes = std::fs::read_to_string(&notes_path).ok();
+        if seed_notes.is_none() {
+            info!("No daily puzzles in {language}, as there is nothing at {notes_path}");
+        }
+
+        by_language.insert(
+            language,
+            LoadedLanguage {
+                words: contents,
+                dictionary,
+                seed_notes,
+            },
+        );
+    }
+
+    if by_language.is_empty() {
+        warn!("No word lists for other languages at {languages_dir}. Set a TR_LANGUAGES_DIR environment variable to offer them.");
+    }
+
+    by_language
+}
+
+/// A dictionary pack along with its word list, as sent to clients, and the words as judged
+pub struct LoadedPack {
+    pub pack: DictionaryPack,
+    pub words: String,
+    pub dictionary: WordDict,
+}
+
+/// An entry in the packs directory's `packs.yml`, where each pack's words are in `<id>.txt`
+#[derive(Deserialize)]
+struct PackListing {
+    id: String,
+    name: String,
+    version: u32,
+    language: Language,
+}
+
+/// Loads the dictionary packs listed in `packs.yml` in the packs directory,
+/// with each word list using the same format as the languages' lists
+fn read_packs() -> HashMap<String, LoadedPack> {
+    let packs_dir = option_env!("TR_PACKS_DIR").unwrap_or_else(|| "/truncate/packs");
+
+    let Ok(listing) = std::fs::read_to_string(format!("{packs_dir}/packs.yml")) else {
+        info!("No dictionary packs at {packs_dir}. Set a TR_PACKS_DIR environment variable to offer them.");
+        return HashMap::new();
+    };
+    let listings: Vec<PackListing> = match serde_yaml::from_str(&listing) {
+        Ok(listings) => listings,
+        Err(e) => {
+            warn!("Couldn't read the dictionary packs listed in {packs_dir}/packs.yml: {e}");
+            return HashMap::new();
+        }
+    };
+
+    let mut packs = HashMap::new();
+    for listing in listings {
+        if !valid_pack_id(&listing.id) {
+            warn!("Skipping the dictionary pack {:?}, as IDs can only use lowercase letters, digits, - and _", listing.id);
+            continue;
+        }
+
+        let path = format!("{packs_dir}/{}.txt", listing.id);
+        let Ok(words) = std::fs::read_to_string(&path) else {
+            warn!(
+                "Skipping the dictionary pack {}, as {path} couldn't be read",
+                listing.id
+            );
+            continue;
+        };
+
+        let pack = DictionaryPack::new(
+            listing.id,
+            listing.name,
+            listing.version,
+            listing.language,
+            &words,
+        );
+        let dictionary = pack.dictionary(&words);
+
+        info!(
+            "Loaded the {} dictionary pack, version {}, with {} words",
+            pack.id, pack.version, pack.word_count
+        );
+        packs.insert(
+            pack.id.clone(),
+            LoadedPack {
+                pack,
+                words,
+                dictionary,
+            },
+        );
+    }
+
+    packs
+}
@@ -15,4 +15,14 @@ pub enum TruncateServerError {
     PuzzleComplete,
     #[error("something about this request was malformed")]
     BadRequest,
+    #[error("snapshot file error: {0}")]
+    SnapshotFile(String),
+    #[error("snapshot doesn't fit this deployment: {0}")]
+    SnapshotMismatch(String),
+    #[error("couldn't compress replay: {0}")]
+    ReplayCompression(String),
+    #[error("sign in provider failed: {0}")]
+    Provider(String),
+    #[error("this player is already linked to another account with that provider")]
+    AlreadyLinked,
 }
@@ -0,0 +1,24 @@
+use std::env;
+
+use tracing::warn;
+use truncate_core::flags::Rollout;
+
+/// Reads the percentage of sessions that get each experiment, which are all off unless set
+pub fn rollout_from_env() -> Rollout {
+    fn read(var: &str) -> u32 {
+        let Some(value) = env::var(var).ok().filter(|v| !v.is_empty()) else {
+            return 0;
+        };
+        match value.parse::<u32>() {
+            Ok(percent) if percent <= 100 => percent,
+            _ => {
+                warn!(%var, %value, "Ignoring an experiment rollout that isn't a percentage");
+                0
+            }
+        }
+    }
+
+    Rollout {
+        new_battle_animation: read("ROLLOUT_NEW_BATTLE_ANIMATION"),
+    }
+}
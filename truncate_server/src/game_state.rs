@@ -1,24 +1,73 @@
 use instant::Duration;
 use parking_lot::{Mutex, MutexGuard};
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::Arc,
+};
+use tracing::{debug, info, instrument, warn};
 use truncate_core::{
     board::{Board, Coordinate},
-    game::Game,
+    game::{self, Game},
     generation::{ArtifactType, BoardParams},
-    messages::{GameMessage, GamePlayerMessage, GameStateMessage, LobbyPlayerMessage},
+    mastery::{BattleSide, LetterMastery},
+    messages::{
+        ArbiterAction, ChatChannel, GameEndStateMessage, GameMessage, GamePlayerMessage,
+        GameStateMessage, LobbyPlayerMessage, RoomRole, SequencedChanges,
+    },
     moves::Move,
+    replay::Replay,
     reporting::Change,
-    rules::GameRules,
+    rules::{GameRules, HandSwaps, Timing, Visibility},
+};
+use uuid::Uuid;
+
+use crate::{
+    capacity,
+    definitions::WordDB,
+    storage::{
+        games::{SavedClock, SavedGame},
+        replays::GameReplay,
+        rooms::RoomSummary,
+    },
+    webhooks::{self, RoomEvent, RoomEventKind, TurnNotice},
 };
 
-use crate::definitions::WordDB;
+/// Longer chat messages are cut off rather than rejected
+const MAX_CHAT_LENGTH: usize = 280;
+/// Turns kept for players who haven't acknowledged them, so that a client
+/// that never acks can't grow the buffer forever
+const EVENT_BUFFER_LIMIT: usize = 64;
+/// Longest that a room can hold spectators back for
+pub const MAX_SPECTATOR_DELAY_SECS: u64 = 60 * 10;
+/// Most blank tiles a room's bag can hold
+pub const MAX_BLANK_TILES: usize = 8;
+/// How long a player can be disconnected from a game in progress before they're counted as having left it
+pub const ABANDON_GRACE_SECS: u64 = 60 * 2;
+/// Games giving at least this long for each move are saved to the database as they're played,
+/// so that players can leave for days and the game survives the server restarting meanwhile
+pub const CORRESPONDENCE_MOVE_SECS: usize = 60 * 60;
+
+/// The changes from one turn, as filtered for each player
+struct BufferedEvent {
+    seq: u64,
+    changes: Vec<Vec<Change>>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Player {
     pub socket: Option<SocketAddr>,
 }
 
+/// Someone removed from a lobby, by the account they were logged in to or else the name they joined with,
+/// since either outlasts the connection they were removed on
+#[derive(Debug, Clone, PartialEq)]
+enum KickedPlayer {
+    Account(Uuid),
+    Name(String),
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PlayerClaims {
     pub player_index: usize,
@@ -28,8 +77,53 @@ pub struct PlayerClaims {
 pub struct GameManager {
     pub game_id: String,
     pub players: Vec<Player>,
+    /// What each player index is allowed to change about the room, starting with its host
+    pub roles: Vec<RoomRole>,
+    /// Players removed from the lobby, who can't join it again
+    kicked: Vec<KickedPlayer>,
     pub core_game: Game,
     pub effective_day: u32,
+    /// When a player last did anything in this room, used to expire stuck games
+    pub last_activity: u64,
+    /// Chat channels each player index has explicitly muted or unmuted
+    pub chat_mutes: HashMap<(usize, ChatChannel), bool>,
+    /// Sequence number of the most recent turn
+    pub event_seq: u64,
+    /// Turns that at least one player hasn't acknowledged yet
+    event_buffer: VecDeque<BufferedEvent>,
+    /// The last turn each player index has acknowledged
+    acked_events: Vec<u64>,
+    /// Where to post this room's start and end, if an operator has flagged it
+    pub webhook: Option<String>,
+    /// The latest event already posted to the webhook
+    reported: Option<RoomEventKind>,
+    /// Connections watching the room rather than playing in it
+    pub spectators: Vec<SocketAddr>,
+    /// How far spectators trail behind the players, so that a streamed game can't be used to cheat
+    pub spectator_delay_secs: u64,
+    /// Views of the game held back from spectators, alongside when each is due to be released
+    spectator_queue: VecDeque<(u64, GameStateMessage)>,
+    /// The latest view released to spectators, for anyone who starts watching partway through
+    spectator_view: Option<GameStateMessage>,
+    /// The gallery board (and its name) the room is set up with, until the board is edited
+    pub gallery_board: Option<(Uuid, String)>,
+    /// The board as the game started on it, and every move played since, for the replay archive
+    starting_board: Option<Board>,
+    move_history: Vec<Move>,
+    /// Whether the finished game has already been handed over for archiving
+    archived: bool,
+    /// What the finished game will be archived as, so that it can be linked to straight away
+    replay_id: Uuid,
+    /// How many moves had been played, and whether the game was over, when it was last saved
+    saved: Option<(usize, bool)>,
+    /// The account each player index is logged in to, for those who are
+    accounts: HashMap<usize, Uuid>,
+    /// How each player index has fared with each letter in this game's battles
+    letter_mastery: Vec<LetterMastery>,
+    /// Whether the letter mastery from the finished game has already been handed over
+    mastery_recorded: bool,
+    /// Set while a tournament arbiter has frozen the game, which holds every move and pause until they resume it
+    arbiter_frozen: bool,
 }
 
 impl GameManager {
@@ -40,9 +134,271 @@ impl GameManager {
         Self {
             game_id,
             players: vec![],
+            roles: vec![],
+            kicked: vec![],
             core_game: game,
             effective_day,
+            last_activity: game::now(),
+            chat_mutes: HashMap::new(),
+            event_seq: 0,
+            event_buffer: VecDeque::new(),
+            acked_events: vec![],
+            webhook: None,
+            reported: None,
+            spectators: vec![],
+            spectator_delay_secs: 0,
+            spectator_queue: VecDeque::new(),
+            spectator_view: None,
+            gallery_board: None,
+            starting_board: None,
+            move_history: vec![],
+            archived: false,
+            replay_id: Uuid::new_v4(),
+            saved: None,
+            accounts: HashMap::new(),
+            letter_mastery: vec![],
+            mastery_recorded: false,
+            arbiter_frozen: false,
+        }
+    }
+
+    /// Sets a correspondence game back up from the database, by playing its moves back from the start
+    pub fn restore(saved: SavedGame, words: &WordDB) -> Result<Self, String> {
+        let mut manager = Self::new(saved.room_code.to_lowercase(), saved.effective_day);
+        manager.core_game = Game::new(9, 9, Some(saved.tile_seed), saved.rules);
+        for name in saved.player_names {
+            manager
+                .add_player(Player { socket: None }, name)
+                .map_err(|_| "Couldn't seat the players".to_string())?;
+        }
+        manager.core_game.board = saved.board.clone();
+
+        let dictionary = words.rules_dictionary(&manager.core_game.rules);
+        manager.core_game =
+            Replay::from_game(manager.core_game.clone(), saved.moves.clone(), dictionary)
+                .finish()
+                .map_err(|e| e.to_string())?;
+
+        // Moves were all replayed just now, so put the clocks back to how they were actually running
+        manager.core_game.started_at = Some(saved.started_at);
+        for (player, clock) in manager.core_game.players.iter_mut().zip(&saved.clocks) {
+            clock.restore(player);
+        }
+        // Anyone who ran out of time while the server was down has lost by now
+        manager.core_game.calculate_game_over(None);
+
+        manager.starting_board = Some(saved.board);
+        manager.saved = Some((saved.moves.len(), false));
+        manager.move_history = saved.moves;
+        // Turns from before the restart can't be resent, but numbering carries on from them
+        // so that reconnecting players aren't told they're ahead of the game
+        manager.event_seq = saved.event_seq;
+        manager.acked_events.fill(saved.event_seq);
+
+        Ok(manager)
+    }
+
+    pub fn touch(&mut self) {
+        self.last_activity = game::now();
+    }
+
+    /// Whether this room is still being played in, and so takes up server capacity
+    pub fn is_live(&self) -> bool {
+        !self.core_game.is_over()
+            && game::now().saturating_sub(self.last_activity) < capacity::LIVE_ROOM_SECS
+    }
+
+    /// Posts to the room's webhook when the game has started or ended since we last did
+    pub fn report_to_webhook(&mut self) {
+        let Some(url) = &self.webhook else {
+            return;
+        };
+        let event = if self.core_game.is_over() {
+            RoomEventKind::Ended
+        } else if self.core_game.started_at.is_some() {
+            RoomEventKind::Started
+        } else {
+            return;
+        };
+        if self.reported == Some(event) {
+            return;
         }
+        self.reported = Some(event);
+
+        // Finished games link to their replay, which is archived from the same moves
+        let replay_id = match event {
+            RoomEventKind::Ended if self.starting_board.is_some() => Some(self.replay_id),
+            _ => None,
+        };
+        let room_link = match replay_id {
+            Some(replay_id) => webhooks::replay_link(&replay_id),
+            None => webhooks::room_link(&self.game_id),
+        };
+
+        webhooks::send(
+            url.clone(),
+            RoomEvent {
+                event,
+                room_code: self.game_id.clone(),
+                players: self
+                    .core_game
+                    .players
+                    .iter()
+                    .map(|p| p.name.clone())
+                    .collect(),
+                winner: self.core_game.winner,
+                adjudication: self.core_game.adjudication.as_ref().map(|a| a.to_string()),
+                tiebreaker: self.core_game.tiebreaker.map(|t| t.to_string()),
+                turns: self.core_game.turn_count,
+                replay_id,
+                room_link,
+                sent_at: game::now(),
+            },
+        );
+    }
+
+    /// Truncate has no ratings yet, so games played to the standard rules count as rated,
+    /// while chaos rules mark a room as casual
+    pub fn is_rated(&self) -> bool {
+        self.players.len() > 1 && matches!(self.core_game.rules.hand_swaps, HandSwaps::None)
+    }
+
+    /// How the room looks to an operator listing what's open on the server
+    pub fn summary(&self, current_time: u64) -> RoomSummary {
+        RoomSummary {
+            room_code: self.game_id.clone(),
+            players: self
+                .core_game
+                .players
+                .iter()
+                .map(|p| p.name.clone())
+                .collect(),
+            spectators: self.spectators.len(),
+            started: self.core_game.started_at.is_some(),
+            finished: self.core_game.is_over(),
+            turn_count: self.core_game.turn_count,
+            idle_secs: current_time.saturating_sub(self.last_activity),
+        }
+    }
+
+    /// Whether the rules give long enough for each move that this game is saved as it's played
+    pub fn is_correspondence(&self) -> bool {
+        matches!(
+            self.core_game.rules.timing,
+            Timing::PerPlayer {
+                move_limit: Some(move_limit),
+                ..
+            } if move_limit >= CORRESPONDENCE_MOVE_SECS
+        )
+    }
+
+    /// This correspondence game as it stands, if it has changed since it was last saved
+    pub fn take_save(&mut self) -> Option<SavedGame> {
+        if !self.is_correspondence() {
+            return None;
+        }
+        let started_at = self.core_game.started_at?;
+        let board = self.starting_board.clone()?;
+
+        let current = (self.move_history.len(), self.core_game.is_over());
+        if self.saved == Some(current) {
+            return None;
+        }
+        self.saved = Some(current);
+
+        Some(SavedGame {
+            room_code: self.game_id.clone(),
+            rules: self.core_game.rules.clone(),
+            player_names: self
+                .core_game
+                .players
+                .iter()
+                .map(|p| p.name.clone())
+                .collect(),
+            tile_seed: self.core_game.seed(),
+            board,
+            moves: self.move_history.clone(),
+            clocks: self.core_game.players.iter().map(SavedClock::of).collect(),
+            effective_day: self.effective_day,
+            started_at,
+            finished: current.1,
+            event_seq: self.event_seq,
+        })
+    }
+
+    /// Who this game is waiting on, to let them know it's their turn
+    pub fn turn_notice(&self) -> Option<TurnNotice> {
+        if self.core_game.is_over() {
+            return None;
+        }
+        let player = self.core_game.next_player?;
+
+        Some(TurnNotice {
+            room_code: self.game_id.clone(),
+            player,
+            player_name: self.core_game.players.get(player)?.name.clone(),
+            move_deadline: self.core_game.move_deadline(player),
+            room_link: webhooks::room_link(&self.game_id),
+            sent_at: game::now(),
+        })
+    }
+
+    /// The replay of this room's game, the first time this is called after it has finished
+    pub fn take_replay(&mut self) -> Option<GameReplay> {
+        if self.archived || !self.core_game.is_over() {
+            return None;
+        }
+        let board = self.starting_board.clone()?;
+        self.archived = true;
+
+        Some(GameReplay {
+            replay_id: self.replay_id,
+            room_code: self.game_id.clone(),
+            player_count: self.core_game.players.len(),
+            rated: self.is_rated(),
+            winner: self.core_game.winner,
+            tiebreaker: self.core_game.tiebreaker,
+            board,
+            moves: self.move_history.clone(),
+            bag_seed: self.core_game.seed(),
+            rules: self.core_game.rules.clone(),
+            player_names: self
+                .core_game
+                .players
+                .iter()
+                .map(|p| p.name.clone())
+                .collect(),
+        })
+    }
+
+    /// Hands and bag contents, only available to send once the game is over
+    pub fn end_state(&self) -> GameEndStateMessage {
+        GameEndStateMessage::new(&self.core_game).expect("Game should be over")
+    }
+
+    /// Messages every player that the game has ended without a winner
+    pub fn drawn_messages(
+        &self,
+        word_map: Option<&MutexGuard<'_, WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        let Some(adjudication) = &self.core_game.adjudication else {
+            return vec![];
+        };
+
+        self.players
+            .iter()
+            .enumerate()
+            .map(|(player_index, player)| {
+                (
+                    player,
+                    GameMessage::GameDrawn(
+                        self.game_msg(player_index, word_map),
+                        adjudication.clone(),
+                        self.end_state(),
+                    ),
+                )
+            })
+            .collect()
     }
 
     pub fn get_player_index(&self, player_addr: SocketAddr) -> Option<usize> {
@@ -64,7 +420,17 @@ impl GameManager {
         }
         // TODO: Check player #
         self.core_game.add_player(name);
+        // Anyone who was watching the lobby is a player now, and should see their own hand
+        self.spectators
+            .retain(|spectator| Some(*spectator) != player.socket);
         self.players.push(player);
+        self.roles.push(if self.roles.is_empty() {
+            RoomRole::Host
+        } else {
+            RoomRole::Player
+        });
+        self.acked_events.push(0);
+        self.touch();
         Ok(self.players.len() - 1)
     }
 
@@ -75,7 +441,7 @@ impl GameManager {
                 Ok(())
             }
             None => {
-                eprintln!("Couldn't reconnect player. Nothing stored for player {index}");
+                warn!("Couldn't reconnect player. Nothing stored for player {index}");
                 Err(())
             }
         }
@@ -86,7 +452,7 @@ impl GameManager {
             self.core_game.players[player_index].name = name;
             Ok(())
         } else {
-            eprintln!("Couldn't rename player. Nothing stored for player {socket}");
+            warn!("Couldn't rename player. Nothing stored for player {socket}");
             Err(())
         }
     }
@@ -99,19 +465,134 @@ impl GameManager {
                 name: p.name.clone(),
                 index: p.index,
                 color: p.color,
+                role: self.roles.get(p.index).copied().unwrap_or_default(),
             })
             .collect()
     }
 
+    pub fn role_of(&self, player: SocketAddr) -> Option<RoomRole> {
+        if self.spectators.contains(&player) {
+            return Some(RoomRole::Spectator);
+        }
+        let player_index = self.get_player_index(player)?;
+        self.roles.get(player_index).copied()
+    }
+
+    /// Whether the player is the room's host or a co-host, and so can set up its game
+    pub fn can_manage(&self, player: SocketAddr) -> bool {
+        self.role_of(player).is_some_and(|role| role.can_manage())
+    }
+
+    pub fn was_kicked(&self, account: Option<Uuid>, name: &str) -> bool {
+        self.kicked.iter().any(|kicked| match kicked {
+            KickedPlayer::Account(kicked_account) => Some(*kicked_account) == account,
+            KickedPlayer::Name(kicked_name) => kicked_name.eq_ignore_ascii_case(name),
+        })
+    }
+
+    /// Hands out a role, which only the host can do, and never their own role or the host's
+    pub fn promote_player(
+        &mut self,
+        by: SocketAddr,
+        player_index: usize,
+        role: RoomRole,
+    ) -> Result<(), String> {
+        if self.role_of(by) != Some(RoomRole::Host) {
+            return Err("Only the host can change who can set up the room".into());
+        }
+        if role == RoomRole::Host {
+            return Err("A room can only have one host".into());
+        }
+        if role == RoomRole::Spectator {
+            return Err(
+                "Players can't be made spectators, they can leave and spectate instead".into(),
+            );
+        }
+        match self.roles.get_mut(player_index) {
+            Some(RoomRole::Host) => Err("The host's role can't be changed".into()),
+            Some(existing_role) => {
+                *existing_role = role;
+                self.touch();
+                Ok(())
+            }
+            None => Err("That player isn't in the room".into()),
+        }
+    }
+
+    /// Removes a player from the lobby, returning the connection they were playing on.
+    /// Hosts can remove anyone else, and co-hosts can remove players without a role.
+    pub fn kick_player(
+        &mut self,
+        by: SocketAddr,
+        player_index: usize,
+    ) -> Result<Option<SocketAddr>, String> {
+        if self.core_game.started_at.is_some() {
+            return Err("Players can't be removed once a game has started".into());
+        }
+        let Some(kicker_role) = self.role_of(by).filter(|role| role.can_manage()) else {
+            return Err("Only the host or a co-host can remove players".into());
+        };
+        let Some(role) = self.roles.get(player_index).copied() else {
+            return Err("That player isn't in the room".into());
+        };
+        if self.get_player_index(by) == Some(player_index) {
+            return Err("You can't remove yourself from the room".into());
+        }
+        if role == RoomRole::Host || (role == RoomRole::CoHost && kicker_role != RoomRole::Host) {
+            return Err(format!("Only the host can remove a {role}"));
+        }
+
+        self.kicked.push(match self.accounts.get(&player_index) {
+            Some(account) => KickedPlayer::Account(*account),
+            None => KickedPlayer::Name(self.core_game.players[player_index].name.clone()),
+        });
+
+        let removed = self.players.remove(player_index);
+        self.roles.remove(player_index);
+        self.acked_events.remove(player_index);
+        self.core_game.remove_player(player_index);
+        self.chat_mutes = self
+            .chat_mutes
+            .drain()
+            .filter(|((index, _), _)| *index != player_index)
+            .map(|((index, channel), muted)| {
+                let index = if index > player_index {
+                    index - 1
+                } else {
+                    index
+                };
+                ((index, channel), muted)
+            })
+            .collect();
+        self.accounts = self
+            .accounts
+            .drain()
+            .filter(|(index, _)| *index != player_index)
+            .map(|(index, account)| {
+                let index = if index > player_index {
+                    index - 1
+                } else {
+                    index
+                };
+                (index, account)
+            })
+            .collect();
+
+        self.touch();
+        Ok(removed.socket)
+    }
+
     pub fn edit_board(&mut self, board: Board) {
         self.core_game.board = board;
+        self.touch();
     }
 
-    pub fn game_msg(
+    /// The latest turn's changes visible to a player, with any definitions we have attached
+    fn player_changes(
         &self,
         player_index: usize,
         word_map: Option<&MutexGuard<'_, WordDB>>,
-    ) -> GameStateMessage {
+    ) -> (Board, Vec<Change>) {
         let (board, mut changes) = self.core_game.filter_game_to_player(player_index);
 
         if let Some(definitions) = word_map {
@@ -143,6 +624,16 @@ impl GameManager {
             }
         }
 
+        (board, changes)
+    }
+
+    pub fn game_msg(
+        &self,
+        player_index: usize,
+        word_map: Option<&MutexGuard<'_, WordDB>>,
+    ) -> GameStateMessage {
+        let (board, changes) = self.player_changes(player_index, word_map);
+
         let hand = self
             .core_game
             .get_player(player_index)
@@ -169,12 +660,15 @@ impl GameManager {
             board,
             hand,
             changes,
+            event_seq: self.event_seq,
             game_ends_at: self.core_game.game_ends_at,
             paused: self.core_game.paused,
             remaining_turns,
+            hand_swap_in: self.core_game.turns_until_hand_swap(),
         }
     }
 
+    #[instrument(skip_all, fields(room = %self.game_id))]
     pub fn start(&mut self) -> Vec<(Player, GameMessage)> {
         // TODO: Check correct # of players
 
@@ -208,6 +702,10 @@ impl GameManager {
         self.core_game.board.trim();
 
         self.core_game.start();
+        self.starting_board = Some(self.core_game.board.clone());
+        self.touch();
+        self.report_to_webhook();
+        self.queue_spectator_view();
         let mut messages = Vec::with_capacity(self.players.len());
 
         // TODO: Maintain an index of Player to the Game player index
@@ -217,14 +715,27 @@ impl GameManager {
                 player.clone(),
                 GameMessage::StartedGame(self.game_msg(player_index, None)),
             ));
+            if self.core_game.can_mulligan(player_index) {
+                let divergence_bits = self
+                    .core_game
+                    .hand_divergence(player_index)
+                    .unwrap_or_default();
+                messages.push((
+                    player.clone(),
+                    GameMessage::MulliganOffer { divergence_bits },
+                ));
+            }
         }
 
         messages
     }
 
+    #[instrument(skip_all, fields(room = %self.game_id, peer = %player))]
     pub fn resign(&mut self, player: SocketAddr) -> Vec<(&Player, GameMessage)> {
         if let Some(player_index) = self.get_player_index(player) {
             self.core_game.resign_player(player_index);
+            self.report_to_webhook();
+            self.queue_spectator_view();
             let mut messages = Vec::with_capacity(self.players.len());
 
             if let Some(winner) = self.core_game.winner {
@@ -233,7 +744,11 @@ impl GameManager {
                     end_game_msg.changes = vec![];
                     messages.push((
                         player,
-                        GameMessage::GameEnd(self.game_msg(player_index, None), winner as u64),
+                        GameMessage::GameEnd(
+                            self.game_msg(player_index, None),
+                            winner as u64,
+                            self.end_state(),
+                        ),
                     ));
                 }
             }
@@ -244,6 +759,7 @@ impl GameManager {
         }
     }
 
+    #[instrument(skip_all, fields(room = %self.game_id, peer = %player))]
     pub fn play(
         &mut self,
         player: SocketAddr,
@@ -251,32 +767,50 @@ impl GameManager {
         tile: char,
         words: Arc<Mutex<WordDB>>,
     ) -> Vec<(&Player, GameMessage)> {
+        if self.arbiter_frozen {
+            return self.refuse_while_frozen(player);
+        }
         let mut messages = Vec::with_capacity(self.players.len());
+        self.touch();
+        debug!(%position, %tile, "Placing tile");
 
         if let Some(player_index) = self.get_player_index(player) {
             let words_db = words.lock();
-            match self.core_game.play_turn(
-                Move::Place {
-                    player: player_index,
-                    tile,
-                    position,
-                },
-                Some(&words_db.valid_words),
-                Some(&words_db.valid_words),
-                None,
-            ) {
+            let played = Move::Place {
+                player: player_index,
+                tile,
+                position,
+            };
+            let dictionary = words_db.rules_dictionary(&self.core_game.rules);
+            let result = self
+                .core_game
+                .play_turn(played.clone(), dictionary, dictionary, None)
+                .map(|winner| winner.or_else(|| self.core_game.resolve_endgame([dictionary; 2])));
+            if result.is_ok() {
+                self.move_history.push(played);
+                self.tally_letters(player_index);
+                self.record_event(Some(&words_db));
+            }
+
+            match result {
                 Ok(Some(winner)) => {
+                    info!(winner, "Game won");
                     for (player_index, player) in self.players.iter().enumerate() {
                         messages.push((
                             player,
                             GameMessage::GameEnd(
                                 self.game_msg(player_index, Some(&words_db)),
                                 winner as u64,
+                                self.end_state(),
                             ),
                         ));
                     }
                     return messages;
                 }
+                Ok(None) if self.core_game.adjudication.is_some() => {
+                    info!(adjudication = ?self.core_game.adjudication, "Game adjudicated");
+                    self.drawn_messages(Some(&words_db))
+                }
                 Ok(None) => {
                     for (player_index, player) in self.players.iter().enumerate() {
                         messages.push((
@@ -287,14 +821,15 @@ impl GameManager {
                     return messages;
                 }
                 Err(msg) => {
+                    debug!(%msg, "Rejected move");
                     return vec![(
                         &self.players[player_index],
                         GameMessage::GameError(
                             self.game_id.clone(),
                             player_index as u64,
-                            msg.into(),
+                            msg.to_string(),
                         ),
-                    )]
+                    )];
                 }
             }
         } else {
@@ -304,6 +839,7 @@ impl GameManager {
 
     // TODO: Combine method with play and pass in a `Move` type
     // (need to solve the player lookup first)
+    #[instrument(skip_all, fields(room = %self.game_id, peer = %player))]
     pub fn swap(
         &mut self,
         player: SocketAddr,
@@ -311,21 +847,48 @@ impl GameManager {
         to: Coordinate,
         words: Arc<Mutex<WordDB>>,
     ) -> Vec<(&Player, GameMessage)> {
+        if self.arbiter_frozen {
+            return self.refuse_while_frozen(player);
+        }
         let mut messages = Vec::with_capacity(self.players.len());
+        self.touch();
+        debug!(%from, %to, "Swapping tiles");
 
         if let Some(player_index) = self.get_player_index(player) {
             let words_db = words.lock();
-            match self.core_game.play_turn(
-                Move::Swap {
-                    player: player_index,
-                    positions: [from, to],
-                },
-                Some(&words_db.valid_words),
-                Some(&words_db.valid_words),
-                None,
-            ) {
-                Ok(Some(_)) => {
-                    unreachable!("Cannot win by swapping")
+            let played = Move::Swap {
+                player: player_index,
+                positions: [from, to],
+            };
+            let dictionary = words_db.rules_dictionary(&self.core_game.rules);
+            let result = self
+                .core_game
+                .play_turn(played.clone(), dictionary, dictionary, None)
+                .map(|winner| winner.or_else(|| self.core_game.resolve_endgame([dictionary; 2])));
+            if result.is_ok() {
+                self.move_history.push(played);
+                self.record_event(None);
+            }
+
+            match result {
+                Ok(Some(winner)) => {
+                    info!(winner, "Game won");
+                    // Swapping can still end a stalled game on territory
+                    for (player_index, player) in self.players.iter().enumerate() {
+                        messages.push((
+                            player,
+                            GameMessage::GameEnd(
+                                self.game_msg(player_index, Some(&words_db)),
+                                winner as u64,
+                                self.end_state(),
+                            ),
+                        ));
+                    }
+                    messages
+                }
+                Ok(None) if self.core_game.adjudication.is_some() => {
+                    info!(adjudication = ?self.core_game.adjudication, "Game adjudicated");
+                    self.drawn_messages(Some(&words_db))
                 }
                 Ok(None) => {
                     for (player_index, player) in self.players.iter().enumerate() {
@@ -338,14 +901,15 @@ impl GameManager {
                     messages
                 }
                 Err(msg) => {
+                    debug!(%msg, "Rejected move");
                     return vec![(
                         &self.players[player_index],
                         GameMessage::GameError(
                             self.game_id.clone(),
                             player_index as u64,
-                            msg.into(),
+                            msg.to_string(),
                         ),
-                    )]
+                    )];
                 }
             }
         } else {
@@ -353,7 +917,161 @@ impl GameManager {
         }
     }
 
-    pub fn pause(&mut self, words: Arc<Mutex<WordDB>>) -> Vec<(&Player, GameMessage)> {
+    /// Trades a tile in hand for one from the bag, spending action points
+    #[instrument(skip_all, fields(room = %self.game_id, peer = %player))]
+    pub fn exchange(&mut self, player: SocketAddr, tile: char) -> Vec<(&Player, GameMessage)> {
+        let Some(player_index) = self.get_player_index(player) else {
+            return vec![];
+        };
+        if self.arbiter_frozen {
+            return self.refuse_while_frozen(player);
+        }
+        self.touch();
+        debug!(%tile, "Exchanging a tile");
+
+        let played = Move::Exchange {
+            player: player_index,
+            tile,
+        };
+        let result = self.core_game.play_turn(played.clone(), None, None, None);
+
+        match result {
+            Ok(_) => {
+                self.move_history.push(played);
+                self.record_event(None);
+                self.players
+                    .iter()
+                    .enumerate()
+                    .map(|(player_index, player)| {
+                        (
+                            player,
+                            GameMessage::GameUpdate(self.game_msg(player_index, None)),
+                        )
+                    })
+                    .collect()
+            }
+            Err(msg) => {
+                debug!(%msg, "Rejected exchange");
+                vec![(
+                    &self.players[player_index],
+                    GameMessage::GameError(
+                        self.game_id.clone(),
+                        player_index as u64,
+                        msg.to_string(),
+                    ),
+                )]
+            }
+        }
+    }
+
+    /// Redraws a poor starting hand, which players can do once before their first turn
+    #[instrument(skip_all, fields(room = %self.game_id, peer = %player))]
+    pub fn mulligan(&mut self, player: SocketAddr) -> Vec<(&Player, GameMessage)> {
+        let Some(player_index) = self.get_player_index(player) else {
+            return vec![];
+        };
+        if self.arbiter_frozen {
+            return self.refuse_while_frozen(player);
+        }
+        self.touch();
+        debug!("Redrawing a starting hand");
+
+        let played = Move::Mulligan {
+            player: player_index,
+        };
+        let result = self.core_game.play_turn(played.clone(), None, None, None);
+
+        match result {
+            Ok(_) => {
+                self.move_history.push(played);
+                self.record_event(None);
+                self.players
+                    .iter()
+                    .enumerate()
+                    .map(|(player_index, player)| {
+                        (
+                            player,
+                            GameMessage::GameUpdate(self.game_msg(player_index, None)),
+                        )
+                    })
+                    .collect()
+            }
+            Err(msg) => {
+                debug!(%msg, "Rejected mulligan");
+                vec![(
+                    &self.players[player_index],
+                    GameMessage::GameError(
+                        self.game_id.clone(),
+                        player_index as u64,
+                        msg.to_string(),
+                    ),
+                )]
+            }
+        }
+    }
+
+    /// Re-judges the last battle for a player who thinks their word was wrongly ruled out
+    #[instrument(skip_all, fields(room = %self.game_id, peer = %player))]
+    pub fn appeal(
+        &mut self,
+        player: SocketAddr,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        let Some(player_index) = self.get_player_index(player) else {
+            return vec![];
+        };
+        self.touch();
+
+        let words_db = words.lock();
+        let rules = self.core_game.rules.clone();
+        match self.core_game.appeal(
+            player_index,
+            words_db.rules_dictionary(&rules),
+            words_db
+                .rules_appeal_dictionary(&rules)
+                .unwrap_or(&words_db.appeal_words),
+        ) {
+            Ok(winner) => {
+                info!(?winner, "Battle appealed");
+                self.record_event(Some(&words_db));
+
+                self.players
+                    .iter()
+                    .enumerate()
+                    .map(|(player_index, player)| {
+                        let state = self.game_msg(player_index, Some(&words_db));
+                        let message = match winner {
+                            Some(winner) => {
+                                GameMessage::GameEnd(state, winner as u64, self.end_state())
+                            }
+                            None => GameMessage::GameUpdate(state),
+                        };
+                        (player, message)
+                    })
+                    .collect()
+            }
+            Err(msg) => {
+                debug!(%msg, "Rejected appeal");
+                vec![(
+                    &self.players[player_index],
+                    GameMessage::GameError(
+                        self.game_id.clone(),
+                        player_index as u64,
+                        msg.to_string(),
+                    ),
+                )]
+            }
+        }
+    }
+
+    pub fn pause(
+        &mut self,
+        player: SocketAddr,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        if self.arbiter_frozen {
+            return self.refuse_while_frozen(player);
+        }
         self.core_game.pause();
 
         let words_db = words.lock();
@@ -369,7 +1087,14 @@ impl GameManager {
             .collect()
     }
 
-    pub fn unpause(&mut self, words: Arc<Mutex<WordDB>>) -> Vec<(&Player, GameMessage)> {
+    pub fn unpause(
+        &mut self,
+        player: SocketAddr,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Vec<(&Player, GameMessage)> {
+        if self.arbiter_frozen {
+            return self.refuse_while_frozen(player);
+        }
         self.core_game.unpause();
 
         let words_db = words.lock();
@@ -384,4 +1109,434 @@ impl GameManager {
             })
             .collect()
     }
+
+    /// Carries out a tournament arbiter's ruling on the game in progress, and tells every player
+    /// about it alongside the game as it now stands
+    #[instrument(skip_all, fields(room = %self.game_id, %action))]
+    pub fn arbitrate(
+        &mut self,
+        action: ArbiterAction,
+        reason: String,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Result<Vec<(&Player, GameMessage)>, String> {
+        if self.core_game.started_at.is_none() {
+            return Err("The game hasn't started yet".into());
+        }
+        if self.core_game.is_over() {
+            return Err("The game is already over".into());
+        }
+
+        let words_db = words.lock();
+        match action {
+            ArbiterAction::Freeze if self.arbiter_frozen => {
+                return Err("The game is already frozen".into())
+            }
+            ArbiterAction::Freeze => {
+                self.arbiter_frozen = true;
+                // The players may have already paused the game themselves
+                if !self.core_game.paused {
+                    self.core_game.pause();
+                }
+            }
+            ArbiterAction::Resume if !self.arbiter_frozen => {
+                return Err("The game isn't frozen".into())
+            }
+            ArbiterAction::Resume => {
+                self.arbiter_frozen = false;
+                self.core_game.unpause();
+            }
+            ArbiterAction::AdjustClock { player, secs } => {
+                let Some(player) = self.core_game.players.get_mut(player as usize) else {
+                    return Err("No such player".into());
+                };
+                let Some(time_remaining) = &mut player.time_remaining else {
+                    return Err("The game isn't played on a clock".into());
+                };
+                *time_remaining = time_remaining
+                    .checked_add(time::Duration::seconds(secs))
+                    .ok_or("That would put the clock out of range")?;
+            }
+            ArbiterAction::AnnulLastMove => {
+                let annulled = self.annul_last_move(&words_db)?;
+                info!(?annulled, "Annulled move");
+            }
+        }
+        self.touch();
+        self.queue_spectator_view();
+
+        Ok(self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(player_index, player)| {
+                let mut state = self.game_msg(player_index, Some(&words_db));
+                // The board and hands are sent whole, so there's nothing to animate
+                state.changes = vec![];
+                (
+                    player,
+                    GameMessage::ArbiterNotice {
+                        state,
+                        action,
+                        reason: reason.clone(),
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Takes back the latest move by playing the game back from the start without it,
+    /// leaving everyone's clocks as they are now
+    fn annul_last_move(&mut self, words: &WordDB) -> Result<Move, String> {
+        let Some(starting_board) = self.starting_board.clone() else {
+            return Err("The game hasn't started yet".into());
+        };
+        let Some((annulled, kept)) = self.move_history.split_last() else {
+            return Err("No moves have been played".into());
+        };
+
+        let mut replayed = Game::new(
+            9,
+            9,
+            Some(self.core_game.seed()),
+            self.core_game.rules.clone(),
+        );
+        for player in &self.core_game.players {
+            replayed.add_player(player.name.clone());
+        }
+        replayed.board = starting_board;
+
+        let dictionary = words.rules_dictionary(&replayed.rules);
+        let mut replayed = Replay::from_game(replayed, kept.to_vec(), dictionary)
+            .finish()
+            .map_err(|e| e.to_string())?;
+
+        replayed.started_at = self.core_game.started_at;
+        replayed.game_ends_at = self.core_game.game_ends_at;
+        for (player, current) in replayed.players.iter_mut().zip(&self.core_game.players) {
+            player.time_remaining = current.time_remaining;
+        }
+        if self.core_game.paused {
+            replayed.pause();
+        }
+
+        let annulled = annulled.clone();
+        self.core_game = replayed;
+        self.move_history.pop();
+        Ok(annulled)
+    }
+
+    /// Tells a player their move or pause can't go ahead while an arbiter has the game frozen
+    fn refuse_while_frozen(&self, player: SocketAddr) -> Vec<(&Player, GameMessage)> {
+        let Some(player_index) = self.get_player_index(player) else {
+            return vec![];
+        };
+        vec![(
+            &self.players[player_index],
+            GameMessage::GameError(
+                self.game_id.clone(),
+                player_index as u64,
+                "The game has been frozen by an arbiter".into(),
+            ),
+        )]
+    }
+
+    /// Tells everyone else in the room whether this player appears to be away
+    pub fn presence(&self, player: SocketAddr, away: bool) -> Vec<(&Player, GameMessage)> {
+        let Some(player_index) = self.get_player_index(player) else {
+            return vec![];
+        };
+        debug!(room = %self.game_id, player_index, away, "Relaying player presence");
+
+        self.players
+            .iter()
+            .filter(|p| p.socket != Some(player))
+            .map(|p| {
+                (
+                    p,
+                    GameMessage::PlayerPresence {
+                        player: player_index as u64,
+                        away,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Flashes a marker on the square a player is pointing at, for everyone else who can see it
+    pub fn point(&self, player: SocketAddr, coordinate: Coordinate) -> Vec<(&Player, GameMessage)> {
+        let Some(player_index) = self.get_player_index(player) else {
+            return vec![];
+        };
+        let game = &self.core_game;
+        if game.players.len() <= player_index {
+            return vec![];
+        }
+
+        let game_coordinate = game.board.map_player_coord_to_game(
+            player_index,
+            coordinate,
+            &game.rules.visibility,
+            &game.rules.board_orientation,
+            &game.players[player_index].seen_tiles,
+        );
+        if game.board.get(game_coordinate).is_err() {
+            return vec![];
+        }
+        debug!(room = %self.game_id, player_index, %game_coordinate, "Relaying player pointer");
+
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != player_index)
+            .filter_map(|(index, p)| {
+                // Don't reveal squares that are hidden from the recipient
+                let coordinate = game.board.map_game_coord_to_player(
+                    index,
+                    game_coordinate,
+                    &game.rules.visibility,
+                    &game.rules.board_orientation,
+                    &game.players.get(index)?.seen_tiles,
+                )?;
+                Some((
+                    p,
+                    GameMessage::Pointed {
+                        player: player_index as u64,
+                        coordinate,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Observer chat starts muted for players, so that nobody is coached without asking for it
+    pub fn chat_muted(&self, player_index: usize, channel: ChatChannel) -> bool {
+        self.chat_mutes
+            .get(&(player_index, channel))
+            .copied()
+            .unwrap_or(channel == ChatChannel::Observers)
+    }
+
+    pub fn mute_chat(&mut self, player: SocketAddr, channel: ChatChannel, muted: bool) {
+        let Some(player_index) = self.get_player_index(player) else {
+            return;
+        };
+        self.chat_mutes.insert((player_index, channel), muted);
+    }
+
+    /// Relays a chat message to everyone in the room listening to its channel.
+    /// Players talk on the players' channel, and spectators on the observers' channel,
+    /// which players only hear if they've unmuted it.
+    pub fn chat(
+        &self,
+        sender: SocketAddr,
+        channel: ChatChannel,
+        text: &str,
+    ) -> Vec<(SocketAddr, GameMessage)> {
+        let sender_name = if let Some(player_index) = self.get_player_index(sender) {
+            if channel != ChatChannel::Players {
+                warn!(room = %self.game_id, player_index, %channel, "Player tried to chat outside their channel");
+                return vec![];
+            }
+            self.core_game.players[player_index].name.clone()
+        } else if let Some(spectator_index) = self.spectators.iter().position(|s| *s == sender) {
+            if channel != ChatChannel::Observers {
+                warn!(room = %self.game_id, spectator_index, %channel, "Spectator tried to chat outside their channel");
+                return vec![];
+            }
+            format!("Spectator {}", spectator_index + 1)
+        } else {
+            return vec![];
+        };
+
+        let text: String = text.trim().chars().take(MAX_CHAT_LENGTH).collect();
+        if text.is_empty() {
+            return vec![];
+        }
+
+        let listening_players = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.chat_muted(*index, channel))
+            .filter_map(|(_, p)| p.socket);
+        let listening_spectators = self
+            .spectators
+            .iter()
+            .copied()
+            .filter(|_| channel == ChatChannel::Observers);
+
+        listening_players
+            .chain(listening_spectators)
+            .map(|socket| {
+                (
+                    socket,
+                    GameMessage::Chat {
+                        channel,
+                        sender: sender_name.clone(),
+                        text: text.clone(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Numbers the turn that was just played and holds onto its changes until everyone has them
+    fn record_event(&mut self, word_map: Option<&MutexGuard<'_, WordDB>>) {
+        self.event_seq += 1;
+        let changes = (0..self.players.len())
+            .map(|player_index| self.player_changes(player_index, word_map).1)
+            .collect();
+        self.event_buffer.push_back(BufferedEvent {
+            seq: self.event_seq,
+            changes,
+        });
+        self.trim_events();
+        self.report_to_webhook();
+        self.queue_spectator_view();
+    }
+
+    fn trim_events(&mut self) {
+        let everyone_has = self.acked_events.iter().copied().min().unwrap_or_default();
+        while let Some(oldest) = self.event_buffer.front() {
+            if oldest.seq > everyone_has && self.event_buffer.len() <= EVENT_BUFFER_LIMIT {
+                break;
+            }
+            self.event_buffer.pop_front();
+        }
+    }
+
+    pub fn ack_events(&mut self, player: SocketAddr, seq: u64) {
+        let Some(player_index) = self.get_player_index(player) else {
+            return;
+        };
+        let acked = &mut self.acked_events[player_index];
+        *acked = (*acked).max(seq.min(self.event_seq));
+        self.trim_events();
+    }
+
+    /// Every buffered turn after `since`, for a player catching up after a dropped connection
+    pub fn events_since(&self, player: SocketAddr, since: u64) -> Option<GameMessage> {
+        let player_index = self.get_player_index(player)?;
+
+        let events: Vec<_> = self
+            .event_buffer
+            .iter()
+            .filter(|event| event.seq > since)
+            .map(|event| SequencedChanges {
+                seq: event.seq,
+                changes: event.changes[player_index].clone(),
+            })
+            .collect();
+
+        let oldest = events.first().map_or(self.event_seq + 1, |event| event.seq);
+        if oldest > since + 1 {
+            warn!(room = %self.game_id, player_index, since, oldest, "Missed events are no longer buffered");
+        }
+
+        Some(GameMessage::MissedEvents(events))
+    }
+
+    /// Remembers which account a player is logged in to, so their letter mastery can be saved
+    pub fn link_account(&mut self, player: SocketAddr, account: Uuid) {
+        if let Some(player_index) = self.get_player_index(player) {
+            self.accounts.insert(player_index, account);
+        }
+    }
+
+    /// Counts the battles from the latest move towards each side's letter mastery.
+    /// Defenders can only be told apart in two player games.
+    fn tally_letters(&mut self, attacker: usize) {
+        let player_count = self.players.len();
+        if self.letter_mastery.len() < player_count {
+            self.letter_mastery
+                .resize_with(player_count, LetterMastery::default);
+        }
+
+        for battle in self
+            .core_game
+            .recent_changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Battle(battle) => Some(battle),
+                _ => None,
+            })
+        {
+            self.letter_mastery[attacker].record_battle(battle, BattleSide::Attacker);
+            if player_count == 2 {
+                self.letter_mastery[(attacker + 1) % 2].record_battle(battle, BattleSide::Defender);
+            }
+        }
+    }
+
+    /// Letter mastery for each logged in player, the first time this is called after the game has finished
+    pub fn take_letter_mastery(&mut self) -> Vec<(Uuid, LetterMastery)> {
+        if self.mastery_recorded || !self.core_game.is_over() {
+            return vec![];
+        }
+        self.mastery_recorded = true;
+
+        self.accounts
+            .iter()
+            .filter_map(|(player_index, account)| {
+                let mastery = self.letter_mastery.get(*player_index)?;
+                (!mastery.is_empty()).then(|| (*account, mastery.clone()))
+            })
+            .collect()
+    }
+
+    /// Holds the game as it stands for spectators, to be released once the room's delay has passed
+    /// Whether spectators can watch the game as it stands. Fog hides something different from each player,
+    /// so games played in fog are only shown once they're over and the whole board is revealed.
+    pub fn can_be_spectated(&self) -> bool {
+        matches!(self.core_game.rules.visibility, Visibility::Standard) || self.core_game.is_over()
+    }
+
+    pub fn queue_spectator_view(&mut self) {
+        if self.core_game.started_at.is_none()
+            || self.players.is_empty()
+            || !self.can_be_spectated()
+        {
+            return;
+        }
+
+        // Spectators see the board as the first player does, but nobody's hand
+        let view = self.game_msg(0, None).for_spectators();
+        self.spectator_queue
+            .push_back((game::now() + self.spectator_delay_secs, view));
+    }
+
+    /// Releases every spectator view that is due, returning the latest for each spectator
+    pub fn release_spectator_views(&mut self, now: u64) -> Vec<(SocketAddr, GameMessage)> {
+        let mut released = false;
+        while let Some((due, _)) = self.spectator_queue.front() {
+            if *due > now {
+                break;
+            }
+            self.spectator_view = self.spectator_queue.pop_front().map(|(_, view)| view);
+            released = true;
+        }
+
+        if !released {
+            return vec![];
+        }
+        self.spectators
+            .iter()
+            .filter_map(|spectator| Some((*spectator, self.spectator_msg()?)))
+            .collect()
+    }
+
+    /// Starts sending spectator views to a connection, returning the latest one if there is any
+    pub fn add_spectator(&mut self, spectator: SocketAddr) -> Option<GameMessage> {
+        if !self.spectators.contains(&spectator) {
+            self.spectators.push(spectator);
+        }
+        self.spectator_msg()
+    }
+
+    fn spectator_msg(&self) -> Option<GameMessage> {
+        Some(GameMessage::SpectatorUpdate {
+            state: self.spectator_view.clone()?,
+            delay_secs: self.spectator_delay_secs,
+        })
+    }
 }
@@ -1,7 +1,13 @@
+mod admin;
+mod capacity;
 mod definitions;
 mod errors;
+mod experiments;
 mod game_state;
+mod matchmaking;
+mod oauth;
 mod storage;
+mod webhooks;
 
 use parking_lot::Mutex;
 use sqlx::postgres::PgPoolOptions;
@@ -17,19 +23,50 @@ use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{debug, error, info, instrument, warn};
+use tracing_subscriber::EnvFilter;
 use tungstenite::protocol::Message;
 
+use crate::capacity::{Capacity, CapacityLimits, QueuedPlayer, Usage};
 use crate::definitions::read_defs;
-use crate::game_state::{Player, PlayerClaims};
+use crate::errors::TruncateServerError;
+use crate::game_state::{
+    Player, PlayerClaims, ABANDON_GRACE_SECS, MAX_BLANK_TILES, MAX_SPECTATOR_DELAY_SECS,
+};
+use crate::matchmaking::{MatchSeeker, Matchmaker};
+use crate::oauth::{OAuthApps, PendingSignIns};
 use crate::storage::accounts::{mark_changelog_read, LoginResponse};
-use crate::storage::daily;
 use crate::storage::events::create_event;
+use crate::storage::games;
+use crate::storage::replays::{self, RetentionPolicy};
+use crate::storage::{
+    arbiter, boards, crashes, daily, fair_play, ladder, mastery, npc_ladder, predictions, rooms,
+    rotations, rush, schedule, snapshot,
+};
 use game_state::GameManager;
 use storage::accounts::{self, mark_most_changelogs_read, AuthedTruncateToken};
+use truncate_core::flags::{Flags, Rollout};
+use truncate_core::game::Adjudication;
+use truncate_core::generation::BoardSeed;
+use truncate_core::ladder::WordLadder;
 use truncate_core::messages::{
     DailyStateMessage, GameMessage, GameStateMessage, LobbyPlayerMessage, Nonce,
-    NoncedPlayerMessage, PlayerMessage,
+    NoncedPlayerMessage, PlayerMessage, RoomRole,
 };
+use truncate_core::npc::progression;
+use truncate_core::rotation::{live_puzzle_days, PuzzleCategory, DAILY_PUZZLE_DAY_ZERO};
+use truncate_core::rules::{
+    GameRules, GameRulesBuilder, HandSwaps, Language, RulesValidator, RulesWarning, Spelling,
+    Timing,
+};
+use truncate_core::schedule::ScheduledGame;
+use truncate_core::season::Season;
+use truncate_core::word_of_day::WordOfTheDay;
+
+/// How long a player can rejoin their room for, which covers the longest correspondence games
+const ROOM_TOKEN_DAYS: u64 = 30;
+/// How long before a scheduled game starts that its players are reminded
+const SCHEDULE_REMINDER_SECS: u64 = 15 * 60;
 
 // TODO: Also find a way to include this in the database to prevent replay if reconnecting to a different backend
 #[derive(Default)]
@@ -64,6 +101,38 @@ impl NonceTracker {
     }
 }
 
+/// Puzzle rushes handed out to players, so that the seed and clock of a ranked run are the server's
+#[derive(Default)]
+pub struct RushTracker {
+    /// The seed of each player's latest rush, and when it started
+    started: HashMap<Uuid, (u32, u64)>,
+}
+
+impl RushTracker {
+    fn start(&mut self, player: Uuid) -> u32 {
+        let current_time = truncate_core::game::now();
+        // Nobody is still playing a rush started a day ago
+        self.started
+            .retain(|_, (_, started_at)| *started_at > current_time.saturating_sub(24 * 60 * 60));
+
+        let seed = rand::random();
+        self.started.insert(player, (seed, current_time));
+        seed
+    }
+
+    /// How long ago the player started the rush with this seed, which can only be finished once
+    fn finish(&mut self, player: Uuid, seed: u32) -> Option<std::time::Duration> {
+        let (started_seed, started_at) = self.started.get(&player).copied()?;
+        if started_seed != seed {
+            return None;
+        }
+        self.started.remove(&player);
+
+        let elapsed = truncate_core::game::now().saturating_sub(started_at);
+        Some(std::time::Duration::from_secs(elapsed))
+    }
+}
+
 #[derive(Clone)]
 pub struct ServerState {
     games: Arc<Mutex<HashMap<String, Arc<Mutex<GameManager>>>>>,
@@ -71,8 +140,23 @@ pub struct ServerState {
     peers: Arc<Mutex<HashMap<SocketAddr, UnboundedSender<GameMessage>>>>,
     word_db: Arc<Mutex<WordDB>>,
     nonces: Arc<Mutex<NonceTracker>>,
+    rushes: Arc<Mutex<RushTracker>>,
+    /// Each day's word of the day once it has been picked, since picking scans the whole dictionary
+    words_of_the_day: Arc<Mutex<HashMap<u32, Option<WordOfTheDay>>>>,
+    capacity: Arc<Mutex<Capacity>>,
+    /// Players waiting to be paired with a random opponent
+    matchmaker: Arc<Mutex<Matchmaker>>,
     truncate_db: Option<PgPool>,
     jwt_key: HS256Key,
+    /// Unlocks operator-only reports, which are disabled when unset
+    admin_key: Option<String>,
+    /// How many sessions get each experiment
+    rollout: Rollout,
+    /// How long finished games are kept in the replay archive
+    replay_retention: RetentionPolicy,
+    /// Providers that players can sign in with
+    oauth_apps: OAuthApps,
+    sign_ins: Arc<Mutex<PendingSignIns>>,
 }
 
 impl ServerState {
@@ -93,13 +177,62 @@ impl ServerState {
         game
     }
 
+    /// The word of the day for a given day, picked the first time it is asked for
+    fn word_of_the_day(&self, day: u32) -> Option<WordOfTheDay> {
+        if let Some(daily) = self.words_of_the_day.lock().get(&day) {
+            return daily.clone();
+        }
+
+        let daily = {
+            let word_db = self.word_db.lock();
+            WordOfTheDay::pick(day, &word_db.valid_words).map(|word| WordOfTheDay {
+                day,
+                definitions: word_db.get_word(&word).unwrap_or_default(),
+                word,
+            })
+        };
+
+        // Players ask for their local day, so only the days live somewhere in the world are kept
+        let live_days = live_puzzle_days(truncate_core::game::now());
+        let is_live = |day: u32| {
+            day.checked_sub(DAILY_PUZZLE_DAY_ZERO)
+                .is_some_and(|puzzle_day| live_days.contains(&puzzle_day))
+        };
+        let mut cached = self.words_of_the_day.lock();
+        cached.retain(|day, _| is_live(*day));
+        if is_live(day) {
+            cached.insert(day, daily.clone());
+        }
+
+        daily
+    }
+
+    /// Counts the rooms that are still being played, and the players in them
+    fn usage(&self) -> Usage {
+        let game_map = self.games.lock();
+        game_map
+            .values()
+            .fold(Usage::default(), |mut usage, existing_game| {
+                let game_manager = existing_game.lock();
+                if game_manager.is_live() {
+                    usage.rooms += 1;
+                    usage.players += game_manager.players.len();
+                }
+                usage
+            })
+    }
+
     fn attach_player_to_game(&self, addr: &SocketAddr, game_id: &String) {
         let mut assignments = self.assignments.lock();
         let game_id = game_id.to_lowercase();
-        println!("Assigning {addr} to {game_id}");
+        info!(peer = %addr, room = %game_id, "Assigning player to room");
         assignments.insert(*addr, game_id);
     }
 
+    fn detach_player(&self, addr: &SocketAddr) {
+        self.assignments.lock().remove(addr);
+    }
+
     fn get_game_by_code(&self, game_id: &String) -> Option<Arc<Mutex<GameManager>>> {
         let game_id = game_id.to_lowercase();
         self.games.lock().get(&game_id).map(Arc::clone)
@@ -111,6 +244,14 @@ impl ServerState {
         self.games.lock().get(game_id).map(Arc::clone)
     }
 
+    /// The room a connection is watching, as spectators aren't assigned to rooms like players are
+    fn get_game_by_spectator(&self, addr: &SocketAddr) -> Option<Arc<Mutex<GameManager>>> {
+        let games: Vec<_> = self.games.lock().values().map(Arc::clone).collect();
+        games
+            .into_iter()
+            .find(|game| game.lock().spectators.contains(addr))
+    }
+
     fn track_peer(&self, addr: &SocketAddr, tx: UnboundedSender<GameMessage>) {
         let mut peers = self.peers.lock();
         peers.insert(*addr, tx);
@@ -135,6 +276,12 @@ impl ServerState {
     }
 }
 
+/// Seasons follow the server's calendar, so everyone sees the same theme change over
+fn active_season() -> Option<Season> {
+    Season::scheduled_on(time::OffsetDateTime::now_utc().date())
+}
+
+#[instrument(skip_all, fields(peer = %player_addr, room = tracing::field::Empty))]
 async fn handle_player_msg(
     msg: Message,
     player_addr: SocketAddr,
@@ -148,10 +295,16 @@ async fn handle_player_msg(
         } else if let Ok(bare_msg) = serde_json::from_str::<PlayerMessage>(msg.to_text().unwrap()) {
             (None, bare_msg)
         } else {
+            warn!("Ignoring a message that could not be parsed");
             return Ok(());
         }
     };
 
+    if let Some(room) = server_state.assignments.lock().get(&player_addr) {
+        tracing::Span::current().record("room", room.as_str());
+    }
+    debug!(message = %parsed_msg, "Handling player message");
+
     if let Some(nonce) = nonce {
         let Some(connection_player) = connection_info_mutex.lock().player.clone() else {
             // Prevent processing any nonces unless the player is logged in.
@@ -180,7 +333,22 @@ async fn handle_player_msg(
             // they may be stuck waiting for the info (e.g. waiting for DailyStats to show splash screen)
             let replayable = matches!(
                 parsed_msg,
-                RequestDefinitions(_) | RequestStats(_) | LoadReplay(_)
+                RequestDefinitions(_)
+                    | RequestStats(..)
+                    | LoadReplay(_)
+                    | LoadWordLadder(_)
+                    | LoadNpcProgress(_)
+                    | LoadPuzzleRush { .. }
+                    | LoadWordOfTheDay(_)
+                    | ExportGames(_)
+                    | LoadSavedBoards(_)
+                    | LoadGallery
+                    | RequestFairPlay(_)
+                    | LoadScheduledGames(_)
+                    | RequestPacks
+                    | DownloadPack(_)
+                    | DownloadLanguage(_)
+                    | RequestPuzzleDifficulty { .. }
             );
 
             if !replayable {
@@ -217,71 +385,136 @@ async fn handle_player_msg(
 
     match parsed_msg {
         Ping => { /* TODO: Track pings and notify the game when players disconnect */ }
+        LatencyPing(sent_at) => {
+            server_state
+                .send_to_player(&player_addr, GameMessage::LatencyPong(sent_at))
+                .unwrap();
+        }
         NewGame {
-            mut player_name,
+            player_name,
             effective_day,
+            rules,
         } => {
-            let new_game_id = server_state.game_code();
-            let mut game = GameManager::new(new_game_id.clone(), effective_day);
-
-            let connection_player = connection_info_mutex.lock().player.clone();
-            _ = create_event(&server_state, &"new_game".into(), connection_player).await;
-
-            if &player_name == "___AUTO___" {
-                player_name = "Player 1".into();
+            let rules = match rules.map(playable_rules).transpose() {
+                Ok(rules) => rules,
+                Err(e) => return player_err(e),
+            };
+            let (connection_player, flags) = connection_info_mutex.lock().analytics();
+            if let Some(wait) = abandonment_penalty(&server_state, connection_player.clone()).await
+            {
+                return player_err(wait);
+            }
+            _ = create_event(&server_state, &"new_game".into(), connection_player, flags).await;
+
+            let usage = server_state.usage();
+            let mut capacity = server_state.capacity.lock();
+            if capacity.must_wait(&usage) {
+                let position = capacity.enqueue(QueuedPlayer {
+                    addr: player_addr,
+                    player_name,
+                    effective_day,
+                    rules,
+                });
+                let estimated_wait_secs = capacity.estimated_wait_secs(position);
+                drop(capacity);
+
+                info!(position, ?usage, "Server is full, queueing new room");
+                server_state
+                    .send_to_player(
+                        &player_addr,
+                        GameMessage::Queued {
+                            position,
+                            estimated_wait_secs,
+                        },
+                    )
+                    .unwrap();
+                return Ok(());
             }
+            drop(capacity);
 
-            game.add_player(
-                Player {
-                    socket: Some(player_addr.clone()),
-                },
-                player_name.clone(),
+            open_room(
+                &server_state,
+                player_addr,
+                player_name,
+                effective_day,
+                rules,
             )
-            .expect("Failed to add first player to game");
-
-            let color = game.core_game.players[0].color;
-            let board = game.core_game.board.clone();
-
-            server_state.add_new_game(&new_game_id, game);
-            server_state.attach_player_to_game(&player_addr, &new_game_id);
-
-            let claims = Claims::with_custom_claims(
-                PlayerClaims {
-                    player_index: 0,
-                    room_code: new_game_id.clone(),
-                },
-                Duration::from_days(7), // TODO: Determine game expiration time
-            );
-            let token = server_state
-                .jwt_key
-                .authenticate(claims)
-                .expect("Claims should be serializable");
+            .unwrap();
+        }
+        FindMatch {
+            player_name,
+            effective_day,
+            ruleset,
+            latency_ms,
+        } => {
+            let (connection_player, flags) = connection_info_mutex.lock().analytics();
+            if let Some(wait) = abandonment_penalty(&server_state, connection_player.clone()).await
+            {
+                return player_err(wait);
+            }
+            _ = create_event(
+                &server_state,
+                &"find_match".into(),
+                connection_player.clone(),
+                flags,
+            )
+            .await;
+
+            let skill = match connection_player {
+                Some(player) => npc_ladder::load_progress(&server_state, player)
+                    .await
+                    .ok()
+                    .map(|progress| progress.beaten.len() as u32),
+                None => None,
+            };
 
+            let waiting = server_state.matchmaker.lock().enqueue(MatchSeeker {
+                addr: player_addr,
+                player_name,
+                effective_day,
+                ruleset,
+                skill,
+                latency_ms,
+                queued_at: truncate_core::game::now(),
+            });
+            info!(%ruleset, ?skill, ?latency_ms, waiting, "Looking for a match");
             server_state
-                .send_to_player(
-                    &player_addr,
-                    GameMessage::JoinedLobby(
-                        0,
-                        new_game_id,
-                        vec![LobbyPlayerMessage {
-                            name: player_name,
-                            color,
-                            index: 0,
-                        }],
-                        board,
-                        token,
-                    ),
-                )
+                .send_to_player(&player_addr, GameMessage::FindingMatch { ruleset, waiting })
                 .unwrap();
         }
+        CancelMatch => {
+            if server_state.matchmaker.lock().cancel(player_addr) {
+                info!("Stopped looking for a match");
+            }
+        }
         JoinGame(room_code, mut player_name, _) => {
             let code = room_code.to_ascii_lowercase();
             if let Some(existing_game) = server_state.get_game_by_code(&code) {
-                let connection_player = connection_info_mutex.lock().player.clone();
-                _ = create_event(&server_state, &"join_game".into(), connection_player).await;
+                let (connection_player, flags) = connection_info_mutex.lock().analytics();
+                let account = connection_player.as_ref().map(|token| token.player());
+                if let Some(wait) =
+                    abandonment_penalty(&server_state, connection_player.clone()).await
+                {
+                    return player_err(wait);
+                }
+                _ = create_event(&server_state, &"join_game".into(), connection_player, flags)
+                    .await;
+
+                // Joining fills a seat, so it waits behind the queue like a new room would
+                let usage = server_state.usage();
+                if server_state.capacity.lock().must_wait(&usage) {
+                    return player_err("The server is full right now, try again shortly".into());
+                }
 
                 let mut game_manager = existing_game.lock();
 
+                if game_manager.was_kicked(account, &player_name) {
+                    return player_err(format!(
+                        "You were removed from room {}",
+                        code.to_ascii_uppercase()
+                    ));
+                }
+
                 // TODO: This is the easiest place to check for lobby capacity right now,
                 // but we'll need to reevaluate if we ever support >2 players, or spectators.
                 if game_manager.players.len() >= 2 {
@@ -303,12 +536,15 @@ async fn handle_player_msg(
                     },
                     player_name.clone(),
                 ) {
+                    if let Some(account) = account {
+                        game_manager.link_account(player_addr, account);
+                    }
                     let claims = Claims::with_custom_claims(
                         PlayerClaims {
                             player_index,
                             room_code: room_code.clone(),
                         },
-                        Duration::from_days(7), // TODO: Determine game expiration time
+                        Duration::from_days(ROOM_TOKEN_DAYS),
                     );
                     let token = server_state
                         .jwt_key
@@ -327,6 +563,12 @@ async fn handle_player_msg(
                             ),
                         )
                         .unwrap();
+                    server_state
+                        .send_to_player(
+                            &player_addr,
+                            GameMessage::RoomRules(game_manager.core_game.rules.clone()),
+                        )
+                        .unwrap();
 
                     for player in &game_manager.players {
                         let Some(socket) = player.socket else {
@@ -345,6 +587,7 @@ async fn handle_player_msg(
                             )
                             .unwrap();
                     }
+                    send_rules_warnings(&server_state, &game_manager);
                 } else {
                     // TODO: Render a better error here
                     return player_err(format!(
@@ -373,7 +616,7 @@ async fn handle_player_msg(
             let code = room_code.to_ascii_lowercase();
             if let Some(existing_game) = server_state.get_game_by_code(&code) {
                 let mut game_manager = existing_game.lock();
-                println!("Trying to reconnect player {player_index} to room {code}");
+                info!(player_index, room = %code, "Trying to reconnect player");
                 match game_manager.reconnect_player(player_addr.clone(), player_index) {
                     Ok(_) => {
                         server_state.attach_player_to_game(&player_addr, &code);
@@ -416,17 +659,12 @@ async fn handle_player_msg(
         EditBoard(board) => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let mut game_manager = existing_game.lock();
+                if !game_manager.can_manage(player_addr) {
+                    return player_err("Only the host or a co-host can edit the board".into());
+                }
                 game_manager.edit_board(board.clone());
-                let player_list: Vec<_> = game_manager
-                    .core_game
-                    .players
-                    .iter()
-                    .map(|p| LobbyPlayerMessage {
-                        name: p.name.clone(),
-                        index: p.index,
-                        color: p.color,
-                    })
-                    .collect();
+                game_manager.gallery_board = None;
+                let player_list = game_manager.player_list();
 
                 let Some(player_index) = game_manager.get_player_index(player_addr) else {
                     todo!("Handle player editing the board without having a turn index");
@@ -456,16 +694,7 @@ async fn handle_player_msg(
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let mut game_manager = existing_game.lock();
                 if game_manager.rename_player(player_addr, name).is_ok() {
-                    let player_list: Vec<_> = game_manager
-                        .core_game
-                        .players
-                        .iter()
-                        .map(|p| LobbyPlayerMessage {
-                            name: p.name.clone(),
-                            index: p.index,
-                            color: p.color,
-                        })
-                        .collect();
+                    let player_list = game_manager.player_list();
 
                     let Some(player_index) = game_manager.get_player_index(player_addr) else {
                         unreachable!("Player just renamed themselves");
@@ -494,32 +723,46 @@ async fn handle_player_msg(
         }
         StartGame => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let connection_player = connection_info_mutex.lock().player.clone();
-                _ = create_event(&server_state, &"start_game".into(), connection_player).await;
+                let (connection_player, flags) = connection_info_mutex.lock().analytics();
+                _ = create_event(
+                    &server_state,
+                    &"start_game".into(),
+                    connection_player,
+                    flags,
+                )
+                .await;
 
-                let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.start() {
-                    let Some(socket) = player.socket else {
-                        continue;
-                    };
+                let gallery_board = {
+                    let mut game_manager = existing_game.lock();
+                    if !game_manager.can_manage(player_addr) {
+                        return player_err("Only the host or a co-host can start the game".into());
+                    }
 
-                    let room_code = game_manager.game_id.clone();
-
-                    match &game_manager.core_game.rules.timing {
-                        truncate_core::rules::Timing::Periodic {
-                            total_time_allowance,
-                            ..
-                        } => {
-                            tokio::spawn(check_game_over(
-                                room_code,
-                                (*total_time_allowance + 1) as i128 * 1000,
-                                server_state.clone(),
-                            ));
-                        }
-                        _ => {}
-                    };
+                    let problems: Vec<_> = RulesValidator::check(&game_manager.core_game.rules)
+                        .into_iter()
+                        .filter(RulesWarning::is_fatal)
+                        .collect();
+                    if !problems.is_empty() {
+                        warn!(?problems, "Refusing to start a game with unplayable rules");
+                        let reasons: Vec<_> = problems.iter().map(|p| p.to_string()).collect();
+                        return player_err(format!(
+                            "These rules can't be played: {}",
+                            reasons.join(", ")
+                        ));
+                    }
 
-                    server_state.send_to_player(&socket, message).unwrap();
+                    start_room(&server_state, &mut game_manager);
+
+                    game_manager
+                        .gallery_board
+                        .as_ref()
+                        .map(|(board_id, _)| *board_id)
+                };
+
+                if let Some(board_id) = gallery_board {
+                    if let Err(e) = boards::record_play(&server_state, board_id).await {
+                        error!(error = ?e, "Errored recording a gallery board play: {e}");
+                    }
                 }
             } else {
                 todo!("Handle player not being enrolled in a game");
@@ -541,6 +784,9 @@ async fn handle_player_msg(
         Place(position, tile) => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let mut game_manager = existing_game.lock();
+                if let Some(account) = &connection_info_mutex.lock().player {
+                    game_manager.link_account(player_addr, account.player());
+                }
                 for (player, message) in
                     game_manager.play(player_addr, position, tile, server_state.words())
                 {
@@ -570,77 +816,140 @@ async fn handle_player_msg(
                 todo!("Handle player not being enrolled in a game");
             }
         }
+        Exchange(tile) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            for (player, message) in game_manager.exchange(player_addr, tile) {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                server_state.send_to_player(&socket, message).unwrap();
+            }
+        }
+        Mulligan => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            for (player, message) in game_manager.mulligan(player_addr) {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                server_state.send_to_player(&socket, message).unwrap();
+            }
+        }
+        Appeal => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let mut game_manager = existing_game.lock();
+                for (player, message) in game_manager.appeal(player_addr, server_state.words()) {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    server_state.send_to_player(&socket, message).unwrap();
+                }
+            }
+        }
         Rematch => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let connection_player = connection_info_mutex.lock().player.clone();
-                _ = create_event(&server_state, &"rematch".into(), connection_player).await;
+                let (connection_player, flags) = connection_info_mutex.lock().analytics();
+                _ = create_event(&server_state, &"rematch".into(), connection_player, flags).await;
 
-                let mut existing_game_manager = existing_game.lock();
-                if existing_game_manager.core_game.winner.is_none() {
+                if !existing_game.lock().core_game.is_over() {
                     return player_err("Cannot rematch unfinished game".into());
-                } else {
-                    let new_game_id = server_state.game_code();
-                    let mut new_game =
-                        GameManager::new(new_game_id.clone(), existing_game_manager.effective_day);
-
-                    let mut next_board = existing_game_manager.core_game.board.clone();
-                    next_board.reset();
-                    new_game.core_game.board = next_board;
-
-                    let mut next_sockets = existing_game_manager.players.clone();
-                    next_sockets.rotate_left(1);
-                    existing_game_manager.players = vec![];
-
-                    let mut next_players = existing_game_manager.core_game.players.clone();
-                    next_players.rotate_left(1);
-                    for (i, player) in next_players.into_iter().enumerate() {
-                        new_game
-                            .add_player(
-                                next_sockets
-                                    .get(i)
-                                    .expect("All players rejoining have a socket")
-                                    .clone(),
-                                player.name,
-                            )
-                            .expect("Failed to add player to game");
-                    }
+                }
+
+                // A rematch opens a new room, so it waits behind the queue like any other
+                let usage = server_state.usage();
+                if server_state.capacity.lock().must_wait(&usage) {
+                    return player_err("The server is full right now, try again shortly".into());
+                }
+
+                let mut existing_game_manager = existing_game.lock();
+                let new_game_id = server_state.game_code();
+                let mut new_game =
+                    GameManager::new(new_game_id.clone(), existing_game_manager.effective_day);
+
+                // Rematches are played the way the room was set up, rather than on the defaults.
+                // The rules keep the new game's language and pack, so those are carried over first.
+                let rules = existing_game_manager.core_game.rules.clone();
+                new_game.core_game.set_language(rules.language);
+                new_game.core_game.rules.dictionary_pack = rules.dictionary_pack.clone();
+                new_game.core_game.set_rules(rules);
+                new_game.spectator_delay_secs = existing_game_manager.spectator_delay_secs;
+
+                let mut next_board = existing_game_manager.core_game.board.clone();
+                next_board.reset();
+                new_game.core_game.board = next_board;
+
+                let mut next_sockets = existing_game_manager.players.clone();
+                next_sockets.rotate_left(1);
+                existing_game_manager.players = vec![];
+
+                let mut next_players = existing_game_manager.core_game.players.clone();
+                next_players.rotate_left(1);
+                for (i, player) in next_players.into_iter().enumerate() {
+                    new_game
+                        .add_player(
+                            next_sockets
+                                .get(i)
+                                .expect("All players rejoining have a socket")
+                                .clone(),
+                            player.name,
+                        )
+                        .expect("Failed to add player to game");
+                }
 
-                    drop(existing_game_manager); // Done with the old game, don't accidentally use it.
+                drop(existing_game_manager); // Done with the old game, don't accidentally use it.
 
-                    let new_game = server_state.add_new_game(&new_game_id, new_game);
-                    let new_game_manager = new_game.lock();
+                let available = server_state.word_db.lock().languages();
+                let new_game = server_state.add_new_game(&new_game_id, new_game);
+                let new_game_manager = new_game.lock();
+                let rules = new_game_manager.core_game.rules.clone();
 
-                    for (i, player) in new_game_manager.players.iter().enumerate() {
-                        let Some(socket) = player.socket else {
-                            continue;
-                        };
+                for (i, player) in new_game_manager.players.iter().enumerate() {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
 
-                        server_state.attach_player_to_game(&socket, &new_game_id);
+                    server_state.attach_player_to_game(&socket, &new_game_id);
+
+                    let claims = Claims::with_custom_claims(
+                        PlayerClaims {
+                            player_index: i,
+                            room_code: new_game_id.clone(),
+                        },
+                        Duration::from_days(ROOM_TOKEN_DAYS),
+                    );
+                    let token = server_state
+                        .jwt_key
+                        .authenticate(claims)
+                        .expect("Claims should be serializable");
 
-                        let claims = Claims::with_custom_claims(
-                            PlayerClaims {
-                                player_index: i,
-                                room_code: new_game_id.clone(),
+                    server_state
+                        .send_to_player(
+                            &socket,
+                            GameMessage::JoinedLobby(
+                                i as u64,
+                                new_game_id.clone(),
+                                new_game_manager.player_list(),
+                                new_game_manager.core_game.board.clone(),
+                                token,
+                            ),
+                        )
+                        .unwrap();
+                    _ = server_state.send_to_player(&socket, GameMessage::RoomRules(rules.clone()));
+                    if available.len() > 1 {
+                        _ = server_state.send_to_player(
+                            &socket,
+                            GameMessage::RoomLanguage {
+                                language: rules.language,
+                                available: available.clone(),
                             },
-                            Duration::from_days(7), // TODO: Determine game expiration time
                         );
-                        let token = server_state
-                            .jwt_key
-                            .authenticate(claims)
-                            .expect("Claims should be serializable");
-
-                        server_state
-                            .send_to_player(
-                                &socket,
-                                GameMessage::JoinedLobby(
-                                    i as u64,
-                                    new_game_id.clone(),
-                                    new_game_manager.player_list(),
-                                    new_game_manager.core_game.board.clone(),
-                                    token,
-                                ),
-                            )
-                            .unwrap();
                     }
                 }
             }
@@ -648,7 +957,7 @@ async fn handle_player_msg(
         Pause => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.pause(server_state.words()) {
+                for (player, message) in game_manager.pause(player_addr, server_state.words()) {
                     let Some(socket) = player.socket else {
                         continue;
                     };
@@ -662,7 +971,7 @@ async fn handle_player_msg(
         Unpause => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
                 let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.unpause(server_state.words()) {
+                for (player, message) in game_manager.unpause(player_addr, server_state.words()) {
                     let Some(socket) = player.socket else {
                         continue;
                     };
@@ -673,59 +982,378 @@ async fn handle_player_msg(
                 todo!("Handle player not being enrolled in a game");
             }
         }
-        RequestDefinitions(words) => {
-            let word_db = server_state.word_db.lock();
-            let definitions: Vec<_> = words
-                .iter()
-                .map(|word| (word.clone(), word_db.get_word(&word.to_lowercase()).clone()))
-                .collect();
-            // Don't hold the lock while sending messages
-            drop(word_db);
+        Presence { away } => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let game_manager = existing_game.lock();
+                for (player, message) in game_manager.presence(player_addr, away) {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    // Presence is best-effort, so don't fuss if the other player has disconnected
+                    _ = server_state.send_to_player(&socket, message);
+                }
+            }
+        }
+        Chat { channel, text } => {
+            let existing_game = server_state
+                .get_game_by_player(&player_addr)
+                .or_else(|| server_state.get_game_by_spectator(&player_addr));
+            if let Some(existing_game) = existing_game {
+                let game_manager = existing_game.lock();
+                for (socket, message) in game_manager.chat(player_addr, channel, &text) {
+                    _ = server_state.send_to_player(&socket, message);
+                }
+            }
+        }
+        Point(coordinate) => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let game_manager = existing_game.lock();
+                for (player, message) in game_manager.point(player_addr, coordinate) {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    _ = server_state.send_to_player(&socket, message);
+                }
+            }
+        }
+        SpectateGame(room_code) => {
+            let code = room_code.to_ascii_lowercase();
+            let Some(existing_game) = server_state.get_game_by_code(&code) else {
+                return player_err("Room does not exist".into());
+            };
 
-            server_state
-                .send_to_player(&player_addr, GameMessage::SupplyDefinitions(definitions))
-                .unwrap();
+            let mut game_manager = existing_game.lock();
+            if game_manager.get_player_index(player_addr).is_some() {
+                return player_err("You can't spectate a game you're playing in".into());
+            }
+            if !game_manager.can_be_spectated() {
+                return player_err(
+                    "Games played in fog can only be watched once they're over".into(),
+                );
+            }
+            info!(room = %code, "Spectator joined");
+            if let Some(message) = game_manager.add_spectator(player_addr) {
+                server_state.send_to_player(&player_addr, message).unwrap();
+            }
         }
-        CreateAnonymousPlayer {
-            screen_width,
-            screen_height,
-            user_agent,
-            referrer,
-            unread_changelogs,
-        } => match accounts::create_player(
-            &server_state,
-            screen_width,
-            screen_height,
-            user_agent,
-            referrer,
-        )
-        .await
-        {
-            Ok(new_player) => {
-                let authed_token = accounts::get_player_token(&server_state, new_player);
+        SetSpectatorDelay(delay_secs) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
 
-                _ = mark_most_changelogs_read(
-                    &server_state,
-                    authed_token.clone(),
-                    unread_changelogs,
-                )
-                .await;
+            let mut game_manager = existing_game.lock();
+            if game_manager.core_game.started_at.is_some() {
+                return player_err(
+                    "The spectator delay can't change once a game has started".into(),
+                );
+            }
+            if !game_manager.can_manage(player_addr) {
+                return player_err(
+                    "Only the host or a co-host can change the spectator delay".into(),
+                );
+            }
+            game_manager.spectator_delay_secs = delay_secs.min(MAX_SPECTATOR_DELAY_SECS);
 
-                let mut connection_info = connection_info_mutex.lock();
-                connection_info.player = Some(authed_token.clone());
+            let message = GameMessage::SpectatorDelay(game_manager.spectator_delay_secs);
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                _ = server_state.send_to_player(&socket, message.clone());
+            }
+        }
+        SetHandSwaps(turns) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
 
-                server_state
-                    .send_to_player(
-                        &player_addr,
-                        GameMessage::LoggedInAs {
-                            token: authed_token.token(),
-                            unread_changelogs: vec![],
-                        },
-                    )
-                    .unwrap();
+            let mut game_manager = existing_game.lock();
+            if game_manager.core_game.started_at.is_some() {
+                return player_err("Hand swaps can't change once a game has started".into());
             }
-            Err(_) => {
-                todo!("Error handling for database actions");
+            if !game_manager.can_manage(player_addr) {
+                return player_err("Only the host or a co-host can change hand swaps".into());
+            }
+            game_manager.core_game.rules.hand_swaps = match turns {
+                0 => HandSwaps::None,
+                turns => HandSwaps::Every { turns },
+            };
+
+            let message = GameMessage::HandSwaps(turns);
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                _ = server_state.send_to_player(&socket, message.clone());
+            }
+            send_rules_warnings(&server_state, &game_manager);
+        }
+        SetLenientSpelling(lenient) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            if game_manager.core_game.started_at.is_some() {
+                return player_err("Spelling rules can't change once a game has started".into());
+            }
+            if !game_manager.can_manage(player_addr) {
+                return player_err(
+                    "Only the host or a co-host can change the spelling rules".into(),
+                );
+            }
+            game_manager.core_game.rules.battle_rules.spelling = if lenient {
+                Spelling::Lenient
+            } else {
+                Spelling::Strict
+            };
+
+            let message = GameMessage::LenientSpelling(lenient);
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                _ = server_state.send_to_player(&socket, message.clone());
+            }
+            send_rules_warnings(&server_state, &game_manager);
+        }
+        SetLanguage(language) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            if game_manager.core_game.started_at.is_some() {
+                return player_err("The language can't change once a game has started".into());
+            }
+            if !game_manager.can_manage(player_addr) {
+                return player_err("Only the host or a co-host can change the language".into());
+            }
+            let available = server_state.word_db.lock().languages();
+            if !available.contains(&language) {
+                return player_err(format!("This server can't play games in {language}"));
+            }
+            game_manager.core_game.set_language(language);
+
+            let message = GameMessage::RoomLanguage {
+                language,
+                available,
+            };
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                _ = server_state.send_to_player(&socket, message.clone());
+            }
+            send_rules_warnings(&server_state, &game_manager);
+        }
+        SetDictionaryPack(pack_id) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            if game_manager.core_game.started_at.is_some() {
+                return player_err("The dictionary can't change once a game has started".into());
+            }
+            if !game_manager.can_manage(player_addr) {
+                return player_err("Only the host or a co-host can change the dictionary".into());
+            }
+
+            let word_db = server_state.word_db.lock();
+            match pack_id {
+                Some(pack_id) => {
+                    let Some(loaded) = word_db.packs.get(&pack_id) else {
+                        return player_err("This server doesn't have that dictionary pack".into());
+                    };
+                    game_manager
+                        .core_game
+                        .set_dictionary_pack(Some(&loaded.pack));
+                }
+                None => game_manager.core_game.set_dictionary_pack(None),
+            }
+            let available = word_db.languages();
+            drop(word_db);
+
+            // Packs can bring their own language, which switches the room's letters too
+            let messages = [
+                GameMessage::RoomRules(game_manager.core_game.rules.clone()),
+                GameMessage::RoomLanguage {
+                    language: game_manager.core_game.rules.language,
+                    available,
+                },
+            ];
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                for message in &messages {
+                    _ = server_state.send_to_player(&socket, message.clone());
+                }
+            }
+            send_rules_warnings(&server_state, &game_manager);
+        }
+        RequestPacks => {
+            let packs = server_state.word_db.lock().available_packs();
+            server_state
+                .send_to_player(&player_addr, GameMessage::AvailablePacks(packs))
+                .unwrap();
+        }
+        DownloadPack(pack_id) => {
+            let contents = server_state
+                .word_db
+                .lock()
+                .packs
+                .get(&pack_id)
+                .map(|loaded| GameMessage::PackContents(loaded.pack.clone(), loaded.words.clone()));
+            let Some(contents) = contents else {
+                return player_err("This server doesn't have that dictionary pack".into());
+            };
+            server_state.send_to_player(&player_addr, contents).unwrap();
+        }
+        DownloadLanguage(language) => {
+            let contents = {
+                let word_db = server_state.word_db.lock();
+                let loaded = word_db.languages.get(&language);
+                GameMessage::LanguageContents {
+                    language,
+                    words: loaded.map(|loaded| loaded.words.clone()),
+                    seed_notes: loaded.and_then(|loaded| loaded.seed_notes.clone()),
+                }
+            };
+            server_state.send_to_player(&player_addr, contents).unwrap();
+        }
+        SetBlankTiles(count) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            if game_manager.core_game.started_at.is_some() {
+                return player_err("Blank tiles can't change once a game has started".into());
+            }
+            if !game_manager.can_manage(player_addr) {
+                return player_err("Only the host or a co-host can change the blank tiles".into());
+            }
+            let count = count.min(MAX_BLANK_TILES);
+            game_manager.core_game.set_blank_tiles(count);
+
+            let message = GameMessage::BlankTiles(count);
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                _ = server_state.send_to_player(&socket, message.clone());
+            }
+            send_rules_warnings(&server_state, &game_manager);
+        }
+        SetRules(rules) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("You aren't in a room".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            if game_manager.core_game.started_at.is_some() {
+                return player_err("The rules can't change once a game has started".into());
+            }
+            if !game_manager.can_manage(player_addr) {
+                return player_err("Only the host or a co-host can change the rules".into());
+            }
+            let rules = match playable_rules(rules) {
+                Ok(rules) => rules,
+                Err(e) => return player_err(e),
+            };
+            game_manager.core_game.set_rules(rules);
+
+            let message = GameMessage::RoomRules(game_manager.core_game.rules.clone());
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                _ = server_state.send_to_player(&socket, message.clone());
+            }
+            send_rules_warnings(&server_state, &game_manager);
+        }
+        MuteChat { channel, muted } => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                existing_game.lock().mute_chat(player_addr, channel, muted);
+            }
+        }
+        AckEvents(seq) => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                existing_game.lock().ack_events(player_addr, seq);
+            }
+        }
+        RequestEventsSince(seq) => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let events = existing_game.lock().events_since(player_addr, seq);
+                if let Some(message) = events {
+                    server_state.send_to_player(&player_addr, message).unwrap();
+                }
+            }
+        }
+        RequestDefinitions(words) => {
+            let word_db = server_state.word_db.lock();
+            let definitions: Vec<_> = words
+                .iter()
+                .map(|word| (word.clone(), word_db.get_word(&word.to_lowercase()).clone()))
+                .collect();
+            // Don't hold the lock while sending messages
+            drop(word_db);
+
+            server_state
+                .send_to_player(&player_addr, GameMessage::SupplyDefinitions(definitions))
+                .unwrap();
+        }
+        CreateAnonymousPlayer {
+            screen_width,
+            screen_height,
+            user_agent,
+            referrer,
+            unread_changelogs,
+        } => match accounts::create_player(
+            &server_state,
+            screen_width,
+            screen_height,
+            user_agent,
+            referrer,
+        )
+        .await
+        {
+            Ok(new_player) => {
+                let authed_token = accounts::get_player_token(&server_state, new_player);
+
+                _ = mark_most_changelogs_read(
+                    &server_state,
+                    authed_token.clone(),
+                    unread_changelogs,
+                )
+                .await;
+
+                let mut connection_info = connection_info_mutex.lock();
+                connection_info.player = Some(authed_token.clone());
+
+                server_state
+                    .send_to_player(
+                        &player_addr,
+                        GameMessage::LoggedInAs {
+                            token: authed_token.token(),
+                            unread_changelogs: vec![],
+                        },
+                    )
+                    .unwrap();
+                server_state
+                    .send_to_player(&player_addr, GameMessage::ActiveSeason(active_season()))
+                    .unwrap();
+                server_state
+                    .send_to_player(
+                        &player_addr,
+                        GameMessage::SignInProviders(server_state.oauth_apps.enabled()),
+                    )
+                    .unwrap();
+            }
+            Err(_) => {
+                todo!("Error handling for database actions");
             }
         },
         Login {
@@ -763,21 +1391,93 @@ async fn handle_player_msg(
                         },
                     )
                     .unwrap();
+                server_state
+                    .send_to_player(&player_addr, GameMessage::ActiveSeason(active_season()))
+                    .unwrap();
+                server_state
+                    .send_to_player(
+                        &player_addr,
+                        GameMessage::SignInProviders(server_state.oauth_apps.enabled()),
+                    )
+                    .unwrap();
             }
             Err(_e) => {
-                eprintln!(
-                    "Player tried to login with a bad token and failed ! ! ! ! ! ! ! ! ! ! !"
-                );
+                warn!("Player tried to login with a bad token and failed");
                 return player_err("Invalid Token".into());
             }
         },
-        LoadDailyPuzzle(token, day) => {
-            let Ok(authed) = accounts::auth_player_token(&server_state, token) else {
+        StartSignIn {
+            provider,
+            redirect_uri,
+        } => {
+            let player = connection_info_mutex
+                .lock()
+                .player
+                .as_ref()
+                .map(|p| p.player());
+            match oauth::authorize_url(&server_state, provider, redirect_uri, player_addr, player) {
+                Ok(url) => {
+                    server_state
+                        .send_to_player(&player_addr, GameMessage::SignInRedirect(url))
+                        .unwrap();
+                }
+                Err(_) => {
+                    return player_err(format!("Signing in with {provider} isn't available"));
+                }
+            }
+        }
+        SignIn { code, state } => {
+            let current = connection_info_mutex.lock().player.clone();
+            let player = current.as_ref().map(|p| p.player());
+            let (provider, subject) =
+                match oauth::identify(&server_state, code, state, player_addr, player).await {
+                    Ok(identity) => identity,
+                    Err(e) => {
+                        warn!(error = ?e, "Player failed to sign in with a provider");
+                        return player_err("Signing in didn't work, please try again".into());
+                    }
+                };
+
+            match accounts::oauth_login(&server_state, provider, subject, current).await {
+                Ok(LoginResponse {
+                    player_id: _,
+                    authed,
+                    unread_changelogs,
+                }) => {
+                    let token = authed.token();
+                    connection_info_mutex.lock().player = Some(authed);
+
+                    server_state
+                        .send_to_player(
+                            &player_addr,
+                            GameMessage::LoggedInAs {
+                                token,
+                                unread_changelogs: unread_changelogs
+                                    .into_iter()
+                                    .map(|c| c.changelog_id)
+                                    .collect(),
+                            },
+                        )
+                        .unwrap();
+                }
+                Err(TruncateServerError::AlreadyLinked) => {
+                    return player_err(format!(
+                        "You're already signed in with a different {provider} account"
+                    ));
+                }
+                Err(e) => {
+                    warn!(error = ?e, "Player failed to sign in with a provider");
+                    return player_err("Signing in didn't work, please try again".into());
+                }
+            }
+        }
+        LoadDailyPuzzle(token, day, language) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token).await else {
                 return player_err("Invalid Token".into());
             };
 
             if let Ok(Some((puzzle, best))) =
-                daily::load_attempt(&server_state, authed, day as i32).await
+                daily::load_attempt(&server_state, authed, day as i32, language).await
             {
                 server_state
                     .send_to_player(&player_addr, GameMessage::ResumeDailyPuzzle(puzzle, best))
@@ -791,6 +1491,9 @@ async fn handle_player_msg(
                                 puzzle_day: day,
                                 attempt: 0,
                                 current_moves: vec![],
+                                think_secs: vec![],
+                                category: PuzzleCategory::Daily,
+                                language,
                             },
                             None,
                         ),
@@ -798,9 +1501,51 @@ async fn handle_player_msg(
                     .unwrap();
             }
         }
+        LoadCategoryPuzzle {
+            player_token,
+            category,
+            period,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            let attempt = match category {
+                // Puzzles picked by category are only made in English
+                PuzzleCategory::Daily => {
+                    daily::load_attempt(&server_state, authed, period as i32, Language::English)
+                        .await
+                }
+                _ => rotations::load_attempt(&server_state, authed, category, period as i32).await,
+            };
+
+            let (puzzle, best) = match attempt {
+                Ok(Some(attempt)) => attempt,
+                _ => (
+                    DailyStateMessage {
+                        puzzle_day: period,
+                        attempt: 0,
+                        current_moves: vec![],
+                        think_secs: vec![],
+                        category,
+                        language: Language::English,
+                    },
+                    None,
+                ),
+            };
+            server_state
+                .send_to_player(&player_addr, GameMessage::ResumeDailyPuzzle(puzzle, best))
+                .unwrap();
+        }
         LoadReplay(id) => {
-            let connection_player = connection_info_mutex.lock().player.clone();
-            _ = create_event(&server_state, &"load_replay".into(), connection_player).await;
+            let (connection_player, flags) = connection_info_mutex.lock().analytics();
+            _ = create_event(
+                &server_state,
+                &"load_replay".into(),
+                connection_player,
+                flags,
+            )
+            .await;
 
             let Ok(uuid) = Uuid::parse_str(&id) else {
                 return player_err("Invalid Replay ID".into());
@@ -810,6 +1555,10 @@ async fn handle_player_msg(
                 server_state
                     .send_to_player(&player_addr, GameMessage::LoadDailyReplay(puzzle))
                     .unwrap();
+            } else if let Ok(Some(replay)) = replays::load_replay(&server_state, uuid).await {
+                server_state
+                    .send_to_player(&player_addr, GameMessage::LoadGameReplay(replay))
+                    .unwrap();
             } else {
                 return player_err("Replay does not exist".into());
             }
@@ -819,159 +1568,1686 @@ async fn handle_player_msg(
             day,
             human_player,
             moves,
+            think_secs,
             won,
+            category,
+            language,
+            rules_generation,
+            board_seed,
         } => {
-            let Ok(authed) = accounts::auth_player_token(&server_state, player_token) else {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
                 return player_err("Invalid Token".into());
             };
 
-            if let Err(e) = daily::persist_moves(
-                &server_state,
-                authed,
-                day as i32,
-                human_player as i32,
-                moves,
-                won,
-            )
-            .await
-            {
-                eprintln!("Errored persisting daily game moves: {e}\n{e:?}");
+            let persisted = match category {
+                PuzzleCategory::Daily => {
+                    daily::persist_moves(
+                        &server_state,
+                        authed,
+                        day as i32,
+                        human_player as i32,
+                        moves,
+                        think_secs,
+                        won,
+                        language,
+                        rules_generation,
+                        board_seed,
+                    )
+                    .await
+                }
+                _ => {
+                    rotations::persist_moves(
+                        &server_state,
+                        authed,
+                        (category, day as i32),
+                        human_player as i32,
+                        moves,
+                        think_secs,
+                        won,
+                    )
+                    .await
+                }
+            };
+            if let Err(e) = persisted {
+                error!(error = ?e, "Errored persisting daily game moves: {e}");
             }
         }
-        RequestStats(token) => {
-            let Ok(authed) = accounts::auth_player_token(&server_state, token) else {
+        RequestStats(token, language) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token).await else {
                 return player_err("Invalid Token".into());
             };
 
-            match daily::load_stats(&server_state, authed).await {
+            match daily::load_stats(&server_state, authed, language).await {
                 Ok(stats) => {
                     server_state
                         .send_to_player(&player_addr, GameMessage::DailyStats(stats))
                         .unwrap();
                 }
                 Err(e) => {
-                    eprintln!("Errored loading stats for player: {e}\n{e:?}");
+                    error!(error = ?e, "Errored loading stats for player: {e}");
                 }
             }
         }
-        MarkChangelogRead(id) => {
-            let Some(connection_player) = connection_info_mutex.lock().player.clone() else {
-                eprintln!(
-                    "No connection player found, but player wanted to mark changelog as read"
-                );
-                return Ok(());
+        LoadWordLadder(token) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token).await else {
+                return player_err("Invalid Token".into());
             };
 
-            _ = mark_changelog_read(&server_state, connection_player, id).await;
-        }
-        GenericEvent { name } => {
-            let connection_player = connection_info_mutex.lock().player.clone();
-            _ = create_event(&server_state, &name, connection_player).await;
+            match ladder::load_stats(&server_state, authed).await {
+                Ok(stats) => {
+                    server_state
+                        .send_to_player(&player_addr, GameMessage::WordLadderStats(stats))
+                        .unwrap();
+                }
+                Err(e) => {
+                    error!(error = ?e, "Errored loading word ladder stats for player: {e}");
+                }
+            }
         }
-    }
+        PersistWordLadder {
+            player_token,
+            day,
+            words,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
 
-    Ok(())
-}
+            // Each player solves the ladder for their own local day, so accept any day that it
+            // is somewhere right now by our clock, rather than whichever day the client says
+            if !live_puzzle_days(truncate_core::game::now()).contains(&day) {
+                warn!(
+                    day,
+                    "Player submitted a word ladder for a day that isn't live"
+                );
+                return player_err("This word ladder has closed".into());
+            }
 
-#[derive(Default)]
-struct ConnectionInfo {
-    player: Option<AuthedTruncateToken>,
-}
+            // Only solved ladders are stored, so check the solution against our own dictionary
+            let solved = {
+                let word_db = server_state.word_db.lock();
+                WordLadder::daily(day, &word_db.valid_words)
+                    .map(|ladder| ladder.validate(&words, &word_db.valid_words))
+            };
+            match solved {
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    warn!(day, "Player submitted an invalid word ladder: {e}");
+                    return player_err(e.to_string());
+                }
+                None => {
+                    warn!(day, "No word ladder could be generated for the day");
+                    return player_err("No word ladder exists for this day".into());
+                }
+            }
 
-async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, addr: SocketAddr) {
-    let ws_stream = tokio_tungstenite::accept_async(raw_stream)
-        .await
-        .expect("Error during the websocket handshake occurred");
+            if let Err(e) =
+                ladder::persist_solve(&server_state, authed.clone(), day as i32, &words).await
+            {
+                error!(error = ?e, "Errored persisting word ladder: {e}");
+                return Ok(());
+            }
 
-    let (player_tx, player_rx) = mpsc::unbounded_channel();
-    server_state.track_peer(&addr, player_tx);
+            match ladder::load_stats(&server_state, authed).await {
+                Ok(stats) => {
+                    server_state
+                        .send_to_player(&player_addr, GameMessage::WordLadderStats(stats))
+                        .unwrap();
+                }
+                Err(e) => {
+                    error!(error = ?e, "Errored loading word ladder stats for player: {e}");
+                }
+            }
+        }
+        LoadNpcProgress(token) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token).await else {
+                return player_err("Invalid Token".into());
+            };
 
-    let (outgoing, incoming) = ws_stream.split();
+            match npc_ladder::load_progress(&server_state, authed).await {
+                Ok(progress) => {
+                    server_state
+                        .send_to_player(&player_addr, GameMessage::NpcProgress(progress))
+                        .unwrap();
+                }
+                Err(e) => {
+                    error!(error = ?e, "Errored loading NPC ladder progress for player: {e}");
+                }
+            }
+        }
+        PersistNpcWin {
+            player_token,
+            rung,
+            moves,
+            rules_generation,
+            board_seed,
+            human_player,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            let mut progress = match npc_ladder::load_progress(&server_state, authed.clone()).await
+            {
+                Ok(progress) => progress,
+                Err(e) => {
+                    error!(error = ?e, "Errored loading NPC ladder progress for player: {e}");
+                    return Ok(());
+                }
+            };
+
+            if let Err(e) = progress.record_win(rung) {
+                warn!(rung, "Player submitted an invalid NPC ladder win: {e}");
+                return player_err(e.to_string());
+            }
+
+            // Only wins that play out the same here are kept
+            let replayed = {
+                let word_db = server_state.word_db.lock();
+                progression::replay_win(
+                    BoardSeed::new_with_generation(board_seed.0, board_seed.1),
+                    rules_generation,
+                    human_player,
+                    &moves,
+                    word_db.dictionary(Language::English),
+                )
+            };
+            if let Err(e) = replayed {
+                warn!(
+                    rung,
+                    "Player submitted an NPC ladder win that didn't replay: {e}"
+                );
+                return player_err(e.to_string());
+            }
+
+            if let Err(e) = npc_ladder::persist_win(
+                &server_state,
+                authed,
+                rung as i32,
+                &moves,
+                rules_generation,
+                board_seed,
+            )
+            .await
+            {
+                error!(error = ?e, "Errored persisting NPC ladder win: {e}");
+                return Ok(());
+            }
+
+            server_state
+                .send_to_player(&player_addr, GameMessage::NpcProgress(progress))
+                .unwrap();
+        }
+        LoadPuzzleRush {
+            player_token,
+            day,
+            language,
+        } => {
+            // The leaderboard is public, so only a personal best needs a valid token
+            let authed = match player_token {
+                Some(token) => accounts::auth_player_token(&server_state, token).await.ok(),
+                None => None,
+            };
+
+            match rush::load_leaderboard(&server_state, authed, day as i32, language).await {
+                Ok(leaderboard) => {
+                    server_state
+                        .send_to_player(
+                            &player_addr,
+                            GameMessage::PuzzleRushLeaderboard(leaderboard),
+                        )
+                        .unwrap();
+                }
+                Err(e) => {
+                    error!(error = ?e, "Errored loading the puzzle rush leaderboard: {e}");
+                }
+            }
+        }
+        StartPuzzleRush(player_token) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            let seed = server_state.rushes.lock().start(authed.player());
+            server_state
+                .send_to_player(&player_addr, GameMessage::PuzzleRushStarted(seed))
+                .unwrap();
+        }
+        PersistPuzzleRush {
+            player_token,
+            day,
+            rush: finished_rush,
+            language,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            // Only runs the server started are ranked, timed by the server's own clock
+            let Some(elapsed) = server_state
+                .rushes
+                .lock()
+                .finish(authed.player(), finished_rush.seed)
+            else {
+                return player_err("That puzzle rush wasn't started by the server".into());
+            };
+            if !finished_rush.finished_in_time(elapsed) {
+                warn!(
+                    day,
+                    elapsed_secs = elapsed.as_secs(),
+                    "Player submitted a puzzle rush that ran over time"
+                );
+                return player_err("That puzzle rush ran over time".into());
+            }
+
+            // Drills are generated from the seed, so replay every solution against our own dictionary
+            let verified = {
+                let word_db = server_state.word_db.lock();
+                let Some(dictionary) = word_db.dictionary(language) else {
+                    return player_err(format!("This server has no puzzle rush in {language}"));
+                };
+                finished_rush.verify(dictionary)
+            };
+            if verified.is_none() {
+                warn!(
+                    day,
+                    seed = finished_rush.seed,
+                    "Player submitted an invalid puzzle rush"
+                );
+                return player_err("That puzzle rush couldn't be verified".into());
+            }
+
+            if let Err(e) = rush::persist_run(
+                &server_state,
+                authed.clone(),
+                day as i32,
+                &finished_rush,
+                language,
+            )
+            .await
+            {
+                error!(error = ?e, "Errored persisting puzzle rush: {e}");
+                return Ok(());
+            }
+
+            match rush::load_leaderboard(&server_state, Some(authed), day as i32, language).await {
+                Ok(leaderboard) => {
+                    server_state
+                        .send_to_player(
+                            &player_addr,
+                            GameMessage::PuzzleRushLeaderboard(leaderboard),
+                        )
+                        .unwrap();
+                }
+                Err(e) => {
+                    error!(error = ?e, "Errored loading the puzzle rush leaderboard: {e}");
+                }
+            }
+        }
+        PersistPredictions {
+            player_token,
+            stats,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            if !stats.is_plausible() {
+                warn!(?stats, "Player submitted implausible prediction stats");
+                return player_err("Those predictions couldn't be recorded".into());
+            }
+
+            if let Err(e) = predictions::record_predictions(&server_state, authed, &stats).await {
+                error!(error = ?e, "Errored persisting predictions: {e}");
+            }
+        }
+        LoadWordOfTheDay(day) => {
+            let Some(daily) = server_state.word_of_the_day(day) else {
+                warn!(day, "No word of the day could be picked");
+                return player_err("No word of the day exists for this day".into());
+            };
+
+            server_state
+                .send_to_player(&player_addr, GameMessage::WordOfTheDay(daily))
+                .unwrap();
+        }
+        ExportGames(token) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            match daily::export_archive(&server_state, authed).await {
+                Ok(archive) => {
+                    server_state
+                        .send_to_player(&player_addr, GameMessage::GameArchive(archive))
+                        .unwrap();
+                }
+                Err(e) => {
+                    error!(error = ?e, "Errored exporting games for player: {e}");
+                    return player_err("Could not export games".into());
+                }
+            }
+        }
+        SaveBoard {
+            player_token,
+            name,
+            board,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            if let Err(e) = boards::save_board(&server_state, authed.clone(), &name, &board).await {
+                error!(error = ?e, "Errored saving board for player: {e}");
+                return player_err("Could not save board".into());
+            }
+            send_saved_boards(&server_state, player_addr, authed).await;
+        }
+        LoadSavedBoards(token) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            send_saved_boards(&server_state, player_addr, authed).await;
+        }
+        RequestFairPlay(token) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            send_fair_play(&server_state, player_addr, authed).await;
+        }
+        AppealAbandonment {
+            player_token,
+            abandonment_id,
+            reason,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+            let Ok(abandonment_id) = Uuid::parse_str(&abandonment_id) else {
+                return player_err("Invalid Abandonment ID".into());
+            };
+
+            if let Err(e) = fair_play::appeal_abandonment(
+                &server_state,
+                authed.clone(),
+                abandonment_id,
+                &reason,
+            )
+            .await
+            {
+                error!(error = ?e, "Errored appealing abandonment: {e}");
+                return player_err("Could not submit appeal".into());
+            }
+            send_fair_play(&server_state, player_addr, authed).await;
+        }
+        DeleteSavedBoard {
+            player_token,
+            board_id,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+            let Ok(board_id) = Uuid::parse_str(&board_id) else {
+                return player_err("Invalid Board ID".into());
+            };
+
+            if let Err(e) = boards::delete_board(&server_state, authed.clone(), board_id).await {
+                error!(error = ?e, "Errored deleting board for player: {e}");
+                return player_err("Could not delete board".into());
+            }
+            send_saved_boards(&server_state, player_addr, authed).await;
+        }
+        ShareBoard {
+            player_token,
+            board_id,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+            let Ok(board_id) = Uuid::parse_str(&board_id) else {
+                return player_err("Invalid Board ID".into());
+            };
+
+            if let Err(e) = boards::share_board(&server_state, authed.clone(), board_id).await {
+                error!(error = ?e, "Errored sharing board for player: {e}");
+                return player_err("Could not share board".into());
+            }
+            send_saved_boards(&server_state, player_addr, authed).await;
+        }
+        ModerateBoard {
+            admin_key,
+            board_id,
+            approved,
+        } => {
+            if server_state.admin_key.as_ref() != Some(&admin_key) {
+                warn!("Rejected a board moderation with an invalid admin key");
+                return player_err("Invalid admin key".into());
+            }
+            let Ok(board_id) = Uuid::parse_str(&board_id) else {
+                return player_err("Invalid Board ID".into());
+            };
+
+            if let Err(e) = boards::moderate_board(&server_state, board_id, approved).await {
+                error!(error = ?e, "Errored moderating board: {e}");
+                return player_err("Could not moderate board".into());
+            }
+            info!(board = %board_id, approved, "Moderated a gallery board");
+        }
+        LoadGallery => match boards::load_gallery(&server_state).await {
+            Ok(gallery) => {
+                server_state
+                    .send_to_player(&player_addr, GameMessage::Gallery(gallery))
+                    .unwrap();
+            }
+            Err(e) => {
+                error!(error = ?e, "Errored loading the board gallery: {e}");
+                return player_err("Could not load the gallery".into());
+            }
+        },
+        UseGalleryBoard(board_id) => {
+            let Ok(board_id) = Uuid::parse_str(&board_id) else {
+                return player_err("Invalid Board ID".into());
+            };
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("Not in a lobby".into());
+            };
+            if !existing_game.lock().can_manage(player_addr) {
+                return player_err("Only the host or a co-host can change the board".into());
+            }
+            let (name, board) = match boards::load_gallery_board(&server_state, board_id).await {
+                Ok(Some(gallery_board)) => gallery_board,
+                Ok(None) => return player_err("That board isn't in the gallery".into()),
+                Err(e) => {
+                    error!(error = ?e, "Errored loading a gallery board: {e}");
+                    return player_err("Could not load board".into());
+                }
+            };
+
+            let mut game_manager = existing_game.lock();
+            game_manager.edit_board(board.clone());
+            game_manager.gallery_board = Some((board_id, name));
+            let player_list = game_manager.player_list();
+
+            let Some(player_index) = game_manager.get_player_index(player_addr) else {
+                return player_err("Not in a lobby".into());
+            };
+
+            for player in &game_manager.players {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                server_state
+                    .send_to_player(
+                        &socket,
+                        GameMessage::LobbyUpdate(
+                            player_index as u64,
+                            game_manager.game_id.clone(),
+                            player_list.clone(),
+                            board.clone(),
+                        ),
+                    )
+                    .unwrap();
+            }
+        }
+        PromotePlayer { player, role } => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("Not in a lobby".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            if let Err(msg) = game_manager.promote_player(player_addr, player, role) {
+                return player_err(msg);
+            }
+            info!(player, %role, "Changed a player's room role");
+
+            let player_list = game_manager.player_list();
+            for lobby_player in &game_manager.players {
+                let Some(socket) = lobby_player.socket else {
+                    continue;
+                };
+                _ = server_state.send_to_player(
+                    &socket,
+                    GameMessage::LobbyUpdate(
+                        player as u64,
+                        game_manager.game_id.clone(),
+                        player_list.clone(),
+                        game_manager.core_game.board.clone(),
+                    ),
+                );
+            }
+        }
+        KickPlayer(player) => {
+            let Some(existing_game) = server_state.get_game_by_player(&player_addr) else {
+                return player_err("Not in a lobby".into());
+            };
+
+            let mut game_manager = existing_game.lock();
+            let kicked_socket = match game_manager.kick_player(player_addr, player) {
+                Ok(socket) => socket,
+                Err(msg) => return player_err(msg),
+            };
+            info!(player, "Removed a player from the lobby");
+
+            let room_code = game_manager.game_id.clone();
+            if let Some(socket) = kicked_socket {
+                server_state.detach_player(&socket);
+                _ = server_state.send_to_player(&socket, GameMessage::Kicked(room_code.clone()));
+            }
+
+            let player_list = game_manager.player_list();
+            for (player_index, lobby_player) in game_manager.players.iter().enumerate() {
+                let Some(socket) = lobby_player.socket else {
+                    continue;
+                };
+
+                // Everyone after the removed player has moved up a seat, so needs a token for their new one
+                let message = if player_index >= player {
+                    let claims = Claims::with_custom_claims(
+                        PlayerClaims {
+                            player_index,
+                            room_code: room_code.clone(),
+                        },
+                        Duration::from_days(ROOM_TOKEN_DAYS),
+                    );
+                    let token = server_state
+                        .jwt_key
+                        .authenticate(claims)
+                        .expect("Claims should be serializable");
+                    GameMessage::JoinedLobby(
+                        player_index as u64,
+                        room_code.clone(),
+                        player_list.clone(),
+                        game_manager.core_game.board.clone(),
+                        token,
+                    )
+                } else {
+                    GameMessage::LobbyUpdate(
+                        player as u64,
+                        room_code.clone(),
+                        player_list.clone(),
+                        game_manager.core_game.board.clone(),
+                    )
+                };
+                _ = server_state.send_to_player(&socket, message);
+            }
+            send_rules_warnings(&server_state, &game_manager);
+        }
+        ScheduleGame {
+            player_token,
+            player_name,
+            effective_day,
+            starts_at,
+            ruleset,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            match schedule::schedule_game(
+                &server_state,
+                authed.clone(),
+                &player_name,
+                effective_day,
+                starts_at,
+                ruleset,
+            )
+            .await
+            {
+                Ok(invite_code) => info!(%invite_code, starts_at, "Scheduled a game"),
+                Err(e) => {
+                    error!(error = ?e, "Errored scheduling game: {e}");
+                    return player_err("Could not schedule game".into());
+                }
+            }
+            send_scheduled_games(&server_state, player_addr, authed).await;
+        }
+        AcceptScheduledGame {
+            player_token,
+            player_name,
+            invite_code,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            if let Err(e) =
+                schedule::accept_invite(&server_state, authed.clone(), &player_name, &invite_code)
+                    .await
+            {
+                error!(error = ?e, "Errored accepting scheduled game: {e}");
+                return player_err("Could not accept invite, it may have been taken".into());
+            }
+            send_scheduled_games(&server_state, player_addr, authed).await;
+        }
+        LoadScheduledGames(token) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            send_scheduled_games(&server_state, player_addr, authed).await;
+        }
+        CancelScheduledGame {
+            player_token,
+            invite_code,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+
+            if let Err(e) = schedule::cancel_game(&server_state, authed.clone(), &invite_code).await
+            {
+                error!(error = ?e, "Errored cancelling scheduled game: {e}");
+                return player_err("Could not cancel game".into());
+            }
+            send_scheduled_games(&server_state, player_addr, authed).await;
+        }
+        RateBoard {
+            player_token,
+            board_id,
+            stars,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token).await else {
+                return player_err("Invalid Token".into());
+            };
+            let Ok(board_id) = Uuid::parse_str(&board_id) else {
+                return player_err("Invalid Board ID".into());
+            };
+
+            if let Err(e) = boards::rate_board(&server_state, authed, board_id, stars).await {
+                error!(error = ?e, "Errored rating board for player: {e}");
+                return player_err("Could not rate board".into());
+            }
+        }
+        RequestPuzzleDifficulty {
+            admin_key,
+            from_day,
+            to_day,
+            language,
+        } => {
+            if server_state.admin_key.as_ref() != Some(&admin_key) {
+                warn!("Rejected a puzzle difficulty report with an invalid admin key");
+                return player_err("Invalid admin key".into());
+            }
+
+            match daily::load_difficulty(&server_state, from_day, to_day, language).await {
+                Ok(days) => {
+                    info!(days = days.len(), "Reporting puzzle difficulty");
+                    server_state
+                        .send_to_player(&player_addr, GameMessage::PuzzleDifficulty(days))
+                        .unwrap();
+                }
+                Err(e) => {
+                    error!(error = ?e, "Errored loading puzzle difficulty: {e}");
+                    return player_err("Could not load puzzle difficulty".into());
+                }
+            }
+        }
+        FlagRoom {
+            admin_key,
+            room_code,
+            webhook_url,
+        } => {
+            if server_state.admin_key.as_ref() != Some(&admin_key) {
+                warn!("Rejected a room flag with an invalid admin key");
+                return player_err("Invalid admin key".into());
+            }
+            if !webhook_url.starts_with("https://") && !webhook_url.starts_with("http://") {
+                return player_err("Webhooks must be an http(s) URL".into());
+            }
+            let Some(game) = server_state.get_game_by_code(&room_code) else {
+                return player_err("Room does not exist".into());
+            };
+
+            info!(room = %room_code, "Flagging room for webhooks");
+            let mut game_manager = game.lock();
+            game_manager.webhook = Some(webhook_url);
+            // Catch the webhook up if the game is already underway
+            game_manager.report_to_webhook();
+        }
+        Arbitrate {
+            admin_key,
+            room_code,
+            action,
+            reason,
+        } => {
+            if server_state.admin_key.as_ref() != Some(&admin_key) {
+                warn!("Rejected an arbiter ruling with an invalid admin key");
+                return player_err("Invalid admin key".into());
+            }
+            if reason.trim().is_empty() {
+                return player_err("Arbiter rulings need a reason".into());
+            }
+            let Some(game) = server_state.get_game_by_code(&room_code) else {
+                return player_err("Room does not exist".into());
+            };
+
+            let turn_count = {
+                let mut game_manager = game.lock();
+                let messages =
+                    match game_manager.arbitrate(action, reason.clone(), server_state.words()) {
+                        Ok(messages) => messages,
+                        Err(e) => return player_err(e),
+                    };
+                for (player, message) in messages {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    _ = server_state.send_to_player(&socket, message);
+                }
+                game_manager.core_game.turn_count
+            };
+
+            info!(room = %room_code, %action, %reason, "Arbiter ruling");
+            if let Err(e) = arbiter::record_arbiter_action(
+                &server_state,
+                &room_code,
+                action,
+                &reason,
+                turn_count,
+            )
+            .await
+            {
+                error!(error = ?e, "Errored recording an arbiter ruling: {e}");
+            }
+        }
+        MarkChangelogRead(id) => {
+            let Some(connection_player) = connection_info_mutex.lock().player.clone() else {
+                warn!("No connection player found, but player wanted to mark changelog as read");
+                return Ok(());
+            };
+
+            _ = mark_changelog_read(&server_state, connection_player, id).await;
+        }
+        GenericEvent { name } => {
+            let (connection_player, flags) = connection_info_mutex.lock().analytics();
+            _ = create_event(&server_state, &name, connection_player, flags).await;
+        }
+        ReportCrash(report) => {
+            if crashes::report_size(&report) > crashes::MAX_REPORT_BYTES {
+                warn!("Dropped an oversized crash report");
+                return Ok(());
+            }
+            {
+                let mut connection_info = connection_info_mutex.lock();
+                let now = std::time::Instant::now();
+                if connection_info
+                    .last_crash_report
+                    .is_some_and(|last| now.duration_since(last) < crashes::REPORT_COOLDOWN)
+                {
+                    debug!("Dropped a crash report sent too soon after the last");
+                    return Ok(());
+                }
+                connection_info.last_crash_report = Some(now);
+            }
+
+            warn!(
+                version = %report.client_version,
+                region = %report.region,
+                board_hash = ?report.board_hash,
+                "Client reported a crash: {}",
+                report.panic_message
+            );
+            if let Err(e) = crashes::persist_report(&server_state, &report).await {
+                error!(error = ?e, "Errored persisting crash report: {e}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends the player every board they have saved, e.g. after they change one
+async fn send_saved_boards(
+    server_state: &ServerState,
+    player_addr: SocketAddr,
+    player: AuthedTruncateToken,
+) {
+    match boards::load_boards(server_state, player).await {
+        Ok(saved) => {
+            _ = server_state.send_to_player(&player_addr, GameMessage::SavedBoards(saved));
+        }
+        Err(e) => {
+            error!(error = ?e, "Errored loading saved boards for player: {e}");
+        }
+    }
+}
+
+async fn send_fair_play(
+    server_state: &ServerState,
+    player_addr: SocketAddr,
+    player: AuthedTruncateToken,
+) {
+    match fair_play::load_fair_play(server_state, player).await {
+        Ok(record) => {
+            _ = server_state.send_to_player(&player_addr, GameMessage::FairPlay(record));
+        }
+        Err(e) => {
+            error!(error = ?e, "Errored loading fair play record for player: {e}");
+        }
+    }
+}
+
+async fn send_scheduled_games(
+    server_state: &ServerState,
+    player_addr: SocketAddr,
+    player: AuthedTruncateToken,
+) {
+    let player_id = player.player();
+    match schedule::load_games(server_state, player).await {
+        Ok(games) => {
+            let games = games
+                .iter()
+                .map(|scheduled| scheduled_game_for(server_state, scheduled, player_id))
+                .collect();
+            _ = server_state.send_to_player(&player_addr, GameMessage::ScheduledGames(games));
+        }
+        Err(e) => {
+            error!(error = ?e, "Errored loading scheduled games for player: {e}");
+        }
+    }
+}
+
+/// A scheduled game as one of its players sees it, with a token for their seat once its room is open
+fn scheduled_game_for(
+    server_state: &ServerState,
+    scheduled: &schedule::Scheduled,
+    player_id: Uuid,
+) -> ScheduledGame {
+    let room_token = scheduled
+        .room_code
+        .clone()
+        .zip(scheduled.seat(player_id))
+        .filter(|(room_code, _)| server_state.get_game_by_code(room_code).is_some())
+        .map(|(room_code, player_index)| {
+            let claims = Claims::with_custom_claims(
+                PlayerClaims {
+                    player_index,
+                    room_code,
+                },
+                Duration::from_days(ROOM_TOKEN_DAYS),
+            );
+            server_state
+                .jwt_key
+                .authenticate(claims)
+                .expect("Claims should be serializable")
+        });
+
+    ScheduledGame {
+        invite_code: scheduled.invite_code.clone(),
+        scheduled_at: scheduled.scheduled_at,
+        starts_at: scheduled.starts_at,
+        ruleset: scheduled.ruleset,
+        host: scheduled.host_name.clone(),
+        guest: scheduled.guest_name.clone(),
+        link: webhooks::room_link(&format!("SCHEDULED:{}", scheduled.invite_code)),
+        room_token,
+    }
+}
+
+#[derive(Default)]
+struct ConnectionInfo {
+    player: Option<AuthedTruncateToken>,
+    /// The experiments this connection was bucketed into when it opened
+    flags: Flags,
+    /// When this connection last had a crash report stored, to keep one client from flooding them
+    last_crash_report: Option<std::time::Instant>,
+}
+
+impl ConnectionInfo {
+    /// Who is connected and which experiments they're seeing, to attribute events to
+    fn analytics(&self) -> (Option<AuthedTruncateToken>, Flags) {
+        (self.player.clone(), self.flags)
+    }
+}
+
+#[instrument(skip_all, fields(peer = %addr))]
+async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, addr: SocketAddr) {
+    let ws_stream = tokio_tungstenite::accept_async(raw_stream)
+        .await
+        .expect("Error during the websocket handshake occurred");
+    info!("Connection opened");
+
+    let (player_tx, player_rx) = mpsc::unbounded_channel();
+    server_state.track_peer(&addr, player_tx);
+
+    let (outgoing, incoming) = ws_stream.split();
+
+    let flags = Flags::assign(Uuid::new_v4().as_u64_pair().0, &server_state.rollout);
+    debug!(%flags, "Assigned session flags");
+    server_state
+        .send_to_player(&addr, GameMessage::SessionFlags(flags))
+        .unwrap();
+    let connection_info = Arc::new(Mutex::new(ConnectionInfo {
+        flags,
+        ..ConnectionInfo::default()
+    }));
+
+    // TODO: try_for_each from TryStreamExt is quite nice,
+    // look to bring that trait to the other stream places
+    let handle_player_msg = incoming.try_for_each(|msg| {
+        handle_player_msg(msg, addr, server_state.clone(), connection_info.clone())
+    });
+
+    let messages_to_player = {
+        UnboundedReceiverStream::new(player_rx)
+            .map(|msg| {
+                match &msg {
+                    GameMessage::GameUpdate(state)
+                    | GameMessage::GameTimingUpdate(state)
+                    | GameMessage::StartedGame(state) => {
+                        schedule_clock_checks(&server_state, state);
+                    }
+                    _ => {}
+                }
+
+                Ok(Message::Text(serde_json::to_string(&msg).unwrap()))
+            })
+            .forward(outgoing)
+    };
+
+    pin_mut!(handle_player_msg, messages_to_player);
+    future::select(handle_player_msg, messages_to_player).await;
+
+    server_state.peers.lock().remove(&addr);
+    server_state.capacity.lock().leave(&addr);
+    server_state.matchmaker.lock().cancel(addr);
+    info!("Connection closed");
+
+    // Players who drop out of a game in progress have a while to come back before it counts against them
+    let Some(player) = connection_info.lock().player.clone() else {
+        return;
+    };
+    let Some(game) = server_state.get_game_by_player(&addr) else {
+        return;
+    };
+    let game_id = {
+        let game_manager = game.lock();
+        // Correspondence games are made to be left and come back to
+        if game_manager.core_game.started_at.is_none()
+            || game_manager.core_game.is_over()
+            || game_manager.is_correspondence()
+            || game_manager.players.len() < 2
+            || game_manager.get_player_index(addr).is_none()
+        {
+            return;
+        }
+        game_manager.game_id.clone()
+    };
+    tokio::spawn(check_abandonment(
+        server_state.clone(),
+        game,
+        game_id,
+        addr,
+        player,
+    ));
+}
+
+/// Records an abandonment if the player hasn't reconnected to their game by the end of the grace window
+#[instrument(skip_all, fields(peer = %addr, room = %game_id))]
+async fn check_abandonment(
+    server_state: ServerState,
+    game: Arc<Mutex<GameManager>>,
+    game_id: String,
+    addr: SocketAddr,
+    player: AuthedTruncateToken,
+) {
+    tokio::time::sleep(Duration::from_secs(ABANDON_GRACE_SECS).into()).await;
+
+    {
+        let game_manager = game.lock();
+        // Reconnecting swaps the player's socket over, so finding them at the old address means they never came back
+        if game_manager.core_game.is_over() || game_manager.get_player_index(addr).is_none() {
+            return;
+        }
+    }
+
+    info!("Player abandoned their game");
+    if let Err(e) = fair_play::record_abandonment(&server_state, player, &game_id).await {
+        error!(error = ?e, "Errored recording an abandonment: {e}");
+    }
+}
+
+/// Describes how long the player must wait before their next game, if they're serving a penalty for leaving games early
+async fn abandonment_penalty(
+    server_state: &ServerState,
+    player: Option<AuthedTruncateToken>,
+) -> Option<String> {
+    let player = player?;
+    let record = fair_play::load_fair_play(server_state, player).await.ok()?;
+    if record.penalty_secs == 0 {
+        return None;
+    }
+
+    let minutes = record.penalty_secs.div_ceil(60);
+    Some(format!(
+        "You left a recent game early, so you can start another in {minutes} minute{}",
+        if minutes == 1 { "" } else { "s" }
+    ))
+}
+
+/// Starts a room's game, letting each player know
+fn start_room(server_state: &ServerState, game_manager: &mut GameManager) {
+    for (player, message) in game_manager.start() {
+        let Some(socket) = player.socket else {
+            continue;
+        };
+
+        let room_code = game_manager.game_id.clone();
+
+        match &game_manager.core_game.rules.timing {
+            truncate_core::rules::Timing::Periodic {
+                total_time_allowance,
+                ..
+            } => {
+                tokio::spawn(check_game_over(
+                    room_code,
+                    (*total_time_allowance + 1) as i128 * 1000,
+                    server_state.clone(),
+                ));
+            }
+            _ => {}
+        };
+
+        server_state.send_to_player(&socket, message).unwrap();
+        if let Some((board_id, name)) = &game_manager.gallery_board {
+            _ = server_state.send_to_player(
+                &socket,
+                GameMessage::PlayingGalleryBoard {
+                    board_id: board_id.to_string(),
+                    name: name.clone(),
+                },
+            );
+        }
+    }
+}
+
+/// Opens a room for two players who were paired up by the matchmaker, and starts their game straight away
+#[instrument(skip_all, fields(ruleset = %first.ruleset))]
+fn open_match(server_state: &ServerState, first: MatchSeeker, second: MatchSeeker) {
+    let new_game_id = server_state.game_code();
+    let mut game = GameManager::new(new_game_id.clone(), first.effective_day);
+    game.core_game.set_rules(first.ruleset.rules());
+
+    let seekers = [first, second];
+    for seeker in &seekers {
+        game.add_player(
+            Player {
+                socket: Some(seeker.addr),
+            },
+            seeker.player_name.clone(),
+        )
+        .expect("A new room should have space for two players");
+    }
+
+    let players = game.player_list();
+    let board = game.core_game.board.clone();
+    let rules = game.core_game.rules.clone();
+    let existing_game = server_state.add_new_game(&new_game_id, game);
+
+    for (player_index, seeker) in seekers.iter().enumerate() {
+        server_state.attach_player_to_game(&seeker.addr, &new_game_id);
+
+        let claims = Claims::with_custom_claims(
+            PlayerClaims {
+                player_index,
+                room_code: new_game_id.clone(),
+            },
+            Duration::from_days(ROOM_TOKEN_DAYS),
+        );
+        let token = server_state
+            .jwt_key
+            .authenticate(claims)
+            .expect("Claims should be serializable");
+
+        _ = server_state.send_to_player(
+            &seeker.addr,
+            GameMessage::JoinedLobby(
+                player_index as u64,
+                new_game_id.clone(),
+                players.clone(),
+                board.clone(),
+                token,
+            ),
+        );
+        _ = server_state.send_to_player(&seeker.addr, GameMessage::RoomRules(rules.clone()));
+    }
+
+    info!(room = %new_game_id, "Matched two players");
+    start_room(server_state, &mut existing_game.lock());
+}
+
+/// Opens the room for a scheduled game, keeping a seat for each player to join with their token.
+/// Correspondence games start straight away, and others wait in the lobby for the host.
+fn open_scheduled_room(server_state: &ServerState, scheduled: &schedule::Scheduled) -> String {
+    let new_game_id = server_state.game_code();
+    let mut game = GameManager::new(new_game_id.clone(), scheduled.effective_day);
+    game.core_game.set_rules(scheduled.ruleset.rules());
+
+    for name in [Some(&scheduled.host_name), scheduled.guest_name.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        game.add_player(Player { socket: None }, name.clone())
+            .expect("A new room should have space for two players");
+    }
+
+    let correspondence = game.is_correspondence();
+    let existing_game = server_state.add_new_game(&new_game_id, game);
+    if correspondence {
+        let mut game_manager = existing_game.lock();
+        start_room(server_state, &mut game_manager);
+        schedule_clock_checks(server_state, &game_manager.game_msg(0, None));
+    }
+
+    info!(room = %new_game_id, invite_code = %scheduled.invite_code, "Opened a scheduled game");
+    new_game_id
+}
+
+/// Opens a new room with the given player in it, and sends them into its lobby
+fn open_room(
+    server_state: &ServerState,
+    player_addr: SocketAddr,
+    mut player_name: String,
+    effective_day: u32,
+    rules: Option<GameRules>,
+) -> Result<(), ()> {
+    let new_game_id = server_state.game_code();
+    let mut game = GameManager::new(new_game_id.clone(), effective_day);
+    if let Some(rules) = rules {
+        game.core_game.set_rules(rules);
+    }
+
+    if &player_name == "___AUTO___" {
+        player_name = "Player 1".into();
+    }
+
+    game.add_player(
+        Player {
+            socket: Some(player_addr.clone()),
+        },
+        player_name.clone(),
+    )
+    .expect("Failed to add first player to game");
+
+    let color = game.core_game.players[0].color;
+    let board = game.core_game.board.clone();
+    let language = game.core_game.rules.language;
+    let rules = game.core_game.rules.clone();
+
+    server_state.add_new_game(&new_game_id, game);
+    server_state.attach_player_to_game(&player_addr, &new_game_id);
+
+    let claims = Claims::with_custom_claims(
+        PlayerClaims {
+            player_index: 0,
+            room_code: new_game_id.clone(),
+        },
+        Duration::from_days(ROOM_TOKEN_DAYS),
+    );
+    let token = server_state
+        .jwt_key
+        .authenticate(claims)
+        .expect("Claims should be serializable");
+
+    server_state.send_to_player(
+        &player_addr,
+        GameMessage::JoinedLobby(
+            0,
+            new_game_id,
+            vec![LobbyPlayerMessage {
+                name: player_name,
+                color,
+                index: 0,
+                role: RoomRole::Host,
+            }],
+            board,
+            token,
+        ),
+    )?;
+    let warnings = RulesValidator::check(&rules);
+    server_state.send_to_player(&player_addr, GameMessage::RoomRules(rules))?;
+    server_state.send_to_player(&player_addr, GameMessage::RulesWarnings(warnings))?;
+
+    // Only offer a choice of language when the server has dictionaries for more than one
+    let available = server_state.word_db.lock().languages();
+    if available.len() > 1 {
+        server_state.send_to_player(
+            &player_addr,
+            GameMessage::RoomLanguage {
+                language,
+                available,
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Checks a ruleset sent by a player, keeping its blank tiles within what the server allows
+fn playable_rules(mut rules: GameRules) -> Result<GameRules, String> {
+    rules.blank_tiles = rules.blank_tiles.min(MAX_BLANK_TILES);
+    GameRulesBuilder::new(rules).build().map_err(|problems| {
+        let reasons: Vec<_> = problems.iter().map(|p| p.to_string()).collect();
+        format!("These rules can't be played: {}", reasons.join(", "))
+    })
+}
+
+/// Lets everyone in a lobby know what's odd about its rules for the players in it.
+/// Sent whenever the rules or the players change, so the lobby always shows the latest.
+fn send_rules_warnings(server_state: &ServerState, game_manager: &GameManager) {
+    let warnings = RulesValidator::check(&game_manager.core_game.rules);
+    for player in &game_manager.players {
+        let Some(socket) = player.socket else {
+            continue;
+        };
+        _ = server_state.send_to_player(&socket, GameMessage::RulesWarnings(warnings.clone()));
+    }
+}
+
+/// Checks back on the game for when the player to move could run out of time
+fn schedule_clock_checks(server_state: &ServerState, state: &GameStateMessage) {
+    let GameStateMessage {
+        room_code,
+        players,
+        next_player_number,
+        ..
+    } = state;
+    let Some(next_player) = next_player_number else {
+        return;
+    };
+
+    let next_player = &players[*next_player as usize];
+    if let Some(time_remaining) = next_player.time_remaining {
+        debug!(room = %room_code, %time_remaining, "Scheduling a timeout check");
+        let delay_ms = next_player.clock_delay as i128 * 1000;
+        tokio::spawn(check_game_over(
+            room_code.clone(),
+            time_remaining.whole_milliseconds() + delay_ms,
+            server_state.clone(),
+        ));
+    }
+    if let Some(move_deadline) = next_player.move_deadline {
+        debug!(room = %room_code, move_deadline, "Scheduling a move limit check");
+        let now = truncate_core::game::now() as i128;
+        tokio::spawn(check_game_over(
+            room_code.clone(),
+            (move_deadline as i128 - now) * 1000,
+            server_state.clone(),
+        ));
+    }
+}
+
+#[instrument(skip_all, fields(room = %game_id))]
+async fn check_game_over(game_id: String, check_in_ms: i128, server_state: ServerState) {
+    if check_in_ms.is_negative() {
+        return;
+    }
+    tokio::time::sleep(Duration::from_millis(check_in_ms as u64 + 10).into()).await;
+
+    let mut game_map = server_state.games.lock();
+    let Some(existing_game) = game_map.get_mut(&game_id) else {
+        return;
+    };
+    let mut game_manager = existing_game.lock();
+    game_manager.core_game.calculate_game_over(None);
+    game_manager.report_to_webhook();
+    game_manager.queue_spectator_view();
+
+    let words_db = server_state.words();
+
+    if let Some(winner) = game_manager.core_game.winner {
+        info!(winner, "Game ended on time");
+        for (player_index, player) in game_manager.players.iter().enumerate() {
+            let Some(socket) = player.socket else {
+                continue;
+            };
+            let mut end_game_msg = game_manager.game_msg(player_index, Some(&words_db.lock()));
+            // Don't send any of the latest battles or hand changes
+            end_game_msg.changes = vec![];
+            server_state
+                .send_to_player(
+                    &socket,
+                    GameMessage::GameEnd(end_game_msg, winner as u64, game_manager.end_state()),
+                )
+                .unwrap();
+        }
+    }
+}
+
+/// How long a started game can go without a move before it is drawn
+const STALE_GAME_SECS: u64 = 60 * 60 * 6;
+/// How long any room can sit idle before it is removed from the server entirely
+const EXPIRED_ROOM_SECS: u64 = 60 * 60 * 24 * 2;
+
+async fn watch_games(server_state: ServerState) {
+    loop {
+        // Look for stuck games every ten minutes
+        tokio::time::sleep(Duration::from_mins(10).into()).await;
+
+        let current_time = truncate_core::game::now();
+        let mut expired_rooms = vec![];
+
+        let game_map = server_state.games.lock();
+        for (game_id, existing_game) in game_map.iter() {
+            let mut game_manager = existing_game.lock();
+            let idle_for = current_time.saturating_sub(game_manager.last_activity);
+            let in_progress =
+                game_manager.core_game.started_at.is_some() && !game_manager.core_game.is_over();
+
+            // Correspondence games are left to their clocks, however long their players are away
+            let waiting_on_correspondence = in_progress && game_manager.is_correspondence();
+            if idle_for > EXPIRED_ROOM_SECS && !waiting_on_correspondence {
+                expired_rooms.push(game_id.clone());
+                continue;
+            }
+
+            // Correspondence games can sit for a long while between moves without being stale
+            let stale_after = match game_manager.core_game.rules.timing {
+                Timing::PerPlayer {
+                    move_limit: Some(move_limit),
+                    ..
+                } => STALE_GAME_SECS.max(move_limit as u64),
+                _ => STALE_GAME_SECS,
+            };
+            if in_progress && idle_for > stale_after {
+                info!(room = %game_id, idle_for, "Adjudicating idle game");
+                game_manager.core_game.adjudicate(Adjudication::Inactivity);
+                game_manager.report_to_webhook();
+                game_manager.queue_spectator_view();
+
+                for (player, message) in game_manager.drawn_messages(None) {
+                    let Some(socket) = player.socket else {
+                        continue;
+                    };
+                    // Players of stale games have often disconnected, so don't fuss over failures
+                    _ = server_state.send_to_player(&socket, message);
+                }
+            }
+        }
+        drop(game_map);
+
+        if expired_rooms.is_empty() {
+            continue;
+        }
+
+        info!(rooms = ?expired_rooms, "Removing expired rooms");
+        let mut game_map = server_state.games.lock();
+        for game_id in &expired_rooms {
+            game_map.remove(game_id);
+        }
+        drop(game_map);
+
+        server_state
+            .assignments
+            .lock()
+            .retain(|_, game_id| !expired_rooms.contains(game_id));
+
+        let mut word_db = server_state.word_db.lock();
+        for game_id in &expired_rooms {
+            word_db.release_code(game_id);
+        }
+    }
+}
+
+async fn release_spectator_views(server_state: ServerState) {
+    loop {
+        // Spectator delays are in whole seconds, so there's no use checking more often
+        tokio::time::sleep(Duration::from_secs(1).into()).await;
+
+        let current_time = truncate_core::game::now();
+        let game_map = server_state.games.lock();
+        for existing_game in game_map.values() {
+            // Views are released even when nobody is watching, so that the queue can't grow
+            // without bound and anyone who starts watching is shown the latest one
+            let mut game_manager = existing_game.lock();
+            let mut departed = vec![];
+            for (spectator, message) in game_manager.release_spectator_views(current_time) {
+                if server_state.send_to_player(&spectator, message).is_err() {
+                    departed.push(spectator);
+                }
+            }
+            game_manager
+                .spectators
+                .retain(|spectator| !departed.contains(spectator));
+        }
+    }
+}
+
+async fn pair_match_seekers(server_state: ServerState) {
+    loop {
+        // Waiting players become more flexible each second, so check often
+        tokio::time::sleep(Duration::from_secs(2).into()).await;
+
+        let connected: Vec<_> = server_state.peers.lock().keys().copied().collect();
+        let usage = server_state.usage();
+        let full = server_state.capacity.lock().must_wait(&usage);
+
+        let mut matchmaker = server_state.matchmaker.lock();
+        let mut changed = matchmaker.retain_connected(|addr| connected.contains(addr));
+
+        // Matches wait for space on a full server like any other room
+        if full {
+            continue;
+        }
+
+        let pairs = matchmaker.pair(truncate_core::game::now());
+        changed |= !pairs.is_empty();
+        let updates: Vec<_> = matchmaker
+            .seekers()
+            .map(|seeker| {
+                (
+                    seeker.addr,
+                    GameMessage::FindingMatch {
+                        ruleset: seeker.ruleset,
+                        waiting: matchmaker.waiting(seeker.ruleset),
+                    },
+                )
+            })
+            .collect();
+        drop(matchmaker);
 
-    let connection_info = Arc::new(Mutex::new(ConnectionInfo::default()));
+        for (first, second) in pairs {
+            open_match(&server_state, first, second);
+        }
 
-    // TODO: try_for_each from TryStreamExt is quite nice,
-    // look to bring that trait to the other stream places
-    let handle_player_msg = incoming.try_for_each(|msg| {
-        handle_player_msg(msg, addr, server_state.clone(), connection_info.clone())
-    });
+        if changed {
+            for (addr, message) in updates {
+                _ = server_state.send_to_player(&addr, message);
+            }
+        }
+    }
+}
 
-    let messages_to_player = {
-        UnboundedReceiverStream::new(player_rx)
-            .map(|msg| {
-                match &msg {
-                    GameMessage::GameUpdate(GameStateMessage {
-                        room_code,
-                        players,
-                        next_player_number,
-                        ..
-                    })
-                    | GameMessage::GameTimingUpdate(GameStateMessage {
-                        room_code,
-                        players,
-                        next_player_number,
-                        ..
-                    })
-                    | GameMessage::StartedGame(GameStateMessage {
-                        room_code,
-                        players,
-                        next_player_number,
-                        ..
-                    }) => {
-                        if let Some(next_player) = next_player_number {
-                            let next_player = &players[*next_player as usize];
-                            if let Some(time_remaining) = next_player.time_remaining {
-                                println!("Some player has {time_remaining} time left");
-                                tokio::spawn(check_game_over(
-                                    room_code.clone(),
-                                    time_remaining.whole_milliseconds(),
-                                    server_state.clone(),
-                                ));
-                            }
-                        }
-                    }
-                    _ => {}
-                }
+async fn archive_replays(server_state: ServerState) {
+    loop {
+        // Pick up finished games every minute, well before their rooms expire
+        tokio::time::sleep(Duration::from_mins(1).into()).await;
+
+        let (finished, masteries): (Vec<_>, Vec<_>) = server_state
+            .games
+            .lock()
+            .values()
+            .map(|existing_game| {
+                let mut game_manager = existing_game.lock();
+                (
+                    game_manager.take_replay(),
+                    game_manager.take_letter_mastery(),
+                )
+            })
+            .unzip();
 
-                Ok(Message::Text(serde_json::to_string(&msg).unwrap()))
+        for replay in finished.into_iter().flatten() {
+            let room_code = replay.room_code.clone();
+            if let Err(e) = replays::archive_replay(&server_state, replay).await {
+                error!(error = ?e, room = %room_code, "Errored archiving replay: {e}");
+            }
+        }
+
+        for (player_id, letters) in masteries.into_iter().flatten() {
+            if let Err(e) = mastery::record_mastery(&server_state, player_id, &letters).await {
+                error!(error = ?e, %player_id, "Errored recording letter mastery: {e}");
+            }
+        }
+    }
+}
+
+async fn save_correspondence_games(server_state: ServerState) {
+    loop {
+        // Correspondence moves are far apart, so a short wait loses little if the server goes down
+        tokio::time::sleep(Duration::from_secs(5).into()).await;
+
+        let changed: Vec<_> = server_state
+            .games
+            .lock()
+            .values()
+            .filter_map(|existing_game| {
+                let mut game_manager = existing_game.lock();
+                let save = game_manager.take_save()?;
+                Some((save, game_manager.turn_notice()))
             })
-            .forward(outgoing)
-    };
+            .collect();
 
-    pin_mut!(handle_player_msg, messages_to_player);
-    future::select(handle_player_msg, messages_to_player).await;
+        for (save, turn_notice) in changed {
+            let room_code = save.room_code.clone();
+            if let Err(e) = games::save_game(&server_state, save).await {
+                error!(error = ?e, room = %room_code, "Errored saving correspondence game: {e}");
+                continue;
+            }
 
-    let mut peer_map = server_state.peers.lock();
-    peer_map.remove(&addr);
+            if let Some((url, notice)) = webhooks::turn_webhook().zip(turn_notice) {
+                webhooks::notify_turn(url, notice);
+            }
+        }
+    }
 }
 
-async fn check_game_over(game_id: String, check_in_ms: i128, server_state: ServerState) {
-    if check_in_ms.is_negative() {
-        return;
+/// Brings back the correspondence games that were still being played when the server last stopped
+async fn restore_correspondence_games(server_state: &ServerState, pool: &PgPool) {
+    let saved_games = match games::load_unfinished_games(pool).await {
+        Ok(saved_games) => saved_games,
+        Err(e) => {
+            error!(error = ?e, "Errored loading correspondence games: {e}");
+            return;
+        }
+    };
+
+    for saved in saved_games {
+        let room_code = saved.room_code.clone();
+        let restored = {
+            let mut word_db = server_state.word_db.lock();
+            word_db.reserve_code(&room_code);
+            GameManager::restore(saved, &word_db)
+        };
+
+        match restored {
+            Ok(game_manager) => {
+                info!(room = %room_code, turns = game_manager.core_game.turn_count, "Restored correspondence game");
+                schedule_clock_checks(server_state, &game_manager.game_msg(0, None));
+                server_state.add_new_game(&room_code, game_manager);
+            }
+            Err(e) => {
+                error!(room = %room_code, error = %e, "Couldn't restore correspondence game");
+            }
+        }
     }
-    tokio::time::sleep(Duration::from_millis(check_in_ms as u64 + 10).into()).await;
+}
 
-    let mut game_map = server_state.games.lock();
-    let Some(existing_game) = game_map.get_mut(&game_id) else {
+async fn open_scheduled_games(server_state: ServerState) {
+    let Some(pool) = server_state.truncate_db.clone() else {
         return;
     };
-    let mut game_manager = existing_game.lock();
-    game_manager.core_game.calculate_game_over(None);
 
-    let words_db = server_state.words();
+    loop {
+        // Players are told when their games start to the minute, so check a little more often than that
+        tokio::time::sleep(Duration::from_secs(30).into()).await;
+        let current_time = truncate_core::game::now();
 
-    if let Some(winner) = game_manager.core_game.winner {
-        for (player_index, player) in game_manager.players.iter().enumerate() {
-            let Some(socket) = player.socket else {
+        match schedule::take_due_reminders(&pool, current_time + SCHEDULE_REMINDER_SECS).await {
+            Ok(due) => {
+                if let Some(url) = webhooks::turn_webhook() {
+                    for scheduled in due {
+                        let reminder = webhooks::ScheduleReminder {
+                            link: webhooks::room_link(&format!(
+                                "SCHEDULED:{}",
+                                scheduled.invite_code
+                            )),
+                            invite_code: scheduled.invite_code,
+                            players: [Some(scheduled.host_name), scheduled.guest_name]
+                                .into_iter()
+                                .flatten()
+                                .collect(),
+                            starts_at: scheduled.starts_at,
+                            ruleset: scheduled.ruleset.to_string(),
+                            sent_at: current_time,
+                        };
+                        webhooks::remind_schedule(url.clone(), reminder);
+                    }
+                }
+            }
+            Err(e) => error!(error = ?e, "Errored loading scheduled game reminders: {e}"),
+        }
+
+        let due = match schedule::load_due_games(&pool, current_time).await {
+            Ok(due) => due,
+            Err(e) => {
+                error!(error = ?e, "Errored loading scheduled games: {e}");
                 continue;
-            };
-            let mut end_game_msg = game_manager.game_msg(player_index, Some(&words_db.lock()));
-            // Don't send any of the latest battles or hand changes
-            end_game_msg.changes = vec![];
-            server_state
-                .send_to_player(&socket, GameMessage::GameEnd(end_game_msg, winner as u64))
-                .unwrap();
+            }
+        };
+        for scheduled in due {
+            let room_code = open_scheduled_room(&server_state, &scheduled);
+            if let Err(e) = schedule::mark_opened(&pool, &scheduled.invite_code, &room_code).await {
+                error!(error = ?e, room = %room_code, "Errored recording scheduled game's room: {e}");
+            }
+        }
+    }
+}
+
+async fn publish_rooms(server_state: ServerState) {
+    loop {
+        // Rooms are listed from the database by the admin commands, so keep it roughly current
+        tokio::time::sleep(Duration::from_mins(1).into()).await;
+
+        let current_time = truncate_core::game::now();
+        let open_rooms: Vec<_> = server_state
+            .games
+            .lock()
+            .values()
+            .map(|existing_game| existing_game.lock().summary(current_time))
+            .collect();
+
+        if let Err(e) = rooms::publish_rooms(&server_state, open_rooms).await {
+            error!(error = ?e, "Errored publishing rooms: {e}");
+        }
+    }
+}
+
+async fn prune_replays(server_state: ServerState) {
+    loop {
+        match replays::prune_replays(&server_state, server_state.replay_retention).await {
+            Ok(report) => {
+                let compression_ratio = if report.stored_bytes > 0 {
+                    report.raw_bytes as f64 / report.stored_bytes as f64
+                } else {
+                    0.0
+                };
+                info!(
+                    pruned_rated = report.pruned_rated,
+                    pruned_casual = report.pruned_casual,
+                    replays = report.replays,
+                    stored_bytes = report.stored_bytes,
+                    raw_bytes = report.raw_bytes,
+                    compression_ratio,
+                    "Pruned replay archive"
+                );
+            }
+            Err(e) => error!(error = ?e, "Errored pruning replays: {e}"),
         }
+
+        // Retention is counted in days, so once an hour is plenty
+        tokio::time::sleep(Duration::from_hours(1).into()).await;
     }
 }
 
@@ -1006,66 +3282,234 @@ async fn ping_peers(server_state: ServerState) {
     }
 }
 
+async fn admit_queued_players(server_state: ServerState) {
+    let tick = std::time::Duration::from_secs(5);
+    loop {
+        // Let waiting players in every five seconds, noting how late we wake up
+        let slept_at = std::time::Instant::now();
+        tokio::time::sleep(tick).await;
+        let lag = slept_at.elapsed().saturating_sub(tick);
+
+        let connected: Vec<_> = server_state.peers.lock().keys().copied().collect();
+        let mut usage = server_state.usage();
+
+        let mut capacity = server_state.capacity.lock();
+        capacity.record_lag(lag);
+        capacity.retain_connected(|addr| connected.contains(addr));
+
+        let mut admitted = vec![];
+        while let Some(queued) = capacity.admit_next(&usage) {
+            // Each admitted player opens a room of their own
+            usage.rooms += 1;
+            usage.players += 1;
+            admitted.push(queued);
+        }
+
+        let still_queued: Vec<_> = capacity
+            .queued()
+            .map(|(position, queued)| {
+                (
+                    queued.addr,
+                    GameMessage::Queued {
+                        position,
+                        estimated_wait_secs: capacity.estimated_wait_secs(position),
+                    },
+                )
+            })
+            .collect();
+        let queued = still_queued.len();
+        let lag_ms = capacity.lag_ms();
+        drop(capacity);
+
+        for queued in admitted {
+            info!(peer = %queued.addr, "Admitting player from the queue");
+            // They may have left since we last checked, in which case the room will expire
+            _ = open_room(
+                &server_state,
+                queued.addr,
+                queued.player_name,
+                queued.effective_day,
+                queued.rules,
+            );
+        }
+
+        for (addr, message) in still_queued {
+            _ = server_state.send_to_player(&addr, message);
+        }
+
+        debug!(
+            rooms = usage.rooms,
+            players = usage.players,
+            queued,
+            lag_ms,
+            "Server capacity"
+        );
+    }
+}
+
+async fn connect_database(db_url: &str) -> PgPool {
+    info!("Initializing database shtuff");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(db_url)
+        .await
+        .expect("Database should be alive");
+
+    info!("Running database migrations");
+    sqlx::migrate!("./migrations")
+        .set_ignore_missing(true)
+        .run(&pool)
+        .await
+        .expect("Database migrations should succeed");
+
+    pool
+}
+
+/// Moves all persisted state between deployments, e.g. when changing hosting providers:
+/// `truncate_server export-snapshot truncate.json` on the old instance,
+/// then `truncate_server import-snapshot truncate.json` against the new, empty, database.
+async fn run_snapshot_command(command: &str, path: Option<String>) {
+    let Some(path) = path.map(std::path::PathBuf::from) else {
+        error!("Usage: truncate_server {command} <snapshot path>");
+        std::process::exit(1);
+    };
+    let Ok(db_url) = env::var("DATABASE_URL") else {
+        error!("{command} needs DATABASE_URL to be set");
+        std::process::exit(1);
+    };
+    let pool = connect_database(&db_url).await;
+
+    let result = if command == "export-snapshot" {
+        match snapshot::export_snapshot(&pool).await {
+            Ok(exported) => exported.write_to(&path),
+            Err(e) => Err(e),
+        }
+    } else {
+        match snapshot::Snapshot::read_from(&path) {
+            Ok(loaded) => snapshot::import_snapshot(&pool, &loaded).await,
+            Err(e) => Err(e),
+        }
+    };
+
+    match result {
+        Ok(()) => info!(path = %path.display(), "Finished {command}"),
+        Err(e) => {
+            error!(error = ?e, path = %path.display(), "{command} failed: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), IoError> {
-    println!("Starting up...");
+    // Load from env file if one exists (local dev).
+    _ = dotenvy::dotenv();
+
+    // Log levels can be tuned with RUST_LOG, e.g. `RUST_LOG=truncate_server=debug`
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    info!("Starting up...");
 
     let addr = env::args()
         .nth(1)
         .unwrap_or_else(|| "0.0.0.0:8080".to_string());
 
-    // Load from env file if one exists (local dev).
-    _ = dotenvy::dotenv();
+    if matches!(addr.as_str(), "export-snapshot" | "import-snapshot") {
+        run_snapshot_command(&addr, env::args().nth(2)).await;
+        return Ok(());
+    }
+    if addr == "admin" {
+        admin::run_admin_command(env::args().skip(2).collect()).await;
+        return Ok(());
+    }
+    if addr == "build-opening-book" {
+        run_opening_book_command(&addr, env::args().nth(2)).await;
+        return Ok(());
+    }
 
     let jwt_key = if let Some(s) = env::var("SIGNING_SECRET").ok() {
-        println!("Loading the signing secret for JWTs");
+        info!("Loading the signing secret for JWTs");
         HS256Key::from_bytes(&hex::decode(s).expect("Signing secret should be valid hex"))
     } else {
         let k = HS256Key::generate();
-        println!("Running without a dedicated secret — generating a new one:");
-        println!("{}", hex::encode(k.to_bytes()));
+        warn!(
+            secret = %hex::encode(k.to_bytes()),
+            "Running without a dedicated secret — generating a new one"
+        );
         k
     };
 
+    let capacity_limits = CapacityLimits::from_env();
+    info!(
+        max_rooms = ?capacity_limits.max_rooms,
+        max_players = ?capacity_limits.max_players,
+        "Loaded capacity limits"
+    );
+
+    let rollout = experiments::rollout_from_env();
+    info!(?rollout, "Loaded experiment rollout");
+
+    let replay_retention = RetentionPolicy::from_env();
+    info!(
+        rated_days = ?replay_retention.rated_days,
+        casual_days = ?replay_retention.casual_days,
+        "Loaded replay retention policy"
+    );
+
+    let oauth_apps = OAuthApps::from_env();
+    info!(providers = ?oauth_apps.enabled(), "Loaded sign in providers");
+
     let mut server_state = ServerState {
         games: Arc::new(Mutex::new(HashMap::new())),
         assignments: Arc::new(Mutex::new(HashMap::new())),
         peers: Arc::new(Mutex::new(HashMap::new())),
         word_db: Arc::new(Mutex::new(read_defs())),
         nonces: Arc::new(Mutex::new(NonceTracker::default())),
+        rushes: Arc::new(Mutex::new(RushTracker::default())),
+        words_of_the_day: Arc::new(Mutex::new(HashMap::new())),
+        capacity: Arc::new(Mutex::new(Capacity::new(capacity_limits))),
+        matchmaker: Arc::new(Mutex::new(Matchmaker::default())),
         truncate_db: None,
         jwt_key,
+        admin_key: env::var("ADMIN_KEY").ok().filter(|key| !key.is_empty()),
+        rollout,
+        replay_retention,
+        oauth_apps,
+        sign_ins: Arc::new(Mutex::new(PendingSignIns::default())),
     };
 
     if let Ok(db_url) = env::var("DATABASE_URL") {
-        println!("Initializing database shtuff");
-
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(&db_url)
-            .await
-            .expect("Database should be alive");
-
-        println!("Running database migrations");
-        sqlx::migrate!("./migrations")
-            .set_ignore_missing(true)
-            .run(&pool)
-            .await
-            .expect("Database migrations should succeed");
-
+        let pool = connect_database(&db_url).await;
+        restore_correspondence_games(&server_state, &pool).await;
         server_state.truncate_db = Some(pool);
 
-        println!("Database is ready.");
+        info!("Database is ready.");
     } else {
-        println!("Running the Truncate server without a database connection.");
+        warn!("Running the Truncate server without a database connection.");
     }
 
     let try_socket = TcpListener::bind(&addr).await;
     let listener = try_socket.expect("Failed to bind");
-    println!("Listening on: {}", addr);
+    info!("Listening on: {}", addr);
 
     tokio::spawn(ping_peers(server_state.clone()));
+    tokio::spawn(admit_queued_players(server_state.clone()));
     tokio::spawn(clean_nonces(server_state.clone()));
+    tokio::spawn(watch_games(server_state.clone()));
+    tokio::spawn(release_spectator_views(server_state.clone()));
+    tokio::spawn(pair_match_seekers(server_state.clone()));
+    if server_state.truncate_db.is_some() {
+        tokio::spawn(archive_replays(server_state.clone()));
+        tokio::spawn(save_correspondence_games(server_state.clone()));
+        tokio::spawn(prune_replays(server_state.clone()));
+        tokio::spawn(publish_rooms(server_state.clone()));
+        tokio::spawn(open_scheduled_games(server_state.clone()));
+    }
 
     std::thread::spawn(move || loop {
         std::thread::sleep(std::time::Duration::from_secs(10));
@@ -1074,12 +3518,15 @@ async fn main() -> Result<(), IoError> {
             continue;
         }
 
-        println!("{} deadlocks detected", deadlocks.len());
+        error!("{} deadlocks detected", deadlocks.len());
         for (i, threads) in deadlocks.iter().enumerate() {
-            println!("Deadlock #{}", i);
             for t in threads {
-                println!("Thread Id {:#?}", t.thread_id());
-                println!("{:#?}", t.backtrace());
+                error!(
+                    deadlock = i,
+                    thread_id = ?t.thread_id(),
+                    backtrace = ?t.backtrace(),
+                    "Deadlocked thread"
+                );
             }
         }
     });
@@ -1090,3 +3537,472 @@ async fn main() -> Result<(), IoError> {
 
     Ok(())
 }
+
+/// Placements seen in fewer games than this are left out of the opening book, as they may have won by luck
+const OPENING_BOOK_MIN_GAMES: u32 = 5;
+/// Placements have to win at least this share of their games to make it into the opening book
+const OPENING_BOOK_MIN_WIN_RATE: f32 = 0.55;
+
+/// Regenerates the NPC's opening book from the replay archive, keeping the comments at the top of the book:
+/// `truncate_server build-opening-book ../truncate_core/src/npc/opening_book.txt`
+async fn run_opening_book_command(command: &str, path: Option<String>) {
+    let Some(path) = path.map(std::path::PathBuf::from) else {
+        error!("Usage: truncate_server {command} <opening book path>");
+        std::process::exit(1);
+    };
+    let Ok(db_url) = env::var("DATABASE_URL") else {
+        error!("{command} needs DATABASE_URL to be set");
+        std::process::exit(1);
+    };
+    let pool = connect_database(&db_url).await;
+
+    let (builder, unreadable) = match replays::mine_openings(&pool).await {
+        Ok(mined) => mined,
+        Err(e) => {
+            error!(error = ?e, "{command} failed: {e}");
+            std::process::exit(1);
+        }
+    };
+    let games = builder.games();
+    let book = builder.build(OPENING_BOOK_MIN_GAMES, OPENING_BOOK_MIN_WIN_RATE);
+
+    let header: String = std::fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .take_while(|line| line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    if let Err(e) = std::fs::write(&path, header + &book.to_text()) {
+        error!(error = ?e, path = %path.display(), "{command} failed: {e}");
+        std::process::exit(1);
+    }
+
+    info!(
+        path = %path.display(),
+        games,
+        unreadable,
+        neighbourhoods = book.neighbourhoods(),
+        "Finished {command}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use tokio::sync::mpsc::UnboundedReceiver;
+    use truncate_core::{
+        board::Coordinate,
+        dictionary::WordDict,
+        messages::{ArbiterAction, ChatChannel},
+        rules::Visibility,
+        rush::PuzzleRush,
+    };
+
+    use crate::definitions::LoadedLanguage;
+
+    use super::*;
+
+    /// A server with no dictionary or database, which is enough for handling messages that fail early
+    fn test_server() -> ServerState {
+        ServerState {
+            games: Arc::new(Mutex::new(HashMap::new())),
+            assignments: Arc::new(Mutex::new(HashMap::new())),
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            word_db: Arc::new(Mutex::new(WordDB {
+                conn: None,
+                valid_words: WordDict::new(),
+                appeal_words: WordDict::new(),
+                languages: HashMap::new(),
+                packs: HashMap::new(),
+                room_codes: vec![],
+                allocated_room_codes: HashSet::new(),
+            })),
+            nonces: Arc::new(Mutex::new(NonceTracker::default())),
+            rushes: Arc::new(Mutex::new(RushTracker::default())),
+            words_of_the_day: Arc::new(Mutex::new(HashMap::new())),
+            capacity: Arc::new(Mutex::new(Capacity::new(CapacityLimits::default()))),
+            matchmaker: Arc::new(Mutex::new(Matchmaker::default())),
+            truncate_db: None,
+            jwt_key: HS256Key::generate(),
+            admin_key: None,
+            rollout: Rollout::default(),
+            replay_retention: RetentionPolicy::default(),
+            oauth_apps: OAuthApps::default(),
+            sign_ins: Arc::new(Mutex::new(PendingSignIns::default())),
+        }
+    }
+
+    /// Opens a connection to the server, returning what the server sends down it
+    fn connect(
+        server_state: &ServerState,
+        port: u16,
+    ) -> (SocketAddr, UnboundedReceiver<GameMessage>) {
+        let addr: SocketAddr = ([127, 0, 0, 1], port).into();
+        let (tx, rx) = mpsc::unbounded_channel();
+        server_state.peers.lock().insert(addr, tx);
+        (addr, rx)
+    }
+
+    async fn send(server_state: &ServerState, addr: SocketAddr, msg: PlayerMessage) {
+        let msg = Message::Text(serde_json::to_string(&msg).unwrap());
+        let connection_info = Arc::new(Mutex::new(ConnectionInfo::default()));
+        handle_player_msg(msg, addr, server_state.clone(), connection_info)
+            .await
+            .unwrap();
+    }
+
+    /// Everything the server has sent to a connection so far
+    fn received(rx: &mut UnboundedReceiver<GameMessage>) -> Vec<GameMessage> {
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+
+    fn is_error(msg: &GameMessage) -> bool {
+        matches!(msg, GameMessage::GenericError(_))
+    }
+
+    #[tokio::test]
+    async fn exchanging_outside_a_room_is_refused() {
+        let server_state = test_server();
+        let (addr, mut rx) = connect(&server_state, 9002);
+
+        send(&server_state, addr, PlayerMessage::Exchange('A')).await;
+        assert!(received(&mut rx).iter().any(is_error));
+    }
+
+    #[tokio::test]
+    async fn taking_a_mulligan_outside_a_room_is_refused() {
+        let server_state = test_server();
+        let (addr, mut rx) = connect(&server_state, 9003);
+
+        send(&server_state, addr, PlayerMessage::Mulligan).await;
+        assert!(received(&mut rx).iter().any(is_error));
+    }
+
+    #[tokio::test]
+    async fn spectators_chat_amongst_themselves() {
+        let server_state = test_server();
+        server_state.add_new_game(&"watch".to_string(), GameManager::new("watch".into(), 0));
+        let (first, mut first_rx) = connect(&server_state, 9005);
+        let (second, mut second_rx) = connect(&server_state, 9006);
+
+        for spectator in [first, second] {
+            send(
+                &server_state,
+                spectator,
+                PlayerMessage::SpectateGame("WATCH".into()),
+            )
+            .await;
+        }
+
+        let chat = |channel| PlayerMessage::Chat {
+            channel,
+            text: "good move".into(),
+        };
+        let is_chat = |msg: &GameMessage| matches!(msg, GameMessage::Chat { .. });
+
+        send(&server_state, first, chat(ChatChannel::Players)).await;
+        assert!(!received(&mut second_rx).iter().any(is_chat));
+
+        send(&server_state, first, chat(ChatChannel::Observers)).await;
+        assert!(received(&mut first_rx).iter().any(is_chat));
+        assert!(received(&mut second_rx).iter().any(is_chat));
+    }
+
+    #[tokio::test]
+    async fn kicked_players_cant_rejoin_from_a_new_connection() {
+        let server_state = test_server();
+        server_state.add_new_game(&"kick".to_string(), GameManager::new("kick".into(), 0));
+        let (host, _host_rx) = connect(&server_state, 9008);
+        let (kicked, _kicked_rx) = connect(&server_state, 9009);
+        let join = |name: &str| PlayerMessage::JoinGame("KICK".into(), name.into(), None);
+
+        send(&server_state, host, join("Host")).await;
+        send(&server_state, kicked, join("Bob")).await;
+        send(&server_state, host, PlayerMessage::KickPlayer(1)).await;
+
+        let (reconnected, mut reconnected_rx) = connect(&server_state, 9010);
+        send(&server_state, reconnected, join("bob")).await;
+        assert!(received(&mut reconnected_rx).iter().any(is_error));
+
+        let (someone_else, mut someone_else_rx) = connect(&server_state, 9011);
+        send(&server_state, someone_else, join("Carol")).await;
+        assert!(!received(&mut someone_else_rx).iter().any(is_error));
+    }
+
+    #[tokio::test]
+    async fn frozen_games_refuse_moves_and_pauses() {
+        let mut server_state = test_server();
+        server_state.admin_key = Some("arbiter".into());
+        server_state.add_new_game(&"freeze".to_string(), GameManager::new("freeze".into(), 0));
+        let (host, mut host_rx) = connect(&server_state, 9013);
+        let (guest, mut guest_rx) = connect(&server_state, 9014);
+        let (arbiter, mut arbiter_rx) = connect(&server_state, 9015);
+        let join = |name: &str| PlayerMessage::JoinGame("FREEZE".into(), name.into(), None);
+
+        send(&server_state, host, join("Host")).await;
+        send(&server_state, guest, join("Guest")).await;
+        send(&server_state, host, PlayerMessage::StartGame).await;
+        let arbitrate = |action| PlayerMessage::Arbitrate {
+            admin_key: "arbiter".into(),
+            room_code: "FREEZE".into(),
+            action,
+            reason: "Checking a dispute".into(),
+        };
+        send(&server_state, arbiter, arbitrate(ArbiterAction::Freeze)).await;
+        assert!(!received(&mut arbiter_rx).iter().any(is_error));
+        received(&mut host_rx);
+        received(&mut guest_rx);
+
+        let is_refusal = |msg: &GameMessage| matches!(msg, GameMessage::GameError(..));
+        let game = server_state.get_game_by_code(&"FREEZE".into()).unwrap();
+        for player in [host, guest] {
+            send(&server_state, player, PlayerMessage::Unpause).await;
+            send(&server_state, player, PlayerMessage::Pause).await;
+        }
+        for rx in [&mut host_rx, &mut guest_rx] {
+            let messages = received(rx);
+            assert_eq!(messages.len(), 2);
+            assert!(messages.iter().all(is_refusal));
+        }
+        assert!(game.lock().core_game.paused);
+
+        let next_player = game.lock().core_game.next_player.unwrap();
+        let (mover, mover_rx) = match next_player {
+            0 => (host, &mut host_rx),
+            _ => (guest, &mut guest_rx),
+        };
+        let turns = game.lock().core_game.turn_count;
+        let placed = PlayerMessage::Place(Coordinate::new(1, 1), 'A');
+        send(&server_state, mover, placed).await;
+        assert!(received(mover_rx).iter().all(is_refusal));
+        assert_eq!(game.lock().core_game.turn_count, turns);
+
+        // Once the arbiter resumes the game, the players can pause it again
+        send(&server_state, arbiter, arbitrate(ArbiterAction::Resume)).await;
+        assert!(!game.lock().core_game.paused);
+        send(&server_state, host, PlayerMessage::Pause).await;
+        assert!(!received(&mut host_rx).iter().any(is_refusal));
+        assert!(game.lock().core_game.paused);
+    }
+
+    #[tokio::test]
+    async fn rematches_keep_the_room_setup() {
+        let server_state = test_server();
+        server_state.add_new_game(&"again".to_string(), GameManager::new("again".into(), 0));
+        server_state.word_db.lock().room_codes = vec!["rematch".into()];
+        let (host, mut host_rx) = connect(&server_state, 9016);
+        let (guest, _guest_rx) = connect(&server_state, 9017);
+        let join = |name: &str| PlayerMessage::JoinGame("AGAIN".into(), name.into(), None);
+
+        send(&server_state, host, join("Host")).await;
+        send(&server_state, guest, join("Guest")).await;
+        let first = server_state.get_game_by_code(&"AGAIN".into()).unwrap();
+        {
+            let mut game_manager = first.lock();
+            let mut rules = game_manager.core_game.rules.clone();
+            rules.battle_rules.spelling = Spelling::Lenient;
+            rules.blank_tiles = 2;
+            rules.hand_swaps = HandSwaps::Every { turns: 5 };
+            game_manager.core_game.set_language(Language::Spanish);
+            game_manager.core_game.set_rules(rules);
+            game_manager.spectator_delay_secs = 30;
+        }
+        let rules = first.lock().core_game.rules.clone();
+
+        send(&server_state, host, PlayerMessage::StartGame).await;
+        send(&server_state, guest, PlayerMessage::Resign).await;
+        received(&mut host_rx);
+        send(&server_state, host, PlayerMessage::Rematch).await;
+
+        let rematch = server_state.get_game_by_player(&host).unwrap();
+        assert!(!Arc::ptr_eq(&rematch, &first));
+        assert_eq!(rematch.lock().core_game.rules, rules);
+        assert_eq!(rematch.lock().spectator_delay_secs, 30);
+        assert!(received(&mut host_rx)
+            .iter()
+            .any(|msg| matches!(msg, GameMessage::RoomRules(sent) if *sent == rules)));
+    }
+
+    #[tokio::test]
+    async fn fog_games_are_hidden_from_spectators_until_they_end() {
+        let server_state = test_server();
+        server_state.add_new_game(&"fog".to_string(), GameManager::new("fog".into(), 0));
+        let (host, _host_rx) = connect(&server_state, 9018);
+        let (guest, _guest_rx) = connect(&server_state, 9019);
+        let (early, mut early_rx) = connect(&server_state, 9020);
+        let join = |name: &str| PlayerMessage::JoinGame("FOG".into(), name.into(), None);
+        let spectate = PlayerMessage::SpectateGame("FOG".into());
+
+        send(&server_state, host, join("Host")).await;
+        send(&server_state, guest, join("Guest")).await;
+        // Spectators can sit in the lobby before the room is switched over to fog
+        send(&server_state, early, spectate.clone()).await;
+        let game = server_state.get_game_by_code(&"FOG".into()).unwrap();
+        let mut rules = game.lock().core_game.rules.clone();
+        rules.visibility = Visibility::LandFog;
+        game.lock().core_game.set_rules(rules);
+        send(&server_state, host, PlayerMessage::StartGame).await;
+
+        // Either player could otherwise watch from a second connection to see past their fog
+        let (second_connection, mut second_rx) = connect(&server_state, 9021);
+        send(&server_state, second_connection, spectate.clone()).await;
+        assert!(received(&mut second_rx).iter().any(is_error));
+        assert!(game.lock().release_spectator_views(u64::MAX).is_empty());
+        assert!(received(&mut early_rx).iter().all(is_error));
+
+        send(&server_state, guest, PlayerMessage::Resign).await;
+        assert_eq!(game.lock().release_spectator_views(u64::MAX).len(), 1);
+        send(&server_state, second_connection, spectate).await;
+        assert!(!received(&mut second_rx).iter().any(is_error));
+    }
+
+    #[tokio::test]
+    async fn puzzle_rushes_are_seeded_by_the_server() {
+        let server_state = test_server();
+        let (addr, mut rx) = connect(&server_state, 9007);
+        let token = accounts::get_player_token(&server_state, Uuid::new_v4()).token();
+        let persist = |seed| PlayerMessage::PersistPuzzleRush {
+            player_token: token.clone(),
+            day: 1,
+            rush: PuzzleRush::new(seed),
+            language: Language::English,
+        };
+
+        send(&server_state, addr, persist(1)).await;
+        assert!(received(&mut rx).iter().any(is_error));
+
+        send(
+            &server_state,
+            addr,
+            PlayerMessage::StartPuzzleRush(token.clone()),
+        )
+        .await;
+        let seed = received(&mut rx)
+            .into_iter()
+            .find_map(|msg| match msg {
+                GameMessage::PuzzleRushStarted(seed) => Some(seed),
+                _ => None,
+            })
+            .expect("The server should hand out a seed");
+
+        send(&server_state, addr, persist(seed)).await;
+        assert!(!received(&mut rx).iter().any(is_error));
+
+        // Each rush can only be submitted once
+        send(&server_state, addr, persist(seed)).await;
+        assert!(received(&mut rx).iter().any(is_error));
+    }
+
+    #[tokio::test]
+    async fn languages_are_downloaded_with_their_daily_puzzles() {
+        let server_state = test_server();
+        server_state.word_db.lock().languages.insert(
+            Language::Spanish,
+            LoadedLanguage {
+                words: "hola\n".into(),
+                dictionary: WordDict::new(),
+                seed_notes: Some("notes: {}\n".into()),
+            },
+        );
+        let (addr, mut rx) = connect(&server_state, 9012);
+
+        for (language, has_contents) in [(Language::Spanish, true), (Language::French, false)] {
+            send(
+                &server_state,
+                addr,
+                PlayerMessage::DownloadLanguage(language),
+            )
+            .await;
+            let contents = received(&mut rx)
+                .into_iter()
+                .find_map(|msg| match msg {
+                    GameMessage::LanguageContents {
+                        words, seed_notes, ..
+                    } => Some((words, seed_notes)),
+                    _ => None,
+                })
+                .expect("The server should answer for any language");
+            assert_eq!(contents.0.is_some(), has_contents);
+            assert_eq!(contents.1.is_some(), has_contents);
+        }
+    }
+
+    #[sqlx::test]
+    async fn revoked_tokens_are_refused(pool: PgPool) {
+        let mut server_state = test_server();
+        server_state.truncate_db = Some(pool.clone());
+        let (addr, mut rx) = connect(&server_state, 9004);
+
+        let player_id =
+            accounts::create_player(&server_state, 800, 600, String::new(), String::new())
+                .await
+                .unwrap();
+        let token = accounts::get_player_token(&server_state, player_id).token();
+
+        send(
+            &server_state,
+            addr,
+            PlayerMessage::RequestStats(token.clone(), Language::English),
+        )
+        .await;
+        assert!(received(&mut rx)
+            .iter()
+            .any(|msg| matches!(msg, GameMessage::DailyStats(_))));
+
+        accounts::revoke_tokens(&pool, player_id).await.unwrap();
+        send(
+            &server_state,
+            addr,
+            PlayerMessage::RequestStats(token, Language::English),
+        )
+        .await;
+        assert!(received(&mut rx).iter().any(is_error));
+    }
+
+    #[sqlx::test]
+    async fn puzzle_rush_leaderboards_are_kept_per_language(pool: PgPool) {
+        let mut server_state = test_server();
+        server_state.truncate_db = Some(pool.clone());
+
+        let player_id =
+            accounts::create_player(&server_state, 800, 600, String::new(), String::new())
+                .await
+                .unwrap();
+        let authed = accounts::auth_player_token(
+            &server_state,
+            accounts::get_player_token(&server_state, player_id).token(),
+        )
+        .await
+        .unwrap();
+
+        for language in [Language::English, Language::Spanish] {
+            rush::persist_run(
+                &server_state,
+                authed.clone(),
+                1,
+                &PuzzleRush::new(1),
+                language,
+            )
+            .await
+            .unwrap();
+        }
+
+        for language in [Language::English, Language::Spanish] {
+            let leaderboard =
+                rush::load_leaderboard(&server_state, Some(authed.clone()), 1, language)
+                    .await
+                    .unwrap();
+            assert_eq!(
+                leaderboard.top.len(),
+                1,
+                "{language} should have its own run"
+            );
+        }
+        let french = rush::load_leaderboard(&server_state, Some(authed), 1, Language::French)
+            .await
+            .unwrap();
+        assert!(french.top.is_empty());
+        assert_eq!(french.personal_best, None);
+    }
+}
@@ -0,0 +1,146 @@
+use std::net::SocketAddr;
+
+use truncate_core::rules::RulesPreset;
+
+/// Rungs of the bot ladder apart that two players can be matched at straight away
+const BASE_SKILL_GAP: u32 = 1;
+/// Every this many seconds of waiting lets a player be matched one more rung away
+const SKILL_GAP_WIDEN_SECS: u64 = 15;
+/// Combined latency that two players can be matched at straight away
+const BASE_LATENCY_MS: u64 = 300;
+/// How much more combined latency is accepted for each second of waiting
+const LATENCY_WIDEN_MS_PER_SEC: u64 = 10;
+
+/// A player waiting to be paired with a random opponent
+#[derive(Debug, Clone)]
+pub struct MatchSeeker {
+    pub addr: SocketAddr,
+    pub player_name: String,
+    pub effective_day: u32,
+    pub ruleset: RulesPreset,
+    /// Truncate has no ratings yet, so logged in players are placed
+    /// by how many rungs of the bot ladder they've beaten
+    pub skill: Option<u32>,
+    /// Round trip to the server, as measured by the player's client
+    pub latency_ms: Option<u64>,
+    pub queued_at: u64,
+}
+
+impl MatchSeeker {
+    /// Whether this player will accept an opponent, which gets easier the longer they've waited
+    fn accepts(&self, other: &MatchSeeker, now: u64) -> bool {
+        let waited = now.saturating_sub(self.queued_at);
+
+        let skill_gap = BASE_SKILL_GAP + (waited / SKILL_GAP_WIDEN_SECS) as u32;
+        let close_in_skill = match (self.skill, other.skill) {
+            (Some(a), Some(b)) => a.abs_diff(b) <= skill_gap,
+            _ => true,
+        };
+
+        let latency_limit = BASE_LATENCY_MS + waited * LATENCY_WIDEN_MS_PER_SEC;
+        let close_in_latency = match (self.latency_ms, other.latency_ms) {
+            (Some(a), Some(b)) => a + b <= latency_limit,
+            _ => true,
+        };
+
+        close_in_skill && close_in_latency
+    }
+
+    /// How poor a pairing is, with unknown skills and latencies counting as a good fit
+    fn distance(&self, other: &MatchSeeker) -> u64 {
+        let skill = match (self.skill, other.skill) {
+            (Some(a), Some(b)) => a.abs_diff(b) as u64,
+            _ => 0,
+        };
+        let latency = match (self.latency_ms, other.latency_ms) {
+            (Some(a), Some(b)) => a + b,
+            _ => 0,
+        };
+        // A rung of the ladder matters more than a few milliseconds
+        skill * 100 + latency
+    }
+}
+
+/// Pairs up players looking for a random opponent with the same ruleset
+#[derive(Debug, Default)]
+pub struct Matchmaker {
+    /// Players waiting for an opponent, in the order they arrived
+    seekers: Vec<MatchSeeker>,
+}
+
+impl Matchmaker {
+    /// Adds a player to the queue, replacing any earlier search of theirs,
+    /// and returns how many players are now looking for a game with their ruleset
+    pub fn enqueue(&mut self, seeker: MatchSeeker) -> usize {
+        self.cancel(seeker.addr);
+        let ruleset = seeker.ruleset;
+        self.seekers.push(seeker);
+        self.waiting(ruleset)
+    }
+
+    /// Takes a player out of the queue, returning whether they were in it
+    pub fn cancel(&mut self, addr: SocketAddr) -> bool {
+        let before = self.seekers.len();
+        self.seekers.retain(|s| s.addr != addr);
+        self.seekers.len() != before
+    }
+
+    pub fn waiting(&self, ruleset: RulesPreset) -> usize {
+        self.seekers.iter().filter(|s| s.ruleset == ruleset).count()
+    }
+
+    pub fn seekers(&self) -> impl Iterator<Item = &MatchSeeker> {
+        self.seekers.iter()
+    }
+
+    /// Drops anyone who has disconnected while waiting, returning whether anyone left
+    pub fn retain_connected(&mut self, is_connected: impl Fn(&SocketAddr) -> bool) -> bool {
+        let before = self.seekers.len();
+        self.seekers.retain(|s| is_connected(&s.addr));
+        self.seekers.len() != before
+    }
+
+    /// Pairs off everyone who can be matched, starting with whoever has waited longest,
+    /// each with the closest opponent that both of them will accept
+    pub fn pair(&mut self, now: u64) -> Vec<(MatchSeeker, MatchSeeker)> {
+        let mut taken = vec![false; self.seekers.len()];
+        let mut pairs = vec![];
+
+        for first in 0..self.seekers.len() {
+            if taken[first] {
+                continue;
+            }
+            let seeker = &self.seekers[first];
+            let opponent = (first + 1..self.seekers.len())
+                .filter(|other| !taken[*other])
+                .map(|other| (other, &self.seekers[other]))
+                .filter(|(_, other)| {
+                    other.ruleset == seeker.ruleset
+                        && seeker.accepts(other, now)
+                        && other.accepts(seeker, now)
+                })
+                .min_by_key(|(_, other)| seeker.distance(other))
+                .map(|(other, _)| other);
+
+            if let Some(second) = opponent {
+                taken[first] = true;
+                taken[second] = true;
+                pairs.push((first, second));
+            }
+        }
+
+        let matched = pairs
+            .iter()
+            .map(|(first, second)| (self.seekers[*first].clone(), self.seekers[*second].clone()))
+            .collect();
+
+        let mut index = 0;
+        self.seekers.retain(|_| {
+            let keep = !taken[index];
+            index += 1;
+            keep
+        });
+
+        matched
+    }
+}
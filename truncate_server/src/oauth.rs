@@ -0,0 +1,347 @@
+use std::{collections::HashMap, env, net::SocketAddr};
+
+use jwt_simple::prelude::*;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use truncate_core::messages::OAuthProvider;
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+/// How long an external provider has to answer before we give up on signing in
+const PROVIDER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// How long a player has to finish signing in once they've been sent to a provider
+const SIGN_IN_MINUTES: u64 = 10;
+
+/// The app registered with a provider, read from `GITHUB_CLIENT_ID` and `GITHUB_CLIENT_SECRET`,
+/// or `GOOGLE_CLIENT_ID` and `GOOGLE_CLIENT_SECRET`
+#[derive(Debug, Clone)]
+struct OAuthApp {
+    client_id: String,
+    client_secret: String,
+}
+
+/// The providers players can sign in with, which are all disabled unless configured
+#[derive(Debug, Clone, Default)]
+pub struct OAuthApps {
+    github: Option<OAuthApp>,
+    google: Option<OAuthApp>,
+}
+
+impl OAuthApps {
+    pub fn from_env() -> Self {
+        fn read(prefix: &str) -> Option<OAuthApp> {
+            let var = |name: &str| {
+                env::var(format!("{prefix}_{name}"))
+                    .ok()
+                    .filter(|v| !v.is_empty())
+            };
+            match (var("CLIENT_ID"), var("CLIENT_SECRET")) {
+                (Some(client_id), Some(client_secret)) => Some(OAuthApp {
+                    client_id,
+                    client_secret,
+                }),
+                (None, None) => None,
+                _ => {
+                    warn!(%prefix, "Ignoring a sign in provider without both a client ID and secret");
+                    None
+                }
+            }
+        }
+
+        Self {
+            github: read("GITHUB"),
+            google: read("GOOGLE"),
+        }
+    }
+
+    pub fn enabled(&self) -> Vec<OAuthProvider> {
+        OAuthProvider::ALL
+            .into_iter()
+            .filter(|provider| self.app(*provider).is_some())
+            .collect()
+    }
+
+    fn app(&self, provider: OAuthProvider) -> Option<&OAuthApp> {
+        match provider {
+            OAuthProvider::GitHub => self.github.as_ref(),
+            OAuthProvider::Google => self.google.as_ref(),
+        }
+    }
+}
+
+/// The name a provider's accounts are stored under in `oauth_identities`
+pub fn provider_key(provider: OAuthProvider) -> &'static str {
+    match provider {
+        OAuthProvider::GitHub => "github",
+        OAuthProvider::Google => "google",
+    }
+}
+
+/// Carried through the provider as the `state` parameter, signed so that
+/// nobody can finish a sign in that this server didn't start
+#[derive(Serialize, Deserialize)]
+struct SignInState {
+    provider: OAuthProvider,
+    redirect_uri: String,
+    /// Looks up the sign in in `PendingSignIns`, which can only happen once
+    nonce: Uuid,
+}
+
+/// Sign ins sent off to a provider and not yet finished
+#[derive(Default)]
+pub struct PendingSignIns {
+    /// The connection that started each sign in, who was logged in on it, and when it started
+    started: HashMap<Uuid, (SocketAddr, Option<Uuid>, u64)>,
+}
+
+impl PendingSignIns {
+    fn start(&mut self, addr: SocketAddr, player: Option<Uuid>) -> Uuid {
+        let current_time = truncate_core::game::now();
+        self.started.retain(|_, (_, _, started_at)| {
+            *started_at > current_time.saturating_sub(SIGN_IN_MINUTES * 60)
+        });
+
+        let nonce = Uuid::new_v4();
+        self.started.insert(nonce, (addr, player, current_time));
+        nonce
+    }
+
+    /// Whether the sign in can be finished on this connection, which can only be asked once.
+    /// A sign in started while logged in must be finished by the same player, who may have
+    /// reconnected since, while one started while logged out must finish on the same connection.
+    fn finish(&mut self, nonce: Uuid, addr: SocketAddr, player: Option<Uuid>) -> bool {
+        let Some((started_on, started_by, _)) = self.started.remove(&nonce) else {
+            return false;
+        };
+        match started_by {
+            Some(started_by) => player == Some(started_by),
+            None => addr == started_on,
+        }
+    }
+}
+
+/// Sign ins can only return to the web client, or to a native client listening on this machine
+fn allowed_redirect(redirect_uri: &str) -> bool {
+    let public_url = env::var("PUBLIC_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "https://truncate.town".into());
+    redirect_matches(redirect_uri, &public_url)
+}
+
+/// Compares the scheme, host and port exactly, since a prefix match
+/// would let `https://truncate.town.example.com` through
+fn redirect_matches(redirect_uri: &str, public_url: &str) -> bool {
+    let Ok(redirect) = Url::parse(redirect_uri) else {
+        return false;
+    };
+    if redirect.scheme() == "http" && redirect.host_str() == Some("127.0.0.1") {
+        return true;
+    }
+    let Ok(public) = Url::parse(public_url) else {
+        return false;
+    };
+    redirect.scheme() == public.scheme()
+        && redirect.host_str() == public.host_str()
+        && redirect.port_or_known_default() == public.port_or_known_default()
+}
+
+/// Where to send the player to sign in with a provider, with `player` being whoever is
+/// logged in on the connection `addr` that asked
+pub fn authorize_url(
+    server_state: &ServerState,
+    provider: OAuthProvider,
+    redirect_uri: String,
+    addr: SocketAddr,
+    player: Option<Uuid>,
+) -> Result<String, TruncateServerError> {
+    let Some(app) = server_state.oauth_apps.app(provider) else {
+        return Err(TruncateServerError::BadRequest);
+    };
+    if !allowed_redirect(&redirect_uri) {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    let nonce = server_state.sign_ins.lock().start(addr, player);
+    let claims = Claims::with_custom_claims(
+        SignInState {
+            provider,
+            redirect_uri: redirect_uri.clone(),
+            nonce,
+        },
+        Duration::from_mins(SIGN_IN_MINUTES),
+    );
+    let state = server_state
+        .jwt_key
+        .authenticate(claims)
+        .expect("Claims should be serializable");
+
+    let (base, scope) = match provider {
+        OAuthProvider::GitHub => ("https://github.com/login/oauth/authorize", "read:user"),
+        OAuthProvider::Google => ("https://accounts.google.com/o/oauth2/v2/auth", "openid"),
+    };
+    let url = Url::parse_with_params(
+        base,
+        &[
+            ("client_id", app.client_id.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", scope),
+            ("state", state.as_str()),
+        ],
+    )
+    .expect("Provider URLs should be valid");
+
+    Ok(url.to_string())
+}
+
+#[derive(Deserialize)]
+struct AccessToken {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct GoogleUser {
+    sub: String,
+}
+
+/// Trades the code a provider sent the player back with for the provider's ID of their account,
+/// as long as the sign in is being finished where it was started, see `PendingSignIns::finish`
+pub async fn identify(
+    server_state: &ServerState,
+    code: String,
+    state: String,
+    addr: SocketAddr,
+    player: Option<Uuid>,
+) -> Result<(OAuthProvider, String), TruncateServerError> {
+    let Ok(state) = server_state
+        .jwt_key
+        .verify_token::<SignInState>(&state, None)
+    else {
+        return Err(TruncateServerError::InvalidToken);
+    };
+    let SignInState {
+        provider,
+        redirect_uri,
+        nonce,
+    } = state.custom;
+    if !server_state.sign_ins.lock().finish(nonce, addr, player) {
+        return Err(TruncateServerError::InvalidToken);
+    }
+    let Some(app) = server_state.oauth_apps.app(provider) else {
+        return Err(TruncateServerError::BadRequest);
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(PROVIDER_TIMEOUT)
+        .user_agent("truncate_server")
+        .build()
+        .map_err(|e| TruncateServerError::Provider(e.to_string()))?;
+    let failed = |e: reqwest::Error| TruncateServerError::Provider(e.to_string());
+
+    let token_url = match provider {
+        OAuthProvider::GitHub => "https://github.com/login/oauth/access_token",
+        OAuthProvider::Google => "https://oauth2.googleapis.com/token",
+    };
+    let token: AccessToken = client
+        .post(token_url)
+        .header("Accept", "application/json")
+        .form(&[
+            ("client_id", app.client_id.as_str()),
+            ("client_secret", app.client_secret.as_str()),
+            ("code", code.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("grant_type", "authorization_code"),
+        ])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(failed)?
+        .json()
+        .await
+        .map_err(failed)?;
+
+    let subject = match provider {
+        OAuthProvider::GitHub => {
+            let user: GitHubUser = client
+                .get("https://api.github.com/user")
+                .bearer_auth(&token.access_token)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(failed)?
+                .json()
+                .await
+                .map_err(failed)?;
+            user.id.to_string()
+        }
+        OAuthProvider::Google => {
+            let user: GoogleUser = client
+                .get("https://openidconnect.googleapis.com/v1/userinfo")
+                .bearer_auth(&token.access_token)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(failed)?
+                .json()
+                .await
+                .map_err(failed)?;
+            user.sub
+        }
+    };
+
+    Ok((provider, subject))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirects_must_match_the_public_host_exactly() {
+        let public = "https://truncate.town";
+        assert!(redirect_matches("https://truncate.town/", public));
+        assert!(redirect_matches("https://truncate.town:443/?a=b", public));
+        assert!(redirect_matches("http://127.0.0.1:4512/", public));
+
+        assert!(!redirect_matches("https://truncate.town.evil.com/", public));
+        assert!(!redirect_matches("https://truncate.town@evil.com/", public));
+        assert!(!redirect_matches("http://truncate.town/", public));
+        assert!(!redirect_matches("https://truncate.town:8443/", public));
+        assert!(!redirect_matches("https://127.0.0.1:4512/", public));
+        assert!(!redirect_matches("not a url", public));
+    }
+
+    #[test]
+    fn sign_ins_finish_once_where_they_started() {
+        let connection: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+        let reconnected: SocketAddr = "10.0.0.1:5001".parse().unwrap();
+        let player = Uuid::new_v4();
+        let mut pending = PendingSignIns::default();
+
+        // Logged out sign ins are held to the connection that started them
+        let nonce = pending.start(connection, None);
+        assert!(!pending.finish(nonce, reconnected, None));
+        let nonce = pending.start(connection, None);
+        assert!(pending.finish(nonce, connection, None));
+        assert!(!pending.finish(nonce, connection, None));
+
+        // Logged in sign ins are held to the player, wherever they reconnect from
+        let nonce = pending.start(connection, Some(player));
+        assert!(!pending.finish(nonce, connection, None));
+        let nonce = pending.start(connection, Some(player));
+        assert!(!pending.finish(nonce, connection, Some(Uuid::new_v4())));
+        let nonce = pending.start(connection, Some(player));
+        assert!(pending.finish(nonce, reconnected, Some(player)));
+        assert!(!pending.finish(nonce, reconnected, Some(player)));
+
+        assert!(!pending.finish(Uuid::new_v4(), connection, None));
+    }
+}
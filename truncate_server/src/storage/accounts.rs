@@ -1,16 +1,18 @@
 use jwt_simple::prelude::*;
 use serde::{Deserialize, Serialize};
-use sqlx::types::time;
-use truncate_core::messages::TruncateToken;
+use sqlx::{types::time, PgPool};
+use tracing::instrument;
+use truncate_core::messages::{OAuthProvider, TruncateToken};
 use uuid::Uuid;
 use woothee::parser::Parser as UAParser;
 
-use crate::{errors::TruncateServerError, ServerState};
+use crate::{errors::TruncateServerError, oauth::provider_key, ServerState};
 
 #[derive(Clone, Hash, PartialEq, Eq)]
 pub struct AuthedTruncateToken {
     token: TruncateToken,
     player_id: Uuid,
+    login_version: i32,
 }
 
 impl AuthedTruncateToken {
@@ -26,11 +28,28 @@ impl AuthedTruncateToken {
 #[derive(Serialize, Deserialize)]
 struct PlayerClaims {
     player_id: Uuid,
+    /// The player's `login_version` when the token was issued, which stops matching once their tokens are revoked
+    #[serde(default)]
+    login_version: i32,
 }
 
 pub fn get_player_token(server_state: &ServerState, player_id: Uuid) -> AuthedTruncateToken {
-    let claims =
-        Claims::with_custom_claims(PlayerClaims { player_id }, Duration::from_days(100000));
+    issue_player_token(server_state, player_id, 0)
+}
+
+/// Issues a token for a player whose tokens may have been revoked before
+fn issue_player_token(
+    server_state: &ServerState,
+    player_id: Uuid,
+    login_version: i32,
+) -> AuthedTruncateToken {
+    let claims = Claims::with_custom_claims(
+        PlayerClaims {
+            player_id,
+            login_version,
+        },
+        Duration::from_days(100000),
+    );
 
     let token = server_state
         .jwt_key
@@ -42,13 +61,15 @@ pub fn get_player_token(server_state: &ServerState, player_id: Uuid) -> AuthedTr
         .map(|t| AuthedTruncateToken {
             token,
             player_id: t.custom.player_id,
+            login_version: t.custom.login_version,
         })
         .expect("We just generated this");
 
     authed_token
 }
 
-pub fn auth_player_token(
+/// Checks that the token was signed by this server, without checking whether it has since been revoked
+fn verify_player_token(
     server_state: &ServerState,
     token: TruncateToken,
 ) -> Result<AuthedTruncateToken, jwt_simple::Error> {
@@ -58,9 +79,41 @@ pub fn auth_player_token(
         .map(|t| AuthedTruncateToken {
             token,
             player_id: t.custom.player_id,
+            login_version: t.custom.login_version,
         })
 }
 
+/// Checks that the token was signed by this server and hasn't been revoked since.
+/// Tokens can only be revoked in the database, so without one the signature is all there is to check.
+#[instrument(skip_all)]
+pub async fn auth_player_token(
+    server_state: &ServerState,
+    token: TruncateToken,
+) -> Result<AuthedTruncateToken, TruncateServerError> {
+    let Ok(authed) = verify_player_token(server_state, token) else {
+        return Err(TruncateServerError::InvalidToken);
+    };
+    let Some(pool) = &server_state.truncate_db else {
+        return Ok(authed);
+    };
+
+    let Some(login_version) = sqlx::query_scalar!(
+        "SELECT login_version FROM players WHERE player_id = $1",
+        authed.player_id
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Err(TruncateServerError::InvalidUser(authed.player_id));
+    };
+    if login_version != authed.login_version {
+        return Err(TruncateServerError::InvalidToken);
+    }
+
+    Ok(authed)
+}
+
+#[instrument(skip_all)]
 pub async fn create_player(
     server_state: &ServerState,
     screen_width: u32,
@@ -116,6 +169,7 @@ pub struct LoginResponse {
     pub unread_changelogs: Vec<UnreadChangelog>,
 }
 
+#[instrument(skip_all)]
 pub async fn login(
     server_state: &ServerState,
     token: TruncateToken,
@@ -127,19 +181,20 @@ pub async fn login(
         return Err(TruncateServerError::DatabaseOffline);
     };
 
-    let Ok(authed) = auth_player_token(server_state, token) else {
+    let Ok(authed) = verify_player_token(server_state, token) else {
         return Err(TruncateServerError::InvalidToken);
     };
     let player_id = authed.player();
 
     struct LoggedInPlayer {
         player_id: Uuid,
+        login_version: i32,
         last_known_changelog: Option<time::OffsetDateTime>,
     }
 
     let Some(login) = sqlx::query_as!(
         LoggedInPlayer,
-        "SELECT player_id, last_known_changelog FROM players WHERE player_id = $1",
+        "SELECT player_id, login_version, last_known_changelog FROM players WHERE player_id = $1",
         player_id
     )
     .fetch_optional(pool)
@@ -147,6 +202,9 @@ pub async fn login(
     else {
         return Err(TruncateServerError::InvalidUser(player_id));
     };
+    if login.login_version != authed.login_version {
+        return Err(TruncateServerError::InvalidToken);
+    }
 
     let unread_changelogs = get_unreads(pool, login.player_id).await?;
 
@@ -183,6 +241,86 @@ pub async fn login(
     })
 }
 
+/// Logs in as whoever has linked this provider's account, or links it to the player
+/// already logged in on this connection if nobody has yet
+#[instrument(skip(server_state, current), fields(provider = provider_key(provider)))]
+pub async fn oauth_login(
+    server_state: &ServerState,
+    provider: OAuthProvider,
+    subject: String,
+    current: Option<AuthedTruncateToken>,
+) -> Result<LoginResponse, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    struct LinkedPlayer {
+        player_id: Uuid,
+        login_version: i32,
+    }
+
+    let linked = sqlx::query_as!(
+        LinkedPlayer,
+        "SELECT p.player_id, p.login_version
+        FROM oauth_identities o
+        JOIN players p ON p.player_id = o.player_id
+        WHERE o.provider = $1 AND o.subject = $2",
+        provider_key(provider),
+        subject
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let authed = match (linked, current) {
+        (Some(linked), _) => {
+            issue_player_token(server_state, linked.player_id, linked.login_version)
+        }
+        (None, Some(current)) => {
+            let inserted = sqlx::query!(
+                "INSERT INTO oauth_identities (provider, subject, player_id)
+                VALUES ($1, $2, $3)
+                ON CONFLICT DO NOTHING",
+                provider_key(provider),
+                subject,
+                current.player()
+            )
+            .execute(pool)
+            .await?;
+            if inserted.rows_affected() == 0 {
+                return Err(TruncateServerError::AlreadyLinked);
+            }
+            current
+        }
+        (None, None) => return Err(TruncateServerError::InvalidToken),
+    };
+
+    let player_id = authed.player();
+    let unread_changelogs = get_unreads(pool, player_id).await?;
+
+    Ok(LoginResponse {
+        player_id,
+        authed,
+        unread_changelogs,
+    })
+}
+
+/// Stops every token issued to the player so far from logging in
+#[instrument(skip(pool))]
+pub async fn revoke_tokens(pool: &PgPool, player_id: Uuid) -> Result<(), TruncateServerError> {
+    let revoked = sqlx::query!(
+        "UPDATE players SET login_version = login_version + 1 WHERE player_id = $1",
+        player_id
+    )
+    .execute(pool)
+    .await?;
+
+    if revoked.rows_affected() == 0 {
+        return Err(TruncateServerError::InvalidUser(player_id));
+    }
+
+    Ok(())
+}
+
 async fn get_unreads(
     pool: &sqlx::Pool<sqlx::Postgres>,
     player_id: Uuid,
@@ -199,6 +337,7 @@ async fn get_unreads(
     .await
 }
 
+#[instrument(skip_all, fields(player = %authed.player(), changelog = %changelog_id))]
 pub async fn mark_changelog_read(
     server_state: &ServerState,
     authed: AuthedTruncateToken,
@@ -225,6 +364,7 @@ pub async fn mark_changelog_read(
     Ok(())
 }
 
+#[instrument(skip_all, fields(player = %authed.player()))]
 pub async fn mark_most_changelogs_read(
     server_state: &ServerState,
     authed: AuthedTruncateToken,
@@ -0,0 +1,30 @@
+use tracing::instrument;
+use truncate_core::messages::ArbiterAction;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+/// Adds an arbiter's ruling to the room's audit log
+#[instrument(skip(server_state))]
+pub async fn record_arbiter_action(
+    server_state: &ServerState,
+    room_code: &str,
+    action: ArbiterAction,
+    reason: &str,
+    turn_count: u32,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query!(
+        "INSERT INTO arbiter_actions (room_code, action, reason, turn_count) VALUES ($1, $2, $3, $4)",
+        room_code,
+        action.to_string(),
+        reason,
+        turn_count as i32
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
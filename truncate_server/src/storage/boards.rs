@@ -0,0 +1,357 @@
+use tracing::{instrument, warn};
+use truncate_core::{
+    board::Board,
+    messages::{GalleryBoard, GalleryStatus, SavedBoard},
+};
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// How many boards each player can keep on their account
+const MAX_SAVED_BOARDS: i64 = 24;
+/// Boards any larger than this in either direction aren't accepted for saving
+const MAX_BOARD_SIDE: usize = 40;
+const MAX_NAME_CHARS: usize = 64;
+/// How many boards are listed in the gallery
+const GALLERY_SIZE: i64 = 30;
+
+fn parse_gallery_status(status: &str) -> Option<GalleryStatus> {
+    match status {
+        "pending" => Some(GalleryStatus::Pending),
+        "approved" => Some(GalleryStatus::Approved),
+        "rejected" => Some(GalleryStatus::Rejected),
+        _ => None,
+    }
+}
+
+/// Saves a board into the player's slot of the given name, replacing whatever was there.
+/// Replacing a shared board takes it back out of the gallery, since moderators saw the old one.
+#[instrument(skip_all, fields(player = %player.player(), name))]
+pub async fn save_board(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    name: &str,
+    board: &Board,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    let name = name.trim();
+    if name.is_empty()
+        || name.chars().count() > MAX_NAME_CHARS
+        || board.width() > MAX_BOARD_SIDE
+        || board.height() > MAX_BOARD_SIDE
+    {
+        return Err(TruncateServerError::BadRequest);
+    }
+    let Ok(serialized_board) = serde_json::to_string(board) else {
+        return Err(TruncateServerError::BadRequest);
+    };
+
+    let other_slots = sqlx::query!(
+        "SELECT COUNT(*) AS slots FROM custom_boards WHERE player_id = $1 AND board_name <> $2",
+        player_id,
+        name
+    )
+    .fetch_one(pool)
+    .await?
+    .slots
+    .unwrap_or_default();
+    if other_slots >= MAX_SAVED_BOARDS {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    sqlx::query!(
+        "INSERT INTO custom_boards (player_id, board_name, board)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (player_id, board_name) DO UPDATE
+        SET board = EXCLUDED.board, gallery_status = NULL, updated_at = CURRENT_TIMESTAMP",
+        player_id,
+        name,
+        serialized_board
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(player = %player.player()))]
+pub async fn load_boards(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+) -> Result<Vec<SavedBoard>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    struct SavedBoardRecord {
+        board_id: Uuid,
+        board_name: String,
+        board: String,
+        gallery_status: Option<String>,
+    }
+
+    let results = sqlx::query_as!(
+        SavedBoardRecord,
+        "SELECT board_id, board_name, board, gallery_status
+        FROM custom_boards
+        WHERE player_id = $1
+        ORDER BY board_name;",
+        player_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(results
+        .into_iter()
+        .filter_map(|record| {
+            let Ok(board) = serde_json::from_str(&record.board) else {
+                // If the board no longer parses, move on as if it wasn't saved.
+                warn!(board = %record.board_id, "Skipping a saved board that could not be parsed");
+                return None;
+            };
+            Some(SavedBoard {
+                id: record.board_id.to_string(),
+                name: record.board_name,
+                board,
+                gallery: record
+                    .gallery_status
+                    .as_deref()
+                    .and_then(parse_gallery_status),
+            })
+        })
+        .collect())
+}
+
+#[instrument(skip_all, fields(player = %player.player(), board = %board_id))]
+pub async fn delete_board(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    board_id: Uuid,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    sqlx::query!(
+        "DELETE FROM custom_boards WHERE board_id = $1 AND player_id = $2",
+        board_id,
+        player_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Puts a private board in line for the gallery. Boards that have been moderated stay as they were.
+#[instrument(skip_all, fields(player = %player.player(), board = %board_id))]
+pub async fn share_board(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    board_id: Uuid,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    sqlx::query!(
+        "UPDATE custom_boards SET gallery_status = 'pending'
+        WHERE board_id = $1 AND player_id = $2 AND gallery_status IS NULL",
+        board_id,
+        player_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(board = %board_id, approved))]
+pub async fn moderate_board(
+    server_state: &ServerState,
+    board_id: Uuid,
+    approved: bool,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let status = if approved {
+        GalleryStatus::Approved
+    } else {
+        GalleryStatus::Rejected
+    };
+
+    let result = sqlx::query!(
+        "UPDATE custom_boards SET gallery_status = $2
+        WHERE board_id = $1 AND gallery_status IS NOT NULL",
+        board_id,
+        status.to_string()
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    Ok(())
+}
+
+/// Lists the approved boards in the gallery, best rated first
+#[instrument(skip_all)]
+pub async fn load_gallery(
+    server_state: &ServerState,
+) -> Result<Vec<GalleryBoard>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    struct GalleryRecord {
+        board_id: Uuid,
+        board_name: String,
+        board: String,
+        play_count: i32,
+        player_name: Option<String>,
+        rating: Option<f32>,
+        ratings: Option<i64>,
+    }
+
+    let results = sqlx::query_as!(
+        GalleryRecord,
+        "SELECT
+            cb.board_id,
+            cb.board_name,
+            cb.board,
+            cb.play_count,
+            p.player_name,
+            AVG(r.stars)::REAL AS rating,
+            COUNT(r.stars) AS ratings
+        FROM
+            custom_boards cb
+        JOIN
+            players p ON p.player_id = cb.player_id
+        LEFT JOIN
+            custom_board_ratings r ON r.board_id = cb.board_id
+        WHERE
+            cb.gallery_status = 'approved'
+        GROUP BY
+            cb.board_id, p.player_name
+        ORDER BY
+            rating DESC NULLS LAST, cb.play_count DESC
+        LIMIT $1;",
+        GALLERY_SIZE
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(results
+        .into_iter()
+        .filter_map(|record| {
+            let Ok(board) = serde_json::from_str(&record.board) else {
+                warn!(board = %record.board_id, "Skipping a gallery board that could not be parsed");
+                return None;
+            };
+            Some(GalleryBoard {
+                id: record.board_id.to_string(),
+                name: record.board_name,
+                author: record.player_name,
+                board,
+                plays: record.play_count.try_into().unwrap_or_default(),
+                rating: record.rating,
+                ratings: record.ratings.unwrap_or_default().try_into().unwrap_or_default(),
+            })
+        })
+        .collect())
+}
+
+/// Fetches a board and its name from the gallery, as long as it has been approved
+#[instrument(skip_all, fields(board = %board_id))]
+pub async fn load_gallery_board(
+    server_state: &ServerState,
+    board_id: Uuid,
+) -> Result<Option<(String, Board)>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let Some(record) = sqlx::query!(
+        "SELECT board_name, board FROM custom_boards
+        WHERE board_id = $1 AND gallery_status = 'approved'",
+        board_id
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let Ok(board) = serde_json::from_str(&record.board) else {
+        return Ok(None);
+    };
+    Ok(Some((record.board_name, board)))
+}
+
+#[instrument(skip_all, fields(board = %board_id))]
+pub async fn record_play(
+    server_state: &ServerState,
+    board_id: Uuid,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query!(
+        "UPDATE custom_boards SET play_count = play_count + 1 WHERE board_id = $1",
+        board_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Records a player's rating of a gallery board, replacing any rating they gave it before
+#[instrument(skip_all, fields(player = %player.player(), board = %board_id, stars))]
+pub async fn rate_board(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    board_id: Uuid,
+    stars: u8,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    if !(1..=5).contains(&stars) {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    let result = sqlx::query!(
+        "INSERT INTO custom_board_ratings (board_id, player_id, stars)
+        SELECT board_id, $2::UUID, $3::SMALLINT FROM custom_boards
+        WHERE board_id = $1 AND gallery_status = 'approved'
+        ON CONFLICT (board_id, player_id) DO UPDATE
+        SET stars = EXCLUDED.stars, rated_at = CURRENT_TIMESTAMP",
+        board_id,
+        player_id,
+        stars as i16
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    Ok(())
+}
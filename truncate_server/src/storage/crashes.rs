@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use tracing::instrument;
+use truncate_core::messages::CrashReport;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+/// Reports come straight from clients, so anything past this many characters is dropped
+const MAX_FIELD_CHARS: usize = 4000;
+/// Reports larger than this are dropped outright rather than clipped
+pub const MAX_REPORT_BYTES: usize = 32 * 1024;
+/// How long a connection has to wait after one report before another is stored
+pub const REPORT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Roughly how much a report would take up, before it is clipped
+pub fn report_size(report: &CrashReport) -> usize {
+    report.client_version.len()
+        + report.region.len()
+        + report.panic_message.len()
+        + report
+            .recent_messages
+            .iter()
+            .map(String::len)
+            .sum::<usize>()
+}
+
+#[instrument(skip_all, fields(version = %report.client_version, region = %report.region))]
+pub async fn persist_report(
+    server_state: &ServerState,
+    report: &CrashReport,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let clip = |text: &str| text.chars().take(MAX_FIELD_CHARS).collect::<String>();
+
+    sqlx::query!(
+        "INSERT INTO crash_reports (client_version, region, panic_message, recent_messages, board_hash)
+        VALUES ($1, $2, $3, $4, $5)",
+        clip(&report.client_version),
+        clip(&report.region),
+        clip(&report.panic_message),
+        clip(&report.recent_messages.join("\n")),
+        report.board_hash.map(|hash| hash as i64)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -1,32 +1,51 @@
 use std::collections::BTreeMap;
 
+use sqlx::PgPool;
+use tracing::{instrument, warn};
 use truncate_core::{
+    archive::{ArchiveResult, GameArchive},
+    difficulty::PuzzleDifficulty,
+    generation::{BoardParams, BoardSeed},
     messages::{DailyAttempt, DailyResult, DailyStateMessage, DailyStats},
     moves::{self, packing::pack_moves, Move},
+    replay::Replay,
+    rotation::PuzzleCategory,
+    rules::{GameRules, Language},
 };
 use uuid::Uuid;
 
 use crate::{errors::TruncateServerError, ServerState};
 
-use super::accounts::AuthedTruncateToken;
+use super::{accounts::AuthedTruncateToken, mastery, predictions};
 
 pub struct AttemptRecord {
     attempt_id: Uuid,
     attempt_number: i32,
     sequence_of_moves: String,
+    think_secs: Vec<i32>,
     won: bool,
 }
 pub struct DailyPuzzleRecord {
     result_id: Uuid,
 }
 
+fn think_secs_from_record(think_secs: &[i32]) -> Vec<u32> {
+    think_secs
+        .iter()
+        .map(|secs| (*secs).try_into().unwrap_or_default())
+        .collect()
+}
+
 /// Returns any partial or completed attempt for a given player on the requested day.
+#[instrument(skip_all, fields(player = %player.player(), day = daily_puzzle, %language))]
 pub async fn load_attempt(
     server_state: &ServerState,
     player: AuthedTruncateToken,
     daily_puzzle: i32,
+    language: Language,
 ) -> Result<Option<(DailyStateMessage, Option<DailyStateMessage>)>, TruncateServerError> {
-    let Some(daily_puzzle_record) = get_day_record(server_state, player, daily_puzzle).await?
+    let Some(daily_puzzle_record) =
+        get_day_record(server_state, player, daily_puzzle, language).await?
     else {
         return Ok(None);
     };
@@ -48,6 +67,9 @@ pub async fn load_attempt(
                 puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
                 attempt: a.attempt_number.try_into().unwrap_or_default(),
                 current_moves: best,
+                think_secs: think_secs_from_record(&a.think_secs),
+                category: PuzzleCategory::Daily,
+                language,
             })
         })
         .flatten();
@@ -63,33 +85,39 @@ pub async fn load_attempt(
             puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
             attempt: attempt_record.attempt_number.try_into().unwrap_or_default(),
             current_moves,
+            think_secs: think_secs_from_record(&attempt_record.think_secs),
+            category: PuzzleCategory::Daily,
+            language,
         },
         best_record,
     )))
 }
 
+#[instrument(skip_all, fields(player = %player.player(), day = daily_puzzle, %language))]
 pub async fn get_or_create_latest_attempt(
     server_state: &ServerState,
     player: AuthedTruncateToken,
     daily_puzzle: i32,
     human_player: i32,
+    language: Language,
 ) -> Result<(DailyStateMessage, AttemptRecord), TruncateServerError> {
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
     };
     let player_id = player.player();
 
-    let daily_puzzle_record = get_day_record(server_state, player, daily_puzzle).await?;
+    let daily_puzzle_record = get_day_record(server_state, player, daily_puzzle, language).await?;
 
     let result_id = if let Some(dpr) = daily_puzzle_record {
         dpr.result_id
     } else {
         let new_puzzle_record = sqlx::query_as!(
             DailyPuzzleRecord,
-            "INSERT INTO daily_puzzle_results (player_id, daily_puzzle, human_player) VALUES ($1, $2, $3) RETURNING result_id",
+            "INSERT INTO daily_puzzle_results (player_id, daily_puzzle, human_player, language) VALUES ($1, $2, $3, $4) RETURNING result_id",
             player_id,
             daily_puzzle,
-            human_player
+            human_player,
+            language.code()
         )
         .fetch_one(pool)
         .await?;
@@ -116,6 +144,9 @@ pub async fn get_or_create_latest_attempt(
             puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
             attempt: latest_attempt.attempt_number.try_into().unwrap_or_default(),
             current_moves,
+            think_secs: think_secs_from_record(&latest_attempt.think_secs),
+            category: PuzzleCategory::Daily,
+            language,
         },
         latest_attempt,
     ))
@@ -125,6 +156,7 @@ async fn get_day_record(
     server_state: &ServerState,
     player: AuthedTruncateToken,
     daily_puzzle: i32,
+    language: Language,
 ) -> Result<Option<DailyPuzzleRecord>, TruncateServerError> {
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
@@ -133,9 +165,10 @@ async fn get_day_record(
 
     let daily_puzzle_record = sqlx::query_as!(
         DailyPuzzleRecord,
-        "SELECT result_id FROM daily_puzzle_results WHERE player_id = $1 AND daily_puzzle = $2",
+        "SELECT result_id FROM daily_puzzle_results WHERE player_id = $1 AND daily_puzzle = $2 AND track = 'puzzle' AND language = $3",
         player_id,
-        daily_puzzle
+        daily_puzzle,
+        language.code()
     )
     .fetch_optional(pool)
     .await?;
@@ -153,7 +186,7 @@ async fn get_latest_attempt_for_day(
 
     sqlx::query_as!(
         AttemptRecord,
-        "SELECT attempt_id, sequence_of_moves, attempt_number, won FROM daily_puzzle_attempts WHERE result_id = $1 ORDER BY attempt_number DESC LIMIT 1",
+        "SELECT attempt_id, sequence_of_moves, think_secs, attempt_number, won FROM daily_puzzle_attempts WHERE result_id = $1 ORDER BY attempt_number DESC LIMIT 1",
         result_id
     )
     .fetch_optional(pool)
@@ -171,7 +204,7 @@ async fn get_best_attempt_for_day(
 
     sqlx::query_as!(
         AttemptRecord,
-        "SELECT attempt_id, sequence_of_moves, attempt_number, won FROM daily_puzzle_attempts WHERE result_id = $1 AND won = true ORDER BY move_count ASC LIMIT 1",
+        "SELECT attempt_id, sequence_of_moves, think_secs, attempt_number, won FROM daily_puzzle_attempts WHERE result_id = $1 AND won = true ORDER BY move_count ASC LIMIT 1",
         result_id
     )
     .fetch_optional(pool)
@@ -205,32 +238,43 @@ async fn create_new_attempt(
         attempt_id: new_attempt.attempt_id,
         attempt_number: new_attempt_number,
         sequence_of_moves: String::new(),
+        think_secs: vec![],
         won: false,
     })
 }
 
+#[instrument(skip_all, fields(player = %player.player(), day = daily_puzzle, %language, moves = moves.len()))]
 pub async fn persist_moves(
     server_state: &ServerState,
     player: AuthedTruncateToken,
     daily_puzzle: i32,
     human_player: i32,
     moves: Vec<Move>,
+    think_secs: Vec<u32>,
     won: bool,
+    language: Language,
+    rules_generation: Option<u32>,
+    board_seed: Option<(u32, u32)>,
 ) -> Result<(), TruncateServerError> {
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
     };
 
-    let (_, mut attempt) =
-        get_or_create_latest_attempt(server_state, player.clone(), daily_puzzle, human_player)
-            .await?;
+    let (_, mut attempt) = get_or_create_latest_attempt(
+        server_state,
+        player.clone(),
+        daily_puzzle,
+        human_player,
+        language,
+    )
+    .await?;
 
     let packed_moves = pack_moves(&moves, 2);
 
     if !packed_moves.starts_with(&attempt.sequence_of_moves) {
         // sacré bleu! somebody is trying to change history!
         // no sir, we will create a new attempt for these moves.
-        let day_record = get_day_record(server_state, player, daily_puzzle)
+        let day_record = get_day_record(server_state, player, daily_puzzle, language)
             .await?
             .expect("Getting the latest attempt should have created the relevant day");
         attempt = create_new_attempt(server_state, day_record.result_id).await?;
@@ -242,22 +286,48 @@ pub async fn persist_moves(
             let player = match m {
                 Move::Place { player, .. } => player,
                 Move::Swap { player, .. } => player,
+                Move::Exchange { player, .. } => player,
+                Move::Mulligan { player } => player,
             };
             *player as i32 == human_player
         })
         .count();
 
-    // TODO: If `won` is supposedly true, we should simulate the puzzle
-    // to ensure that the move sequence indeed wins
-
+    // Wins are only kept if the moves play out to one here
+    let won = won
+        && replays_to_win(
+            server_state,
+            &packed_moves,
+            human_player,
+            language,
+            rules_generation,
+            board_seed,
+        );
+
+    // Times are only kept for moves that were actually persisted
+    let think_secs: Vec<i32> = think_secs
+        .into_iter()
+        .take(moves.len())
+        .map(|secs| secs.try_into().unwrap_or(i32::MAX))
+        .collect();
+
+    // Older clients don't send the puzzle's setup, so keep whatever an attempt already has
+    let as_int = |n: u32| i32::try_from(n).ok();
     sqlx::query!(
         "UPDATE daily_puzzle_attempts 
-         SET sequence_of_moves = $1, move_count = $2, won = $3
+         SET sequence_of_moves = $1, move_count = $2, won = $3, think_secs = $5,
+             rules_generation = COALESCE($6, rules_generation),
+             board_generation = COALESCE($7, board_generation),
+             board_seed = COALESCE($8, board_seed)
          WHERE attempt_id = $4",
         packed_moves,
         human_moves as i32,
         won,
-        attempt.attempt_id
+        attempt.attempt_id,
+        &think_secs,
+        rules_generation.and_then(as_int),
+        board_seed.and_then(|(generation, _)| as_int(generation)),
+        board_seed.and_then(|(_, seed)| as_int(seed))
     )
     .execute(pool)
     .await?;
@@ -265,15 +335,65 @@ pub async fn persist_moves(
     Ok(())
 }
 
+/// Replays a puzzle from the setup the client played it on, checking that the human player won.
+/// Older clients don't send the setup, so their wins can't be checked and aren't kept.
+fn replays_to_win(
+    server_state: &ServerState,
+    packed_moves: &String,
+    human_player: i32,
+    language: Language,
+    rules_generation: Option<u32>,
+    board_seed: Option<(u32, u32)>,
+) -> bool {
+    let (Some(rules), Some((generation, seed))) = (
+        rules_generation.and_then(GameRules::checked_generation),
+        board_seed.filter(|(generation, _)| BoardParams::checked_generation(*generation).is_some()),
+    ) else {
+        warn!("Player claimed a puzzle win without the puzzle's setup");
+        return false;
+    };
+    let rules = GameRules { language, ..rules };
+    let seed = BoardSeed::new_with_generation(generation, seed);
+
+    let word_db = server_state.word_db.lock();
+    let winner = Replay::from_seed(&seed, rules, packed_moves, word_db.dictionary(language))
+        .and_then(Replay::winner);
+    match winner {
+        Ok(Some(winner)) if winner as i32 == human_player => true,
+        Ok(_) => {
+            warn!("Player claimed a puzzle win that their moves don't reach");
+            false
+        }
+        Err(e) => {
+            warn!("Player claimed a puzzle win that didn't replay: {e}");
+            false
+        }
+    }
+}
+
+#[instrument(skip_all, fields(player = %player.player(), %language))]
 pub async fn load_stats(
     server_state: &ServerState,
     player: AuthedTruncateToken,
+    language: Language,
 ) -> Result<DailyStats, TruncateServerError> {
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
     };
-    let player_id = player.player();
 
+    let mut stats = load_player_stats(pool, player.player(), language).await?;
+    stats.letter_mastery = mastery::load_mastery(pool, player.player()).await?;
+    stats.prediction_stats = predictions::load_predictions(pool, player.player()).await?;
+    Ok(stats)
+}
+
+/// Every daily puzzle attempt a player has made in a language, grouped by day
+#[instrument(skip(pool))]
+pub async fn load_player_stats(
+    pool: &PgPool,
+    player_id: Uuid,
+    language: Language,
+) -> Result<DailyStats, TruncateServerError> {
     struct PuzzleStatsRecord {
         daily_puzzle: i32,
         attempt_ids: Option<Vec<Uuid>>,
@@ -293,10 +413,11 @@ pub async fn load_stats(
         JOIN 
             daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
         WHERE 
-            dpr.player_id = $1
+            dpr.player_id = $1 AND dpr.track = 'puzzle' AND dpr.language = $2
         GROUP BY 
             dpr.daily_puzzle;",
-        player_id
+        player_id,
+        language.code()
     )
     .fetch_all(pool)
     .await?;
@@ -324,10 +445,78 @@ pub async fn load_stats(
 
     Ok(DailyStats {
         days: BTreeMap::from_iter(day_iter),
+        ..Default::default()
     })
 }
 
+/// Aggregates every player's attempts into difficulty stats for each day in the range
+#[instrument(skip(server_state))]
+pub async fn load_difficulty(
+    server_state: &ServerState,
+    from_day: u32,
+    to_day: u32,
+    language: Language,
+) -> Result<Vec<PuzzleDifficulty>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let (Ok(from_day), Ok(to_day)) = (i32::try_from(from_day), i32::try_from(to_day)) else {
+        return Err(TruncateServerError::BadRequest);
+    };
+
+    struct DifficultyRecord {
+        daily_puzzle: i32,
+        players: Option<i64>,
+        attempts: Option<i64>,
+        winners: Option<i64>,
+        average_winning_moves: Option<f64>,
+    }
+
+    // Attempts without any moves were opened but never played, so they don't count towards difficulty
+    let records = sqlx::query_as!(
+        DifficultyRecord,
+        "SELECT
+            dpr.daily_puzzle,
+            COUNT(DISTINCT dpr.result_id) AS players,
+            COUNT(dpa.attempt_id) AS attempts,
+            COUNT(DISTINCT dpr.result_id) FILTER (WHERE dpa.won) AS winners,
+            AVG(dpa.move_count) FILTER (WHERE dpa.won)::FLOAT8 AS average_winning_moves
+        FROM
+            daily_puzzle_results dpr
+        JOIN
+            daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
+        WHERE
+            dpr.track = 'puzzle'
+            AND dpr.language = $3
+            AND dpa.move_count > 0
+            AND dpr.daily_puzzle BETWEEN $1 AND $2
+        GROUP BY
+            dpr.daily_puzzle
+        ORDER BY
+            dpr.daily_puzzle;",
+        from_day,
+        to_day,
+        language.code()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let count = |n: Option<i64>| n.unwrap_or_default().try_into().unwrap_or_default();
+
+    Ok(records
+        .into_iter()
+        .map(|record| PuzzleDifficulty {
+            day: record.daily_puzzle.try_into().unwrap_or_default(),
+            players: count(record.players),
+            attempts: count(record.attempts),
+            winners: count(record.winners),
+            average_winning_moves: record.average_winning_moves.map(|m| m as f32),
+        })
+        .collect())
+}
+
 /// Returns an attempt given its ID
+#[instrument(skip_all, fields(attempt = %id))]
 pub async fn load_exact_attempt(
     server_state: &ServerState,
     id: Uuid,
@@ -339,7 +528,9 @@ pub async fn load_exact_attempt(
     struct LoadedAttemptRecord {
         attempt_number: i32,
         sequence_of_moves: String,
+        think_secs: Vec<i32>,
         daily_puzzle: i32,
+        language: String,
     }
 
     let record = sqlx::query_as!(
@@ -347,13 +538,15 @@ pub async fn load_exact_attempt(
         "SELECT 
             dpa.sequence_of_moves,
             dpa.attempt_number,
-            dpr.daily_puzzle
+            dpa.think_secs,
+            dpr.daily_puzzle,
+            dpr.language
         FROM
             daily_puzzle_attempts dpa
         JOIN 
             daily_puzzle_results dpr ON dpr.result_id = dpa.result_id
         WHERE
-            attempt_id = $1",
+            attempt_id = $1 AND dpr.track = 'puzzle'",
         id
     )
     .fetch_optional(pool)
@@ -373,5 +566,97 @@ pub async fn load_exact_attempt(
         puzzle_day: attempt_record.daily_puzzle.try_into().unwrap_or_default(),
         attempt: attempt_record.attempt_number.try_into().unwrap_or_default(),
         current_moves,
+        think_secs: think_secs_from_record(&attempt_record.think_secs),
+        category: PuzzleCategory::Daily,
+        language: Language::from_code(&attempt_record.language).unwrap_or_default(),
     }))
 }
+
+/// Bundles every daily puzzle attempt for a player into one archive file
+#[instrument(skip_all, fields(player = %player.player()))]
+pub async fn export_archive(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+) -> Result<String, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    struct ArchivedAttemptRecord {
+        daily_puzzle: i32,
+        human_player: i32,
+        attempt_number: i32,
+        sequence_of_moves: String,
+        won: bool,
+        attempt_started: Option<time::OffsetDateTime>,
+        language: String,
+        rules_generation: Option<i32>,
+        board_generation: Option<i32>,
+        board_seed: Option<i32>,
+    }
+
+    let records = sqlx::query_as!(
+        ArchivedAttemptRecord,
+        "SELECT
+            dpr.daily_puzzle,
+            dpr.human_player,
+            dpa.attempt_number,
+            dpa.sequence_of_moves,
+            dpa.won,
+            dpa.attempt_started,
+            dpr.language,
+            dpa.rules_generation,
+            dpa.board_generation,
+            dpa.board_seed
+        FROM
+            daily_puzzle_results dpr
+        JOIN
+            daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
+        WHERE
+            dpr.player_id = $1 AND dpr.track = 'puzzle'
+        ORDER BY
+            dpr.language, dpr.daily_puzzle, dpa.attempt_number;",
+        player_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let archives = records.into_iter().filter_map(|record| {
+        // Skip anything we can't read back, rather than failing the whole export
+        let moves = moves::packing::unpack_moves(&record.sequence_of_moves, 2).ok()?;
+        let human_player = usize::try_from(record.human_player).ok()?;
+
+        let mut players = vec!["Computer".to_string(); 2];
+        *players.get_mut(human_player)? = "You".to_string();
+
+        let mut archive = GameArchive::new("Daily Puzzle".to_string(), players, moves);
+        archive.date = record.attempt_started.map(|t| t.date().to_string());
+        // Attempts from before rules were recorded are archived without them, rather than guessed
+        archive.rules = record
+            .rules_generation
+            .and_then(|r| r.try_into().ok())
+            .and_then(GameRules::checked_generation);
+        archive.seed =
+            record
+                .board_generation
+                .zip(record.board_seed)
+                .and_then(|(generation, seed)| {
+                    Some((generation.try_into().ok()?, seed.try_into().ok()?))
+                });
+        if record.won {
+            archive.result = ArchiveResult::Winner(human_player);
+        }
+        archive
+            .tags
+            .insert("Day".to_string(), record.daily_puzzle.to_string());
+        archive
+            .tags
+            .insert("Attempt".to_string(), record.attempt_number.to_string());
+        archive.tags.insert("Language".to_string(), record.language);
+
+        Some(archive.to_string())
+    });
+
+    Ok(archives.collect::<Vec<_>>().join("\n"))
+}
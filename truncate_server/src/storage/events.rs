@@ -1,31 +1,40 @@
+use tracing::{debug, instrument};
+use truncate_core::flags::Flags;
+
 use crate::{errors::TruncateServerError, ServerState};
 
 use super::accounts::AuthedTruncateToken;
 
+#[instrument(skip_all, fields(event = %event_type))]
 pub async fn create_event(
     server_state: &ServerState,
     event_type: &String,
     player: Option<AuthedTruncateToken>,
+    flags: Flags,
 ) -> Result<(), TruncateServerError> {
     let Some(player_token) = player else {
         return Ok(());
     };
 
-    println!("Tracking event: {event_type}");
+    debug!("Tracking event");
 
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
     };
 
     let player_id = player_token.player();
+    // Kept with each event so that experiments can be compared
+    let flags = flags.to_string();
 
     sqlx::query!(
         "INSERT INTO events (
             event_type,
-            player_id
-        ) VALUES ($1, $2) RETURNING player_id;",
+            player_id,
+            flags
+        ) VALUES ($1, $2, $3) RETURNING player_id;",
         event_type,
-        player_id
+        player_id,
+        flags
     )
     .fetch_one(pool)
     .await
@@ -0,0 +1,142 @@
+use time::OffsetDateTime;
+use tracing::{info, instrument};
+use truncate_core::messages::{Abandonment, FairPlayRecord};
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// Points taken off the fair play rating for each abandonment in the last thirty days
+const RATING_COST: u32 = 10;
+/// Abandonments this close together escalate the penalty
+const PENALTY_WINDOW_SECS: i64 = 60 * 60 * 24 * 7;
+/// How long a player waits before their next game, by how many games they've recently abandoned.
+/// The first one is free, since connections drop.
+const PENALTY_STEPS_SECS: [u64; 4] = [0, 5 * 60, 30 * 60, 2 * 60 * 60];
+const MAX_APPEAL_CHARS: usize = 1000;
+
+#[instrument(skip_all, fields(player = %player.player(), room = %room_code))]
+pub async fn record_abandonment(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    room_code: &str,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    sqlx::query!(
+        "INSERT INTO abandonments (player_id, room_code) VALUES ($1, $2)",
+        player_id,
+        room_code
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(player = %player.player()))]
+pub async fn load_fair_play(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+) -> Result<FairPlayRecord, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    struct AbandonmentRecord {
+        abandonment_id: Uuid,
+        room_code: String,
+        abandoned_at: Option<OffsetDateTime>,
+        appealed: Option<bool>,
+    }
+
+    let records = sqlx::query_as!(
+        AbandonmentRecord,
+        "SELECT abandonment_id, room_code, abandoned_at, appeal IS NOT NULL AS appealed
+        FROM abandonments
+        WHERE player_id = $1 AND abandoned_at > CURRENT_TIMESTAMP - INTERVAL '30 days'
+        ORDER BY abandoned_at DESC;",
+        player_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let recent: Vec<_> = records
+        .iter()
+        .filter_map(|record| record.abandoned_at)
+        .map(|at| at.unix_timestamp())
+        .filter(|at| now - at < PENALTY_WINDOW_SECS)
+        .collect();
+    // The penalty runs from the latest abandonment, and grows with each one in the window
+    let penalty_secs = match recent.first() {
+        Some(latest) => {
+            let step = PENALTY_STEPS_SECS[(recent.len() - 1).min(PENALTY_STEPS_SECS.len() - 1)];
+            (latest + step as i64 - now).max(0) as u64
+        }
+        None => 0,
+    };
+
+    let rating = 100_u32.saturating_sub(RATING_COST * records.len() as u32) as u8;
+
+    Ok(FairPlayRecord {
+        rating,
+        recent_abandonments: records
+            .into_iter()
+            .map(|record| Abandonment {
+                id: record.abandonment_id.to_string(),
+                room_code: record.room_code,
+                abandoned_at: record
+                    .abandoned_at
+                    .map(|at| at.unix_timestamp().max(0) as u64)
+                    .unwrap_or_default(),
+                appealed: record.appealed.unwrap_or_default(),
+            })
+            .collect(),
+        penalty_secs,
+    })
+}
+
+/// Logs the player's reason for leaving a game, for moderators to review.
+/// Each abandonment can only be appealed once.
+#[instrument(skip_all, fields(player = %player.player(), abandonment = %abandonment_id))]
+pub async fn appeal_abandonment(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    abandonment_id: Uuid,
+    reason: &str,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(TruncateServerError::BadRequest);
+    }
+    let reason: String = reason.chars().take(MAX_APPEAL_CHARS).collect();
+
+    let result = sqlx::query!(
+        "UPDATE abandonments SET appeal = $3, appealed_at = CURRENT_TIMESTAMP
+        WHERE abandonment_id = $1 AND player_id = $2 AND appeal IS NULL",
+        abandonment_id,
+        player_id,
+        reason
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    info!(%reason, "Abandonment appealed, awaiting moderation");
+
+    Ok(())
+}
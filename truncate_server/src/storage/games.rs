@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::Duration;
+use tracing::{instrument, warn};
+use truncate_core::{
+    board::Board,
+    moves::{
+        packing::{pack_moves, unpack_moves},
+        Move,
+    },
+    player::Player,
+    rules::GameRules,
+};
+
+use crate::{errors::TruncateServerError, ServerState};
+
+/// A correspondence game as it stood after its latest move,
+/// holding what's needed to play it back into a fresh room
+#[derive(Debug, Clone)]
+pub struct SavedGame {
+    pub room_code: String,
+    pub rules: GameRules,
+    /// Player names, in turn order
+    pub player_names: Vec<String>,
+    pub tile_seed: u64,
+    /// The board as the game started on it
+    pub board: Board,
+    pub moves: Vec<Move>,
+    pub clocks: Vec<SavedClock>,
+    pub effective_day: u32,
+    pub started_at: u64,
+    pub finished: bool,
+    /// The number of the latest turn sent out to the players, which reconnecting clients catch up from
+    pub event_seq: u64,
+}
+
+/// The parts of a player's clock that replaying their moves can't recover,
+/// since the moves are replayed all at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedClock {
+    pub time_remaining: Option<Duration>,
+    pub turn_starts_no_later_than: Option<u64>,
+    pub turn_starts_no_sooner_than: Option<u64>,
+    pub paused_turn_delta: Option<i64>,
+}
+
+impl SavedClock {
+    pub fn of(player: &Player) -> Self {
+        Self {
+            time_remaining: player.time_remaining,
+            turn_starts_no_later_than: player.turn_starts_no_later_than,
+            turn_starts_no_sooner_than: player.turn_starts_no_sooner_than,
+            paused_turn_delta: player.paused_turn_delta,
+        }
+    }
+
+    pub fn restore(&self, player: &mut Player) {
+        player.time_remaining = self.time_remaining;
+        player.turn_starts_no_later_than = self.turn_starts_no_later_than;
+        player.turn_starts_no_sooner_than = self.turn_starts_no_sooner_than;
+        player.paused_turn_delta = self.paused_turn_delta;
+    }
+}
+
+/// Saves the game over whatever was saved for its room before
+#[instrument(skip_all, fields(room = %game.room_code, moves = game.moves.len()))]
+pub async fn save_game(
+    server_state: &ServerState,
+    game: SavedGame,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let (Ok(rules), Ok(board), Ok(clocks)) = (
+        serde_json::to_string(&game.rules),
+        serde_json::to_string(&game.board),
+        serde_json::to_string(&game.clocks),
+    ) else {
+        return Err(TruncateServerError::BadRequest);
+    };
+
+    sqlx::query!(
+        "INSERT INTO games (room_code, rules, player_names, tile_seed, board, moves, move_count, clocks, effective_day, started_at, finished, event_seq)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        ON CONFLICT (room_code) DO UPDATE
+        SET moves = EXCLUDED.moves, move_count = EXCLUDED.move_count, clocks = EXCLUDED.clocks,
+            finished = EXCLUDED.finished, event_seq = EXCLUDED.event_seq, updated_at = CURRENT_TIMESTAMP",
+        game.room_code,
+        rules,
+        &game.player_names,
+        game.tile_seed as i64,
+        board,
+        pack_moves(&game.moves, game.player_names.len()),
+        game.moves.len() as i32,
+        clocks,
+        game.effective_day as i32,
+        game.started_at as i64,
+        game.finished,
+        game.event_seq as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Every saved game that hasn't finished yet, skipping (and logging) any that can't be read back
+#[instrument(skip_all)]
+pub async fn load_unfinished_games(pool: &PgPool) -> Result<Vec<SavedGame>, TruncateServerError> {
+    let games = sqlx::query!(
+        "SELECT room_code, rules, player_names, tile_seed, board, moves, clocks, effective_day, started_at, event_seq
+        FROM games
+        WHERE finished = FALSE;"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games
+        .into_iter()
+        .filter_map(|game| {
+            let player_count = game.player_names.len();
+            let decoded = (|| {
+                Some((
+                    serde_json::from_str::<GameRules>(&game.rules).ok()?,
+                    serde_json::from_str::<Board>(&game.board).ok()?,
+                    unpack_moves(&game.moves, player_count).ok()?,
+                    serde_json::from_str::<Vec<SavedClock>>(&game.clocks).ok()?,
+                ))
+            })();
+            let Some((rules, board, moves, clocks)) = decoded else {
+                warn!(room = %game.room_code, "Skipping a saved game that couldn't be read");
+                return None;
+            };
+
+            Some(SavedGame {
+                room_code: game.room_code,
+                rules,
+                player_names: game.player_names,
+                tile_seed: game.tile_seed as u64,
+                board,
+                moves,
+                clocks,
+                effective_day: game.effective_day as u32,
+                started_at: game.started_at as u64,
+                finished: false,
+                event_seq: game.event_seq as u64,
+            })
+        })
+        .collect())
+}
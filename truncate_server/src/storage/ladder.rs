@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use tracing::instrument;
+use truncate_core::ladder::LadderStats;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// Records a solved word ladder as a won attempt on the word ladder track of the daily tables
+#[instrument(skip_all, fields(player = %player.player(), day = daily_ladder, steps))]
+pub async fn persist_solve(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    daily_ladder: i32,
+    words: &[String],
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+    let steps = words.len().saturating_sub(1) as i32;
+    tracing::Span::current().record("steps", steps);
+
+    let result = sqlx::query!(
+        "INSERT INTO daily_puzzle_results (player_id, daily_puzzle, human_player, track)
+        VALUES ($1, $2, 0, 'word_ladder')
+        ON CONFLICT (player_id, daily_puzzle, track, language) DO UPDATE SET track = EXCLUDED.track
+        RETURNING result_id",
+        player_id,
+        daily_ladder
+    )
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO daily_puzzle_attempts (result_id, attempt_number, sequence_of_moves, move_count, won)
+        SELECT $1, COUNT(*)::INT, $2::TEXT, $3::INT, true
+        FROM daily_puzzle_attempts WHERE result_id = $1",
+        result.result_id,
+        words.join(","),
+        steps
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(player = %player.player()))]
+pub async fn load_stats(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+) -> Result<LadderStats, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    struct LadderStatsRecord {
+        daily_puzzle: i32,
+        best_steps: Option<i32>,
+    }
+
+    let results = sqlx::query_as!(
+        LadderStatsRecord,
+        "SELECT
+            dpr.daily_puzzle,
+            MIN(dpa.move_count) AS best_steps
+        FROM
+            daily_puzzle_results dpr
+        JOIN
+            daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
+        WHERE
+            dpr.player_id = $1 AND dpr.track = 'word_ladder' AND dpa.won = true
+        GROUP BY
+            dpr.daily_puzzle;",
+        player_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let solved = results.into_iter().filter_map(|day| {
+        Some((
+            day.daily_puzzle.try_into().ok()?,
+            day.best_steps?.try_into().ok()?,
+        ))
+    });
+
+    Ok(LadderStats {
+        solved: BTreeMap::from_iter(solved),
+    })
+}
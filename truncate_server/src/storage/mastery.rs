@@ -0,0 +1,72 @@
+use sqlx::PgPool;
+use tracing::instrument;
+use truncate_core::mastery::{LetterMastery, LetterRecord};
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+/// Adds a finished game's battles onto everything the player has fought with each letter before
+#[instrument(skip(server_state, mastery))]
+pub async fn record_mastery(
+    server_state: &ServerState,
+    player_id: Uuid,
+    mastery: &LetterMastery,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    if mastery.is_empty() {
+        return Ok(());
+    }
+
+    let letters: Vec<_> = mastery.letters.keys().map(|l| l.to_string()).collect();
+    let won: Vec<_> = mastery.letters.values().map(|r| r.won as i32).collect();
+    let lost: Vec<_> = mastery.letters.values().map(|r| r.lost as i32).collect();
+
+    sqlx::query!(
+        "INSERT INTO letter_mastery (player_id, letter, battles_won, battles_lost)
+        SELECT $1, * FROM UNNEST($2::text[], $3::int[], $4::int[])
+        ON CONFLICT (player_id, letter) DO UPDATE
+        SET battles_won = letter_mastery.battles_won + EXCLUDED.battles_won,
+            battles_lost = letter_mastery.battles_lost + EXCLUDED.battles_lost,
+            updated_at = CURRENT_TIMESTAMP",
+        player_id,
+        &letters,
+        &won,
+        &lost
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Everything the player has fought with each letter
+#[instrument(skip(pool))]
+pub async fn load_mastery(
+    pool: &PgPool,
+    player_id: Uuid,
+) -> Result<LetterMastery, TruncateServerError> {
+    let records = sqlx::query!(
+        "SELECT letter, battles_won, battles_lost FROM letter_mastery WHERE player_id = $1",
+        player_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(LetterMastery {
+        letters: records
+            .into_iter()
+            .filter_map(|record| {
+                let letter = record.letter.chars().next()?;
+                Some((
+                    letter,
+                    LetterRecord {
+                        won: record.battles_won.try_into().unwrap_or_default(),
+                        lost: record.battles_lost.try_into().unwrap_or_default(),
+                    },
+                ))
+            })
+            .collect(),
+    })
+}
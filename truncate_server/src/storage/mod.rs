@@ -1,3 +1,18 @@
 pub mod accounts;
+pub mod arbiter;
+pub mod boards;
+pub mod crashes;
 pub mod daily;
 pub mod events;
+pub mod fair_play;
+pub mod games;
+pub mod ladder;
+pub mod mastery;
+pub mod npc_ladder;
+pub mod predictions;
+pub mod replays;
+pub mod rooms;
+pub mod rotations;
+pub mod rush;
+pub mod schedule;
+pub mod snapshot;
@@ -0,0 +1,73 @@
+use tracing::instrument;
+use truncate_core::{
+    moves::{packing::pack_moves, Move},
+    npc::progression::NpcProgress,
+};
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// Records a win against an NPC ladder rung, along with the board it was replayed on
+#[instrument(skip_all, fields(player = %player.player(), rung))]
+pub async fn persist_win(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    rung: i32,
+    moves: &[Move],
+    rules_generation: u32,
+    board_seed: (u32, u32),
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+    let (board_generation, seed) = board_seed;
+
+    sqlx::query!(
+        "INSERT INTO npc_ladder_wins
+            (player_id, rung, sequence_of_moves, move_count, rules_generation, board_generation, board_seed)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        player_id,
+        rung,
+        pack_moves(moves, 2),
+        moves.len() as i32,
+        rules_generation as i32,
+        board_generation as i32,
+        seed as i32
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(player = %player.player()))]
+pub async fn load_progress(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+) -> Result<NpcProgress, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    struct BeatenRungRecord {
+        rung: i32,
+    }
+
+    let results = sqlx::query_as!(
+        BeatenRungRecord,
+        "SELECT DISTINCT rung FROM npc_ladder_wins WHERE player_id = $1;",
+        player_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(NpcProgress {
+        beaten: results
+            .into_iter()
+            .filter_map(|record| record.rung.try_into().ok())
+            .collect(),
+    })
+}
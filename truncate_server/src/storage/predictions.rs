@@ -0,0 +1,63 @@
+use sqlx::PgPool;
+use tracing::instrument;
+use truncate_core::prediction::PredictionStats;
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// Adds a game of prediction training onto everything the player has predicted before
+#[instrument(skip_all, fields(player = %player.player(), predictions = stats.predictions))]
+pub async fn record_predictions(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    stats: &PredictionStats,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    if stats.predictions == 0 {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO prediction_stats (player_id, predictions, exact, close)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (player_id) DO UPDATE
+        SET predictions = prediction_stats.predictions + EXCLUDED.predictions,
+            exact = prediction_stats.exact + EXCLUDED.exact,
+            close = prediction_stats.close + EXCLUDED.close,
+            updated_at = CURRENT_TIMESTAMP",
+        player.player(),
+        stats.predictions as i32,
+        stats.exact as i32,
+        stats.close as i32
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Everything the player has predicted in training
+#[instrument(skip(pool))]
+pub async fn load_predictions(
+    pool: &PgPool,
+    player_id: Uuid,
+) -> Result<PredictionStats, TruncateServerError> {
+    let record = sqlx::query!(
+        "SELECT predictions, exact, close FROM prediction_stats WHERE player_id = $1",
+        player_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record
+        .map(|record| PredictionStats {
+            predictions: record.predictions.try_into().unwrap_or_default(),
+            exact: record.exact.try_into().unwrap_or_default(),
+            close: record.close.try_into().unwrap_or_default(),
+        })
+        .unwrap_or_default())
+}
@@ -0,0 +1,279 @@
+use std::env;
+
+use futures_util::stream::TryStreamExt;
+use sqlx::PgPool;
+use tracing::{instrument, warn};
+use truncate_core::{
+    board::Board,
+    messages::GameReplayMessage,
+    moves::{
+        packing::{pack_moves, unpack_moves},
+        Move,
+    },
+    npc::opening_book::OpeningBookBuilder,
+    rules::{GameRules, Tiebreaker},
+};
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+/// Replays are written once and rarely read, so trade some speed for a smaller archive
+const ZSTD_LEVEL: i32 = 19;
+/// How long casual games are kept if `REPLAY_RETENTION_CASUAL_DAYS` isn't set
+const DEFAULT_CASUAL_DAYS: u32 = 90;
+
+/// A finished online game, as handed over by its room for archiving
+#[derive(Debug, Clone)]
+pub struct GameReplay {
+    /// Chosen by the room, so that it can be linked to before the replay is archived
+    pub replay_id: Uuid,
+    pub room_code: String,
+    pub player_count: usize,
+    pub rated: bool,
+    pub winner: Option<usize>,
+    /// What decided the winner, when the game would otherwise have been drawn
+    pub tiebreaker: Option<Tiebreaker>,
+    pub board: Board,
+    pub moves: Vec<Move>,
+    pub bag_seed: u64,
+    pub rules: GameRules,
+    pub player_names: Vec<String>,
+}
+
+/// How many days replays are kept for, or `None` to keep them forever.
+/// Configured through `REPLAY_RETENTION_RATED_DAYS` and `REPLAY_RETENTION_CASUAL_DAYS`,
+/// where `forever` lifts the limit.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub rated_days: Option<u32>,
+    pub casual_days: Option<u32>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            rated_days: None,
+            casual_days: Some(DEFAULT_CASUAL_DAYS),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn from_env() -> Self {
+        fn read(var: &str, default: Option<u32>) -> Option<u32> {
+            let Some(value) = env::var(var).ok().filter(|v| !v.is_empty()) else {
+                return default;
+            };
+            if value.eq_ignore_ascii_case("forever") {
+                return None;
+            }
+            match value.parse() {
+                Ok(days) => Some(days),
+                Err(_) => {
+                    warn!(%var, %value, "Ignoring a replay retention that isn't a number of days");
+                    default
+                }
+            }
+        }
+
+        let defaults = Self::default();
+        Self {
+            rated_days: read("REPLAY_RETENTION_RATED_DAYS", defaults.rated_days),
+            casual_days: read("REPLAY_RETENTION_CASUAL_DAYS", defaults.casual_days),
+        }
+    }
+}
+
+/// Replays removed by a pruning run, and the size of the archive left behind
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PruneReport {
+    pub pruned_rated: u64,
+    pub pruned_casual: u64,
+    pub replays: i64,
+    /// Bytes held in the archive after compression
+    pub stored_bytes: i64,
+    /// Bytes the archive would hold without compression
+    pub raw_bytes: i64,
+}
+
+fn compress(text: &str) -> Result<Vec<u8>, TruncateServerError> {
+    zstd::encode_all(text.as_bytes(), ZSTD_LEVEL)
+        .map_err(|e| TruncateServerError::ReplayCompression(e.to_string()))
+}
+
+fn decompress(bytes: &[u8]) -> Option<String> {
+    let raw = zstd::decode_all(bytes).ok()?;
+    String::from_utf8(raw).ok()
+}
+
+#[instrument(skip_all, fields(room = %replay.room_code, moves = replay.moves.len(), rated = replay.rated))]
+pub async fn archive_replay(
+    server_state: &ServerState,
+    replay: GameReplay,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let (Ok(serialized_board), Ok(serialized_rules)) = (
+        serde_json::to_string(&replay.board),
+        serde_json::to_string(&replay.rules),
+    ) else {
+        return Err(TruncateServerError::BadRequest);
+    };
+    let packed_moves = pack_moves(&replay.moves, replay.player_count);
+    let raw_bytes = (serialized_board.len() + packed_moves.len()) as i32;
+
+    sqlx::query!(
+        "INSERT INTO game_replays
+            (replay_id, room_code, player_count, rated, winner, tiebreaker, move_count, board, moves, raw_bytes, bag_seed, rules, player_names)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+        replay.replay_id,
+        replay.room_code,
+        replay.player_count as i32,
+        replay.rated,
+        replay.winner.map(|w| w as i32),
+        replay.tiebreaker.map(|t| t.to_string()),
+        replay.moves.len() as i32,
+        compress(&serialized_board)?,
+        compress(&packed_moves)?,
+        raw_bytes,
+        replay.bag_seed as i64,
+        serialized_rules,
+        &replay.player_names
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Loads an archived game to be played back, if it was archived with everything that takes
+#[instrument(skip(server_state))]
+pub async fn load_replay(
+    server_state: &ServerState,
+    replay_id: Uuid,
+) -> Result<Option<GameReplayMessage>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let Some(replay) = sqlx::query!(
+        "SELECT player_count, board, moves, bag_seed, rules, player_names FROM game_replays
+        WHERE replay_id = $1",
+        replay_id
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let (Some(bag_seed), Some(rules), Some(player_names)) =
+        (replay.bag_seed, replay.rules, replay.player_names)
+    else {
+        warn!(%replay_id, "Replay was archived without what's needed to play it back");
+        return Ok(None);
+    };
+
+    let decoded = decompress(&replay.board)
+        .zip(decompress(&replay.moves))
+        .and_then(|(board, moves)| {
+            Some(GameReplayMessage {
+                player_names,
+                rules: serde_json::from_str(&rules).ok()?,
+                bag_seed: bag_seed as u64,
+                board: serde_json::from_str(&board).ok()?,
+                moves: unpack_moves(&moves, replay.player_count as usize).ok()?,
+            })
+        });
+    if decoded.is_none() {
+        warn!(%replay_id, "Replay couldn't be read back");
+    }
+
+    Ok(decoded)
+}
+
+/// Deletes every replay that has outlived the retention policy
+#[instrument(skip_all)]
+pub async fn prune_replays(
+    server_state: &ServerState,
+    policy: RetentionPolicy,
+) -> Result<PruneReport, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let mut report = PruneReport::default();
+    for (rated, days) in [(true, policy.rated_days), (false, policy.casual_days)] {
+        let Some(days) = days else {
+            continue;
+        };
+        let pruned = sqlx::query!(
+            "DELETE FROM game_replays
+            WHERE rated = $1 AND finished_at < CURRENT_TIMESTAMP - make_interval(days => $2)",
+            rated,
+            days as i32
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        if rated {
+            report.pruned_rated = pruned;
+        } else {
+            report.pruned_casual = pruned;
+        }
+    }
+
+    let totals = sqlx::query!(
+        r#"SELECT
+            COUNT(*) AS "replays!",
+            COALESCE(SUM(octet_length(board) + octet_length(moves)), 0)::BIGINT AS "stored_bytes!",
+            COALESCE(SUM(raw_bytes), 0)::BIGINT AS "raw_bytes!"
+        FROM game_replays;"#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    report.replays = totals.replays;
+    report.stored_bytes = totals.stored_bytes;
+    report.raw_bytes = totals.raw_bytes;
+
+    Ok(report)
+}
+
+/// Feeds every decided two player game in the archive through an opening book builder,
+/// also returning how many replays couldn't be read back
+#[instrument(skip_all)]
+pub async fn mine_openings(
+    pool: &PgPool,
+) -> Result<(OpeningBookBuilder, usize), TruncateServerError> {
+    let mut replays = sqlx::query!(
+        "SELECT player_count, winner, board, moves FROM game_replays
+        WHERE player_count = 2 AND winner IS NOT NULL"
+    )
+    .fetch(pool);
+
+    let mut builder = OpeningBookBuilder::new();
+    let mut unreadable = 0;
+    while let Some(replay) = replays.try_next().await? {
+        let player_count = replay.player_count as usize;
+        let decoded = decompress(&replay.board)
+            .zip(decompress(&replay.moves))
+            .and_then(|(board, moves)| {
+                Some((
+                    serde_json::from_str::<Board>(&board).ok()?,
+                    unpack_moves(&moves, player_count).ok()?,
+                ))
+            });
+        let Some((board, moves)) = decoded else {
+            unreadable += 1;
+            continue;
+        };
+
+        builder.record_game(&board, &moves, replay.winner.map(|w| w as usize));
+    }
+
+    Ok((builder, unreadable))
+}
@@ -0,0 +1,88 @@
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::instrument;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+/// A room as it stood when the server last published it
+#[derive(Debug, Clone)]
+pub struct RoomSummary {
+    pub room_code: String,
+    pub players: Vec<String>,
+    pub spectators: usize,
+    pub started: bool,
+    pub finished: bool,
+    pub turn_count: u32,
+    /// Seconds since anyone in the room did anything
+    pub idle_secs: u64,
+}
+
+/// Replaces the published rooms with the ones open right now
+#[instrument(skip_all, fields(rooms = rooms.len()))]
+pub async fn publish_rooms(
+    server_state: &ServerState,
+    rooms: Vec<RoomSummary>,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    // Swap the whole list at once so that a listing never sees it half written
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM live_rooms;")
+        .execute(&mut *tx)
+        .await?;
+
+    for room in rooms {
+        sqlx::query!(
+            "INSERT INTO live_rooms (room_code, players, spectators, started, finished, turn_count, idle_secs)
+            VALUES ($1, $2, $3, $4, $5, $6, $7);",
+            room.room_code,
+            &room.players,
+            room.spectators as i32,
+            room.started,
+            room.finished,
+            room.turn_count as i32,
+            room.idle_secs as i64
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// The rooms most recently published by the server, alongside when they were published
+#[instrument(skip_all)]
+pub async fn load_rooms(
+    pool: &PgPool,
+) -> Result<Vec<(RoomSummary, OffsetDateTime)>, TruncateServerError> {
+    let rooms = sqlx::query!(
+        "SELECT room_code, players, spectators, started, finished, turn_count, idle_secs, published_at
+        FROM live_rooms
+        ORDER BY room_code;"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rooms
+        .into_iter()
+        .map(|room| {
+            (
+                RoomSummary {
+                    room_code: room.room_code,
+                    players: room.players,
+                    spectators: room.spectators.try_into().unwrap_or_default(),
+                    started: room.started,
+                    finished: room.finished,
+                    turn_count: room.turn_count.try_into().unwrap_or_default(),
+                    idle_secs: room.idle_secs.try_into().unwrap_or_default(),
+                },
+                room.published_at,
+            )
+        })
+        .collect())
+}
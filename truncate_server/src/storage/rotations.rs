@@ -0,0 +1,216 @@
+use tracing::instrument;
+use truncate_core::{
+    messages::DailyStateMessage,
+    moves::{self, packing::pack_moves, Move},
+    rotation::PuzzleCategory,
+    rules::Language,
+};
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+struct AttemptRecord {
+    attempt_id: Uuid,
+    attempt_number: i32,
+    sequence_of_moves: String,
+    think_secs: Vec<i32>,
+}
+
+struct ResultRecord {
+    result_id: Uuid,
+}
+
+impl AttemptRecord {
+    fn into_message(self, category: PuzzleCategory, period: i32) -> Option<DailyStateMessage> {
+        let current_moves = moves::packing::unpack_moves(&self.sequence_of_moves, 2).ok()?;
+        Some(DailyStateMessage {
+            puzzle_day: period.try_into().unwrap_or_default(),
+            attempt: self.attempt_number.try_into().unwrap_or_default(),
+            current_moves,
+            think_secs: self
+                .think_secs
+                .iter()
+                .map(|secs| (*secs).try_into().unwrap_or_default())
+                .collect(),
+            category,
+            language: Language::English,
+        })
+    }
+}
+
+/// Returns any partial or completed attempt for a given player at one of a category's puzzles,
+/// alongside their best winning attempt. Daily puzzles are stored separately, in `daily`.
+#[instrument(skip_all, fields(player = %player.player(), category = category.key(), period))]
+pub async fn load_attempt(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    category: PuzzleCategory,
+    period: i32,
+) -> Result<Option<(DailyStateMessage, Option<DailyStateMessage>)>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let Some(result) = get_result_record(server_state, &player, category, period).await? else {
+        return Ok(None);
+    };
+
+    let Some(latest) = get_latest_attempt(server_state, result.result_id).await? else {
+        return Ok(None);
+    };
+
+    let best = sqlx::query_as!(
+        AttemptRecord,
+        "SELECT attempt_id, sequence_of_moves, think_secs, attempt_number FROM rotation_puzzle_attempts
+        WHERE result_id = $1 AND won = true ORDER BY move_count ASC LIMIT 1",
+        result.result_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .and_then(|best| best.into_message(category, period));
+
+    // If move parsing fails, move on as if there was no attempt.
+    Ok(latest
+        .into_message(category, period)
+        .map(|latest| (latest, best)))
+}
+
+/// Saves the moves of the player's latest attempt at a `(category, period)` puzzle
+#[instrument(skip_all, fields(player = %player.player(), category = puzzle.0.key(), period = puzzle.1, moves = moves.len()))]
+pub async fn persist_moves(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    puzzle: (PuzzleCategory, i32),
+    human_player: i32,
+    moves: Vec<Move>,
+    think_secs: Vec<u32>,
+    won: bool,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let (category, period) = puzzle;
+    if category == PuzzleCategory::Daily {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    let result_id = match get_result_record(server_state, &player, category, period).await? {
+        Some(result) => result.result_id,
+        None => {
+            sqlx::query_as!(
+                ResultRecord,
+                "INSERT INTO rotation_puzzle_results (player_id, category, period, human_player)
+                VALUES ($1, $2, $3, $4) RETURNING result_id",
+                player.player(),
+                category.key(),
+                period,
+                human_player
+            )
+            .fetch_one(pool)
+            .await?
+            .result_id
+        }
+    };
+
+    let packed_moves = pack_moves(&moves, 2);
+
+    // Moves that don't continue the latest attempt start a new one, rather than rewriting history
+    let attempt = match get_latest_attempt(server_state, result_id).await? {
+        Some(attempt) if packed_moves.starts_with(&attempt.sequence_of_moves) => attempt,
+        latest => {
+            let attempt_number = latest.map(|a| a.attempt_number + 1).unwrap_or_default();
+            let new_attempt = sqlx::query!(
+                "INSERT INTO rotation_puzzle_attempts (result_id, attempt_number) VALUES ($1, $2) RETURNING attempt_id",
+                result_id,
+                attempt_number
+            )
+            .fetch_one(pool)
+            .await?;
+
+            AttemptRecord {
+                attempt_id: new_attempt.attempt_id,
+                attempt_number,
+                sequence_of_moves: String::new(),
+                think_secs: vec![],
+            }
+        }
+    };
+
+    let human_moves = moves
+        .iter()
+        .filter(|m| {
+            let player = match m {
+                Move::Place { player, .. } => player,
+                Move::Swap { player, .. } => player,
+                Move::Exchange { player, .. } => player,
+                Move::Mulligan { player } => player,
+            };
+            *player as i32 == human_player
+        })
+        .count();
+
+    // Times are only kept for moves that were actually persisted
+    let think_secs: Vec<i32> = think_secs
+        .into_iter()
+        .take(moves.len())
+        .map(|secs| secs.try_into().unwrap_or(i32::MAX))
+        .collect();
+
+    sqlx::query!(
+        "UPDATE rotation_puzzle_attempts
+         SET sequence_of_moves = $1, move_count = $2, won = $3, think_secs = $5
+         WHERE attempt_id = $4",
+        packed_moves,
+        human_moves as i32,
+        won,
+        attempt.attempt_id,
+        &think_secs
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn get_result_record(
+    server_state: &ServerState,
+    player: &AuthedTruncateToken,
+    category: PuzzleCategory,
+    period: i32,
+) -> Result<Option<ResultRecord>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query_as!(
+        ResultRecord,
+        "SELECT result_id FROM rotation_puzzle_results WHERE player_id = $1 AND category = $2 AND period = $3",
+        player.player(),
+        category.key(),
+        period
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+async fn get_latest_attempt(
+    server_state: &ServerState,
+    result_id: Uuid,
+) -> Result<Option<AttemptRecord>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query_as!(
+        AttemptRecord,
+        "SELECT attempt_id, sequence_of_moves, think_secs, attempt_number FROM rotation_puzzle_attempts
+        WHERE result_id = $1 ORDER BY attempt_number DESC LIMIT 1",
+        result_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
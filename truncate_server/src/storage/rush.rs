@@ -0,0 +1,136 @@
+use tracing::instrument;
+use truncate_core::{
+    moves::packing::pack_moves,
+    rules::Language,
+    rush::{PuzzleRush, RushLeaderboard, RushScore},
+};
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// How many players are shown on each day's leaderboard, per language
+const LEADERBOARD_SIZE: i64 = 10;
+
+/// Records a finished puzzle rush as an attempt on the puzzle rush track of the daily tables
+#[instrument(skip_all, fields(player = %player.player(), day, %language, solved = rush.solved()))]
+pub async fn persist_run(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    day: i32,
+    rush: &PuzzleRush,
+    language: Language,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let player_id = player.player();
+
+    let result = sqlx::query!(
+        "INSERT INTO daily_puzzle_results (player_id, daily_puzzle, human_player, track, language)
+        VALUES ($1, $2, 0, 'puzzle_rush', $3)
+        ON CONFLICT (player_id, daily_puzzle, track, language) DO UPDATE SET track = EXCLUDED.track
+        RETURNING result_id",
+        player_id,
+        day,
+        language.code()
+    )
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO daily_puzzle_attempts (result_id, attempt_number, sequence_of_moves, move_count, won)
+        SELECT $1, COUNT(*)::INT, $2::TEXT, $3::INT, true
+        FROM daily_puzzle_attempts WHERE result_id = $1",
+        result.result_id,
+        format!("{}:{}", rush.seed, pack_moves(&rush.solutions, 2)),
+        rush.solved() as i32
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all, fields(day, %language))]
+pub async fn load_leaderboard(
+    server_state: &ServerState,
+    player: Option<AuthedTruncateToken>,
+    day: i32,
+    language: Language,
+) -> Result<RushLeaderboard, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    struct RushScoreRecord {
+        player_name: Option<String>,
+        best_solved: Option<i32>,
+    }
+
+    let results = sqlx::query_as!(
+        RushScoreRecord,
+        "SELECT
+            p.player_name,
+            MAX(dpa.move_count) AS best_solved
+        FROM
+            daily_puzzle_results dpr
+        JOIN
+            daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
+        JOIN
+            players p ON p.player_id = dpr.player_id
+        WHERE
+            dpr.daily_puzzle = $1 AND dpr.track = 'puzzle_rush' AND dpr.language = $3
+        GROUP BY
+            dpr.player_id, p.player_name
+        ORDER BY
+            best_solved DESC
+        LIMIT $2;",
+        day,
+        LEADERBOARD_SIZE,
+        language.code()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let personal_best = match player {
+        Some(player) => {
+            struct PersonalBestRecord {
+                best_solved: Option<i32>,
+            }
+
+            sqlx::query_as!(
+                PersonalBestRecord,
+                "SELECT
+                    MAX(dpa.move_count) AS best_solved
+                FROM
+                    daily_puzzle_results dpr
+                JOIN
+                    daily_puzzle_attempts dpa ON dpr.result_id = dpa.result_id
+                WHERE
+                    dpr.player_id = $1 AND dpr.daily_puzzle = $2 AND dpr.track = 'puzzle_rush' AND dpr.language = $3;",
+                player.player(),
+                day,
+                language.code()
+            )
+            .fetch_one(pool)
+            .await?
+            .best_solved
+        }
+        None => None,
+    };
+
+    Ok(RushLeaderboard {
+        day: day as u32,
+        top: results
+            .into_iter()
+            .filter_map(|record| {
+                Some(RushScore {
+                    name: record.player_name,
+                    solved: record.best_solved?.try_into().ok()?,
+                })
+            })
+            .collect(),
+        personal_best: personal_best.and_then(|best| best.try_into().ok()),
+    })
+}
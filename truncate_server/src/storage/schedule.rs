@@ -0,0 +1,282 @@
+use sqlx::PgPool;
+use tracing::instrument;
+use truncate_core::rules::RulesPreset;
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// How many upcoming games each player can have arranged at once
+const MAX_SCHEDULED_GAMES: i64 = 20;
+/// Games can't be arranged any further ahead than this
+const MAX_SCHEDULE_AHEAD_SECS: u64 = 60 * 60 * 24 * 90;
+/// How long a game stays listed after its room opens, so that players can find their way back to it
+const LISTED_AFTER_START_SECS: u64 = 60 * 60 * 24;
+const MAX_NAME_CHARS: usize = 64;
+
+/// A game arranged between two players, as stored
+#[derive(Debug, Clone)]
+pub struct Scheduled {
+    pub invite_code: String,
+    pub host_id: Uuid,
+    pub host_name: String,
+    pub guest_id: Option<Uuid>,
+    pub guest_name: Option<String>,
+    pub ruleset: RulesPreset,
+    pub effective_day: u32,
+    pub starts_at: u64,
+    pub room_code: Option<String>,
+    pub scheduled_at: u64,
+}
+
+impl Scheduled {
+    /// Which seat in the room the player takes, with the host moving first
+    pub fn seat(&self, player_id: Uuid) -> Option<usize> {
+        if player_id == self.host_id {
+            Some(0)
+        } else if Some(player_id) == self.guest_id {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+struct ScheduledRecord {
+    invite_code: String,
+    host_id: Uuid,
+    host_name: String,
+    guest_id: Option<Uuid>,
+    guest_name: Option<String>,
+    ruleset: String,
+    effective_day: i32,
+    starts_at: i64,
+    room_code: Option<String>,
+    scheduled_at: time::OffsetDateTime,
+}
+
+impl ScheduledRecord {
+    fn parse(self) -> Option<Scheduled> {
+        Some(Scheduled {
+            invite_code: self.invite_code,
+            host_id: self.host_id,
+            host_name: self.host_name,
+            guest_id: self.guest_id,
+            guest_name: self.guest_name,
+            ruleset: RulesPreset::ALL
+                .into_iter()
+                .find(|preset| preset.to_string() == self.ruleset)?,
+            effective_day: self.effective_day.try_into().ok()?,
+            starts_at: self.starts_at.try_into().ok()?,
+            room_code: self.room_code,
+            scheduled_at: self.scheduled_at.unix_timestamp().try_into().ok()?,
+        })
+    }
+}
+
+/// Arranges a game for the host, returning the invite code to share with their opponent
+#[instrument(skip_all, fields(player = %host.player(), starts_at, %ruleset))]
+pub async fn schedule_game(
+    server_state: &ServerState,
+    host: AuthedTruncateToken,
+    host_name: &str,
+    effective_day: u32,
+    starts_at: u64,
+    ruleset: RulesPreset,
+) -> Result<String, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+    let host_id = host.player();
+
+    let now = truncate_core::game::now();
+    let host_name = host_name.trim();
+    if starts_at <= now
+        || starts_at > now + MAX_SCHEDULE_AHEAD_SECS
+        || host_name.is_empty()
+        || host_name.chars().count() > MAX_NAME_CHARS
+    {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    let upcoming = sqlx::query!(
+        "SELECT COUNT(*) AS games FROM scheduled_games
+        WHERE (host_id = $1 OR guest_id = $1) AND room_code IS NULL",
+        host_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if upcoming.games.unwrap_or_default() >= MAX_SCHEDULED_GAMES {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    let invite_code = Uuid::new_v4().simple().to_string()[..10].to_ascii_uppercase();
+    sqlx::query!(
+        "INSERT INTO scheduled_games (invite_code, host_id, host_name, ruleset, effective_day, starts_at)
+        VALUES ($1, $2, $3, $4, $5, $6)",
+        invite_code,
+        host_id,
+        host_name,
+        ruleset.to_string(),
+        effective_day as i32,
+        starts_at as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(invite_code)
+}
+
+/// Takes the open seat in a scheduled game. Players can't accept their own invites,
+/// or invites someone else has already accepted.
+#[instrument(skip_all, fields(player = %guest.player(), invite_code))]
+pub async fn accept_invite(
+    server_state: &ServerState,
+    guest: AuthedTruncateToken,
+    guest_name: &str,
+    invite_code: &str,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let guest_name = guest_name.trim();
+    if guest_name.is_empty() || guest_name.chars().count() > MAX_NAME_CHARS {
+        return Err(TruncateServerError::BadRequest);
+    }
+
+    let accepted = sqlx::query!(
+        "UPDATE scheduled_games SET guest_id = $1, guest_name = $2
+        WHERE invite_code = $3 AND guest_id IS NULL AND host_id <> $1 AND room_code IS NULL",
+        guest.player(),
+        guest_name,
+        invite_code.to_ascii_uppercase()
+    )
+    .execute(pool)
+    .await?;
+
+    if accepted.rows_affected() == 0 {
+        return Err(TruncateServerError::BadRequest);
+    }
+    Ok(())
+}
+
+/// Calls off a game that hasn't started yet, for either of its players
+#[instrument(skip_all, fields(player = %player.player(), invite_code))]
+pub async fn cancel_game(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+    invite_code: &str,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query!(
+        "DELETE FROM scheduled_games
+        WHERE invite_code = $1 AND (host_id = $2 OR guest_id = $2) AND room_code IS NULL",
+        invite_code.to_ascii_uppercase(),
+        player.player()
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The player's games that are yet to start, or started recently, soonest first
+#[instrument(skip_all, fields(player = %player.player()))]
+pub async fn load_games(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+) -> Result<Vec<Scheduled>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let listed_since = truncate_core::game::now().saturating_sub(LISTED_AFTER_START_SECS);
+    let games = sqlx::query_as!(
+        ScheduledRecord,
+        "SELECT invite_code, host_id, host_name, guest_id, guest_name, ruleset,
+            effective_day, starts_at, room_code, scheduled_at
+        FROM scheduled_games
+        WHERE (host_id = $1 OR guest_id = $1) AND starts_at > $2
+        ORDER BY starts_at",
+        player.player(),
+        listed_since as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games
+        .into_iter()
+        .filter_map(ScheduledRecord::parse)
+        .collect())
+}
+
+/// Accepted games starting before the given time whose players haven't been reminded,
+/// marking them as reminded
+#[instrument(skip_all, fields(before))]
+pub async fn take_due_reminders(
+    pool: &PgPool,
+    before: u64,
+) -> Result<Vec<Scheduled>, TruncateServerError> {
+    let games = sqlx::query_as!(
+        ScheduledRecord,
+        "UPDATE scheduled_games SET reminded = TRUE
+        WHERE NOT reminded AND guest_id IS NOT NULL AND room_code IS NULL AND starts_at <= $1
+        RETURNING invite_code, host_id, host_name, guest_id, guest_name, ruleset,
+            effective_day, starts_at, room_code, scheduled_at",
+        before as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games
+        .into_iter()
+        .filter_map(ScheduledRecord::parse)
+        .collect())
+}
+
+/// Accepted games whose start time has come, which still need a room
+#[instrument(skip_all, fields(now))]
+pub async fn load_due_games(
+    pool: &PgPool,
+    now: u64,
+) -> Result<Vec<Scheduled>, TruncateServerError> {
+    let games = sqlx::query_as!(
+        ScheduledRecord,
+        "SELECT invite_code, host_id, host_name, guest_id, guest_name, ruleset,
+            effective_day, starts_at, room_code, scheduled_at
+        FROM scheduled_games
+        WHERE guest_id IS NOT NULL AND room_code IS NULL AND starts_at <= $1
+        ORDER BY starts_at",
+        now as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games
+        .into_iter()
+        .filter_map(ScheduledRecord::parse)
+        .collect())
+}
+
+/// Records the room that was opened for a scheduled game
+#[instrument(skip_all, fields(invite_code, room_code))]
+pub async fn mark_opened(
+    pool: &PgPool,
+    invite_code: &str,
+    room_code: &str,
+) -> Result<(), TruncateServerError> {
+    sqlx::query!(
+        "UPDATE scheduled_games SET room_code = $2 WHERE invite_code = $1",
+        invite_code,
+        room_code
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
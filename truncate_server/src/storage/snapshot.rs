@@ -0,0 +1,295 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{info, instrument};
+
+use crate::errors::TruncateServerError;
+
+/// Everything the server persists, in a form that can be loaded into a fresh deployment.
+/// Tagged with the migration the source database was on, since rows are copied column for column.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub schema_version: i64,
+    pub exported_at: OffsetDateTime,
+    pub counts: SnapshotCounts,
+    /// Every table, in the order the migrations created them
+    pub tables: Vec<TableRows>,
+}
+
+/// Rows in each table, by table name
+pub type SnapshotCounts = BTreeMap<String, i64>;
+
+/// A table's rows, each as a JSON object keyed by column name
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableRows {
+    pub table: String,
+    pub rows: Vec<serde_json::Value>,
+}
+
+impl Snapshot {
+    fn row_counts(&self) -> SnapshotCounts {
+        self.tables
+            .iter()
+            .map(|t| (t.table.clone(), t.rows.len() as i64))
+            .collect()
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<(), TruncateServerError> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| TruncateServerError::SnapshotFile(e.to_string()))?;
+        serde_json::to_writer(std::io::BufWriter::new(file), self)
+            .map_err(|e| TruncateServerError::SnapshotFile(e.to_string()))
+    }
+
+    /// Reads a snapshot from disk, checking that it wasn't cut short or edited since export
+    pub fn read_from(path: &Path) -> Result<Self, TruncateServerError> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| TruncateServerError::SnapshotFile(e.to_string()))?;
+        let snapshot: Snapshot = serde_json::from_reader(std::io::BufReader::new(file))
+            .map_err(|e| TruncateServerError::SnapshotFile(e.to_string()))?;
+
+        if snapshot.row_counts() != snapshot.counts {
+            return Err(TruncateServerError::SnapshotMismatch(format!(
+                "snapshot claims {:?} rows but contains {:?}",
+                snapshot.counts,
+                snapshot.row_counts()
+            )));
+        }
+
+        Ok(snapshot)
+    }
+}
+
+/// Every table the migrations leave behind, in the order they were created.
+/// Tables can only reference tables created before them, so this is also an order they can be filled in.
+pub fn snapshot_tables() -> Vec<String> {
+    let mut tables: Vec<String> = vec![];
+
+    for migration in sqlx::migrate!("./migrations").iter() {
+        if migration.migration_type.is_down_migration() {
+            continue;
+        }
+
+        let sql = migration.sql.to_lowercase();
+        for statement in sql.split(';') {
+            let words: Vec<_> = statement
+                .lines()
+                .filter(|line| !line.trim_start().starts_with("--"))
+                .flat_map(|line| line.split_whitespace())
+                .collect();
+
+            let table_after = |prefix: &[&str]| {
+                words.starts_with(prefix).then(|| {
+                    let rest = &words[prefix.len()..];
+                    let rest = rest.strip_prefix(&["if", "not", "exists"]).unwrap_or(rest);
+                    let rest = rest.strip_prefix(&["if", "exists"]).unwrap_or(rest);
+                    rest.first()
+                        .map(|name| name.trim_end_matches('(').to_string())
+                })
+            };
+
+            if let Some(Some(table)) = table_after(&["create", "table"]) {
+                tables.push(table);
+            } else if let Some(Some(table)) = table_after(&["drop", "table"]) {
+                tables.retain(|t| *t != table);
+            }
+        }
+    }
+
+    tables
+}
+
+/// The latest migration applied to this database.
+/// Read at runtime, as sqlx's own bookkeeping table isn't part of our prepared queries.
+pub async fn schema_version(pool: &PgPool) -> Result<i64, TruncateServerError> {
+    let version: Option<i64> =
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = true")
+            .fetch_one(pool)
+            .await?;
+
+    Ok(version.unwrap_or_default())
+}
+
+// Table names can't be bound as parameters, so the queries below are built at runtime.
+// They only ever name tables from `snapshot_tables`, never anything from the snapshot file.
+
+async fn table_counts(
+    executor: &mut sqlx::PgConnection,
+    tables: &[String],
+) -> Result<SnapshotCounts, TruncateServerError> {
+    let mut counts = SnapshotCounts::new();
+    for table in tables {
+        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table};"))
+            .fetch_one(&mut *executor)
+            .await?;
+        counts.insert(table.clone(), count);
+    }
+    Ok(counts)
+}
+
+#[instrument(skip_all)]
+pub async fn export_snapshot(pool: &PgPool) -> Result<Snapshot, TruncateServerError> {
+    // Read everything in one transaction so that rows can't reference anything missing
+    let mut tx = pool.begin().await?;
+
+    let mut tables = vec![];
+    for table in snapshot_tables() {
+        let rows: serde_json::Value = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(json_agg(t), '[]'::json) FROM {table} t;"
+        ))
+        .fetch_one(&mut *tx)
+        .await?;
+        let serde_json::Value::Array(rows) = rows else {
+            return Err(TruncateServerError::SnapshotMismatch(format!(
+                "{table} didn't export as a list of rows"
+            )));
+        };
+        tables.push(TableRows { table, rows });
+    }
+
+    tx.commit().await?;
+
+    let mut snapshot = Snapshot {
+        schema_version: schema_version(pool).await?,
+        exported_at: OffsetDateTime::now_utc(),
+        counts: SnapshotCounts::default(),
+        tables,
+    };
+    snapshot.counts = snapshot.row_counts();
+
+    info!(counts = ?snapshot.counts, "Exported snapshot");
+
+    Ok(snapshot)
+}
+
+/// Loads a snapshot into an empty database on the same schema version.
+/// Nothing is kept unless every row made it across.
+#[instrument(skip_all, fields(schema = snapshot.schema_version))]
+pub async fn import_snapshot(
+    pool: &PgPool,
+    snapshot: &Snapshot,
+) -> Result<(), TruncateServerError> {
+    let target_version = schema_version(pool).await?;
+    if target_version != snapshot.schema_version {
+        return Err(TruncateServerError::SnapshotMismatch(format!(
+            "snapshot is from schema {} but this database is on {}",
+            snapshot.schema_version, target_version
+        )));
+    }
+
+    let tables = snapshot_tables();
+    let exported: Vec<_> = snapshot.tables.iter().map(|t| &t.table).collect();
+    if exported != tables.iter().collect::<Vec<_>>() {
+        return Err(TruncateServerError::SnapshotMismatch(format!(
+            "snapshot has tables {exported:?} but this database has {tables:?}"
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    // Migrations seed the changelogs, which the snapshot carries its own copies of
+    let existing = table_counts(&mut *tx, &tables).await?;
+    if existing
+        .iter()
+        .any(|(table, count)| table != "changelogs" && *count > 0)
+    {
+        return Err(TruncateServerError::SnapshotMismatch(format!(
+            "refusing to import over existing data ({existing:?})"
+        )));
+    }
+    for table in tables.iter().rev() {
+        sqlx::query(&format!("DELETE FROM {table};"))
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    for TableRows { table, rows } in &snapshot.tables {
+        sqlx::query(&format!(
+            "INSERT INTO {table} SELECT * FROM jsonb_populate_recordset(NULL::{table}, $1);"
+        ))
+        .bind(serde_json::Value::Array(rows.clone()))
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Dropping the transaction on a mismatch rolls the whole import back
+    let imported = table_counts(&mut *tx, &tables).await?;
+    if imported != snapshot.counts {
+        return Err(TruncateServerError::SnapshotMismatch(format!(
+            "expected {:?} rows after import but found {:?}",
+            snapshot.counts, imported
+        )));
+    }
+
+    tx.commit().await?;
+
+    info!(counts = ?imported, "Imported snapshot");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_every_table() {
+        let tables = snapshot_tables();
+
+        assert_eq!(tables.first().map(String::as_str), Some("players"));
+        for table in [
+            "crash_reports",
+            "game_replays",
+            "live_rooms",
+            "games",
+            "oauth_identities",
+            "rotation_puzzle_results",
+            "prediction_stats",
+        ] {
+            assert!(tables.iter().any(|t| t == table), "{table} is missing");
+        }
+        let position = |table: &str| tables.iter().position(|t| t == table);
+        assert!(position("daily_puzzle_results") < position("daily_puzzle_attempts"));
+    }
+
+    #[sqlx::test]
+    async fn snapshots_round_trip(pool: PgPool) {
+        let player_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO players (player_name) VALUES ('snap') RETURNING player_id;",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO game_replays (room_code, player_count, rated, move_count, board, moves, raw_bytes)
+            VALUES ('snap', 2, true, 0, '\\x0102', '\\x03', 3);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO oauth_identities (provider, subject, player_id) VALUES ('github', '1', $1);")
+            .bind(player_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let exported = export_snapshot(&pool).await.unwrap();
+        assert_eq!(exported.counts["players"], 1);
+        assert_eq!(exported.counts["game_replays"], 1);
+
+        let tables = snapshot_tables().join(", ");
+        sqlx::query(&format!("TRUNCATE {tables};"))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        import_snapshot(&pool, &exported).await.unwrap();
+        let reexported = export_snapshot(&pool).await.unwrap();
+        assert_eq!(reexported.counts, exported.counts);
+        for (before, after) in exported.tables.iter().zip(&reexported.tables) {
+            assert_eq!(before.rows, after.rows, "{} changed", before.table);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{env, time::Duration};
+
+use serde::Serialize;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// How long an external service has to accept a webhook before we give up on it
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomEventKind {
+    Started,
+    Ended,
+}
+
+/// The summary of a room that is posted to its webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomEvent {
+    pub event: RoomEventKind,
+    pub room_code: String,
+    /// Player names, in turn order
+    pub players: Vec<String>,
+    /// Index of the winning player, if the game ended with one
+    pub winner: Option<usize>,
+    /// Why the game ended without a winner
+    pub adjudication: Option<String>,
+    /// What decided the winner, when the game would otherwise have been drawn
+    pub tiebreaker: Option<String>,
+    pub turns: u32,
+    /// The finished game's replay, once the room has ended
+    pub replay_id: Option<Uuid>,
+    /// Where the room can be watched, or its replay once it has ended
+    pub room_link: String,
+    pub sent_at: u64,
+}
+
+/// Posted to `TURN_WEBHOOK` when a correspondence game is waiting on a player to move
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnNotice {
+    pub room_code: String,
+    /// Index of the player to move, and their name
+    pub player: usize,
+    pub player_name: String,
+    /// When the player will lose the game if they haven't moved, if the rules set a limit
+    pub move_deadline: Option<u64>,
+    pub room_link: String,
+    pub sent_at: u64,
+}
+
+/// Also posted to `TURN_WEBHOOK` shortly before a scheduled game's room opens
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleReminder {
+    pub invite_code: String,
+    /// The host's name, then their opponent's
+    pub players: Vec<String>,
+    pub starts_at: u64,
+    pub ruleset: String,
+    pub link: String,
+    pub sent_at: u64,
+}
+
+/// Where to let players know it's their turn, which is `TURN_WEBHOOK` if set
+pub fn turn_webhook() -> Option<String> {
+    env::var("TURN_WEBHOOK").ok().filter(|url| !url.is_empty())
+}
+
+/// The web client, which is `PUBLIC_URL` if set
+fn public_url() -> String {
+    let base = env::var("PUBLIC_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "https://truncate.town".into());
+    base.trim_end_matches('/').to_string()
+}
+
+/// Links to a room on the web client
+pub fn room_link(room_code: &str) -> String {
+    format!("{}/join/?j={}", public_url(), room_code.to_uppercase())
+}
+
+/// Links to a finished game's replay on the web client
+pub fn replay_link(replay_id: &Uuid) -> String {
+    format!("{}/replay/?j=REPLAY:{replay_id}", public_url())
+}
+
+/// Posts the event in the background, as a slow webhook shouldn't hold up the game
+pub fn send(url: String, event: RoomEvent) {
+    let description = format!("{:?} room webhook", event.event);
+    post(url, event.room_code.clone(), description, event);
+}
+
+/// Posts the notice in the background, as with room events
+pub fn notify_turn(url: String, notice: TurnNotice) {
+    let description = format!("turn webhook for player {}", notice.player);
+    post(url, notice.room_code.clone(), description, notice);
+}
+
+/// Posts the reminder in the background, as with room events
+pub fn remind_schedule(url: String, reminder: ScheduleReminder) {
+    let description = "scheduled game reminder".to_string();
+    post(url, reminder.invite_code.clone(), description, reminder);
+}
+
+fn post<T: Serialize + Send + 'static>(
+    url: String,
+    room_code: String,
+    description: String,
+    body: T,
+) {
+    tokio::spawn(async move {
+        let response = reqwest::Client::new()
+            .post(&url)
+            .json(&body)
+            .timeout(WEBHOOK_TIMEOUT)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => {
+                info!(room = %room_code, %description, "Sent webhook");
+            }
+            Ok(response) => {
+                warn!(room = %room_code, %description, status = %response.status(), "Webhook was rejected");
+            }
+            Err(e) => {
+                warn!(room = %room_code, %description, error = %e, "Failed to send webhook");
+            }
+        }
+    });
+}
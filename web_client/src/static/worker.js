@@ -1,11 +1,20 @@
 let loadedWasm = false;
+let resolving_id = null;
+
+// Called from within the wasm while a search is running,
+// so that the page can show how far along it is.
+self.truncate_search_progress = (report) => {
+    self.postMessage({ action: 'progress', report, id: resolving_id });
+}
 
 const resolve_backchannel = (e) => {
     if (!loadedWasm) {
         return setTimeout(() => resolve_backchannel(e), 10);
     }
 
+    resolving_id = e.data.id;
     const result = wasm_bindgen.backchannel(e.data.msg);
+    resolving_id = null;
     self.postMessage({ action: 'result', result, id: e.data.id });
 }
 